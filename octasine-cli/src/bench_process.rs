@@ -17,12 +17,12 @@ pub fn run() -> anyhow::Result<()> {
     // Don't forget trailing space
     let hash = "36 6f 1b 0a 3e 93 a3 d5 ";
 
-    let mut all_sleef_hashes_match = true;
+    let mut all_hashes_match = true;
 
     let fallback_speed = {
         let (success, r) = benchmark::<octasine::simd::Fallback>("fallback", hash);
 
-        all_sleef_hashes_match &= success;
+        all_hashes_match &= success;
 
         r
     };
@@ -31,7 +31,7 @@ pub fn run() -> anyhow::Result<()> {
     {
         let (success, r) = benchmark::<octasine::simd::Sse2>("sse2", hash);
 
-        all_sleef_hashes_match &= success;
+        all_hashes_match &= success;
 
         println!("Speed compared to fallback:     {}x", fallback_speed / r);
     }
@@ -40,23 +40,17 @@ pub fn run() -> anyhow::Result<()> {
     if is_x86_feature_detected!("avx") {
         let (success, r) = benchmark::<octasine::simd::Avx>("avx", hash);
 
-        all_sleef_hashes_match &= success;
+        all_hashes_match &= success;
 
         println!("Speed compared to fallback:     {}x", fallback_speed / r);
     }
 
-    if all_sleef_hashes_match {
-        println!(
-            "\n{}",
-            "All sleef output hashes matched reference hash".green()
-        );
+    if all_hashes_match {
+        println!("\n{}", "All output hashes matched reference hash".green());
 
         Ok(())
     } else {
-        println!(
-            "\n{}",
-            "Sleef output hashes didn't match reference hash".red()
-        );
+        println!("\n{}", "Output hashes didn't match reference hash".red());
 
         Err(anyhow::anyhow!("Hashes didn't match"))
     }
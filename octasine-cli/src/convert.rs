@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use octasine::sync::{Patch, PatchBank};
+
+/// Patch or patch bank read from file
+enum Input {
+    Patch(Patch),
+    Bank(PatchBank),
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Fxp,
+    Fxb,
+    Json,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+
+        match extension.as_deref() {
+            Some("fxp") => Ok(Self::Fxp),
+            Some("fxb") => Ok(Self::Fxb),
+            Some("json") => Ok(Self::Json),
+            _ => Err(anyhow::anyhow!(
+                "unsupported file extension in {}, use fxp, fxb or json",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// Convert patch or patch bank between fxp/fxb/native and JSON formats
+///
+/// Input format is determined by contents, output format by file extension.
+/// Input files not ending in .json are treated as fxp/fxb or as native
+/// (plain) bank data, e.g. as stored by CLAP hosts.
+pub fn run(
+    input_path: PathBuf,
+    output_path: PathBuf,
+    patch_index: Option<usize>,
+) -> anyhow::Result<()> {
+    let output_format = Format::from_path(&output_path)?;
+
+    let input = read_input(&input_path)
+        .with_context(|| format!("read patch data from {}", input_path.display()))?;
+
+    let output_bytes = match (input, output_format) {
+        (Input::Patch(patch), Format::Fxp) => patch.export_fxp_bytes(),
+        (Input::Patch(patch), Format::Json) => patch.export_json().into_bytes(),
+        (Input::Patch(patch), Format::Fxb) => {
+            let bank = PatchBank::default();
+
+            bank.import_bytes_into_current_patch(&patch.export_fxp_bytes());
+
+            bank.export_fxb_bytes()
+        }
+        (Input::Bank(bank), Format::Fxb) => bank.export_fxb_bytes(),
+        (Input::Bank(bank), Format::Json) => match patch_index {
+            Some(index) => get_patch(&bank, index)?.export_json().into_bytes(),
+            None => bank.export_json().into_bytes(),
+        },
+        (Input::Bank(bank), Format::Fxp) => {
+            let index = patch_index.ok_or_else(|| {
+                anyhow::anyhow!("input is a patch bank, pass --patch-index to select a patch")
+            })?;
+
+            get_patch(&bank, index)?.export_fxp_bytes()
+        }
+    };
+
+    ::std::fs::write(&output_path, output_bytes)
+        .with_context(|| format!("write {}", output_path.display()))?;
+
+    println!("Wrote {}", output_path.display());
+
+    Ok(())
+}
+
+fn read_input(path: &Path) -> anyhow::Result<Input> {
+    let bytes = ::std::fs::read(path)?;
+
+    if let Ok(Format::Json) = Format::from_path(path) {
+        let json = String::from_utf8(bytes)?;
+        let value: serde_json::Value = serde_json::from_str(&json)?;
+
+        if value.get("patches").is_some() {
+            let bank = PatchBank::default();
+
+            bank.import_bank_from_json(&json)?;
+
+            Ok(Input::Bank(bank))
+        } else {
+            Ok(Input::Patch(Patch::new_from_json(&json)?))
+        }
+    } else if is_fxp(&bytes) {
+        Ok(Input::Patch(Patch::new_from_bytes(&bytes)?))
+    } else {
+        let bank = PatchBank::default();
+
+        bank.import_bank_from_bytes(&bytes)?;
+
+        Ok(Input::Bank(bank))
+    }
+}
+
+/// Check for fxp opaque chunk header
+fn is_fxp(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"CcnK") && bytes.get(8..12) == Some(b"FPCh")
+}
+
+fn get_patch(bank: &PatchBank, index: usize) -> anyhow::Result<&Patch> {
+    bank.patches.get(index).ok_or_else(|| {
+        anyhow::anyhow!(
+            "patch index {} out of range, bank has {} patches",
+            index,
+            bank.patches.len()
+        )
+    })
+}
@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use colored::*;
+use octasine::sync::{sysex::dx7, PatchBank};
+
+/// Convert all DX7 SysEx files (.syx) in input directory into OctaSine
+/// banks (.fxb) in output directory, printing voices that couldn't be fully
+/// translated
+pub fn run(input_directory: PathBuf, output_directory: PathBuf) -> anyhow::Result<()> {
+    let mut paths = ::std::fs::read_dir(&input_directory)
+        .with_context(|| format!("read directory {}", input_directory.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .map_or(false, |extension| extension.eq_ignore_ascii_case("syx"))
+        })
+        .collect::<Vec<_>>();
+
+    paths.sort();
+
+    if paths.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no .syx files found in {}",
+            input_directory.display()
+        ));
+    }
+
+    ::std::fs::create_dir_all(&output_directory)
+        .with_context(|| format!("create directory {}", output_directory.display()))?;
+
+    let mut num_failed_files = 0;
+    let mut num_voices = 0;
+    let mut num_voices_with_warnings = 0;
+
+    for path in paths.iter() {
+        println!("\n{}", path.display().to_string().bold());
+
+        match convert_file(path, &output_directory) {
+            Ok(summary) => {
+                num_voices += summary.num_voices;
+                num_voices_with_warnings += summary.num_voices_with_warnings;
+            }
+            Err(err) => {
+                num_failed_files += 1;
+
+                println!("{}", format!("Conversion failed: {:#}", err).red());
+            }
+        }
+    }
+
+    println!(
+        "\nConverted {} voices from {} files, {} with warnings",
+        num_voices,
+        paths.len() - num_failed_files,
+        num_voices_with_warnings
+    );
+
+    if num_failed_files == 0 {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "{} files could not be converted",
+            num_failed_files
+        ))
+    }
+}
+
+struct FileSummary {
+    num_voices: usize,
+    num_voices_with_warnings: usize,
+}
+
+fn convert_file(path: &Path, output_directory: &Path) -> anyhow::Result<FileSummary> {
+    let bytes = ::std::fs::read(path)?;
+    let voices = dx7::convert_voices(&bytes)?;
+
+    let bank = PatchBank::new_from_converted_voices(&voices);
+    let num_converted = voices.len().min(bank.num_patches());
+
+    let mut num_voices_with_warnings = 0;
+
+    for (index, voice) in voices.iter().take(num_converted).enumerate() {
+        if voice.warnings.is_empty() {
+            println!("{:>3} {}", index + 1, voice.name);
+        } else {
+            num_voices_with_warnings += 1;
+
+            println!("{:>3} {}", index + 1, voice.name.yellow());
+
+            for warning in voice.warnings.iter() {
+                println!("      - {}", warning);
+            }
+        }
+    }
+
+    if num_converted < voices.len() {
+        println!(
+            "{}",
+            format!(
+                "Skipped {} voices not fitting in bank",
+                voices.len() - num_converted
+            )
+            .yellow()
+        );
+    }
+
+    let file_stem = path.file_stem().unwrap_or_default();
+    let output_path = output_directory.join(file_stem).with_extension("fxb");
+
+    ::std::fs::write(&output_path, bank.export_fxb_bytes())
+        .with_context(|| format!("write {}", output_path.display()))?;
+
+    println!("Wrote {}", output_path.display());
+
+    Ok(FileSummary {
+        num_voices: num_converted,
+        num_voices_with_warnings,
+    })
+}
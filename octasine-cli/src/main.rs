@@ -1,7 +1,11 @@
 mod bench_process;
+mod convert;
+mod convert_dx7;
 #[cfg(feature = "plot")]
 mod plot;
 
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
@@ -18,6 +22,28 @@ enum Commands {
     RunGui,
     /// Benchmark OctaSine process functions and check output sample accuracy
     BenchProcess,
+    /// Convert patch or patch bank between fxp/fxb/native and JSON formats
+    ///
+    /// Input format is detected from file contents, output format from file
+    /// extension (fxp, fxb or json).
+    Convert {
+        /// Patch or patch bank file to read
+        input: PathBuf,
+        /// File to write
+        output: PathBuf,
+        /// Patch to extract when converting a bank to a single patch
+        #[arg(long)]
+        patch_index: Option<usize>,
+    },
+    /// Convert folder of DX7 SysEx banks (.syx) into OctaSine banks (.fxb)
+    ///
+    /// Prints a report of voice features that couldn't be translated.
+    ConvertDx7Banks {
+        /// Directory containing .syx files
+        input_directory: PathBuf,
+        /// Directory to write .fxb files to
+        output_directory: PathBuf,
+    },
     /// Plot envelope and LFO curves (useful during development)
     #[cfg(feature = "plot")]
     Plot,
@@ -51,6 +77,15 @@ fn main() -> anyhow::Result<()> {
             Ok(())
         }
         Commands::BenchProcess => bench_process::run(),
+        Commands::Convert {
+            input,
+            output,
+            patch_index,
+        } => convert::run(input, output, patch_index),
+        Commands::ConvertDx7Banks {
+            input_directory,
+            output_directory,
+        } => convert_dx7::run(input_directory, output_directory),
         #[cfg(feature = "plot")]
         Commands::Plot => plot::run(),
     }
@@ -0,0 +1,172 @@
+//! Minimal WebAssembly wrapper for running OctaSine in a browser AudioWorklet
+//!
+//! Exposes a plain C ABI, so the module can be instantiated inside the
+//! AudioWorkletGlobalScope without any JavaScript glue code. See `www/` for
+//! a small demo page.
+//!
+//! Build with:
+//!
+//! ```sh
+//! cargo build --release --target wasm32-unknown-unknown
+//! cp target/wasm32-unknown-unknown/release/octasine_wasm.wasm www/
+//! ```
+//!
+//! Then serve the `www` directory over HTTP.
+
+use octasine::audio::gen::process_f32_runtime_select;
+use octasine::audio::AudioState;
+use octasine::common::{NoteEvent, NoteEventInner, SampleRate};
+use octasine::parameters::PARAMETERS;
+use octasine::sync::SyncState;
+use octasine::utils::update_audio_parameters;
+
+/// Web Audio renders in blocks of 128 frames, but leave some room
+pub const MAX_BLOCK_SIZE: usize = 1024;
+
+pub struct OctaSineWasm {
+    audio: Box<AudioState>,
+    sync: SyncState<()>,
+    lefts: Vec<f32>,
+    rights: Vec<f32>,
+}
+
+impl OctaSineWasm {
+    pub fn new(sample_rate: f64) -> Self {
+        let sync = SyncState::new(None);
+        let mut audio: Box<AudioState> = Default::default();
+
+        audio.set_lfo_phases(sync.lfo_phases.clone());
+        audio.set_lfo_target_modulations(sync.lfo_target_modulations.clone());
+        audio.set_sample_rate(SampleRate(sample_rate));
+        sync.set_sample_rate(SampleRate(sample_rate));
+
+        Self {
+            audio,
+            sync,
+            lefts: vec![0.0; MAX_BLOCK_SIZE],
+            rights: vec![0.0; MAX_BLOCK_SIZE],
+        }
+    }
+
+    pub fn enqueue_midi(&mut self, data: [u8; 3]) {
+        self.audio.enqueue_note_event(NoteEvent {
+            delta_frames: 0,
+            event: NoteEventInner::Midi { data },
+        });
+    }
+
+    pub fn set_parameter(&self, index: usize, value: f32) {
+        self.sync.patches.set_parameter_from_host(index, value);
+    }
+
+    pub fn set_patch_index(&self, index: usize) {
+        self.sync.patches.set_patch_index(index);
+    }
+
+    /// Render audio into internal buffers
+    pub fn process(&mut self, num_samples: usize) {
+        let num_samples = num_samples.min(MAX_BLOCK_SIZE);
+
+        let lefts = &mut self.lefts[..num_samples];
+        let rights = &mut self.rights[..num_samples];
+
+        let sync = &self.sync;
+
+        process_f32_runtime_select(&mut self.audio, lefts, rights, 0, |audio_state| {
+            update_audio_parameters(audio_state, sync);
+        });
+
+        self.audio.apply_output_stage(lefts, rights);
+    }
+
+    pub fn lefts(&self) -> &[f32] {
+        &self.lefts
+    }
+
+    pub fn rights(&self) -> &[f32] {
+        &self.rights
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn octasine_new(sample_rate: f32) -> *mut OctaSineWasm {
+    Box::into_raw(Box::new(OctaSineWasm::new(sample_rate.into())))
+}
+
+/// # Safety
+///
+/// Pointer must have been returned by `octasine_new` and not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn octasine_free(synth: *mut OctaSineWasm) {
+    if !synth.is_null() {
+        drop(Box::from_raw(synth));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn octasine_max_block_size() -> usize {
+    MAX_BLOCK_SIZE
+}
+
+#[no_mangle]
+pub extern "C" fn octasine_num_parameters() -> usize {
+    PARAMETERS.len()
+}
+
+/// Pointer to left channel buffer, containing `octasine_max_block_size()`
+/// samples
+///
+/// # Safety
+///
+/// Pointer must have been returned by `octasine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn octasine_lefts(synth: *mut OctaSineWasm) -> *mut f32 {
+    (*synth).lefts.as_mut_ptr()
+}
+
+/// Pointer to right channel buffer, containing `octasine_max_block_size()`
+/// samples
+///
+/// # Safety
+///
+/// Pointer must have been returned by `octasine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn octasine_rights(synth: *mut OctaSineWasm) -> *mut f32 {
+    (*synth).rights.as_mut_ptr()
+}
+
+/// # Safety
+///
+/// Pointer must have been returned by `octasine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn octasine_process(synth: *mut OctaSineWasm, num_samples: usize) {
+    (*synth).process(num_samples);
+}
+
+/// # Safety
+///
+/// Pointer must have been returned by `octasine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn octasine_midi(synth: *mut OctaSineWasm, status: u8, data1: u8, data2: u8) {
+    (*synth).enqueue_midi([status, data1, data2]);
+}
+
+/// # Safety
+///
+/// Pointer must have been returned by `octasine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn octasine_set_parameter(
+    synth: *mut OctaSineWasm,
+    index: usize,
+    value: f32,
+) {
+    (*synth).set_parameter(index, value);
+}
+
+/// # Safety
+///
+/// Pointer must have been returned by `octasine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn octasine_set_patch_index(synth: *mut OctaSineWasm, index: usize) {
+    (*synth).set_patch_index(index);
+}
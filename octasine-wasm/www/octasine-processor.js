@@ -0,0 +1,81 @@
+// AudioWorklet processor running OctaSine compiled to WebAssembly.
+//
+// The compiled module is sent from the main thread in an "init" message.
+// MIDI and parameter messages are forwarded to the synth before rendering
+// the next block.
+
+class OctaSineProcessor extends AudioWorkletProcessor {
+    constructor() {
+        super();
+
+        this.exports = null;
+        this.synth = 0;
+        this.pendingMessages = [];
+
+        this.port.onmessage = (event) => this.handleMessage(event.data);
+    }
+
+    handleMessage(message) {
+        if (message.type === "init") {
+            const instance = new WebAssembly.Instance(message.module, {});
+
+            this.exports = instance.exports;
+            this.synth = this.exports.octasine_new(sampleRate);
+
+            for (const pending of this.pendingMessages) {
+                this.handleMessage(pending);
+            }
+            this.pendingMessages = [];
+
+            this.port.postMessage({ type: "ready" });
+
+            return;
+        }
+
+        if (this.exports === null) {
+            this.pendingMessages.push(message);
+
+            return;
+        }
+
+        switch (message.type) {
+            case "midi":
+                this.exports.octasine_midi(this.synth, message.data[0], message.data[1], message.data[2]);
+                break;
+            case "parameter":
+                this.exports.octasine_set_parameter(this.synth, message.index, message.value);
+                break;
+            case "patch":
+                this.exports.octasine_set_patch_index(this.synth, message.index);
+                break;
+        }
+    }
+
+    process(_inputs, outputs) {
+        const output = outputs[0];
+
+        if (this.exports === null || output.length === 0) {
+            return true;
+        }
+
+        const numSamples = Math.min(output[0].length, this.exports.octasine_max_block_size());
+
+        this.exports.octasine_process(this.synth, numSamples);
+
+        // Views are created for each block, since memory growth detaches
+        // previously created buffers
+        const memory = this.exports.memory.buffer;
+        const lefts = new Float32Array(memory, this.exports.octasine_lefts(this.synth), numSamples);
+        const rights = new Float32Array(memory, this.exports.octasine_rights(this.synth), numSamples);
+
+        output[0].set(lefts);
+
+        if (output.length > 1) {
+            output[1].set(rights);
+        }
+
+        return true;
+    }
+}
+
+registerProcessor("octasine-processor", OctaSineProcessor);
@@ -0,0 +1,10 @@
+//! Import arbitrary data as patch bank (fxb or plain bank data)
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use octasine::sync::PatchBank;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = PatchBank::default().import_bank_from_bytes(data);
+});
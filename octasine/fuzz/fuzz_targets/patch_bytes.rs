@@ -0,0 +1,10 @@
+//! Import arbitrary data as patch (fxp or plain patch data)
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use octasine::sync::Patch;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Patch::new_from_bytes(data);
+});
@@ -0,0 +1,25 @@
+//! Import arbitrary data preceded by a valid serialization format header,
+//! so that fuzzing reaches the deserializers quickly. First byte selects
+//! header.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use octasine::sync::{Patch, PatchBank};
+
+const PREFIXES: &[&[u8]] = &[
+    b"\n\nOCTASINE-GZ-DATA-V1-BEGIN\n\n",
+    b"\n\nOCTASINE-DATA-V2-PLAIN\n\n",
+    b"\n\nOCTASINE-DATA-V2-GZ\n\n",
+];
+
+fuzz_target!(|data: &[u8]| {
+    if let Some((selector, data)) = data.split_first() {
+        let mut bytes = PREFIXES[usize::from(*selector) % PREFIXES.len()].to_vec();
+
+        bytes.extend_from_slice(data);
+
+        let _ = Patch::new_from_bytes(&bytes);
+        let _ = PatchBank::default().import_bank_from_bytes(&bytes);
+    }
+});
@@ -0,0 +1,70 @@
+use std::f64::consts::TAU;
+
+use crate::common::SampleRate;
+
+/// DC blocker cutoff frequency in Hz
+const DC_BLOCKER_CUTOFF: f64 = 10.0;
+
+/// One-pole DC blocking filter, applied to each channel
+pub struct DcBlocker {
+    coefficient: f64,
+    previous_inputs: [f64; 2],
+    previous_outputs: [f64; 2],
+}
+
+impl Default for DcBlocker {
+    fn default() -> Self {
+        Self::new(SampleRate::default())
+    }
+}
+
+impl DcBlocker {
+    pub fn new(sample_rate: SampleRate) -> Self {
+        Self {
+            coefficient: (-TAU * DC_BLOCKER_CUTOFF / sample_rate.0).exp(),
+            previous_inputs: [0.0; 2],
+            previous_outputs: [0.0; 2],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.previous_inputs = [0.0; 2];
+        self.previous_outputs = [0.0; 2];
+    }
+
+    /// Process samples in place
+    pub fn process(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        for (l, r) in lefts.iter_mut().zip(rights.iter_mut()) {
+            *l = self.process_sample(0, f64::from(*l)) as f32;
+            *r = self.process_sample(1, f64::from(*r)) as f32;
+        }
+    }
+
+    fn process_sample(&mut self, channel: usize, input: f64) -> f64 {
+        let output = input - self.previous_inputs[channel]
+            + self.coefficient * self.previous_outputs[channel];
+
+        self.previous_inputs[channel] = input;
+        self.previous_outputs[channel] = output;
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dc_blocker_removes_offset() {
+        let mut dc_blocker = DcBlocker::default();
+
+        let mut lefts = [0.5f32; 44100];
+        let mut rights = [-0.5f32; 44100];
+
+        dc_blocker.process(&mut lefts, &mut rights);
+
+        assert!(lefts[lefts.len() - 1].abs() < 0.0001);
+        assert!(rights[rights.len() - 1].abs() < 0.0001);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::common::{SampleRate, NUM_OPERATORS};
+use crate::parameters::operator_feedback::feedback_gain_compensation;
+
+use super::interpolation::{InterpolationDuration, Interpolator};
+use super::parameters::{common::AudioParameter, AudioParameters};
+use super::voices::KeyVelocity;
+
+/// Lowest compensating gain (-18 dB)
+const MIN_GAIN: f32 = 0.125;
+/// Highest compensating gain (+6 dB), so that quiet patches aren't boosted
+/// into clipping
+const MAX_GAIN: f32 = 2.0;
+
+/// Output gain normalizing estimated patch loudness to that of the default
+/// patch. Loudness is estimated as the sum of operator mix out levels at
+/// default key velocity, so modulation and envelopes are not taken into
+/// account.
+pub struct GainCompensation {
+    reference_loudness: f32,
+    target_gain: f32,
+    gain: Interpolator,
+}
+
+impl Default for GainCompensation {
+    fn default() -> Self {
+        Self {
+            reference_loudness: estimate_loudness(&AudioParameters::default()),
+            target_gain: 1.0,
+            gain: Interpolator::new(1.0, InterpolationDuration::approx_30ms()),
+        }
+    }
+}
+
+impl GainCompensation {
+    /// Process samples in place. Gain is faded back to unity when disabled
+    /// or while auditioning an operator.
+    #[allow(clippy::float_cmp)]
+    pub fn process(
+        &mut self,
+        parameters: &AudioParameters,
+        sample_rate: SampleRate,
+        lefts: &mut [f32],
+        rights: &mut [f32],
+    ) {
+        let target_gain =
+            if parameters.gain_compensation.get_value() && parameters.audition_operator.is_none() {
+                self.calculate_gain(parameters)
+            } else {
+                1.0
+            };
+
+        // Only restart interpolation on changes, since restarting it with
+        // an unchanged target would slow it down
+        if target_gain != self.target_gain {
+            self.target_gain = target_gain;
+            self.gain.set_value(target_gain);
+        }
+
+        if self.gain.get_value() == 1.0 && target_gain == 1.0 {
+            return;
+        }
+
+        for (l, r) in lefts.iter_mut().zip(rights.iter_mut()) {
+            self.gain.advance_one_sample(sample_rate, &mut |_| ());
+
+            let gain = self.gain.get_value();
+
+            *l *= gain;
+            *r *= gain;
+        }
+    }
+
+    fn calculate_gain(&self, parameters: &AudioParameters) -> f32 {
+        let loudness = estimate_loudness(parameters);
+
+        if loudness > 0.0 {
+            (self.reference_loudness / loudness).clamp(MIN_GAIN, MAX_GAIN)
+        } else {
+            MAX_GAIN
+        }
+    }
+}
+
+/// Sum of operator mix out levels, scaled by master and mix out velocity
+/// sensitivity at default key velocity and by feedback gain compensation
+fn estimate_loudness(parameters: &AudioParameters) -> f32 {
+    let key_velocity = KeyVelocity::default().0;
+    let velocity_factor = |sensitivity: f32| sensitivity * key_velocity + (1.0 - sensitivity);
+
+    let mix_out_sum = (0..NUM_OPERATORS)
+        .map(|index| {
+            let operator = &parameters.operators[index];
+
+            operator.active.get_value()
+                * operator.volume.get_value()
+                * operator.mix_out.get_value()
+                * feedback_gain_compensation(operator.feedback.get_value())
+                * velocity_factor(operator.velocity_sensitivity_mix_out.get_value())
+        })
+        .sum::<f32>();
+
+    mix_out_sum * velocity_factor(parameters.volume_velocity_sensitivity.get_value())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parameters::{MasterParameter, OperatorParameter, Parameter};
+
+    use super::*;
+
+    #[test]
+    fn test_gain_compensation() {
+        let mut parameters = AudioParameters::default();
+        let mut gain_compensation = GainCompensation::default();
+
+        assert_eq!(gain_compensation.calculate_gain(&parameters), 1.0);
+
+        parameters
+            .set_parameter_from_patch(Parameter::Master(MasterParameter::GainCompensation), 1.0);
+
+        // Add full-level second carrier
+        parameters.set_parameter_from_patch(Parameter::Operator(1, OperatorParameter::MixOut), 0.5);
+
+        for _ in 0..44100 {
+            parameters.advance_one_sample(SampleRate::default());
+        }
+
+        let gain = gain_compensation.calculate_gain(&parameters);
+
+        assert!((gain - 0.5).abs() < 0.01);
+
+        let mut lefts = [1.0f32; 44100];
+        let mut rights = [1.0f32; 44100];
+
+        gain_compensation.process(&parameters, SampleRate::default(), &mut lefts, &mut rights);
+
+        assert!((lefts[lefts.len() - 1] - gain).abs() < 0.0001);
+        assert!((rights[rights.len() - 1] - gain).abs() < 0.0001);
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
 use arrayvec::ArrayVec;
 
 use crate::audio::parameters::{common::AudioParameter, LfoAudioParameters};
@@ -7,7 +9,8 @@ use crate::parameters::{LfoParameter, PARAMETERS};
 
 pub struct LfoTargetValues {
     values: [Option<f32>; PARAMETERS.len()],
-    set_indices: ArrayVec<u8, NUM_LFOS>,
+    /// One slot per LFO target plus one for velocity modulation
+    set_indices: ArrayVec<u8, { NUM_LFOS * NUM_LFO_TARGETS + 1 }>,
 }
 
 impl Default for LfoTargetValues {
@@ -35,6 +38,21 @@ impl LfoTargetValues {
         }
     }
 
+    /// Add LFO output to all of its targets, scaled by target depth
+    fn add_lfo_output(&mut self, targets: &[(u8, f32)], addition: f32) {
+        for (target_index, depth) in targets.iter().copied() {
+            self.set_or_add(target_index, addition * depth);
+        }
+    }
+
+    fn publish(&self, modulations: &LfoTargetModulations) {
+        for i in self.set_indices.iter().copied() {
+            if let Some(value) = self.values[i as usize] {
+                modulations.set(i, value);
+            }
+        }
+    }
+
     fn clear_set(&mut self) {
         for i in self.set_indices.iter().copied() {
             self.values[i as usize] = None;
@@ -44,10 +62,132 @@ impl LfoTargetValues {
     }
 }
 
+/// LFO phases published by the audio thread for display in the GUI
+///
+/// Phases are stored as f32 bits. Reading a phase resets it, so the GUI can
+/// tell if an LFO has stopped running.
+#[derive(Debug)]
+pub struct LfoPhases([AtomicU32; NUM_LFOS]);
+
+impl Default for LfoPhases {
+    fn default() -> Self {
+        Self(::std::array::from_fn(|_| AtomicU32::new(Self::NO_PHASE)))
+    }
+}
+
+impl LfoPhases {
+    const NO_PHASE: u32 = u32::MAX;
+
+    #[inline]
+    fn set(&self, lfo_index: usize, phase: Phase) {
+        self.0[lfo_index].store((phase.0 as f32).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Get phase published since last call, if any
+    pub fn take(&self, lfo_index: usize) -> Option<f32> {
+        let bits = self.0[lfo_index].swap(Self::NO_PHASE, Ordering::Relaxed);
+
+        if bits == Self::NO_PHASE {
+            None
+        } else {
+            Some(f32::from_bits(bits))
+        }
+    }
+}
+
+/// LFO target modulation published by the audio thread for display in the
+/// GUI
+///
+/// Patch value additions are stored as f32 bits, indexed by parameter. As
+/// with LFO phases, reading a value resets it.
+#[derive(Debug)]
+pub struct LfoTargetModulations([AtomicU32; PARAMETERS.len()]);
+
+impl Default for LfoTargetModulations {
+    fn default() -> Self {
+        Self(::std::array::from_fn(|_| {
+            AtomicU32::new(Self::NO_MODULATION)
+        }))
+    }
+}
+
+impl LfoTargetModulations {
+    const NO_MODULATION: u32 = u32::MAX;
+
+    #[inline]
+    fn set(&self, parameter_index: u8, addition: f32) {
+        self.0[parameter_index as usize].store(addition.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Get modulation published since last call, if any
+    pub fn take(&self, parameter_index: usize) -> Option<f32> {
+        let bits = self.0[parameter_index].swap(Self::NO_MODULATION, Ordering::Relaxed);
+
+        if bits == Self::NO_MODULATION {
+            None
+        } else {
+            Some(f32::from_bits(bits))
+        }
+    }
+}
+
+/// Advance LFOs shared by all voices
+///
+/// Since they are shared, their shape and frequency can't be modulated per
+/// voice. Amount is still applied per voice.
+pub fn advance_global_lfos(
+    global_lfos: &mut [VoiceLfo; NUM_LFOS],
+    lfo_parameters: &[LfoAudioParameters; NUM_LFOS],
+    lfo_phases: &LfoPhases,
+    sample_rate: SampleRate,
+    time_per_sample: TimePerSample,
+    bpm_lfo_multiplier: BpmLfoMultiplier,
+) {
+    for (lfo_index, (global_lfo, lfo_parameter)) in global_lfos
+        .iter_mut()
+        .zip(lfo_parameters.iter())
+        .enumerate()
+    {
+        if !lfo_parameter.global.get_value() || global_lfo.is_stopped() {
+            continue;
+        }
+
+        let bpm_lfo_multiplier = if lfo_parameter.bpm_sync.get_value() {
+            bpm_lfo_multiplier
+        } else {
+            BpmLfoMultiplier(1.0)
+        };
+
+        global_lfo.advance_one_sample(
+            sample_rate,
+            time_per_sample,
+            bpm_lfo_multiplier,
+            lfo_parameter.shape.get_value(),
+            lfo_parameter.mode.get_value(),
+            lfo_parameter.frequency_ratio.get_value() * lfo_parameter.frequency_free.get_value(),
+            lfo_parameter.smoothing.get_value(),
+        );
+
+        lfo_phases.set(lfo_index, global_lfo.get_phase());
+    }
+}
+
+/// Calculate per-voice modulation of LFO targets
+///
+/// Velocity modulation is applied first, so that it can affect LFO parameters.
+/// `velocity_modulation` is (target index, patch value offset). `mod_wheel`
+/// is mod wheel position from 0.0 to 1.0, scaling LFO amounts according to
+/// their mod wheel depth.
+#[allow(clippy::too_many_arguments)]
 pub fn update_lfo_target_values(
     lfo_values: &mut LfoTargetValues,
+    velocity_modulation: Option<(u8, f32)>,
+    mod_wheel: f32,
     lfo_parameters: &mut [LfoAudioParameters; NUM_LFOS],
     voice_lfos: &mut [VoiceLfo; NUM_LFOS],
+    global_lfos: &mut [VoiceLfo; NUM_LFOS],
+    lfo_phases: &LfoPhases,
+    lfo_target_modulations: &LfoTargetModulations,
     sample_rate: SampleRate,
     time_per_sample: TimePerSample,
     bpm_lfo_multiplier: BpmLfoMultiplier,
@@ -59,6 +199,10 @@ pub fn update_lfo_target_values(
 
     lfo_values.clear_set();
 
+    if let Some((target_index, addition)) = velocity_modulation {
+        lfo_values.set_or_add(target_index, addition);
+    }
+
     for (lfo_index, (voice_lfo, lfo_parameter)) in voice_lfos
         .iter_mut()
         .zip(lfo_parameters.iter_mut())
@@ -67,17 +211,34 @@ pub fn update_lfo_target_values(
     {
         assert!(lfo_index < NUM_LFOS);
 
-        let target_index = lfo_parameter.target.get_value().index();
+        let targets = lfo_targets(lfo_parameter);
 
-        let target_index = match (target_index, voice_lfo.is_stopped()) {
-            (None, _) | (_, true) => continue,
-            (Some(index), false) => index,
+        let global = lfo_parameter.global.get_value();
+
+        let lfo_stopped = if global {
+            global_lfos[lfo_index].is_stopped()
+        } else {
+            voice_lfo.is_stopped()
         };
 
+        if targets.is_empty() || lfo_stopped {
+            continue;
+        }
+
         let amount = lfo_parameter.active.get_value()
             * lfo_parameter
                 .amount
-                .get_value_with_lfo_addition(lfo_values.get(AMOUNT_PARAMETER_INDICES[lfo_index]));
+                .get_value_with_lfo_addition(lfo_values.get(AMOUNT_PARAMETER_INDICES[lfo_index]))
+            * mod_wheel_factor(lfo_parameter.mod_wheel_depth.get_value(), mod_wheel);
+
+        if global {
+            // Global LFO has already been advanced for this sample
+            let addition = global_lfos[lfo_index].get_value(amount);
+
+            lfo_values.add_lfo_output(&targets, addition);
+
+            continue;
+        }
 
         let mode = lfo_parameter.mode.get_value();
         let bpm_sync = lfo_parameter.bpm_sync.get_value();
@@ -105,10 +266,287 @@ pub fn update_lfo_target_values(
             shape,
             mode,
             frequency_ratio * frequency_free,
+            lfo_parameter.smoothing.get_value(),
         );
 
+        lfo_phases.set(lfo_index, voice_lfo.get_phase());
+
         let addition = voice_lfo.get_value(amount);
 
-        lfo_values.set_or_add(target_index, addition);
+        lfo_values.add_lfo_output(&targets, addition);
+    }
+
+    lfo_values.publish(lfo_target_modulations);
+}
+
+/// Indices of parameters targeted by LFO and their depths. The first target
+/// always has full depth.
+fn lfo_targets(lfo_parameter: &LfoAudioParameters) -> ArrayVec<(u8, f32), NUM_LFO_TARGETS> {
+    let mut targets = ArrayVec::new();
+
+    if let Some(index) = lfo_parameter.target.get_value().index() {
+        targets.push((index, 1.0));
+    }
+
+    for (target, depth) in lfo_parameter
+        .additional_targets
+        .iter()
+        .zip(lfo_parameter.additional_target_depths.iter())
+    {
+        if let Some(index) = target.get_value().index() {
+            targets.push((index, depth.get_value()));
+        }
+    }
+
+    targets
+}
+
+/// Factor scaling LFO amount by mod wheel position according to depth
+fn mod_wheel_factor(depth: f32, mod_wheel: f32) -> f32 {
+    1.0 - depth + depth * mod_wheel
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audio::parameters::AudioParameters;
+    use crate::parameters::{
+        Lfo1TargetParameterValue, LfoAmountValue, LfoGlobalValue, LfoTargetDepthValue,
+        LfoTargetParameter, MasterParameter, OperatorParameter, Parameter, ParameterValue,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_lfo_targets() {
+        let mut parameters = AudioParameters::default();
+
+        assert!(lfo_targets(&parameters.lfos[0]).is_empty());
+
+        let target = Parameter::Operator(1, OperatorParameter::Volume);
+        let additional_target = Parameter::Master(MasterParameter::Pan);
+
+        for (lfo_parameter, target_parameter) in [
+            (LfoParameter::Target, target),
+            (LfoParameter::Target3, additional_target),
+        ] {
+            parameters.set_parameter_from_patch(
+                Parameter::Lfo(0, lfo_parameter),
+                Lfo1TargetParameterValue::new_from_audio(LfoTargetParameter::new(target_parameter))
+                    .to_patch(),
+            );
+        }
+
+        parameters.set_parameter_from_patch(
+            Parameter::Lfo(0, LfoParameter::Target3Depth),
+            LfoTargetDepthValue::new_from_audio(-0.5).to_patch(),
+        );
+
+        for _ in 0..1000 {
+            parameters.advance_one_sample(SampleRate::default());
+        }
+
+        let targets = lfo_targets(&parameters.lfos[0]);
+
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0], (target.to_index(), 1.0));
+        assert_eq!(targets[1].0, additional_target.to_index());
+        assert!((targets[1].1 + 0.5).abs() < 1e-6);
+
+        let mut lfo_values = LfoTargetValues::default();
+
+        lfo_values.add_lfo_output(&targets, 0.2);
+
+        assert_eq!(lfo_values.get(target.to_index()), Some(0.2));
+        assert!((lfo_values.get(additional_target.to_index()).unwrap() + 0.1).abs() < 1e-6);
+    }
+    #[test]
+    fn test_velocity_modulation() {
+        let mut parameters = AudioParameters::default();
+        let mut stopped_lfos: [VoiceLfo; NUM_LFOS] = Default::default();
+        let mut running_lfos: [VoiceLfo; NUM_LFOS] = Default::default();
+        let mut global_lfos: [VoiceLfo; NUM_LFOS] = Default::default();
+        let lfo_phases = LfoPhases::default();
+        let lfo_target_modulations = LfoTargetModulations::default();
+
+        let pan = Parameter::Master(MasterParameter::Pan);
+        let lfo_amount = Parameter::Lfo(0, LfoParameter::Amount);
+
+        parameters.set_parameter_from_patch(
+            Parameter::Lfo(0, LfoParameter::Target),
+            Lfo1TargetParameterValue::new_from_audio(LfoTargetParameter::new(pan)).to_patch(),
+        );
+        parameters
+            .set_parameter_from_patch(lfo_amount, LfoAmountValue::new_from_audio(1.0).to_patch());
+
+        for _ in 0..1000 {
+            parameters.advance_one_sample(SampleRate::default());
+        }
+
+        for (voice_lfo, lfo_parameters) in running_lfos.iter_mut().zip(parameters.lfos.iter()) {
+            voice_lfo.restart(lfo_parameters);
+        }
+
+        let mut run = |velocity_modulation, voice_lfos: &mut [VoiceLfo; NUM_LFOS]| {
+            let mut lfo_values = LfoTargetValues::default();
+
+            for _ in 0..100 {
+                update_lfo_target_values(
+                    &mut lfo_values,
+                    velocity_modulation,
+                    0.0,
+                    &mut parameters.lfos,
+                    voice_lfos,
+                    &mut global_lfos,
+                    &lfo_phases,
+                    &lfo_target_modulations,
+                    SampleRate::default(),
+                    SampleRate::default().into(),
+                    BpmLfoMultiplier(1.0),
+                );
+            }
+
+            lfo_values
+        };
+
+        // Stopped LFOs only leave the velocity modulation
+        let lfo_values = run(Some((pan.to_index(), 0.3)), &mut stopped_lfos);
+
+        assert_eq!(lfo_values.get(pan.to_index()), Some(0.3));
+        assert_eq!(lfo_values.get(lfo_amount.to_index()), None);
+
+        let lfo_values = run(None, &mut stopped_lfos);
+
+        assert_eq!(lfo_values.get(pan.to_index()), None);
+
+        // Running LFO output is added to velocity modulation of same target
+        let lfo_output = run(None, &mut running_lfos.clone())
+            .get(pan.to_index())
+            .unwrap();
+
+        assert_ne!(lfo_output, 0.0);
+
+        let lfo_values = run(Some((pan.to_index(), 0.3)), &mut running_lfos.clone());
+
+        assert!((lfo_values.get(pan.to_index()).unwrap() - (lfo_output + 0.3)).abs() < 1e-6);
+
+        // Velocity modulation is applied before LFO parameters are read, so
+        // it can scale LFO amount
+        let lfo_values = run(
+            Some((lfo_amount.to_index(), -1.0)),
+            &mut running_lfos.clone(),
+        );
+
+        assert!((lfo_values.get(pan.to_index()).unwrap() / lfo_output - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_global_lfo_shared_by_voices() {
+        let mut parameters = AudioParameters::default();
+        let mut voice_lfos: [[VoiceLfo; NUM_LFOS]; 2] = Default::default();
+        let mut stopped_global_lfos: [VoiceLfo; NUM_LFOS] = Default::default();
+        let mut running_global_lfos: [VoiceLfo; NUM_LFOS] = Default::default();
+        let lfo_phases = LfoPhases::default();
+        let lfo_target_modulations = LfoTargetModulations::default();
+        let sample_rate = SampleRate::default();
+
+        let pan = Parameter::Master(MasterParameter::Pan);
+        let lfo_amount = Parameter::Lfo(0, LfoParameter::Amount);
+
+        parameters.set_parameter_from_patch(
+            Parameter::Lfo(0, LfoParameter::Target),
+            Lfo1TargetParameterValue::new_from_audio(LfoTargetParameter::new(pan)).to_patch(),
+        );
+        parameters
+            .set_parameter_from_patch(lfo_amount, LfoAmountValue::new_from_audio(1.0).to_patch());
+        parameters.set_parameter_from_patch(
+            Parameter::Lfo(0, LfoParameter::Global),
+            LfoGlobalValue::new_from_audio(true).to_patch(),
+        );
+
+        for _ in 0..1000 {
+            parameters.advance_one_sample(sample_rate);
+        }
+
+        for lfos in voice_lfos.iter_mut() {
+            for (voice_lfo, lfo_parameters) in lfos.iter_mut().zip(parameters.lfos.iter()) {
+                voice_lfo.restart(lfo_parameters);
+            }
+        }
+
+        voice_lfos[1][0].set_phase(Phase(0.5));
+        running_global_lfos[0].restart(&parameters.lfos[0]);
+
+        let mut run = |voice_lfos: &mut [[VoiceLfo; NUM_LFOS]; 2],
+                       global_lfos: &mut [VoiceLfo; NUM_LFOS]| {
+            let mut values = [[None; 2]; 100];
+
+            for voice_values in values.iter_mut() {
+                advance_global_lfos(
+                    global_lfos,
+                    &parameters.lfos,
+                    &lfo_phases,
+                    sample_rate,
+                    sample_rate.into(),
+                    BpmLfoMultiplier(1.0),
+                );
+
+                for ((value, lfos), velocity_modulation) in voice_values
+                    .iter_mut()
+                    .zip(voice_lfos.iter_mut())
+                    .zip([None, Some((lfo_amount.to_index(), -1.0))])
+                {
+                    let mut lfo_values = LfoTargetValues::default();
+
+                    update_lfo_target_values(
+                        &mut lfo_values,
+                        velocity_modulation,
+                        0.0,
+                        &mut parameters.lfos,
+                        lfos,
+                        global_lfos,
+                        &lfo_phases,
+                        &lfo_target_modulations,
+                        sample_rate,
+                        sample_rate.into(),
+                        BpmLfoMultiplier(1.0),
+                    );
+
+                    *value = lfo_values.get(pan.to_index());
+                }
+            }
+
+            values
+        };
+
+        // Stopped global LFO produces no output, even if voice LFOs are running
+        let values = run(&mut voice_lfos, &mut stopped_global_lfos);
+
+        assert!(values.iter().flatten().all(Option::is_none));
+
+        let values = run(&mut voice_lfos, &mut running_global_lfos);
+
+        assert_ne!(values[values.len() - 1][0], Some(0.0));
+
+        // Both voices follow the same LFO, but amount is applied per voice
+        for [a, b] in values {
+            let (a, b) = (a.unwrap(), b.unwrap());
+
+            assert!((b - a * 0.5).abs() <= a.abs() * 0.01);
+        }
+
+        // Voice LFOs are not advanced while global mode is on
+        assert_eq!(voice_lfos[0][0].get_phase().0, 0.0);
+        assert_eq!(voice_lfos[1][0].get_phase().0, 0.5);
+        assert_ne!(running_global_lfos[0].get_phase().0, 0.0);
+    }
+
+    #[test]
+    fn test_mod_wheel_factor() {
+        assert_eq!(mod_wheel_factor(0.0, 0.0), 1.0);
+        assert_eq!(mod_wheel_factor(0.0, 1.0), 1.0);
+        assert_eq!(mod_wheel_factor(1.0, 0.0), 0.0);
+        assert_eq!(mod_wheel_factor(1.0, 0.25), 0.25);
+        assert_eq!(mod_wheel_factor(0.5, 0.0), 0.5);
+        assert_eq!(mod_wheel_factor(0.5, 1.0), 1.0);
     }
 }
@@ -1,22 +1,55 @@
 pub mod lfo;
 
-use std::f64::consts::TAU;
+use std::f64::consts::{FRAC_1_SQRT_2, PI, TAU};
 
 use duplicate::duplicate_item;
 use ringbuf::ring_buffer::RbBase;
+use serde::{Deserialize, Serialize};
 
 use crate::audio::parameters::{common::AudioParameter, OperatorAudioParameters};
+use crate::audio::tasks::{TaskExecutor, TaskSlice};
+use crate::audio::voices::envelopes::EnvelopeDurationModulation;
 use crate::audio::voices::log10_table::Log10Table;
-use crate::audio::AudioState;
+use crate::audio::voices::Voice;
+use crate::audio::{AudioState, MAX_FADING_VOICES};
 use crate::common::*;
+use crate::math::wave::{sine_variant, sine_variant_scalar};
+use crate::parameters::key_pan::KeyPanValue;
+use crate::parameters::operator_feedback::feedback_gain_compensation;
+use crate::parameters::operator_key_scaling::key_scaling_gain;
+use crate::parameters::operator_panning_lfo_mode::PanningLfoMode;
+use crate::parameters::operator_panning_mode::PanningMode;
 use crate::parameters::operator_wave_type::WaveType;
-use crate::parameters::{MasterParameter, ModTargetStorage, OperatorParameter, Parameter};
+use crate::parameters::unison::{unison_voice_position, MAX_UNISON_VOICES};
+use crate::parameters::{
+    MasterPanValue, MasterParameter, ModTargetStorage, OperatorFeedbackToneValue,
+    OperatorParameter, Parameter, ParameterValue,
+};
 use crate::simd::*;
 
 use lfo::*;
 
 const MASTER_VOLUME_FACTOR: f64 = 0.2;
-const LIMIT: f64 = 10.0;
+/// Mix output of auditioned operator
+const AUDITION_MIX_OUT: f32 = 0.5;
+/// Maximum volume of auditioned operator, keeping level safe
+const AUDITION_MAX_VOLUME: f32 = 1.0;
+
+/// Voice index of monophonic voice in voice data
+const MONOPHONIC_VOICE_INDEX: u8 = 128;
+/// Voice index of first fading voice in voice data
+const FADING_VOICES_START_INDEX: u8 = MONOPHONIC_VOICE_INDEX + 1;
+/// Voice index of first unison voice in voice data
+const UNISON_VOICES_START_INDEX: u8 = FADING_VOICES_START_INDEX + MAX_FADING_VOICES as u8;
+/// Number of voice data items needed if all voices are active
+const MAX_VOICE_DATAS: usize = UNISON_VOICES_START_INDEX as usize + MAX_UNISON_VOICES - 1;
+
+/// Minimum number of voices per parallel render task. Voices are rendered
+/// one or two samples at a time, so each task needs a fair amount of them
+/// to be worth handing over to another thread.
+const MIN_VOICES_PER_TASK: usize = 8;
+/// Maximum number of parallel voice render tasks
+const MAX_VOICE_RENDER_TASKS: usize = 8;
 
 pub trait AudioGen {
     #[allow(clippy::missing_safety_doc)]
@@ -35,9 +68,10 @@ pub trait AudioGen {
 pub struct AudioGenData<const W: usize> {
     lfo_target_values: LfoTargetValues,
     volume_velocity_sensitivity: [f64; W],
-    /// Allocate room for data for 128 polyphonic voices as well as the mono
-    /// voice, even if they won't all be used at once in practice.
-    voices: [VoiceData<W>; 129],
+    /// Allocate room for data for 128 polyphonic voices, the mono voice,
+    /// fading stolen voices and unison voices, even if they won't all be
+    /// used at once in practice.
+    voices: [VoiceData<W>; MAX_VOICE_DATAS],
 }
 
 impl<const W: usize> Default for AudioGenData<W> {
@@ -56,6 +90,8 @@ struct VoiceData<const W: usize> {
     key_velocity: [f64; W],
     /// Master volume is calculated per-voice, since it can be an LFO target
     master_volume: [f64; W],
+    /// Constant power master panning factors. Even index = left channel
+    master_pan: [f64; W],
     operators: [VoiceOperatorData<W>; 4],
 }
 
@@ -66,6 +102,7 @@ impl<const W: usize> Default for VoiceData<W> {
             key_velocity: [0.0; W],
             /// Master volume is calculated per-voice, since it can be an LFO target
             master_volume: [0.0; W],
+            master_pan: [0.0; W],
             operators: Default::default(),
         }
     }
@@ -81,6 +118,13 @@ impl<const W: usize> VoiceData<W> {
         self.operators[2].envelope_volume.copy_from_slice(&[0.0; W]);
         self.operators[3].envelope_volume.copy_from_slice(&[0.0; W]);
     }
+
+    #[inline]
+    fn has_backward_modulation_targets(&self) -> bool {
+        self.operators
+            .iter()
+            .any(|operator| !operator.backward_modulation_targets.is_empty())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -89,14 +133,26 @@ struct VoiceOperatorData<const W: usize> {
     mix_out: [f64; W],
     mod_out: [f64; W],
     feedback: [f64; W],
+    /// Lowpass filtered feedback signal, only used if feedback_tone_active
+    /// is set
+    feedback_signal: [f64; W],
+    feedback_tone_active: bool,
     panning: [f64; W],
     constant_power_panning: [f64; W],
+    mid_side_panning: bool,
+    /// Mid/side panning factors for mono component. Even index = left channel
+    mid_side_mono_factors: [f64; W],
+    mid_side_side_factor: [f64; W],
     envelope_volume: [f64; W],
     phase: [f64; W],
     wave_type: WaveType,
     modulation_targets: ModTargetStorage,
+    backward_modulation_targets: ModTargetStorage,
+    /// Stereo modulation output of the sample preceding this pass
+    last_mod_out: [f64; 2],
     velocity_sensitivity_mod_out: [f64; W],
     velocity_sensitivity_feedback: [f64; W],
+    velocity_sensitivity_mix_out: [f64; W],
 }
 
 impl<const W: usize> Default for VoiceOperatorData<W> {
@@ -106,14 +162,75 @@ impl<const W: usize> Default for VoiceOperatorData<W> {
             mix_out: [0.0; W],
             mod_out: [0.0; W],
             feedback: [0.0; W],
+            feedback_signal: [0.0; W],
+            feedback_tone_active: false,
             panning: [0.0; W],
             constant_power_panning: [0.0; W],
+            mid_side_panning: false,
+            mid_side_mono_factors: [0.0; W],
+            mid_side_side_factor: [0.0; W],
             envelope_volume: [0.0; W],
             phase: [0.0; W],
             wave_type: Default::default(),
             modulation_targets: Default::default(),
+            backward_modulation_targets: Default::default(),
+            last_mod_out: [0.0; 2],
             velocity_sensitivity_mod_out: [0.0; W],
             velocity_sensitivity_feedback: [0.0; W],
+            velocity_sensitivity_mix_out: [0.0; W],
+        }
+    }
+}
+
+/// Audio generation code path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SimdBackend {
+    Fallback,
+    Sse2,
+    Avx,
+}
+
+impl SimdBackend {
+    /// Fastest backend supported by CPU, as selected at runtime by
+    /// `process_f32_runtime_select`
+    pub fn detect() -> Self {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx") {
+                Self::Avx
+            } else {
+                Self::Sse2
+            }
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            Self::Fallback
+        }
+    }
+
+    /// Use backend override if CPU supports it, otherwise fastest one
+    pub fn select(opt_override: Option<Self>) -> Self {
+        match opt_override {
+            Some(backend) if backend.is_supported() => backend,
+            _ => Self::detect(),
+        }
+    }
+
+    pub fn is_supported(self) -> bool {
+        match self {
+            Self::Fallback => true,
+            Self::Sse2 => cfg!(target_arch = "x86_64"),
+            Self::Avx => Self::detect() == Self::Avx,
+        }
+    }
+}
+
+impl ::std::fmt::Display for SimdBackend {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match self {
+            Self::Fallback => write!(f, "fallback"),
+            Self::Sse2 => write!(f, "SSE2"),
+            Self::Avx => write!(f, "AVX"),
         }
     }
 }
@@ -127,6 +244,30 @@ pub fn process_f32_runtime_select<F>(
     updater: F,
 ) where
     F: Fn(&mut AudioState),
+{
+    process_f32_runtime_select_with_operator_buses(
+        audio_state,
+        lefts,
+        rights,
+        &mut [],
+        frame_offset,
+        updater,
+    )
+}
+
+/// Generate audio, additionally writing operator bus outputs to
+/// `operator_bus_channels`, which contains left and right channels for each
+/// operator in order. Missing or empty channels are skipped.
+#[inline]
+pub fn process_f32_runtime_select_with_operator_buses<F>(
+    audio_state: &mut AudioState,
+    lefts: &mut [f32],
+    rights: &mut [f32],
+    operator_bus_channels: &mut [&mut [f32]],
+    frame_offset: usize,
+    updater: F,
+) where
+    F: Fn(&mut AudioState),
 {
     let num_samples = lefts.len();
 
@@ -135,12 +276,31 @@ pub fn process_f32_runtime_select<F>(
     loop {
         updater(audio_state);
 
+        audio_state.update_bypass();
+
         let num_remaining_samples = (num_samples - position) as u64;
 
-        unsafe {
-            match num_remaining_samples {
+        if (num_remaining_samples > 0) && audio_state.is_bypassed() {
+            audio_state.process_events_for_sample(frame_offset + position);
+
+            lefts[position] = 0.0;
+            rights[position] = 0.0;
+
+            for channel in operator_bus_channels.iter_mut().take(NUM_OPERATORS * 2) {
+                if let Some(sample) = channel.get_mut(position) {
+                    *sample = 0.0;
+                }
+            }
+
+            position += 1;
+
+            continue;
+        }
+
+        let new_position = unsafe {
+            match (num_remaining_samples, audio_state.simd_backend) {
                 #[cfg(target_arch = "x86_64")]
-                (2..) if is_x86_feature_detected!("avx") => {
+                (2.., SimdBackend::Avx) => {
                     let new_position = position + 2;
 
                     Avx::process_f32(
@@ -150,10 +310,23 @@ pub fn process_f32_runtime_select<F>(
                         frame_offset + position,
                     );
 
-                    position = new_position;
+                    new_position
+                }
+                #[cfg(target_arch = "x86_64")]
+                (1.., SimdBackend::Fallback) => {
+                    let new_position = position + 1;
+
+                    Fallback::process_f32(
+                        audio_state,
+                        &mut lefts[position..new_position],
+                        &mut rights[position..new_position],
+                        frame_offset + position,
+                    );
+
+                    new_position
                 }
                 #[cfg(target_arch = "x86_64")]
-                1.. => {
+                (1.., _) => {
                     let new_position = position + 1;
 
                     Sse2::process_f32(
@@ -163,10 +336,10 @@ pub fn process_f32_runtime_select<F>(
                         frame_offset + position,
                     );
 
-                    position = new_position;
+                    new_position
                 }
                 #[cfg(not(target_arch = "x86_64"))]
-                1.. => {
+                (1.., _) => {
                     let new_position = position + 1;
 
                     Fallback::process_f32(
@@ -176,12 +349,104 @@ pub fn process_f32_runtime_select<F>(
                         frame_offset + position,
                     );
 
-                    position = new_position;
+                    new_position
                 }
-                0 => {
+                (0, _) => {
                     break;
                 }
             }
+        };
+
+        for (channel_index, channel) in operator_bus_channels
+            .iter_mut()
+            .take(NUM_OPERATORS * 2)
+            .enumerate()
+        {
+            let bus_samples = &audio_state.operator_bus_samples[channel_index / 2];
+
+            for (sample_index, position) in (position..new_position).enumerate() {
+                if let Some(sample) = channel.get_mut(position) {
+                    *sample = bus_samples[sample_index * 2 + channel_index % 2];
+                }
+            }
+        }
+
+        position = new_position;
+    }
+}
+
+/// Advance operator phase by one sample, wrapping it into [0.0, 1.0) so
+/// that precision isn't lost on long notes. All wave forms have a period
+/// of 1.0, so wrapping doesn't affect output.
+#[inline]
+fn advance_phase(phase: f64, frequency: f64, time_per_sample: TimePerSample) -> f64 {
+    let new_phase = phase + frequency * time_per_sample.0;
+
+    new_phase - new_phase.floor()
+}
+
+/// Scalar operator waveform used as feedback signal
+#[inline]
+fn feedback_wave(wave_type: WaveType, phase: f64) -> f64 {
+    match wave_type {
+        WaveType::Sine => (phase * TAU).sin(),
+        WaveType::Square => crate::math::wave::square(phase),
+        WaveType::Triangle => crate::math::wave::triangle(phase),
+        WaveType::Saw => crate::math::wave::saw(phase),
+        WaveType::WhiteNoise => 0.0,
+        WaveType::SineVariant(variant) => sine_variant_scalar(variant, phase),
+    }
+}
+
+/// Mid/side panning factors. Panning 0.0 only keeps the mid component, 0.5
+/// leaves the signal unchanged and 1.0 moves all of it to the sides.
+///
+/// Returns factors for mono component in left and right channels and factor
+/// for side component.
+#[inline]
+fn mid_side_panning_factors(panning: f64) -> ([f64; 2], f64) {
+    if panning <= 0.5 {
+        ([1.0, 1.0], panning * 2.0)
+    } else {
+        let phase = (panning - 0.5) * PI;
+        let (mid, side) = (phase.cos(), phase.sin());
+
+        ([mid + side, mid - side], 1.0)
+    }
+}
+
+/// Store operator modulation outputs of the most recent sample in voices,
+/// so that they are available for backward modulation in the next pass
+fn save_last_mod_outs<const W: usize>(
+    voice_datas: &[VoiceData<W>],
+    polyphonic_voices: &mut IndexMap<u8, Voice>,
+    monophonic_voice: &mut Voice,
+    fading_voices: &mut [Voice],
+    unison_voices: &mut [Voice],
+) {
+    for voice_data in voice_datas {
+        if !voice_data.has_backward_modulation_targets() {
+            continue;
+        }
+
+        let opt_voice = match voice_data.voice_index {
+            MONOPHONIC_VOICE_INDEX => Some(&mut *monophonic_voice),
+            i if i >= UNISON_VOICES_START_INDEX => {
+                unison_voices.get_mut(usize::from(i - UNISON_VOICES_START_INDEX))
+            }
+            i if i >= FADING_VOICES_START_INDEX => {
+                fading_voices.get_mut(usize::from(i - FADING_VOICES_START_INDEX))
+            }
+            key => polyphonic_voices.get_mut(&key),
+        };
+
+        // Voices deactivated during this pass must keep their reset state
+        if let Some(voice) = opt_voice.filter(|voice| voice.active) {
+            for (voice_operator, operator_data) in
+                voice.operators.iter_mut().zip(voice_data.operators.iter())
+            {
+                voice_operator.last_mod_out = operator_data.last_mod_out;
+            }
         }
     }
 }
@@ -228,27 +493,49 @@ mod gen {
             assert_eq!(lefts.len(), Pd::SAMPLES);
             assert_eq!(rights.len(), Pd::SAMPLES);
 
-            if audio_state.pending_note_events.is_empty()
-                & audio_state.polyphonic_voices.is_empty()
-                & !audio_state.monophonic_voice.active
-            {
+            if audio_state.pending_note_events.is_empty() & !audio_state.has_active_voices() {
                 for (l, r) in lefts.iter_mut().zip(rights.iter_mut()) {
                     *l = 0.0;
                     *r = 0.0;
                 }
 
+                audio_state.operator_bus_samples = [[0.0; 4]; NUM_OPERATORS];
+
                 return;
             }
 
             let num_valid_voice_datas = extract_voice_data(audio_state, position);
 
+            // Auditioned operator is always heard on main output
+            let operator_buses = if audio_state.operator_buses_enabled
+                && audio_state.parameters.audition_operator.is_none()
+            {
+                ::std::array::from_fn(|i| {
+                    audio_state.parameters.operators[i].output_bus.get_value()
+                })
+            } else {
+                [false; NUM_OPERATORS]
+            };
+
             gen_audio(
                 &mut audio_state.rng,
+                audio_state.task_executor.as_deref(),
                 audio_state.audio_gen_data_field.volume_velocity_sensitivity,
-                &audio_state.audio_gen_data_field.voices[..num_valid_voice_datas],
+                &mut audio_state.audio_gen_data_field.voices[..num_valid_voice_datas],
+                operator_buses,
+                &mut audio_state.operator_bus_samples,
+                &mut audio_state.operator_mod_out_peaks,
                 lefts,
                 rights,
             );
+
+            save_last_mod_outs(
+                &audio_state.audio_gen_data_field.voices[..num_valid_voice_datas],
+                &mut audio_state.polyphonic_voices,
+                &mut audio_state.monophonic_voice,
+                &mut audio_state.fading_voices,
+                &mut audio_state.unison_voices,
+            );
         }
     }
 
@@ -263,6 +550,22 @@ mod gen {
             audio_state.advance_one_sample();
             audio_state.process_events_for_sample(position + sample_index);
 
+            // Advance after processing events, so that pitch bend without
+            // smoothing takes effect immediately
+            audio_state.global_pitch_bend.advance_one_sample(
+                audio_state.parameters.pitch_bend_smoothing.get_value(),
+                time_per_sample,
+            );
+
+            advance_global_lfos(
+                &mut audio_state.global_lfos,
+                &audio_state.parameters.lfos,
+                &audio_state.lfo_phases,
+                audio_state.sample_rate,
+                time_per_sample,
+                audio_state.bpm_lfo_multiplier,
+            );
+
             set_value_for_both_channels(
                 &mut audio_state.audio_gen_data_field.volume_velocity_sensitivity,
                 sample_index,
@@ -272,6 +575,12 @@ mod gen {
                     .get_value() as f64,
             );
 
+            let num_unison_voices = audio_state.num_unison_voices();
+            let unison_detune = audio_state.parameters.unison_detune.get_value();
+            let unison_spread = audio_state.parameters.unison_spread.get_value();
+            let key_pan = audio_state.parameters.key_pan.get_value();
+
+            let audition_operator = audio_state.parameters.audition_operator;
             let operators = &mut audio_state.parameters.operators;
             let lfo_values = &mut audio_state.audio_gen_data_field.lfo_target_values;
 
@@ -279,17 +588,33 @@ mod gen {
                 .polyphonic_voices
                 .iter_mut()
                 .chain(
-                    ::std::iter::once((&128u8, &mut audio_state.monophonic_voice))
+                    ::std::iter::once((&MONOPHONIC_VOICE_INDEX, &mut audio_state.monophonic_voice))
                         .filter(|(_, v)| v.active),
                 )
-                .map(|(k, v)| (*k, v));
+                .map(|(k, v)| (*k, v))
+                .chain(
+                    audio_state
+                        .fading_voices
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(_, v)| v.active)
+                        .map(|(i, v)| (FADING_VOICES_START_INDEX + i as u8, v)),
+                )
+                .chain(
+                    audio_state
+                        .unison_voices
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(_, v)| v.active)
+                        .map(|(i, v)| (UNISON_VOICES_START_INDEX + i as u8, v)),
+                );
 
             // Temporary storage for ownership reasons
             // bool = voice.is_monophonic
             #[cfg(feature = "clap")]
             let mut ended_clap_notes: ::arrayvec::ArrayVec<
                 (bool, crate::audio::ClapNoteEnded),
-                129,
+                { MAX_VOICE_DATAS },
             > = ::arrayvec::ArrayVec::new();
 
             for (voice_index, voice) in voice_iterator {
@@ -329,28 +654,98 @@ mod gen {
                     }
                 };
 
+                for (operator_data, voice_operator) in
+                    voice_data.operators.iter_mut().zip(voice.operators.iter())
+                {
+                    operator_data.last_mod_out = voice_operator.last_mod_out;
+                }
+
                 voice.advance_interpolators_one_sample(audio_state.sample_rate);
 
-                for (operator_index, operator) in operators.iter_mut().enumerate() {
-                    voice.operators[operator_index]
-                        .volume_envelope
-                        .advance_one_sample(
-                            &operator.volume_envelope,
-                            &mut voice.operators[operator_index].last_phase,
-                            voice.key_pressed | audio_state.sustain_pedal_on,
-                            time_per_sample,
-                        );
+                // Position within unison stack from -1.0 to 1.0 if voice is
+                // part of one
+                let opt_unison_position = match voice_index {
+                    _ if num_unison_voices == 1 => None,
+                    MONOPHONIC_VOICE_INDEX => Some(0),
+                    i if i >= UNISON_VOICES_START_INDEX => {
+                        Some((i - UNISON_VOICES_START_INDEX) as usize + 1)
+                    }
+                    _ => None,
                 }
+                .map(|i| unison_voice_position(i, num_unison_voices));
+
+                let velocity_modulation = audio_state
+                    .parameters
+                    .velocity_mod_target
+                    .get_value()
+                    .index()
+                    .map(|target_index| {
+                        let amount = audio_state.parameters.velocity_mod_amount.get_value();
+
+                        (target_index, amount * voice.get_key_velocity().0)
+                    });
 
                 update_lfo_target_values(
                     lfo_values,
+                    velocity_modulation,
+                    audio_state.mod_wheel,
                     &mut audio_state.parameters.lfos,
                     &mut voice.lfos,
+                    &mut audio_state.global_lfos,
+                    &audio_state.lfo_phases,
+                    &audio_state.lfo_target_modulations,
                     audio_state.sample_rate,
                     time_per_sample,
                     audio_state.bpm_lfo_multiplier,
                 );
 
+                const GLIDE_TIME_INDEX: u8 =
+                    Parameter::Master(MasterParameter::GlideTime).to_index();
+
+                let glide_time = audio_state
+                    .parameters
+                    .glide_time
+                    .get_value_with_lfo_addition(lfo_values.get(GLIDE_TIME_INDEX));
+
+                voice.update_glide_time(glide_time);
+
+                let sustain_pedal_depth =
+                    if voice.is_monophonic && audio_state.ignore_sustain_in_mono {
+                        0.0
+                    } else {
+                        audio_state.sustain_pedal_depth
+                    };
+
+                for (operator_index, operator) in operators.iter_mut().enumerate() {
+                    const ATTACK_INDICES: [u8; NUM_OPERATORS] =
+                        OperatorParameter::AttackDuration.index_array();
+                    const DECAY_INDICES: [u8; NUM_OPERATORS] =
+                        OperatorParameter::DecayDuration.index_array();
+                    const RELEASE_INDICES: [u8; NUM_OPERATORS] =
+                        OperatorParameter::ReleaseDuration.index_array();
+
+                    let voice_operator = &mut voice.operators[operator_index];
+
+                    // Stage durations are only modulated at note start
+                    if !voice_operator.volume_envelope.has_duration_modulation() {
+                        voice_operator.volume_envelope.set_duration_modulation(
+                            EnvelopeDurationModulation {
+                                attack: lfo_values.get(ATTACK_INDICES[operator_index]),
+                                decay: lfo_values.get(DECAY_INDICES[operator_index]),
+                                release: lfo_values.get(RELEASE_INDICES[operator_index]),
+                            },
+                        );
+                    }
+
+                    voice_operator.volume_envelope.advance_one_sample(
+                        &mut operator.volume_envelope,
+                        &mut voice_operator.last_phase,
+                        voice.key_pressed,
+                        sustain_pedal_depth,
+                        time_per_sample,
+                    );
+                }
+
                 set_value_for_both_channels(
                     &mut voice_data.key_velocity,
                     sample_index,
@@ -360,17 +755,54 @@ mod gen {
                 const MASTER_VOLUME_INDEX: u8 =
                     Parameter::Master(MasterParameter::Volume).to_index();
 
-                let master_volume = audio_state
+                let mut master_volume = audio_state
                     .parameters
                     .master_volume
                     .get_value_with_lfo_addition(lfo_values.get(MASTER_VOLUME_INDEX));
 
+                if opt_unison_position.is_some() {
+                    // Keep perceived loudness roughly constant regardless
+                    // of number of stacked voices
+                    master_volume *= (num_unison_voices as f32).sqrt().recip();
+                }
+
                 set_value_for_both_channels(
                     &mut voice_data.master_volume,
                     sample_index,
                     master_volume as f64,
                 );
 
+                const MASTER_PAN_INDEX: u8 = Parameter::Master(MasterParameter::Pan).to_index();
+
+                let master_pan = audio_state
+                    .parameters
+                    .master_pan
+                    .get_value_with_lfo_addition(lfo_values.get(MASTER_PAN_INDEX));
+
+                {
+                    let unison_pan_offset = match opt_unison_position {
+                        Some(position) => position * unison_spread * 0.5,
+                        None => 0.0,
+                    };
+                    let key_pan_offset =
+                        KeyPanValue::new_from_audio(key_pan).pan_offset(voice.midi_pitch.key());
+
+                    let pan_offset = unison_pan_offset + key_pan_offset;
+
+                    let [l, r] = if pan_offset != 0.0 {
+                        let pan = (master_pan + pan_offset).clamp(0.0, 1.0);
+
+                        MasterPanValue::new_from_audio(pan).calculate_left_and_right()
+                    } else {
+                        audio_state.parameters.master_pan.left_and_right
+                    };
+
+                    let sample_index_offset = sample_index * 2;
+
+                    voice_data.master_pan[sample_index_offset] = l as f64;
+                    voice_data.master_pan[sample_index_offset + 1] = r as f64;
+                }
+
                 const MASTER_FREQUENCY_INDEX: u8 =
                     Parameter::Master(MasterParameter::Frequency).to_index();
 
@@ -396,8 +828,21 @@ mod gen {
 
                 master_frequency *= pitch_bend_frequency_multiplier;
 
+                if let Some(position) = opt_unison_position {
+                    let cents = position * unison_detune * 0.5;
+
+                    master_frequency *= f64::from(cents * (1.0 / 1200.0)).exp2();
+                }
+
+                master_frequency *= voice.advance_aftertouch_vibrato(
+                    audio_state.parameters.aftertouch_vibrato_depth.get_value(),
+                    audio_state.parameters.aftertouch_vibrato_rate.get_value(),
+                    time_per_sample,
+                );
+
                 let voice_base_frequency =
                     voice.pitch_interpolator.get_value() as f64 * master_frequency;
+                let voice_key = voice.midi_pitch.key();
 
                 for (operator_index, operator) in operators.iter_mut().enumerate() {
                     if voice.operators[operator_index].volume_envelope.is_ended() {
@@ -414,6 +859,8 @@ mod gen {
                         lfo_values,
                         time_per_sample,
                         voice_base_frequency,
+                        voice_key,
+                        audition_operator,
                     )
                 }
 
@@ -422,7 +869,9 @@ mod gen {
 
                 #[cfg(feature = "clap")]
                 if deactivated {
-                    if let Some(clap_note_id) = voice.clap_note_id {
+                    if let Some(clap_note_id) =
+                        voice.clap_note_id.filter(|_| voice.report_clap_note_end)
+                    {
                         let key = voice.midi_pitch.key();
 
                         let note_ended = crate::audio::ClapNoteEnded {
@@ -491,11 +940,16 @@ mod gen {
                 .retain(|_, voice| voice.active);
         }
 
+        audio_state.status.set_num_active_voices(
+            audio_state.polyphonic_voices.len() + audio_state.monophonic_voice.active as usize,
+        );
+
         num_valid_voice_datas
     }
 
     #[feature_gate]
     #[target_feature_enable]
+    #[allow(clippy::too_many_arguments)]
     unsafe fn extract_voice_operator_data(
         log10table: &Log10Table,
         sample_index: usize,
@@ -506,10 +960,11 @@ mod gen {
         lfo_values: &LfoTargetValues,
         time_per_sample: TimePerSample,
         voice_base_frequency: f64,
+        voice_key: u8,
+        audition_operator: Option<usize>,
     ) {
         const VOLUME_INDICES: [u8; NUM_OPERATORS] = OperatorParameter::Volume.index_array();
         const MIX_INDICES: [u8; NUM_OPERATORS] = OperatorParameter::MixOut.index_array();
-        /// Note: MOD_INDICES index 0 is invalid (0) and must never be used
         const MOD_INDICES: [u8; NUM_OPERATORS] = OperatorParameter::ModOut.index_array();
         const FEEDBACK_INDICES: [u8; NUM_OPERATORS] = OperatorParameter::Feedback.index_array();
         const PANNING_INDICES: [u8; NUM_OPERATORS] = OperatorParameter::Panning.index_array();
@@ -524,10 +979,13 @@ mod gen {
         if let Some(p) = &mut operator_parameters.mod_targets {
             operator_data.modulation_targets = p.get_value();
         }
+        if let Some(p) = &mut operator_parameters.backward_mod_targets {
+            operator_data.backward_modulation_targets = p.get_value();
+        }
 
         let envelope_volume = voice_operator
             .volume_envelope
-            .get_volume(log10table, &operator_parameters.volume_envelope);
+            .get_volume(log10table, &mut operator_parameters.volume_envelope);
 
         set_value_for_both_channels(
             &mut operator_data.envelope_volume,
@@ -539,45 +997,109 @@ mod gen {
             .volume
             .get_value_with_lfo_addition(lfo_values.get(VOLUME_INDICES[operator_index]));
 
-        let volume_active = operator_parameters.active.get_value();
+        let auditioned = audition_operator == Some(operator_index);
+
+        // Auditioned operator is heard even if muted, but never too loud
+        let (volume, volume_active) = if auditioned {
+            (volume.min(AUDITION_MAX_VOLUME), 1.0)
+        } else {
+            (volume, operator_parameters.active.get_value())
+        };
+
+        let key_scaling = key_scaling_gain(
+            voice_key,
+            operator_parameters.key_scaling_breakpoint.get_value(),
+            operator_parameters.key_scaling_left_depth.get_value(),
+            operator_parameters.key_scaling_left_curve.get_value(),
+            operator_parameters.key_scaling_right_depth.get_value(),
+            operator_parameters.key_scaling_right_curve.get_value(),
+        );
 
         set_value_for_both_channels(
             &mut operator_data.volume,
             sample_index,
-            (volume * volume_active) as f64,
+            (volume * volume_active * key_scaling) as f64,
         );
 
-        let mix_out = operator_parameters
-            .mix_out
-            .get_value_with_lfo_addition(lfo_values.get(MIX_INDICES[operator_index]));
+        let feedback = operator_parameters
+            .feedback
+            .get_value_with_lfo_addition(lfo_values.get(FEEDBACK_INDICES[operator_index]));
+
+        set_value_for_both_channels(&mut operator_data.feedback, sample_index, feedback as f64);
+
+        let mix_out = match audition_operator {
+            Some(_) if auditioned => AUDITION_MIX_OUT,
+            Some(_) => 0.0,
+            None => operator_parameters
+                .mix_out
+                .get_value_with_lfo_addition(lfo_values.get(MIX_INDICES[operator_index])),
+        };
+        // Modulation output is left as is, since changing it would change
+        // the timbre of modulated operators
+        let mix_out = mix_out * feedback_gain_compensation(feedback);
 
         set_value_for_both_channels(&mut operator_data.mix_out, sample_index, mix_out as f64);
 
-        let mod_out = operator_parameters.mod_out.as_mut().map_or(0.0, |p| {
-            p.get_value_with_lfo_addition(lfo_values.get(MOD_INDICES[operator_index]))
-        });
+        let mod_out = operator_parameters
+            .mod_out
+            .get_value_with_lfo_addition(lfo_values.get(MOD_INDICES[operator_index]));
 
         set_value_for_both_channels(&mut operator_data.mod_out, sample_index, mod_out as f64);
 
-        let feedback = operator_parameters
-            .feedback
-            .get_value_with_lfo_addition(lfo_values.get(FEEDBACK_INDICES[operator_index]));
+        let panning_lfo_addition = lfo_values.get(PANNING_INDICES[operator_index]);
 
-        set_value_for_both_channels(&mut operator_data.feedback, sample_index, feedback as f64);
+        operator_data.mid_side_panning =
+            operator_parameters.panning_mode.get_value() == PanningMode::MidSide;
 
-        let panning = operator_parameters
-            .panning
-            .get_value_with_lfo_addition(lfo_values.get(PANNING_INDICES[operator_index]));
+        let spread_panning_lfo = !operator_data.mid_side_panning
+            && operator_parameters.panning_lfo_mode.get_value() == PanningLfoMode::Spread;
 
-        set_value_for_both_channels(&mut operator_data.panning, sample_index, panning as f64);
+        if let (true, Some(lfo_addition)) = (spread_panning_lfo, panning_lfo_addition) {
+            // Apply LFO modulation with opposite signs to left and right
+            // channels, so that they move in opposite directions
+            let left_panning = operator_parameters
+                .panning
+                .get_value_with_lfo_addition(Some(-lfo_addition));
+            let [l, _] = operator_parameters.panning.left_and_right;
+
+            let right_panning = operator_parameters
+                .panning
+                .get_value_with_lfo_addition(Some(lfo_addition));
+            let [_, r] = operator_parameters.panning.left_and_right;
+
+            let sample_index_offset = sample_index * 2;
+
+            operator_data.panning[sample_index_offset] = left_panning as f64;
+            operator_data.panning[sample_index_offset + 1] = right_panning as f64;
+
+            operator_data.constant_power_panning[sample_index_offset] = l as f64;
+            operator_data.constant_power_panning[sample_index_offset + 1] = r as f64;
+        } else {
+            let panning = operator_parameters
+                .panning
+                .get_value_with_lfo_addition(panning_lfo_addition);
+
+            set_value_for_both_channels(&mut operator_data.panning, sample_index, panning as f64);
 
-        {
             let [l, r] = operator_parameters.panning.left_and_right;
 
             let sample_index_offset = sample_index * 2;
 
             operator_data.constant_power_panning[sample_index_offset] = l as f64;
             operator_data.constant_power_panning[sample_index_offset + 1] = r as f64;
+
+            if operator_data.mid_side_panning {
+                let ([l, r], side) = mid_side_panning_factors(panning as f64);
+
+                operator_data.mid_side_mono_factors[sample_index_offset] = l;
+                operator_data.mid_side_mono_factors[sample_index_offset + 1] = r;
+
+                set_value_for_both_channels(
+                    &mut operator_data.mid_side_side_factor,
+                    sample_index,
+                    side,
+                );
+            }
         }
 
         set_value_for_both_channels(
@@ -592,6 +1114,11 @@ mod gen {
                 .velocity_sensitivity_feedback
                 .get_value() as f64,
         );
+        set_value_for_both_channels(
+            &mut operator_data.velocity_sensitivity_mix_out,
+            sample_index,
+            operator_parameters.velocity_sensitivity_mix_out.get_value() as f64,
+        );
 
         let frequency_ratio = operator_parameters
             .frequency_ratio
@@ -605,74 +1132,336 @@ mod gen {
 
         let frequency =
             voice_base_frequency * frequency_ratio.value * frequency_free * frequency_fine;
-        let new_phase = voice_operator.last_phase.0 + frequency * time_per_sample.0;
+        let new_phase = advance_phase(voice_operator.last_phase.0, frequency, time_per_sample);
 
         set_value_for_both_channels(&mut operator_data.phase, sample_index, new_phase);
 
+        // Lowpass filter feedback signal. This is done here rather than in
+        // gen_voice_operator_audio since filter state depends on previous
+        // sample.
+        {
+            if sample_index == 0 {
+                operator_data.feedback_tone_active = false;
+            }
+
+            let cutoff = OperatorFeedbackToneValue::cutoff_frequency(
+                operator_parameters.feedback_tone.get_value(),
+            );
+
+            let feedback_signal = match (cutoff, operator_data.wave_type) {
+                (_, WaveType::WhiteNoise) | (None, _) => {
+                    voice_operator.feedback_lowpass = None;
+
+                    // Value only used if another sample in this pass has
+                    // feedback tone active
+                    feedback_wave(operator_data.wave_type, new_phase)
+                }
+                (Some(cutoff), wave_type) => {
+                    let input = feedback_wave(wave_type, new_phase);
+                    let factor = 1.0 - (-TAU * cutoff * time_per_sample.0).exp();
+
+                    let output = match voice_operator.feedback_lowpass {
+                        Some(previous) => previous + (input - previous) * factor,
+                        None => input,
+                    };
+
+                    voice_operator.feedback_lowpass = Some(output);
+                    operator_data.feedback_tone_active = true;
+
+                    output
+                }
+            };
+
+            set_value_for_both_channels(
+                &mut operator_data.feedback_signal,
+                sample_index,
+                feedback_signal,
+            );
+        }
+
         // Save phase
         voice_operator.last_phase.0 = new_phase;
     }
 
+    /// Summed output of a number of voices
+    #[feature_gate]
+    #[derive(Clone, Copy)]
+    struct VoicesOutput {
+        /// Pd::SAMPLES * 2 because of two channels. Even index = left channel
+        mix_out: Pd,
+        bus_mix_outs: [Pd; NUM_OPERATORS],
+        mod_out_peaks: [Pd; NUM_OPERATORS],
+    }
+
+    /// Chunk of voices rendered by one task, with own random number
+    /// generator and output accumulator, so that tasks can run in parallel
+    #[feature_gate]
+    struct VoiceRenderTask<'a> {
+        voices: &'a mut [VoiceData<{ Pd::WIDTH }>],
+        rng: fastrand::Rng,
+        output: Option<VoicesOutput>,
+    }
+
     #[feature_gate]
     #[target_feature_enable]
+    #[allow(clippy::too_many_arguments)]
     unsafe fn gen_audio(
         rng: &mut fastrand::Rng,
+        task_executor: Option<&dyn TaskExecutor>,
         volume_velocity_sensitivity: [f64; Pd::WIDTH],
-        active_voices: &[VoiceData<{ Pd::WIDTH }>],
+        active_voices: &mut [VoiceData<{ Pd::WIDTH }>],
+        operator_buses: [bool; NUM_OPERATORS],
+        operator_bus_samples: &mut [[f32; 4]; NUM_OPERATORS],
+        operator_mod_out_peaks: &mut [f32; NUM_OPERATORS],
         audio_buffer_lefts: &mut [f32],
         audio_buffer_rights: &mut [f32],
     ) {
-        // Pd::SAMPLES * 2 because of two channels. Even index = left channel
+        let opt_output = match task_executor {
+            Some(task_executor) => gen_voices_audio_in_tasks(
+                task_executor,
+                rng,
+                volume_velocity_sensitivity,
+                active_voices,
+                operator_buses,
+            ),
+            None => None,
+        };
+
+        let VoicesOutput {
+            mix_out: total_mix_out,
+            bus_mix_outs: total_bus_mix_outs,
+            mod_out_peaks,
+        } = match opt_output {
+            Some(output) => output,
+            None => gen_voices_audio(
+                rng,
+                volume_velocity_sensitivity,
+                active_voices,
+                operator_buses,
+            ),
+        };
+
+        let total_mix_out_arr = (total_mix_out * Pd::new(MASTER_VOLUME_FACTOR)).to_arr();
+
+        for (sample_index, chunk) in total_mix_out_arr.chunks_exact(2).enumerate() {
+            audio_buffer_lefts[sample_index] = chunk[0] as f32;
+            audio_buffer_rights[sample_index] = chunk[1] as f32;
+        }
+
+        for (peak, mod_out_peak) in operator_mod_out_peaks.iter_mut().zip(mod_out_peaks) {
+            *peak = mod_out_peak
+                .to_arr()
+                .into_iter()
+                .fold(*peak, |peak, value| peak.max(value as f32));
+        }
+
+        // Operator buses don't pass through output stage
+        for (samples, bus_mix_out) in operator_bus_samples.iter_mut().zip(total_bus_mix_outs) {
+            let bus_mix_out_arr = (bus_mix_out * Pd::new(MASTER_VOLUME_FACTOR)).to_arr();
+
+            for (sample, value) in samples.iter_mut().zip(bus_mix_out_arr) {
+                *sample = value as f32;
+            }
+        }
+    }
+
+    /// Split voices into chunks rendered by separate tasks on executor,
+    /// then sum their outputs. Returns None if there are too few voices for
+    /// this to be worthwhile or if executor didn't run the tasks.
+    #[feature_gate]
+    #[target_feature_enable]
+    unsafe fn gen_voices_audio_in_tasks(
+        task_executor: &dyn TaskExecutor,
+        rng: &mut fastrand::Rng,
+        volume_velocity_sensitivity: [f64; Pd::WIDTH],
+        active_voices: &mut [VoiceData<{ Pd::WIDTH }>],
+        operator_buses: [bool; NUM_OPERATORS],
+    ) -> Option<VoicesOutput> {
+        let num_tasks = (active_voices.len() / MIN_VOICES_PER_TASK).min(MAX_VOICE_RENDER_TASKS);
+
+        if num_tasks < 2 {
+            return None;
+        }
+
+        let voices_per_task = active_voices.len().div_ceil(num_tasks);
+
+        let mut tasks: ::arrayvec::ArrayVec<VoiceRenderTask, MAX_VOICE_RENDER_TASKS> =
+            active_voices
+                .chunks_mut(voices_per_task)
+                .map(|voices| VoiceRenderTask {
+                    voices,
+                    rng: rng.fork(),
+                    output: None,
+                })
+                .collect();
+
+        let task_slice = TaskSlice::new(&mut tasks);
+
+        let executed = task_executor.execute(task_slice.len(), &|task_index| {
+            // Each task only accesses the chunk at its own index
+            if let Some(task) = task_slice.get_mut(task_index) {
+                task.output = Some(gen_voices_audio(
+                    &mut task.rng,
+                    volume_velocity_sensitivity,
+                    task.voices,
+                    operator_buses,
+                ));
+            }
+        });
+
+        if !executed {
+            return None;
+        }
+
+        let mut total = VoicesOutput {
+            mix_out: Pd::new_zeroed(),
+            bus_mix_outs: [Pd::new_zeroed(); NUM_OPERATORS],
+            mod_out_peaks: [Pd::new_zeroed(); NUM_OPERATORS],
+        };
+
+        for output in tasks.into_iter().filter_map(|task| task.output) {
+            total.mix_out += output.mix_out;
+
+            for (total, bus_mix_out) in total.bus_mix_outs.iter_mut().zip(output.bus_mix_outs) {
+                *total += bus_mix_out;
+            }
+            for (total, peak) in total.mod_out_peaks.iter_mut().zip(output.mod_out_peaks) {
+                *total = total.max(peak);
+            }
+        }
+
+        Some(total)
+    }
+
+    #[feature_gate]
+    #[target_feature_enable]
+    unsafe fn gen_voices_audio(
+        rng: &mut fastrand::Rng,
+        volume_velocity_sensitivity: [f64; Pd::WIDTH],
+        voices: &mut [VoiceData<{ Pd::WIDTH }>],
+        operator_buses: [bool; NUM_OPERATORS],
+    ) -> VoicesOutput {
         let mut total_mix_out = Pd::new_zeroed();
+        let mut total_bus_mix_outs = [Pd::new_zeroed(); NUM_OPERATORS];
+        let mut mod_out_peaks = [Pd::new_zeroed(); NUM_OPERATORS];
 
-        for voice_data in active_voices.iter() {
+        for voice_data in voices.iter_mut() {
             let operator_generate_audio = run_operator_dependency_analysis(voice_data);
 
-            // Voice modulation input storage, indexed by operator
-            let mut voice_modulation_inputs = [Pd::new_zeroed(); 4];
+            let has_backward_modulation = voice_data.has_backward_modulation_targets();
+
+            // Modulation of operators with higher index is delayed by one
+            // sample. When generating more than one sample at a time, run
+            // the operators once per sample, so that delayed modulation from
+            // the first sample is correct when generating the second one.
+            let num_passes = if has_backward_modulation {
+                Pd::SAMPLES
+            } else {
+                1
+            };
+
             let mut voice_mix_out = Pd::new_zeroed();
+            let mut voice_bus_mix_outs = [Pd::new_zeroed(); NUM_OPERATORS];
+            let mut voice_mod_outs = [Pd::new_zeroed(); NUM_OPERATORS];
 
             let key_velocity = Pd::from_arr(voice_data.key_velocity);
 
-            // Go through operators downwards, starting with operator 4
-            for operator_index in (0..4).map(|i| 3 - i) {
-                // Possibly skip generation based on previous dependency analysis
-                if !operator_generate_audio[operator_index] {
-                    continue;
+            for _ in 0..num_passes {
+                // Voice modulation input storage, indexed by operator
+                let mut voice_modulation_inputs = [Pd::new_zeroed(); 4];
+
+                voice_mix_out = Pd::new_zeroed();
+                voice_bus_mix_outs = [Pd::new_zeroed(); NUM_OPERATORS];
+
+                if has_backward_modulation {
+                    for (operator_voice_data, mod_out) in
+                        voice_data.operators.iter().zip(voice_mod_outs)
+                    {
+                        let delayed_mod_out =
+                            delay_by_one_sample(mod_out, operator_voice_data.last_mod_out);
+
+                        for target in operator_voice_data
+                            .backward_modulation_targets
+                            .active_indices()
+                        {
+                            voice_modulation_inputs[target] += delayed_mod_out;
+                        }
+                    }
                 }
 
-                let operator_voice_data = &voice_data.operators[operator_index];
+                // Go through operators downwards, starting with operator 4
+                for operator_index in (0..4).map(|i| 3 - i) {
+                    // Possibly skip generation based on previous dependency analysis
+                    if !operator_generate_audio[operator_index] {
+                        continue;
+                    }
 
-                let (mix_out, mod_out) = gen_voice_operator_audio(
-                    rng,
-                    operator_voice_data,
-                    voice_modulation_inputs[operator_index],
-                    key_velocity,
-                );
+                    let operator_voice_data = &voice_data.operators[operator_index];
+
+                    let (mix_out, mod_out) = gen_voice_operator_audio(
+                        rng,
+                        operator_voice_data,
+                        voice_modulation_inputs[operator_index],
+                        key_velocity,
+                    );
+
+                    if operator_buses[operator_index] {
+                        voice_bus_mix_outs[operator_index] += mix_out;
+                    } else {
+                        voice_mix_out += mix_out;
+                    }
+
+                    // Add modulation output to target operators' modulation inputs
+                    for target in operator_voice_data.modulation_targets.active_indices() {
+                        voice_modulation_inputs[target] += mod_out;
+                    }
+
+                    voice_mod_outs[operator_index] = mod_out;
+                }
+            }
 
-                voice_mix_out += mix_out;
+            // Only count modulation output that reaches other operators
+            for ((peak, mod_out), operator_voice_data) in mod_out_peaks
+                .iter_mut()
+                .zip(voice_mod_outs)
+                .zip(voice_data.operators.iter())
+            {
+                if !(operator_voice_data.modulation_targets.is_empty()
+                    && operator_voice_data.backward_modulation_targets.is_empty())
+                {
+                    *peak = peak.max(mod_out.abs());
+                }
+            }
 
-                // Add modulation output to target operators' modulation inputs
-                for target in operator_voice_data.modulation_targets.active_indices() {
-                    voice_modulation_inputs[target] += mod_out;
+            if has_backward_modulation {
+                for (operator_voice_data, mod_out) in
+                    voice_data.operators.iter_mut().zip(voice_mod_outs)
+                {
+                    let mod_out = mod_out.to_arr();
+
+                    operator_voice_data.last_mod_out =
+                        [mod_out[Pd::WIDTH - 2], mod_out[Pd::WIDTH - 1]];
                 }
             }
 
             let master_volume = Pd::from_arr(voice_data.master_volume);
+            let master_pan = Pd::from_arr(voice_data.master_pan);
             let volume_velocity_factor =
                 velocity_factor(Pd::from_arr(volume_velocity_sensitivity), key_velocity);
 
-            total_mix_out += voice_mix_out * volume_velocity_factor * master_volume;
-        }
+            let voice_factor = volume_velocity_factor * master_volume * master_pan;
 
-        let total_mix_out_arr = (total_mix_out * Pd::new(MASTER_VOLUME_FACTOR))
-            .min(Pd::new(LIMIT))
-            .max(Pd::new(-LIMIT))
-            .to_arr();
+            total_mix_out += voice_mix_out * voice_factor;
 
-        for (sample_index, chunk) in total_mix_out_arr.chunks_exact(2).enumerate() {
-            audio_buffer_lefts[sample_index] = chunk[0] as f32;
-            audio_buffer_rights[sample_index] = chunk[1] as f32;
+            for (total, voice) in total_bus_mix_outs.iter_mut().zip(voice_bus_mix_outs) {
+                *total += voice * voice_factor;
+            }
+        }
+
+        VoicesOutput {
+            mix_out: total_mix_out,
+            bus_mix_outs: total_bus_mix_outs,
+            mod_out_peaks,
         }
     }
 
@@ -692,28 +1481,59 @@ mod gen {
             feedback * velocity_factor(velocity_sensitivity, key_velocity)
         };
 
+        let feedback_signal = if operator_data.feedback_tone_active {
+            Some(Pd::from_arr(operator_data.feedback_signal))
+        } else {
+            None
+        };
+
         let sample = match operator_data.wave_type {
             WaveType::Sine => {
                 let phase = phase * Pd::new(TAU);
-                let feedback = feedback * phase.fast_sin();
+                let feedback = feedback
+                    * match feedback_signal {
+                        Some(feedback_signal) => feedback_signal,
+                        None => phase.fast_sin(),
+                    };
 
                 (phase + feedback + modulation_inputs).fast_sin()
             }
             WaveType::Square => {
-                let feedback = feedback * phase.square();
+                let feedback = feedback
+                    * match feedback_signal {
+                        Some(feedback_signal) => feedback_signal,
+                        None => phase.square(),
+                    };
 
                 (phase + feedback + modulation_inputs).square()
             }
             WaveType::Triangle => {
-                let feedback = feedback * phase.triangle();
+                let feedback = feedback
+                    * match feedback_signal {
+                        Some(feedback_signal) => feedback_signal,
+                        None => phase.triangle(),
+                    };
 
                 (phase + feedback + modulation_inputs).triangle()
             }
             WaveType::Saw => {
-                let feedback = feedback * phase.saw();
+                let feedback = feedback
+                    * match feedback_signal {
+                        Some(feedback_signal) => feedback_signal,
+                        None => phase.saw(),
+                    };
 
                 (phase + feedback + modulation_inputs).saw()
             }
+            WaveType::SineVariant(variant) => {
+                let feedback = feedback
+                    * match feedback_signal {
+                        Some(feedback_signal) => feedback_signal,
+                        None => sine_variant(variant, phase),
+                    };
+
+                sine_variant(variant, phase + feedback + modulation_inputs)
+            }
             WaveType::WhiteNoise => {
                 let mut random_numbers = <Pd as SimdPackedDouble>::Arr::default();
 
@@ -734,32 +1554,50 @@ mod gen {
         let panning = Pd::from_arr(operator_data.panning);
 
         let sample = sample * volume * envelope_volume;
-
-        // Mix channels depending on panning of current operator. If panned to
-        // the middle, just pass through the stereo signals. If panned to any
-        // side, mix out the original stereo signals and mix in mono.
-        let sample = {
+        let mono = sample.pairwise_horizontal_sum() * Pd::new(0.5);
+
+        let (sample, mix_out_pan_factor, mod_out_pan_factor) = if operator_data.mid_side_panning {
+            // Scale mono component and move it to the sides by adding it
+            // with opposite signs, then scale existing side component. Use
+            // centered constant power panning factor for mix out, so that
+            // volume matches that of left/right mode.
+            let side = sample - mono;
+            let sample = mono * Pd::from_arr(operator_data.mid_side_mono_factors)
+                + side * Pd::from_arr(operator_data.mid_side_side_factor);
+
+            (sample, Pd::new(FRAC_1_SQRT_2), Pd::new(1.0))
+        } else {
+            // Mix channels depending on panning of current operator. If
+            // panned to the middle, just pass through the stereo signals. If
+            // panned to any side, mix out the original stereo signals and
+            // mix in mono.
             let mono_mix_factor = mono_mix_factor(panning);
-            let mono = sample.pairwise_horizontal_sum() * Pd::new(0.5);
+            let sample = (mono_mix_factor * mono) + ((Pd::new(1.0) - mono_mix_factor) * sample);
 
-            (mono_mix_factor * mono) + ((Pd::new(1.0) - mono_mix_factor) * sample)
+            (
+                sample,
+                Pd::from_arr(operator_data.constant_power_panning),
+                linear_panning_factor(panning),
+            )
         };
 
         let mix_out = {
-            let pan_factor = Pd::from_arr(operator_data.constant_power_panning);
+            let velocity_factor = velocity_factor(
+                Pd::from_arr(operator_data.velocity_sensitivity_mix_out),
+                key_velocity,
+            );
             let mix_out = Pd::from_arr(operator_data.mix_out);
 
-            sample * pan_factor * mix_out
+            sample * mix_out_pan_factor * velocity_factor * mix_out
         };
         let mod_out = {
-            let pan_factor = linear_panning_factor(panning);
             let velocity_factor = velocity_factor(
                 Pd::from_arr(operator_data.velocity_sensitivity_mod_out),
                 key_velocity,
             );
             let mod_out = Pd::from_arr(operator_data.mod_out);
 
-            sample * pan_factor * velocity_factor * mod_out
+            sample * mod_out_pan_factor * velocity_factor * mod_out
         };
 
         (mix_out, mod_out)
@@ -786,20 +1624,53 @@ mod gen {
             operator_mix_out_active[operator_index] = mix_out_active;
         }
 
-        for operator_index in 1..4 {
-            let all_targets_inactive = voice_data.operators[operator_index]
-                .modulation_targets
-                .active_indices()
-                .all(|mod_target| !operator_generate_audio[mod_target]);
+        // Backward modulation targets may form cycles, so repeat until no
+        // further operators can be skipped
+        loop {
+            let mut changed = false;
 
-            if all_targets_inactive & !operator_mix_out_active[operator_index] {
-                operator_generate_audio[operator_index] = false;
+            for operator_index in 0..4 {
+                if !operator_generate_audio[operator_index]
+                    | operator_mix_out_active[operator_index]
+                {
+                    continue;
+                }
+
+                let operator_data = &voice_data.operators[operator_index];
+
+                let all_targets_inactive = operator_data
+                    .modulation_targets
+                    .active_indices()
+                    .chain(operator_data.backward_modulation_targets.active_indices())
+                    .all(|mod_target| !operator_generate_audio[mod_target]);
+
+                if all_targets_inactive {
+                    operator_generate_audio[operator_index] = false;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
             }
         }
 
         operator_generate_audio
     }
 
+    /// Delay stereo samples by one sample, using `previous` as the first one
+    #[feature_gate]
+    #[target_feature_enable]
+    unsafe fn delay_by_one_sample(samples: Pd, previous: [f64; 2]) -> Pd {
+        let samples = samples.to_arr();
+        let mut delayed = samples;
+
+        delayed[..2].copy_from_slice(&previous);
+        delayed[2..].copy_from_slice(&samples[..Pd::WIDTH - 2]);
+
+        Pd::from_arr(delayed)
+    }
+
     #[feature_gate]
     #[target_feature_enable]
     unsafe fn set_value_for_both_channels(
@@ -896,5 +1767,232 @@ mod gen {
                 );
             }
         }
+
+        #[test_feature_gate]
+        #[test]
+        fn test_delay_by_one_sample() {
+            unsafe {
+                let mut arr = [0.0; Pd::WIDTH];
+
+                for (i, v) in arr.iter_mut().enumerate() {
+                    *v = i as f64 + 2.0;
+                }
+
+                let mut expected = [0.0; Pd::WIDTH];
+
+                for (i, v) in expected.iter_mut().enumerate() {
+                    *v = i as f64;
+                }
+
+                assert_eq!(
+                    delay_by_one_sample(Pd::from_arr(arr), [0.0, 1.0]).to_arr(),
+                    expected
+                );
+            }
+        }
+
+        /// Generating several samples at once must give the same result as
+        /// generating one sample at a time when there is backward modulation
+        #[test_feature_gate]
+        #[test]
+        fn test_backward_modulation() {
+            use crate::audio::KeyVelocity;
+            use crate::parameters::{Operator1BackwardModulationTargetValue, OperatorModOutValue};
+
+            const NUM_SAMPLES: usize = 64;
+
+            unsafe fn render<A: AudioGen>(samples_per_pass: usize) -> Vec<f32> {
+                let mut audio = AudioState::default();
+
+                // Operator 1 modulates operator 2, which modulates operator 1
+                audio.set_parameter_from_patch(
+                    Parameter::Operator(0, OperatorParameter::BackwardModTargets),
+                    Operator1BackwardModulationTargetValue::new_from_text("2")
+                        .unwrap()
+                        .to_patch(),
+                );
+                for operator_index in [0, 1] {
+                    audio.set_parameter_from_patch(
+                        Parameter::Operator(operator_index, OperatorParameter::ModOut),
+                        OperatorModOutValue::new_from_audio(8.0).to_patch(),
+                    );
+                }
+                audio.set_parameter_from_patch(
+                    Parameter::Operator(0, OperatorParameter::MixOut),
+                    0.0,
+                );
+                audio.set_parameter_from_patch(
+                    Parameter::Operator(1, OperatorParameter::MixOut),
+                    1.0,
+                );
+
+                audio.key_on(60, KeyVelocity::default(), None, 0);
+
+                let mut lefts = vec![0.0; NUM_SAMPLES];
+                let mut rights = vec![0.0; NUM_SAMPLES];
+
+                for position in (0..NUM_SAMPLES).step_by(samples_per_pass) {
+                    let end = position + samples_per_pass;
+
+                    A::process_f32(
+                        &mut audio,
+                        &mut lefts[position..end],
+                        &mut rights[position..end],
+                        position,
+                    );
+                }
+
+                assert!(audio.polyphonic_voices[&60].operators[0].last_mod_out[0] != 0.0);
+
+                lefts
+            }
+
+            unsafe {
+                let expected = render::<Fallback>(1);
+                let actual = render::<S>(Pd::SAMPLES);
+
+                assert!(expected.iter().any(|sample| sample.abs() > 0.01));
+
+                for (a, b) in actual.into_iter().zip(expected) {
+                    assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_phase_hour_long_note() {
+        let time_per_sample: TimePerSample = SampleRate::default().into();
+        let frequency = 440.0 * 1.5;
+        let num_samples = 60 * 60 * SampleRate::default().0 as usize;
+
+        let mut phase = 0.0;
+
+        for _ in 0..num_samples {
+            phase = advance_phase(phase, frequency, time_per_sample);
+        }
+
+        assert!((0.0..1.0).contains(&phase));
+
+        let expected = (num_samples as f64 * frequency * time_per_sample.0).fract();
+
+        assert!(((phase * TAU).sin() - (expected * TAU).sin()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mid_side_panning_factors() {
+        assert_eq!(mid_side_panning_factors(0.0), ([1.0, 1.0], 0.0));
+        assert_eq!(mid_side_panning_factors(0.5), ([1.0, 1.0], 1.0));
+
+        let ([l, r], side) = mid_side_panning_factors(1.0);
+
+        assert!((l - 1.0).abs() < 1e-10);
+        assert!((r + 1.0).abs() < 1e-10);
+        assert_eq!(side, 1.0);
+    }
+
+    #[test]
+    fn test_advance_phase_wraps() {
+        let time_per_sample = TimePerSample(0.25);
+
+        assert_eq!(advance_phase(0.5, 1.0, time_per_sample), 0.75);
+        assert_eq!(advance_phase(0.75, 1.0, time_per_sample), 0.0);
+        assert_eq!(advance_phase(0.75, 3.0, time_per_sample), 0.5);
+    }
+
+    #[test]
+    fn test_simd_backend_select() {
+        assert_eq!(SimdBackend::select(None), SimdBackend::detect());
+        assert_eq!(
+            SimdBackend::select(Some(SimdBackend::Fallback)),
+            SimdBackend::Fallback
+        );
+        assert!(SimdBackend::select(Some(SimdBackend::Avx)).is_supported());
+
+        #[cfg(not(target_arch = "x86_64"))]
+        assert_eq!(
+            SimdBackend::select(Some(SimdBackend::Sse2)),
+            SimdBackend::Fallback
+        );
+    }
+
+    #[test]
+    fn test_voices_rendered_in_tasks() {
+        use std::sync::atomic::Ordering;
+        use std::sync::Arc;
+
+        use crate::audio::tasks::tests::ThreadTaskExecutor;
+
+        /// Executor that never runs tasks
+        struct BusyTaskExecutor;
+
+        impl TaskExecutor for BusyTaskExecutor {
+            fn execute(&self, _num_tasks: usize, _task: &(dyn Fn(usize) + Sync)) -> bool {
+                false
+            }
+        }
+
+        fn render(
+            opt_executor: Option<Arc<dyn TaskExecutor>>,
+            num_voices: u8,
+        ) -> ([f32; 256], [f32; 256]) {
+            let mut audio = AudioState::default();
+
+            if let Some(executor) = opt_executor {
+                audio.set_task_executor(executor);
+            }
+
+            for key in 0..num_voices {
+                audio.enqueue_note_event(NoteEvent {
+                    delta_frames: 0,
+                    event: NoteEventInner::Midi {
+                        data: [0b_1001_0000, 40 + key, 100],
+                    },
+                });
+            }
+
+            let mut lefts = [0.0f32; 256];
+            let mut rights = [0.0f32; 256];
+
+            process_f32_runtime_select(&mut audio, &mut lefts, &mut rights, 0, |_| ());
+
+            (lefts, rights)
+        }
+
+        let executor = Arc::new(ThreadTaskExecutor::default());
+
+        // Too few voices to be rendered in tasks
+        render(Some(executor.clone()), MIN_VOICES_PER_TASK as u8 * 2 - 1);
+
+        assert_eq!(executor.num_executions.load(Ordering::Relaxed), 0);
+
+        // Output matches that of voices rendered on current thread, apart
+        // from summation order
+        let num_voices = MIN_VOICES_PER_TASK as u8 * 3;
+
+        let (lefts, rights) = render(Some(executor.clone()), num_voices);
+        let (expected_lefts, expected_rights) = render(None, num_voices);
+
+        assert!(executor.num_executions.load(Ordering::Relaxed) > 0);
+        assert!(lefts.iter().any(|s| *s != 0.0));
+
+        for (sample, expected) in lefts
+            .iter()
+            .chain(rights.iter())
+            .zip(expected_lefts.iter().chain(expected_rights.iter()))
+        {
+            assert!((sample - expected).abs() < 1e-5);
+        }
+
+        // Voices are rendered on current thread if executor doesn't run tasks
+        let (lefts, rights) = render(Some(Arc::new(BusyTaskExecutor)), num_voices);
+
+        assert_eq!(lefts, expected_lefts);
+        assert_eq!(rights, expected_rights);
     }
 }
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::common::SampleRate;
 
 #[derive(Debug, Copy, Clone)]
@@ -43,6 +45,42 @@ impl InterpolationDuration {
     }
 }
 
+/// Global parameter smoothing setting, scaling interpolation durations of
+/// audio parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ParameterSmoothing {
+    Fast,
+    #[default]
+    Default,
+    Slow,
+}
+
+impl ParameterSmoothing {
+    pub fn duration_factor(self) -> f64 {
+        match self {
+            Self::Fast => 0.5,
+            Self::Default => 1.0,
+            Self::Slow => 4.0,
+        }
+    }
+}
+
+/// Interpolation curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum InterpolationCurve {
+    /// Constant step size
+    #[default]
+    Linear,
+    /// One-pole lowpass filter, approaching target value quickly at first,
+    /// then slowing down. Avoids audible corners at the start and end of
+    /// large jumps.
+    Exponential,
+}
+
+/// Number of time constants that exponential interpolation lasts. After this
+/// many, 0.1% of the distance to the target remains and is skipped.
+const EXPONENTIAL_TIME_CONSTANTS: f32 = 6.9;
+
 /// Interpolation value factor for increasing precision and avoiding subnormals
 /// with very small numbers.
 const FACTOR: f32 = 1_000_000_000.0;
@@ -54,9 +92,15 @@ pub struct Interpolator {
     cached_value: f32,
     current_value: f32,
     target_value: f32,
+    /// Step size with linear curve, filter coefficient with exponential curve
     step_size: f32,
     steps_remaining: usize,
+    curve: InterpolationCurve,
     interpolation_duration: InterpolationDuration,
+    /// Multiplier for interpolation duration, e.g., from ParameterSmoothing
+    duration_factor: f64,
+    /// Lower bound for duration of interpolations started while set
+    min_duration: Option<InterpolationDuration>,
     sample_rate: SampleRate,
 }
 
@@ -68,7 +112,10 @@ impl Interpolator {
             target_value: value * FACTOR,
             step_size: 0.0,
             steps_remaining: 0,
+            curve: InterpolationCurve::Linear,
             interpolation_duration,
+            duration_factor: 1.0,
+            min_duration: None,
             sample_rate: SampleRate::default(),
         }
     }
@@ -92,7 +139,19 @@ impl Interpolator {
         }
 
         self.steps_remaining -= 1;
-        self.current_value += self.step_size;
+
+        match self.curve {
+            // Set final value exactly to avoid accumulated rounding errors
+            _ if self.steps_remaining == 0 => {
+                self.current_value = self.target_value;
+            }
+            InterpolationCurve::Linear => {
+                self.current_value += self.step_size;
+            }
+            InterpolationCurve::Exponential => {
+                self.current_value += (self.target_value - self.current_value) * self.step_size;
+            }
+        }
 
         // Force value to be at least zero to avoid breaking expectations
         // elsewhere, notable in operator volume/mod out/mix out operator
@@ -106,12 +165,42 @@ impl Interpolator {
         self.cached_value
     }
 
+    fn num_steps(&self, duration: InterpolationDuration) -> usize {
+        let mut duration = duration.0 * self.duration_factor;
+
+        if let Some(min_duration) = self.min_duration {
+            duration = duration.max(min_duration.0);
+        }
+
+        InterpolationDuration(duration).samples(self.sample_rate)
+    }
+
     fn restart_interpolation(&mut self) {
-        let num_steps = self.interpolation_duration.samples(self.sample_rate);
-        let step_size = (self.target_value - self.current_value) / (num_steps as f32);
+        let num_steps = self.num_steps(self.interpolation_duration);
 
         self.steps_remaining = num_steps;
-        self.step_size = step_size;
+        self.step_size = self.calculate_step_size(num_steps);
+    }
+
+    fn calculate_step_size(&self, num_steps: usize) -> f32 {
+        match self.curve {
+            InterpolationCurve::Linear => {
+                (self.target_value - self.current_value) / (num_steps as f32)
+            }
+            InterpolationCurve::Exponential => {
+                1.0 - (-EXPONENTIAL_TIME_CONSTANTS / (num_steps as f32)).exp()
+            }
+        }
+    }
+
+    pub fn set_curve(&mut self, curve: InterpolationCurve) {
+        if curve != self.curve {
+            self.curve = curve;
+
+            if self.steps_remaining != 0 {
+                self.restart_interpolation();
+            }
+        }
     }
 
     #[allow(clippy::float_cmp)]
@@ -140,6 +229,58 @@ impl Interpolator {
             self.restart_interpolation();
         }
     }
+
+    /// Change duration of ongoing interpolation, keeping its progress
+    pub fn change_remaining_duration(&mut self, duration: InterpolationDuration) {
+        if (duration.0 - self.interpolation_duration.0).abs() <= 0.001 {
+            return;
+        }
+
+        let previous_num_steps = self.num_steps(self.interpolation_duration);
+
+        self.interpolation_duration = duration;
+
+        self.rescale_remaining_steps(previous_num_steps);
+    }
+
+    /// Set lower bound for duration of interpolations started from now on.
+    /// Ongoing interpolations are not affected.
+    pub fn set_min_duration(&mut self, min_duration: Option<InterpolationDuration>) {
+        self.min_duration = min_duration;
+    }
+
+    /// Change duration factor, keeping progress of ongoing interpolation
+    pub fn set_duration_factor(&mut self, duration_factor: f64) {
+        if (duration_factor - self.duration_factor).abs() <= f64::EPSILON {
+            return;
+        }
+
+        let previous_num_steps = self.num_steps(self.interpolation_duration);
+
+        self.duration_factor = duration_factor;
+
+        self.rescale_remaining_steps(previous_num_steps);
+    }
+
+    fn rescale_remaining_steps(&mut self, previous_num_steps: usize) {
+        if self.steps_remaining == 0 {
+            return;
+        }
+
+        let remaining_fraction = self.steps_remaining as f64 / previous_num_steps as f64;
+        let num_steps = ((self.num_steps(self.interpolation_duration) as f64 * remaining_fraction)
+            .round() as usize)
+            .max(1);
+
+        self.steps_remaining = num_steps;
+        self.step_size = match self.curve {
+            InterpolationCurve::Linear => self.calculate_step_size(num_steps),
+            // Keep rate of approach proportional to full duration
+            InterpolationCurve::Exponential => {
+                self.calculate_step_size(self.num_steps(self.interpolation_duration))
+            }
+        };
+    }
 }
 
 #[cfg(test)]
@@ -219,4 +360,97 @@ mod tests {
                 as fn(f32) -> TestResult,
         );
     }
+
+    #[test]
+    fn test_interpolator_change_remaining_duration() {
+        let sample_rate = SampleRate::default();
+
+        let mut interpolator = Interpolator::new(0.0, InterpolationDuration::exactly_100ms());
+
+        interpolator.set_value(1.0);
+
+        let num_samples = InterpolationDuration::exactly_100ms().samples(sample_rate);
+
+        for _ in 0..num_samples / 2 {
+            interpolator.advance_one_sample(sample_rate, &mut |_| {})
+        }
+
+        interpolator.change_remaining_duration(InterpolationDuration::exactly_1s());
+
+        let num_samples = InterpolationDuration::exactly_1s().samples(sample_rate);
+
+        for _ in 0..num_samples / 4 {
+            interpolator.advance_one_sample(sample_rate, &mut |_| {})
+        }
+
+        assert!((interpolator.get_value() - 0.75).abs() < 0.001);
+
+        for _ in 0..num_samples / 4 {
+            interpolator.advance_one_sample(sample_rate, &mut |_| {})
+        }
+
+        assert!((interpolator.get_value() - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_interpolator_duration_factor() {
+        let sample_rate = SampleRate::default();
+
+        let mut interpolator = Interpolator::new(0.0, InterpolationDuration::exactly_100ms());
+
+        interpolator.set_duration_factor(ParameterSmoothing::Slow.duration_factor());
+        interpolator.set_value(1.0);
+
+        let num_samples = InterpolationDuration::exactly_100ms().samples(sample_rate);
+
+        for _ in 0..num_samples {
+            interpolator.advance_one_sample(sample_rate, &mut |_| {})
+        }
+
+        assert!((interpolator.get_value() - 0.25).abs() < 0.001);
+
+        interpolator.set_duration_factor(ParameterSmoothing::Default.duration_factor());
+
+        for _ in 0..(num_samples * 3) / 4 {
+            interpolator.advance_one_sample(sample_rate, &mut |_| {})
+        }
+
+        assert!((interpolator.get_value() - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_interpolator_exponential_curve() {
+        let sample_rate = SampleRate::default();
+        let duration = InterpolationDuration::exactly_100ms();
+
+        let mut interpolator = Interpolator::new(0.0, duration);
+
+        interpolator.set_curve(InterpolationCurve::Exponential);
+        interpolator.set_value(1.0);
+
+        let num_samples = duration.samples(sample_rate);
+
+        let mut previous_value = 0.0;
+
+        for i in 0..num_samples - 1 {
+            interpolator.advance_one_sample(sample_rate, &mut |_| {});
+
+            let value = interpolator.get_value();
+
+            assert!(value >= previous_value);
+
+            // Exponential curve approaches target faster than linear one
+            if i == num_samples / 2 {
+                assert!(value > 0.95);
+            }
+
+            previous_value = value;
+        }
+
+        assert!(previous_value > 0.99 && previous_value < 1.0);
+
+        interpolator.advance_one_sample(sample_rate, &mut |_| {});
+
+        assert_eq!(interpolator.get_value(), 1.0);
+    }
 }
@@ -0,0 +1,301 @@
+use std::f64::consts::TAU;
+
+use crate::common::SampleRate;
+
+/// Q of peak band, wide enough for broad tonal adjustments
+const PEAK_Q: f64 = 0.7;
+/// Shelf slope, where 1.0 is the steepest slope without overshoot
+const SHELF_SLOPE: f64 = 1.0;
+
+/// Current master EQ parameter values. Gains are linear amplitude factors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MasterEqSettings {
+    pub low_gain: f32,
+    pub low_frequency: f32,
+    pub mid_gain: f32,
+    pub mid_frequency: f32,
+    pub high_gain: f32,
+    pub high_frequency: f32,
+}
+
+impl MasterEqSettings {
+    fn is_flat(&self) -> bool {
+        self.low_gain == 1.0 && self.mid_gain == 1.0 && self.high_gain == 1.0
+    }
+}
+
+/// Three-band master EQ: low shelf, peak and high shelf
+#[derive(Default)]
+pub struct MasterEq {
+    sample_rate: SampleRate,
+    /// Settings that coefficients were last calculated for
+    last_settings: Option<MasterEqSettings>,
+    low: Biquad,
+    mid: Biquad,
+    high: Biquad,
+}
+
+impl MasterEq {
+    pub fn set_sample_rate(&mut self, sample_rate: SampleRate) {
+        self.sample_rate = sample_rate;
+        self.last_settings = None;
+    }
+
+    pub fn reset(&mut self) {
+        self.low.reset();
+        self.mid.reset();
+        self.high.reset();
+    }
+
+    /// Process samples in place. Skipped when all bands are at unity gain, in
+    /// which case the filters are transparent anyway.
+    pub fn process(&mut self, settings: MasterEqSettings, lefts: &mut [f32], rights: &mut [f32]) {
+        if settings.is_flat() {
+            // Filter states of transparent filters are zero, so resetting
+            // doesn't cause discontinuities when bands are enabled again
+            self.reset();
+
+            return;
+        }
+
+        if self.last_settings != Some(settings) {
+            self.update_coefficients(settings);
+        }
+
+        for (l, r) in lefts.iter_mut().zip(rights.iter_mut()) {
+            let mut samples = [f64::from(*l), f64::from(*r)];
+
+            for (channel, sample) in samples.iter_mut().enumerate() {
+                *sample = self.low.process(channel, *sample);
+                *sample = self.mid.process(channel, *sample);
+                *sample = self.high.process(channel, *sample);
+            }
+
+            *l = samples[0] as f32;
+            *r = samples[1] as f32;
+        }
+    }
+
+    fn update_coefficients(&mut self, settings: MasterEqSettings) {
+        let sample_rate = self.sample_rate.0;
+
+        self.low.coefficients = BiquadCoefficients::low_shelf(
+            sample_rate,
+            settings.low_frequency.into(),
+            settings.low_gain.into(),
+        );
+        self.mid.coefficients = BiquadCoefficients::peak(
+            sample_rate,
+            settings.mid_frequency.into(),
+            settings.mid_gain.into(),
+        );
+        self.high.coefficients = BiquadCoefficients::high_shelf(
+            sample_rate,
+            settings.high_frequency.into(),
+            settings.high_gain.into(),
+        );
+
+        self.last_settings = Some(settings);
+    }
+}
+
+/// Normalized biquad coefficients (a0 = 1.0), calculated with formulas from
+/// Robert Bristow-Johnson's Audio EQ Cookbook
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BiquadCoefficients {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+impl Default for BiquadCoefficients {
+    fn default() -> Self {
+        Self {
+            b0: 1.0,
+            b1: 0.0,
+            b2: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+        }
+    }
+}
+
+impl BiquadCoefficients {
+    fn low_shelf(sample_rate: f64, frequency: f64, gain: f64) -> Self {
+        let (a, cos, alpha) = Self::shelf_intermediates(sample_rate, frequency, gain);
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        Self::normalized(
+            a * ((a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+            a * ((a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha),
+            (a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+            (a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha,
+        )
+    }
+
+    fn high_shelf(sample_rate: f64, frequency: f64, gain: f64) -> Self {
+        let (a, cos, alpha) = Self::shelf_intermediates(sample_rate, frequency, gain);
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        Self::normalized(
+            a * ((a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+            a * ((a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha),
+            (a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos),
+            (a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha,
+        )
+    }
+
+    fn peak(sample_rate: f64, frequency: f64, gain: f64) -> Self {
+        let a = gain.sqrt();
+        let omega = Self::omega(sample_rate, frequency);
+        let cos = omega.cos();
+        let alpha = omega.sin() / (2.0 * PEAK_Q);
+
+        Self::normalized(
+            1.0 + alpha * a,
+            -2.0 * cos,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        )
+    }
+
+    /// Returns (amplitude, cosine of angular frequency, alpha)
+    fn shelf_intermediates(sample_rate: f64, frequency: f64, gain: f64) -> (f64, f64, f64) {
+        let a = gain.sqrt();
+        let omega = Self::omega(sample_rate, frequency);
+        let alpha = omega.sin() / 2.0 * ((a + 1.0 / a) * (1.0 / SHELF_SLOPE - 1.0) + 2.0).sqrt();
+
+        (a, omega.cos(), alpha)
+    }
+
+    /// Angular frequency, with frequency kept below Nyquist frequency
+    fn omega(sample_rate: f64, frequency: f64) -> f64 {
+        TAU * frequency.min(sample_rate * 0.49) / sample_rate
+    }
+
+    fn normalized(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// Stereo biquad filter in transposed direct form II
+#[derive(Debug, Default)]
+struct Biquad {
+    coefficients: BiquadCoefficients,
+    states: [[f64; 2]; 2],
+}
+
+impl Biquad {
+    fn reset(&mut self) {
+        self.states = [[0.0; 2]; 2];
+    }
+
+    fn process(&mut self, channel: usize, input: f64) -> f64 {
+        let c = self.coefficients;
+        let state = &mut self.states[channel];
+
+        let output = c.b0 * input + state[0];
+
+        state[0] = c.b1 * input - c.a1 * output + state[1];
+        state[1] = c.b2 * input - c.a2 * output;
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> MasterEqSettings {
+        MasterEqSettings {
+            low_gain: 1.0,
+            low_frequency: 150.0,
+            mid_gain: 1.0,
+            mid_frequency: 1000.0,
+            high_gain: 1.0,
+            high_frequency: 6000.0,
+        }
+    }
+
+    /// Peak amplitude of sine after filter has settled
+    fn sine_amplitude(eq: &mut MasterEq, settings: MasterEqSettings, frequency: f64) -> f32 {
+        let sample_rate = SampleRate::default().0;
+
+        let mut lefts: Vec<f32> = (0..44100)
+            .map(|i| (TAU * frequency * i as f64 / sample_rate).sin() as f32)
+            .collect();
+        let mut rights = lefts.clone();
+
+        eq.process(settings, &mut lefts, &mut rights);
+
+        lefts[lefts.len() / 2..]
+            .iter()
+            .fold(0.0f32, |max, sample| max.max(sample.abs()))
+    }
+
+    #[test]
+    fn test_master_eq_flat_is_transparent() {
+        let mut eq = MasterEq::default();
+
+        let input = [0.5f32, -0.25, 1.0, 0.0];
+
+        let mut lefts = input;
+        let mut rights = input;
+
+        eq.process(settings(), &mut lefts, &mut rights);
+
+        assert_eq!(lefts, input);
+        assert_eq!(rights, input);
+    }
+
+    #[test]
+    fn test_master_eq_band_gains() {
+        let gain_6_db = 10.0f32.powf(6.0 / 20.0);
+
+        let low_boost = MasterEqSettings {
+            low_gain: gain_6_db,
+            ..settings()
+        };
+
+        assert!(
+            (sine_amplitude(&mut MasterEq::default(), low_boost, 30.0) - gain_6_db).abs() < 0.05
+        );
+        assert!((sine_amplitude(&mut MasterEq::default(), low_boost, 10_000.0) - 1.0).abs() < 0.05);
+
+        let mid_cut = MasterEqSettings {
+            mid_gain: 1.0 / gain_6_db,
+            ..settings()
+        };
+
+        assert!(
+            (sine_amplitude(&mut MasterEq::default(), mid_cut, 1000.0) - 1.0 / gain_6_db).abs()
+                < 0.05
+        );
+
+        let high_boost = MasterEqSettings {
+            high_gain: gain_6_db,
+            ..settings()
+        };
+
+        assert!(
+            (sine_amplitude(&mut MasterEq::default(), high_boost, 18_000.0) - gain_6_db).abs()
+                < 0.1
+        );
+        assert!((sine_amplitude(&mut MasterEq::default(), high_boost, 100.0) - 1.0).abs() < 0.05);
+    }
+}
@@ -1,25 +1,57 @@
+mod dc_blocker;
+mod gain_compensation;
 pub mod gen;
 mod interpolation;
+mod master_eq;
+mod output_stage;
 pub mod parameters;
+pub mod preview;
+pub mod status;
+pub mod tasks;
+pub mod timbres;
 pub mod voices;
 
 use std::mem::MaybeUninit;
+use std::sync::Arc;
+use std::time::Duration;
 
 use fastrand::Rng;
 use ringbuf::{LocalRb, Rb};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     common::*,
     parameters::{
-        glide_active::GlideActive, glide_mode::GlideMode, voice_mode::VoiceMode, Parameter,
+        effect_order::Effect, glide_active::GlideActive, glide_mode::GlideMode, lfo_mode::LfoMode,
+        unison::MAX_UNISON_VOICES, velocity_sensitivity::ReleaseVelocitySensitivityValue,
+        voice_mode::VoiceMode, Parameter, ParameterValue,
     },
 };
 
+pub use interpolation::{InterpolationCurve, ParameterSmoothing};
 use parameters::*;
 use voices::*;
 
 use self::{
-    gen::AudioGenData, parameters::common::AudioParameter, voices::log10_table::Log10Table,
+    dc_blocker::DcBlocker,
+    gain_compensation::GainCompensation,
+    gen::{
+        lfo::{LfoPhases, LfoTargetModulations},
+        AudioGenData, SimdBackend,
+    },
+    interpolation::InterpolationDuration,
+    master_eq::{MasterEq, MasterEqSettings},
+    output_stage::OutputStage,
+    parameters::common::AudioParameter,
+    preview::PatchPreviewPlayer,
+    status::AudioStatus,
+    tasks::TaskExecutor,
+    timbres::Timbre,
+    voices::{
+        lfos::VoiceLfo,
+        log10_table::Log10Table,
+        note_stack::{HeldNote, MonophonicNoteStack},
+    },
 };
 
 #[cfg(feature = "clap")]
@@ -34,21 +66,139 @@ pub struct ClapNoteEnded {
 pub type ClapEndedNotesRb =
     ringbuf::LocalRb<ClapNoteEnded, Vec<::std::mem::MaybeUninit<ClapNoteEnded>>>;
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AudioSettings {
+    /// Scale interpolation durations of parameters, e.g., to smooth out
+    /// coarse host automation
+    #[serde(default)]
+    pub parameter_smoothing: ParameterSmoothing,
+    /// Interpolation curve for volume-like parameters
+    #[serde(default)]
+    pub level_smoothing_curve: InterpolationCurve,
+    /// How keys pressed again while held by sustain pedal are handled
+    #[serde(default)]
+    pub sustain_repress_mode: SustainRepressMode,
+    /// Don't let sustain pedal hold notes in monophonic voice mode
+    #[serde(default)]
+    pub ignore_sustain_in_mono: bool,
+    /// Expose one additional stereo output per operator in plugin wrappers.
+    /// Operators are routed to them with the operator output bus parameter.
+    #[serde(default)]
+    pub operator_bus_outputs: bool,
+    /// Play the three patches following the current one on MIDI channels
+    /// 2-4, with separate parameters and voices. Channel 1 (and any other
+    /// channel) plays the current patch.
+    #[serde(default)]
+    pub multitimbral: bool,
+    /// Interpolate parameter values over a short time when switching
+    /// patches instead of jumping to them, avoiding clicks in held notes.
+    /// Only applies to parameters that are interpolated anyway, such as
+    /// volumes.
+    #[serde(default)]
+    pub crossfade_patch_changes: bool,
+    /// Force audio generation code path instead of using the fastest one
+    /// supported by the CPU, e.g., to diagnose CPU-specific glitches.
+    /// Ignored if the CPU doesn't support it.
+    #[serde(default)]
+    pub simd_backend: Option<SimdBackend>,
+}
+
+/// Handling of keys pressed again while their voice is held by sustain pedal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum SustainRepressMode {
+    /// Retrigger the sustained voice
+    #[default]
+    Retrigger,
+    /// Leave sustained voice ringing and start a new one
+    Stack,
+}
+
+/// Maximum number of detached voice copies (stolen or stacked) sounding at
+/// once
+const MAX_FADING_VOICES: usize = 16;
+
+/// Duration in seconds that clip status stays on after output last exceeded
+/// full scale
+const CLIP_HOLD_DURATION: f64 = 2.0;
+
+/// Duration in seconds of output fade when bypass is turned on or off.
+/// Longer than envelope kill duration, so voices have ended by the time
+/// output is silent.
+const BYPASS_FADE_DURATION: f64 = 0.005;
+
+/// Minimum interpolation duration of parameter values changed by switching
+/// patches, if patch change crossfading is enabled in settings
+const PATCH_CROSSFADE_DURATION: InterpolationDuration = InterpolationDuration::exactly_50ms();
+
 pub struct AudioState {
     sample_rate: SampleRate,
     time_per_sample: TimePerSample,
     bpm: BeatsPerMinute,
     bpm_lfo_multiplier: BpmLfoMultiplier,
     pub global_pitch_bend: GlobalPitchBend,
-    sustain_pedal_on: bool,
+    /// Sustain pedal (CC 64) position, from 0.0 (released) to 1.0
+    sustain_pedal_depth: f32,
+    /// Mod wheel (CC 1) position, from 0.0 to 1.0
+    mod_wheel: f32,
+    /// Latest channel pressure, applied to voices started while it is held
+    channel_pressure: KeyVelocity,
+    sustain_repress_mode: SustainRepressMode,
+    ignore_sustain_in_mono: bool,
+    operator_buses_enabled: bool,
+    crossfade_patch_changes: bool,
+    simd_backend: SimdBackend,
+    /// Operator bus output of most recent call to audio generation,
+    /// interleaved stereo samples
+    operator_bus_samples: [[f32; 4]; NUM_OPERATORS],
     parameters: AudioParameters,
     rng: Rng,
     log10table: Log10Table,
     pub polyphonic_voices: IndexMap<u8, Voice>,
     pub monophonic_voice: Voice,
-    monophonic_pressed_keys: IndexMap<u8, Option<i32>>,
+    /// Copies of restarted voices, either quickly fading out to avoid clicks
+    /// or, when stacking sustained notes, ringing out until pedal is released
+    pub fading_voices: [Voice; MAX_FADING_VOICES],
+    /// Copies of monophonic voice stacked on it in fat mono voice mode.
+    /// They are detuned and spread in audio gen.
+    pub unison_voices: [Voice; MAX_UNISON_VOICES - 1],
+    monophonic_note_stack: MonophonicNoteStack,
+    /// Keys that incoming keys were quantized to when pressed, used to
+    /// release the same key even if scale was changed in the meantime
+    quantized_keys: [u8; 128],
+    /// LFOs shared by all voices, used when LFO global parameter is on
+    global_lfos: [VoiceLfo; NUM_LFOS],
+    /// Notes pressed since no voices were active, per LFO, used to
+    /// determine if LFOs are retriggered according to their modes
+    lfo_note_counters: [u8; NUM_LFOS],
     pending_note_events: LocalRb<NoteEvent, Vec<MaybeUninit<NoteEvent>>>,
     opt_last_voice_mode: Option<VoiceMode>,
+    lfo_phases: Arc<LfoPhases>,
+    lfo_target_modulations: Arc<LfoTargetModulations>,
+    status: Arc<AudioStatus>,
+    /// Smoothed DSP load, published through status
+    dsp_load: f32,
+    /// Samples processed since output last exceeded full scale, if within
+    /// clip hold duration
+    samples_since_clip: Option<usize>,
+    /// Peak absolute modulation output per operator in current block,
+    /// published through status
+    operator_mod_out_peaks: [f32; NUM_OPERATORS],
+    patch_preview_player: Option<PatchPreviewPlayer>,
+    /// Whether bypass parameter was on when last checked, used to kill
+    /// voices when it is turned on
+    bypass_active: bool,
+    /// Output gain, faded towards 0.0 when bypassed and 1.0 otherwise
+    bypass_gain: f32,
+    /// Additional patch slots played in multitimbral mode
+    timbres: Vec<Timbre>,
+    /// Number of samples timbres can render at a time
+    timbre_buffer_len: usize,
+    /// Executor for rendering voices in parallel, if provided by host
+    task_executor: Option<Arc<dyn TaskExecutor>>,
+    master_eq: MasterEq,
+    dc_blocker: DcBlocker,
+    gain_compensation: GainCompensation,
+    output_stage: OutputStage,
     audio_gen_data_w2: Box<AudioGenData<2>>,
     #[cfg(target_arch = "x86_64")]
     audio_gen_data_w4: Box<AudioGenData<4>>,
@@ -65,13 +215,8 @@ impl Default for AudioState {
 
             voices
         };
-        let monophonic_pressed_keys = {
-            let mut pressed_keys = IndexMap::default();
 
-            pressed_keys.reserve(128);
-
-            pressed_keys
-        };
+        let key_tuning = KeyTuning::default();
 
         Self {
             sample_rate: SampleRate::default(),
@@ -79,15 +224,48 @@ impl Default for AudioState {
             bpm: Default::default(),
             bpm_lfo_multiplier: BeatsPerMinute::default().into(),
             global_pitch_bend: Default::default(),
-            sustain_pedal_on: false,
+            sustain_pedal_depth: 0.0,
+            channel_pressure: KeyVelocity(0.0),
+            mod_wheel: 0.0,
+            sustain_repress_mode: Default::default(),
+            ignore_sustain_in_mono: false,
+            operator_buses_enabled: false,
+            crossfade_patch_changes: false,
+            simd_backend: SimdBackend::detect(),
+            operator_bus_samples: [[0.0; 4]; NUM_OPERATORS],
             parameters: AudioParameters::default(),
             rng: Rng::new(),
             log10table: Default::default(),
             polyphonic_voices,
-            monophonic_voice: Voice::new(MidiPitch::new(0), true),
-            monophonic_pressed_keys,
+            monophonic_voice: Voice::new(MidiPitch::new(0, &key_tuning), true),
+            fading_voices: ::std::array::from_fn(|_| {
+                Voice::new(MidiPitch::new(0, &key_tuning), false)
+            }),
+            unison_voices: ::std::array::from_fn(|_| {
+                Voice::new(MidiPitch::new(0, &key_tuning), true)
+            }),
+            monophonic_note_stack: Default::default(),
+            quantized_keys: ::std::array::from_fn(|key| key as u8),
+            global_lfos: Default::default(),
+            lfo_note_counters: [0; NUM_LFOS],
             pending_note_events: LocalRb::new(1024),
             opt_last_voice_mode: None,
+            lfo_phases: Default::default(),
+            lfo_target_modulations: Default::default(),
+            status: Default::default(),
+            dsp_load: 0.0,
+            samples_since_clip: None,
+            operator_mod_out_peaks: [0.0; NUM_OPERATORS],
+            patch_preview_player: None,
+            bypass_active: false,
+            bypass_gain: 1.0,
+            timbres: Vec::new(),
+            timbre_buffer_len: timbres::DEFAULT_BUFFER_LEN,
+            task_executor: None,
+            master_eq: Default::default(),
+            dc_blocker: Default::default(),
+            gain_compensation: Default::default(),
+            output_stage: Default::default(),
             audio_gen_data_w2: Default::default(),
             #[cfg(target_arch = "x86_64")]
             audio_gen_data_w4: Default::default(),
@@ -102,17 +280,301 @@ impl AudioState {
         self.parameters.set_parameter_from_patch(parameter, value);
     }
 
+    /// Detune key by given number of cents relative to twelve-tone equal
+    /// temperament. Applies to subsequent key presses.
+    pub fn set_key_detune(&mut self, key: u8, cents: f32) {
+        self.parameters.key_tuning.set_key_detune(key, cents);
+    }
+
+    /// Output operator at index directly at a safe level, silencing mix
+    /// outputs of other operators, or go back to normal routing if None
+    pub fn set_audition_operator(&mut self, opt_operator_index: Option<usize>) {
+        self.parameters.audition_operator = opt_operator_index;
+    }
+
+    /// Reseed random number generator, e.g., with per-instance seed
+    pub fn set_rng_seed(&mut self, seed: u64) {
+        self.rng = Rng::with_seed(seed);
+    }
+
+    /// Call with true before and false after setting parameters changed by
+    /// switching patches. Crossfades them if enabled in settings.
+    pub fn set_patch_crossfade_active(&mut self, active: bool) {
+        if self.crossfade_patch_changes {
+            self.parameters
+                .set_min_interpolation_duration(active.then_some(PATCH_CROSSFADE_DURATION));
+        }
+    }
+
     pub fn set_sample_rate(&mut self, sample_rate: SampleRate) {
         self.sample_rate = sample_rate;
         self.time_per_sample = sample_rate.into();
+        self.master_eq.set_sample_rate(sample_rate);
+        self.dc_blocker = DcBlocker::new(sample_rate);
+        self.output_stage.set_sample_rate(sample_rate);
+
+        for timbre in self.timbres.iter_mut() {
+            timbre.set_sample_rate(sample_rate);
+        }
+    }
+
+    pub fn apply_settings(&mut self, settings: &AudioSettings) {
+        self.parameters.set_smoothing(settings.parameter_smoothing);
+        self.parameters
+            .set_level_smoothing_curve(settings.level_smoothing_curve);
+
+        self.sustain_repress_mode = settings.sustain_repress_mode;
+        self.ignore_sustain_in_mono = settings.ignore_sustain_in_mono;
+        self.operator_buses_enabled = settings.operator_bus_outputs;
+        self.crossfade_patch_changes = settings.crossfade_patch_changes;
+        self.simd_backend = SimdBackend::select(settings.simd_backend);
+
+        self.set_multitimbral(settings);
+    }
+
+    pub fn set_simd_backend(&mut self, simd_backend: SimdBackend) {
+        if simd_backend == self.simd_backend {
+            return;
+        }
+
+        self.simd_backend = simd_backend;
+
+        for timbre in self.timbres.iter_mut() {
+            timbre.set_simd_backend(simd_backend);
+        }
     }
 
     pub fn set_bpm(&mut self, bpm: BeatsPerMinute) {
         self.bpm = bpm;
         self.bpm_lfo_multiplier = bpm.into();
+
+        for timbre in self.timbres.iter_mut() {
+            timbre.set_bpm(bpm);
+        }
+    }
+
+    /// Set storage for publishing LFO phases to GUI
+    pub fn set_lfo_phases(&mut self, lfo_phases: Arc<LfoPhases>) {
+        self.lfo_phases = lfo_phases;
+    }
+
+    /// Set storage for publishing LFO target modulation to GUI
+    pub fn set_lfo_target_modulations(&mut self, modulations: Arc<LfoTargetModulations>) {
+        self.lfo_target_modulations = modulations;
+    }
+
+    /// Set storage for publishing audio thread status to GUI
+    pub fn set_status(&mut self, status: Arc<AudioStatus>) {
+        self.status = status;
+    }
+
+    /// Set executor used to render voices in parallel when many are active
+    pub fn set_task_executor(&mut self, task_executor: Arc<dyn TaskExecutor>) {
+        self.task_executor = Some(task_executor);
+    }
+
+    /// Publish time spent processing buffer relative to its duration
+    pub fn report_process_duration(&mut self, duration: Duration, num_samples: usize) {
+        /// Time constant of DSP load smoothing in seconds
+        const SMOOTHING_TIME: f64 = 0.5;
+
+        if num_samples == 0 {
+            return;
+        }
+
+        let buffer_duration = num_samples as f64 / self.sample_rate.0;
+        let dsp_load = (duration.as_secs_f64() / buffer_duration) as f32;
+        let coefficient = (1.0 - (-buffer_duration / SMOOTHING_TIME).exp()) as f32;
+
+        self.dsp_load += (dsp_load - self.dsp_load) * coefficient;
+
+        self.status.set_dsp_load(self.dsp_load);
+    }
+
+    /// Publish snapshot of active voices for display in GUI. Call once
+    /// per processed block.
+    pub fn publish_voice_snapshots(&self) {
+        let num_unison_voices = self.num_unison_voices();
+        let sample_rate = self.sample_rate;
+
+        let voices = self
+            .polyphonic_voices
+            .values()
+            .chain(::std::iter::once(&self.monophonic_voice))
+            .chain(self.unison_voices[..num_unison_voices - 1].iter())
+            .filter(|voice| voice.active)
+            .map(|voice| voice.get_snapshot(sample_rate));
+
+        self.status.set_voice_snapshots(voices);
+    }
+
+    /// Publish peak modulation output per operator for display in GUI.
+    /// Call once per processed block.
+    pub fn publish_operator_mod_out_peaks(&mut self) {
+        self.status
+            .update_operator_mod_out_peaks(self.operator_mod_out_peaks);
+
+        self.operator_mod_out_peaks = [0.0; NUM_OPERATORS];
+    }
+
+    /// Set receiving side of patch preview queues. Don't call from audio
+    /// thread.
+    pub fn set_patch_preview_player(&mut self, player: PatchPreviewPlayer) {
+        self.patch_preview_player = Some(player);
+    }
+
+    /// Add patch preview audio (if any) to output buffers. Call before
+    /// [Self::apply_output_stage].
+    pub fn mix_patch_preview(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        if let Some(player) = self.patch_preview_player.as_mut() {
+            player.mix_into(lefts, rights);
+        }
+    }
+
+    /// Kill voices and forget held keys when bypass parameter is turned on.
+    /// Note events are discarded while it stays on, so there is nothing to
+    /// release the keys later.
+    pub fn update_bypass(&mut self) {
+        let bypass = self.parameters.bypass.get_value();
+
+        if bypass && !self.bypass_active {
+            for voice in self.polyphonic_voices.values_mut() {
+                voice.kill_envelopes();
+            }
+
+            self.for_each_monophonic_voice(|voice, _, _| voice.kill_envelopes());
+
+            self.monophonic_note_stack.clear();
+            self.sustain_pedal_depth = 0.0;
+        }
+
+        self.bypass_active = bypass;
+    }
+
+    /// Whether bypass is on, output has faded out and all voices have
+    /// ended, meaning that audio generation can be skipped
+    pub fn is_bypassed(&self) -> bool {
+        self.bypass_active & (self.bypass_gain == 0.0) & !self.has_active_voices()
+    }
+
+    /// Fade output out when bypass is turned on and back in when it is
+    /// turned off. Call after all audio has been mixed into output buffers.
+    pub fn apply_bypass_fade(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        let target = if self.parameters.bypass.get_value() {
+            0.0
+        } else {
+            1.0
+        };
+
+        if self.bypass_gain == target {
+            if target == 0.0 {
+                lefts.fill(0.0);
+                rights.fill(0.0);
+            }
+
+            return;
+        }
+
+        let step = (self.time_per_sample.0 / BYPASS_FADE_DURATION) as f32;
+
+        for (l, r) in lefts.iter_mut().zip(rights.iter_mut()) {
+            self.bypass_gain = if target > self.bypass_gain {
+                (self.bypass_gain + step).min(target)
+            } else {
+                (self.bypass_gain - step).max(target)
+            };
+
+            *l *= self.bypass_gain;
+            *r *= self.bypass_gain;
+        }
+    }
+
+    fn has_active_voices(&self) -> bool {
+        !self.polyphonic_voices.is_empty()
+            | self.monophonic_voice.active
+            | self.fading_voices.iter().any(|v| v.active)
+            | self.unison_voices.iter().any(|v| v.active)
+    }
+
+    /// Output latency in samples caused by current output mode
+    pub fn get_latency(&self) -> usize {
+        self.output_stage
+            .get_latency(self.parameters.output_mode.get_value())
+    }
+
+    /// Apply gain compensation if enabled and master effects that aren't
+    /// bypassed in order selected by effect order parameter, followed by
+    /// clipping or limiting selected by output mode parameter. Call once per
+    /// processed block, after generated audio, timbres and patch preview
+    /// have been summed.
+    pub fn apply_output_stage(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        self.gain_compensation
+            .process(&self.parameters, self.sample_rate, lefts, rights);
+
+        for effect in self.parameters.effect_order.get_value().effects() {
+            match effect {
+                Effect::Eq => self.apply_master_eq(lefts, rights),
+                Effect::DcBlocker => {
+                    if self.parameters.dc_blocker.get_value() {
+                        self.dc_blocker.process(lefts, rights);
+                    } else {
+                        self.dc_blocker.reset();
+                    }
+                }
+            }
+        }
+
+        let mode = self.parameters.output_mode.get_value();
+
+        let clipped = self.output_stage.process(mode, lefts, rights);
+
+        self.update_clip_status(clipped, lefts.len());
+    }
+
+    fn apply_master_eq(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        if self.parameters.eq_bypass.get_value() {
+            self.master_eq.reset();
+
+            return;
+        }
+
+        let settings = MasterEqSettings {
+            low_gain: self.parameters.eq_low_gain.get_value(),
+            low_frequency: self.parameters.eq_low_frequency.get_value(),
+            mid_gain: self.parameters.eq_mid_gain.get_value(),
+            mid_frequency: self.parameters.eq_mid_frequency.get_value(),
+            high_gain: self.parameters.eq_high_gain.get_value(),
+            high_frequency: self.parameters.eq_high_frequency.get_value(),
+        };
+
+        self.master_eq.process(settings, lefts, rights);
+    }
+
+    /// Publish whether output exceeded full scale during clip hold duration
+    fn update_clip_status(&mut self, clipped: bool, num_samples: usize) {
+        if clipped {
+            // Store on every clip so that latch is set again after reset
+            self.status.set_clipping(true);
+            self.samples_since_clip = Some(0);
+        } else if let Some(samples) = self.samples_since_clip.as_mut() {
+            *samples += num_samples;
+
+            if *samples as f64 * self.time_per_sample.0 >= CLIP_HOLD_DURATION {
+                self.samples_since_clip = None;
+                self.status.set_clipping(false);
+            }
+        }
     }
 
     pub fn enqueue_note_events<I: Iterator<Item = NoteEvent>>(&mut self, mut events: I) {
+        if !self.timbres.is_empty() {
+            for event in events {
+                self.enqueue_note_event(event);
+            }
+
+            return;
+        }
+
         self.pending_note_events.push_iter(&mut events);
 
         if events.next().is_some() {
@@ -121,6 +583,18 @@ impl AudioState {
     }
 
     pub fn enqueue_note_event(&mut self, event: NoteEvent) {
+        if let Some(timbre) = self.get_timbre_for_note_event(&event) {
+            timbre.enqueue_note_event(event);
+
+            return;
+        }
+
+        if let NoteEventInner::ClapBpm { .. } = event.event {
+            for timbre in self.timbres.iter_mut() {
+                timbre.enqueue_note_event(event);
+            }
+        }
+
         if self.pending_note_events.push(event).is_err() {
             ::log::error!("Audio note event buffer full");
         }
@@ -132,24 +606,81 @@ impl AudioState {
         let voice_mode = self.parameters.voice_mode.get_value();
 
         if let Some(last_voice_mode) = self.opt_last_voice_mode {
-            match (last_voice_mode, voice_mode) {
-                (VoiceMode::Polyphonic, VoiceMode::Monophonic) => {
-                    self.monophonic_pressed_keys.clear();
+            match (last_voice_mode.is_monophonic(), voice_mode.is_monophonic()) {
+                (false, true) => {
+                    self.monophonic_note_stack.clear();
 
                     for voice in self.polyphonic_voices.values_mut() {
                         voice.kill_envelopes();
                     }
                 }
-                (VoiceMode::Monophonic, VoiceMode::Polyphonic) => {
-                    self.monophonic_pressed_keys.clear();
+                (true, false) => {
+                    self.monophonic_note_stack.clear();
 
-                    self.monophonic_voice.kill_envelopes();
+                    self.for_each_monophonic_voice(|voice, _, _| voice.kill_envelopes());
                 }
                 _ => (),
             }
         }
 
         self.opt_last_voice_mode = Some(voice_mode);
+
+        self.stop_unused_unison_voices();
+    }
+
+    /// Number of voices sounding for the active note in monophonic modes
+    fn num_unison_voices(&self) -> usize {
+        match self.parameters.voice_mode.get_value() {
+            VoiceMode::FatMono => self.parameters.unison_voices.get_value() as usize,
+            VoiceMode::Polyphonic | VoiceMode::Monophonic => 1,
+        }
+    }
+
+    /// Hand over sound of unison voices that are no longer part of the
+    /// stack (e.g., after leaving fat mono mode or lowering unison voice
+    /// count) to fading copies
+    fn stop_unused_unison_voices(&mut self) {
+        let num_unison_voices = self.num_unison_voices();
+
+        for voice in self.unison_voices[num_unison_voices - 1..]
+            .iter_mut()
+            .filter(|v| v.active)
+        {
+            Self::steal_voice(&mut self.fading_voices, voice);
+
+            *voice = Voice::new(MidiPitch::new(0, &self.parameters.key_tuning), true);
+        }
+    }
+
+    /// Apply function to monophonic voice and, in fat mono mode, to the
+    /// unison voices stacked on it
+    fn for_each_monophonic_voice<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Voice, &mut [Voice; MAX_FADING_VOICES], &AudioParameters),
+    {
+        let num_unison_voices = self.num_unison_voices();
+
+        for voice in self.unison_voices[..num_unison_voices - 1].iter_mut() {
+            if !voice.active {
+                // Start from same state as monophonic voice, e.g. in case
+                // unison voice count was raised while note was sounding
+                *voice = self.monophonic_voice.clone();
+            }
+
+            f(voice, &mut self.fading_voices, &self.parameters);
+
+            #[cfg(feature = "clap")]
+            {
+                // Note end is only reported for monophonic voice
+                voice.clap_note_id = None;
+            }
+        }
+
+        f(
+            &mut self.monophonic_voice,
+            &mut self.fading_voices,
+            &self.parameters,
+        );
     }
 
     #[cfg(feature = "vst2")]
@@ -158,6 +689,10 @@ impl AudioState {
 
         a.sort_unstable_by_key(|e| e.delta_frames);
         b.sort_unstable_by_key(|e| e.delta_frames);
+
+        for timbre in self.timbres.iter_mut() {
+            timbre.sort_note_events();
+        }
     }
 
     fn process_events_for_sample(&mut self, buffer_offset: usize) {
@@ -171,13 +706,44 @@ impl AudioState {
                 Some(event_delta_frames) if event_delta_frames == buffer_offset => {
                     let event = self.pending_note_events.pop().unwrap();
 
-                    self.process_note_event(event.event, event_delta_frames);
+                    if self.bypass_active {
+                        self.discard_note_event(event.event, event_delta_frames);
+                    } else {
+                        self.process_note_event(event.event, event_delta_frames);
+                    }
                 }
                 _ => break,
             }
         }
     }
 
+    /// Handle note event received while bypassed. Only tempo changes are
+    /// applied, but hosts are told that notes won't be played.
+    fn discard_note_event(
+        &mut self,
+        event: NoteEventInner,
+        #[cfg_attr(not(feature = "clap"), allow(unused_variables))] sample_index: usize,
+    ) {
+        match event {
+            #[cfg(feature = "clap")]
+            NoteEventInner::ClapNoteOn {
+                key, clap_note_id, ..
+            } => {
+                if let Err(err) = self.clap_ended_notes.push(ClapNoteEnded {
+                    key,
+                    clap_note_id,
+                    sample_index: sample_index as u32,
+                }) {
+                    ::log::error!("clap_ended_notes buffer full, couldn't push {:?}", err);
+                }
+            }
+            NoteEventInner::ClapBpm { bpm } => {
+                self.set_bpm(bpm);
+            }
+            _ => (),
+        }
+    }
+
     fn process_note_event(&mut self, event: NoteEventInner, sample_index: usize) {
         match event {
             NoteEventInner::Midi { mut data } => {
@@ -185,16 +751,46 @@ impl AudioState {
                 data[0] >>= 4;
 
                 match data {
-                    [0b_1000, key, _] => self.key_off(key, sample_index),
-                    [0b_1001, key, 0] => self.key_off(key, sample_index),
+                    [0b_1000, key, velocity] => {
+                        let key = self.quantized_key_off(key);
+
+                        self.key_off(
+                            key,
+                            Some(KeyVelocity::from_midi_velocity(velocity)),
+                            None,
+                            false,
+                            sample_index,
+                        )
+                    }
+                    // Note on with zero velocity carries no release velocity
+                    [0b_1001, key, 0] => {
+                        let key = self.quantized_key_off(key);
+
+                        self.key_off(key, None, None, false, sample_index)
+                    }
                     [0b_1001, key, velocity] => {
-                        self.key_on(key, KeyVelocity::from_midi_velocity(velocity), None)
+                        let key = self.quantized_key_on(key);
+
+                        self.key_on(
+                            key,
+                            KeyVelocity::from_midi_velocity(velocity),
+                            None,
+                            sample_index,
+                        )
                     }
                     [0b_1010, key, pressure] => {
+                        let key = self.quantized_key_off(key);
+
                         self.aftertouch(key, KeyVelocity::from_midi_velocity(pressure));
                     }
+                    [0b_1011, 1, v] => {
+                        self.mod_wheel = v.min(127) as f32 * (1.0 / 127.0);
+                    }
                     [0b_1011, 64, v] => {
-                        self.sustain_pedal_on = v >= 64;
+                        self.sustain_pedal_depth = v.min(127) as f32 * (1.0 / 127.0);
+                    }
+                    [0b_1101, pressure, _] => {
+                        self.channel_aftertouch(KeyVelocity::from_midi_velocity(pressure));
                     }
                     [0b_1110, lsb, msb] => {
                         self.global_pitch_bend.update_from_midi(lsb, msb);
@@ -207,13 +803,29 @@ impl AudioState {
                 velocity,
                 clap_note_id,
             } => {
-                self.key_on(key, KeyVelocity(velocity as f32), Some(clap_note_id));
+                let key = self.quantized_key_on(key);
+
+                self.key_on(
+                    key,
+                    KeyVelocity(velocity as f32),
+                    Some(clap_note_id),
+                    sample_index,
+                );
             }
             NoteEventInner::ClapNotePressure { key, pressure } => {
+                let key = self.quantized_key_off(key);
+
                 self.aftertouch(key, KeyVelocity(pressure as f32));
             }
-            NoteEventInner::ClapNoteOff { key } => {
-                self.key_off(key, sample_index);
+            NoteEventInner::ClapNoteOff { key, clap_note_id } => {
+                let key = key.map(|key| self.quantized_key_off(key));
+
+                self.clap_key_off(key, clap_note_id, false, sample_index);
+            }
+            NoteEventInner::ClapNoteChoke { key, clap_note_id } => {
+                let key = key.map(|key| self.quantized_key_off(key));
+
+                self.clap_key_off(key, clap_note_id, true, sample_index);
             }
             NoteEventInner::ClapBpm { bpm } => {
                 self.set_bpm(bpm);
@@ -221,11 +833,59 @@ impl AudioState {
         }
     }
 
-    fn key_on(&mut self, key: u8, velocity: KeyVelocity, opt_clap_note_id: Option<i32>) {
+    /// Quantize key to selected scale and remember result for key release
+    fn quantized_key_on(&mut self, key: u8) -> u8 {
+        let key = key.min(127);
+        let scale = self.parameters.scale.get_value();
+        let root = self.parameters.scale_root.get_value();
+
+        let quantized_key = scale.quantize(root, key);
+
+        self.quantized_keys[key as usize] = quantized_key;
+
+        quantized_key
+    }
+
+    fn quantized_key_off(&self, key: u8) -> u8 {
+        self.quantized_keys[key.min(127) as usize]
+    }
+
+    fn key_on(
+        &mut self,
+        key: u8,
+        velocity: KeyVelocity,
+        opt_clap_note_id: Option<i32>,
+        #[cfg_attr(not(feature = "clap"), allow(unused_variables))] sample_index: usize,
+    ) {
+        let key_range_low = self.parameters.key_range_low.get_value();
+        let key_range_high = self.parameters.key_range_high.get_value();
+        let channel_pressure = self.channel_pressure;
+
+        if !(key_range_low..=key_range_high).contains(&key) {
+            // Let host know right away that note won't be played
+            #[cfg(feature = "clap")]
+            if let Some(clap_note_id) = opt_clap_note_id {
+                if let Err(err) = self.clap_ended_notes.push(ClapNoteEnded {
+                    key,
+                    clap_note_id,
+                    sample_index: sample_index as u32,
+                }) {
+                    ::log::error!("clap_ended_notes buffer full, couldn't push {:?}", err);
+                }
+            }
+
+            return;
+        }
+
         let voice_mode = self.parameters.voice_mode.get_value();
         let glide_active = self.parameters.glide_active.get_value();
         let glide_retrigger = self.parameters.glide_retrigger.get_value();
 
+        let lfo_retriggers = self.lfo_retriggers();
+        let lfo_phases = self.last_voice_lfo_phases(voice_mode);
+
+        self.restart_global_lfos(lfo_retriggers);
+
         match voice_mode {
             VoiceMode::Polyphonic => {
                 let mut most_recent_still_pressed_keys = self
@@ -249,19 +909,35 @@ impl AudioState {
                     }
                 };
 
-                let voice = if let Some(voice) = self.polyphonic_voices.shift_remove(&key) {
+                let voice = if let Some(mut voice) = self.polyphonic_voices.shift_remove(&key) {
+                    let stack = self.sustain_pedal_depth > 0.0
+                        && !voice.key_pressed
+                        && self.sustain_repress_mode == SustainRepressMode::Stack;
+
+                    if stack {
+                        Self::stack_voice(&mut self.fading_voices, &mut voice);
+                    } else {
+                        Self::steal_voice(&mut self.fading_voices, &mut voice);
+                    }
+
                     // Shift voice to last position (most recently pressed)
                     self.polyphonic_voices.entry(key).or_insert(voice)
                 } else {
-                    self.polyphonic_voices
-                        .entry(key)
-                        .or_insert(Voice::new(MidiPitch::new(key), false))
+                    self.polyphonic_voices.entry(key).or_insert(Voice::new(
+                        MidiPitch::new(key, &self.parameters.key_tuning),
+                        false,
+                    ))
                 };
 
                 if let Some(glide_from_key) = opt_glide_from_key {
                     let glide = VoiceGlide {
                         to_key: key,
-                        time: Self::glide_time(&self.parameters, self.bpm, glide_from_key, key),
+                        time_factor: Self::glide_time_factor(
+                            &self.parameters,
+                            self.bpm,
+                            glide_from_key,
+                            key,
+                        ),
                         retrigger_envelopes: true,
                         retrigger_lfos: true,
                     };
@@ -269,6 +945,7 @@ impl AudioState {
                     voice.press_key(
                         &self.parameters,
                         velocity,
+                        channel_pressure,
                         Some(glide_from_key),
                         Some(glide),
                         opt_clap_note_id,
@@ -277,53 +954,87 @@ impl AudioState {
                     voice.press_key(
                         &self.parameters,
                         velocity,
+                        channel_pressure,
                         Some(key),
                         None,
                         opt_clap_note_id,
                     );
                 }
             }
-            VoiceMode::Monophonic => {
-                self.monophonic_pressed_keys.shift_remove(&key);
-                self.monophonic_pressed_keys.insert(key, opt_clap_note_id);
+            VoiceMode::Monophonic | VoiceMode::FatMono => {
+                let priority = self.parameters.mono_note_priority.get_value();
+
+                self.monophonic_note_stack.press(HeldNote {
+                    key,
+                    velocity,
+                    clap_note_id: opt_clap_note_id,
+                });
+
+                // Key is held but doesn't have priority over currently
+                // sounding key
+                if self
+                    .monophonic_note_stack
+                    .current(priority)
+                    .map(|note| note.key != key)
+                    .unwrap_or(false)
+                {
+                    return;
+                }
 
                 if glide_active == GlideActive::Off || !self.monophonic_voice.active {
-                    self.monophonic_voice.press_key(
-                        &self.parameters,
-                        velocity,
-                        Some(key),
-                        None,
-                        opt_clap_note_id,
-                    );
+                    let steal = self.monophonic_voice.key() != key;
+
+                    self.for_each_monophonic_voice(|voice, fading_voices, parameters| {
+                        if steal {
+                            Self::steal_voice(fading_voices, voice);
+                        }
+
+                        voice.press_key(
+                            parameters,
+                            velocity,
+                            channel_pressure,
+                            Some(key),
+                            None,
+                            opt_clap_note_id,
+                        );
+                    });
                 } else if self.monophonic_voice.key() == key {
                     // mono_voice is active and for current key: retrigger key, but don't
                     // force an initial key in case there are previous glides
-                    self.monophonic_voice.press_key(
-                        &self.parameters,
-                        velocity,
-                        None,
-                        None,
-                        opt_clap_note_id,
-                    )
+                    self.for_each_monophonic_voice(|voice, _, parameters| {
+                        voice.press_key(
+                            parameters,
+                            velocity,
+                            channel_pressure,
+                            None,
+                            None,
+                            opt_clap_note_id,
+                        )
+                    });
                 } else if !self.monophonic_voice.key_pressed {
                     // mono voice is active for another key, but in release stage
 
                     if glide_active == GlideActive::Legato {
                         // trigger key press for voice with new key without glide
-                        self.monophonic_voice.press_key(
-                            &self.parameters,
-                            velocity,
-                            Some(key),
-                            None,
-                            opt_clap_note_id,
-                        )
+                        self.for_each_monophonic_voice(|voice, fading_voices, parameters| {
+                            Self::steal_voice(fading_voices, voice);
+
+                            voice.press_key(
+                                parameters,
+                                velocity,
+                                channel_pressure,
+                                Some(key),
+                                None,
+                                opt_clap_note_id,
+                            )
+                        });
                     } else {
                         // in always glide mode: glide to new key and retrigger
                         // envelopes since voice is in release phase
 
                         let glide = VoiceGlide {
                             to_key: key,
-                            time: Self::glide_time(
+                            time_factor: Self::glide_time_factor(
                                 &self.parameters,
                                 self.bpm,
                                 self.monophonic_voice.key(),
@@ -333,13 +1044,16 @@ impl AudioState {
                             retrigger_lfos: glide_retrigger,
                         };
 
-                        self.monophonic_voice.press_key(
-                            &self.parameters,
-                            velocity,
-                            None,
-                            Some(glide),
-                            opt_clap_note_id,
-                        )
+                        self.for_each_monophonic_voice(|voice, _, parameters| {
+                            voice.press_key(
+                                parameters,
+                                velocity,
+                                channel_pressure,
+                                None,
+                                Some(glide),
+                                opt_clap_note_id,
+                            )
+                        });
                     }
                 } else {
                     // mono_voice is active for a different key and is in
@@ -350,7 +1064,7 @@ impl AudioState {
 
                     let glide = VoiceGlide {
                         to_key: key,
-                        time: Self::glide_time(
+                        time_factor: Self::glide_time_factor(
                             &self.parameters,
                             self.bpm,
                             self.monophonic_voice.key(),
@@ -360,79 +1074,311 @@ impl AudioState {
                         retrigger_lfos: glide_retrigger,
                     };
 
-                    self.monophonic_voice.press_key(
-                        &self.parameters,
-                        velocity,
-                        None,
-                        Some(glide),
-                        opt_clap_note_id,
-                    )
+                    self.for_each_monophonic_voice(|voice, _, parameters| {
+                        voice.press_key(
+                            parameters,
+                            velocity,
+                            channel_pressure,
+                            None,
+                            Some(glide),
+                            opt_clap_note_id,
+                        )
+                    });
                 }
             }
         }
+
+        self.continue_lfo_phases(key, voice_mode, lfo_retriggers, lfo_phases);
     }
 
-    fn key_off(
-        &mut self,
-        key: u8,
-        #[cfg_attr(not(feature = "clap"), allow(unused_variables))] sample_index: usize,
+    /// Hand over sound of active voice that is about to be restarted to a
+    /// quickly fading copy. If too many copies are already fading out, voice
+    /// is instead restarted with a short fade from its current volume.
+    fn steal_voice(fading_voices: &mut [Voice; MAX_FADING_VOICES], voice: &mut Voice) {
+        if !voice.active {
+            return;
+        }
+
+        if let Some(slot) = fading_voices.iter_mut().find(|v| !v.active) {
+            *slot = voice.steal();
+        }
+    }
+
+    /// Leave sound of sustained voice that is about to be restarted to a
+    /// copy that keeps ringing until released. If too many copies are
+    /// already sounding, voice is simply restarted.
+    fn stack_voice(fading_voices: &mut [Voice; MAX_FADING_VOICES], voice: &mut Voice) {
+        if !voice.active {
+            return;
+        }
+
+        if let Some(slot) = fading_voices.iter_mut().find(|v| !v.active) {
+            *slot = voice.split_off_sustained();
+        }
+    }
+
+    /// Start global LFOs if they are not yet running. If key sync is on,
+    /// restart them when no other keys are pressed, or for LFOs with
+    /// retrigger modes, when their mode says so.
+    fn restart_global_lfos(&mut self, lfo_retriggers: [bool; NUM_LFOS]) {
+        let any_key_pressed = self.polyphonic_voices.values().any(|v| v.key_pressed)
+            || !self.monophonic_note_stack.is_empty();
+
+        for ((lfo, parameters), retrigger) in self
+            .global_lfos
+            .iter_mut()
+            .zip(self.parameters.lfos.iter())
+            .zip(lfo_retriggers)
+        {
+            if !parameters.global.get_value() {
+                continue;
+            }
+
+            let retrigger = match parameters.mode.get_value() {
+                LfoMode::Once | LfoMode::Forever => !any_key_pressed,
+                _ => retrigger,
+            };
+
+            if lfo.is_stopped() || (retrigger && parameters.key_sync.get_value()) {
+                lfo.restart(parameters);
+            }
+        }
+    }
+
+    /// Count note for each LFO and return whether it should be retriggered
+    /// by it according to its mode. Counting restarts when no voices are
+    /// active.
+    fn lfo_retriggers(&mut self) -> [bool; NUM_LFOS] {
+        let any_voice_active =
+            self.polyphonic_voices.values().any(|v| v.active) || self.monophonic_voice.active;
+
+        ::std::array::from_fn(|lfo_index| {
+            let counter = &mut self.lfo_note_counters[lfo_index];
+
+            if !any_voice_active {
+                *counter = 0;
+            }
+
+            let retrigger = self.parameters.lfos[lfo_index]
+                .mode
+                .get_value()
+                .retrigger(*counter);
+
+            *counter = counter.wrapping_add(1);
+
+            retrigger
+        })
+    }
+
+    /// Phases of running voice LFOs of most recently pressed active voice
+    fn last_voice_lfo_phases(&self, voice_mode: VoiceMode) -> [Option<Phase>; NUM_LFOS] {
+        let opt_voice = match voice_mode {
+            VoiceMode::Polyphonic => self.polyphonic_voices.values().rev().find(|v| v.active),
+            VoiceMode::Monophonic | VoiceMode::FatMono => {
+                Some(&self.monophonic_voice).filter(|v| v.active)
+            }
+        };
+
+        ::std::array::from_fn(|lfo_index| {
+            opt_voice
+                .map(|voice| &voice.lfos[lfo_index])
+                .filter(|lfo| !lfo.is_stopped())
+                .map(|lfo| lfo.get_phase())
+        })
+    }
+
+    /// Let LFOs of pressed voice that should not be retriggered continue
+    /// from phases of previously pressed voice
+    fn continue_lfo_phases(
+        &mut self,
+        key: u8,
+        voice_mode: VoiceMode,
+        lfo_retriggers: [bool; NUM_LFOS],
+        lfo_phases: [Option<Phase>; NUM_LFOS],
+    ) {
+        let continue_phases = |voice: &mut Voice| {
+            for ((lfo, retrigger), opt_phase) in
+                voice.lfos.iter_mut().zip(lfo_retriggers).zip(lfo_phases)
+            {
+                if let (false, Some(phase)) = (retrigger, opt_phase) {
+                    lfo.set_phase(phase);
+                }
+            }
+        };
+
+        match voice_mode {
+            VoiceMode::Polyphonic => {
+                if let Some(voice) = self.polyphonic_voices.get_mut(&key) {
+                    continue_phases(voice);
+                }
+            }
+            VoiceMode::Monophonic | VoiceMode::FatMono => {
+                self.for_each_monophonic_voice(|voice, _, _| continue_phases(voice));
+            }
+        }
+    }
+
+    /// Handle CLAP note off / choke events, which may use wildcard keys
+    fn clap_key_off(
+        &mut self,
+        opt_key: Option<u8>,
+        opt_clap_note_id: Option<i32>,
+        choke: bool,
+        sample_index: usize,
+    ) {
+        if let Some(key) = opt_key {
+            self.key_off(key, None, opt_clap_note_id, choke, sample_index);
+        } else {
+            // Release currently sounding monophonic key last to avoid
+            // unnecessary glides
+            let opt_last_key = self
+                .monophonic_note_stack
+                .current(self.parameters.mono_note_priority.get_value())
+                .map(|note| note.key);
+
+            for key in 0..128 {
+                if Some(key) != opt_last_key {
+                    self.key_off(key, None, opt_clap_note_id, choke, sample_index);
+                }
+            }
+
+            if let Some(key) = opt_last_key {
+                self.key_off(key, None, opt_clap_note_id, choke, sample_index);
+            }
+        }
+    }
+
+    /// Release key. Release velocity, if set, scales release durations
+    /// according to release velocity sensitivity. If CLAP note id is set,
+    /// only release matching notes. If choke is set, stop voice immediately.
+    fn key_off(
+        &mut self,
+        key: u8,
+        opt_release_velocity: Option<KeyVelocity>,
+        opt_clap_note_id: Option<i32>,
+        choke: bool,
+        #[cfg_attr(not(feature = "clap"), allow(unused_variables))] sample_index: usize,
     ) {
         let voice_mode = self.parameters.voice_mode.get_value();
+        let channel_pressure = self.channel_pressure;
         let glide_mode = self.parameters.glide_active.get_value();
         let glide_retrigger = self.parameters.glide_retrigger.get_value();
 
+        let release_duration_factor = match opt_release_velocity {
+            Some(velocity) => ReleaseVelocitySensitivityValue::new_from_audio(
+                self.parameters.release_velocity_sensitivity.get_value(),
+            )
+            .release_duration_factor(velocity.0),
+            None => 1.0,
+        };
+
+        // Copies of stolen or stacked voices keep note id of the note they
+        // were playing. They are already released, but may still need to be
+        // choked.
+        if choke || opt_clap_note_id.is_some() {
+            for voice in self
+                .fading_voices
+                .iter_mut()
+                .filter(|v| v.active && v.key() == key)
+            {
+                if voice.matches_clap_note_id(opt_clap_note_id) {
+                    voice.release_key(release_duration_factor);
+
+                    if choke {
+                        voice.kill_envelopes();
+                    }
+                }
+            }
+        }
+
         match voice_mode {
             VoiceMode::Polyphonic => {
                 if let Some(voice) = self.polyphonic_voices.get_mut(&key) {
-                    voice.release_key();
+                    if voice.matches_clap_note_id(opt_clap_note_id) {
+                        voice.release_key(release_duration_factor);
+
+                        if choke {
+                            voice.kill_envelopes();
+                        }
+                    }
                 }
             }
-            VoiceMode::Monophonic => {
-                let key_was_most_recently_pressed = self
-                    .monophonic_pressed_keys
-                    .last()
-                    .map(|(k, _)| *k == key)
-                    .unwrap_or(false);
+            VoiceMode::Monophonic | VoiceMode::FatMono => {
+                if let Some(clap_note_id) = opt_clap_note_id {
+                    let matches = self
+                        .monophonic_note_stack
+                        .get(key)
+                        .map(|note| note.clap_note_id == Some(clap_note_id))
+                        .unwrap_or(false);
+
+                    if !matches {
+                        // Key might have been released already, but voice
+                        // could still be in release phase
+                        if choke
+                            && self.monophonic_voice.key() == key
+                            && self.monophonic_voice.matches_clap_note_id(opt_clap_note_id)
+                        {
+                            self.for_each_monophonic_voice(|voice, _, _| voice.kill_envelopes());
+                        }
 
-                #[cfg_attr(not(feature = "clap"), allow(unused_variables))]
-                let opt_removed_clap_note_id =
-                    self.monophonic_pressed_keys.shift_remove(&key).flatten();
+                        return;
+                    }
+                }
+
+                let priority = self.parameters.mono_note_priority.get_value();
 
-                if key_was_most_recently_pressed {
-                    if let Some(next_most_recently_pressed_key) =
-                        self.monophonic_pressed_keys.last().map(|(k, _)| *k)
-                    {
-                        // FIXME: maybe previous velocity should be stored in pressed_keys?
-                        let current_velocity = self.monophonic_voice.get_key_velocity();
+                let key_was_sounding = self
+                    .monophonic_note_stack
+                    .current(priority)
+                    .map(|note| note.key == key)
+                    .unwrap_or(false);
 
+                #[cfg_attr(not(feature = "clap"), allow(unused_variables))]
+                let opt_removed_clap_note_id = self
+                    .monophonic_note_stack
+                    .release(key)
+                    .and_then(|note| note.clap_note_id);
+
+                if key_was_sounding {
+                    // Return to the held note that now has priority, using
+                    // the velocity it was originally pressed with
+                    if let Some(next) = self.monophonic_note_stack.current(priority) {
                         if let GlideActive::Off = glide_mode {
-                            self.monophonic_voice.press_key(
-                                &self.parameters,
-                                current_velocity,
-                                Some(next_most_recently_pressed_key),
-                                None,
-                                opt_removed_clap_note_id,
-                            );
+                            self.for_each_monophonic_voice(|voice, fading_voices, parameters| {
+                                Self::steal_voice(fading_voices, voice);
+
+                                voice.press_key(
+                                    parameters,
+                                    next.velocity,
+                                    channel_pressure,
+                                    Some(next.key),
+                                    None,
+                                    next.clap_note_id,
+                                );
+                            });
                         } else {
                             let glide = VoiceGlide {
-                                to_key: next_most_recently_pressed_key,
-                                time: Self::glide_time(
+                                to_key: next.key,
+                                time_factor: Self::glide_time_factor(
                                     &self.parameters,
                                     self.bpm,
                                     key,
-                                    next_most_recently_pressed_key,
+                                    next.key,
                                 ),
                                 retrigger_envelopes: glide_retrigger,
                                 retrigger_lfos: glide_retrigger,
                             };
 
-                            self.monophonic_voice.press_key(
-                                &self.parameters,
-                                current_velocity,
-                                None,
-                                Some(glide),
-                                opt_removed_clap_note_id,
-                            );
+                            self.for_each_monophonic_voice(|voice, _, parameters| {
+                                voice.press_key(
+                                    parameters,
+                                    next.velocity,
+                                    channel_pressure,
+                                    None,
+                                    Some(glide),
+                                    next.clap_note_id,
+                                );
+                            });
                         };
 
                         #[cfg(feature = "clap")]
@@ -449,19 +1395,47 @@ impl AudioState {
                             }
                         }
                     } else {
-                        self.monophonic_voice.release_key();
+                        self.for_each_monophonic_voice(|voice, _, _| {
+                            voice.release_key(release_duration_factor);
+
+                            if choke {
+                                voice.kill_envelopes();
+                            }
+                        });
                     }
+                } else if choke
+                    && self.monophonic_voice.key() == key
+                    && !self.monophonic_voice.key_pressed
+                {
+                    self.for_each_monophonic_voice(|voice, _, _| voice.kill_envelopes());
                 }
             }
         }
     }
 
-    #[allow(unused_variables)]
-    fn aftertouch(&mut self, key: u8, velocity: KeyVelocity) {
-        // Disabled for now
-        // if let Some(voice) = self.voices.get_mut(&key) {
-        //     voice.aftertouch(velocity);
-        // }
+    /// Set polyphonic key pressure, used for aftertouch vibrato
+    fn aftertouch(&mut self, key: u8, pressure: KeyVelocity) {
+        if let Some(voice) = self.polyphonic_voices.get_mut(&key) {
+            voice.aftertouch(pressure);
+        }
+
+        if self.monophonic_voice.active && self.monophonic_voice.key() == key {
+            self.for_each_monophonic_voice(|voice, _, _| voice.aftertouch(pressure));
+        }
+    }
+
+    /// Set channel pressure, which applies to all voices, including ones
+    /// started while it is held
+    fn channel_aftertouch(&mut self, pressure: KeyVelocity) {
+        self.channel_pressure = pressure;
+
+        for voice in self.polyphonic_voices.values_mut() {
+            voice.aftertouch(pressure);
+        }
+
+        if self.monophonic_voice.active {
+            self.for_each_monophonic_voice(|voice, _, _| voice.aftertouch(pressure));
+        }
     }
 
     #[cfg(test)]
@@ -471,33 +1445,45 @@ impl AudioState {
             .unwrap()
     }
 
-    fn glide_time(
+    /// Multiplier for glide time parameter value. Glide time itself is
+    /// applied per sample, since it can be modulated.
+    fn glide_time_factor(
         parameters: &AudioParameters,
         bpm: BeatsPerMinute,
         from_key: u8,
         to_key: u8,
     ) -> f64 {
-        let mut glide_time = parameters.glide_time.get_value() as f64;
+        let mut factor = 1.0;
 
         if parameters.glide_bpm_sync.get_value() {
-            glide_time *= 120.0 / bpm.0;
+            factor *= 120.0 / bpm.0;
         }
         if let GlideMode::Lcr = parameters.glide_mode.get_value() {
-            glide_time *= (from_key as f64 - to_key as f64).abs() * (1.0 / 12.0);
+            factor *= (from_key as f64 - to_key as f64).abs() * (1.0 / 12.0);
         }
 
-        glide_time
+        factor
     }
 }
 
+/// Below this difference, smoothed pitch bend is set to latest value
+const PITCH_BEND_SMOOTHING_THRESHOLD: f32 = 0.00001;
+
 #[derive(Clone, Copy, Debug)]
 pub struct GlobalPitchBend {
+    /// Latest value received over MIDI, in range -1.0 to 1.0
     factor: f32,
+    /// Value approaching factor exponentially with pitch bend smoothing time
+    /// constant
+    smoothed_factor: f32,
 }
 
 impl Default for GlobalPitchBend {
     fn default() -> Self {
-        Self { factor: 0.0 }
+        Self {
+            factor: 0.0,
+            smoothed_factor: 0.0,
+        }
     }
 }
 
@@ -518,20 +1504,57 @@ impl GlobalPitchBend {
 
         self.factor = x;
     }
+    /// Move smoothed value towards latest value. Smoothing time is in
+    /// milliseconds, with zero meaning no smoothing.
+    #[allow(clippy::float_cmp)]
+    pub fn advance_one_sample(&mut self, smoothing_time: f32, time_per_sample: TimePerSample) {
+        let difference = self.factor - self.smoothed_factor;
+
+        if difference == 0.0 {
+            return;
+        }
+
+        if smoothing_time <= 0.0 || difference.abs() < PITCH_BEND_SMOOTHING_THRESHOLD {
+            self.smoothed_factor = self.factor;
+        } else {
+            let coefficient = 1.0 - (-(time_per_sample.0 as f32) * 1000.0 / smoothing_time).exp();
+
+            self.smoothed_factor += difference * coefficient;
+        }
+    }
     pub fn as_frequency_multiplier(&self, range_up: f32, range_down: f32) -> f64 {
-        let semitone_range = if self.factor >= 0.0 {
+        let semitone_range = if self.smoothed_factor >= 0.0 {
             range_up
         } else {
             -range_down
         };
 
-        crate::math::exp2_fast(self.factor * semitone_range * (1.0 / 12.0)).into()
+        crate::math::exp2_fast(self.smoothed_factor * semitone_range * (1.0 / 12.0)).into()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::GlobalPitchBend;
+    use crate::parameters::{
+        bypass::BypassValue,
+        key_range::{KeyRangeHighValue, KeyRangeLowValue},
+        lfo_mode::LfoMode,
+        unison::UnisonVoicesValue,
+        voice_mode::{VoiceMode, VoiceModeValue},
+        LfoGlobalValue, LfoKeySyncValue, LfoModeValue, LfoParameter, MasterParameter,
+        MasterVolumeValue, OperatorParameter, Parameter, ParameterValue,
+    };
+
+    use crate::audio::gen::process_f32_runtime_select;
+    use crate::audio::parameters::common::AudioParameter;
+    use crate::common::{NoteEvent, NoteEventInner, Phase, SampleRate, TimePerSample};
+    use crate::sync::SyncState;
+    use crate::utils::update_audio_parameters;
+
+    use super::{
+        AudioSettings, AudioState, GlobalPitchBend, KeyVelocity, SustainRepressMode,
+        MAX_FADING_VOICES,
+    };
 
     #[test]
     fn test_global_pitch_bend_from_midi() {
@@ -546,4 +1569,715 @@ mod tests {
         pitch_bend.update_from_midi(127, 127);
         assert_eq!(pitch_bend.factor, 1.0);
     }
+
+    #[test]
+    fn test_mod_wheel_from_midi() {
+        let mut audio = AudioState::default();
+
+        assert_eq!(audio.mod_wheel, 0.0);
+
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0b_1011_0000, 1, 127],
+            },
+            0,
+        );
+        assert_eq!(audio.mod_wheel, 1.0);
+
+        // Other controllers don't affect mod wheel position
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0b_1011_0000, 2, 0],
+            },
+            0,
+        );
+        assert_eq!(audio.mod_wheel, 1.0);
+    }
+
+    #[test]
+    fn test_global_pitch_bend_smoothing() {
+        let time_per_sample = TimePerSample(1.0 / 44100.0);
+        let mut pitch_bend = GlobalPitchBend::default();
+
+        pitch_bend.update_from_midi(127, 127);
+        pitch_bend.advance_one_sample(0.0, time_per_sample);
+        assert_eq!(pitch_bend.smoothed_factor, 1.0);
+
+        pitch_bend.update_from_midi(0, 64);
+        pitch_bend.advance_one_sample(10.0, time_per_sample);
+        assert!(pitch_bend.smoothed_factor < 1.0 && pitch_bend.smoothed_factor > 0.9);
+
+        // Roughly 63% of the way after one time constant
+        for _ in 1..441 {
+            pitch_bend.advance_one_sample(10.0, time_per_sample);
+        }
+        assert!((pitch_bend.smoothed_factor - (-1.0f32).exp()).abs() < 0.01);
+
+        for _ in 0..44100 {
+            pitch_bend.advance_one_sample(10.0, time_per_sample);
+        }
+        assert_eq!(pitch_bend.smoothed_factor, 0.0);
+    }
+
+    #[test]
+    fn test_fat_mono_unison_voices() {
+        let mut audio = AudioState::default();
+
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::VoiceMode),
+            VoiceModeValue::new_from_audio(VoiceMode::FatMono).to_patch(),
+        );
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::UnisonVoices),
+            UnisonVoicesValue::new_from_audio(3).to_patch(),
+        );
+        audio.advance_one_sample();
+
+        audio.key_on(60, KeyVelocity::default(), None, 0);
+        audio.key_on(64, KeyVelocity::default(), None, 0);
+
+        assert!(audio.monophonic_voice.active);
+        assert!(audio.polyphonic_voices.is_empty());
+
+        for (i, voice) in audio.unison_voices.iter().enumerate() {
+            assert_eq!(voice.active, i < 2);
+
+            if voice.active {
+                assert_eq!(voice.key(), 64);
+                assert!(voice.key_pressed);
+            }
+        }
+
+        audio.key_off(64, None, None, false, 0);
+
+        assert_eq!(audio.monophonic_voice.key(), 60);
+        assert_eq!(audio.unison_voices[0].key(), 60);
+        assert_eq!(audio.unison_voices[1].key(), 60);
+
+        audio.key_off(60, None, None, false, 0);
+
+        assert!(!audio.monophonic_voice.key_pressed);
+        assert!(!audio.unison_voices[0].key_pressed);
+        assert!(!audio.unison_voices[1].key_pressed);
+
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::VoiceMode),
+            VoiceModeValue::new_from_audio(VoiceMode::Monophonic).to_patch(),
+        );
+        audio.advance_one_sample();
+
+        assert!(audio.unison_voices.iter().all(|v| !v.active));
+    }
+
+    #[test]
+    fn test_key_range() {
+        let mut audio = AudioState::default();
+
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::KeyRangeLow),
+            KeyRangeLowValue::new_from_audio(48).to_patch(),
+        );
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::KeyRangeHigh),
+            KeyRangeHighValue::new_from_audio(59).to_patch(),
+        );
+
+        audio.key_on(47, KeyVelocity::default(), None, 0);
+        audio.key_on(60, KeyVelocity::default(), None, 0);
+
+        assert!(audio.polyphonic_voices.is_empty());
+
+        audio.key_on(48, KeyVelocity::default(), None, 0);
+        audio.key_on(59, KeyVelocity::default(), None, 0);
+
+        assert_eq!(audio.polyphonic_voices.len(), 2);
+    }
+
+    #[test]
+    fn test_key_tuning() {
+        let sync = SyncState::<()>::new(None);
+        let mut audio = AudioState::default();
+
+        let frequency_factor =
+            |audio: &AudioState, key| audio.polyphonic_voices[&key].midi_pitch.get_frequency(1.0);
+        let equal_temperament = |key: u8| ((f64::from(key) - 69.0) / 12.0).exp2();
+
+        sync.patches.set_key_detune_from_gui(60, 100.0);
+        assert!(sync.patches.set_key_detune_text_from_gui(62, "440 Hz"));
+        assert!(!sync.patches.set_key_detune_text_from_gui(64, "x"));
+
+        update_audio_parameters(&mut audio, &sync);
+
+        for key in [60, 62, 64] {
+            audio.key_on(key, KeyVelocity::default(), None, 0);
+        }
+
+        assert!((frequency_factor(&audio, 60) - equal_temperament(61)).abs() < 1e-9);
+        assert!((frequency_factor(&audio, 62) - 1.0).abs() < 1e-6);
+        assert!((frequency_factor(&audio, 64) - equal_temperament(64)).abs() < 1e-9);
+
+        // Patch without detuned keys
+        sync.patches.set_patch_index(1);
+
+        update_audio_parameters(&mut audio, &sync);
+
+        audio.key_on(60, KeyVelocity::default(), None, 0);
+
+        assert!((frequency_factor(&audio, 60) - equal_temperament(60)).abs() < 1e-9);
+
+        sync.patches.set_patch_index(0);
+        sync.patches.reset_key_tuning_from_gui();
+
+        update_audio_parameters(&mut audio, &sync);
+
+        audio.key_on(62, KeyVelocity::default(), None, 0);
+
+        assert!((frequency_factor(&audio, 62) - equal_temperament(62)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_channel_aftertouch_vibrato() {
+        let sample_rate = SampleRate::default();
+        let time_per_sample = sample_rate.into();
+
+        let channel_pressure = |audio: &mut AudioState, pressure| {
+            audio.process_note_event(
+                NoteEventInner::Midi {
+                    data: [0b_1101_0000, pressure, 0],
+                },
+                0,
+            );
+        };
+        let has_vibrato = |audio: &mut AudioState, key| {
+            let voice = audio.polyphonic_voices.get_mut(&key).unwrap();
+
+            let mut min = f64::MAX;
+            let mut max = f64::MIN;
+
+            for _ in 0..sample_rate.0 as usize {
+                voice.advance_interpolators_one_sample(sample_rate);
+
+                let multiplier = voice.advance_aftertouch_vibrato(100.0, 5.0, time_per_sample);
+
+                min = min.min(multiplier);
+                max = max.max(multiplier);
+            }
+
+            if max > 1.05 && min < 0.95 {
+                true
+            } else {
+                assert_eq!((min, max), (1.0, 1.0));
+
+                false
+            }
+        };
+
+        let mut audio = AudioState::default();
+
+        audio.key_on(60, KeyVelocity::default(), None, 0);
+        channel_pressure(&mut audio, 127);
+        // Note started while channel pressure is held picks it up
+        audio.key_on(64, KeyVelocity::default(), None, 0);
+
+        assert!(has_vibrato(&mut audio, 60));
+        assert!(has_vibrato(&mut audio, 64));
+
+        channel_pressure(&mut audio, 0);
+        audio.key_on(67, KeyVelocity::default(), None, 0);
+
+        assert!(!has_vibrato(&mut audio, 67));
+    }
+
+    #[cfg(feature = "clap")]
+    fn clap_note_on(audio: &mut AudioState, key: u8, clap_note_id: i32) {
+        audio.process_note_event(
+            NoteEventInner::ClapNoteOn {
+                key,
+                velocity: 1.0,
+                clap_note_id,
+            },
+            0,
+        );
+    }
+
+    #[cfg(feature = "clap")]
+    fn fading_voice_stages(audio: &AudioState) -> Vec<crate::common::EnvelopeStage> {
+        audio
+            .fading_voices
+            .iter()
+            .filter(|v| v.active)
+            .map(|v| v.operators[0].volume_envelope.get_stage())
+            .collect()
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn test_clap_note_off_by_note_id() {
+        let mut audio = AudioState::default();
+
+        // Pressing sounding key again hands over its sound to stolen copy
+        clap_note_on(&mut audio, 60, 1);
+        clap_note_on(&mut audio, 60, 2);
+
+        assert_eq!(fading_voice_stages(&audio).len(), 1);
+
+        audio.process_note_event(
+            NoteEventInner::ClapNoteOff {
+                key: Some(60),
+                clap_note_id: Some(1),
+            },
+            0,
+        );
+
+        assert!(audio.polyphonic_voices[&60].key_pressed);
+
+        audio.process_note_event(
+            NoteEventInner::ClapNoteOff {
+                key: Some(60),
+                clap_note_id: Some(2),
+            },
+            0,
+        );
+
+        assert!(!audio.polyphonic_voices[&60].key_pressed);
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn test_clap_note_choke_of_fading_voices() {
+        use crate::common::EnvelopeStage;
+
+        let mut audio = AudioState::default();
+
+        audio.apply_settings(&AudioSettings {
+            sustain_repress_mode: super::SustainRepressMode::Stack,
+            ..Default::default()
+        });
+
+        // Sustained note 1 is stacked when key is pressed again
+        clap_note_on(&mut audio, 60, 1);
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0b_1011_0000, 64, 127],
+            },
+            0,
+        );
+        audio.process_note_event(
+            NoteEventInner::ClapNoteOff {
+                key: Some(60),
+                clap_note_id: Some(1),
+            },
+            0,
+        );
+        clap_note_on(&mut audio, 60, 2);
+
+        // Note 2 is stolen when key is pressed again
+        clap_note_on(&mut audio, 60, 3);
+
+        assert_eq!(fading_voice_stages(&audio).len(), 2);
+        assert!(!fading_voice_stages(&audio).contains(&EnvelopeStage::Kill));
+
+        for clap_note_id in [1, 2] {
+            audio.process_note_event(
+                NoteEventInner::ClapNoteChoke {
+                    key: Some(60),
+                    clap_note_id: Some(clap_note_id),
+                },
+                0,
+            );
+        }
+
+        assert_eq!(
+            fading_voice_stages(&audio),
+            [EnvelopeStage::Kill, EnvelopeStage::Kill]
+        );
+        assert_ne!(
+            audio.polyphonic_voices[&60].operators[0]
+                .volume_envelope
+                .get_stage(),
+            EnvelopeStage::Kill
+        );
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn test_clap_note_wildcard_note_id() {
+        use crate::common::EnvelopeStage;
+
+        let mut audio = AudioState::default();
+
+        clap_note_on(&mut audio, 60, 1);
+        clap_note_on(&mut audio, 60, 2);
+        clap_note_on(&mut audio, 64, 3);
+
+        // Wildcard note id (-1 from host) releases voice regardless of id
+        audio.process_note_event(
+            NoteEventInner::ClapNoteOff {
+                key: Some(60),
+                clap_note_id: None,
+            },
+            0,
+        );
+
+        assert!(!audio.polyphonic_voices[&60].key_pressed);
+        assert!(audio.polyphonic_voices[&64].key_pressed);
+
+        // Wildcard key and note id chokes all voices, including copies
+        audio.process_note_event(
+            NoteEventInner::ClapNoteChoke {
+                key: None,
+                clap_note_id: None,
+            },
+            0,
+        );
+
+        assert!(audio
+            .polyphonic_voices
+            .values()
+            .chain(audio.fading_voices.iter().filter(|v| v.active))
+            .all(|v| v.operators[0].volume_envelope.get_stage() == EnvelopeStage::Kill));
+    }
+
+    #[test]
+    fn test_lfo_retrigger_every_second_note() {
+        let mut audio = AudioState::default();
+
+        audio.set_parameter_from_patch(
+            Parameter::Lfo(0, LfoParameter::Mode),
+            LfoModeValue::new_from_audio(LfoMode::EverySecondNote).to_patch(),
+        );
+        audio.advance_one_sample();
+
+        audio.key_on(60, KeyVelocity::default(), None, 0);
+
+        let lfo_phase =
+            |audio: &AudioState, key| audio.polyphonic_voices[&key].lfos[0].get_phase().0;
+
+        assert_eq!(lfo_phase(&audio, 60), 0.0);
+
+        audio.polyphonic_voices.get_mut(&60).unwrap().lfos[0].set_phase(Phase(0.25));
+
+        // Second note continues from phase of first note
+        audio.key_on(62, KeyVelocity::default(), None, 0);
+
+        assert_eq!(lfo_phase(&audio, 62), 0.25);
+
+        // Third note is retriggered, since key sync is on by default
+        audio.key_on(64, KeyVelocity::default(), None, 0);
+
+        assert_eq!(lfo_phase(&audio, 64), 0.0);
+
+        // Other LFOs are retriggered on every note
+        assert_eq!(audio.polyphonic_voices[&62].lfos[1].get_phase().0, 0.0);
+    }
+
+    #[test]
+    fn test_global_lfo_restart() {
+        let mut audio = AudioState::default();
+
+        let set_lfo_parameter = |audio: &mut AudioState, parameter, value| {
+            audio.set_parameter_from_patch(Parameter::Lfo(0, parameter), value);
+            audio.advance_one_sample();
+        };
+        let global_lfo_phase = |audio: &AudioState| audio.global_lfos[0].get_phase().0;
+
+        // Global LFO is not started when global mode is off
+        audio.key_on(60, KeyVelocity::default(), None, 0);
+        audio.key_off(60, None, None, false, 0);
+
+        assert!(audio.global_lfos.iter().all(|lfo| lfo.is_stopped()));
+
+        set_lfo_parameter(
+            &mut audio,
+            LfoParameter::Global,
+            LfoGlobalValue::new_from_audio(true).to_patch(),
+        );
+
+        audio.key_on(60, KeyVelocity::default(), None, 0);
+
+        assert!(!audio.global_lfos[0].is_stopped());
+        assert!(audio.global_lfos[1].is_stopped());
+        assert_eq!(global_lfo_phase(&audio), 0.0);
+
+        audio.global_lfos[0].set_phase(Phase(0.25));
+
+        // Keys pressed while another one is held don't restart it
+        audio.key_on(62, KeyVelocity::default(), None, 0);
+
+        assert_eq!(global_lfo_phase(&audio), 0.25);
+
+        // First key pressed after all have been released restarts it, since
+        // key sync is on by default
+        audio.key_off(60, None, None, false, 0);
+        audio.key_off(62, None, None, false, 0);
+        audio.key_on(64, KeyVelocity::default(), None, 0);
+
+        assert_eq!(global_lfo_phase(&audio), 0.0);
+
+        audio.global_lfos[0].set_phase(Phase(0.25));
+        audio.key_off(64, None, None, false, 0);
+
+        set_lfo_parameter(
+            &mut audio,
+            LfoParameter::KeySync,
+            LfoKeySyncValue::new_from_audio(false).to_patch(),
+        );
+
+        // Without key sync, a running global LFO is never restarted
+        audio.key_on(65, KeyVelocity::default(), None, 0);
+
+        assert_eq!(global_lfo_phase(&audio), 0.25);
+    }
+
+    #[test]
+    fn test_sustain_pedal_settings() {
+        use crate::common::EnvelopeStage;
+
+        fn process(audio: &mut AudioState, events: &[[u8; 3]]) {
+            for data in events.iter().copied() {
+                audio.enqueue_note_event(NoteEvent {
+                    delta_frames: 0,
+                    event: NoteEventInner::Midi { data },
+                });
+            }
+
+            for _ in 0..8 {
+                let mut lefts = [0.0f32; 512];
+                let mut rights = [0.0f32; 512];
+
+                process_f32_runtime_select(audio, &mut lefts, &mut rights, 0, |_| ());
+            }
+        }
+
+        fn new_audio(settings: AudioSettings, voice_mode: VoiceMode) -> AudioState {
+            let mut audio = AudioState::default();
+
+            audio.apply_settings(&settings);
+            audio.set_parameter_from_patch(
+                Parameter::Master(MasterParameter::VoiceMode),
+                VoiceModeValue::new_from_audio(voice_mode).to_patch(),
+            );
+            audio.advance_one_sample();
+
+            audio
+        }
+
+        let note_on = |key| [0b_1001_0000, key, 100];
+        let note_off = |key| [0b_1000_0000, key, 0];
+        let sustain = |on| [0b_1011_0000, 64, if on { 127 } else { 0 }];
+
+        let fading_voice_stages = |audio: &AudioState| {
+            audio
+                .fading_voices
+                .iter()
+                .filter(|v| v.active)
+                .map(|v| v.operators[0].volume_envelope.get_stage())
+                .collect::<Vec<_>>()
+        };
+
+        // By default, pressing sustained key again quickly fades out its
+        // previous sound
+        let mut audio = new_audio(AudioSettings::default(), VoiceMode::Polyphonic);
+
+        process(
+            &mut audio,
+            &[note_on(60), sustain(true), note_off(60), note_on(60)],
+        );
+
+        assert!(fading_voice_stages(&audio).is_empty());
+
+        // When stacking, previous sound keeps ringing while pedal is held
+        let settings = AudioSettings {
+            sustain_repress_mode: SustainRepressMode::Stack,
+            ..Default::default()
+        };
+
+        let mut audio = new_audio(settings.clone(), VoiceMode::Polyphonic);
+
+        process(
+            &mut audio,
+            &[note_on(60), sustain(true), note_off(60), note_on(60)],
+        );
+
+        assert_eq!(fading_voice_stages(&audio), [EnvelopeStage::Sustain]);
+
+        // Keys still held are restarted as usual
+        process(&mut audio, &[note_on(60)]);
+
+        assert_eq!(fading_voice_stages(&audio), [EnvelopeStage::Sustain]);
+
+        // Stacked sound is released along with pedal
+        process(&mut audio, &[sustain(false)]);
+
+        assert_eq!(fading_voice_stages(&audio), [EnvelopeStage::Release]);
+        assert!(audio.polyphonic_voices[&60].key_pressed);
+
+        // Number of stacked copies is limited
+        let mut audio = new_audio(settings, VoiceMode::Polyphonic);
+
+        process(&mut audio, &[note_on(60), sustain(true)]);
+
+        for _ in 0..MAX_FADING_VOICES + 4 {
+            process(&mut audio, &[note_off(60), note_on(60)]);
+        }
+
+        assert_eq!(fading_voice_stages(&audio).len(), MAX_FADING_VOICES);
+
+        // Sustain pedal can be ignored in monophonic voice mode only
+        for (ignore_sustain_in_mono, voice_mode, expected_stage) in [
+            (false, VoiceMode::Monophonic, EnvelopeStage::Sustain),
+            (true, VoiceMode::Monophonic, EnvelopeStage::Release),
+            (true, VoiceMode::Polyphonic, EnvelopeStage::Sustain),
+        ] {
+            let settings = AudioSettings {
+                ignore_sustain_in_mono,
+                ..Default::default()
+            };
+
+            let mut audio = new_audio(settings, voice_mode);
+
+            process(&mut audio, &[note_on(60), sustain(true), note_off(60)]);
+
+            let voice = if voice_mode == VoiceMode::Monophonic {
+                &audio.monophonic_voice
+            } else {
+                &audio.polyphonic_voices[&60]
+            };
+
+            assert_eq!(
+                voice.operators[0].volume_envelope.get_stage(),
+                expected_stage
+            );
+        }
+    }
+
+    #[test]
+    fn test_bypass_fades_out_and_in() {
+        fn process(audio: &mut AudioState, key: Option<u8>) -> ([f32; 512], [f32; 512]) {
+            if let Some(key) = key {
+                audio.enqueue_note_event(NoteEvent {
+                    delta_frames: 0,
+                    event: NoteEventInner::Midi {
+                        data: [0b_1001_0000, key, 100],
+                    },
+                });
+            }
+
+            let mut lefts = [0.0f32; 512];
+            let mut rights = [0.0f32; 512];
+
+            process_f32_runtime_select(audio, &mut lefts, &mut rights, 0, |_| ());
+
+            audio.apply_bypass_fade(&mut lefts, &mut rights);
+
+            (lefts, rights)
+        }
+
+        fn set_bypass(audio: &mut AudioState, bypass: bool) {
+            audio.set_parameter_from_patch(
+                Parameter::Master(MasterParameter::Bypass),
+                BypassValue::new_from_audio(bypass).to_patch(),
+            );
+        }
+
+        let mut audio = AudioState::default();
+
+        let (lefts, _) = process(&mut audio, Some(60));
+
+        assert!(lefts.iter().any(|s| *s != 0.0));
+
+        set_bypass(&mut audio, true);
+
+        // Fade out is shorter than buffer
+        let (lefts, rights) = process(&mut audio, None);
+
+        assert!(lefts[0] != 0.0);
+        assert_eq!(lefts[511], 0.0);
+        assert_eq!(rights[511], 0.0);
+
+        // Notes are ignored while bypassed
+        let (lefts, rights) = process(&mut audio, Some(64));
+
+        assert!(audio.is_bypassed());
+        assert!(audio.polyphonic_voices.is_empty());
+        assert!(lefts.iter().chain(rights.iter()).all(|s| *s == 0.0));
+
+        set_bypass(&mut audio, false);
+
+        let (lefts, _) = process(&mut audio, Some(64));
+
+        assert!(!audio.is_bypassed());
+        assert!(lefts[1].abs() < lefts[511].abs());
+        assert_eq!(audio.bypass_gain, 1.0);
+    }
+
+    #[test]
+    fn test_patch_crossfade() {
+        let mut audio = AudioState::default();
+
+        audio.apply_settings(&AudioSettings {
+            crossfade_patch_changes: true,
+            ..Default::default()
+        });
+
+        let sample_rate = SampleRate::default();
+        let target = MasterVolumeValue::new_from_audio(0.5);
+
+        audio.set_patch_crossfade_active(true);
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::Volume),
+            target.to_patch(),
+        );
+        audio.set_patch_crossfade_active(false);
+
+        // Regular interpolation would have finished after about 1 ms
+        for _ in 0..(sample_rate.0 * 0.025) as usize {
+            audio.parameters.advance_one_sample(sample_rate);
+        }
+
+        let volume = audio.parameters.master_volume.get_value();
+
+        assert!(volume > target.get() && volume < 1.0);
+
+        for _ in 0..(sample_rate.0 * 0.03) as usize {
+            audio.parameters.advance_one_sample(sample_rate);
+        }
+
+        assert_eq!(audio.parameters.master_volume.get_value(), target.get());
+    }
+
+    #[test]
+    fn test_operator_audition() {
+        fn render(opt_audition_operator: Option<usize>) -> [f32; 512] {
+            let mut audio = AudioState::default();
+
+            // Operator 2 is muted and not mixed to output
+            audio.set_parameter_from_patch(Parameter::Operator(1, OperatorParameter::Active), 0.0);
+            audio.set_parameter_from_patch(Parameter::Operator(1, OperatorParameter::MixOut), 0.0);
+            audio.set_audition_operator(opt_audition_operator);
+
+            audio.enqueue_note_event(NoteEvent {
+                delta_frames: 0,
+                event: NoteEventInner::Midi {
+                    data: [0b_1001_0000, 60, 100],
+                },
+            });
+
+            let mut lefts = [0.0f32; 512];
+            let mut rights = [0.0f32; 512];
+
+            process_f32_runtime_select(&mut audio, &mut lefts, &mut rights, 0, |_| ());
+
+            lefts
+        }
+
+        let normal = render(None);
+        let auditioned = render(Some(1));
+
+        assert!(auditioned.iter().any(|s| s.abs() > 0.001));
+        assert!(normal
+            .iter()
+            .zip(auditioned.iter())
+            .any(|(a, b)| (a - b).abs() > 0.001));
+    }
 }
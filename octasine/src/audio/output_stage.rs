@@ -0,0 +1,282 @@
+use crate::common::SampleRate;
+use crate::parameters::output_mode::OutputMode;
+
+/// Hard clip limit. Only meant to protect against extreme values.
+const HARD_CLIP_LIMIT: f64 = 10.0;
+
+/// Samples exceeding this are reported as clipped
+const FULL_SCALE: f64 = 1.0;
+
+const LIMITER_CEILING: f64 = FULL_SCALE;
+/// Limiter lookahead in seconds
+const LIMITER_LOOKAHEAD: f64 = 0.0015;
+/// Limiter release time constant in seconds
+const LIMITER_RELEASE: f64 = 0.05;
+/// Enough for lookahead at 192 kHz, with some margin
+const LIMITER_MAX_LOOKAHEAD_SAMPLES: usize = 512;
+
+/// Final processing of audio before it is sent to host
+pub struct OutputStage {
+    last_mode: OutputMode,
+    limiter: Limiter,
+}
+
+impl Default for OutputStage {
+    fn default() -> Self {
+        Self {
+            last_mode: OutputMode::default(),
+            limiter: Limiter::new(SampleRate::default()),
+        }
+    }
+}
+
+impl OutputStage {
+    pub fn set_sample_rate(&mut self, sample_rate: SampleRate) {
+        self.limiter = Limiter::new(sample_rate);
+    }
+
+    /// Output latency in samples
+    pub fn get_latency(&self, mode: OutputMode) -> usize {
+        match mode {
+            OutputMode::HardClip | OutputMode::SoftClip => 0,
+            OutputMode::Limiter => self.limiter.latency(),
+        }
+    }
+
+    /// Process samples in place. Returns true if any sample exceeded full
+    /// scale, meaning that clipping or limiting engaged.
+    pub fn process(&mut self, mode: OutputMode, lefts: &mut [f32], rights: &mut [f32]) -> bool {
+        if mode != self.last_mode {
+            self.limiter.reset();
+            self.last_mode = mode;
+        }
+
+        let mut clipped = false;
+
+        for (out_l, out_r) in lefts.iter_mut().zip(rights.iter_mut()) {
+            let (l, r) = (f64::from(*out_l), f64::from(*out_r));
+
+            clipped |= l.abs().max(r.abs()) > FULL_SCALE;
+
+            let (new_l, new_r) = match mode {
+                OutputMode::HardClip => (
+                    l.clamp(-HARD_CLIP_LIMIT, HARD_CLIP_LIMIT),
+                    r.clamp(-HARD_CLIP_LIMIT, HARD_CLIP_LIMIT),
+                ),
+                OutputMode::SoftClip => (l.tanh(), r.tanh()),
+                OutputMode::Limiter => self.limiter.process(l, r),
+            };
+
+            *out_l = new_l as f32;
+            *out_r = new_r as f32;
+        }
+
+        clipped
+    }
+}
+
+/// Stereo-linked lookahead peak limiter
+///
+/// Required gain is held for the lookahead duration and then averaged over
+/// the same duration, guaranteeing that gain is fully reduced by the time the
+/// (delayed) peak reaches the output.
+struct Limiter {
+    lookahead_samples: usize,
+    release_factor: f64,
+    /// Absolute sample counter
+    sample_index: u64,
+    delay_lefts: [f64; LIMITER_MAX_LOOKAHEAD_SAMPLES],
+    delay_rights: [f64; LIMITER_MAX_LOOKAHEAD_SAMPLES],
+    /// Monotonic queue of (sample index, required gain) for windowed minimum
+    min_queue: [(u64, f64); LIMITER_MAX_LOOKAHEAD_SAMPLES],
+    min_queue_start: usize,
+    min_queue_len: usize,
+    held_gain: f64,
+    held_gains: [f64; LIMITER_MAX_LOOKAHEAD_SAMPLES],
+    held_gains_sum: f64,
+}
+
+impl Limiter {
+    fn new(sample_rate: SampleRate) -> Self {
+        let lookahead_samples =
+            ((LIMITER_LOOKAHEAD * sample_rate.0) as usize).clamp(2, LIMITER_MAX_LOOKAHEAD_SAMPLES);
+        let release_factor = 1.0 - (-1.0 / (LIMITER_RELEASE * sample_rate.0)).exp();
+
+        let mut limiter = Self {
+            lookahead_samples,
+            release_factor,
+            sample_index: 0,
+            delay_lefts: [0.0; LIMITER_MAX_LOOKAHEAD_SAMPLES],
+            delay_rights: [0.0; LIMITER_MAX_LOOKAHEAD_SAMPLES],
+            min_queue: [(0, 1.0); LIMITER_MAX_LOOKAHEAD_SAMPLES],
+            min_queue_start: 0,
+            min_queue_len: 0,
+            held_gain: 1.0,
+            held_gains: [1.0; LIMITER_MAX_LOOKAHEAD_SAMPLES],
+            held_gains_sum: 0.0,
+        };
+
+        limiter.reset();
+
+        limiter
+    }
+
+    fn reset(&mut self) {
+        self.sample_index = 0;
+        self.delay_lefts = [0.0; LIMITER_MAX_LOOKAHEAD_SAMPLES];
+        self.delay_rights = [0.0; LIMITER_MAX_LOOKAHEAD_SAMPLES];
+        self.min_queue_start = 0;
+        self.min_queue_len = 0;
+        self.held_gain = 1.0;
+        self.held_gains = [1.0; LIMITER_MAX_LOOKAHEAD_SAMPLES];
+        self.held_gains_sum = self.lookahead_samples as f64;
+    }
+
+    fn latency(&self) -> usize {
+        self.lookahead_samples - 1
+    }
+
+    fn process(&mut self, l: f64, r: f64) -> (f64, f64) {
+        const MAX: usize = LIMITER_MAX_LOOKAHEAD_SAMPLES;
+
+        let index = (self.sample_index % MAX as u64) as usize;
+
+        self.delay_lefts[index] = l;
+        self.delay_rights[index] = r;
+
+        let peak = l.abs().max(r.abs());
+        let required_gain = if peak > LIMITER_CEILING {
+            LIMITER_CEILING / peak
+        } else {
+            1.0
+        };
+
+        let window_min = self.push_to_min_queue(required_gain);
+
+        if window_min < self.held_gain {
+            self.held_gain = window_min;
+        } else {
+            self.held_gain += (window_min - self.held_gain) * self.release_factor;
+        }
+
+        let leaving_index = (index + MAX - self.lookahead_samples) % MAX;
+
+        self.held_gains_sum += self.held_gain - self.held_gains[leaving_index];
+        self.held_gains[index] = self.held_gain;
+
+        let gain = (self.held_gains_sum / self.lookahead_samples as f64).min(1.0);
+
+        let delayed_index = (index + MAX - self.latency()) % MAX;
+
+        self.sample_index += 1;
+
+        (
+            self.delay_lefts[delayed_index] * gain,
+            self.delay_rights[delayed_index] * gain,
+        )
+    }
+
+    /// Push required gain and return minimum over lookahead window
+    fn push_to_min_queue(&mut self, required_gain: f64) -> f64 {
+        const MAX: usize = LIMITER_MAX_LOOKAHEAD_SAMPLES;
+
+        while self.min_queue_len > 0 {
+            let back = (self.min_queue_start + self.min_queue_len - 1) % MAX;
+
+            if self.min_queue[back].1 >= required_gain {
+                self.min_queue_len -= 1;
+            } else {
+                break;
+            }
+        }
+
+        let back = (self.min_queue_start + self.min_queue_len) % MAX;
+
+        self.min_queue[back] = (self.sample_index, required_gain);
+        self.min_queue_len += 1;
+
+        while self.min_queue[self.min_queue_start].0 + (self.lookahead_samples as u64)
+            <= self.sample_index
+        {
+            self.min_queue_start = (self.min_queue_start + 1) % MAX;
+            self.min_queue_len -= 1;
+        }
+
+        self.min_queue[self.min_queue_start].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_output_below_ceiling() {
+        let mut stage = OutputStage::default();
+
+        let mut lefts = [0.0f32; 4096];
+        let mut rights = [0.0f32; 4096];
+
+        for (i, (l, r)) in lefts.iter_mut().zip(rights.iter_mut()).enumerate() {
+            let v = (i as f32 * 0.05).sin() * 4.0;
+
+            *l = v;
+            *r = -v * 0.5;
+        }
+
+        stage.process(OutputMode::Limiter, &mut lefts, &mut rights);
+
+        for sample in lefts.iter().chain(rights.iter()) {
+            assert!(sample.abs() <= LIMITER_CEILING as f32 + 0.0001);
+        }
+        assert!(lefts.iter().any(|s| s.abs() > 0.5));
+    }
+
+    #[test]
+    fn test_limiter_passes_quiet_audio_with_latency() {
+        let mut stage = OutputStage::default();
+
+        let latency = stage.get_latency(OutputMode::Limiter);
+
+        let input: Vec<f32> = (0..1024).map(|i| (i as f32 * 0.1).sin() * 0.5).collect();
+
+        let mut lefts = input.clone();
+        let mut rights = input.clone();
+
+        stage.process(OutputMode::Limiter, &mut lefts, &mut rights);
+
+        for (output, input) in lefts[latency..].iter().zip(input.iter()) {
+            assert!((output - input).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_soft_clip_bounded() {
+        let mut stage = OutputStage::default();
+
+        let mut lefts = [100.0f32, -100.0, 0.0];
+        let mut rights = [0.5f32, -0.5, 0.0];
+
+        stage.process(OutputMode::SoftClip, &mut lefts, &mut rights);
+
+        for sample in lefts.iter().chain(rights.iter()) {
+            assert!(sample.abs() <= 1.0);
+        }
+        assert_eq!(lefts[2], 0.0);
+    }
+
+    #[test]
+    fn test_clipping_reported() {
+        let mut stage = OutputStage::default();
+
+        let mut lefts = [0.5f32, -0.9, 0.0];
+        let mut rights = [0.5f32, 0.9, 1.0];
+
+        assert!(!stage.process(OutputMode::HardClip, &mut lefts, &mut rights));
+
+        let mut lefts = [0.5f32, -1.5, 0.0];
+        let mut rights = [0.5f32, 0.9, 0.0];
+
+        assert!(stage.process(OutputMode::SoftClip, &mut lefts, &mut rights));
+    }
+}
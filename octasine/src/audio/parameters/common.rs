@@ -1,6 +1,8 @@
 use std::marker::PhantomData;
 
-use crate::audio::interpolation::{InterpolationDuration, Interpolator};
+use crate::audio::interpolation::{
+    InterpolationCurve, InterpolationDuration, Interpolator, ParameterSmoothing,
+};
 use crate::common::SampleRate;
 use crate::parameters::*;
 
@@ -16,9 +18,24 @@ pub trait AudioParameter {
         lfo_addition: Option<f32>,
     ) -> <Self::ParameterValue as ParameterValue>::Value;
 
+    /// Scale interpolation duration, if any
+    fn set_smoothing(&mut self, _smoothing: ParameterSmoothing) {}
+    /// Set interpolation curve of volume-like parameters
+    fn set_level_smoothing_curve(&mut self, _curve: InterpolationCurve) {}
+    /// Lengthen interpolations started while set, if any
+    fn set_min_interpolation_duration(&mut self, _duration: Option<InterpolationDuration>) {}
+
     fn get_parameter_value(&self) -> Self::ParameterValue {
         Self::ParameterValue::new_from_audio(self.get_value())
     }
+
+    #[cfg(test)]
+    fn compare_patch_value(&self, value: f32) -> bool {
+        let a = Self::ParameterValue::new_from_patch(value).to_patch();
+        let b = self.get_parameter_value().to_patch();
+
+        (a - b).abs() <= 1.0 / 100_000.0
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +75,13 @@ where
     fn set_from_patch(&mut self, value: f32) {
         self.interpolator.set_value(V::new_from_patch(value).get())
     }
+    fn set_smoothing(&mut self, smoothing: ParameterSmoothing) {
+        self.interpolator
+            .set_duration_factor(smoothing.duration_factor())
+    }
+    fn set_min_interpolation_duration(&mut self, duration: Option<InterpolationDuration>) {
+        self.interpolator.set_min_duration(duration)
+    }
     fn get_value_with_lfo_addition(
         &mut self,
         lfo_addition: Option<f32>,
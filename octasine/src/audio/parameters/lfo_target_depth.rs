@@ -0,0 +1,54 @@
+use crate::audio::interpolation::{InterpolationDuration, Interpolator, ParameterSmoothing};
+use crate::common::SampleRate;
+use crate::parameters::{LfoTargetDepthValue, ParameterValue};
+
+use super::common::AudioParameter;
+
+/// Interpolator only supports non-negative values, so interpolate depth
+/// offset by this amount
+const OFFSET: f32 = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct LfoTargetDepthAudioParameter(Interpolator);
+
+impl Default for LfoTargetDepthAudioParameter {
+    fn default() -> Self {
+        Self(Interpolator::new(
+            LfoTargetDepthValue::default().get() + OFFSET,
+            InterpolationDuration::approx_1ms(),
+        ))
+    }
+}
+
+impl AudioParameter for LfoTargetDepthAudioParameter {
+    type ParameterValue = LfoTargetDepthValue;
+
+    fn advance_one_sample(&mut self, sample_rate: SampleRate) {
+        self.0.advance_one_sample(sample_rate, &mut |_| ())
+    }
+    fn get_value(&self) -> <Self::ParameterValue as ParameterValue>::Value {
+        self.0.get_value() - OFFSET
+    }
+    fn set_from_patch(&mut self, value: f32) {
+        self.0
+            .set_value(Self::ParameterValue::new_from_patch(value).get() + OFFSET)
+    }
+    fn set_smoothing(&mut self, smoothing: ParameterSmoothing) {
+        self.0.set_duration_factor(smoothing.duration_factor())
+    }
+    fn set_min_interpolation_duration(&mut self, duration: Option<InterpolationDuration>) {
+        self.0.set_min_duration(duration)
+    }
+    fn get_value_with_lfo_addition(
+        &mut self,
+        lfo_addition: Option<f32>,
+    ) -> <Self::ParameterValue as ParameterValue>::Value {
+        if let Some(lfo_addition) = lfo_addition {
+            let patch_value = Self::ParameterValue::new_from_audio(self.get_value()).to_patch();
+
+            Self::ParameterValue::new_from_patch((patch_value + lfo_addition).clamp(0.0, 1.0)).get()
+        } else {
+            self.get_value()
+        }
+    }
+}
@@ -3,12 +3,15 @@ mod lfo_active;
 mod lfo_amount;
 mod lfo_frequency_free;
 mod lfo_target;
+mod lfo_target_depth;
 mod master_frequency;
+mod master_pan;
 mod master_volume;
 mod operator_active;
 mod operator_frequency_fine;
 mod operator_frequency_free;
 mod operator_mix;
+mod operator_mod_out;
 mod operator_mod_target;
 mod operator_panning;
 mod operator_sustain_volume;
@@ -16,16 +19,41 @@ mod operator_volume;
 
 use array_init::array_init;
 
-use crate::common::{SampleRate, NUM_LFOS, NUM_OPERATORS};
+use crate::audio::interpolation::{InterpolationCurve, InterpolationDuration, ParameterSmoothing};
+use crate::audio::voices::KeyTuning;
+use crate::common::{SampleRate, NUM_LFOS, NUM_LFO_TARGETS, NUM_OPERATORS};
+use crate::parameters::aftertouch_vibrato::{
+    AftertouchVibratoDepthValue, AftertouchVibratoRateValue,
+};
+use crate::parameters::bypass::BypassValue;
+use crate::parameters::dc_blocker::DcBlockerValue;
+use crate::parameters::effect_order::EffectOrderValue;
+use crate::parameters::gain_compensation::GainCompensationValue;
 use crate::parameters::glide_active::GlideActiveValue;
 use crate::parameters::glide_bpm_sync::GlideBpmSyncValue;
 use crate::parameters::glide_mode::GlideModeValue;
 use crate::parameters::glide_retrigger::GlideRetriggerValue;
 use crate::parameters::glide_time::GlideTimeValue;
+use crate::parameters::key_pan::KeyPanValue;
+use crate::parameters::key_range::{KeyRangeHighValue, KeyRangeLowValue};
+use crate::parameters::master_eq::{
+    MasterEqBypassValue, MasterEqGainValue, MasterEqHighFrequencyValue, MasterEqLowFrequencyValue,
+    MasterEqMidFrequencyValue,
+};
 use crate::parameters::master_pitch_bend_range::{
     MasterPitchBendRangeDownValue, MasterPitchBendRangeUpValue,
 };
-use crate::parameters::velocity_sensitivity::VelocitySensitivityValue;
+use crate::parameters::mod_out_scaling::{ModOutScaling, ModOutScalingValue};
+use crate::parameters::mono_note_priority::MonoNotePriorityValue;
+use crate::parameters::output_mode::OutputModeValue;
+use crate::parameters::pitch_bend_smoothing::PitchBendSmoothingValue;
+use crate::parameters::scale_quantization::{ScaleRootValue, ScaleValue};
+use crate::parameters::unison::{UnisonDetuneValue, UnisonSpreadValue, UnisonVoicesValue};
+use crate::parameters::velocity_mod::{VelocityModAmountValue, VelocityModTargetValue};
+use crate::parameters::velocity_sensitivity::{
+    AttackVelocitySensitivityValue, MixOutVelocitySensitivityValue,
+    ReleaseVelocitySensitivityValue, VelocitySensitivityValue,
+};
 use crate::parameters::voice_mode::VoiceModeValue;
 use crate::parameters::*;
 
@@ -34,18 +62,26 @@ use self::lfo_active::LfoActiveAudioParameter;
 use self::lfo_amount::LfoAmountAudioParameter;
 use self::lfo_frequency_free::LfoFrequencyFreeAudioParameter;
 use self::lfo_target::LfoTargetAudioParameter;
+use self::lfo_target_depth::LfoTargetDepthAudioParameter;
 use self::master_frequency::MasterFrequencyAudioParameter;
+use self::master_pan::MasterPanAudioParameter;
 use self::master_volume::MasterVolumeAudioParameter;
 use self::operator_frequency_fine::OperatorFrequencyFineAudioParameter;
 use self::operator_frequency_free::OperatorFrequencyFreeAudioParameter;
 use self::operator_mix::OperatorMixAudioParameter;
-use self::operator_mod_target::OperatorModulationTargetAudioParameter;
+use self::operator_mod_out::OperatorModOutAudioParameter;
+use self::operator_mod_target::{
+    OperatorBackwardModulationTargetAudioParameter, OperatorModulationTargetAudioParameter,
+};
 use self::operator_panning::OperatorPanningAudioParameter;
 use self::operator_sustain_volume::OperatorSustainVolumeAudioParameter;
 use self::operator_volume::OperatorVolumeAudioParameter;
 
 trait AudioParameterPatchInteraction {
     fn set_patch_value(&mut self, value: f32);
+    fn set_smoothing(&mut self, _smoothing: ParameterSmoothing) {}
+    fn set_level_smoothing_curve(&mut self, _curve: InterpolationCurve) {}
+    fn set_min_interpolation_duration(&mut self, _duration: Option<InterpolationDuration>) {}
     #[cfg(test)]
     fn compare_patch_value(&mut self, value: f32) -> bool;
 }
@@ -54,18 +90,25 @@ impl<P: AudioParameter> AudioParameterPatchInteraction for P {
     fn set_patch_value(&mut self, value: f32) {
         self.set_from_patch(value)
     }
+    fn set_smoothing(&mut self, smoothing: ParameterSmoothing) {
+        AudioParameter::set_smoothing(self, smoothing)
+    }
+    fn set_level_smoothing_curve(&mut self, curve: InterpolationCurve) {
+        AudioParameter::set_level_smoothing_curve(self, curve)
+    }
+    fn set_min_interpolation_duration(&mut self, duration: Option<InterpolationDuration>) {
+        AudioParameter::set_min_interpolation_duration(self, duration)
+    }
     #[cfg(test)]
     fn compare_patch_value(&mut self, value: f32) -> bool {
-        let a = P::ParameterValue::new_from_patch(value).to_patch();
-        let b = self.get_parameter_value().to_patch();
-
-        (a - b).abs() <= 1.0 / 100_000.0
+        AudioParameter::compare_patch_value(self, value)
     }
 }
 
 pub struct AudioParameters {
     pub master_volume: MasterVolumeAudioParameter,
     pub master_frequency: MasterFrequencyAudioParameter,
+    pub master_pan: MasterPanAudioParameter,
     pub master_pitch_bend_range_up: SimpleAudioParameter<MasterPitchBendRangeUpValue>,
     pub master_pitch_bend_range_down: SimpleAudioParameter<MasterPitchBendRangeDownValue>,
     pub volume_velocity_sensitivity: InterpolatableAudioParameter<VelocitySensitivityValue>,
@@ -75,8 +118,43 @@ pub struct AudioParameters {
     pub glide_bpm_sync: SimpleAudioParameter<GlideBpmSyncValue>,
     pub glide_mode: SimpleAudioParameter<GlideModeValue>,
     pub glide_retrigger: SimpleAudioParameter<GlideRetriggerValue>,
+    pub output_mode: SimpleAudioParameter<OutputModeValue>,
+    pub dc_blocker: SimpleAudioParameter<DcBlockerValue>,
+    pub mod_out_scaling: SimpleAudioParameter<ModOutScalingValue>,
+    pub velocity_mod_target: SimpleAudioParameter<VelocityModTargetValue>,
+    pub velocity_mod_amount: SimpleAudioParameter<VelocityModAmountValue>,
+    pub scale: SimpleAudioParameter<ScaleValue>,
+    pub scale_root: SimpleAudioParameter<ScaleRootValue>,
+    pub mono_note_priority: SimpleAudioParameter<MonoNotePriorityValue>,
+    pub unison_voices: SimpleAudioParameter<UnisonVoicesValue>,
+    pub unison_detune: InterpolatableAudioParameter<UnisonDetuneValue>,
+    pub unison_spread: InterpolatableAudioParameter<UnisonSpreadValue>,
+    pub key_range_low: SimpleAudioParameter<KeyRangeLowValue>,
+    pub key_range_high: SimpleAudioParameter<KeyRangeHighValue>,
+    pub aftertouch_vibrato_depth: InterpolatableAudioParameter<AftertouchVibratoDepthValue>,
+    pub aftertouch_vibrato_rate: InterpolatableAudioParameter<AftertouchVibratoRateValue>,
+    pub bypass: SimpleAudioParameter<BypassValue>,
+    pub eq_low_gain: InterpolatableAudioParameter<MasterEqGainValue>,
+    pub eq_low_frequency: InterpolatableAudioParameter<MasterEqLowFrequencyValue>,
+    pub eq_mid_gain: InterpolatableAudioParameter<MasterEqGainValue>,
+    pub eq_mid_frequency: InterpolatableAudioParameter<MasterEqMidFrequencyValue>,
+    pub eq_high_gain: InterpolatableAudioParameter<MasterEqGainValue>,
+    pub eq_high_frequency: InterpolatableAudioParameter<MasterEqHighFrequencyValue>,
+    pub eq_bypass: SimpleAudioParameter<MasterEqBypassValue>,
+    pub effect_order: SimpleAudioParameter<EffectOrderValue>,
+    pub key_pan: InterpolatableAudioParameter<KeyPanValue>,
+    pub release_velocity_sensitivity: SimpleAudioParameter<ReleaseVelocitySensitivityValue>,
+    pub gain_compensation: SimpleAudioParameter<GainCompensationValue>,
+    pub pitch_bend_smoothing: SimpleAudioParameter<PitchBendSmoothingValue>,
     pub operators: [OperatorAudioParameters; NUM_OPERATORS],
     pub lfos: [LfoAudioParameters; NUM_LFOS],
+    /// Temporary routing override, not stored in patch: output operator at
+    /// this index directly at a safe level and silence mix outputs of other
+    /// operators, which still modulate as usual
+    pub audition_operator: Option<usize>,
+    /// Per-key detuning from patch. Not a parameter, since it doesn't fit in
+    /// parameter index space.
+    pub key_tuning: KeyTuning,
 }
 
 impl Default for AudioParameters {
@@ -84,6 +162,7 @@ impl Default for AudioParameters {
         Self {
             master_volume: Default::default(),
             master_frequency: Default::default(),
+            master_pan: Default::default(),
             master_pitch_bend_range_up: Default::default(),
             master_pitch_bend_range_down: Default::default(),
             volume_velocity_sensitivity: Default::default(),
@@ -93,8 +172,38 @@ impl Default for AudioParameters {
             glide_bpm_sync: Default::default(),
             glide_mode: Default::default(),
             glide_retrigger: Default::default(),
+            output_mode: Default::default(),
+            dc_blocker: Default::default(),
+            mod_out_scaling: Default::default(),
+            velocity_mod_target: Default::default(),
+            velocity_mod_amount: Default::default(),
+            scale: Default::default(),
+            scale_root: Default::default(),
+            mono_note_priority: Default::default(),
+            unison_voices: Default::default(),
+            unison_detune: Default::default(),
+            unison_spread: Default::default(),
+            key_range_low: Default::default(),
+            key_range_high: Default::default(),
+            aftertouch_vibrato_depth: Default::default(),
+            aftertouch_vibrato_rate: Default::default(),
+            bypass: Default::default(),
+            eq_low_gain: Default::default(),
+            eq_low_frequency: Default::default(),
+            eq_mid_gain: Default::default(),
+            eq_mid_frequency: Default::default(),
+            eq_high_gain: Default::default(),
+            eq_high_frequency: Default::default(),
+            eq_bypass: Default::default(),
+            effect_order: Default::default(),
+            key_pan: Default::default(),
+            release_velocity_sensitivity: Default::default(),
+            gain_compensation: Default::default(),
+            pitch_bend_smoothing: Default::default(),
             operators: array_init(OperatorAudioParameters::new),
             lfos: array_init(LfoAudioParameters::new),
+            audition_operator: None,
+            key_tuning: Default::default(),
         }
     }
 }
@@ -122,6 +231,43 @@ macro_rules! impl_patch_interaction {
                     MasterParameter::GlideBpmSync => $f(&mut self.glide_bpm_sync, input),
                     MasterParameter::GlideMode => $f(&mut self.glide_mode, input),
                     MasterParameter::GlideRetrigger => $f(&mut self.glide_retrigger, input),
+                    MasterParameter::Pan => $f(&mut self.master_pan, input),
+                    MasterParameter::OutputMode => $f(&mut self.output_mode, input),
+                    MasterParameter::DcBlocker => $f(&mut self.dc_blocker, input),
+                    MasterParameter::ModOutScaling => $f(&mut self.mod_out_scaling, input),
+                    MasterParameter::VelocityModTarget => $f(&mut self.velocity_mod_target, input),
+                    MasterParameter::VelocityModAmount => $f(&mut self.velocity_mod_amount, input),
+                    MasterParameter::Scale => $f(&mut self.scale, input),
+                    MasterParameter::ScaleRoot => $f(&mut self.scale_root, input),
+                    MasterParameter::MonoNotePriority => $f(&mut self.mono_note_priority, input),
+                    MasterParameter::UnisonVoices => $f(&mut self.unison_voices, input),
+                    MasterParameter::UnisonDetune => $f(&mut self.unison_detune, input),
+                    MasterParameter::UnisonSpread => $f(&mut self.unison_spread, input),
+                    MasterParameter::KeyRangeLow => $f(&mut self.key_range_low, input),
+                    MasterParameter::KeyRangeHigh => $f(&mut self.key_range_high, input),
+                    MasterParameter::AftertouchVibratoDepth => {
+                        $f(&mut self.aftertouch_vibrato_depth, input)
+                    }
+                    MasterParameter::AftertouchVibratoRate => {
+                        $f(&mut self.aftertouch_vibrato_rate, input)
+                    }
+                    MasterParameter::Bypass => $f(&mut self.bypass, input),
+                    MasterParameter::EqLowGain => $f(&mut self.eq_low_gain, input),
+                    MasterParameter::EqLowFrequency => $f(&mut self.eq_low_frequency, input),
+                    MasterParameter::EqMidGain => $f(&mut self.eq_mid_gain, input),
+                    MasterParameter::EqMidFrequency => $f(&mut self.eq_mid_frequency, input),
+                    MasterParameter::EqHighGain => $f(&mut self.eq_high_gain, input),
+                    MasterParameter::EqHighFrequency => $f(&mut self.eq_high_frequency, input),
+                    MasterParameter::EqBypass => $f(&mut self.eq_bypass, input),
+                    MasterParameter::EffectOrder => $f(&mut self.effect_order, input),
+                    MasterParameter::KeyPan => $f(&mut self.key_pan, input),
+                    MasterParameter::ReleaseVelocitySensitivity => {
+                        $f(&mut self.release_velocity_sensitivity, input)
+                    }
+                    MasterParameter::GainCompensation => $f(&mut self.gain_compensation, input),
+                    MasterParameter::PitchBendSmoothing => {
+                        $f(&mut self.pitch_bend_smoothing, input)
+                    }
                 },
                 Parameter::Operator(index, p) => {
                     use OperatorParameter::*;
@@ -141,8 +287,9 @@ macro_rules! impl_patch_interaction {
                                 None
                             }
                         }
-                        ModOut => {
-                            if let Some(p) = operator.mod_out.as_mut() {
+                        ModOut => $f(&mut operator.mod_out, input),
+                        BackwardModTargets => {
+                            if let Some(p) = &mut operator.backward_mod_targets {
                                 $f(p, input)
                             } else {
                                 None
@@ -165,6 +312,22 @@ macro_rules! impl_patch_interaction {
                         VelocitySensitivityFeedback => {
                             $f(&mut operator.velocity_sensitivity_feedback, input)
                         }
+                        VelocitySensitivityMixOut => {
+                            $f(&mut operator.velocity_sensitivity_mix_out, input)
+                        }
+                        VelocitySensitivityAttack => $f(
+                            &mut operator.volume_envelope.attack_velocity_sensitivity,
+                            input,
+                        ),
+                        FeedbackTone => $f(&mut operator.feedback_tone, input),
+                        OutputBus => $f(&mut operator.output_bus, input),
+                        PanningMode => $f(&mut operator.panning_mode, input),
+                        PanningLfoMode => $f(&mut operator.panning_lfo_mode, input),
+                        KeyScalingBreakpoint => $f(&mut operator.key_scaling_breakpoint, input),
+                        KeyScalingLeftDepth => $f(&mut operator.key_scaling_left_depth, input),
+                        KeyScalingRightDepth => $f(&mut operator.key_scaling_right_depth, input),
+                        KeyScalingLeftCurve => $f(&mut operator.key_scaling_left_curve, input),
+                        KeyScalingRightCurve => $f(&mut operator.key_scaling_right_curve, input),
                     }
                 }
                 Parameter::Lfo(index, p) => {
@@ -180,6 +343,21 @@ macro_rules! impl_patch_interaction {
                         LfoParameter::Amount => $f(&mut lfo.amount, input),
                         LfoParameter::Active => $f(&mut lfo.active, input),
                         LfoParameter::KeySync => $f(&mut lfo.key_sync, input),
+                        LfoParameter::Global => $f(&mut lfo.global, input),
+                        LfoParameter::Smoothing => $f(&mut lfo.smoothing, input),
+                        LfoParameter::Target2 => $f(&mut lfo.additional_targets[0], input),
+                        LfoParameter::Target3 => $f(&mut lfo.additional_targets[1], input),
+                        LfoParameter::Target4 => $f(&mut lfo.additional_targets[2], input),
+                        LfoParameter::Target2Depth => {
+                            $f(&mut lfo.additional_target_depths[0], input)
+                        }
+                        LfoParameter::Target3Depth => {
+                            $f(&mut lfo.additional_target_depths[1], input)
+                        }
+                        LfoParameter::Target4Depth => {
+                            $f(&mut lfo.additional_target_depths[2], input)
+                        }
+                        LfoParameter::ModWheelDepth => $f(&mut lfo.mod_wheel_depth, input),
                     }
                 }
             }
@@ -198,6 +376,36 @@ impl AudioParameters {
         }
     );
 
+    impl_patch_interaction!(
+        set_parameter_smoothing,
+        ParameterSmoothing,
+        (),
+        |p: &mut dyn AudioParameterPatchInteraction, smoothing| {
+            p.set_smoothing(smoothing);
+            Some(())
+        }
+    );
+
+    impl_patch_interaction!(
+        set_parameter_level_smoothing_curve,
+        InterpolationCurve,
+        (),
+        |p: &mut dyn AudioParameterPatchInteraction, curve| {
+            p.set_level_smoothing_curve(curve);
+            Some(())
+        }
+    );
+
+    impl_patch_interaction!(
+        set_parameter_min_interpolation_duration,
+        Option<InterpolationDuration>,
+        (),
+        |p: &mut dyn AudioParameterPatchInteraction, duration| {
+            p.set_min_interpolation_duration(duration);
+            Some(())
+        }
+    );
+
     #[cfg(test)]
     impl_patch_interaction!(
         compare_patch_value,
@@ -206,13 +414,52 @@ impl AudioParameters {
         |p: &mut dyn AudioParameterPatchInteraction, v| Some(p.compare_patch_value(v))
     );
 
+    /// Scale interpolation durations of all parameters
+    pub fn set_smoothing(&mut self, smoothing: ParameterSmoothing) {
+        for parameter in PARAMETERS.iter().copied() {
+            self.set_parameter_smoothing(parameter, smoothing);
+        }
+    }
+
+    /// Set interpolation curve of all volume-like parameters
+    pub fn set_level_smoothing_curve(&mut self, curve: InterpolationCurve) {
+        for parameter in PARAMETERS.iter().copied() {
+            self.set_parameter_level_smoothing_curve(parameter, curve);
+        }
+    }
+
+    /// Set minimum duration of interpolations started while set, e.g., to
+    /// crossfade parameter values when switching patches
+    pub fn set_min_interpolation_duration(&mut self, duration: Option<InterpolationDuration>) {
+        for parameter in PARAMETERS.iter().copied() {
+            self.set_parameter_min_interpolation_duration(parameter, duration);
+        }
+    }
+
     pub fn advance_one_sample(&mut self, sample_rate: SampleRate) {
         self.master_volume.advance_one_sample(sample_rate);
         self.master_frequency.advance_one_sample(sample_rate);
+        self.master_pan.advance_one_sample(sample_rate);
         self.volume_velocity_sensitivity
             .advance_one_sample(sample_rate);
+        self.unison_detune.advance_one_sample(sample_rate);
+        self.unison_spread.advance_one_sample(sample_rate);
+        self.aftertouch_vibrato_depth
+            .advance_one_sample(sample_rate);
+        self.aftertouch_vibrato_rate.advance_one_sample(sample_rate);
+        self.eq_low_gain.advance_one_sample(sample_rate);
+        self.eq_low_frequency.advance_one_sample(sample_rate);
+        self.eq_mid_gain.advance_one_sample(sample_rate);
+        self.eq_mid_frequency.advance_one_sample(sample_rate);
+        self.eq_high_gain.advance_one_sample(sample_rate);
+        self.eq_high_frequency.advance_one_sample(sample_rate);
+        self.key_pan.advance_one_sample(sample_rate);
+
+        let dx7_mod_out_scaling = self.mod_out_scaling.get_value() == ModOutScaling::Dx7;
 
         for operator in self.operators.iter_mut() {
+            operator.mod_out.set_dx7_scaling(dx7_mod_out_scaling);
+
             operator.advance_one_sample(sample_rate);
         }
 
@@ -227,41 +474,59 @@ pub struct OperatorAudioParameters {
     pub wave_type: SimpleAudioParameter<OperatorWaveTypeValue>,
     pub volume: OperatorVolumeAudioParameter,
     pub panning: OperatorPanningAudioParameter,
+    pub panning_mode: SimpleAudioParameter<OperatorPanningModeValue>,
+    pub panning_lfo_mode: SimpleAudioParameter<OperatorPanningLfoModeValue>,
     pub mix_out: OperatorMixAudioParameter,
-    pub mod_out: Option<InterpolatableAudioParameter<OperatorModOutValue>>,
+    pub mod_out: OperatorModOutAudioParameter,
     pub mod_targets: Option<OperatorModulationTargetAudioParameter>,
+    pub backward_mod_targets: Option<OperatorBackwardModulationTargetAudioParameter>,
     pub feedback: InterpolatableAudioParameter<OperatorFeedbackValue>,
+    pub feedback_tone: InterpolatableAudioParameter<OperatorFeedbackToneValue>,
+    pub output_bus: SimpleAudioParameter<OperatorOutputBusValue>,
+    pub key_scaling_breakpoint: SimpleAudioParameter<OperatorKeyScalingBreakpointValue>,
+    pub key_scaling_left_depth: InterpolatableAudioParameter<OperatorKeyScalingDepthValue>,
+    pub key_scaling_right_depth: InterpolatableAudioParameter<OperatorKeyScalingDepthValue>,
+    pub key_scaling_left_curve: SimpleAudioParameter<OperatorKeyScalingCurveValue>,
+    pub key_scaling_right_curve: SimpleAudioParameter<OperatorKeyScalingCurveValue>,
     pub frequency_ratio: SimpleAudioParameter<OperatorFrequencyRatioValue>,
     pub frequency_free: OperatorFrequencyFreeAudioParameter,
     pub frequency_fine: OperatorFrequencyFineAudioParameter,
     pub volume_envelope: OperatorEnvelopeAudioParameters,
     pub velocity_sensitivity_mod_out: InterpolatableAudioParameter<VelocitySensitivityValue>,
     pub velocity_sensitivity_feedback: InterpolatableAudioParameter<VelocitySensitivityValue>,
+    pub velocity_sensitivity_mix_out: InterpolatableAudioParameter<MixOutVelocitySensitivityValue>,
 }
 
 impl OperatorAudioParameters {
     pub fn new(operator_index: usize) -> Self {
-        let modulation_index = if operator_index == 0 {
-            None
-        } else {
-            Some(Default::default())
-        };
-
         Self {
             active: Default::default(),
             wave_type: Default::default(),
             volume: Default::default(),
             panning: OperatorPanningAudioParameter::default(),
+            panning_mode: Default::default(),
+            panning_lfo_mode: Default::default(),
             mix_out: OperatorMixAudioParameter::new(operator_index),
-            mod_out: modulation_index,
+            mod_out: Default::default(),
             mod_targets: OperatorModulationTargetAudioParameter::opt_new(operator_index),
+            backward_mod_targets: OperatorBackwardModulationTargetAudioParameter::opt_new(
+                operator_index,
+            ),
             feedback: Default::default(),
+            feedback_tone: Default::default(),
+            output_bus: Default::default(),
+            key_scaling_breakpoint: Default::default(),
+            key_scaling_left_depth: Default::default(),
+            key_scaling_right_depth: Default::default(),
+            key_scaling_left_curve: Default::default(),
+            key_scaling_right_curve: Default::default(),
             frequency_ratio: Default::default(),
             frequency_free: Default::default(),
             frequency_fine: Default::default(),
             volume_envelope: Default::default(),
             velocity_sensitivity_mod_out: Default::default(),
             velocity_sensitivity_feedback: Default::default(),
+            velocity_sensitivity_mix_out: Default::default(),
         }
     }
 
@@ -270,14 +535,24 @@ impl OperatorAudioParameters {
         self.volume.advance_one_sample(sample_rate);
         self.wave_type.advance_one_sample(sample_rate);
         self.panning.advance_one_sample(sample_rate);
+        self.panning_mode.advance_one_sample(sample_rate);
+        self.panning_lfo_mode.advance_one_sample(sample_rate);
         if let Some(mod_targets) = &mut self.mod_targets {
             mod_targets.advance_one_sample(sample_rate);
         }
-        self.mix_out.advance_one_sample(sample_rate);
-        if let Some(mod_out) = self.mod_out.as_mut() {
-            mod_out.advance_one_sample(sample_rate);
+        if let Some(backward_mod_targets) = &mut self.backward_mod_targets {
+            backward_mod_targets.advance_one_sample(sample_rate);
         }
+        self.mix_out.advance_one_sample(sample_rate);
+        self.mod_out.advance_one_sample(sample_rate);
         self.feedback.advance_one_sample(sample_rate);
+        self.feedback_tone.advance_one_sample(sample_rate);
+        self.output_bus.advance_one_sample(sample_rate);
+        self.key_scaling_breakpoint.advance_one_sample(sample_rate);
+        self.key_scaling_left_depth.advance_one_sample(sample_rate);
+        self.key_scaling_right_depth.advance_one_sample(sample_rate);
+        self.key_scaling_left_curve.advance_one_sample(sample_rate);
+        self.key_scaling_right_curve.advance_one_sample(sample_rate);
         self.frequency_ratio.advance_one_sample(sample_rate);
         self.frequency_free.advance_one_sample(sample_rate);
         self.frequency_fine.advance_one_sample(sample_rate);
@@ -286,6 +561,8 @@ impl OperatorAudioParameters {
             .advance_one_sample(sample_rate);
         self.velocity_sensitivity_feedback
             .advance_one_sample(sample_rate);
+        self.velocity_sensitivity_mix_out
+            .advance_one_sample(sample_rate);
     }
 }
 
@@ -296,6 +573,7 @@ pub struct OperatorEnvelopeAudioParameters {
     pub sustain_volume: OperatorSustainVolumeAudioParameter,
     pub release_duration: SimpleAudioParameter<OperatorReleaseDurationValue>,
     pub lock_group: SimpleAudioParameter<OperatorEnvelopeGroupValue>,
+    pub attack_velocity_sensitivity: SimpleAudioParameter<AttackVelocitySensitivityValue>,
 }
 
 impl OperatorEnvelopeAudioParameters {
@@ -305,6 +583,8 @@ impl OperatorEnvelopeAudioParameters {
         self.sustain_volume.advance_one_sample(sample_rate);
         self.release_duration.advance_one_sample(sample_rate);
         self.lock_group.advance_one_sample(sample_rate);
+        self.attack_velocity_sensitivity
+            .advance_one_sample(sample_rate);
     }
 }
 
@@ -318,6 +598,11 @@ pub struct LfoAudioParameters {
     pub amount: LfoAmountAudioParameter,
     pub active: LfoActiveAudioParameter,
     pub key_sync: SimpleAudioParameter<LfoKeySyncValue>,
+    pub global: SimpleAudioParameter<LfoGlobalValue>,
+    pub smoothing: SimpleAudioParameter<LfoSmoothingValue>,
+    pub additional_targets: [LfoTargetAudioParameter; NUM_LFO_TARGETS - 1],
+    pub additional_target_depths: [LfoTargetDepthAudioParameter; NUM_LFO_TARGETS - 1],
+    pub mod_wheel_depth: InterpolatableAudioParameter<LfoModWheelDepthValue>,
 }
 
 impl LfoAudioParameters {
@@ -332,6 +617,11 @@ impl LfoAudioParameters {
             amount: Default::default(),
             active: Default::default(),
             key_sync: Default::default(),
+            global: Default::default(),
+            smoothing: Default::default(),
+            additional_targets: array_init(|_| LfoTargetAudioParameter::new(lfo_index)),
+            additional_target_depths: Default::default(),
+            mod_wheel_depth: Default::default(),
         }
     }
 
@@ -344,6 +634,14 @@ impl LfoAudioParameters {
         self.shape.advance_one_sample(sample_rate);
         self.amount.advance_one_sample(sample_rate);
         self.active.advance_one_sample(sample_rate);
+        self.mod_wheel_depth.advance_one_sample(sample_rate);
+
+        for target in self.additional_targets.iter_mut() {
+            target.advance_one_sample(sample_rate);
+        }
+        for depth in self.additional_target_depths.iter_mut() {
+            depth.advance_one_sample(sample_rate);
+        }
     }
 }
 
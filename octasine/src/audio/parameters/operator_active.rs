@@ -1,4 +1,4 @@
-use crate::audio::interpolation::{InterpolationDuration, Interpolator};
+use crate::audio::interpolation::{InterpolationDuration, Interpolator, ParameterSmoothing};
 use crate::common::SampleRate;
 use crate::parameters::{OperatorActiveValue, ParameterValue};
 
@@ -29,6 +29,12 @@ impl AudioParameter for OperatorActiveAudioParameter {
         self.0
             .set_value(Self::ParameterValue::new_from_patch(value).get())
     }
+    fn set_smoothing(&mut self, smoothing: ParameterSmoothing) {
+        self.0.set_duration_factor(smoothing.duration_factor())
+    }
+    fn set_min_interpolation_duration(&mut self, duration: Option<InterpolationDuration>) {
+        self.0.set_min_duration(duration)
+    }
     fn get_value_with_lfo_addition(
         &mut self,
         _lfo_addition: Option<f32>,
@@ -1,4 +1,6 @@
-use crate::audio::interpolation::{InterpolationDuration, Interpolator};
+use crate::audio::interpolation::{
+    InterpolationCurve, InterpolationDuration, Interpolator, ParameterSmoothing,
+};
 use crate::common::SampleRate;
 use crate::parameters::{OperatorMixOutValue, ParameterValue};
 
@@ -29,6 +31,15 @@ impl AudioParameter for OperatorMixAudioParameter {
         self.0
             .set_value(Self::ParameterValue::new_from_patch(value).get())
     }
+    fn set_smoothing(&mut self, smoothing: ParameterSmoothing) {
+        self.0.set_duration_factor(smoothing.duration_factor())
+    }
+    fn set_min_interpolation_duration(&mut self, duration: Option<InterpolationDuration>) {
+        self.0.set_min_duration(duration)
+    }
+    fn set_level_smoothing_curve(&mut self, curve: InterpolationCurve) {
+        self.0.set_curve(curve)
+    }
     fn get_value_with_lfo_addition(
         &mut self,
         lfo_addition: Option<f32>,
@@ -1,6 +1,8 @@
 use crate::common::SampleRate;
 use crate::parameters::{
-    ModTargetStorage, Operator2ModulationTargetValue, Operator3ModulationTargetValue,
+    ModTargetStorage, Operator1BackwardModulationTargetValue,
+    Operator2BackwardModulationTargetValue, Operator2ModulationTargetValue,
+    Operator3BackwardModulationTargetValue, Operator3ModulationTargetValue,
     Operator4ModulationTargetValue,
 };
 
@@ -74,3 +76,71 @@ impl AudioParameterPatchInteraction for OperatorModulationTargetAudioParameter {
         a == b
     }
 }
+
+pub enum OperatorBackwardModulationTargetAudioParameter {
+    One(SimpleAudioParameter<Operator1BackwardModulationTargetValue>),
+    Two(SimpleAudioParameter<Operator2BackwardModulationTargetValue>),
+    Three(SimpleAudioParameter<Operator3BackwardModulationTargetValue>),
+}
+
+impl OperatorBackwardModulationTargetAudioParameter {
+    pub fn opt_new(operator_index: usize) -> Option<Self> {
+        match operator_index {
+            0 => Some(Self::One(Default::default())),
+            1 => Some(Self::Two(Default::default())),
+            2 => Some(Self::Three(Default::default())),
+            _ => None,
+        }
+    }
+
+    pub fn get_value(&self) -> ModTargetStorage {
+        match self {
+            Self::One(p) => p.get_value(),
+            Self::Two(p) => p.get_value(),
+            Self::Three(p) => p.get_value(),
+        }
+    }
+
+    pub fn advance_one_sample(&mut self, sample_rate: SampleRate) {
+        match self {
+            Self::One(p) => p.advance_one_sample(sample_rate),
+            Self::Two(p) => p.advance_one_sample(sample_rate),
+            Self::Three(p) => p.advance_one_sample(sample_rate),
+        }
+    }
+}
+
+impl AudioParameterPatchInteraction for OperatorBackwardModulationTargetAudioParameter {
+    fn set_patch_value(&mut self, value: f32) {
+        match self {
+            Self::One(p) => p.set_from_patch(value),
+            Self::Two(p) => p.set_from_patch(value),
+            Self::Three(p) => p.set_from_patch(value),
+        }
+    }
+
+    #[cfg(test)]
+    fn compare_patch_value(&mut self, value: f32) -> bool {
+        use crate::parameters::ParameterValue;
+
+        let a = match self {
+            Self::One(_) => {
+                Operator1BackwardModulationTargetValue::new_from_patch(value).to_patch()
+            }
+            Self::Two(_) => {
+                Operator2BackwardModulationTargetValue::new_from_patch(value).to_patch()
+            }
+            Self::Three(_) => {
+                Operator3BackwardModulationTargetValue::new_from_patch(value).to_patch()
+            }
+        };
+
+        let b = match self {
+            Self::One(p) => p.get_parameter_value().to_patch(),
+            Self::Two(p) => p.get_parameter_value().to_patch(),
+            Self::Three(p) => p.get_parameter_value().to_patch(),
+        };
+
+        a == b
+    }
+}
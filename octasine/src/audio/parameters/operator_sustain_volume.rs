@@ -1,4 +1,6 @@
-use crate::audio::interpolation::{InterpolationDuration, Interpolator};
+use crate::audio::interpolation::{
+    InterpolationCurve, InterpolationDuration, Interpolator, ParameterSmoothing,
+};
 use crate::common::SampleRate;
 use crate::parameters::{OperatorSustainVolumeValue, ParameterValue};
 
@@ -34,6 +36,16 @@ impl AudioParameter for OperatorSustainVolumeAudioParameter {
         self.interpolator
             .set_value(Self::ParameterValue::new_from_patch(value).get())
     }
+    fn set_smoothing(&mut self, smoothing: ParameterSmoothing) {
+        self.interpolator
+            .set_duration_factor(smoothing.duration_factor())
+    }
+    fn set_min_interpolation_duration(&mut self, duration: Option<InterpolationDuration>) {
+        self.interpolator.set_min_duration(duration)
+    }
+    fn set_level_smoothing_curve(&mut self, curve: InterpolationCurve) {
+        self.interpolator.set_curve(curve)
+    }
     fn get_value_with_lfo_addition(&mut self, _lfo_addition: Option<f32>) -> f32 {
         self.get_value()
     }
@@ -0,0 +1,308 @@
+use ringbuf::{HeapConsumer, HeapProducer, HeapRb};
+
+use crate::common::{NoteEvent, NoteEventInner, SampleRate};
+use crate::parameters::Parameter;
+
+use super::gen::process_f32_runtime_select;
+use super::AudioState;
+
+const PREVIEW_KEY: u8 = 60;
+const PREVIEW_VELOCITY: u8 = 100;
+
+/// Time to let parameter interpolators settle before note on (seconds)
+const SETTLE_DURATION: f64 = 0.05;
+/// Time from note on to note off (seconds)
+const NOTE_DURATION: f64 = 1.0;
+/// Total preview duration (seconds)
+const TOTAL_DURATION: f64 = 2.0;
+/// Fade out at end of preview to prevent clicks (seconds)
+const FADE_OUT_DURATION: f64 = 0.05;
+/// Capacity of queues passing previews to audio thread and back
+const QUEUE_CAPACITY: usize = 4;
+
+/// Short note rendered offline with a secondary AudioState, used for
+/// auditioning patches
+pub struct PatchPreview {
+    lefts: Vec<f32>,
+    rights: Vec<f32>,
+}
+
+impl PatchPreview {
+    /// Render preview note. Parameter values are patch values in the same
+    /// order as `PARAMETERS`.
+    pub fn render(parameter_values: &[f32], sample_rate: SampleRate) -> Self {
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(sample_rate);
+
+        for (index, value) in parameter_values.iter().copied().enumerate() {
+            if let Some(parameter) = Parameter::from_index(index) {
+                audio.set_parameter_from_patch(parameter, value);
+            }
+        }
+
+        for _ in 0..(SETTLE_DURATION * sample_rate.0) as usize {
+            audio.advance_one_sample();
+        }
+
+        let num_samples = (TOTAL_DURATION * sample_rate.0) as usize;
+        let note_off_frame = (NOTE_DURATION * sample_rate.0) as u32;
+
+        audio.enqueue_note_events(
+            [
+                NoteEvent {
+                    delta_frames: 0,
+                    event: NoteEventInner::Midi {
+                        data: [0b_1001_0000, PREVIEW_KEY, PREVIEW_VELOCITY],
+                    },
+                },
+                NoteEvent {
+                    delta_frames: note_off_frame,
+                    event: NoteEventInner::Midi {
+                        data: [0b_1000_0000, PREVIEW_KEY, 0],
+                    },
+                },
+            ]
+            .into_iter(),
+        );
+
+        let mut lefts = vec![0.0; num_samples];
+        let mut rights = vec![0.0; num_samples];
+
+        process_f32_runtime_select(&mut audio, &mut lefts, &mut rights, 0, |_| ());
+
+        audio.apply_output_stage(&mut lefts, &mut rights);
+
+        let num_fade_out_samples = ((FADE_OUT_DURATION * sample_rate.0) as usize).min(num_samples);
+        let fade_out_start = num_samples - num_fade_out_samples;
+
+        for (i, (l, r)) in lefts[fade_out_start..]
+            .iter_mut()
+            .zip(rights[fade_out_start..].iter_mut())
+            .enumerate()
+        {
+            let factor = 1.0 - (i as f32 / num_fade_out_samples as f32);
+
+            *l *= factor;
+            *r *= factor;
+        }
+
+        Self { lefts, rights }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lefts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lefts.is_empty()
+    }
+}
+
+/// Create connected worker and audio thread sides of patch preview queues
+pub fn patch_preview_queues() -> (PatchPreviewSender, PatchPreviewPlayer) {
+    let (preview_producer, preview_consumer) = HeapRb::new(QUEUE_CAPACITY).split();
+    let (returned_producer, returned_consumer) = HeapRb::new(QUEUE_CAPACITY).split();
+
+    let sender = PatchPreviewSender {
+        previews: preview_producer,
+        returned: returned_consumer,
+    };
+    let player = PatchPreviewPlayer {
+        previews: preview_consumer,
+        returned: returned_producer,
+        opt_playback: None,
+    };
+
+    (sender, player)
+}
+
+/// Sending side of patch preview queues, owned by preview worker thread.
+/// Previews are handed back once played or replaced, so that they are
+/// dropped here instead of on audio thread.
+pub struct PatchPreviewSender {
+    previews: HeapProducer<Box<PatchPreview>>,
+    returned: HeapConsumer<Box<PatchPreview>>,
+}
+
+impl PatchPreviewSender {
+    /// Queue preview for playback. It is dropped if queue is full, which
+    /// means that audio isn't being processed.
+    pub fn send(&mut self, preview: Box<PatchPreview>) {
+        self.drop_returned();
+
+        if self.previews.push(preview).is_err() {
+            ::log::warn!("Patch preview queue full");
+        }
+    }
+
+    /// Drop previews that audio thread is done with
+    pub fn drop_returned(&mut self) {
+        self.returned.clear();
+    }
+}
+
+/// Receiving side of patch preview queues, owned by audio thread
+pub struct PatchPreviewPlayer {
+    previews: HeapConsumer<Box<PatchPreview>>,
+    returned: HeapProducer<Box<PatchPreview>>,
+    opt_playback: Option<PatchPreviewPlayback>,
+}
+
+impl PatchPreviewPlayer {
+    /// Add next preview samples (if any) to buffers. Newly received
+    /// previews replace the one currently playing.
+    pub fn mix_into(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        // Only take previews while the one they replace can be handed back
+        while !self.returned.is_full() {
+            match self.previews.pop() {
+                Some(preview) => {
+                    let opt_replaced = self
+                        .opt_playback
+                        .replace(PatchPreviewPlayback::new(preview));
+
+                    if let Some(playback) = opt_replaced {
+                        self.return_preview(playback.preview);
+                    }
+                }
+                None => break,
+            }
+        }
+
+        if let Some(playback) = self.opt_playback.as_mut() {
+            playback.mix_into(lefts, rights);
+
+            // If preview can't be handed back yet, keep it around and try
+            // again on next call
+            if playback.is_finished() && !self.returned.is_full() {
+                if let Some(playback) = self.opt_playback.take() {
+                    self.return_preview(playback.preview);
+                }
+            }
+        }
+    }
+
+    fn return_preview(&mut self, preview: Box<PatchPreview>) {
+        // Queue was checked not to be full, so this never fails
+        if let Err(preview) = self.returned.push(preview) {
+            ::std::mem::forget(preview);
+        }
+    }
+}
+
+/// Playback position in a preview
+struct PatchPreviewPlayback {
+    preview: Box<PatchPreview>,
+    position: usize,
+}
+
+impl PatchPreviewPlayback {
+    fn new(preview: Box<PatchPreview>) -> Self {
+        Self {
+            preview,
+            position: 0,
+        }
+    }
+
+    /// Add next preview samples to buffers
+    fn mix_into(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        let end = (self.position + lefts.len()).min(self.preview.len());
+
+        let preview_lefts = &self.preview.lefts[self.position..end];
+        let preview_rights = &self.preview.rights[self.position..end];
+
+        for (out, sample) in lefts.iter_mut().zip(preview_lefts.iter()) {
+            *out += *sample;
+        }
+        for (out, sample) in rights.iter_mut().zip(preview_rights.iter()) {
+            *out += *sample;
+        }
+
+        self.position = end;
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position >= self.preview.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parameters::PARAMETERS;
+    use crate::sync::PatchBank;
+
+    use super::*;
+
+    #[test]
+    fn test_render_default_patch_preview() {
+        let bank = PatchBank::default();
+
+        let values = bank
+            .get_current_patch()
+            .parameters
+            .values()
+            .map(|p| p.get_value())
+            .collect::<Vec<f32>>();
+
+        assert_eq!(values.len(), PARAMETERS.len());
+
+        let preview = PatchPreview::render(&values, SampleRate(44100.0));
+
+        assert_eq!(preview.len(), (TOTAL_DURATION * 44100.0) as usize);
+        assert!(preview.lefts.iter().any(|s| s.abs() > 0.0));
+        assert!(preview
+            .lefts
+            .iter()
+            .chain(preview.rights.iter())
+            .all(|s| s.is_finite()));
+
+        let (mut sender, mut player) = patch_preview_queues();
+
+        sender.send(Box::new(preview));
+
+        let mut lefts = [0.0f32; 256];
+        let mut rights = [0.0f32; 256];
+
+        player.mix_into(&mut lefts, &mut rights);
+
+        assert!(player.opt_playback.is_some());
+
+        while player.opt_playback.is_some() {
+            player.mix_into(&mut lefts, &mut rights);
+        }
+
+        // Finished preview is handed back instead of being dropped
+        assert_eq!(sender.returned.len(), 1);
+
+        sender.drop_returned();
+
+        assert!(sender.returned.is_empty());
+    }
+
+    #[test]
+    fn test_new_patch_preview_replaces_current_one() {
+        let (mut sender, mut player) = patch_preview_queues();
+
+        let preview = |value: f32| PatchPreview {
+            lefts: vec![value; 1024],
+            rights: vec![value; 1024],
+        };
+
+        sender.send(Box::new(preview(1.0)));
+
+        let mut lefts = [0.0f32; 256];
+        let mut rights = [0.0f32; 256];
+
+        player.mix_into(&mut lefts, &mut rights);
+
+        sender.send(Box::new(preview(2.0)));
+
+        let mut lefts = [0.0f32; 256];
+        let mut rights = [0.0f32; 256];
+
+        player.mix_into(&mut lefts, &mut rights);
+
+        assert!(lefts.iter().chain(rights.iter()).all(|s| *s == 2.0));
+        assert_eq!(sender.returned.len(), 1);
+    }
+}
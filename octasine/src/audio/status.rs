@@ -0,0 +1,223 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use crate::common::{EnvelopeStage, NUM_OPERATORS};
+
+/// Maximum number of voices included in published voice snapshot
+pub const MAX_VOICE_SNAPSHOTS: usize = 32;
+
+/// State of an active voice at end of most recent audio block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoiceSnapshot {
+    pub key: u8,
+    /// Key velocity as MIDI velocity (0-127)
+    pub velocity: u8,
+    /// Earliest envelope stage among operators that haven't finished
+    pub stage: EnvelopeStage,
+    /// Time since key was pressed in milliseconds
+    pub age_ms: u32,
+    pub monophonic: bool,
+}
+
+impl VoiceSnapshot {
+    fn to_bits(self) -> u64 {
+        let stage = match self.stage {
+            EnvelopeStage::Attack => 0,
+            EnvelopeStage::Decay => 1,
+            EnvelopeStage::Sustain => 2,
+            EnvelopeStage::Release => 3,
+            EnvelopeStage::Ended => 4,
+            EnvelopeStage::Kill => 5,
+            EnvelopeStage::Steal => 6,
+        };
+
+        u64::from(self.key)
+            | u64::from(self.velocity) << 8
+            | stage << 16
+            | u64::from(self.monophonic) << 20
+            | u64::from(self.age_ms) << 32
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        let stage = match (bits >> 16) & 0b1111 {
+            0 => EnvelopeStage::Attack,
+            1 => EnvelopeStage::Decay,
+            2 => EnvelopeStage::Sustain,
+            3 => EnvelopeStage::Release,
+            4 => EnvelopeStage::Ended,
+            5 => EnvelopeStage::Kill,
+            _ => EnvelopeStage::Steal,
+        };
+
+        Self {
+            key: bits as u8,
+            velocity: (bits >> 8) as u8,
+            stage,
+            age_ms: (bits >> 32) as u32,
+            monophonic: (bits >> 20) & 1 == 1,
+        }
+    }
+}
+
+/// Audio thread status published for display in the GUI
+#[derive(Debug, Default)]
+pub struct AudioStatus {
+    /// Time spent processing relative to real-time budget, stored as f32 bits
+    dsp_load: AtomicU32,
+    num_active_voices: AtomicU32,
+    /// Set when audio processing panicked. Plugin outputs silence from then on.
+    bypassed_due_to_error: AtomicBool,
+    /// Output exceeded full scale during last CLIP_HOLD_DURATION seconds
+    clipping: AtomicBool,
+    /// Output exceeded full scale since clip indicator was last reset
+    clip_latched: AtomicBool,
+    /// Active voices encoded with `VoiceSnapshot::to_bits`. Each entry is
+    /// updated atomically, so entries are never torn, but a reader may see
+    /// a mix of two consecutive snapshots.
+    voice_snapshots: [AtomicU64; MAX_VOICE_SNAPSHOTS],
+    num_voice_snapshots: AtomicU32,
+    /// Peak absolute modulation output per operator since last taken,
+    /// stored as f32 bits. Bit patterns of non-negative floats sort like
+    /// the values themselves, so peaks can be merged with `fetch_max`.
+    operator_mod_out_peaks: [AtomicU32; NUM_OPERATORS],
+}
+
+impl AudioStatus {
+    pub(super) fn set_dsp_load(&self, dsp_load: f32) {
+        self.dsp_load.store(dsp_load.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Get smoothed DSP load, where 1.0 means that processing takes as long
+    /// as playing back the buffer
+    pub fn get_dsp_load(&self) -> f32 {
+        f32::from_bits(self.dsp_load.load(Ordering::Relaxed))
+    }
+
+    pub(super) fn set_num_active_voices(&self, num_active_voices: usize) {
+        self.num_active_voices
+            .store(num_active_voices as u32, Ordering::Relaxed);
+    }
+
+    /// Get number of voices currently producing sound, including ones in
+    /// release stage
+    pub fn get_num_active_voices(&self) -> usize {
+        self.num_active_voices.load(Ordering::Relaxed) as usize
+    }
+
+    pub fn set_bypassed_due_to_error(&self) {
+        self.bypassed_due_to_error.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_bypassed_due_to_error(&self) -> bool {
+        self.bypassed_due_to_error.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set_clipping(&self, clipping: bool) {
+        self.clipping.store(clipping, Ordering::Relaxed);
+
+        if clipping {
+            self.clip_latched.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Output exceeded full scale recently, so clipper or limiter engaged
+    pub fn is_clipping(&self) -> bool {
+        self.clipping.load(Ordering::Relaxed)
+    }
+
+    /// Output exceeded full scale at some point since last reset
+    pub fn is_clip_latched(&self) -> bool {
+        self.clip_latched.load(Ordering::Relaxed)
+    }
+
+    /// Publish snapshot of active voices. Voices beyond
+    /// MAX_VOICE_SNAPSHOTS are left out.
+    pub(super) fn set_voice_snapshots<I: Iterator<Item = VoiceSnapshot>>(&self, voices: I) {
+        let mut num_voices = 0;
+
+        for (atomic, voice) in self.voice_snapshots.iter().zip(voices) {
+            atomic.store(voice.to_bits(), Ordering::Relaxed);
+
+            num_voices += 1;
+        }
+
+        self.num_voice_snapshots
+            .store(num_voices, Ordering::Release);
+    }
+
+    /// Get most recently published snapshot of active voices
+    pub fn get_voice_snapshots(&self) -> Vec<VoiceSnapshot> {
+        let num_voices = self.num_voice_snapshots.load(Ordering::Acquire) as usize;
+
+        self.voice_snapshots[..num_voices]
+            .iter()
+            .map(|atomic| VoiceSnapshot::from_bits(atomic.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Merge peak absolute modulation outputs into published ones
+    pub(super) fn update_operator_mod_out_peaks(&self, peaks: [f32; NUM_OPERATORS]) {
+        for (atomic, peak) in self.operator_mod_out_peaks.iter().zip(peaks) {
+            atomic.fetch_max(peak.max(0.0).to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Get peak absolute modulation output per operator (post envelope,
+    /// LFO and velocity) since last call
+    pub fn take_operator_mod_out_peaks(&self) -> [f32; NUM_OPERATORS] {
+        ::std::array::from_fn(|i| {
+            f32::from_bits(self.operator_mod_out_peaks[i].swap(0, Ordering::Relaxed))
+        })
+    }
+
+    /// Turn off clip indicator until output exceeds full scale again
+    pub fn reset_clip_status(&self) {
+        self.clipping.store(false, Ordering::Relaxed);
+        self.clip_latched.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voice_snapshot_bits_roundtrip() {
+        let status = AudioStatus::default();
+
+        let voices = [
+            VoiceSnapshot {
+                key: 127,
+                velocity: 100,
+                stage: EnvelopeStage::Release,
+                age_ms: u32::MAX,
+                monophonic: true,
+            },
+            VoiceSnapshot {
+                key: 0,
+                velocity: 0,
+                stage: EnvelopeStage::Steal,
+                age_ms: 0,
+                monophonic: false,
+            },
+        ];
+
+        status.set_voice_snapshots(voices.iter().copied());
+
+        assert_eq!(status.get_voice_snapshots(), voices);
+
+        status.set_voice_snapshots(::std::iter::repeat_n(voices[0], 100));
+
+        assert_eq!(status.get_voice_snapshots().len(), MAX_VOICE_SNAPSHOTS);
+    }
+
+    #[test]
+    fn test_operator_mod_out_peaks() {
+        let status = AudioStatus::default();
+
+        status.update_operator_mod_out_peaks([0.5, 0.0, 2.0, 0.25]);
+        status.update_operator_mod_out_peaks([1.0, 0.0, 1.5, 0.125]);
+
+        assert_eq!(status.take_operator_mod_out_peaks(), [1.0, 0.0, 2.0, 0.25]);
+        assert_eq!(status.take_operator_mod_out_peaks(), [0.0; NUM_OPERATORS]);
+    }
+}
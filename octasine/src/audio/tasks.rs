@@ -0,0 +1,98 @@
+use std::marker::PhantomData;
+
+/// Executes independent rendering tasks, e.g., on host worker threads
+pub trait TaskExecutor: Send + Sync {
+    /// Call `task` once for each index in `0..num_tasks`, possibly in
+    /// parallel, and return once all calls have completed. Return false
+    /// without calling `task` if tasks can't be executed right now, in which
+    /// case the caller does the work itself.
+    fn execute(&self, num_tasks: usize, task: &(dyn Fn(usize) + Sync)) -> bool;
+}
+
+/// Mutable slice shared with tasks, each of which only accesses the item at
+/// its own task index
+pub struct TaskSlice<'a, T> {
+    ptr: *mut T,
+    len: usize,
+    phantom_data: PhantomData<&'a mut [T]>,
+}
+
+unsafe impl<T: Send> Sync for TaskSlice<'_, T> {}
+
+impl<'a, T> TaskSlice<'a, T> {
+    pub fn new(slice: &'a mut [T]) -> Self {
+        Self {
+            ptr: slice.as_mut_ptr(),
+            len: slice.len(),
+            phantom_data: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// # Safety
+    ///
+    /// Item must not be accessed by any other task at the same time, which
+    /// holds if it is only accessed from the task with the same index.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_mut(&self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            Some(&mut *self.ptr.add(index))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Runs each task on its own thread
+    #[derive(Default)]
+    pub struct ThreadTaskExecutor {
+        pub num_executions: AtomicUsize,
+    }
+
+    impl TaskExecutor for ThreadTaskExecutor {
+        fn execute(&self, num_tasks: usize, task: &(dyn Fn(usize) + Sync)) -> bool {
+            self.num_executions.fetch_add(1, Ordering::Relaxed);
+
+            std::thread::scope(|scope| {
+                for task_index in 0..num_tasks {
+                    scope.spawn(move || task(task_index));
+                }
+            });
+
+            true
+        }
+    }
+
+    #[test]
+    fn test_task_slice() {
+        let mut items = [0usize; 8];
+
+        let executed = {
+            let task_slice = TaskSlice::new(&mut items);
+
+            assert_eq!(task_slice.len(), 8);
+
+            ThreadTaskExecutor::default().execute(10, &|task_index| {
+                if let Some(item) = unsafe { task_slice.get_mut(task_index) } {
+                    *item = task_index * 2;
+                }
+            })
+        };
+
+        assert!(executed);
+        assert_eq!(items, [0, 2, 4, 6, 8, 10, 12, 14]);
+    }
+}
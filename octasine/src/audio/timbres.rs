@@ -0,0 +1,316 @@
+use crate::common::{BeatsPerMinute, NoteEvent, NoteEventInner, SampleRate};
+use crate::parameters::bypass::BypassValue;
+use crate::parameters::{MasterParameter, Parameter, ParameterValue, PARAMETERS};
+
+use super::gen::{process_f32_runtime_select, SimdBackend};
+use super::parameters::common::AudioParameter;
+use super::{AudioSettings, AudioState};
+
+/// Number of patch slots played in addition to the current patch in
+/// multitimbral mode. MIDI channel 1 plays the current patch and channels
+/// 2-4 play the patches following it in the bank.
+pub const NUM_TIMBRES: usize = 3;
+
+/// Number of samples timbres can render at a time unless set by host
+pub(super) const DEFAULT_BUFFER_LEN: usize = 1024;
+
+/// Secondary AudioState with own parameters and voices, playing one
+/// additional patch slot in multitimbral mode. Output is summed with that of
+/// the current patch before the master output stage, so gain compensation,
+/// master effects and output mode of the current patch apply to it.
+pub struct Timbre {
+    audio: AudioState,
+    /// Patch values last applied to parameters, NaN before first update
+    patch_values: Vec<f32>,
+    /// Output of last render, kept per timbre so that timbres can be
+    /// rendered in parallel
+    lefts: Vec<f32>,
+    rights: Vec<f32>,
+}
+
+impl Timbre {
+    fn new(sample_rate: SampleRate, settings: &AudioSettings, buffer_len: usize) -> Self {
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(sample_rate);
+        audio.apply_settings(&AudioSettings {
+            multitimbral: false,
+            operator_bus_outputs: false,
+            ..settings.clone()
+        });
+
+        Self {
+            audio,
+            patch_values: vec![f32::NAN; PARAMETERS.len()],
+            lefts: vec![0.0; buffer_len],
+            rights: vec![0.0; buffer_len],
+        }
+    }
+
+    pub(super) fn set_sample_rate(&mut self, sample_rate: SampleRate) {
+        self.audio.set_sample_rate(sample_rate);
+    }
+
+    pub(super) fn set_bpm(&mut self, bpm: BeatsPerMinute) {
+        self.audio.set_bpm(bpm);
+    }
+
+    pub(super) fn set_simd_backend(&mut self, simd_backend: SimdBackend) {
+        self.audio.set_simd_backend(simd_backend);
+    }
+
+    pub(super) fn enqueue_note_event(&mut self, event: NoteEvent) {
+        self.audio.enqueue_note_event(event);
+    }
+
+    #[cfg(feature = "vst2")]
+    pub(super) fn sort_note_events(&mut self) {
+        self.audio.sort_note_events();
+    }
+
+    /// Render `num_samples` (at most buffer length) samples starting at
+    /// `frame_offset` into timbre's own buffers. Timbres don't share any
+    /// state, so different timbres may be rendered at the same time, e.g.,
+    /// on host worker threads.
+    pub fn render(&mut self, frame_offset: usize, num_samples: usize) {
+        process_f32_runtime_select(
+            &mut self.audio,
+            &mut self.lefts[..num_samples],
+            &mut self.rights[..num_samples],
+            frame_offset,
+            |_| (),
+        );
+    }
+
+    /// Follow bypass state of current patch instead of that of timbre patch
+    fn set_bypass(&mut self, bypass: bool) {
+        self.audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::Bypass),
+            BypassValue::new_from_audio(bypass).to_patch(),
+        );
+    }
+
+    pub fn set_key_detune(&mut self, key: u8, cents: f32) {
+        self.audio.set_key_detune(key, cents);
+    }
+
+    /// Set parameter from patch value, skipping values that were already
+    /// applied so that interpolation isn't restarted needlessly
+    pub fn set_parameter_from_patch(&mut self, index: usize, value: f32) {
+        if let Some(patch_value) = self.patch_values.get_mut(index) {
+            if *patch_value != value {
+                match Parameter::from_index(index) {
+                    Some(Parameter::Master(MasterParameter::Bypass)) | None => (),
+                    Some(parameter) => self.audio.set_parameter_from_patch(parameter, value),
+                }
+
+                *patch_value = value;
+            }
+        }
+    }
+}
+
+impl AudioState {
+    /// Create or remove timbres depending on whether multitimbral mode is
+    /// enabled. Don't call from audio thread.
+    pub(super) fn set_multitimbral(&mut self, settings: &AudioSettings) {
+        self.timbres = if settings.multitimbral {
+            (0..NUM_TIMBRES)
+                .map(|_| Timbre::new(self.sample_rate, settings, self.timbre_buffer_len))
+                .collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Resize timbre buffers to fit blocks of given size, so that timbres
+    /// render each block in one go. Don't call from audio thread.
+    pub fn set_max_block_size(&mut self, max_block_size: usize) {
+        self.timbre_buffer_len = max_block_size.max(1);
+
+        for timbre in self.timbres.iter_mut() {
+            timbre.lefts.resize(self.timbre_buffer_len, 0.0);
+            timbre.rights.resize(self.timbre_buffer_len, 0.0);
+        }
+    }
+
+    /// Timbres for MIDI channels 2-4, empty unless multitimbral mode is
+    /// enabled
+    pub fn timbres_mut(&mut self) -> &mut [Timbre] {
+        &mut self.timbres
+    }
+
+    /// Return timbre that should handle note event instead of this audio
+    /// state, if any. Only MIDI events carry channel information, so other
+    /// note events are always handled by the current patch.
+    pub(super) fn get_timbre_for_note_event(&mut self, event: &NoteEvent) -> Option<&mut Timbre> {
+        match event.event {
+            NoteEventInner::Midi { data } => {
+                let channel = usize::from(data[0] & 0b_0000_1111);
+
+                channel
+                    .checked_sub(1)
+                    .and_then(|index| self.timbres.get_mut(index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Add audio of timbres (if any) to output buffers. Call before
+    /// [Self::apply_output_stage].
+    pub fn mix_timbres(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        self.mix_timbres_with(lefts, rights, |timbres, frame_offset, num_samples| {
+            for timbre in timbres.iter_mut() {
+                timbre.render(frame_offset, num_samples);
+            }
+        })
+    }
+
+    /// Add audio of timbres (if any) to output buffers, rendering them with
+    /// `render`, which must call [Timbre::render] with the passed frame
+    /// offset and number of samples for every timbre, possibly in parallel.
+    /// `render` is called once unless the block is larger than the size set
+    /// with [Self::set_max_block_size].
+    pub fn mix_timbres_with<F>(&mut self, lefts: &mut [f32], rights: &mut [f32], mut render: F)
+    where
+        F: FnMut(&mut [Timbre], usize, usize),
+    {
+        if self.timbres.is_empty() {
+            return;
+        }
+
+        let bypass = self.parameters.bypass.get_value();
+
+        for timbre in self.timbres.iter_mut() {
+            timbre.set_bypass(bypass);
+        }
+
+        let buffer_len = self.timbre_buffer_len;
+
+        for (segment_index, (lefts, rights)) in lefts
+            .chunks_mut(buffer_len)
+            .zip(rights.chunks_mut(buffer_len))
+            .enumerate()
+        {
+            render(&mut self.timbres, segment_index * buffer_len, lefts.len());
+
+            for timbre in self.timbres.iter() {
+                for (out, sample) in lefts.iter_mut().zip(timbre.lefts.iter()) {
+                    *out += *sample;
+                }
+                for (out, sample) in rights.iter_mut().zip(timbre.rights.iter()) {
+                    *out += *sample;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_midi_channels_are_routed_to_timbres() {
+        let mut audio = AudioState::default();
+
+        audio.apply_settings(&AudioSettings {
+            multitimbral: true,
+            ..Default::default()
+        });
+
+        // Note on for channel 3 should only sound in second timbre
+        audio.enqueue_note_event(NoteEvent {
+            delta_frames: 0,
+            event: NoteEventInner::Midi {
+                data: [0b_1001_0010, 60, 100],
+            },
+        });
+
+        let mut lefts = [0.0f32; 256];
+        let mut rights = [0.0f32; 256];
+
+        process_f32_runtime_select(&mut audio, &mut lefts, &mut rights, 0, |_| ());
+
+        assert!(lefts.iter().chain(rights.iter()).all(|s| *s == 0.0));
+
+        audio.mix_timbres(&mut lefts, &mut rights);
+
+        assert!(lefts.iter().any(|s| *s != 0.0));
+        assert!(audio.timbres[0].audio.polyphonic_voices.is_empty());
+        assert!(!audio.timbres[1].audio.polyphonic_voices.is_empty());
+    }
+
+    #[test]
+    fn test_timbres_pass_through_master_output_stage() {
+        use crate::parameters::output_mode::{OutputMode, OutputModeValue};
+
+        let mut audio = AudioState::default();
+
+        audio.apply_settings(&AudioSettings {
+            multitimbral: true,
+            ..Default::default()
+        });
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::OutputMode),
+            OutputModeValue::new_from_audio(OutputMode::SoftClip).to_patch(),
+        );
+        audio.timbres_mut()[0].set_parameter_from_patch(
+            Parameter::Master(MasterParameter::Volume).to_index() as usize,
+            1.0,
+        );
+
+        // Loud chord on channel 2
+        for key in (48..96).step_by(4) {
+            audio.enqueue_note_event(NoteEvent {
+                delta_frames: 0,
+                event: NoteEventInner::Midi {
+                    data: [0b_1001_0001, key, 127],
+                },
+            });
+        }
+
+        let mut lefts = [0.0f32; 2048];
+        let mut rights = [0.0f32; 2048];
+
+        process_f32_runtime_select(&mut audio, &mut lefts, &mut rights, 0, |_| ());
+        audio.mix_timbres(&mut lefts, &mut rights);
+
+        assert!(lefts.iter().chain(rights.iter()).any(|s| s.abs() > 1.0));
+
+        audio.apply_output_stage(&mut lefts, &mut rights);
+
+        assert!(lefts.iter().chain(rights.iter()).all(|s| s.abs() < 1.0));
+    }
+
+    #[test]
+    fn test_timbres_are_rendered_once_per_block() {
+        let mut audio = AudioState::default();
+
+        audio.apply_settings(&AudioSettings {
+            multitimbral: true,
+            ..Default::default()
+        });
+        audio.set_max_block_size(512);
+
+        let mut lefts = [0.0f32; 1280];
+        let mut rights = [0.0f32; 1280];
+
+        let mut calls = Vec::new();
+
+        audio.mix_timbres_with(&mut lefts[..512], &mut rights[..512], |_, offset, len| {
+            calls.push((offset, len))
+        });
+
+        assert_eq!(calls, [(0, 512)]);
+
+        calls.clear();
+
+        // Blocks larger than max block size are rendered in segments
+        audio.mix_timbres_with(&mut lefts, &mut rights, |_, offset, len| {
+            calls.push((offset, len))
+        });
+
+        assert_eq!(calls, [(0, 512), (512, 512), (1024, 256)]);
+    }
+}
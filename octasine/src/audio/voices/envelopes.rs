@@ -8,6 +8,16 @@ use super::VoiceDuration;
 
 const INTERPOLATION_DURATION: f64 = 0.00333;
 const KILL_DURATION: f64 = INTERPOLATION_DURATION;
+/// Fade out duration of stolen voice copy. Matches fade in of restarted voice.
+const STEAL_DURATION: f64 = INTERPOLATION_DURATION;
+
+/// LFO additions to stage duration patch values, sampled when note starts
+#[derive(Debug, Copy, Clone, Default)]
+pub struct EnvelopeDurationModulation {
+    pub attack: Option<f32>,
+    pub decay: Option<f32>,
+    pub release: Option<f32>,
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct VoiceOperatorVolumeEnvelope {
@@ -19,14 +29,39 @@ pub struct VoiceOperatorVolumeEnvelope {
     /// Value to interpolate from when restarting without keeping initial
     /// volume
     restarting_from_volume: Option<f32>,
+    /// Unset until first sample of note has been processed
+    duration_modulation: Option<EnvelopeDurationModulation>,
+    fade_in_on_restart: bool,
+    /// Volume reached at end of attack stage
+    attack_peak: f32,
+    /// Factor to multiply release duration by, e.g., based on release
+    /// velocity
+    release_duration_factor: f32,
 }
 
 impl VoiceOperatorVolumeEnvelope {
+    pub fn has_duration_modulation(&self) -> bool {
+        self.duration_modulation.is_some()
+    }
+
+    /// Set stage duration modulation for rest of note
+    pub fn set_duration_modulation(&mut self, modulation: EnvelopeDurationModulation) {
+        self.duration_modulation = Some(modulation);
+    }
+
+    fn get_duration_modulation(&self) -> EnvelopeDurationModulation {
+        self.duration_modulation.unwrap_or_default()
+    }
+
+    /// Advance envelope. Sustain pedal depth ranges from 0.0 (released) to
+    /// 1.0 (fully pressed). Partial depth keeps released notes in release
+    /// stage but slows it down, like half-pedaling on a piano.
     pub fn advance_one_sample(
         &mut self,
-        parameters: &OperatorEnvelopeAudioParameters,
+        parameters: &mut OperatorEnvelopeAudioParameters,
         voice_operator_phase: &mut Phase,
-        key_or_sustain_pedal_pressed: bool,
+        key_pressed: bool,
+        sustain_pedal_depth: f32,
         time_per_sample: TimePerSample,
     ) {
         use EnvelopeStage::*;
@@ -35,13 +70,18 @@ impl VoiceOperatorVolumeEnvelope {
             return;
         }
 
-        self.duration.0 += time_per_sample.0;
+        if let Release = self.stage {
+            self.duration.0 += time_per_sample.0 * (1.0 - sustain_pedal_depth as f64)
+                / self.release_duration_factor as f64;
+        } else {
+            self.duration.0 += time_per_sample.0;
+        }
 
         if self.restarting_from_volume.is_some() && self.duration.0 >= INTERPOLATION_DURATION {
             self.restarting_from_volume = None;
         }
 
-        if !key_or_sustain_pedal_pressed {
+        if !key_pressed && sustain_pedal_depth < 1.0 {
             match self.stage {
                 Attack | Decay | Sustain => {
                     self.stage = Release;
@@ -50,24 +90,40 @@ impl VoiceOperatorVolumeEnvelope {
 
                     return;
                 }
-                Release | Kill | Ended => (),
+                Release | Kill | Steal | Ended => (),
             }
         }
 
         let duration_since_stage_change = self.duration_since_stage_change();
+        let modulation = self.get_duration_modulation();
 
         match self.stage {
-            Attack if duration_since_stage_change >= parameters.attack_duration.get_value() => {
+            Attack
+                if duration_since_stage_change
+                    >= parameters
+                        .attack_duration
+                        .get_value_with_lfo_addition(modulation.attack) =>
+            {
                 self.stage = Decay;
                 self.duration_at_stage_change = self.duration;
                 self.volume_at_stage_change = self.last_volume;
             }
-            Decay if duration_since_stage_change >= parameters.decay_duration.get_value() => {
+            Decay
+                if duration_since_stage_change
+                    >= parameters
+                        .decay_duration
+                        .get_value_with_lfo_addition(modulation.decay) =>
+            {
                 self.stage = Sustain;
                 self.duration_at_stage_change = self.duration;
                 self.volume_at_stage_change = self.last_volume;
             }
-            Release if duration_since_stage_change >= parameters.release_duration.get_value() => {
+            Release
+                if duration_since_stage_change
+                    >= parameters
+                        .release_duration
+                        .get_value_with_lfo_addition(modulation.release) =>
+            {
                 self.stage = Ended;
                 self.duration_at_stage_change = VoiceDuration(0.0);
                 self.volume_at_stage_change = 0.0;
@@ -83,6 +139,13 @@ impl VoiceOperatorVolumeEnvelope {
                 // Set voice operator phase to zero if envelope just ended
                 voice_operator_phase.0 = 0.0;
             }
+            Steal if duration_since_stage_change >= STEAL_DURATION => {
+                self.stage = Ended;
+                self.duration_at_stage_change = VoiceDuration(0.0);
+                self.volume_at_stage_change = 0.0;
+
+                voice_operator_phase.0 = 0.0;
+            }
             _ => {}
         }
     }
@@ -90,7 +153,7 @@ impl VoiceOperatorVolumeEnvelope {
     pub fn get_volume(
         &mut self,
         log10table: &Log10Table,
-        parameters: &OperatorEnvelopeAudioParameters,
+        parameters: &mut OperatorEnvelopeAudioParameters,
     ) -> f32 {
         use EnvelopeStage::*;
 
@@ -100,20 +163,26 @@ impl VoiceOperatorVolumeEnvelope {
             return 0.0;
         }
 
+        let modulation = self.get_duration_modulation();
+
         let volume = match self.stage {
             Attack => Self::calculate_curve(
                 log10table,
                 self.volume_at_stage_change,
-                1.0,
+                self.attack_peak,
                 self.duration_since_stage_change(),
-                parameters.attack_duration.get_value(),
+                parameters
+                    .attack_duration
+                    .get_value_with_lfo_addition(modulation.attack),
             ),
             Decay => Self::calculate_curve(
                 log10table,
                 self.volume_at_stage_change,
                 parameters.sustain_volume.get_value(),
                 self.duration_since_stage_change(),
-                parameters.decay_duration.get_value(),
+                parameters
+                    .decay_duration
+                    .get_value_with_lfo_addition(modulation.decay),
             ),
             Sustain => parameters.sustain_volume.get_value(),
             Release => Self::calculate_curve(
@@ -121,7 +190,9 @@ impl VoiceOperatorVolumeEnvelope {
                 self.volume_at_stage_change,
                 0.0,
                 self.duration_since_stage_change(),
-                parameters.release_duration.get_value(),
+                parameters
+                    .release_duration
+                    .get_value_with_lfo_addition(modulation.release),
             ),
             Kill => Self::calculate_curve(
                 log10table,
@@ -130,6 +201,13 @@ impl VoiceOperatorVolumeEnvelope {
                 self.duration_since_stage_change(),
                 KILL_DURATION,
             ),
+            Steal => Self::calculate_curve(
+                log10table,
+                self.volume_at_stage_change,
+                0.0,
+                self.duration_since_stage_change(),
+                STEAL_DURATION,
+            ),
             Ended => unreachable!(),
         };
 
@@ -172,26 +250,62 @@ impl VoiceOperatorVolumeEnvelope {
             *self = Self {
                 volume_at_stage_change: self.last_volume,
                 last_volume: self.last_volume,
+                restarting_from_volume: self.fade_in_on_restart.then_some(0.0),
                 ..Default::default()
             }
         } else {
+            let restart_volume = if self.fade_in_on_restart {
+                0.0
+            } else {
+                self.last_volume
+            };
+
             *self = Self {
-                restarting_from_volume: Some(self.last_volume),
+                restarting_from_volume: Some(restart_volume),
                 ..Default::default()
             }
         }
     }
 
+    /// Set factor to multiply release duration by. Call when key is
+    /// released.
+    pub fn set_release_duration_factor(&mut self, factor: f32) {
+        self.release_duration_factor = factor;
+    }
+
+    /// Set volume reached at end of attack stage, e.g., based on key
+    /// velocity. Call after restarting.
+    pub fn set_attack_peak(&mut self, attack_peak: f32) {
+        self.attack_peak = attack_peak;
+    }
+
     pub fn kill(&mut self) {
         self.stage = EnvelopeStage::Kill;
         self.duration_at_stage_change = self.duration;
         self.volume_at_stage_change = self.last_volume;
     }
 
+    /// Quickly fade out, regardless of key state
+    pub fn steal(&mut self) {
+        self.stage = EnvelopeStage::Steal;
+        self.duration_at_stage_change = self.duration;
+        self.volume_at_stage_change = self.last_volume;
+    }
+
+    /// Fade in from silence on next restart, since current sound has been
+    /// handed over to a stolen copy
+    pub fn set_fade_in_on_restart(&mut self) {
+        self.fade_in_on_restart = true;
+    }
+
     #[inline]
     pub fn is_ended(&self) -> bool {
         self.stage == EnvelopeStage::Ended
     }
+
+    pub fn get_stage(&self) -> EnvelopeStage {
+        self.stage
+    }
 }
 
 impl Default for VoiceOperatorVolumeEnvelope {
@@ -203,6 +317,10 @@ impl Default for VoiceOperatorVolumeEnvelope {
             volume_at_stage_change: 0.0,
             last_volume: 0.0,
             restarting_from_volume: None,
+            duration_modulation: None,
+            fade_in_on_restart: false,
+            attack_peak: 1.0,
+            release_duration_factor: 1.0,
         }
     }
 }
@@ -323,4 +441,163 @@ mod tests {
 
         quickcheck(prop as fn(f32) -> TestResult);
     }
+
+    #[test]
+    fn steal_crossfade() {
+        let table = Log10Table::default();
+        let mut parameters = OperatorEnvelopeAudioParameters::default();
+        let time_per_sample: TimePerSample = SampleRate::default().into();
+
+        let mut envelope = VoiceOperatorVolumeEnvelope::default();
+        let mut phase = Phase(0.0);
+
+        for _ in 0..1000 {
+            envelope.advance_one_sample(&mut parameters, &mut phase, true, 0.0, time_per_sample);
+            envelope.get_volume(&table, &mut parameters);
+        }
+
+        let mut stolen = envelope;
+
+        stolen.steal();
+        envelope.set_fade_in_on_restart();
+        envelope.restart(false);
+
+        let num_samples = (STEAL_DURATION / time_per_sample.0).ceil() as usize + 1;
+
+        let mut previous_stolen_volume = stolen.last_volume;
+
+        for _ in 0..num_samples {
+            stolen.advance_one_sample(&mut parameters, &mut phase, true, 0.0, time_per_sample);
+            envelope.advance_one_sample(&mut parameters, &mut phase, true, 0.0, time_per_sample);
+
+            let stolen_volume = stolen.get_volume(&table, &mut parameters);
+
+            envelope.get_volume(&table, &mut parameters);
+
+            assert!(stolen_volume <= previous_stolen_volume);
+
+            previous_stolen_volume = stolen_volume;
+        }
+
+        assert!(stolen.is_ended());
+        assert!(!envelope.is_ended());
+    }
+
+    #[test]
+    fn attack_peak() {
+        let table = Log10Table::default();
+        let mut parameters = OperatorEnvelopeAudioParameters::default();
+        let time_per_sample: TimePerSample = SampleRate::default().into();
+
+        let mut envelope = VoiceOperatorVolumeEnvelope::default();
+        let mut phase = Phase(0.0);
+
+        envelope.set_attack_peak(0.5);
+
+        let mut max_volume = 0.0f32;
+
+        while envelope.stage == EnvelopeStage::Attack {
+            envelope.advance_one_sample(&mut parameters, &mut phase, true, 0.0, time_per_sample);
+
+            max_volume = max_volume.max(envelope.get_volume(&table, &mut parameters));
+        }
+
+        assert_approx_eq!(max_volume, 0.5, 0.01);
+    }
+
+    #[test]
+    fn half_pedal_slows_release() {
+        let table = Log10Table::default();
+        let mut parameters = OperatorEnvelopeAudioParameters::default();
+        let time_per_sample: TimePerSample = SampleRate::default().into();
+
+        let mut phase = Phase(0.0);
+
+        // Number of samples until envelope ends after key release
+        let mut release_samples = |sustain_pedal_depth: f32| {
+            let mut envelope = VoiceOperatorVolumeEnvelope::default();
+
+            for _ in 0..1000 {
+                envelope.advance_one_sample(
+                    &mut parameters,
+                    &mut phase,
+                    true,
+                    0.0,
+                    time_per_sample,
+                );
+                envelope.get_volume(&table, &mut parameters);
+            }
+
+            let mut num_samples = 0usize;
+
+            while !envelope.is_ended() {
+                envelope.advance_one_sample(
+                    &mut parameters,
+                    &mut phase,
+                    false,
+                    sustain_pedal_depth,
+                    time_per_sample,
+                );
+                envelope.get_volume(&table, &mut parameters);
+
+                num_samples += 1;
+            }
+
+            num_samples
+        };
+
+        let no_pedal = release_samples(0.0);
+        let half_pedal = release_samples(0.5);
+
+        assert!(half_pedal > no_pedal + no_pedal / 2);
+    }
+
+    #[test]
+    fn release_duration_factor_scales_release() {
+        let table = Log10Table::default();
+        let mut parameters = OperatorEnvelopeAudioParameters::default();
+        let time_per_sample: TimePerSample = SampleRate::default().into();
+
+        let mut phase = Phase(0.0);
+
+        // Number of samples until envelope ends after key release
+        let mut release_samples = |release_duration_factor: f32| {
+            let mut envelope = VoiceOperatorVolumeEnvelope::default();
+
+            for _ in 0..1000 {
+                envelope.advance_one_sample(
+                    &mut parameters,
+                    &mut phase,
+                    true,
+                    0.0,
+                    time_per_sample,
+                );
+                envelope.get_volume(&table, &mut parameters);
+            }
+
+            envelope.set_release_duration_factor(release_duration_factor);
+
+            let mut num_samples = 0usize;
+
+            while !envelope.is_ended() {
+                envelope.advance_one_sample(
+                    &mut parameters,
+                    &mut phase,
+                    false,
+                    0.0,
+                    time_per_sample,
+                );
+                envelope.get_volume(&table, &mut parameters);
+
+                num_samples += 1;
+            }
+
+            num_samples as f32
+        };
+
+        let normal = release_samples(1.0);
+
+        assert_approx_eq!(release_samples(2.0) / normal, 2.0, 0.01);
+        assert_approx_eq!(release_samples(0.5) / normal, 0.5, 0.01);
+    }
 }
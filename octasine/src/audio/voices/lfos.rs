@@ -29,6 +29,8 @@ pub struct VoiceLfo {
     phase: Phase,
     last_value: f32,
     sample_rate: SampleRate,
+    /// Cached (smoothing time in ms, one-pole filter coefficient)
+    smoothing: (f32, f32),
 }
 
 impl Default for VoiceLfo {
@@ -41,11 +43,13 @@ impl Default for VoiceLfo {
             phase: Phase(0.0),
             last_value: 0.0,
             sample_rate,
+            smoothing: (0.0, 0.0),
         }
     }
 }
 
 impl VoiceLfo {
+    #[allow(clippy::too_many_arguments)]
     pub fn advance_one_sample(
         &mut self,
         sample_rate: SampleRate,
@@ -54,6 +58,7 @@ impl VoiceLfo {
         shape: LfoShape,
         mode: LfoMode,
         frequency: f64,
+        smoothing_ms: f32,
     ) {
         if let LfoStage::Stopped | LfoStage::OneshotComplete = self.stage {
             return;
@@ -65,6 +70,7 @@ impl VoiceLfo {
 
         if self.sample_rate != sample_rate {
             self.sample_rate = sample_rate;
+            self.smoothing = (0.0, 0.0);
 
             // Restart interpolation
             self.stage = match self.stage {
@@ -140,17 +146,15 @@ impl VoiceLfo {
                 unreachable!()
             }
         }
-    }
 
-    pub fn get_value(&mut self, amount: f32) -> f32 {
-        if let LfoStage::Stopped = self.stage {
-            return 0.0;
-        }
+        self.update_value(smoothing_ms);
+    }
 
+    fn update_value(&mut self, smoothing_ms: f32) {
         let shape = if let Some(shape) = self.current_shape {
             shape
         } else {
-            return 0.0;
+            return;
         };
 
         let value = match self.stage {
@@ -164,15 +168,38 @@ impl VoiceLfo {
                 progress * shape.calculate(self.phase) + (1.0 - progress) * from_value
             }
             LfoStage::Running => shape.calculate(self.phase),
-            LfoStage::OneshotComplete => self.last_value,
+            // Keep last value
+            LfoStage::OneshotComplete => return,
             LfoStage::Stopped => {
                 unreachable!()
             }
         };
 
-        self.last_value = value;
+        if smoothing_ms > 0.0 {
+            if self.smoothing.0 != smoothing_ms {
+                let samples = f64::from(smoothing_ms) * 0.001 * self.sample_rate.0;
+
+                self.smoothing = (smoothing_ms, (-1.0 / samples).exp() as f32);
+            }
+
+            let coefficient = self.smoothing.1;
+
+            self.last_value = value + (self.last_value - value) * coefficient;
+        } else {
+            self.last_value = value;
+        }
+    }
+
+    /// Get value calculated in last call to `advance_one_sample`
+    pub fn get_value(&self, amount: f32) -> f32 {
+        if let LfoStage::Stopped = self.stage {
+            return 0.0;
+        }
+        if self.current_shape.is_none() {
+            return 0.0;
+        }
 
-        value * amount
+        self.last_value * amount
     }
 
     pub fn restart(&mut self, parameters: &LfoAudioParameters) {
@@ -213,7 +240,109 @@ impl VoiceLfo {
         self.last_value = 0.0;
     }
 
+    /// Continue from phase of another LFO instead of phase set on restart
+    pub fn set_phase(&mut self, phase: Phase) {
+        self.phase = phase;
+    }
+
+    pub fn get_phase(&self) -> Phase {
+        self.phase
+    }
+
     pub fn is_stopped(&self) -> bool {
         matches!(self.stage, LfoStage::Stopped)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::audio::parameters::AudioParameters;
+
+    use super::*;
+
+    /// Run square LFO at 20 Hz for two seconds, returning (peak value, largest
+    /// change between samples) over the last half second
+    fn run_square_lfo(
+        lfo: &mut VoiceLfo,
+        sample_rate: SampleRate,
+        smoothing_ms: f32,
+    ) -> (f32, f32) {
+        let mut peak = 0.0f32;
+        let mut max_change = 0.0f32;
+
+        for i in 0..sample_rate.0 as usize * 2 {
+            let previous_value = lfo.get_value(1.0);
+
+            lfo.advance_one_sample(
+                sample_rate,
+                sample_rate.into(),
+                BpmLfoMultiplier(1.0),
+                LfoShape::Square,
+                LfoMode::Forever,
+                20.0,
+                smoothing_ms,
+            );
+
+            // Skip start interpolation and settling of filter
+            if i > sample_rate.0 as usize * 3 / 2 {
+                let value = lfo.get_value(1.0);
+
+                peak = peak.max(value.abs());
+                max_change = max_change.max((value - previous_value).abs());
+            }
+        }
+
+        (peak, max_change)
+    }
+
+    #[test]
+    fn test_lfo_smoothing() {
+        let parameters = AudioParameters::default();
+
+        for sample_rate in [SampleRate(44100.0), SampleRate(96000.0)] {
+            let new_lfo = || {
+                let mut lfo = VoiceLfo::default();
+
+                lfo.restart(&parameters.lfos[0]);
+
+                lfo
+            };
+
+            // Largest change per sample of full-scale ramps of given length
+            let ramp_max_change = |ms: f32| 2.0 / (ms * 0.001 * sample_rate.0 as f32);
+
+            // Without smoothing, square transitions are short ramps
+            let (peak, max_change) = run_square_lfo(&mut new_lfo(), sample_rate, 0.0);
+
+            assert_eq!(peak, 1.0);
+            assert!(max_change > ramp_max_change(1.0));
+
+            // With smoothing, square is slewed and doesn't quite reach peaks
+            let (peak, max_change) = run_square_lfo(&mut new_lfo(), sample_rate, 10.0);
+
+            assert!(peak > 0.75 && peak < 0.95);
+            assert!(max_change < ramp_max_change(10.0));
+
+            // Long smoothing mostly flattens fast LFO
+            let (peak, _) = run_square_lfo(&mut new_lfo(), sample_rate, 200.0);
+
+            assert!(peak < 0.2);
+        }
+    }
+
+    #[test]
+    fn test_lfo_smoothing_sample_rate_change() {
+        let parameters = AudioParameters::default();
+
+        let mut lfo = VoiceLfo::default();
+
+        lfo.restart(&parameters.lfos[0]);
+
+        run_square_lfo(&mut lfo, SampleRate(44100.0), 10.0);
+
+        // Filter coefficient is recalculated for new sample rate
+        let (_, max_change) = run_square_lfo(&mut lfo, SampleRate(96000.0), 10.0);
+
+        assert!(max_change < 2.0 / (0.01 * 96000.0));
+    }
+}
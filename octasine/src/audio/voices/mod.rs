@@ -1,6 +1,7 @@
 pub mod envelopes;
 pub mod lfos;
 pub mod log10_table;
+pub mod note_stack;
 
 use array_init::array_init;
 
@@ -11,7 +12,8 @@ use lfos::*;
 
 use super::{
     interpolation::{InterpolationDuration, Interpolator},
-    parameters::AudioParameters,
+    parameters::{common::AudioParameter, AudioParameters},
+    status::VoiceSnapshot,
 };
 
 const VELOCITY_INTERPOLATION_DURATION: InterpolationDuration =
@@ -42,15 +44,15 @@ pub struct MidiPitch {
 }
 
 impl MidiPitch {
-    pub fn new(midi_pitch: u8) -> Self {
+    pub fn new(midi_pitch: u8, key_tuning: &KeyTuning) -> Self {
         Self {
-            frequency_factor: Self::calculate_frequency_factor(midi_pitch),
+            frequency_factor: key_tuning.frequency_factor(midi_pitch),
             key: midi_pitch,
         }
     }
 
-    fn calculate_frequency_factor(midi_pitch: u8) -> f64 {
-        let note_diff = f64::from(midi_pitch as i8 - 69);
+    fn calculate_frequency_factor(midi_pitch: u8, cents: f32) -> f64 {
+        let note_diff = f64::from(midi_pitch as i8 - 69) + f64::from(cents) / 100.0;
 
         (note_diff / 12.0).exp2()
     }
@@ -64,10 +66,42 @@ impl MidiPitch {
     }
 }
 
+/// Frequency factor (relative to master frequency) of each MIDI key, looked
+/// up when keys are pressed. Defaults to twelve-tone equal temperament.
+#[derive(Debug, Clone)]
+pub struct KeyTuning {
+    frequency_factors: [f64; 128],
+}
+
+impl Default for KeyTuning {
+    fn default() -> Self {
+        Self {
+            frequency_factors: ::std::array::from_fn(|key| {
+                MidiPitch::calculate_frequency_factor(key as u8, 0.0)
+            }),
+        }
+    }
+}
+
+impl KeyTuning {
+    /// Detune key by given number of cents relative to twelve-tone equal
+    /// temperament
+    pub fn set_key_detune(&mut self, key: u8, cents: f32) {
+        if let Some(frequency_factor) = self.frequency_factors.get_mut(key as usize) {
+            *frequency_factor = MidiPitch::calculate_frequency_factor(key, cents);
+        }
+    }
+
+    pub fn frequency_factor(&self, key: u8) -> f64 {
+        self.frequency_factors[key.min(127) as usize]
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct VoiceGlide {
     pub to_key: u8,
-    pub time: f64,
+    /// Glide time multiplier from BPM sync and key distance
+    pub time_factor: f64,
     pub retrigger_envelopes: bool,
     pub retrigger_lfos: bool,
 }
@@ -76,6 +110,11 @@ pub struct VoiceGlide {
 pub struct VoiceOperator {
     pub last_phase: Phase,
     pub volume_envelope: VoiceOperatorVolumeEnvelope,
+    /// Feedback lowpass filter state, None when feedback tone is off
+    pub feedback_lowpass: Option<f64>,
+    /// Stereo modulation output of the most recent sample, used for
+    /// modulating operators with higher index
+    pub last_mod_out: [f64; 2],
 }
 
 impl Default for VoiceOperator {
@@ -83,6 +122,8 @@ impl Default for VoiceOperator {
         Self {
             last_phase: Phase(0.0),
             volume_envelope: VoiceOperatorVolumeEnvelope::default(),
+            feedback_lowpass: None,
+            last_mod_out: [0.0; 2],
         }
     }
 }
@@ -95,11 +136,24 @@ pub struct Voice {
     pub midi_pitch: MidiPitch,
     pub key_pressed: bool,
     pub pitch_interpolator: Interpolator,
+    /// Time factor of most recent glide
+    glide_time_factor: f64,
     key_velocity_interpolator: Interpolator,
+    /// Polyphonic key pressure or channel pressure
+    pressure_interpolator: Interpolator,
+    /// Phase of internal LFO used for aftertouch vibrato
+    vibrato_phase: Phase,
+    /// Number of samples processed since most recent key press
+    age_in_samples: u64,
     pub operators: [VoiceOperator; NUM_OPERATORS],
     pub lfos: [VoiceLfo; NUM_LFOS],
     #[cfg(feature = "clap")]
     pub clap_note_id: Option<i32>,
+    /// Whether note end is reported when voice is deactivated. Not the case
+    /// for stolen copies, which keep note id only so that they can be
+    /// released or choked.
+    #[cfg(feature = "clap")]
+    pub report_clap_note_end: bool,
 }
 
 impl Voice {
@@ -115,33 +169,98 @@ impl Voice {
                 midi_pitch.frequency_factor as f32,
                 InterpolationDuration::exactly_1s(),
             ),
+            glide_time_factor: 1.0,
             key_velocity_interpolator: Interpolator::new(
                 KeyVelocity::default().0,
                 VELOCITY_INTERPOLATION_DURATION,
             ),
+            pressure_interpolator: Interpolator::new(0.0, VELOCITY_INTERPOLATION_DURATION),
+            vibrato_phase: Phase(0.0),
+            age_in_samples: 0,
             operators,
             lfos: array_init(|_| VoiceLfo::default()),
             #[cfg(feature = "clap")]
             clap_note_id: None,
+            #[cfg(feature = "clap")]
+            report_clap_note_end: true,
         }
     }
 
+    /// Advance interpolators and voice age
     pub fn advance_interpolators_one_sample(&mut self, sample_rate: SampleRate) {
+        self.age_in_samples += 1;
+
         self.key_velocity_interpolator
             .advance_one_sample(sample_rate, &mut |_| ());
+        self.pressure_interpolator
+            .advance_one_sample(sample_rate, &mut |_| ());
         self.pitch_interpolator
             .advance_one_sample(sample_rate, &mut |_| ());
     }
 
+    /// Update duration of ongoing glide, e.g. when glide time is modulated
+    pub fn update_glide_time(&mut self, glide_time: f32) {
+        self.pitch_interpolator
+            .change_remaining_duration(InterpolationDuration(
+                glide_time as f64 * self.glide_time_factor,
+            ));
+    }
+
     pub fn get_key_velocity(&mut self) -> KeyVelocity {
         KeyVelocity(self.key_velocity_interpolator.get_value())
     }
 
+    /// Get voice state for display in GUI
+    pub fn get_snapshot(&self, sample_rate: SampleRate) -> VoiceSnapshot {
+        let stage = self
+            .operators
+            .iter()
+            .map(|operator| operator.volume_envelope.get_stage())
+            .filter(|stage| *stage != EnvelopeStage::Ended)
+            .min_by_key(|stage| *stage as u8)
+            .unwrap_or(EnvelopeStage::Ended);
+
+        let velocity = self.key_velocity_interpolator.get_value().clamp(0.0, 1.0);
+        let age_ms = (self.age_in_samples as f64 * 1000.0 / sample_rate.0).min(u32::MAX as f64);
+
+        VoiceSnapshot {
+            key: self.midi_pitch.key,
+            velocity: (velocity * 127.0).round() as u8,
+            stage,
+            age_ms: age_ms as u32,
+            monophonic: self.is_monophonic,
+        }
+    }
+
+    /// Advance aftertouch vibrato LFO and return frequency multiplier for
+    /// current pressure
+    pub fn advance_aftertouch_vibrato(
+        &mut self,
+        depth_cents: f32,
+        rate: f32,
+        time_per_sample: TimePerSample,
+    ) -> f64 {
+        let cents = depth_cents * self.pressure_interpolator.get_value();
+
+        let multiplier = if cents == 0.0 {
+            1.0
+        } else {
+            let lfo_value = (self.vibrato_phase.0 * ::std::f64::consts::TAU).sin();
+
+            (f64::from(cents) * lfo_value * (1.0 / 1200.0)).exp2()
+        };
+
+        self.vibrato_phase.0 = (self.vibrato_phase.0 + f64::from(rate) * time_per_sample.0).fract();
+
+        multiplier
+    }
+
     #[inline]
     pub fn press_key(
         &mut self,
         parameters: &AudioParameters,
         velocity: KeyVelocity,
+        channel_pressure: KeyVelocity,
         initial_key: Option<u8>,
         target_key: Option<VoiceGlide>,
         #[cfg_attr(not(feature = "clap"), allow(unused_variables))] opt_clap_note_id: Option<i32>,
@@ -149,19 +268,24 @@ impl Voice {
         if self.active {
             self.key_velocity_interpolator.set_value(velocity.0)
         } else {
-            self.key_velocity_interpolator.force_set_value(velocity.0)
+            self.key_velocity_interpolator.force_set_value(velocity.0);
+            self.pressure_interpolator
+                .force_set_value(channel_pressure.0);
+            self.vibrato_phase = Phase(0.0);
         }
 
         if let Some(key) = initial_key {
-            self.change_pitch(key, None);
+            self.change_pitch(key, &parameters.key_tuning, None);
         }
 
+        self.age_in_samples = 0;
+
         let mut retrigger_envelopes = true;
         let mut retrigger_lfos = true;
 
         if let Some(VoiceGlide {
             to_key,
-            time,
+            time_factor,
             retrigger_envelopes: re,
             retrigger_lfos: rl,
         }) = target_key
@@ -169,12 +293,26 @@ impl Voice {
             retrigger_envelopes = re;
             retrigger_lfos = rl;
 
-            self.change_pitch(to_key, Some(time));
+            self.glide_time_factor = time_factor;
+
+            let time = parameters.glide_time.get_value() as f64 * time_factor;
+
+            self.change_pitch(to_key, &parameters.key_tuning, Some(time));
         }
 
         if retrigger_envelopes {
-            for operator in self.operators.iter_mut() {
+            for (operator, operator_parameters) in
+                self.operators.iter_mut().zip(parameters.operators.iter())
+            {
+                let sensitivity = operator_parameters
+                    .volume_envelope
+                    .attack_velocity_sensitivity
+                    .get_value();
+
                 operator.volume_envelope.restart(self.is_monophonic);
+                operator
+                    .volume_envelope
+                    .set_attack_peak(1.0 - sensitivity * (1.0 - velocity.0));
             }
         }
         if retrigger_lfos {
@@ -192,8 +330,8 @@ impl Voice {
         self.active = true;
     }
 
-    fn change_pitch(&mut self, key: u8, interpolate: Option<f64>) {
-        self.midi_pitch = MidiPitch::new(key);
+    fn change_pitch(&mut self, key: u8, key_tuning: &KeyTuning, interpolate: Option<f64>) {
+        self.midi_pitch = MidiPitch::new(key, key_tuning);
 
         if let Some(glide_time) = interpolate {
             self.pitch_interpolator
@@ -207,17 +345,39 @@ impl Voice {
         }
     }
 
-    pub fn aftertouch(&mut self, velocity: KeyVelocity) {
-        self.key_velocity_interpolator.set_value(velocity.0)
+    pub fn aftertouch(&mut self, pressure: KeyVelocity) {
+        self.pressure_interpolator.set_value(pressure.0)
     }
 
     pub fn key(&self) -> u8 {
         self.midi_pitch.key
     }
 
+    /// Returns false if note id is set and doesn't match id of most recent
+    /// key press
+    #[inline]
+    pub fn matches_clap_note_id(
+        &self,
+        #[cfg_attr(not(feature = "clap"), allow(unused_variables))] opt_clap_note_id: Option<i32>,
+    ) -> bool {
+        #[cfg(feature = "clap")]
+        if let Some(clap_note_id) = opt_clap_note_id {
+            return self.clap_note_id == Some(clap_note_id);
+        }
+
+        true
+    }
+
+    /// Release key, multiplying envelope release durations by factor
     #[inline]
-    pub fn release_key(&mut self) {
+    pub fn release_key(&mut self, release_duration_factor: f32) {
         self.key_pressed = false;
+
+        for operator in self.operators.iter_mut() {
+            operator
+                .volume_envelope
+                .set_release_duration_factor(release_duration_factor);
+        }
     }
 
     pub fn kill_envelopes(&mut self) {
@@ -226,6 +386,54 @@ impl Voice {
         }
     }
 
+    /// Hand over current sound to returned copy, which quickly fades out,
+    /// while this voice fades in when envelopes are retriggered. Call right
+    /// before pressing key.
+    pub fn steal(&mut self) -> Self {
+        let mut stolen = self.clone();
+
+        stolen.key_pressed = false;
+
+        #[cfg(feature = "clap")]
+        {
+            // Note end is reported for this voice, not the copy
+            stolen.report_clap_note_end = false;
+        }
+
+        for operator in stolen.operators.iter_mut() {
+            if !operator.volume_envelope.is_ended() {
+                operator.volume_envelope.steal();
+            }
+        }
+
+        for operator in self.operators.iter_mut() {
+            operator.volume_envelope.set_fade_in_on_restart();
+        }
+
+        stolen
+    }
+
+    /// Hand over current sound to returned copy, which behaves as if key
+    /// was released, while this voice starts from silence when envelopes
+    /// are retriggered. Call right before pressing key.
+    pub fn split_off_sustained(&mut self) -> Self {
+        let mut copy = self.clone();
+
+        copy.key_pressed = false;
+
+        #[cfg(feature = "clap")]
+        {
+            // Note end is reported for the copy, not this voice
+            self.clap_note_id = None;
+        }
+
+        for operator in self.operators.iter_mut() {
+            operator.volume_envelope.set_fade_in_on_restart();
+        }
+
+        copy
+    }
+
     #[inline]
     pub fn deactivate_if_envelopes_ended(&mut self) -> bool {
         let all_envelopes_ended = self
@@ -240,6 +448,8 @@ impl Voice {
 
             for operator in self.operators.iter_mut() {
                 operator.last_phase.0 = 0.0;
+                operator.feedback_lowpass = None;
+                operator.last_mod_out = [0.0; 2];
             }
 
             self.active = false;
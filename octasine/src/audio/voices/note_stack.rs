@@ -0,0 +1,153 @@
+use crate::common::IndexMap;
+use crate::parameters::mono_note_priority::MonoNotePriority;
+
+use super::KeyVelocity;
+
+#[derive(Debug, Clone, Copy)]
+pub struct HeldNote {
+    pub key: u8,
+    pub velocity: KeyVelocity,
+    pub clap_note_id: Option<i32>,
+}
+
+/// Keys held in monophonic mode, in order of key press
+#[derive(Debug)]
+pub struct MonophonicNoteStack {
+    notes: IndexMap<u8, HeldNote>,
+}
+
+impl Default for MonophonicNoteStack {
+    fn default() -> Self {
+        let mut notes = IndexMap::default();
+
+        notes.reserve(128);
+
+        Self { notes }
+    }
+}
+
+impl MonophonicNoteStack {
+    /// Add note, moving it to the top of the stack if key is already held
+    pub fn press(&mut self, note: HeldNote) {
+        self.notes.shift_remove(&note.key);
+        self.notes.insert(note.key, note);
+    }
+
+    /// Remove key, returning its note if it was held
+    pub fn release(&mut self, key: u8) -> Option<HeldNote> {
+        self.notes.shift_remove(&key)
+    }
+
+    pub fn get(&self, key: u8) -> Option<&HeldNote> {
+        self.notes.get(&key)
+    }
+
+    /// Held note that the monophonic voice should play
+    pub fn current(&self, priority: MonoNotePriority) -> Option<HeldNote> {
+        match priority {
+            MonoNotePriority::Last => self.notes.last().map(|(_, note)| *note),
+            MonoNotePriority::Low => self.notes.values().min_by_key(|note| note.key).copied(),
+            MonoNotePriority::High => self.notes.values().max_by_key(|note| note.key).copied(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.notes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(key: u8) -> HeldNote {
+        HeldNote {
+            key,
+            velocity: KeyVelocity::default(),
+            clap_note_id: None,
+        }
+    }
+
+    fn current_key(stack: &MonophonicNoteStack, priority: MonoNotePriority) -> Option<u8> {
+        stack.current(priority).map(|note| note.key)
+    }
+
+    #[test]
+    fn test_last_note_priority() {
+        use MonoNotePriority::Last;
+
+        let mut stack = MonophonicNoteStack::default();
+
+        stack.press(note(60));
+        stack.press(note(64));
+        stack.press(note(62));
+
+        assert_eq!(current_key(&stack, Last), Some(62));
+
+        // Releasing key that is not sounding doesn't change current note
+        stack.release(64);
+        assert_eq!(current_key(&stack, Last), Some(62));
+
+        // Pressing held key again moves it to the top
+        stack.press(note(60));
+        assert_eq!(current_key(&stack, Last), Some(60));
+
+        stack.release(60);
+        assert_eq!(current_key(&stack, Last), Some(62));
+
+        stack.release(62);
+        assert_eq!(current_key(&stack, Last), None);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_low_and_high_note_priority() {
+        use MonoNotePriority::{High, Low};
+
+        let mut stack = MonophonicNoteStack::default();
+
+        stack.press(note(64));
+        stack.press(note(60));
+        stack.press(note(67));
+
+        assert_eq!(current_key(&stack, Low), Some(60));
+        assert_eq!(current_key(&stack, High), Some(67));
+
+        stack.release(60);
+        assert_eq!(current_key(&stack, Low), Some(64));
+
+        stack.release(67);
+        assert_eq!(current_key(&stack, High), Some(64));
+
+        stack.release(64);
+        assert_eq!(current_key(&stack, Low), None);
+        assert_eq!(current_key(&stack, High), None);
+    }
+
+    #[test]
+    fn test_held_note_keeps_velocity() {
+        let mut stack = MonophonicNoteStack::default();
+
+        stack.press(HeldNote {
+            key: 60,
+            velocity: KeyVelocity(0.25),
+            clap_note_id: Some(1),
+        });
+        stack.press(HeldNote {
+            key: 62,
+            velocity: KeyVelocity(1.0),
+            clap_note_id: Some(2),
+        });
+
+        stack.release(62);
+
+        let note = stack.current(MonoNotePriority::Last).unwrap();
+
+        assert_eq!(note.velocity.0, 0.25);
+        assert_eq!(note.clap_note_id, Some(1));
+    }
+}
@@ -2,6 +2,8 @@ use crate::parameters::ParameterKey;
 
 pub const NUM_OPERATORS: usize = 4;
 pub const NUM_LFOS: usize = 4;
+/// Number of targets per LFO
+pub const NUM_LFO_TARGETS: usize = 4;
 
 pub const OPERATOR_MOD_INDEX_STEPS: [f32; 16] = [
     0.0, 0.01, 0.1, 0.2, 0.5, 1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 35.0, 50.0, 75.0, 100.0, 1000.0,
@@ -69,6 +71,8 @@ pub enum EnvelopeStage {
     Release,
     Ended,
     Kill,
+    /// Fast release of copy of voice that is being restarted
+    Steal,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -87,8 +91,15 @@ pub enum NoteEventInner {
         velocity: f64,
         clap_note_id: i32,
     },
+    /// Key and note id are None for wildcard events
     ClapNoteOff {
-        key: u8,
+        key: Option<u8>,
+        clap_note_id: Option<i32>,
+    },
+    /// Stop note immediately. Key and note id are None for wildcard events
+    ClapNoteChoke {
+        key: Option<u8>,
+        clap_note_id: Option<i32>,
     },
     ClapNotePressure {
         key: u8,
@@ -4,14 +4,19 @@ use iced_baseview::widget::canvas::{
 };
 use iced_baseview::{Color, Element, Length, Point, Rectangle, Size};
 
+use crate::parameters::dc_blocker::DcBlockerValue;
+use crate::parameters::gain_compensation::GainCompensationValue;
 use crate::parameters::glide_bpm_sync::GlideBpmSyncValue;
 use crate::parameters::glide_mode::{GlideMode, GlideModeValue};
 use crate::parameters::glide_retrigger::GlideRetriggerValue;
+use crate::parameters::lfo_global::LfoGlobalValue;
 use crate::parameters::lfo_key_sync::LfoKeySyncValue;
 use crate::parameters::lfo_mode::LfoMode;
 use crate::parameters::list::MasterParameter;
+use crate::parameters::mod_out_scaling::{ModOutScaling, ModOutScalingValue};
 use crate::parameters::operator_envelope::OperatorEnvelopeGroupValue;
-use crate::parameters::voice_mode::{VoiceMode, VoiceModeValue};
+use crate::parameters::operator_panning_lfo_mode::{OperatorPanningLfoModeValue, PanningLfoMode};
+use crate::parameters::operator_panning_mode::{OperatorPanningModeValue, PanningMode};
 use crate::parameters::{
     LfoActiveValue, LfoBpmSyncValue, LfoModeValue, LfoParameter, OperatorActiveValue,
     OperatorParameter, Parameter, ParameterValue, WrappedParameter,
@@ -57,6 +62,85 @@ pub fn operator_mute_button<H: GuiSyncHandle>(
     )
 }
 
+/// GUI-only toggle, since auditioning is a temporary routing override
+pub fn operator_audition_button(operator_index: usize, on: bool) -> BooleanButton {
+    let f: fn(bool) -> Message = match operator_index {
+        0 => |on| Message::ToggleOperatorAudition {
+            operator_index: 0,
+            on,
+        },
+        1 => |on| Message::ToggleOperatorAudition {
+            operator_index: 1,
+            on,
+        },
+        2 => |on| Message::ToggleOperatorAudition {
+            operator_index: 2,
+            on,
+        },
+        _ => |on| Message::ToggleOperatorAudition {
+            operator_index: 3,
+            on,
+        },
+    };
+
+    BooleanButton::new_with_message(
+        on,
+        "A",
+        LINE_HEIGHT,
+        LINE_HEIGHT,
+        f,
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn operator_panning_mode_button<H: GuiSyncHandle>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::PanningMode),
+        "M/S",
+        LINE_HEIGHT * 2,
+        LINE_HEIGHT,
+        |v| OperatorPanningModeValue::new_from_patch(v).get() == PanningMode::MidSide,
+        |b| {
+            let mode = if b {
+                PanningMode::MidSide
+            } else {
+                PanningMode::LeftRight
+            };
+
+            OperatorPanningModeValue::new_from_audio(mode).to_patch()
+        },
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn operator_panning_lfo_mode_button<H: GuiSyncHandle>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::PanningLfoMode),
+        "SPR",
+        LINE_HEIGHT * 2,
+        LINE_HEIGHT,
+        |v| OperatorPanningLfoModeValue::new_from_patch(v).get() == PanningLfoMode::Spread,
+        |b| {
+            let mode = if b {
+                PanningLfoMode::Spread
+            } else {
+                PanningLfoMode::Normal
+            };
+
+            OperatorPanningLfoModeValue::new_from_audio(mode).to_patch()
+        },
+        BooleanButtonStyle::Regular,
+    )
+}
+
 pub fn lfo_bpm_sync_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> BooleanButton {
     BooleanButton::new(
         sync_handle,
@@ -83,6 +167,19 @@ pub fn lfo_key_sync_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize)
     )
 }
 
+pub fn lfo_global_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::Global),
+        "G",
+        LINE_HEIGHT,
+        LINE_HEIGHT,
+        |v| LfoGlobalValue::new_from_patch(v).get(),
+        |on| LfoGlobalValue::new_from_audio(on).to_patch(),
+        BooleanButtonStyle::Regular,
+    )
+}
+
 pub fn lfo_mode_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> BooleanButton {
     BooleanButton::new(
         sync_handle,
@@ -165,27 +262,6 @@ pub fn envelope_group_b_button<H: GuiSyncHandle>(
     )
 }
 
-pub fn voice_mode_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButton {
-    BooleanButton::new(
-        sync_handle,
-        Parameter::Master(MasterParameter::VoiceMode),
-        "POLY",
-        LINE_HEIGHT * 2 + 6,
-        LINE_HEIGHT,
-        |v| VoiceModeValue::new_from_patch(v).get() == VoiceMode::Polyphonic,
-        |b| {
-            let mode = if b {
-                VoiceMode::Polyphonic
-            } else {
-                VoiceMode::Monophonic
-            };
-
-            VoiceModeValue::new_from_audio(mode).to_patch()
-        },
-        BooleanButtonStyle::Regular,
-    )
-}
-
 pub fn glide_bpm_sync_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButton {
     BooleanButton::new(
         sync_handle,
@@ -229,13 +305,99 @@ pub fn glide_retrigger_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButto
     )
 }
 
+pub fn dc_blocker_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Master(MasterParameter::DcBlocker),
+        "DC",
+        LINE_HEIGHT * 2,
+        LINE_HEIGHT,
+        |v| DcBlockerValue::new_from_patch(v).get(),
+        |b| DcBlockerValue::new_from_audio(b).to_patch(),
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn gain_compensation_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Master(MasterParameter::GainCompensation),
+        "AGC",
+        LINE_HEIGHT * 2,
+        LINE_HEIGHT,
+        |v| GainCompensationValue::new_from_patch(v).get(),
+        |b| GainCompensationValue::new_from_audio(b).to_patch(),
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn mod_out_scaling_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Master(MasterParameter::ModOutScaling),
+        "DX7",
+        LINE_HEIGHT * 2,
+        LINE_HEIGHT,
+        |v| ModOutScalingValue::new_from_patch(v).get() == ModOutScaling::Dx7,
+        |b| {
+            if b {
+                ModOutScalingValue::new_from_audio(ModOutScaling::Dx7).to_patch()
+            } else {
+                ModOutScalingValue::new_from_audio(ModOutScaling::Native).to_patch()
+            }
+        },
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn patch_preview_button(on: bool) -> BooleanButton {
+    BooleanButton::new_with_message(
+        on,
+        "PREV",
+        LINE_HEIGHT * 2 + 6,
+        LINE_HEIGHT,
+        Message::TogglePatchPreview,
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn envelope_link_button(on: bool) -> BooleanButton {
+    BooleanButton::new_with_message(
+        on,
+        "LINK",
+        LINE_HEIGHT * 2 + 6,
+        LINE_HEIGHT,
+        Message::ToggleEnvelopeLink,
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn logging_button(on: bool) -> BooleanButton {
+    BooleanButton::new_with_message(
+        on,
+        "LOG",
+        LINE_HEIGHT * 2,
+        LINE_HEIGHT,
+        Message::ToggleLogging,
+        BooleanButtonStyle::Regular,
+    )
+}
+
+enum Target {
+    Parameter {
+        parameter: WrappedParameter,
+        patch_value_to_is_on: fn(f32) -> bool,
+        is_on_to_patch_value: fn(bool) -> f32,
+    },
+    /// GUI-only state, not backed by a parameter
+    Message(fn(bool) -> Message),
+}
+
 pub struct BooleanButton {
-    parameter: WrappedParameter,
+    target: Target,
     on: bool,
     cache: Cache,
     bounds_path: Path,
-    patch_value_to_is_on: fn(f32) -> bool,
-    is_on_to_patch_value: fn(bool) -> f32,
     button_style: BooleanButtonStyle,
     text: &'static str,
     width: u16,
@@ -254,19 +416,47 @@ impl BooleanButton {
         button_style: BooleanButtonStyle,
     ) -> Self {
         let parameter = parameter.into();
+        let on = f(sync_handle.get_parameter(parameter));
+
+        let target = Target::Parameter {
+            parameter,
+            patch_value_to_is_on: f,
+            is_on_to_patch_value: g,
+        };
+
+        Self::new_inner(target, on, text, width, height, button_style)
+    }
+
+    /// Create button that emits a message with the new state when toggled
+    pub fn new_with_message(
+        on: bool,
+        text: &'static str,
+        width: u16,
+        height: u16,
+        f: fn(bool) -> Message,
+        button_style: BooleanButtonStyle,
+    ) -> Self {
+        Self::new_inner(Target::Message(f), on, text, width, height, button_style)
+    }
 
+    fn new_inner(
+        target: Target,
+        on: bool,
+        text: &'static str,
+        width: u16,
+        height: u16,
+        button_style: BooleanButtonStyle,
+    ) -> Self {
         let bounds_path = Path::rectangle(
             Point::new(0.5, 0.5),
             Size::new((width - 1) as f32, (height - 1) as f32),
         );
 
         Self {
-            parameter,
-            on: f(sync_handle.get_parameter(parameter)),
+            target,
+            on,
             cache: Cache::new(),
             bounds_path,
-            patch_value_to_is_on: f,
-            is_on_to_patch_value: g,
             button_style,
             text,
             width,
@@ -275,7 +465,21 @@ impl BooleanButton {
     }
 
     pub fn set_value(&mut self, value: f32) {
-        self.on = (self.patch_value_to_is_on)(value);
+        if let Target::Parameter {
+            patch_value_to_is_on,
+            ..
+        } = self.target
+        {
+            self.set_on(patch_value_to_is_on(value));
+        }
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    pub fn set_on(&mut self, on: bool) {
+        self.on = on;
 
         self.cache.clear();
     }
@@ -388,10 +592,16 @@ impl Program<Message, Theme> for BooleanButton {
                 iced_baseview::mouse::Button::Left | iced_baseview::mouse::Button::Right,
             )) if state.click_started => {
                 if state.cursor_within_bounds {
-                    let message = {
-                        let patch_value = (self.is_on_to_patch_value)(!self.on);
-
-                        Message::ChangeSingleParameterImmediate(self.parameter, patch_value)
+                    let message = match self.target {
+                        Target::Parameter {
+                            parameter,
+                            is_on_to_patch_value,
+                            ..
+                        } => Message::ChangeSingleParameterImmediate(
+                            parameter,
+                            is_on_to_patch_value(!self.on),
+                        ),
+                        Target::Message(f) => f(!self.on),
                     };
 
                     (event::Status::Captured, Some(message))
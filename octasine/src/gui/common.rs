@@ -1,11 +1,12 @@
 use std::borrow::Cow;
 
 use iced_baseview::{
+    alignment::Horizontal,
     widget::Column,
     widget::Row,
     widget::Space,
-    widget::{tooltip::Position, Container, Tooltip},
-    Element, Length,
+    widget::{tooltip::Position, Button, Container, Text, Tooltip},
+    Element, Font, Length,
 };
 
 use super::LINE_HEIGHT;
@@ -92,3 +93,28 @@ pub fn tooltip<'a>(
         .style(ContainerStyle::Tooltip)
         .padding(theme.tooltip_padding())
 }
+
+/// Small button with single-character text
+pub fn button_with_tooltip<'a>(
+    theme: &Theme,
+    button_font: Font,
+    button_text: &'static str,
+    button_message: Message,
+    tooltip_text: &'static str,
+) -> Element<'a, Message, Theme> {
+    tooltip(
+        theme,
+        tooltip_text,
+        Position::Top,
+        Button::new(
+            Text::new(button_text)
+                .font(button_font)
+                .height(Length::Fixed(LINE_HEIGHT.into()))
+                .width(Length::Fixed(10.0))
+                .horizontal_alignment(Horizontal::Center),
+        )
+        .on_press(button_message)
+        .padding(theme.button_padding()),
+    )
+    .into()
+}
@@ -1,5 +1,5 @@
 use iced_baseview::{
-    alignment::Horizontal,
+    alignment::{Horizontal, Vertical},
     widget::tooltip::Position,
     widget::Button,
     widget::Container,
@@ -16,32 +16,46 @@ use crate::{
         glide_time::GlideTimeValue,
         list::{MasterParameter, Parameter},
         master_pitch_bend_range::{MasterPitchBendRangeDownValue, MasterPitchBendRangeUpValue},
+        output_mode::{OutputModeValue, OUTPUT_MODE_STEPS},
+        velocity_mod::VelocityModAmountValue,
         velocity_sensitivity::VelocitySensitivityValue,
-        MasterFrequencyValue, MasterVolumeValue, ParameterValue,
+        MasterFrequencyValue, MasterPanValue, MasterVolumeValue, ParameterSection, ParameterValue,
+    },
+    settings::Settings,
+    sync::{
+        patch_defaults::{overrides_patch_default, patch_default_overrides},
+        GuiSyncHandle,
     },
-    sync::GuiSyncHandle,
     utils::get_version_info,
 };
 
 use super::{
     boolean_button::{
-        glide_bpm_sync_button, glide_mode_button, glide_retrigger_button, BooleanButton,
+        dc_blocker_button, envelope_link_button, gain_compensation_button, glide_bpm_sync_button,
+        glide_mode_button, glide_retrigger_button, logging_button, mod_out_scaling_button,
+        BooleanButton,
+    },
+    common::{
+        button_with_tooltip, container_l1, container_l2, container_l3, space_l3, tooltip,
+        triple_container,
     },
-    common::{container_l1, container_l2, container_l3, space_l3, tooltip, triple_container},
     knob::{self, OctaSineKnob},
+    lfo_target_picker::LfoTargetPicker,
     mod_matrix::ModulationMatrix,
     patch_picker::PatchPicker,
-    style::{container::ContainerStyle, Theme},
-    Message, FONT_SIZE, LINE_HEIGHT,
+    style::{button::ButtonStyle, container::ContainerStyle, Theme},
+    Message, ModalAction, FONT_SIZE, LINE_HEIGHT,
 };
 
 pub struct CornerWidgets {
     pub alternative_controls: bool,
     pub master_volume: OctaSineKnob<MasterVolumeValue>,
     pub master_frequency: OctaSineKnob<MasterFrequencyValue>,
+    pub master_pan: OctaSineKnob<MasterPanValue>,
     pub volume_velocity_sensitivity: OctaSineKnob<VelocitySensitivityValue>,
     pub modulation_matrix: ModulationMatrix,
     pub patch_picker: PatchPicker,
+    pub patch_preview: bool,
     pub master_pitch_bend_up: OctaSineKnob<MasterPitchBendRangeUpValue>,
     pub master_pitch_bend_down: OctaSineKnob<MasterPitchBendRangeDownValue>,
     pub glide_time: OctaSineKnob<GlideTimeValue>,
@@ -49,33 +63,74 @@ pub struct CornerWidgets {
     pub glide_mode: BooleanButton,
     pub glide_retrigger: BooleanButton,
     pub glide_active: f32,
+    pub output_mode: f32,
+    pub dc_blocker: BooleanButton,
+    pub gain_compensation: BooleanButton,
+    pub mod_out_scaling: BooleanButton,
+    pub envelope_link: BooleanButton,
+    pub logging: BooleanButton,
+    pub velocity_mod_target: LfoTargetPicker,
+    pub velocity_mod_amount: OctaSineKnob<VelocityModAmountValue>,
+    /// DSP load published by audio thread
+    pub dsp_load: f32,
+    /// Number of active voices published by audio thread
+    pub num_active_voices: usize,
+    /// Audio processing panicked and plugin is outputting silence
+    pub bypassed_due_to_error: bool,
+    /// Output exceeded full scale recently
+    pub clipping: bool,
+    /// Output exceeded full scale since clip indicator was last reset
+    pub clip_latched: bool,
+    /// Parameters for which current patch overrides defaults set in
+    /// settings
+    pub overridden_patch_defaults: Vec<Parameter>,
 }
 
 impl CornerWidgets {
     pub fn new<H: GuiSyncHandle>(sync_handle: &H) -> Self {
         let master_volume = knob::master_volume(sync_handle);
         let master_frequency = knob::master_frequency(sync_handle);
+        let master_pan = knob::master_pan(sync_handle);
         let volume_velocity_sensitivity = knob::master_velocity_sensitivity(sync_handle);
         let modulation_matrix = ModulationMatrix::new(sync_handle);
-        let patch_picker = PatchPicker::new(sync_handle);
+        let patch_picker = PatchPicker::new(sync_handle, false);
         let master_pitch_bend_up = knob::master_pitch_bend_range_up(sync_handle);
         let master_pitch_bend_down = knob::master_pitch_bend_range_down(sync_handle);
         let glide_time = knob::glide_time(sync_handle);
+        let velocity_mod_target = LfoTargetPicker::new_velocity_mod(sync_handle);
+        let velocity_mod_amount = knob::velocity_mod_amount(sync_handle);
 
         let glide_active =
             sync_handle.get_parameter(Parameter::Master(MasterParameter::GlideActive).into());
+        let output_mode =
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::OutputMode).into());
 
         let glide_bpm_sync = glide_bpm_sync_button(sync_handle);
         let glide_mode = glide_mode_button(sync_handle);
         let glide_retrigger = glide_retrigger_button(sync_handle);
+        let dc_blocker = dc_blocker_button(sync_handle);
+        let gain_compensation = gain_compensation_button(sync_handle);
+        let mod_out_scaling = mod_out_scaling_button(sync_handle);
+        let envelope_link = envelope_link_button(false);
+        let logging = logging_button(!Settings::load_or_default().disable_logging);
+
+        let overridden_patch_defaults = patch_default_overrides()
+            .iter()
+            .map(|(parameter, _)| *parameter)
+            .filter(|parameter| {
+                overrides_patch_default(*parameter, sync_handle.get_parameter((*parameter).into()))
+            })
+            .collect();
 
         Self {
             alternative_controls: false,
             master_volume,
             master_frequency,
+            master_pan,
             volume_velocity_sensitivity,
             modulation_matrix,
             patch_picker,
+            patch_preview: false,
             master_pitch_bend_up,
             master_pitch_bend_down,
             glide_active,
@@ -83,6 +138,29 @@ impl CornerWidgets {
             glide_bpm_sync,
             glide_mode,
             glide_retrigger,
+            output_mode,
+            dc_blocker,
+            gain_compensation,
+            mod_out_scaling,
+            envelope_link,
+            logging,
+            velocity_mod_target,
+            velocity_mod_amount,
+            dsp_load: 0.0,
+            num_active_voices: 0,
+            bypassed_due_to_error: false,
+            clipping: false,
+            clip_latched: false,
+            overridden_patch_defaults,
+        }
+    }
+
+    /// Track whether current patch overrides defaults set in settings
+    pub fn update_overridden_patch_defaults(&mut self, parameter: Parameter, value: f32) {
+        self.overridden_patch_defaults.retain(|p| *p != parameter);
+
+        if overrides_patch_default(parameter, value) {
+            self.overridden_patch_defaults.push(parameter);
         }
     }
 
@@ -92,6 +170,40 @@ impl CornerWidgets {
         self.glide_bpm_sync.theme_changed();
         self.glide_mode.theme_changed();
         self.glide_retrigger.theme_changed();
+        self.dc_blocker.theme_changed();
+        self.gain_compensation.theme_changed();
+        self.mod_out_scaling.theme_changed();
+        self.envelope_link.theme_changed();
+        self.logging.theme_changed();
+    }
+
+    /// Badge shown when current patch overrides voice settings that new
+    /// patches start with according to settings
+    fn patch_defaults_badge(&self, theme: &Theme) -> Element<'_, Message, Theme> {
+        if self.overridden_patch_defaults.is_empty() {
+            return Space::with_height(Length::Fixed(LINE_HEIGHT.into())).into();
+        }
+
+        let names = self
+            .overridden_patch_defaults
+            .iter()
+            .map(|parameter| parameter.name().to_lowercase())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        tooltip(
+            theme,
+            format!("Patch overrides default {} from settings", names),
+            Position::Bottom,
+            Text::new("OVERRIDE")
+                .size(FONT_SIZE - 2)
+                .font(theme.font_regular())
+                .height(Length::Fixed(LINE_HEIGHT.into()))
+                .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)))
+                .horizontal_alignment(Horizontal::Center)
+                .vertical_alignment(Vertical::Center),
+        )
+        .into()
     }
 
     pub fn view(&self, theme: &Theme) -> Element<'_, Message, Theme> {
@@ -138,6 +250,52 @@ impl CornerWidgets {
                 .on_press(Message::SwitchTheme)
                 .padding(theme.button_padding()),
             );
+            let reset_button = button_with_tooltip(
+                theme,
+                theme.font_regular(),
+                "R",
+                Message::ResetSection(ParameterSection::Master),
+                "Reset master parameters to init patch values",
+            );
+
+            let (status_text, status_tooltip) = if self.bypassed_due_to_error {
+                (
+                    "ERROR".to_string(),
+                    "Audio processing stopped due to an internal error, so OctaSine is\n\
+                     outputting silence. Please reload the plugin and report the issue\n\
+                     (details can be found in the log file).",
+                )
+            } else {
+                (
+                    format!(
+                        "{} V / {:.0}%",
+                        self.num_active_voices,
+                        self.dsp_load * 100.0
+                    ),
+                    "Active voices / DSP load (processing time relative to real-time budget).\n\
+                     Click to show list of active voices.",
+                )
+            };
+
+            let clip_indicator = tooltip(
+                theme,
+                "Output clip indicator. Lights up when output exceeds full scale\n\
+                 (0 dBFS) and stays lit until clicked. Filled while clipping\n\
+                 happened during the last few seconds.",
+                Position::Top,
+                Button::new(
+                    Text::new("CLIP")
+                        .size(FONT_SIZE - 2)
+                        .font(theme.font_regular())
+                        .horizontal_alignment(Horizontal::Center),
+                )
+                .padding([0, 2])
+                .style(ButtonStyle::ClipIndicator {
+                    clipping: self.clipping,
+                    latched: self.clip_latched,
+                })
+                .on_press(Message::ResetClipIndicator),
+            );
 
             Container::new(
                 Column::new()
@@ -145,23 +303,57 @@ impl CornerWidgets {
                     .width(Length::Fill)
                     .push(controls_button)
                     .push(Space::with_height(Length::Fixed(f32::from(
-                        LINE_HEIGHT / 2 + LINE_HEIGHT / 4,
+                        LINE_HEIGHT / 4,
                     ))))
                     .push(tooltip(
                         theme,
                         get_info_text(),
                         Position::Top,
-                        Text::new("OctaSine")
-                            .size(FONT_SIZE * 3 / 2)
-                            .height(Length::Fixed(f32::from(FONT_SIZE * 3 / 2)))
-                            .width(Length::Fill)
-                            .font(theme.font_heading())
-                            .horizontal_alignment(Horizontal::Center),
+                        Button::new(
+                            Text::new("OctaSine")
+                                .size(FONT_SIZE * 3 / 2)
+                                .height(Length::Fixed(f32::from(FONT_SIZE * 3 / 2)))
+                                .width(Length::Fill)
+                                .font(theme.font_heading())
+                                .horizontal_alignment(Horizontal::Center),
+                        )
+                        .padding(0)
+                        .width(Length::Fill)
+                        .style(ButtonStyle::Value)
+                        .on_press(Message::ModalOpen(ModalAction::About)),
                     ))
+                    .push(
+                        Row::new()
+                            .align_items(Alignment::Center)
+                            .height(Length::Fixed(LINE_HEIGHT.into()))
+                            .push(tooltip(
+                                theme,
+                                status_tooltip,
+                                Position::Top,
+                                Button::new(
+                                    Text::new(status_text)
+                                        .size(FONT_SIZE - 2)
+                                        .font(theme.font_regular())
+                                        .width(Length::Fill)
+                                        .horizontal_alignment(Horizontal::Center),
+                                )
+                                .padding(0)
+                                .width(Length::Fill)
+                                .style(ButtonStyle::Value)
+                                .on_press(Message::ModalOpen(ModalAction::Voices)),
+                            ))
+                            .push(clip_indicator),
+                    )
                     .push(Space::with_height(Length::Fixed(f32::from(
-                        LINE_HEIGHT / 2 + LINE_HEIGHT / 4,
+                        LINE_HEIGHT / 4,
                     ))))
-                    .push(theme_button),
+                    .push(
+                        Row::new()
+                            .align_items(Alignment::Center)
+                            .push(theme_button)
+                            .push(Space::with_width(Length::Fixed(3.0)))
+                            .push(reset_button),
+                    ),
             )
             .width(Length::Fixed(f32::from(LINE_HEIGHT * 5)))
             .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
@@ -232,11 +424,109 @@ impl CornerWidgets {
             )
         };
 
+        let output_controls = {
+            let output_mode_title = tooltip(
+                theme,
+                "Output stage\n\nCLIP = hard clip at extreme values\nSOFT = tanh soft clip\nLIMIT = lookahead limiter",
+                Position::Top,
+                Text::new("OUTPUT")
+                    .horizontal_alignment(Horizontal::Center)
+                    .font(theme.font_bold())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(LINE_HEIGHT * 4),
+            );
+
+            let dc_blocker = tooltip(
+                theme,
+                "Remove DC offset from output",
+                Position::Top,
+                self.dc_blocker.view(),
+            );
+
+            let gain_compensation = tooltip(
+                theme,
+                "Automatic gain compensation: adjust output level by estimated\npatch loudness (operator mix out levels and velocity sensitivity)",
+                Position::Top,
+                self.gain_compensation.view(),
+            );
+
+            let mod_out_scaling = tooltip(
+                theme,
+                "Interpret operator mod out as DX7-style output levels (0-99)",
+                Position::Top,
+                self.mod_out_scaling.view(),
+            );
+
+            let envelope_link = tooltip(
+                theme,
+                "Link envelopes: changing a stage duration scales the same\nstage on all other operators proportionally",
+                Position::Top,
+                self.envelope_link.view(),
+            );
+
+            let output_mode_picker = PickList::new(
+                OUTPUT_MODE_STEPS,
+                Some(OutputModeValue::new_from_patch(self.output_mode).get()),
+                move |option| {
+                    let v = OutputModeValue::new_from_audio(option).to_patch();
+
+                    Message::ChangeSingleParameterImmediate(
+                        Parameter::Master(MasterParameter::OutputMode).into(),
+                        v,
+                    )
+                },
+            )
+            .font(theme.font_regular())
+            .text_size(FONT_SIZE)
+            .padding(theme.picklist_padding())
+            .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)));
+
+            Container::new(
+                Column::new()
+                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                    .align_items(Alignment::Center)
+                    .push(output_mode_title)
+                    .push(Space::with_height(LINE_HEIGHT / 2))
+                    .push(output_mode_picker)
+                    .push(Space::with_height(LINE_HEIGHT / 2))
+                    .push(
+                        Row::new()
+                            .push(dc_blocker)
+                            .push(Space::with_width(Length::Fixed(4.0)))
+                            .push(gain_compensation),
+                    )
+                    .push(Space::with_height(LINE_HEIGHT / 2))
+                    .push(
+                        Row::new()
+                            .push(mod_out_scaling)
+                            .push(Space::with_width(Length::Fixed(4.0)))
+                            .push(envelope_link),
+                    ),
+            )
+        };
+
         let top: Element<Message, Theme> = if !self.alternative_controls {
             Row::new()
                 .push(mod_matrix)
                 .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
-                .push(triple_container(self.patch_picker.view(theme)))
+                .push(triple_container(
+                    Row::new()
+                        .push(self.patch_picker.view(theme))
+                        .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
+                        .push(
+                            Column::new()
+                                .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
+                                .push(self.patch_picker.voice_mode_view(theme))
+                                .push(self.patch_defaults_badge(theme))
+                                .push(Space::with_height(Length::Fill))
+                                .push(tooltip(
+                                    theme,
+                                    "Algorithm (current operator routing)",
+                                    Position::Bottom,
+                                    self.modulation_matrix.algorithm_diagram_view(),
+                                )),
+                        ),
+                ))
                 .into()
         } else {
             Row::new()
@@ -250,19 +540,29 @@ impl CornerWidgets {
                         .push(space_l3())
                         .push(container_l3(self.master_pitch_bend_down.view(theme)))
                         .push(space_l3())
-                        .push(container_l3(Space::with_width(LINE_HEIGHT * 4))),
+                        .push(container_l3(self.master_pan.view(theme))),
                 )))
                 .into()
         };
 
+        let (middle_controls, right_controls) = if !self.alternative_controls {
+            (voice_buttons, self.glide_time.view(theme))
+        } else {
+            let velocity_mod = self
+                .velocity_mod_amount
+                .view_with_header(theme, Some(self.velocity_mod_target.view(theme)));
+
+            (output_controls, velocity_mod)
+        };
+
         let bottom = Row::new()
             .push(container_l1(container_l2(
                 Row::new()
                     .push(container_l3(self.master_volume.view(theme)))
                     .push(space_l3())
-                    .push(container_l3(voice_buttons))
+                    .push(container_l3(middle_controls))
                     .push(space_l3())
-                    .push(container_l3(self.glide_time.view(theme))),
+                    .push(container_l3(right_controls)),
             )))
             .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
             .push(triple_container(logo));
@@ -275,7 +575,7 @@ impl CornerWidgets {
     }
 }
 
-fn get_info_text() -> String {
+pub fn get_info_text() -> String {
     format!(
         "OctaSine frequency modulation synthesizer
 Site: OctaSine.com
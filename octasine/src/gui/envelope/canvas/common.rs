@@ -1,10 +1,12 @@
 use iced_baseview::widget::canvas::{path, Frame, Path, Stroke};
-use iced_baseview::{Color, Point, Size, Vector};
+use iced_baseview::{Color, Point, Rectangle, Size, Vector};
+use serde::{Deserialize, Serialize};
 
 use crate::audio::voices::envelopes::VoiceOperatorVolumeEnvelope;
 use crate::audio::voices::log10_table::Log10Table;
 use crate::gui::style::Theme;
 use crate::gui::{SnapPoint, LINE_HEIGHT};
+use crate::parameters::operator_envelope::ENVELOPE_MAX_DURATION;
 
 pub const WIDTH: u16 = LINE_HEIGHT * 20;
 pub const HEIGHT: u16 = LINE_HEIGHT * 5;
@@ -19,8 +21,14 @@ pub const DRAGGER_RADIUS: f32 = 4.0;
 pub const ENVELOPE_PATH_SCALE_X: f32 = (WIDTH as f32 - 2.0 * LINE_HEIGHT as f32) / WIDTH as f32;
 pub const ENVELOPE_PATH_SCALE_Y: f32 = 1.0 - (1.0 / 8.0) - (1.0 / 16.0);
 
-pub const TOTAL_DURATION: f32 = 3.0;
-pub const MIN_VIEWPORT_FACTOR: f32 = 1.0 / 64.0;
+/// Total duration in seconds of attack, decay and release stages at maximum
+/// length
+pub const TOTAL_DURATION: f32 = 3.0 * ENVELOPE_MAX_DURATION as f32;
+pub const MIN_VIEWPORT_FACTOR: f32 = 1.0 / 512.0;
+
+/// Maximum number of beat grid lines visible at once
+pub const MAX_VISIBLE_BEAT_GRID_LINES: f32 = 64.0;
+pub const BEATS_PER_BAR: usize = 4;
 
 pub const FIXED_VIEWPORT_FACTORS: &[f32] = &[
     1.0,
@@ -30,8 +38,114 @@ pub const FIXED_VIEWPORT_FACTORS: &[f32] = &[
     1.0 / 16.0,
     1.0 / 32.0,
     1.0 / 64.0,
+    1.0 / 128.0,
+    1.0 / 256.0,
+    1.0 / 512.0,
 ];
 
+/// Ranges in dB below full level selectable for decibel scale, from zoomed
+/// out to zoomed in
+pub const DECIBEL_RANGES: &[u8] = &[96, 72, 48, 24];
+pub const DEFAULT_DECIBEL_RANGE: u8 = 48;
+
+/// Number of line segments per stage path when using decibel scale, since
+/// bezier curves approximate it poorly
+const DECIBEL_PATH_SEGMENTS: usize = 32;
+
+/// Vertical axis scale for envelope levels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum EnvelopeLevelScale {
+    #[default]
+    Linear,
+    /// Decibel scale spanning given range below full level, making
+    /// low-level decay tails easier to edit. Smaller ranges zoom in
+    /// vertically.
+    Decibels(u8),
+}
+
+impl EnvelopeLevelScale {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Linear => Self::Decibels(DEFAULT_DECIBEL_RANGE),
+            Self::Decibels(_) => Self::Linear,
+        }
+    }
+
+    pub fn zoomed_in(self) -> Self {
+        match self {
+            Self::Linear => self,
+            Self::Decibels(range) => DECIBEL_RANGES
+                .iter()
+                .copied()
+                .find(|r| *r < range)
+                .map_or(self, Self::Decibels),
+        }
+    }
+
+    pub fn zoomed_out(self) -> Self {
+        match self {
+            Self::Linear => self,
+            Self::Decibels(range) => DECIBEL_RANGES
+                .iter()
+                .rev()
+                .copied()
+                .find(|r| *r > range)
+                .map_or(self, Self::Decibels),
+        }
+    }
+
+    /// Vertical position of level, from 0.0 (bottom) to 1.0 (top)
+    pub fn level_to_y_fraction(self, level: f32) -> f32 {
+        match self {
+            Self::Linear => level,
+            Self::Decibels(range) => {
+                let range = f32::from(range);
+
+                if level <= 0.0 {
+                    0.0
+                } else {
+                    ((20.0 * level.log10() + range) / range).max(0.0).min(1.0)
+                }
+            }
+        }
+    }
+
+    /// Level at vertical position. Bottom maps to silence.
+    pub fn y_fraction_to_level(self, y_fraction: f32) -> f32 {
+        let y_fraction = y_fraction.max(0.0).min(1.0);
+
+        match self {
+            Self::Linear => y_fraction,
+            Self::Decibels(_) if y_fraction == 0.0 => 0.0,
+            Self::Decibels(range) => {
+                let db = (y_fraction - 1.0) * f32::from(range);
+
+                10.0f32.powf(db / 20.0)
+            }
+        }
+    }
+
+    /// Interval in dB between level grid lines, if any should be drawn
+    pub fn grid_interval_db(self) -> Option<u8> {
+        match self {
+            Self::Linear => None,
+            Self::Decibels(range) if range <= 24 => Some(6),
+            Self::Decibels(range) if range <= 48 => Some(12),
+            Self::Decibels(_) => Some(24),
+        }
+    }
+}
+
+/// Clickable area in left margin for toggling decibel scale
+pub fn level_scale_toggle_bounds() -> Rectangle {
+    Rectangle {
+        x: 0.0,
+        y: f32::from(LINE_HEIGHT),
+        width: f32::from(LINE_HEIGHT) - 3.0,
+        height: f32::from(LINE_HEIGHT * 2),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Appearance {
     pub background_color: Color,
@@ -40,6 +154,8 @@ pub struct Appearance {
     pub text_color: Color,
     pub time_marker_minor_color: Color,
     pub time_marker_color_major: Color,
+    pub beat_grid_color: Color,
+    pub bar_grid_color: Color,
     pub path_color: Color,
     pub dragger_fill_color_active: Color,
     pub dragger_fill_color_hover: Color,
@@ -47,6 +163,8 @@ pub struct Appearance {
     pub dragger_border_color: Color,
     pub viewport_indicator_border: Color,
     pub viewport_indicator_border_active: Color,
+    /// Selected dragger rings and rubber band rectangle
+    pub selection_color: Color,
 }
 
 pub trait StyleSheet {
@@ -61,6 +179,7 @@ pub struct EnvelopeStagePath {
 impl EnvelopeStagePath {
     pub fn new(
         log10table: &Log10Table,
+        level_scale: EnvelopeLevelScale,
         size: Size,
         total_duration: f32,
         x_offset: f32,
@@ -69,10 +188,25 @@ impl EnvelopeStagePath {
         stage_duration: f32,
         stage_end_value: f32,
     ) -> Self {
+        if let EnvelopeLevelScale::Decibels(_) = level_scale {
+            return Self::new_segmented(
+                log10table,
+                level_scale,
+                size,
+                total_duration,
+                x_offset,
+                start_duration,
+                start_value,
+                stage_duration,
+                stage_end_value,
+            );
+        }
+
         let mut path = path::Builder::new();
 
         let start = Self::calculate_stage_progress_point(
             log10table,
+            level_scale,
             size,
             total_duration,
             x_offset,
@@ -84,6 +218,7 @@ impl EnvelopeStagePath {
         );
         let control_a = Self::calculate_stage_progress_point(
             log10table,
+            level_scale,
             size,
             total_duration,
             x_offset,
@@ -95,6 +230,7 @@ impl EnvelopeStagePath {
         );
         let control_b = Self::calculate_stage_progress_point(
             log10table,
+            level_scale,
             size,
             total_duration,
             x_offset,
@@ -106,6 +242,7 @@ impl EnvelopeStagePath {
         );
         let to = Self::calculate_stage_progress_point(
             log10table,
+            level_scale,
             size,
             total_duration,
             x_offset,
@@ -125,8 +262,53 @@ impl EnvelopeStagePath {
         }
     }
 
+    /// Build path from straight line segments
+    fn new_segmented(
+        log10table: &Log10Table,
+        level_scale: EnvelopeLevelScale,
+        size: Size,
+        total_duration: f32,
+        x_offset: f32,
+        start_duration: f32,
+        start_value: f32,
+        stage_duration: f32,
+        stage_end_value: f32,
+    ) -> Self {
+        let mut path = path::Builder::new();
+        let mut end_point = Point::default();
+
+        for i in 0..=DECIBEL_PATH_SEGMENTS {
+            let point = Self::calculate_stage_progress_point(
+                log10table,
+                level_scale,
+                size,
+                total_duration,
+                x_offset,
+                start_duration,
+                start_value,
+                stage_duration,
+                stage_end_value,
+                i as f32 / DECIBEL_PATH_SEGMENTS as f32,
+            );
+
+            if i == 0 {
+                path.move_to(point);
+            } else {
+                path.line_to(point);
+            }
+
+            end_point = point;
+        }
+
+        Self {
+            path: path.build(),
+            end_point,
+        }
+    }
+
     fn calculate_stage_progress_point(
         log10table: &Log10Table,
+        level_scale: EnvelopeLevelScale,
         size: Size,
         total_duration: f32,
         x_offset: f32,
@@ -149,7 +331,7 @@ impl EnvelopeStagePath {
         // Watch out for point.y.is_nan() when duration = 0.0 here
         let point = Point::new(
             (x_offset + (start_duration + duration) / total_duration) * size.width,
-            size.height * (1.0 - value),
+            size.height * (1.0 - level_scale.level_to_y_fraction(value)),
         );
 
         scale_point(size, point).snap()
@@ -199,7 +381,17 @@ impl EnvelopeDragger {
         self.center = center;
     }
 
-    pub fn draw(&self, frame: &mut Frame, theme: &Theme, status: &EnvelopeDraggerStatus) {
+    pub fn center(&self) -> Point {
+        self.center
+    }
+
+    pub fn draw(
+        &self,
+        frame: &mut Frame,
+        theme: &Theme,
+        status: &EnvelopeDraggerStatus,
+        selected: bool,
+    ) {
         let size = frame.size();
         let appearance = theme.appearance();
 
@@ -232,6 +424,16 @@ impl EnvelopeDragger {
             .with_color(appearance.dragger_border_color);
 
         frame.stroke(&circle_path, stroke);
+
+        if selected {
+            let ring_path = Path::circle(self.center, self.radius + 2.0);
+
+            let stroke = Stroke::default()
+                .with_width(1.0)
+                .with_color(appearance.selection_color);
+
+            frame.stroke(&ring_path, stroke);
+        }
     }
 
     pub fn cursor_overlaps(&self, cursor_position: Point) -> bool {
@@ -264,14 +466,83 @@ pub struct DoubleClickData {
     pub releases: usize,
 }
 
+/// Draggers selected with rubber band, moved together when one of them is
+/// dragged
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EnvelopeSelection {
+    pub attack: bool,
+    pub decay: bool,
+    pub release: bool,
+}
+
+impl EnvelopeSelection {
+    pub fn num_selected(&self) -> usize {
+        usize::from(self.attack) + usize::from(self.decay) + usize::from(self.release)
+    }
+
+    /// Factors by which attack, decay and release durations change when
+    /// selected stage ends are moved by the same amount of time
+    pub fn duration_change_factors(&self) -> [f32; 3] {
+        let attack = f32::from(u8::from(self.attack));
+        let decay = f32::from(u8::from(self.decay));
+        let release = f32::from(u8::from(self.release));
+
+        [attack, decay - attack, release - decay]
+    }
+}
+
+/// Rubber band selection rectangle corners, relative to canvas bounds
+#[derive(Clone, Copy, Debug)]
+pub struct RubberBand {
+    pub from: Point,
+    pub to: Point,
+}
+
+impl RubberBand {
+    pub fn rectangle(&self) -> Rectangle {
+        Rectangle {
+            x: self.from.x.min(self.to.x),
+            y: self.from.y.min(self.to.y),
+            width: (self.from.x - self.to.x).abs(),
+            height: (self.from.y - self.to.y).abs(),
+        }
+    }
+}
+
+/// Durations in seconds are stored in attack, decay, release order
+#[derive(Clone, Copy, Debug)]
+pub struct DraggingSelection {
+    pub from: Point,
+    pub original_durations: [f32; 3],
+    pub original_sustain_volume: f32,
+}
+
+/// Durations in seconds are stored in attack, decay, release order
+#[derive(Clone, Copy, Debug)]
+pub struct ScalingDurations {
+    pub from: Point,
+    pub original_durations: [f32; 3],
+}
+
 #[derive(Default)]
 pub struct EnvelopeCanvasState {
     pub last_cursor_position: Point,
     pub dragging_background_from: Option<DraggingBackground>,
     pub double_click_data: Option<DoubleClickData>,
+    /// Draw beat grid and snap dragged stage ends to it (shift held)
+    pub beat_grid_active: bool,
     pub attack_dragger_status: EnvelopeDraggerStatus,
     pub decay_dragger_status: EnvelopeDraggerStatus,
     pub release_dragger_status: EnvelopeDraggerStatus,
+    pub shift_held: bool,
+    pub command_held: bool,
+    pub selection: EnvelopeSelection,
+    /// Command/ctrl-drag on background
+    pub rubber_band: Option<RubberBand>,
+    /// Dragging one of multiple selected draggers
+    pub dragging_selection: Option<DraggingSelection>,
+    /// Shift-drag on background
+    pub scaling_durations: Option<ScalingDurations>,
 }
 
 pub fn scale_point(size: Size, point: Point) -> Point {
@@ -16,7 +16,7 @@ impl EnvelopeCanvas {
         let total_duration = self.viewport_factor * TOTAL_DURATION;
         let x_offset = self.x_offset / self.viewport_factor;
 
-        let mut time_marker_interval = 0.01 / 4.0;
+        let mut time_marker_interval = 0.01;
 
         loop {
             let num_markers = (total_duration / time_marker_interval) as usize;
@@ -50,7 +50,7 @@ impl EnvelopeCanvas {
                 let text_point = Point::new(x - 10.0, self.size.height);
 
                 let text = Text {
-                    content: format!("{:.1}s", time_marker_interval * 4.0 * i as f32),
+                    content: format!("{:.1}s", time_marker_interval * i as f32),
                     position: scale_point_x(self.size, text_point),
                     font: font_regular,
                     size: FONT_SIZE as f32,
@@ -75,6 +75,102 @@ impl EnvelopeCanvas {
         }
     }
 
+    pub fn draw_beat_grid(&self, frame: &mut Frame, theme: &Theme) {
+        let (interval, lines_per_bar) = match self.get_beat_grid_data() {
+            Some(data) => data,
+            None => return,
+        };
+
+        let appearance = theme.appearance();
+
+        let total_duration = self.viewport_factor * TOTAL_DURATION;
+        let x_offset = self.x_offset / self.viewport_factor;
+
+        let iterations = (TOTAL_DURATION / interval) as usize + 1;
+
+        for i in 0..iterations {
+            let x = (x_offset + (interval * i as f32) / total_duration) * self.size.width;
+
+            if x < 0.0 || x > self.size.width {
+                continue;
+            }
+
+            let top_point = Point::new(x, 0.0);
+            let bottom_point = Point::new(x, self.size.height);
+
+            let path = Path::line(
+                scale_point_x(self.size, top_point).snap(),
+                scale_point_x(self.size, bottom_point).snap(),
+            );
+
+            let color = if i % lines_per_bar == 0 {
+                appearance.bar_grid_color
+            } else {
+                appearance.beat_grid_color
+            };
+
+            frame.stroke(&path, Stroke::default().with_width(1.0).with_color(color));
+        }
+    }
+
+    /// Draw horizontal level grid lines when using decibel scale
+    pub fn draw_level_markers(&self, frame: &mut Frame, theme: &Theme) {
+        let (range, interval) = match (self.level_scale, self.level_scale.grid_interval_db()) {
+            (EnvelopeLevelScale::Decibels(range), Some(interval)) => (range, interval),
+            _ => return,
+        };
+
+        let appearance = theme.appearance();
+        let size = frame.size();
+
+        let stroke = Stroke::default()
+            .with_width(1.0)
+            .with_color(appearance.time_marker_minor_color);
+
+        for db in (interval..range).step_by(interval.into()) {
+            let y = size.height * f32::from(db) / f32::from(range);
+
+            let path = Path::line(
+                scale_point(size, Point::new(0.0, y)).snap(),
+                scale_point(size, Point::new(size.width, y)).snap(),
+            );
+
+            frame.stroke(&path, stroke.clone());
+        }
+    }
+
+    /// Draw decibel scale toggle in left margin, with dB range below it
+    /// when active
+    pub fn draw_level_scale_toggle(&self, frame: &mut Frame, theme: &Theme) {
+        let appearance = theme.appearance();
+        let bounds = level_scale_toggle_bounds();
+
+        let (color, range) = match self.level_scale {
+            EnvelopeLevelScale::Linear => (appearance.time_marker_color_major, None),
+            EnvelopeLevelScale::Decibels(range) => (appearance.text_color, Some(range)),
+        };
+
+        frame.fill_text(Text {
+            content: "dB".into(),
+            position: Point::new(bounds.x + 1.0, bounds.y),
+            font: theme.font_regular(),
+            size: (FONT_SIZE - 4) as f32,
+            color,
+            ..Default::default()
+        });
+
+        if let Some(range) = range {
+            frame.fill_text(Text {
+                content: range.to_string(),
+                position: Point::new(bounds.x + 1.0, bounds.y + bounds.height / 2.0),
+                font: theme.font_regular(),
+                size: (FONT_SIZE - 4) as f32,
+                color,
+                ..Default::default()
+            });
+        }
+    }
+
     pub fn draw_stage_paths(&self, frame: &mut Frame, theme: &Theme) {
         let appearance = theme.appearance();
         let size = frame.size();
@@ -195,6 +291,22 @@ impl EnvelopeCanvas {
         frame.fill(&viewport_rect, appearance.background_color);
         frame.stroke(&viewport_rect, border_stroke);
     }
+
+    pub fn draw_rubber_band(&self, frame: &mut Frame, theme: &Theme, rubber_band: RubberBand) {
+        let appearance = theme.appearance();
+        let rectangle = rubber_band.rectangle();
+
+        let path = Path::rectangle(
+            Point::new(rectangle.x, rectangle.y).snap(),
+            Size::new(rectangle.width.round(), rectangle.height.round()),
+        );
+
+        let stroke = Stroke::default()
+            .with_width(1.0)
+            .with_color(appearance.selection_color);
+
+        frame.stroke(&path, stroke);
+    }
 }
 
 fn scale_point_x(size: Size, point: Point) -> Point {
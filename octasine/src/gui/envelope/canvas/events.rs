@@ -1,8 +1,12 @@
+use iced_baseview::keyboard::Modifiers;
+use iced_baseview::mouse::ScrollDelta;
 use iced_baseview::widget::canvas::event;
 use iced_baseview::{Point, Rectangle};
 
-use crate::gui::Message;
-use crate::parameters::operator_envelope::{ENVELOPE_MAX_DURATION, ENVELOPE_MIN_DURATION};
+use crate::gui::{EnvelopeParameterChanges, Message};
+use crate::parameters::operator_envelope::{
+    envelope_duration_to_patch, ENVELOPE_MAX_DURATION, ENVELOPE_MIN_DURATION,
+};
 
 use super::common::*;
 use super::EnvelopeCanvas;
@@ -20,31 +24,65 @@ impl EnvelopeCanvas {
                 state.last_cursor_position.y - bounds.y,
             );
 
-            if self.release_dragger.cursor_overlaps(relative_position)
+            let selected_dragger_hit = (state.selection.attack
+                && self.attack_dragger.cursor_overlaps(relative_position))
+                || (state.selection.decay && self.decay_dragger.cursor_overlaps(relative_position))
+                || (state.selection.release
+                    && self.release_dragger.cursor_overlaps(relative_position));
+
+            if selected_dragger_hit && state.selection.num_selected() > 1 {
+                state.dragging_selection = Some(DraggingSelection {
+                    from: state.last_cursor_position,
+                    original_durations: self.get_duration_seconds(),
+                    original_sustain_volume: self.sustain_volume,
+                });
+            } else if self.release_dragger.cursor_overlaps(relative_position)
                 && !state.release_dragger_status.is_dragging()
             {
+                state.selection = EnvelopeSelection::default();
                 state.release_dragger_status = EnvelopeDraggerStatus::Dragging {
                     from: state.last_cursor_position,
-                    original_duration: self.release_duration,
+                    original_duration: self.get_release_duration_seconds(),
                     original_end_value: 0.0,
                 };
             } else if self.decay_dragger.cursor_overlaps(relative_position)
                 && !state.decay_dragger_status.is_dragging()
             {
+                state.selection = EnvelopeSelection::default();
                 state.decay_dragger_status = EnvelopeDraggerStatus::Dragging {
                     from: state.last_cursor_position,
-                    original_duration: self.decay_duration,
+                    original_duration: self.get_decay_duration_seconds(),
                     original_end_value: self.sustain_volume,
                 };
             } else if self.attack_dragger.cursor_overlaps(relative_position)
                 && !state.attack_dragger_status.is_dragging()
             {
+                state.selection = EnvelopeSelection::default();
                 state.attack_dragger_status = EnvelopeDraggerStatus::Dragging {
                     from: state.last_cursor_position,
-                    original_duration: self.attack_duration,
+                    original_duration: self.get_attack_duration_seconds(),
                     original_end_value: 1.0,
                 };
+            } else if level_scale_toggle_bounds().contains(relative_position) {
+                let message = Message::EnvelopeChangeLevelScale {
+                    operator_index: self.operator_index,
+                    level_scale: self.level_scale.toggled(),
+                };
+
+                return (event::Status::Captured, Some(message));
+            } else if state.shift_held {
+                state.scaling_durations = Some(ScalingDurations {
+                    from: state.last_cursor_position,
+                    original_durations: self.get_duration_seconds(),
+                });
+            } else if state.command_held {
+                state.rubber_band = Some(RubberBand {
+                    from: relative_position,
+                    to: relative_position,
+                });
             } else {
+                state.selection = EnvelopeSelection::default();
+
                 let pos_in_bounds = state.last_cursor_position.x - bounds.x;
                 let pos_in_viewport =
                     (pos_in_bounds - (WIDTH as f32 * (1.0 - ENVELOPE_PATH_SCALE_X)) / 2.0).max(0.0);
@@ -91,6 +129,24 @@ impl EnvelopeCanvas {
 
         let relative_position = Point::new(x - bounds.x, y - bounds.y);
 
+        if let Some(rubber_band) = state.rubber_band.as_mut() {
+            rubber_band.to = relative_position;
+
+            self.cache.clear();
+
+            return (event::Status::Captured, None);
+        }
+        if let Some(dragging) = state.dragging_selection {
+            let message = self.get_dragging_selection_message(state.selection, dragging, x, y);
+
+            return (event::Status::Captured, Some(message));
+        }
+        if let Some(scaling) = state.scaling_durations {
+            let message = self.get_scaling_durations_message(scaling, x);
+
+            return (event::Status::Captured, Some(message));
+        }
+
         let attack_hitbox_hit = self.attack_dragger.cursor_overlaps(relative_position);
 
         match state.attack_dragger_status {
@@ -113,13 +169,20 @@ impl EnvelopeCanvas {
                 original_duration,
                 ..
             } => {
+                let mut parameters = EnvelopeParameterChanges::new();
+
+                parameters.push((
+                    self.attack_duration_parameter,
+                    envelope_duration_to_patch(f64::from(self.snap_stage_duration(
+                        state,
+                        0.0,
+                        dragging_to_duration(self.viewport_factor, x, from, original_duration),
+                    ))),
+                ));
+
                 let message = Message::ChangeEnvelopeParametersSetValue {
                     operator_index: self.operator_index,
-                    parameter_1: (
-                        self.attack_duration_parameter,
-                        dragging_to_duration(self.viewport_factor, x, from, original_duration),
-                    ),
-                    parameter_2: None,
+                    parameters,
                 };
 
                 return (event::Status::Captured, Some(message));
@@ -154,16 +217,24 @@ impl EnvelopeCanvas {
                 original_duration,
                 original_end_value,
             } => {
+                let mut parameters = EnvelopeParameterChanges::new();
+
+                parameters.push((
+                    self.decay_duration_parameter,
+                    envelope_duration_to_patch(f64::from(self.snap_stage_duration(
+                        state,
+                        self.get_attack_duration_seconds(),
+                        dragging_to_duration(self.viewport_factor, x, from, original_duration),
+                    ))),
+                ));
+                parameters.push((
+                    self.sustain_volume_parameter,
+                    dragging_to_end_value(self.level_scale, y, from, original_end_value),
+                ));
+
                 let message = Message::ChangeEnvelopeParametersSetValue {
                     operator_index: self.operator_index,
-                    parameter_1: (
-                        self.decay_duration_parameter,
-                        dragging_to_duration(self.viewport_factor, x, from, original_duration),
-                    ),
-                    parameter_2: Some((
-                        self.sustain_volume_parameter,
-                        dragging_to_end_value(y, from, original_end_value),
-                    )),
+                    parameters,
                 };
 
                 return (event::Status::Captured, Some(message));
@@ -204,13 +275,20 @@ impl EnvelopeCanvas {
                 original_duration,
                 ..
             } => {
+                let mut parameters = EnvelopeParameterChanges::new();
+
+                parameters.push((
+                    self.release_duration_parameter,
+                    envelope_duration_to_patch(f64::from(self.snap_stage_duration(
+                        state,
+                        self.get_attack_duration_seconds() + self.get_decay_duration_seconds(),
+                        dragging_to_duration(self.viewport_factor, x, from, original_duration),
+                    ))),
+                ));
+
                 let message = Message::ChangeEnvelopeParametersSetValue {
                     operator_index: self.operator_index,
-                    parameter_1: (
-                        self.release_duration_parameter,
-                        dragging_to_duration(self.viewport_factor, x, from, original_duration),
-                    ),
-                    parameter_2: None,
+                    parameters,
                 };
 
                 return (event::Status::Captured, Some(message));
@@ -251,17 +329,119 @@ impl EnvelopeCanvas {
         }
     }
 
+    pub fn handle_modifiers_changed(
+        &self,
+        state: &mut EnvelopeCanvasState,
+        modifiers: Modifiers,
+    ) -> (event::Status, Option<Message>) {
+        state.shift_held = modifiers.shift();
+        state.command_held = modifiers.command();
+
+        let beat_grid_active = modifiers.shift() && self.get_beat_grid_data().is_some();
+
+        if beat_grid_active != state.beat_grid_active {
+            state.beat_grid_active = beat_grid_active;
+
+            self.cache.clear();
+        }
+
+        (event::Status::Ignored, None)
+    }
+
+    /// Zoom vertically when using decibel scale
+    pub fn handle_wheel_scrolled(
+        &self,
+        state: &mut EnvelopeCanvasState,
+        bounds: Rectangle,
+        delta: ScrollDelta,
+    ) -> (event::Status, Option<Message>) {
+        if !bounds.contains(state.last_cursor_position) {
+            return (event::Status::Ignored, None);
+        }
+        if let EnvelopeLevelScale::Linear = self.level_scale {
+            return (event::Status::Ignored, None);
+        }
+
+        let y = match delta {
+            ScrollDelta::Lines { y, .. } | ScrollDelta::Pixels { y, .. } => y,
+        };
+
+        let level_scale = if y > 0.0 {
+            self.level_scale.zoomed_in()
+        } else if y < 0.0 {
+            self.level_scale.zoomed_out()
+        } else {
+            self.level_scale
+        };
+
+        if level_scale == self.level_scale {
+            return (event::Status::Captured, None);
+        }
+
+        let message = Message::EnvelopeChangeLevelScale {
+            operator_index: self.operator_index,
+            level_scale,
+        };
+
+        (event::Status::Captured, Some(message))
+    }
+
     pub fn handle_button_released(
         &self,
         state: &mut EnvelopeCanvasState,
     ) -> (event::Status, Option<Message>) {
-        if state.release_dragger_status.is_dragging() {
+        if let Some(rubber_band) = state.rubber_band.take() {
+            let rectangle = rubber_band.rectangle();
+
+            state.selection = EnvelopeSelection {
+                attack: rectangle.contains(self.attack_dragger.center()),
+                decay: rectangle.contains(self.decay_dragger.center()),
+                release: rectangle.contains(self.release_dragger.center()),
+            };
+
+            self.cache.clear();
+
+            (event::Status::Captured, None)
+        } else if state.dragging_selection.take().is_some() {
+            let message = Message::ChangeEnvelopeParametersEnd {
+                operator_index: self.operator_index,
+                parameters: self.get_selection_parameter_changes(
+                    state.selection,
+                    [
+                        self.attack_duration,
+                        self.decay_duration,
+                        self.release_duration,
+                    ],
+                    self.sustain_volume,
+                ),
+            };
+
+            self.cache.clear();
+
+            (event::Status::Captured, Some(message))
+        } else if state.scaling_durations.take().is_some() {
+            let message = Message::ChangeEnvelopeParametersEnd {
+                operator_index: self.operator_index,
+                parameters: [
+                    (self.attack_duration_parameter, self.attack_duration),
+                    (self.decay_duration_parameter, self.decay_duration),
+                    (self.release_duration_parameter, self.release_duration),
+                ]
+                .into_iter()
+                .collect(),
+            };
+
+            self.cache.clear();
+
+            (event::Status::Captured, Some(message))
+        } else if state.release_dragger_status.is_dragging() {
             state.release_dragger_status = EnvelopeDraggerStatus::Normal;
 
             let message = Message::ChangeEnvelopeParametersEnd {
                 operator_index: self.operator_index,
-                parameter_1: (self.release_duration_parameter, self.release_duration),
-                parameter_2: None,
+                parameters: [(self.release_duration_parameter, self.release_duration)]
+                    .into_iter()
+                    .collect(),
             };
 
             self.cache.clear();
@@ -272,8 +452,12 @@ impl EnvelopeCanvas {
 
             let message = Message::ChangeEnvelopeParametersEnd {
                 operator_index: self.operator_index,
-                parameter_1: (self.decay_duration_parameter, self.decay_duration),
-                parameter_2: Some((self.sustain_volume_parameter, self.sustain_volume)),
+                parameters: [
+                    (self.decay_duration_parameter, self.decay_duration),
+                    (self.sustain_volume_parameter, self.sustain_volume),
+                ]
+                .into_iter()
+                .collect(),
             };
 
             self.cache.clear();
@@ -284,8 +468,9 @@ impl EnvelopeCanvas {
 
             let message = Message::ChangeEnvelopeParametersEnd {
                 operator_index: self.operator_index,
-                parameter_1: (self.attack_duration_parameter, self.attack_duration),
-                parameter_2: None,
+                parameters: [(self.attack_duration_parameter, self.attack_duration)]
+                    .into_iter()
+                    .collect(),
             };
 
             self.cache.clear();
@@ -331,6 +516,127 @@ impl EnvelopeCanvas {
     }
 }
 
+/// Selection and scaling helpers
+impl EnvelopeCanvas {
+    /// Move selected stage ends by the same amount of time (and sustain
+    /// volume if decay end is selected), preserving their relative positions
+    fn get_dragging_selection_message(
+        &self,
+        selection: EnvelopeSelection,
+        dragging: DraggingSelection,
+        cursor_x: f32,
+        cursor_y: f32,
+    ) -> Message {
+        let factors = selection.duration_change_factors();
+
+        let mut min_change = f32::NEG_INFINITY;
+        let mut max_change = f32::INFINITY;
+
+        // Limit change so that no duration is clamped, since that would
+        // move other selected stage ends relative to each other
+        for (original, factor) in dragging.original_durations.into_iter().zip(factors) {
+            if factor != 0.0 {
+                let a = (ENVELOPE_MIN_DURATION as f32 - original) / factor;
+                let b = (ENVELOPE_MAX_DURATION as f32 - original) / factor;
+
+                min_change = min_change.max(a.min(b));
+                max_change = max_change.min(a.max(b));
+            }
+        }
+
+        let change = dragging_to_duration_change(self.viewport_factor, cursor_x, dragging.from)
+            .max(min_change)
+            .min(max_change);
+
+        let mut durations = [0.0; 3];
+
+        for ((duration, original), factor) in durations
+            .iter_mut()
+            .zip(dragging.original_durations)
+            .zip(factors)
+        {
+            *duration = envelope_duration_to_patch(f64::from(original + change * factor));
+        }
+
+        let sustain_volume = dragging_to_end_value(
+            self.level_scale,
+            cursor_y,
+            dragging.from,
+            dragging.original_sustain_volume,
+        );
+
+        Message::ChangeEnvelopeParametersSetValue {
+            operator_index: self.operator_index,
+            parameters: self.get_selection_parameter_changes(selection, durations, sustain_volume),
+        }
+    }
+
+    /// Scale all stage durations proportionally, by a factor of two for
+    /// every 100 pixels of horizontal cursor movement
+    fn get_scaling_durations_message(&self, scaling: ScalingDurations, cursor_x: f32) -> Message {
+        let mut min_factor: f32 = 0.0;
+        let mut max_factor = f32::INFINITY;
+
+        for original in scaling.original_durations {
+            let original = original.max(ENVELOPE_MIN_DURATION as f32);
+
+            min_factor = min_factor.max(ENVELOPE_MIN_DURATION as f32 / original);
+            max_factor = max_factor.min(ENVELOPE_MAX_DURATION as f32 / original);
+        }
+
+        let factor = ((cursor_x - scaling.from.x) / 100.0)
+            .exp2()
+            .max(min_factor)
+            .min(max_factor);
+
+        let [attack, decay, release] = scaling
+            .original_durations
+            .map(|duration| envelope_duration_to_patch(f64::from(duration * factor)));
+
+        Message::ChangeEnvelopeParametersSetValue {
+            operator_index: self.operator_index,
+            parameters: [
+                (self.attack_duration_parameter, attack),
+                (self.decay_duration_parameter, decay),
+                (self.release_duration_parameter, release),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    /// Get changes for parameters affected by moving selected stage ends.
+    /// Durations are patch values in attack, decay, release order.
+    fn get_selection_parameter_changes(
+        &self,
+        selection: EnvelopeSelection,
+        durations: [f32; 3],
+        sustain_volume: f32,
+    ) -> EnvelopeParameterChanges {
+        let mut parameters = EnvelopeParameterChanges::new();
+
+        for ((parameter, duration), factor) in [
+            self.attack_duration_parameter,
+            self.decay_duration_parameter,
+            self.release_duration_parameter,
+        ]
+        .into_iter()
+        .zip(durations)
+        .zip(selection.duration_change_factors())
+        {
+            if factor != 0.0 {
+                parameters.push((parameter, duration));
+            }
+        }
+
+        if selection.decay {
+            parameters.push((self.sustain_volume_parameter, sustain_volume));
+        }
+
+        parameters
+    }
+}
+
 // Almost-correct reverse transformation for envelope dragger to duration
 fn dragging_to_duration(
     viewport_factor: f32,
@@ -338,18 +644,33 @@ fn dragging_to_duration(
     from: Point,
     original_value: f32,
 ) -> f32 {
+    let change = dragging_to_duration_change(viewport_factor, cursor_x, from);
+
+    (original_value + change)
+        .min(ENVELOPE_MAX_DURATION as f32)
+        .max(ENVELOPE_MIN_DURATION as f32)
+}
+
+fn dragging_to_duration_change(viewport_factor: f32, cursor_x: f32, from: Point) -> f32 {
     let change = (cursor_x - from.x) / WIDTH as f32;
     let change = change / ENVELOPE_PATH_SCALE_X;
-    let change = change * viewport_factor * TOTAL_DURATION;
 
-    (original_value + change)
-        .min(1.0)
-        .max(ENVELOPE_MIN_DURATION as f32 / ENVELOPE_MAX_DURATION as f32)
+    change * viewport_factor * TOTAL_DURATION
 }
 
-fn dragging_to_end_value(cursor_y: f32, from: Point, original_value: f32) -> f32 {
+fn dragging_to_end_value(
+    level_scale: EnvelopeLevelScale,
+    cursor_y: f32,
+    from: Point,
+    original_value: f32,
+) -> f32 {
     let change = -(cursor_y - from.y) / HEIGHT as f32;
     let change = change / ENVELOPE_PATH_SCALE_Y;
 
-    (original_value + change).min(1.0).max(0.0)
+    // Don't move levels below decibel scale floor on horizontal drags
+    if change == 0.0 {
+        return original_value;
+    }
+
+    level_scale.y_fraction_to_level(level_scale.level_to_y_fraction(original_value) + change)
 }
@@ -6,9 +6,11 @@ use iced_baseview::widget::canvas::{event, Cache, Canvas, Cursor, Geometry, Prog
 use iced_baseview::{widget::Container, Element, Length, Point, Rectangle, Size};
 
 use crate::audio::voices::log10_table::Log10Table;
+use crate::common::BeatsPerMinute;
 use crate::parameters::operator_envelope::{
-    OperatorAttackDurationValue, OperatorDecayDurationValue, OperatorEnvelopeGroupValue,
-    OperatorReleaseDurationValue, OperatorSustainVolumeValue,
+    envelope_duration_from_patch, OperatorAttackDurationValue, OperatorDecayDurationValue,
+    OperatorEnvelopeGroupValue, OperatorReleaseDurationValue, OperatorSustainVolumeValue,
+    ENVELOPE_MAX_DURATION, ENVELOPE_MIN_DURATION,
 };
 use crate::parameters::{OperatorParameter, Parameter, ParameterValue, WrappedParameter};
 use crate::sync::GuiSyncHandle;
@@ -17,7 +19,7 @@ use super::super::style::Theme;
 use super::super::{Message, LINE_HEIGHT};
 
 use common::*;
-pub use common::{Appearance, StyleSheet};
+pub use common::{Appearance, EnvelopeLevelScale, StyleSheet};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct EnvelopeValues {
@@ -42,6 +44,8 @@ pub struct EnvelopeCanvas {
     size: Size,
     viewport_factor: f32,
     x_offset: f32,
+    level_scale: EnvelopeLevelScale,
+    host_bpm: Option<BeatsPerMinute>,
     attack_stage_path: EnvelopeStagePath,
     decay_stage_path: EnvelopeStagePath,
     release_stage_path: EnvelopeStagePath,
@@ -100,6 +104,8 @@ impl EnvelopeCanvas {
             size: SIZE,
             viewport_factor: 1.0,
             x_offset: 0.0,
+            level_scale: EnvelopeLevelScale::default(),
+            host_bpm: sync_handle.get_host_bpm(),
             attack_stage_path: Default::default(),
             decay_stage_path: Default::default(),
             release_stage_path: Default::default(),
@@ -143,6 +149,22 @@ impl EnvelopeCanvas {
         self.update_data();
     }
 
+    pub fn set_level_scale(&mut self, level_scale: EnvelopeLevelScale) {
+        if level_scale != self.level_scale {
+            self.level_scale = level_scale;
+
+            self.update_data();
+        }
+    }
+
+    pub fn set_host_bpm(&mut self, host_bpm: Option<BeatsPerMinute>) {
+        if host_bpm != self.host_bpm {
+            self.host_bpm = host_bpm;
+
+            self.cache.clear();
+        }
+    }
+
     pub fn set_attack_duration(&mut self, value: f32, internal: bool) {
         let value = OperatorAttackDurationValue::new_from_patch(value).to_patch();
 
@@ -212,36 +234,43 @@ impl EnvelopeCanvas {
         let total_duration = self.viewport_factor * TOTAL_DURATION;
         let x_offset = self.x_offset / self.viewport_factor;
 
+        let attack_duration = self.get_attack_duration_seconds();
+        let decay_duration = self.get_decay_duration_seconds();
+        let release_duration = self.get_release_duration_seconds();
+
         self.attack_stage_path = EnvelopeStagePath::new(
             &self.log10table,
+            self.level_scale,
             self.size,
             total_duration,
             x_offset,
             0.0,
             0.0,
-            self.attack_duration,
+            attack_duration,
             1.0,
         );
 
         self.decay_stage_path = EnvelopeStagePath::new(
             &self.log10table,
+            self.level_scale,
             self.size,
             total_duration,
             x_offset,
-            self.attack_duration,
+            attack_duration,
             1.0,
-            self.decay_duration,
+            decay_duration,
             self.sustain_volume,
         );
 
         self.release_stage_path = EnvelopeStagePath::new(
             &self.log10table,
+            self.level_scale,
             self.size,
             total_duration,
             x_offset,
-            self.attack_duration + self.decay_duration,
+            attack_duration + decay_duration,
             self.sustain_volume,
-            self.release_duration,
+            release_duration,
             0.0,
         );
     }
@@ -269,11 +298,35 @@ impl EnvelopeCanvas {
     pub fn get_x_offset(&self) -> f32 {
         self.x_offset
     }
+    pub fn get_level_scale(&self) -> EnvelopeLevelScale {
+        self.level_scale
+    }
     pub fn get_modified_by_automation(&self) -> bool {
         self.modified_by_automation
     }
 }
 
+/// Stage durations in seconds, for display and dragging
+impl EnvelopeCanvas {
+    pub(super) fn get_attack_duration_seconds(&self) -> f32 {
+        envelope_duration_from_patch(self.attack_duration) as f32
+    }
+    pub(super) fn get_decay_duration_seconds(&self) -> f32 {
+        envelope_duration_from_patch(self.decay_duration) as f32
+    }
+    pub(super) fn get_release_duration_seconds(&self) -> f32 {
+        envelope_duration_from_patch(self.release_duration) as f32
+    }
+    /// Attack, decay and release durations
+    pub(super) fn get_duration_seconds(&self) -> [f32; 3] {
+        [
+            self.get_attack_duration_seconds(),
+            self.get_decay_duration_seconds(),
+            self.get_release_duration_seconds(),
+        ]
+    }
+}
+
 /// Viewport change helpers
 impl EnvelopeCanvas {
     pub(super) fn get_zoom_in_data(&self) -> (f32, f32) {
@@ -332,7 +385,59 @@ impl EnvelopeCanvas {
     }
 
     fn get_current_duration(&self) -> f32 {
-        self.attack_duration + self.decay_duration + self.release_duration
+        self.get_attack_duration_seconds()
+            + self.get_decay_duration_seconds()
+            + self.get_release_duration_seconds()
+    }
+}
+
+/// Beat grid helpers
+impl EnvelopeCanvas {
+    /// Get beat grid line interval (in seconds) and number of lines per bar,
+    /// or None if host hasn't reported a tempo
+    pub(super) fn get_beat_grid_data(&self) -> Option<(f32, usize)> {
+        let bpm = self.host_bpm?;
+
+        let beat_duration = (60.0 / bpm.0) as f32;
+        let visible_duration = self.viewport_factor * TOTAL_DURATION;
+
+        let mut interval = beat_duration / 16.0;
+
+        while visible_duration / interval > MAX_VISIBLE_BEAT_GRID_LINES {
+            interval *= 2.0;
+        }
+
+        let lines_per_bar = ((BEATS_PER_BAR as f32 * beat_duration) / interval)
+            .round()
+            .max(1.0) as usize;
+
+        Some((interval, lines_per_bar))
+    }
+
+    /// Snap end of dragged stage to closest beat grid line following stage
+    /// start, if beat grid is active
+    pub(super) fn snap_stage_duration(
+        &self,
+        state: &EnvelopeCanvasState,
+        stage_start: f32,
+        duration: f32,
+    ) -> f32 {
+        if !state.beat_grid_active {
+            return duration;
+        }
+
+        if let Some((interval, _)) = self.get_beat_grid_data() {
+            let first_line_after_start = (stage_start / interval).floor() + 1.0;
+            let end_line = ((stage_start + duration) / interval)
+                .round()
+                .max(first_line_after_start);
+
+            (end_line * interval - stage_start)
+                .min(ENVELOPE_MAX_DURATION as f32)
+                .max(ENVELOPE_MIN_DURATION as f32)
+        } else {
+            duration
+        }
     }
 }
 
@@ -348,16 +453,39 @@ impl Program<Message, Theme> for EnvelopeCanvas {
     ) -> Vec<Geometry> {
         let geometry = self.cache.draw(bounds.size(), |frame| {
             self.draw_time_markers(frame, theme);
+            self.draw_level_markers(frame, theme);
+
+            if state.beat_grid_active {
+                self.draw_beat_grid(frame, theme);
+            }
+
             self.draw_stage_paths(frame, theme);
 
-            self.attack_dragger
-                .draw(frame, theme, &state.attack_dragger_status);
-            self.decay_dragger
-                .draw(frame, theme, &state.decay_dragger_status);
-            self.release_dragger
-                .draw(frame, theme, &state.release_dragger_status);
+            self.attack_dragger.draw(
+                frame,
+                theme,
+                &state.attack_dragger_status,
+                state.selection.attack,
+            );
+            self.decay_dragger.draw(
+                frame,
+                theme,
+                &state.decay_dragger_status,
+                state.selection.decay,
+            );
+            self.release_dragger.draw(
+                frame,
+                theme,
+                &state.release_dragger_status,
+                state.selection.release,
+            );
+
+            if let Some(rubber_band) = state.rubber_band {
+                self.draw_rubber_band(frame, theme, rubber_band);
+            }
 
             self.draw_viewport_indicator(frame, theme);
+            self.draw_level_scale_toggle(frame, theme);
         });
 
         vec![geometry]
@@ -380,6 +508,12 @@ impl Program<Message, Theme> for EnvelopeCanvas {
             event::Event::Mouse(iced_baseview::mouse::Event::ButtonReleased(
                 iced_baseview::mouse::Button::Left,
             )) => self.handle_button_released(state),
+            event::Event::Mouse(iced_baseview::mouse::Event::WheelScrolled { delta }) => {
+                self.handle_wheel_scrolled(state, bounds, delta)
+            }
+            event::Event::Keyboard(iced_baseview::keyboard::Event::ModifiersChanged(modifiers)) => {
+                self.handle_modifiers_changed(state, modifiers)
+            }
             _ => (event::Status::Ignored, None),
         }
     }
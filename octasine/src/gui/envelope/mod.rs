@@ -2,19 +2,18 @@ pub mod canvas;
 
 use iced_baseview::alignment::Horizontal;
 use iced_baseview::widget::tooltip::Position;
-use iced_baseview::Font;
 use iced_baseview::{
-    widget::Button, widget::Column, widget::Row, widget::Space, widget::Text, Alignment, Element,
+    widget::Column, widget::PickList, widget::Row, widget::Space, widget::Text, Alignment, Element,
     Length,
 };
 
 use crate::parameters::list::{OperatorParameter, Parameter};
-use crate::parameters::operator_envelope::OperatorEnvelopeGroupValue;
+use crate::parameters::operator_envelope::{OperatorEnvelopeGroupValue, ENVELOPE_PRESETS};
 use crate::parameters::ParameterValue;
 use crate::sync::GuiSyncHandle;
 
 use super::boolean_button::{envelope_group_a_button, envelope_group_b_button, BooleanButton};
-use super::common::{container_l3, tooltip};
+use super::common::{button_with_tooltip, container_l3, tooltip};
 use super::style::Theme;
 use super::{Message, FONT_SIZE, LINE_HEIGHT};
 
@@ -146,6 +145,25 @@ impl Envelope {
             "Distribute view to other envelopes",
         );
 
+        let operator_index = self.operator_index as u8;
+
+        let preset = tooltip(
+            theme,
+            "Apply envelope preset",
+            Position::Top,
+            PickList::new(ENVELOPE_PRESETS, None, move |preset| {
+                Message::ApplyEnvelopePreset {
+                    operator_index,
+                    preset,
+                }
+            })
+            .font(theme.font_regular())
+            .text_size(FONT_SIZE - 2)
+            .padding(theme.picklist_padding())
+            .placeholder("PRE")
+            .width(Length::Fill),
+        );
+
         let group_a = tooltip(
             theme,
             "Toggle group A membership",
@@ -186,32 +204,10 @@ impl Envelope {
                             .push(fit)
                             .push(Space::with_width(Length::Fixed(4.0)))
                             .push(distribute),
-                    ),
+                    )
+                    .push(Space::with_height(Length::Fixed(6.0)))
+                    .push(preset),
             ))
             .into()
     }
 }
-
-fn button_with_tooltip<'a>(
-    theme: &Theme,
-    button_font: Font,
-    button_text: &'static str,
-    button_message: Message,
-    tooltip_text: &'static str,
-) -> Element<'a, Message, Theme> {
-    tooltip(
-        theme,
-        tooltip_text,
-        Position::Top,
-        Button::new(
-            Text::new(button_text)
-                .font(button_font)
-                .height(Length::Fixed(LINE_HEIGHT.into()))
-                .width(Length::Fixed(10.0))
-                .horizontal_alignment(Horizontal::Center),
-        )
-        .on_press(button_message)
-        .padding(theme.button_padding()),
-    )
-    .into()
-}
@@ -0,0 +1,244 @@
+use iced_baseview::widget::canvas::{
+    event, Cache, Canvas, Cursor, Frame, Geometry, Path, Program, Stroke,
+};
+use iced_baseview::widget::{Row, Space, Text};
+use iced_baseview::{Alignment, Color, Element, Length, Point, Rectangle, Size};
+
+use super::{style::Theme, Message, FONT_SIZE, LINE_HEIGHT};
+
+const WIDTH: u16 = LINE_HEIGHT * 6;
+const HEIGHT: u16 = LINE_HEIGHT;
+
+#[derive(Debug, Clone)]
+pub struct Appearance {
+    pub background_color: Color,
+    pub border_color: Color,
+    pub border_color_hovered: Color,
+    pub bar_color: Color,
+    pub center_line_color: Color,
+}
+
+pub trait StyleSheet {
+    fn appearance(&self) -> Appearance;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExpressionStripKind {
+    /// Bipolar, springs back to center when released
+    PitchBend,
+    /// Unipolar, keeps value when released
+    ModWheel,
+}
+
+/// Strip sending pitch bend or mod wheel events to the audio thread, so
+/// expression can be tested without a hardware controller
+pub struct ExpressionStrip {
+    kind: ExpressionStripKind,
+    /// -1.0 to 1.0 for pitch bend, 0.0 to 1.0 for mod wheel
+    value: f32,
+    cache: Cache,
+    bounds_path: Path,
+}
+
+impl ExpressionStrip {
+    pub fn new(kind: ExpressionStripKind) -> Self {
+        let bounds_path = Path::rectangle(
+            Point::new(0.5, 0.5),
+            Size::new((WIDTH - 1) as f32, (HEIGHT - 1) as f32),
+        );
+
+        Self {
+            kind,
+            value: 0.0,
+            cache: Cache::new(),
+            bounds_path,
+        }
+    }
+
+    pub fn set_value(&mut self, value: f32) {
+        if value != self.value {
+            self.value = value;
+
+            self.cache.clear();
+        }
+    }
+
+    pub fn theme_changed(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn view(&self) -> Element<Message, Theme> {
+        Canvas::new(self)
+            .width(Length::Fixed(WIDTH.into()))
+            .height(Length::Fixed(HEIGHT.into()))
+            .into()
+    }
+
+    fn message(&self, value: f32) -> Message {
+        match self.kind {
+            ExpressionStripKind::PitchBend => Message::SendPitchBend(value),
+            ExpressionStripKind::ModWheel => Message::SendModWheel(value),
+        }
+    }
+
+    fn value_from_cursor(&self, bounds: Rectangle, cursor_x: f32) -> f32 {
+        let fraction = ((cursor_x - bounds.x) / bounds.width).max(0.0).min(1.0);
+
+        match self.kind {
+            ExpressionStripKind::PitchBend => fraction * 2.0 - 1.0,
+            ExpressionStripKind::ModWheel => fraction,
+        }
+    }
+
+    /// Horizontal position of value within strip, from 0.0 to 1.0
+    fn value_to_fraction(&self, value: f32) -> f32 {
+        match self.kind {
+            ExpressionStripKind::PitchBend => (value + 1.0) / 2.0,
+            ExpressionStripKind::ModWheel => value,
+        }
+    }
+
+    fn draw_bar(&self, frame: &mut Frame, appearance: &Appearance) {
+        let width = frame.size().width - 2.0;
+
+        let from = self.value_to_fraction(0.0) * width;
+        let to = self.value_to_fraction(self.value) * width;
+
+        let left = from.min(to).round() + 1.0;
+        let right = from.max(to).round() + 1.0;
+
+        if right > left {
+            let bar = Path::rectangle(
+                Point::new(left, 1.0),
+                Size::new(right - left, frame.size().height - 2.0),
+            );
+
+            frame.fill(&bar, appearance.bar_color);
+        }
+
+        if let ExpressionStripKind::PitchBend = self.kind {
+            let x = (frame.size().width / 2.0).floor() + 0.5;
+
+            let center_line =
+                Path::line(Point::new(x, 1.0), Point::new(x, frame.size().height - 1.0));
+
+            frame.stroke(
+                &center_line,
+                Stroke::default()
+                    .with_width(1.0)
+                    .with_color(appearance.center_line_color),
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CanvasState {
+    cursor_within_bounds: bool,
+    dragging: bool,
+}
+
+impl Program<Message, Theme> for ExpressionStrip {
+    type State = CanvasState;
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let geometry = self.cache.draw(bounds.size(), |frame| {
+            let appearance = theme.appearance();
+
+            frame.fill(&self.bounds_path, appearance.background_color);
+
+            self.draw_bar(frame, &appearance);
+
+            let border_color = if state.cursor_within_bounds || state.dragging {
+                appearance.border_color_hovered
+            } else {
+                appearance.border_color
+            };
+
+            frame.stroke(
+                &self.bounds_path,
+                Stroke::default().with_color(border_color),
+            );
+        });
+
+        vec![geometry]
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: event::Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (event::Status, Option<Message>) {
+        match event {
+            event::Event::Mouse(iced_baseview::mouse::Event::CursorMoved { position }) => {
+                let cursor_within_bounds = bounds.contains(position);
+
+                if state.cursor_within_bounds != cursor_within_bounds {
+                    state.cursor_within_bounds = cursor_within_bounds;
+
+                    self.cache.clear();
+                }
+
+                if state.dragging {
+                    let message = self.message(self.value_from_cursor(bounds, position.x));
+
+                    (event::Status::Captured, Some(message))
+                } else {
+                    (event::Status::Ignored, None)
+                }
+            }
+            event::Event::Mouse(iced_baseview::mouse::Event::ButtonPressed(
+                iced_baseview::mouse::Button::Left,
+            )) if state.cursor_within_bounds => {
+                state.dragging = true;
+
+                let message = cursor
+                    .position()
+                    .map(|position| self.message(self.value_from_cursor(bounds, position.x)));
+
+                (event::Status::Captured, message)
+            }
+            event::Event::Mouse(iced_baseview::mouse::Event::ButtonReleased(
+                iced_baseview::mouse::Button::Left,
+            )) if state.dragging => {
+                state.dragging = false;
+
+                self.cache.clear();
+
+                let message = match self.kind {
+                    ExpressionStripKind::PitchBend => Some(self.message(0.0)),
+                    ExpressionStripKind::ModWheel => None,
+                };
+
+                (event::Status::Captured, message)
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+}
+
+/// Labeled pitch bend and mod wheel strips
+pub fn expression_strips<'a>(
+    theme: &Theme,
+    pitch_bend: &'a ExpressionStrip,
+    mod_wheel: &'a ExpressionStrip,
+) -> Element<'a, Message, Theme> {
+    Row::new()
+        .align_items(Alignment::Center)
+        .push(Text::new("BEND").size(FONT_SIZE).font(theme.font_regular()))
+        .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT / 2))))
+        .push(pitch_bend.view())
+        .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
+        .push(Text::new("MOD").size(FONT_SIZE).font(theme.font_regular()))
+        .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT / 2))))
+        .push(mod_wheel.view())
+        .into()
+}
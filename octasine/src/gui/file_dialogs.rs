@@ -0,0 +1,191 @@
+//! Native file dialogs for loading and saving patches and banks
+//!
+//! On macOS and Windows, rfd is used. On other platforms, tinyfiledialogs is
+//! used instead. Since it blocks until the dialog is closed, it is run on a
+//! separate thread, so that the GUI keeps being updated in the meantime.
+
+use std::path::PathBuf;
+
+use cfg_if::cfg_if;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SaveDialog {
+    pub title: &'static str,
+    pub filter_name: &'static str,
+    pub extension: &'static str,
+}
+
+pub const SAVE_PATCH: SaveDialog = SaveDialog {
+    title: "Save OctaSine patch as",
+    filter_name: "Patch",
+    extension: "fxp",
+};
+
+pub const SAVE_BANK: SaveDialog = SaveDialog {
+    title: "Save OctaSine bank as",
+    filter_name: "Patch bank",
+    extension: "fxb",
+};
+
+/// Let user pick patch and/or bank files to load
+pub async fn pick_patch_or_bank_files(opt_directory: Option<PathBuf>) -> Option<Vec<PathBuf>> {
+    const TITLE: &str = "Load OctaSine patch bank, patches or SysEx voices";
+
+    cfg_if! {
+        if #[cfg(any(target_os = "macos", target_os = "windows"))] {
+            let mut builder = rfd::AsyncFileDialog::new()
+                .set_title(TITLE)
+                .add_filter("Patch", &["fxp"])
+                .add_filter("Patch bank", &["fxb"])
+                .add_filter("SysEx voices", &["syx"]);
+
+            if let Some(directory) = opt_directory.as_ref() {
+                builder = builder.set_directory(directory);
+            }
+            #[cfg(target_os = "macos")]
+            if let Some(h) = CurrentWindowHandle::get() {
+                builder = builder.set_parent(&h);
+            }
+
+            let opt_paths = builder.pick_files().await.map(|handles| {
+                handles
+                    .into_iter()
+                    .map(|h| h.path().to_owned())
+                    .collect()
+            });
+        } else {
+            let opt_paths = run_on_separate_thread(move || {
+                tinyfiledialogs::open_file_dialog_multi(
+                    TITLE,
+                    &tinyfiledialogs_default_path(opt_directory.as_deref(), ""),
+                    Some((&["*.fxp", "*.fxb", "*.syx"], "Patch bank, patch or SysEx files")),
+                )
+                .map(|strings| strings.into_iter().map(PathBuf::from).collect())
+            })
+            .await;
+        }
+    }
+
+    opt_paths
+}
+
+/// Let user pick path to save file to
+pub async fn pick_save_path(
+    dialog: SaveDialog,
+    file_name: String,
+    opt_directory: Option<PathBuf>,
+) -> Option<PathBuf> {
+    cfg_if! {
+        if #[cfg(any(target_os = "macos", target_os = "windows"))] {
+            let mut builder = rfd::AsyncFileDialog::new()
+                .set_title(dialog.title)
+                .add_filter(dialog.filter_name, &[dialog.extension])
+                .set_file_name(&file_name);
+
+            if let Some(directory) = opt_directory.as_ref() {
+                builder = builder.set_directory(directory);
+            }
+            #[cfg(target_os = "macos")]
+            if let Some(h) = CurrentWindowHandle::get() {
+                builder = builder.set_parent(&h);
+            }
+
+            let opt_path_buf = builder.save_file().await.map(|handle| handle.path().to_owned());
+        } else {
+            let opt_path_buf = run_on_separate_thread(move || {
+                let pattern = format!("*.{}", dialog.extension);
+
+                tinyfiledialogs::save_file_dialog_with_filter(
+                    dialog.title,
+                    &tinyfiledialogs_default_path(opt_directory.as_deref(), &file_name),
+                    &[pattern.as_str()],
+                    dialog.filter_name,
+                )
+                .map(PathBuf::from)
+            })
+            .await;
+        }
+    }
+
+    opt_path_buf
+}
+
+/// Run blocking dialog function on new thread and wait for it to return
+/// without blocking executor
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+async fn run_on_separate_thread<T, F>(f: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Option<T> + Send + 'static,
+{
+    let (sender, receiver) = futures_channel::oneshot::channel();
+
+    let spawn_result = ::std::thread::Builder::new()
+        .name("octasine-file-dialog".into())
+        .spawn(move || {
+            // Receiver is only dropped if GUI was closed, so ignore errors
+            let _ = sender.send(f());
+        });
+
+    if let Err(err) = spawn_result {
+        ::log::error!("Couldn't spawn file dialog thread: {:#}", err);
+
+        return None;
+    }
+
+    receiver.await.ok().flatten()
+}
+
+/// Default path for tinyfiledialogs: file name in directory if one is
+/// given. With empty file name, dialog starts in directory.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn tinyfiledialogs_default_path(
+    opt_directory: Option<&::std::path::Path>,
+    file_name: &str,
+) -> String {
+    match opt_directory {
+        Some(directory) => directory.join(file_name).to_string_lossy().into_owned(),
+        None => file_name.to_owned(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+struct CurrentWindowHandle(rwh05::RawWindowHandle);
+
+#[cfg(target_os = "macos")]
+impl CurrentWindowHandle {
+    fn get() -> Option<Self> {
+        use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+
+        unsafe {
+            let ns_app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            if ns_app.is_null() {
+                return None;
+            }
+
+            let ns_window: *mut Object = msg_send![ns_app, keyWindow];
+            if ns_window.is_null() {
+                return None;
+            }
+
+            let ns_view: *mut Object = msg_send![ns_window, contentView];
+            if ns_view.is_null() {
+                return None;
+            }
+
+            let mut handle = rwh05::AppKitWindowHandle::empty();
+
+            handle.ns_window = ns_window as *mut core::ffi::c_void;
+            handle.ns_view = ns_view as *mut core::ffi::c_void;
+
+            Some(Self(rwh05::RawWindowHandle::AppKit(handle)))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+unsafe impl rwh05::HasRawWindowHandle for CurrentWindowHandle {
+    fn raw_window_handle(&self) -> rwh05::RawWindowHandle {
+        self.0
+    }
+}
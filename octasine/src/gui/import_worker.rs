@@ -0,0 +1,80 @@
+//! Background worker for reading and decoding patch, bank and SysEx files
+//!
+//! Reading many files or converting large SysEx banks can take a while, so
+//! it is done on a separate thread owned by the GUI. Results are delivered
+//! to the GUI as messages and then applied to the patch bank, which is
+//! quick.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use futures_channel::oneshot;
+
+use crate::sync::ImportFiles;
+
+struct Job {
+    paths: Vec<PathBuf>,
+    result_sender: oneshot::Sender<ImportFiles>,
+}
+
+/// Worker thread is spawned on first use and exits when the worker is
+/// dropped along with the GUI
+#[derive(Default)]
+pub struct ImportWorker {
+    opt_sender: Option<mpsc::Sender<Job>>,
+}
+
+impl ImportWorker {
+    /// Read and decode files on worker thread. Future resolves to None if
+    /// the worker thread couldn't be started or exited prematurely.
+    pub fn read_files(
+        &mut self,
+        paths: Vec<PathBuf>,
+    ) -> impl Future<Output = Option<ImportFiles>> + Send + 'static {
+        let (result_sender, result_receiver) = oneshot::channel();
+
+        if let Some(sender) = self.get_or_spawn_sender() {
+            if sender
+                .send(Job {
+                    paths,
+                    result_sender,
+                })
+                .is_err()
+            {
+                ::log::error!("Import worker thread has exited");
+
+                // Respawn on next import
+                self.opt_sender = None;
+            }
+        }
+
+        // If job wasn't sent, result sender has been dropped and receiver
+        // resolves to an error right away
+        async move { result_receiver.await.ok() }
+    }
+
+    fn get_or_spawn_sender(&mut self) -> Option<&mpsc::Sender<Job>> {
+        if self.opt_sender.is_none() {
+            let (sender, receiver) = mpsc::channel::<Job>();
+
+            let spawn_result = ::std::thread::Builder::new()
+                .name("octasine-import".into())
+                .spawn(move || {
+                    // Iteration ends when sender is dropped
+                    for job in receiver.iter() {
+                        // Receiver is only dropped if GUI was closed, so
+                        // ignore errors
+                        let _ = job.result_sender.send(ImportFiles::read(&job.paths));
+                    }
+                });
+
+            match spawn_result {
+                Ok(_) => self.opt_sender = Some(sender),
+                Err(err) => ::log::error!("Couldn't spawn import worker thread: {:#}", err),
+            }
+        }
+
+        self.opt_sender.as_ref()
+    }
+}
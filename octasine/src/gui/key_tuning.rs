@@ -0,0 +1,94 @@
+use iced_baseview::alignment::Horizontal;
+use iced_baseview::widget::{Column, Row, Scrollable, Text, TextInput};
+use iced_baseview::{Alignment, Element, Length};
+
+use crate::parameters::key_tuning::KeyDetuneValue;
+use crate::parameters::utils::format_key;
+
+use super::style::Theme;
+use super::{Message, FONT_SIZE, LINE_HEIGHT};
+
+const HELP_TEXT: &str = "Detune keys in cents (e.g., \"+12.5c\") or map them to a frequency \
+    (e.g., \"261.6 Hz\" or \"C4 +5c\"). Press Enter to apply.";
+
+/// Editor for detuning each key of current patch, either in cents or by
+/// mapping it to a frequency
+#[derive(Debug, Clone)]
+pub struct KeyTuningEditor {
+    /// Input text for each key, which is the formatted detuning unless the
+    /// key is being edited
+    texts: Vec<String>,
+}
+
+impl KeyTuningEditor {
+    pub fn new(key_tuning: [f32; 128]) -> Self {
+        let texts = key_tuning
+            .into_iter()
+            .map(|cents| KeyDetuneValue::new(cents).get_formatted().into())
+            .collect();
+
+        Self { texts }
+    }
+
+    pub fn text(&self, key: u8) -> Option<&str> {
+        self.texts.get(key as usize).map(String::as_str)
+    }
+
+    pub fn set_text(&mut self, key: u8, text: String) {
+        if let Some(t) = self.texts.get_mut(key as usize) {
+            *t = text;
+        }
+    }
+
+    /// Replace input text of key with formatted detuning, e.g., after
+    /// submitting it
+    pub fn set_cents(&mut self, key: u8, cents: f32) {
+        self.set_text(key, KeyDetuneValue::new(cents).get_formatted().into());
+    }
+
+    pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
+        let mut rows = Column::new().spacing(LINE_HEIGHT / 4);
+
+        for (key, text) in (0..=127u8).zip(self.texts.iter()) {
+            let input = TextInput::new("0.0c", text, move |text| Message::KeyTuningInput {
+                key,
+                text,
+            })
+            .on_submit(Message::KeyTuningSubmit(key))
+            .font(theme.font_regular())
+            .size(FONT_SIZE)
+            .padding(theme.picklist_padding())
+            .width(Length::Fixed(f32::from(LINE_HEIGHT * 8)));
+
+            rows = rows.push(
+                Row::new()
+                    .spacing(LINE_HEIGHT / 2)
+                    .align_items(Alignment::Center)
+                    .push(
+                        Text::new(format_key(key).to_string())
+                            .size(FONT_SIZE)
+                            .font(theme.font_bold())
+                            .width(Length::Fixed(f32::from(LINE_HEIGHT * 3))),
+                    )
+                    .push(
+                        Text::new(key.to_string())
+                            .size(FONT_SIZE)
+                            .font(theme.font_regular())
+                            .width(Length::Fixed(f32::from(LINE_HEIGHT * 2)))
+                            .horizontal_alignment(Horizontal::Right),
+                    )
+                    .push(input),
+            );
+        }
+
+        Column::new()
+            .spacing(LINE_HEIGHT / 2)
+            .push(
+                Text::new(HELP_TEXT)
+                    .size(FONT_SIZE)
+                    .font(theme.font_regular()),
+            )
+            .push(Scrollable::new(rows).height(Length::Fixed(f32::from(LINE_HEIGHT * 24))))
+            .into()
+    }
+}
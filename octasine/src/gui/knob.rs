@@ -1,19 +1,25 @@
-use iced_audio::{graphics::knob, text_marks, tick_marks, Normal, NormalParam};
+use compact_str::CompactString;
+use iced_audio::{graphics::knob, text_marks, tick_marks, ModulationRange, Normal, NormalParam};
 use iced_baseview::widget::tooltip::Position;
 use iced_baseview::widget::Container;
 use iced_baseview::{
-    alignment::Horizontal, keyboard::Modifiers, widget::Column, widget::Space, widget::Text,
-    Alignment, Element, Length,
+    alignment::Horizontal, keyboard::Modifiers, widget::Column, widget::Row, widget::Space,
+    widget::Text, Alignment, Element, Length,
 };
 
 use crate::parameters::glide_time::GlideTimeValue;
 use crate::parameters::master_pitch_bend_range::{
     MasterPitchBendRangeDownValue, MasterPitchBendRangeUpValue,
 };
-use crate::parameters::velocity_sensitivity::VelocitySensitivityValue;
+use crate::parameters::utils::format_frequency_as_key;
+use crate::parameters::velocity_mod::VelocityModAmountValue;
+use crate::parameters::velocity_sensitivity::{
+    MixOutVelocitySensitivityValue, VelocitySensitivityValue,
+};
 use crate::parameters::{
-    LfoAmountValue, LfoFrequencyFreeValue, LfoFrequencyRatioValue, LfoParameter,
-    MasterFrequencyValue, MasterParameter, MasterVolumeValue, OperatorFeedbackValue,
+    LfoAmountValue, LfoFrequencyFreeValue, LfoFrequencyRatioValue, LfoModWheelDepthValue,
+    LfoParameter, LfoSmoothingValue, MasterFrequencyValue, MasterPanValue, MasterParameter,
+    MasterVolumeValue, OperatorFeedbackToneValue, OperatorFeedbackValue,
     OperatorFrequencyFineValue, OperatorFrequencyFreeValue, OperatorFrequencyRatioValue,
     OperatorMixOutValue, OperatorModOutValue, OperatorPanningValue, OperatorParameter,
     OperatorVolumeValue, Parameter, ParameterValue, WrappedParameter,
@@ -27,6 +33,13 @@ use super::value_text::ValueText;
 use super::{Message, LINE_HEIGHT};
 
 const KNOB_SIZE: Length = Length::Fixed((LINE_HEIGHT * 2) as f32);
+/// Hide modulation range when no update has been received in this many frames
+const MODULATION_TIMEOUT_FRAMES: usize = 30;
+/// Width of edited marker next to title
+const EDITED_MARKER_WIDTH: Length = Length::Fixed((LINE_HEIGHT / 2) as f32);
+/// Patch value difference from loaded value below which knob isn't
+/// considered edited
+const EDITED_EPSILON: f32 = 1e-5;
 
 enum TickMarkType {
     MinMaxAndDefault,
@@ -50,13 +63,34 @@ pub fn master_frequency<H>(sync_handle: &H) -> OctaSineKnob<MasterFrequencyValue
 where
     H: GuiSyncHandle,
 {
-    OctaSineKnob::new(
+    let mut knob = OctaSineKnob::new(
         sync_handle,
         Parameter::Master(MasterParameter::Frequency),
         "FREQ",
         "Master frequency",
         TickMarkType::MinMaxAndDefault,
         KnobStyle::Bipolar,
+    );
+
+    // Display as key name with cent offset, e.g., "A4 +8c" for 442 Hz
+    knob.set_value_format(Some(|v| {
+        format_frequency_as_key(MasterFrequencyValue::new_from_patch(v).get())
+    }));
+
+    knob
+}
+
+pub fn master_pan<H>(sync_handle: &H) -> OctaSineKnob<MasterPanValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Master(MasterParameter::Pan),
+        "PAN",
+        "Master pan",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Bipolar,
     )
 }
 
@@ -211,6 +245,23 @@ where
     )
 }
 
+pub fn operator_feedback_tone<H>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> OctaSineKnob<OperatorFeedbackToneValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::FeedbackTone),
+        "FB TONE",
+        "Feedback lowpass filtering, taming harshness of high feedback",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
 pub fn operator_frequency_ratio<H>(
     sync_handle: &H,
     operator_index: usize,
@@ -302,6 +353,26 @@ where
     )
 }
 
+pub fn operator_mix_out_velocity_sensitivity<H>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> OctaSineKnob<MixOutVelocitySensitivityValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Operator(
+            operator_index as u8,
+            OperatorParameter::VelocitySensitivityMixOut,
+        ),
+        "MIX VS",
+        "Mix output velocity sensitivity",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
 pub fn lfo_frequency_ratio<H>(
     sync_handle: &H,
     lfo_index: usize,
@@ -350,6 +421,51 @@ where
     )
 }
 
+pub fn lfo_smoothing<H>(sync_handle: &H, lfo_index: usize) -> OctaSineKnob<LfoSmoothingValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::Smoothing),
+        "SMOOTH",
+        "LFO output slew time. Softens abrupt changes in square and S&H shapes",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn lfo_mod_wheel_depth<H>(
+    sync_handle: &H,
+    lfo_index: usize,
+) -> OctaSineKnob<LfoModWheelDepthValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::ModWheelDepth),
+        "MOD WHL",
+        "How much mod wheel position scales LFO amount",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn velocity_mod_amount<H>(sync_handle: &H) -> OctaSineKnob<VelocityModAmountValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Master(MasterParameter::VelocityModAmount),
+        "VEL MOD",
+        "How much note velocity affects target parameter",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Bipolar,
+    )
+}
+
 pub struct OctaSineKnob<P: ParameterValue> {
     text_marks: Option<text_marks::Group>,
     tick_marks: Option<tick_marks::Group>,
@@ -361,6 +477,13 @@ pub struct OctaSineKnob<P: ParameterValue> {
     parameter: WrappedParameter,
     phantom_data: ::std::marker::PhantomData<P>,
     knob_style: KnobStyle,
+    /// Patch value addition from LFO modulation published by audio thread
+    modulation: Option<f32>,
+    frames_without_modulation_update: usize,
+    mod_range: Option<ModulationRange>,
+    /// Patch value when patch was loaded (or GUI was opened)
+    loaded_value: f32,
+    edited: bool,
 }
 
 impl<P> OctaSineKnob<P>
@@ -403,8 +526,10 @@ where
     ) -> Self {
         let parameter = parameter.into();
 
+        let loaded_value = sync_handle.get_parameter(parameter);
+
         let value = NormalParam {
-            value: Normal::from_clipped(sync_handle.get_parameter(parameter)),
+            value: Normal::from_clipped(loaded_value),
             default: Normal::from_clipped(default_patch_value),
         };
         let value_text = ValueText::new(sync_handle, parameter);
@@ -426,6 +551,11 @@ where
             parameter,
             phantom_data: ::std::marker::PhantomData::default(),
             knob_style,
+            modulation: None,
+            frames_without_modulation_update: 0,
+            mod_range: None,
+            loaded_value,
+            edited: false,
         }
     }
     pub fn set_value(&mut self, value: f32) {
@@ -436,15 +566,85 @@ where
         self.value.update(Normal::from_clipped(value));
 
         self.value_text.set_value(value);
+
+        self.update_mod_range();
+        self.update_edited();
+    }
+
+    /// Set patch value that knob value is compared to when showing whether
+    /// it has been edited
+    pub fn set_loaded_value(&mut self, value: f32) {
+        self.loaded_value = value;
+
+        self.update_edited();
+    }
+
+    fn update_edited(&mut self) {
+        self.edited = (self.value.value.as_f32() - self.loaded_value).abs() > EDITED_EPSILON;
+    }
+
+    pub fn update_modulation(&mut self, opt_modulation: Option<f32>) {
+        match opt_modulation {
+            Some(modulation) => {
+                self.modulation = Some(modulation);
+                self.frames_without_modulation_update = 0;
+            }
+            None if self.modulation.is_some() => {
+                self.frames_without_modulation_update += 1;
+
+                if self.frames_without_modulation_update > MODULATION_TIMEOUT_FRAMES {
+                    self.modulation = None;
+                }
+            }
+            None => (),
+        }
+
+        self.update_mod_range();
+    }
+
+    /// Arc from current parameter value to modulated value
+    fn update_mod_range(&mut self) {
+        self.mod_range = self.modulation.map(|modulation| {
+            let value = self.value.value.as_f32();
+
+            ModulationRange::new(
+                Normal::from_clipped(value),
+                Normal::from_clipped(value + modulation),
+            )
+        });
+    }
+
+    pub fn set_value_format(&mut self, format: Option<fn(f32) -> CompactString>) {
+        self.value_text.set_format(format);
     }
 
     pub fn view<'a>(&'a self, theme: &Theme) -> Element<Message, Theme> {
+        self.view_with_header(theme, None)
+    }
+
+    /// View with optional extra widget, e.g. a target picker, between title
+    /// and knob. Total height is unchanged.
+    pub fn view_with_header<'a>(
+        &'a self,
+        theme: &Theme,
+        header: Option<Element<'a, Message, Theme>>,
+    ) -> Element<'a, Message, Theme> {
         let title = Text::new(self.title.clone())
             .horizontal_alignment(Horizontal::Center)
             .font(theme.font_bold())
             .height(Length::Fixed(LINE_HEIGHT.into()));
         let title = tooltip(theme, &self.tooltip_text, Position::Top, title);
 
+        // Marker is balanced by space of same width, keeping title centered
+        let edited_marker = Text::new(if self.edited { "•" } else { "" })
+            .font(theme.font_bold())
+            .width(EDITED_MARKER_WIDTH)
+            .height(Length::Fixed(LINE_HEIGHT.into()));
+        let title = Row::new()
+            .push(Space::with_width(EDITED_MARKER_WIDTH))
+            .push(title)
+            .push(edited_marker);
+
         let parameter = self.parameter;
 
         let modifier_keys = Modifiers::SHIFT;
@@ -465,19 +665,35 @@ where
         if let Some(tick_marks) = self.tick_marks.as_ref() {
             knob = knob.tick_marks(tick_marks);
         }
+        if let Some(mod_range) = self.mod_range.as_ref() {
+            knob = knob.mod_range(mod_range);
+        }
 
-        Container::new(
-            Column::new()
-                .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
-                .align_items(Alignment::Center)
-                .push(title)
+        let column = Column::new()
+            .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+            .align_items(Alignment::Center)
+            .push(title);
+
+        let column = if let Some(header) = header {
+            column
+                .push(Space::with_height(Length::Fixed(f32::from(
+                    LINE_HEIGHT / 2,
+                ))))
+                .push(header)
+                .push(Space::with_height(Length::Fixed(f32::from(
+                    LINE_HEIGHT / 2,
+                ))))
+                .push(knob)
+        } else {
+            column
                 .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
                 .push(knob)
                 .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
-                .push(self.value_text.view(theme)),
-        )
-        .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
-        .into()
+        };
+
+        Container::new(column.push(self.value_text.view(theme)))
+            .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
+            .into()
     }
 }
 
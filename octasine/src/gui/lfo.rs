@@ -2,53 +2,89 @@ use iced_baseview::widget::tooltip::Position;
 use iced_baseview::widget::Container;
 use iced_baseview::{
     alignment::Horizontal, alignment::Vertical, widget::Column, widget::Row, widget::Space,
-    widget::Text, Element, Length,
+    widget::Text, Alignment, Element, Length,
 };
 
+use crate::common::NUM_LFO_TARGETS;
 use crate::parameters::{
-    LfoAmountValue, LfoFrequencyFreeValue, LfoFrequencyRatioValue, LfoParameter, LfoShapeValue,
-    Parameter,
+    LfoAmountValue, LfoFrequencyFreeValue, LfoFrequencyRatioValue, LfoModWheelDepthValue,
+    LfoModeValue, LfoParameter, LfoShapeValue, LfoSmoothingValue, LfoTargetDepthValue, Parameter,
+    ParameterSection,
 };
 use crate::sync::GuiSyncHandle;
 
 use super::boolean_button::{
-    lfo_active_button, lfo_bpm_sync_button, lfo_key_sync_button, lfo_mode_button, BooleanButton,
+    lfo_active_button, lfo_bpm_sync_button, lfo_global_button, lfo_key_sync_button,
+    lfo_mode_button, BooleanButton,
+};
+use super::common::{
+    button_with_tooltip, container_l1, container_l2, container_l3, space_l3, tooltip,
 };
-use super::common::{container_l1, container_l2, container_l3, space_l3, tooltip};
 use super::knob::{self, OctaSineKnob};
 use super::lfo_target_picker::LfoTargetPicker;
 use super::style::Theme;
+use super::value_text::ValueText;
 use super::wave_picker::WavePicker;
 use super::{Message, FONT_SIZE, LINE_HEIGHT};
 
 pub struct LfoWidgets {
     index: usize,
     pub target: LfoTargetPicker,
+    pub additional_targets: [LfoTargetPicker; NUM_LFO_TARGETS - 1],
+    pub additional_target_depths: [ValueText<LfoTargetDepthValue>; NUM_LFO_TARGETS - 1],
     pub shape: WavePicker<LfoShapeValue>,
     pub mode: BooleanButton,
+    pub mode_text: ValueText<LfoModeValue>,
     pub bpm_sync: BooleanButton,
     pub key_sync: BooleanButton,
+    pub global: BooleanButton,
     pub frequency_ratio: OctaSineKnob<LfoFrequencyRatioValue>,
     pub frequency_free: OctaSineKnob<LfoFrequencyFreeValue>,
     pub amount: OctaSineKnob<LfoAmountValue>,
     pub active: BooleanButton,
+    pub smoothing: OctaSineKnob<LfoSmoothingValue>,
+    pub mod_wheel_depth: OctaSineKnob<LfoModWheelDepthValue>,
+    pub alternative_controls: bool,
 }
 
 impl LfoWidgets {
     pub fn new<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> Self {
         let lfo_wave_type_parameter = Parameter::Lfo(lfo_index as u8, LfoParameter::Shape);
 
+        let additional_targets = [
+            LfoParameter::Target2,
+            LfoParameter::Target3,
+            LfoParameter::Target4,
+        ]
+        .map(|p| LfoTargetPicker::new(sync_handle, lfo_index, p));
+        let additional_target_depths = [
+            LfoParameter::Target2Depth,
+            LfoParameter::Target3Depth,
+            LfoParameter::Target4Depth,
+        ]
+        .map(|p| ValueText::new(sync_handle, Parameter::Lfo(lfo_index as u8, p).into()));
+
         Self {
             index: lfo_index,
-            target: LfoTargetPicker::new(sync_handle, lfo_index),
+            target: LfoTargetPicker::new(sync_handle, lfo_index, LfoParameter::Target),
+            additional_targets,
+            additional_target_depths,
             shape: WavePicker::new(sync_handle, lfo_wave_type_parameter, "SHAPE"),
             mode: lfo_mode_button(sync_handle, lfo_index),
+            mode_text: ValueText::new(
+                sync_handle,
+                Parameter::Lfo(lfo_index as u8, LfoParameter::Mode).into(),
+            ),
             bpm_sync: lfo_bpm_sync_button(sync_handle, lfo_index),
             key_sync: lfo_key_sync_button(sync_handle, lfo_index),
+            global: lfo_global_button(sync_handle, lfo_index),
             frequency_ratio: knob::lfo_frequency_ratio(sync_handle, lfo_index),
             frequency_free: knob::lfo_frequency_free(sync_handle, lfo_index),
             amount: knob::lfo_amount(sync_handle, lfo_index),
             active: lfo_active_button(sync_handle, lfo_index),
+            smoothing: knob::lfo_smoothing(sync_handle, lfo_index),
+            mod_wheel_depth: knob::lfo_mod_wheel_depth(sync_handle, lfo_index),
+            alternative_controls: false,
         }
     }
 
@@ -56,6 +92,7 @@ impl LfoWidgets {
         self.mode.theme_changed();
         self.bpm_sync.theme_changed();
         self.key_sync.theme_changed();
+        self.global.theme_changed();
         self.active.theme_changed();
         self.shape.theme_changed();
     }
@@ -78,7 +115,7 @@ impl LfoWidgets {
 
         let mode = tooltip(
             theme,
-            "Toggle oneshot mode",
+            "Toggle oneshot mode. Retrigger modes are available in alternative controls",
             Position::Top,
             self.mode.view(),
         );
@@ -89,6 +126,62 @@ impl LfoWidgets {
             Position::Top,
             self.key_sync.view(),
         );
+        let global = tooltip(
+            theme,
+            "Toggle global mode: share a single LFO between all voices",
+            Position::Top,
+            self.global.view(),
+        );
+        let reset = button_with_tooltip(
+            theme,
+            theme.font_regular(),
+            "R",
+            Message::ResetSection(ParameterSection::Lfo(self.index as u8)),
+            "Reset LFO to init patch values",
+        );
+
+        // Alternative controls replace title and primary target picker with
+        // mode (including retrigger modes) and pickers for additional
+        // targets and their depths
+        let targets: Element<Message, Theme> = if self.alternative_controls {
+            let mode_row = Row::new()
+                .push(
+                    Text::new("MODE")
+                        .size(FONT_SIZE)
+                        .font(theme.font_bold())
+                        .width(Length::Fill),
+                )
+                .push(
+                    Container::new(self.mode_text.view(theme))
+                        .width(Length::Fixed(f32::from(LINE_HEIGHT * 5))),
+                );
+
+            self.additional_targets
+                .iter()
+                .zip(self.additional_target_depths.iter())
+                .fold(
+                    Column::new().spacing(2).push(mode_row),
+                    |column, (target, depth)| {
+                        column.push(
+                            Row::new()
+                                .align_items(Alignment::Center)
+                                .push(target.view(theme))
+                                .push(Space::with_width(Length::Fixed(3.0)))
+                                .push(
+                                    Container::new(depth.view(theme))
+                                        .width(Length::Fixed(f32::from(LINE_HEIGHT * 3))),
+                                ),
+                        )
+                    },
+                )
+                .into()
+        } else {
+            Column::new()
+                .push(title)
+                .push(Space::with_height(Length::Fixed(f32::from(LINE_HEIGHT))))
+                .push(Row::new().push(self.target.view(theme)))
+                .into()
+        };
 
         container_l1(
             Row::new()
@@ -102,16 +195,18 @@ impl LfoWidgets {
                                     .push(active)
                                     .push(Space::with_width(Length::Fixed(3.0)))
                                     .push(key_sync)
+                                    .push(Space::with_width(Length::Fixed(3.0)))
+                                    .push(global)
+                                    .push(Space::with_width(Length::Fixed(3.0)))
+                                    .push(reset)
                                     .push(Space::with_width(Length::Fixed(f32::from(
-                                        LINE_HEIGHT * 5 - 6 - 1,
+                                        LINE_HEIGHT * 4 - 6 - 1 - 3 - 3 - 16,
                                     ))))
                                     .push(bpm_sync)
                                     .push(Space::with_width(Length::Fixed(3.0)))
                                     .push(mode),
                             )
-                            .push(title)
-                            .push(Space::with_height(Length::Fixed(f32::from(LINE_HEIGHT))))
-                            .push(Row::new().push(self.target.view(theme))),
+                            .push(targets),
                     )
                     .width(Length::Fixed(f32::from(LINE_HEIGHT * 9))),
                 )
@@ -122,9 +217,17 @@ impl LfoWidgets {
                         .push(space_l3())
                         .push(container_l3(self.amount.view(theme)))
                         .push(space_l3())
-                        .push(container_l3(self.frequency_ratio.view(theme)))
+                        .push(if self.alternative_controls {
+                            container_l3(self.mod_wheel_depth.view(theme))
+                        } else {
+                            container_l3(self.frequency_ratio.view(theme))
+                        })
                         .push(space_l3())
-                        .push(container_l3(self.frequency_free.view(theme))),
+                        .push(if self.alternative_controls {
+                            container_l3(self.smoothing.view(theme))
+                        } else {
+                            container_l3(self.frequency_free.view(theme))
+                        }),
                 )),
         )
         .into()
@@ -1,11 +1,12 @@
 use iced_baseview::widget::PickList;
 use iced_baseview::{Element, Length};
 
-use crate::parameters::lfo_target::LfoTargetParameter;
+use crate::parameters::lfo_target::{LfoTargetParameter, LFO_TARGETS};
+use crate::parameters::velocity_mod::VelocityModTargetValue;
 use crate::parameters::{
     get_lfo_target_parameters, Lfo1TargetParameterValue, Lfo2TargetParameterValue,
-    Lfo3TargetParameterValue, Lfo4TargetParameterValue, LfoParameter, Parameter, ParameterValue,
-    WrappedParameter,
+    Lfo3TargetParameterValue, Lfo4TargetParameterValue, LfoParameter, MasterParameter, Parameter,
+    ParameterValue, WrappedParameter,
 };
 
 use super::{style::Theme, GuiSyncHandle, Message, FONT_SIZE};
@@ -22,19 +23,84 @@ impl ToString for LfoTarget {
     }
 }
 
+/// Modulation source whose target is picked
+#[derive(Clone, Copy)]
+enum ModulationSource {
+    Lfo(usize),
+    Velocity,
+}
+
+impl ModulationSource {
+    fn target_parameters(self) -> &'static [LfoTargetParameter] {
+        match self {
+            Self::Lfo(lfo_index) => get_lfo_target_parameters(lfo_index),
+            Self::Velocity => LFO_TARGETS,
+        }
+    }
+
+    fn target_from_patch(self, sync_value: f32) -> LfoTargetParameter {
+        match self {
+            Self::Lfo(0) => Lfo1TargetParameterValue::new_from_patch(sync_value).0,
+            Self::Lfo(1) => Lfo2TargetParameterValue::new_from_patch(sync_value).0,
+            Self::Lfo(2) => Lfo3TargetParameterValue::new_from_patch(sync_value).0,
+            Self::Lfo(3) => Lfo4TargetParameterValue::new_from_patch(sync_value).0,
+            Self::Velocity => VelocityModTargetValue::new_from_patch(sync_value).0,
+            Self::Lfo(_) => unreachable!(),
+        }
+    }
+
+    fn target_to_patch(self, target: LfoTargetParameter) -> f32 {
+        match self {
+            Self::Lfo(0) => Lfo1TargetParameterValue::new_from_audio(target).to_patch(),
+            Self::Lfo(1) => Lfo2TargetParameterValue::new_from_audio(target).to_patch(),
+            Self::Lfo(2) => Lfo3TargetParameterValue::new_from_audio(target).to_patch(),
+            Self::Lfo(3) => Lfo4TargetParameterValue::new_from_audio(target).to_patch(),
+            Self::Velocity => VelocityModTargetValue::new_from_audio(target).to_patch(),
+            Self::Lfo(_) => unreachable!(),
+        }
+    }
+}
+
 pub struct LfoTargetPicker {
     options: Vec<LfoTarget>,
     selected: usize,
-    lfo_index: usize,
+    source: ModulationSource,
     parameter: WrappedParameter,
 }
 
 impl LfoTargetPicker {
-    pub fn new<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> Self {
-        let parameter = Parameter::Lfo(lfo_index as u8, LfoParameter::Target).into();
+    /// Picker for one of the LFO target parameters (`Target`, `Target2`,
+    /// `Target3` or `Target4`)
+    pub fn new<H: GuiSyncHandle>(
+        sync_handle: &H,
+        lfo_index: usize,
+        parameter: LfoParameter,
+    ) -> Self {
+        Self::new_for_source(
+            sync_handle,
+            ModulationSource::Lfo(lfo_index),
+            Parameter::Lfo(lfo_index as u8, parameter),
+        )
+    }
+
+    /// Picker for velocity modulation target, which can be any LFO target
+    pub fn new_velocity_mod<H: GuiSyncHandle>(sync_handle: &H) -> Self {
+        Self::new_for_source(
+            sync_handle,
+            ModulationSource::Velocity,
+            Parameter::Master(MasterParameter::VelocityModTarget),
+        )
+    }
+
+    fn new_for_source<H: GuiSyncHandle>(
+        sync_handle: &H,
+        source: ModulationSource,
+        parameter: Parameter,
+    ) -> Self {
+        let parameter = parameter.into();
         let sync_value = sync_handle.get_parameter(parameter);
-        let selected = Self::get_index_from_sync(lfo_index, sync_value);
-        let target_parameters = get_lfo_target_parameters(lfo_index);
+        let selected = Self::get_index_from_sync(source, sync_value);
+        let target_parameters = source.target_parameters();
 
         let options = target_parameters
             .iter()
@@ -47,21 +113,15 @@ impl LfoTargetPicker {
         Self {
             options,
             selected,
-            lfo_index,
+            source,
             parameter,
         }
     }
 
-    fn get_index_from_sync(lfo_index: usize, sync_value: f32) -> usize {
-        let target = match lfo_index {
-            0 => Lfo1TargetParameterValue::new_from_patch(sync_value).0,
-            1 => Lfo2TargetParameterValue::new_from_patch(sync_value).0,
-            2 => Lfo3TargetParameterValue::new_from_patch(sync_value).0,
-            3 => Lfo4TargetParameterValue::new_from_patch(sync_value).0,
-            _ => unreachable!(),
-        };
+    fn get_index_from_sync(source: ModulationSource, sync_value: f32) -> usize {
+        let target = source.target_from_patch(sync_value);
 
-        let target_parameters = get_lfo_target_parameters(lfo_index);
+        let target_parameters = source.target_parameters();
 
         for (i, t) in target_parameters.iter().enumerate() {
             if *t == target {
@@ -73,24 +133,18 @@ impl LfoTargetPicker {
     }
 
     pub fn set_value(&mut self, sync_value: f32) {
-        self.selected = Self::get_index_from_sync(self.lfo_index, sync_value);
+        self.selected = Self::get_index_from_sync(self.source, sync_value);
     }
 
     pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
-        let lfo_index = self.lfo_index;
+        let source = self.source;
         let parameter = self.parameter;
 
         PickList::new(
             &self.options[..],
             Some(self.options[self.selected].clone()),
             move |option| {
-                let sync = match lfo_index {
-                    0 => Lfo1TargetParameterValue::new_from_audio(option.value).to_patch(),
-                    1 => Lfo2TargetParameterValue::new_from_audio(option.value).to_patch(),
-                    2 => Lfo3TargetParameterValue::new_from_audio(option.value).to_patch(),
-                    3 => Lfo4TargetParameterValue::new_from_audio(option.value).to_patch(),
-                    _ => unreachable!(),
-                };
+                let sync = source.target_to_patch(option.value);
 
                 Message::ChangeSingleParameterImmediate(parameter, sync)
             },
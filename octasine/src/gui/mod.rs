@@ -2,23 +2,34 @@ mod boolean_button;
 mod common;
 mod corner;
 mod envelope;
+mod expression_strip;
+mod file_dialogs;
+mod import_worker;
+mod key_tuning;
 mod knob;
 mod lfo;
 mod lfo_target_picker;
 mod mod_matrix;
+mod mod_out_meter;
 mod mod_target_picker;
 mod operator;
 mod patch_picker;
+mod patch_search;
 pub mod style;
+mod value_panel;
+mod value_readout;
 mod value_text;
+mod voice_list;
 mod wave_display;
 mod wave_picker;
 
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
-use cfg_if::cfg_if;
+use arrayvec::ArrayVec;
 use compact_str::CompactString;
 use iced_aw::native::{Card, Modal};
 use iced_baseview::alignment::Horizontal;
@@ -26,14 +37,18 @@ use iced_baseview::command::Action;
 use iced_baseview::widget::{Button, PickList, Text};
 use iced_baseview::{executor, window::WindowSubs, Application, Command, Subscription};
 use iced_baseview::{
-    widget::Column, widget::Container, widget::Row, widget::Space, window::WindowQueue, Element,
-    Length, Point,
+    widget::Column, widget::Container, widget::Row, widget::Space, window::WindowQueue, Alignment,
+    Element, Length, Point,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::audio::gen::SimdBackend;
+use crate::audio::status::VoiceSnapshot;
+use crate::audio::AudioSettings;
 use crate::common::NUM_OPERATORS;
+use crate::parameters::mod_out_scaling::{ModOutScaling, ModOutScalingValue};
 use crate::parameters::*;
-use crate::sync::GuiSyncHandle;
+use crate::sync::{GuiSyncHandle, ImportFiles, Patch};
 
 use lfo::LfoWidgets;
 use operator::OperatorWidgets;
@@ -41,13 +56,19 @@ use patch_picker::PatchPicker;
 use style::Theme;
 
 use self::corner::CornerWidgets;
-use self::operator::ModTargetPicker;
+use self::envelope::canvas::EnvelopeLevelScale;
+use self::expression_strip::{ExpressionStrip, ExpressionStripKind};
+use self::import_worker::ImportWorker;
+use self::key_tuning::KeyTuningEditor;
+use self::operator::{BackwardModTargetPicker, ModTargetPicker};
+use self::patch_search::PatchSearch;
 use self::style::container::ContainerStyle;
+use self::value_readout::ValueReadout;
 
 use crate::settings::Settings;
 
 pub const GUI_WIDTH: usize = 12 * 82;
-pub const GUI_HEIGHT: usize = 12 * 55;
+pub const GUI_HEIGHT: usize = 12 * 57;
 
 const FONT_SIZE: u16 = 12;
 const LINE_HEIGHT: u16 = 12;
@@ -77,13 +98,87 @@ impl SnapPoint for Point {
 
 pub struct GuiSettings {
     pub theme: style::Theme,
+    #[serde(default)]
+    pub frame_rate_limit: FrameRateLimit,
+    /// Redraw window every frame, even if nothing changed
+    #[serde(default)]
+    pub redraw_when_idle: bool,
+    /// Directory that patches or banks were most recently loaded from
+    #[serde(default)]
+    pub import_directory: Option<PathBuf>,
+    /// Directory that a patch or bank was most recently saved to
+    #[serde(default)]
+    pub export_directory: Option<PathBuf>,
 }
 
+/// Per-instance editor view state, stored in plugin state chunk so that
+/// reopening a project restores the editor as it was left
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuiState {
+    /// Theme selected in this instance, overriding theme from settings
+    pub theme: Option<style::Theme>,
+    /// Envelope viewport factor and x offset for each operator
+    pub envelope_viewports: Option<[(f32, f32); NUM_OPERATORS]>,
+    /// Envelope level scale (linear or decibel) for each operator
+    pub envelope_level_scales: Option<[EnvelopeLevelScale; NUM_OPERATORS]>,
+    pub envelopes_linked: bool,
+    pub patch_preview: bool,
+    pub alternative_controls: bool,
+}
+
+impl GuiState {
+    pub fn from_json(json: &str) -> Self {
+        match serde_json::from_str(json) {
+            Ok(state) => state,
+            Err(err) => {
+                ::log::warn!("Couldn't parse GUI state: {}", err);
+
+                Self::default()
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("serialize GUI state")
+    }
+}
+
+/// Maximum rate at which GUI polls for parameter changes and updates widgets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum FrameRateLimit {
+    Fps15,
+    Fps30,
+    #[default]
+    Fps60,
+}
+
+impl FrameRateLimit {
+    fn min_frame_interval(self) -> Duration {
+        let fps = match self {
+            Self::Fps15 => 15,
+            Self::Fps30 => 30,
+            Self::Fps60 => 60,
+        };
+
+        // Allow some jitter in window frame timing
+        Duration::from_secs_f64(0.9 / f64::from(fps))
+    }
+}
+
+/// Parameter changes of a single envelope edit: at most attack, decay,
+/// sustain and release
+pub type EnvelopeParameterChanges = ArrayVec<(WrappedParameter, f32), 4>;
+
 #[derive(Debug, Clone)]
 pub enum Message {
     NoOp,
     Frame,
+    /// Begin host automation gesture, e.g., on knob grab. Values sent with
+    /// ChangeSingleParameterSetValue until ChangeSingleParameterEnd are
+    /// recorded as part of it.
     ChangeSingleParameterBegin(WrappedParameter),
+    /// End host automation gesture, e.g., on knob release
     ChangeSingleParameterEnd(WrappedParameter),
     ChangeSingleParameterSetValue(WrappedParameter, f32),
     ChangeSingleParameterImmediate(WrappedParameter, f32),
@@ -92,8 +187,7 @@ pub enum Message {
     /// Call host.begin_edit, host.automate and host.end_edit.
     ChangeEnvelopeParametersEnd {
         operator_index: u8,
-        parameter_1: (WrappedParameter, f32),
-        parameter_2: Option<(WrappedParameter, f32)>,
+        parameters: EnvelopeParameterChanges,
     },
     /// Set envelope parameters (but don't automate host for performance
     /// reasons). Broadcast all envelope values to group members.
@@ -101,10 +195,51 @@ pub enum Message {
     /// Remember to wrap calls with appropriate begin/end messages
     ChangeEnvelopeParametersSetValue {
         operator_index: u8,
-        parameter_1: (WrappedParameter, f32),
-        parameter_2: Option<(WrappedParameter, f32)>,
+        parameters: EnvelopeParameterChanges,
     },
     ChangePatch(usize),
+    /// Open patch search modal with filter box focused
+    OpenPatchSearch,
+    /// Filter box text changed
+    PatchSearchQuery(String),
+    /// Move patch search highlight by offset, e.g., with arrow keys
+    PatchSearchMoveSelection(isize),
+    /// Load patch at index and close patch search modal
+    PatchSearchLoad(usize),
+    /// Open key tuning editor for current patch
+    OpenKeyTuning,
+    /// Key tuning editor input text changed
+    KeyTuningInput {
+        key: u8,
+        text: String,
+    },
+    /// Apply key tuning editor input text of key
+    KeyTuningSubmit(u8),
+    /// Reset detuning of all keys in current patch
+    ResetKeyTuning,
+    /// Toggle playing preview note of patch highlighted in patch search
+    TogglePatchPreview(bool),
+    /// Toggle proportional scaling of envelope stage durations across
+    /// operators
+    ToggleEnvelopeLink(bool),
+    /// Toggle writing log file, stored in settings
+    ToggleLogging(bool),
+    /// Toggle hearing operator directly, with other operators silenced.
+    /// Only one operator can be auditioned at a time.
+    ToggleOperatorAudition {
+        operator_index: usize,
+        on: bool,
+    },
+    /// Force audio generation code path and store choice in settings
+    SetSimdBackendOverride(SimdBackendChoice),
+    /// Set all envelope stage parameters of operator in one host gesture
+    ApplyEnvelopePreset {
+        operator_index: u8,
+        preset: OperatorEnvelopePreset,
+    },
+    /// Set all parameters of section to init patch values in one host
+    /// gesture
+    ResetSection(ParameterSection),
     /// Set viewport, broadcast it to group members
     EnvelopeChangeViewport {
         operator_index: u8,
@@ -116,16 +251,33 @@ pub enum Message {
         viewport_factor: f32,
         x_offset: f32,
     },
+    /// Switch between linear and decibel level scale or change decibel
+    /// range
+    EnvelopeChangeLevelScale {
+        operator_index: u8,
+        level_scale: EnvelopeLevelScale,
+    },
     SwitchTheme,
     ToggleAlternativeControls,
+    ResetClipIndicator,
+    /// Send pitch bend from expression strip to audio thread
+    SendPitchBend(f32),
+    /// Send mod wheel value from expression strip to audio thread
+    SendModWheel(f32),
     SavePatch,
     SaveBank,
     LoadBankOrPatch,
     RenamePatch,
+    /// Move current patch one slot towards start (-1) or end (1) of bank
+    MovePatch(isize),
+    CopyPatch,
+    PastePatch,
     ClearPatch,
     ClearBank,
     SaveBankOrPatchToFile(PathBuf, Vec<u8>),
     LoadBankOrPatchesFromPaths(Vec<PathBuf>),
+    /// Files read and decoded by import worker, ready to be imported
+    ImportFiles(Arc<ImportFiles>),
     ChangeParameterByTextInput {
         parameter: WrappedParameter,
         value_text: CompactString,
@@ -141,6 +293,14 @@ pub enum Message {
 pub enum ModalAction {
     ClearPatch,
     ClearBank,
+    /// Show list of active voices, updated every frame
+    Voices,
+    /// Show build, instance and SIMD backend info for bug reports
+    About,
+    /// Find patch slot by name
+    PatchSearch(PatchSearch),
+    /// Detune keys of current patch
+    KeyTuning(KeyTuningEditor),
     /// Currently not used
     SetParameterByChoices {
         parameter: WrappedParameter,
@@ -149,9 +309,36 @@ pub enum ModalAction {
     },
 }
 
+/// Choice of SIMD backend override in about panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimdBackendChoice(Option<SimdBackend>);
+
+impl SimdBackendChoice {
+    /// Automatic selection followed by backends supported by CPU
+    fn supported() -> Vec<Self> {
+        let backends = [SimdBackend::Fallback, SimdBackend::Sse2, SimdBackend::Avx]
+            .into_iter()
+            .filter(|backend| backend.is_supported())
+            .map(|backend| Self(Some(backend)));
+
+        ::std::iter::once(Self(None)).chain(backends).collect()
+    }
+}
+
+impl ::std::fmt::Display for SimdBackendChoice {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match self.0 {
+            Some(backend) => write!(f, "{}", backend.to_string().to_uppercase()),
+            None => write!(f, "AUTO"),
+        }
+    }
+}
+
 pub struct OctaSineIcedApplication<H: GuiSyncHandle> {
     sync_handle: H,
     theme: style::Theme,
+    /// Theme was switched in this instance, so store it in GUI state
+    theme_overridden: bool,
     operator_1: OperatorWidgets,
     operator_2: OperatorWidgets,
     operator_3: OperatorWidgets,
@@ -162,10 +349,36 @@ pub struct OctaSineIcedApplication<H: GuiSyncHandle> {
     lfo_4: LfoWidgets,
     corner: CornerWidgets,
     modal_action: Option<ModalAction>,
+    frame_rate_limit: FrameRateLimit,
+    redraw_when_idle: bool,
+    /// SIMD backend override stored in audio settings
+    simd_backend_override: Option<SimdBackend>,
+    import_directory: Option<PathBuf>,
+    export_directory: Option<PathBuf>,
+    last_frame_update: Option<Instant>,
+    /// When on, changing a stage duration on one envelope scales the same
+    /// stage on all other envelopes proportionally
+    envelopes_linked: bool,
+    /// Parameter currently being dragged and its formatted value, shown
+    /// next to the cursor
+    value_readout: Option<(WrappedParameter, CompactString)>,
+    /// Most recently changed parameter and its formatted value, shown in
+    /// footer
+    value_panel: Option<(WrappedParameter, CompactString)>,
+    /// Index of patch slot copied with patch action, if any
+    copied_patch_index: Option<usize>,
+    /// Active voices published by audio thread, only updated while voice
+    /// list is open
+    voice_snapshots: Vec<VoiceSnapshot>,
+    import_worker: ImportWorker,
+    pitch_bend_strip: ExpressionStrip,
+    mod_wheel_strip: ExpressionStrip,
 }
 
 impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
     fn set_value(&mut self, parameter: Parameter, v: f32, internal: bool) {
+        self.corner.update_overridden_patch_defaults(parameter, v);
+
         match parameter {
             Parameter::None => (),
             Parameter::Master(MasterParameter::Volume) => self.corner.master_volume.set_value(v),
@@ -182,7 +395,7 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                 self.corner.volume_velocity_sensitivity.set_value(v)
             }
             Parameter::Master(MasterParameter::VoiceMode) => {
-                self.corner.patch_picker.voice_mode_button.set_value(v)
+                self.corner.patch_picker.voice_mode = v;
             }
             Parameter::Master(MasterParameter::GlideActive) => {
                 self.corner.glide_active = v;
@@ -195,6 +408,54 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
             Parameter::Master(MasterParameter::GlideRetrigger) => {
                 self.corner.glide_retrigger.set_value(v)
             }
+            Parameter::Master(MasterParameter::Pan) => self.corner.master_pan.set_value(v),
+            Parameter::Master(MasterParameter::OutputMode) => {
+                self.corner.output_mode = v;
+            }
+            Parameter::Master(MasterParameter::DcBlocker) => self.corner.dc_blocker.set_value(v),
+            Parameter::Master(MasterParameter::GainCompensation) => {
+                self.corner.gain_compensation.set_value(v)
+            }
+            Parameter::Master(MasterParameter::VelocityModTarget) => {
+                self.corner.velocity_mod_target.set_value(v)
+            }
+            Parameter::Master(MasterParameter::VelocityModAmount) => {
+                self.corner.velocity_mod_amount.set_value(v)
+            }
+            Parameter::Master(
+                MasterParameter::Scale
+                | MasterParameter::ScaleRoot
+                | MasterParameter::MonoNotePriority
+                | MasterParameter::UnisonVoices
+                | MasterParameter::UnisonDetune
+                | MasterParameter::UnisonSpread
+                | MasterParameter::KeyRangeLow
+                | MasterParameter::KeyRangeHigh
+                | MasterParameter::AftertouchVibratoDepth
+                | MasterParameter::AftertouchVibratoRate
+                | MasterParameter::Bypass
+                | MasterParameter::EqLowGain
+                | MasterParameter::EqLowFrequency
+                | MasterParameter::EqMidGain
+                | MasterParameter::EqMidFrequency
+                | MasterParameter::EqHighGain
+                | MasterParameter::EqHighFrequency
+                | MasterParameter::EqBypass
+                | MasterParameter::EffectOrder
+                | MasterParameter::KeyPan
+                | MasterParameter::ReleaseVelocitySensitivity
+                | MasterParameter::PitchBendSmoothing,
+            ) => (),
+            Parameter::Master(MasterParameter::ModOutScaling) => {
+                self.corner.mod_out_scaling.set_value(v);
+
+                let dx7 = ModOutScalingValue::new_from_patch(v).get() == ModOutScaling::Dx7;
+
+                self.operator_1.set_dx7_mod_out_scaling(dx7);
+                self.operator_2.set_dx7_mod_out_scaling(dx7);
+                self.operator_3.set_dx7_mod_out_scaling(dx7);
+                self.operator_4.set_dx7_mod_out_scaling(dx7);
+            }
             outer_p @ Parameter::Operator(index, p) => {
                 self.operator_1.wave_display.set_value(outer_p, v);
                 self.operator_2.wave_display.set_value(outer_p, v);
@@ -214,6 +475,10 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                     OperatorParameter::WaveType => operator.wave_type.set_value(v),
                     OperatorParameter::Volume => operator.volume.set_value(v),
                     OperatorParameter::Panning => operator.panning.set_value(v),
+                    OperatorParameter::PanningMode => operator.panning_mode_button.set_value(v),
+                    OperatorParameter::PanningLfoMode => {
+                        operator.panning_lfo_mode_button.set_value(v)
+                    }
                     OperatorParameter::MixOut => {
                         operator.mix.set_value(v);
 
@@ -226,9 +491,7 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                         }
                     }
                     OperatorParameter::ModOut => {
-                        if let Some(mod_index) = operator.mod_index.as_mut() {
-                            mod_index.set_value(v)
-                        }
+                        operator.mod_index.set_value(v);
 
                         match index {
                             1 => self.corner.modulation_matrix.set_operator_2_mod(v),
@@ -251,6 +514,14 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                             _ => (),
                         }
                     }
+                    OperatorParameter::BackwardModTargets => {
+                        match operator.backward_mod_target.as_mut() {
+                            Some(BackwardModTargetPicker::Operator1(p)) => p.set_value(v),
+                            Some(BackwardModTargetPicker::Operator2(p)) => p.set_value(v),
+                            Some(BackwardModTargetPicker::Operator3(p)) => p.set_value(v),
+                            None => (),
+                        }
+                    }
                     OperatorParameter::Feedback => operator.feedback.set_value(v),
                     OperatorParameter::FrequencyRatio => operator.frequency_ratio.set_value(v),
                     OperatorParameter::FrequencyFree => operator.frequency_free.set_value(v),
@@ -295,6 +566,17 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                     OperatorParameter::VelocitySensitivityFeedback => {
                         operator.feedback_velocity_sensitivity.set_value(v)
                     }
+                    OperatorParameter::VelocitySensitivityMixOut => {
+                        operator.mix_out_velocity_sensitivity.set_value(v)
+                    }
+                    OperatorParameter::FeedbackTone => operator.feedback_tone.set_value(v),
+                    OperatorParameter::VelocitySensitivityAttack
+                    | OperatorParameter::OutputBus
+                    | OperatorParameter::KeyScalingBreakpoint
+                    | OperatorParameter::KeyScalingLeftDepth
+                    | OperatorParameter::KeyScalingRightDepth
+                    | OperatorParameter::KeyScalingLeftCurve
+                    | OperatorParameter::KeyScalingRightCurve => (),
                 }
             }
             Parameter::Lfo(index, p) => {
@@ -308,37 +590,257 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
 
                 match p {
                     LfoParameter::Target => lfo.target.set_value(v),
+                    LfoParameter::Target2 => lfo.additional_targets[0].set_value(v),
+                    LfoParameter::Target3 => lfo.additional_targets[1].set_value(v),
+                    LfoParameter::Target4 => lfo.additional_targets[2].set_value(v),
+                    LfoParameter::Target2Depth => lfo.additional_target_depths[0].set_value(v),
+                    LfoParameter::Target3Depth => lfo.additional_target_depths[1].set_value(v),
+                    LfoParameter::Target4Depth => lfo.additional_target_depths[2].set_value(v),
                     LfoParameter::BpmSync => lfo.bpm_sync.set_value(v),
                     LfoParameter::FrequencyRatio => lfo.frequency_ratio.set_value(v),
                     LfoParameter::FrequencyFree => lfo.frequency_free.set_value(v),
-                    LfoParameter::Mode => lfo.mode.set_value(v),
+                    LfoParameter::Mode => {
+                        lfo.mode.set_value(v);
+                        lfo.mode_text.set_value(v);
+                    }
                     LfoParameter::Shape => lfo.shape.set_value(v),
                     LfoParameter::Amount => lfo.amount.set_value(v),
                     LfoParameter::Active => lfo.active.set_value(v),
                     LfoParameter::KeySync => lfo.key_sync.set_value(v),
+                    LfoParameter::Global => lfo.global.set_value(v),
+                    LfoParameter::Smoothing => lfo.smoothing.set_value(v),
+                    LfoParameter::ModWheelDepth => lfo.mod_wheel_depth.set_value(v),
+                }
+            }
+        }
+    }
+
+    /// Update modulation ring of knob of LFO target parameter
+    fn set_modulation(&mut self, parameter: Parameter, modulation: Option<f32>) {
+        match parameter {
+            Parameter::Master(MasterParameter::Volume) => {
+                self.corner.master_volume.update_modulation(modulation)
+            }
+            Parameter::Master(MasterParameter::Frequency) => {
+                self.corner.master_frequency.update_modulation(modulation)
+            }
+            Parameter::Master(MasterParameter::Pan) => {
+                self.corner.master_pan.update_modulation(modulation)
+            }
+            Parameter::Master(MasterParameter::GlideTime) => {
+                self.corner.glide_time.update_modulation(modulation)
+            }
+            Parameter::Operator(index, p) => {
+                let operator = match index {
+                    0 => &mut self.operator_1,
+                    1 => &mut self.operator_2,
+                    2 => &mut self.operator_3,
+                    3 => &mut self.operator_4,
+                    _ => panic!("No such operator"),
+                };
+
+                match p {
+                    OperatorParameter::Volume => operator.volume.update_modulation(modulation),
+                    OperatorParameter::Panning => operator.panning.update_modulation(modulation),
+                    OperatorParameter::MixOut => operator.mix.update_modulation(modulation),
+                    OperatorParameter::ModOut => operator.mod_index.update_modulation(modulation),
+                    OperatorParameter::Feedback => operator.feedback.update_modulation(modulation),
+                    OperatorParameter::FrequencyRatio => {
+                        operator.frequency_ratio.update_modulation(modulation)
+                    }
+                    OperatorParameter::FrequencyFree => {
+                        operator.frequency_free.update_modulation(modulation)
+                    }
+                    OperatorParameter::FrequencyFine => {
+                        operator.frequency_fine.update_modulation(modulation)
+                    }
+                    _ => (),
+                }
+            }
+            Parameter::Lfo(index, p) => {
+                let lfo = match index {
+                    0 => &mut self.lfo_1,
+                    1 => &mut self.lfo_2,
+                    2 => &mut self.lfo_3,
+                    3 => &mut self.lfo_4,
+                    _ => panic!("No such LFO"),
+                };
+
+                match p {
+                    LfoParameter::FrequencyRatio => {
+                        lfo.frequency_ratio.update_modulation(modulation)
+                    }
+                    LfoParameter::FrequencyFree => lfo.frequency_free.update_modulation(modulation),
+                    LfoParameter::Amount => lfo.amount.update_modulation(modulation),
+                    _ => (),
                 }
             }
+            _ => (),
         }
     }
 
-    fn update_widgets_from_parameters(&mut self) {
+    /// Update patch value that knob of parameter marks deviations from
+    fn set_loaded_value(&mut self, parameter: Parameter, v: f32) {
+        match parameter {
+            Parameter::Master(p) => {
+                let corner = &mut self.corner;
+
+                match p {
+                    MasterParameter::Volume => corner.master_volume.set_loaded_value(v),
+                    MasterParameter::Frequency => corner.master_frequency.set_loaded_value(v),
+                    MasterParameter::Pan => corner.master_pan.set_loaded_value(v),
+                    MasterParameter::PitchBendRangeUp => {
+                        corner.master_pitch_bend_up.set_loaded_value(v)
+                    }
+                    MasterParameter::PitchBendRangeDown => {
+                        corner.master_pitch_bend_down.set_loaded_value(v)
+                    }
+                    MasterParameter::VelocitySensitivityVolume => {
+                        corner.volume_velocity_sensitivity.set_loaded_value(v)
+                    }
+                    MasterParameter::GlideTime => corner.glide_time.set_loaded_value(v),
+                    MasterParameter::VelocityModAmount => {
+                        corner.velocity_mod_amount.set_loaded_value(v)
+                    }
+                    _ => (),
+                }
+            }
+            Parameter::Operator(index, p) => {
+                let operator = match index {
+                    0 => &mut self.operator_1,
+                    1 => &mut self.operator_2,
+                    2 => &mut self.operator_3,
+                    3 => &mut self.operator_4,
+                    _ => panic!("No such operator"),
+                };
+
+                match p {
+                    OperatorParameter::Volume => operator.volume.set_loaded_value(v),
+                    OperatorParameter::Panning => operator.panning.set_loaded_value(v),
+                    OperatorParameter::MixOut => operator.mix.set_loaded_value(v),
+                    OperatorParameter::ModOut => operator.mod_index.set_loaded_value(v),
+                    OperatorParameter::Feedback => operator.feedback.set_loaded_value(v),
+                    OperatorParameter::FrequencyRatio => {
+                        operator.frequency_ratio.set_loaded_value(v)
+                    }
+                    OperatorParameter::FrequencyFree => operator.frequency_free.set_loaded_value(v),
+                    OperatorParameter::FrequencyFine => operator.frequency_fine.set_loaded_value(v),
+                    OperatorParameter::VelocitySensitivityModOut => {
+                        operator.mod_out_velocity_sensitivity.set_loaded_value(v)
+                    }
+                    OperatorParameter::VelocitySensitivityFeedback => {
+                        operator.feedback_velocity_sensitivity.set_loaded_value(v)
+                    }
+                    OperatorParameter::VelocitySensitivityMixOut => {
+                        operator.mix_out_velocity_sensitivity.set_loaded_value(v)
+                    }
+                    OperatorParameter::FeedbackTone => operator.feedback_tone.set_loaded_value(v),
+                    _ => (),
+                }
+            }
+            Parameter::Lfo(index, p) => {
+                let lfo = match index {
+                    0 => &mut self.lfo_1,
+                    1 => &mut self.lfo_2,
+                    2 => &mut self.lfo_3,
+                    3 => &mut self.lfo_4,
+                    _ => panic!("No such LFO"),
+                };
+
+                match p {
+                    LfoParameter::FrequencyRatio => lfo.frequency_ratio.set_loaded_value(v),
+                    LfoParameter::FrequencyFree => lfo.frequency_free.set_loaded_value(v),
+                    LfoParameter::Amount => lfo.amount.set_loaded_value(v),
+                    LfoParameter::Smoothing => lfo.smoothing.set_loaded_value(v),
+                    LfoParameter::ModWheelDepth => lfo.mod_wheel_depth.set_loaded_value(v),
+                    _ => (),
+                }
+            }
+            Parameter::None => (),
+        }
+    }
+
+    fn update_widgets_from_parameters(&mut self, patches_changed: bool) {
         let opt_changes = self.sync_handle.get_changed_parameters();
 
         if let Some(changes) = opt_changes {
+            let mut last_change = None;
+
+            // Patch switches and imports mark all parameters as changed,
+            // while e.g. renaming patches doesn't
+            let patch_loaded =
+                patches_changed && changes[..PARAMETERS.len()].iter().all(Option::is_some);
+
             for (index, opt_new_value) in changes.iter().enumerate() {
                 if let Some(new_value) = opt_new_value {
                     if let Some(parameter) = Parameter::from_index(index) {
+                        if patch_loaded {
+                            self.set_loaded_value(parameter, *new_value);
+                        }
+
                         self.set_value(parameter, *new_value, false);
+
+                        last_change = Some((parameter, *new_value));
                     }
                 }
             }
+
+            // All parameters are marked as changed when switching patches,
+            // so only show changes made within the current patch
+            if let (false, Some((parameter, value))) = (patches_changed, last_change) {
+                let parameter = parameter.into();
+
+                self.value_panel = Some((
+                    parameter,
+                    self.sync_handle.format_parameter_value(parameter, value),
+                ));
+            }
+        }
+
+        if patches_changed {
+            self.value_panel = None;
+        }
+    }
+
+    fn change_patch(&mut self, index: usize) {
+        self.sync_handle.set_patch_index(index);
+    }
+
+    /// Play preview note of patch highlighted in patch search, if enabled
+    fn preview_patch_search_selection(&self) {
+        if !self.corner.patch_preview {
+            return;
+        }
+
+        if let Some(ModalAction::PatchSearch(search)) = self.modal_action.as_ref() {
+            if let Some(index) = search.selected_patch_index() {
+                self.sync_handle.preview_patch(index);
+            }
         }
     }
 
     fn save_settings(&self) {
+        let stored_settings = Settings::load_or_default();
+
         let settings = Settings {
             schema_version: 1,
-            gui: GuiSettings { theme: self.theme },
+            // Audio settings other than SIMD backend override, user
+            // frequency ratios and patch defaults are not editable from GUI,
+            // so keep stored values
+            audio: AudioSettings {
+                simd_backend: self.simd_backend_override,
+                ..stored_settings.audio
+            },
+            disable_logging: !self.corner.logging.is_on(),
+            user_frequency_ratios: stored_settings.user_frequency_ratios,
+            patch_defaults: stored_settings.patch_defaults,
+            gui: GuiSettings {
+                theme: self.theme,
+                frame_rate_limit: self.frame_rate_limit,
+                redraw_when_idle: self.redraw_when_idle,
+                import_directory: self.import_directory.clone(),
+                export_directory: self.export_directory.clone(),
+            },
         };
 
         if let Err(err) = settings.save() {
@@ -346,6 +848,92 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
         }
     }
 
+    /// Store view state in sync handle, for inclusion in plugin state chunk
+    fn save_gui_state(&self) {
+        let envelope_viewports = [
+            &self.operator_1,
+            &self.operator_2,
+            &self.operator_3,
+            &self.operator_4,
+        ]
+        .map(|operator| {
+            (
+                operator.envelope.widget.get_viewport_factor(),
+                operator.envelope.widget.get_x_offset(),
+            )
+        });
+        let envelope_level_scales = [
+            &self.operator_1,
+            &self.operator_2,
+            &self.operator_3,
+            &self.operator_4,
+        ]
+        .map(|operator| operator.envelope.widget.get_level_scale());
+
+        self.sync_handle.set_gui_state(&GuiState {
+            theme: self.theme_overridden.then_some(self.theme),
+            envelope_viewports: Some(envelope_viewports),
+            envelope_level_scales: Some(envelope_level_scales),
+            envelopes_linked: self.envelopes_linked,
+            patch_preview: self.corner.patch_preview,
+            alternative_controls: self.corner.alternative_controls,
+        });
+    }
+
+    /// Restore view state stored in plugin state chunk. Call on startup.
+    fn apply_gui_state(&mut self, state: GuiState) {
+        if let Some(envelope_viewports) = state.envelope_viewports {
+            for (operator_index, (viewport_factor, x_offset)) in
+                envelope_viewports.into_iter().enumerate()
+            {
+                self.get_envelope_by_index(operator_index as u8)
+                    .widget
+                    .set_viewport(viewport_factor, x_offset);
+            }
+        }
+        if let Some(envelope_level_scales) = state.envelope_level_scales {
+            for (operator_index, level_scale) in envelope_level_scales.into_iter().enumerate() {
+                self.get_envelope_by_index(operator_index as u8)
+                    .widget
+                    .set_level_scale(level_scale);
+            }
+        }
+
+        self.envelopes_linked = state.envelopes_linked;
+        self.corner.envelope_link.set_on(state.envelopes_linked);
+
+        self.corner.patch_preview = state.patch_preview;
+        self.corner
+            .patch_picker
+            .patch_preview_button
+            .set_on(state.patch_preview);
+
+        if state.alternative_controls {
+            self.toggle_alternative_controls();
+        }
+    }
+
+    fn toggle_alternative_controls(&mut self) {
+        for operator in [
+            &mut self.operator_1,
+            &mut self.operator_2,
+            &mut self.operator_3,
+            &mut self.operator_4,
+        ] {
+            operator.alternative_controls = !operator.alternative_controls;
+        }
+        for lfo in [
+            &mut self.lfo_1,
+            &mut self.lfo_2,
+            &mut self.lfo_3,
+            &mut self.lfo_4,
+        ] {
+            lfo.alternative_controls = !lfo.alternative_controls;
+        }
+
+        self.corner.alternative_controls = !self.corner.alternative_controls;
+    }
+
     fn get_envelope_by_index(&mut self, operator_index: u8) -> &mut envelope::Envelope {
         match operator_index {
             0 => &mut self.operator_1.envelope,
@@ -409,6 +997,81 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
         self.update_envelope_group_statuses();
     }
 
+    /// Get envelope stage duration parameter if envelopes are linked
+    fn get_linked_envelope_stage(&self, parameter: WrappedParameter) -> Option<OperatorParameter> {
+        match parameter.parameter() {
+            Parameter::Operator(
+                _,
+                stage @ (OperatorParameter::AttackDuration
+                | OperatorParameter::DecayDuration
+                | OperatorParameter::ReleaseDuration),
+            ) if self.envelopes_linked => Some(stage),
+            _ => None,
+        }
+    }
+
+    /// If envelopes are linked and parameter is a stage duration, scale the
+    /// same stage on other operators by the factor the duration was changed
+    /// by. Call before applying the new value.
+    fn scale_linked_envelope_stages(
+        &mut self,
+        sending_operator_index: u8,
+        change: (WrappedParameter, f32),
+    ) {
+        let (parameter, new_value) = change;
+
+        let stage = match self.get_linked_envelope_stage(parameter) {
+            Some(stage) => stage,
+            None => return,
+        };
+
+        let old_duration = envelope_duration_from_patch(self.sync_handle.get_parameter(parameter));
+        let factor = envelope_duration_from_patch(new_value) / old_duration;
+
+        if factor == 1.0 {
+            return;
+        }
+
+        for index in 0..NUM_OPERATORS as u8 {
+            if index == sending_operator_index {
+                continue;
+            }
+
+            let p: WrappedParameter = Parameter::Operator(index, stage).into();
+
+            let duration = envelope_duration_from_patch(self.sync_handle.get_parameter(p)) * factor;
+            let v = envelope_duration_to_patch(duration).min(1.0);
+
+            self.set_value(p.parameter(), v, true);
+            self.sync_handle.set_parameter_audio_only(p, v);
+        }
+    }
+
+    /// Send values of linked envelope stages to host when edit ends
+    fn automate_linked_envelope_stages(
+        &mut self,
+        sending_operator_index: u8,
+        parameter: WrappedParameter,
+    ) {
+        let stage = match self.get_linked_envelope_stage(parameter) {
+            Some(stage) => stage,
+            None => return,
+        };
+
+        for index in 0..NUM_OPERATORS as u8 {
+            if index == sending_operator_index {
+                continue;
+            }
+
+            let p: WrappedParameter = Parameter::Operator(index, stage).into();
+            let v = self.sync_handle.get_parameter(p);
+
+            self.sync_handle.begin_edit(p);
+            self.sync_handle.set_parameter(p, v);
+            self.sync_handle.end_edit(p);
+        }
+    }
+
     fn update_envelope_group_statuses(&mut self) {
         for group in [OperatorEnvelopeGroupValue::A, OperatorEnvelopeGroupValue::B] {
             let mut any_modified_by_automation = false;
@@ -469,7 +1132,9 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
     type Theme = Theme;
 
     fn new(sync_handle: Self::Flags) -> (Self, Command<Self::Message>) {
-        let style = sync_handle.get_gui_settings().theme;
+        let gui_settings = sync_handle.get_gui_settings();
+        let gui_state = sync_handle.get_gui_state();
+        let style = gui_state.theme.unwrap_or(gui_settings.theme);
 
         let operator_1 = OperatorWidgets::new(&sync_handle, 0);
         let operator_2 = OperatorWidgets::new(&sync_handle, 1);
@@ -483,9 +1148,10 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
 
         let corner = CornerWidgets::new(&sync_handle);
 
-        let app = Self {
+        let mut app = Self {
             sync_handle,
             theme: style,
+            theme_overridden: gui_state.theme.is_some(),
             operator_1,
             operator_2,
             operator_3,
@@ -496,8 +1162,24 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
             lfo_4,
             corner,
             modal_action: None,
+            frame_rate_limit: gui_settings.frame_rate_limit,
+            redraw_when_idle: gui_settings.redraw_when_idle,
+            simd_backend_override: Settings::load_or_default().audio.simd_backend,
+            import_directory: gui_settings.import_directory,
+            export_directory: gui_settings.export_directory,
+            last_frame_update: None,
+            envelopes_linked: false,
+            value_readout: None,
+            value_panel: None,
+            copied_patch_index: None,
+            voice_snapshots: Vec::new(),
+            import_worker: ImportWorker::default(),
+            pitch_bend_strip: ExpressionStrip::new(ExpressionStripKind::PitchBend),
+            mod_wheel_strip: ExpressionStrip::new(ExpressionStripKind::ModWheel),
         };
 
+        app.apply_gui_state(gui_state);
+
         (app, Command::none())
     }
 
@@ -507,7 +1189,7 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
     ) -> Subscription<Self::Message> {
         window_subs.on_frame = Some(|| Message::Frame);
 
-        Subscription::none()
+        iced_baseview::subscription::events_with(PatchSearch::handle_event)
     }
 
     #[cfg(feature = "wgpu")]
@@ -541,10 +1223,85 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
     ) -> Command<Self::Message> {
         match message {
             Message::Frame => {
-                if self.sync_handle.have_patches_changed() {
-                    self.corner.patch_picker = PatchPicker::new(&self.sync_handle);
+                let now = Instant::now();
+
+                if let Some(last_frame_update) = self.last_frame_update {
+                    if now - last_frame_update < self.frame_rate_limit.min_frame_interval() {
+                        return Command::none();
+                    }
+                }
+
+                self.last_frame_update = Some(now);
+
+                let patches_changed = self.sync_handle.have_patches_changed();
+
+                if patches_changed {
+                    self.corner.patch_picker =
+                        PatchPicker::new(&self.sync_handle, self.corner.patch_preview);
+                }
+                self.update_widgets_from_parameters(patches_changed);
+
+                if let Some(ModalAction::Voices) = self.modal_action {
+                    self.voice_snapshots = self.sync_handle.get_voice_snapshots();
+                }
+                if let Some(ModalAction::KeyTuning(editor)) = self.modal_action.as_mut() {
+                    if patches_changed {
+                        *editor = KeyTuningEditor::new(self.sync_handle.get_key_tuning());
+                    }
+                }
+
+                self.corner.dsp_load = self.sync_handle.get_dsp_load();
+                self.corner.num_active_voices = self.sync_handle.get_num_active_voices();
+                self.corner.bypassed_due_to_error = self.sync_handle.is_bypassed_due_to_error();
+                (self.corner.clipping, self.corner.clip_latched) =
+                    self.sync_handle.get_clip_status();
+
+                let mod_out_peaks = self.sync_handle.take_operator_mod_out_peaks();
+
+                for (operator, peak) in [
+                    &mut self.operator_1,
+                    &mut self.operator_2,
+                    &mut self.operator_3,
+                    &mut self.operator_4,
+                ]
+                .into_iter()
+                .zip(mod_out_peaks)
+                {
+                    operator.mod_out_meter.update_peak(peak);
+                }
+
+                let host_bpm = self.sync_handle.get_host_bpm();
+
+                for operator_index in 0..NUM_OPERATORS {
+                    self.get_envelope_by_index(operator_index as u8)
+                        .widget
+                        .set_host_bpm(host_bpm);
+                }
+
+                for (lfo_index, lfo) in [
+                    &mut self.lfo_1,
+                    &mut self.lfo_2,
+                    &mut self.lfo_3,
+                    &mut self.lfo_4,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    lfo.shape
+                        .update_playhead(self.sync_handle.get_lfo_phase(lfo_index));
+                }
+
+                for target in LFO_TARGETS.iter() {
+                    let parameter = target.parameter();
+
+                    if let Parameter::None = parameter {
+                        continue;
+                    }
+
+                    let modulation = self.sync_handle.get_lfo_target_modulation(parameter.into());
+
+                    self.set_modulation(parameter, modulation);
                 }
-                self.update_widgets_from_parameters();
             }
             Message::NoOp => {}
             Message::EnvelopeChangeViewport {
@@ -557,6 +1314,7 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                     .set_viewport(viewport_factor, x_offset);
 
                 self.sync_envelopes(operator_index, false);
+                self.save_gui_state();
             }
             Message::EnvelopeDistributeViewports {
                 viewport_factor,
@@ -567,17 +1325,44 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                         .widget
                         .set_viewport(viewport_factor, x_offset);
                 }
+
+                self.save_gui_state();
+            }
+            Message::EnvelopeChangeLevelScale {
+                operator_index,
+                level_scale,
+            } => {
+                self.get_envelope_by_index(operator_index)
+                    .widget
+                    .set_level_scale(level_scale);
+
+                self.save_gui_state();
             }
             Message::ChangeSingleParameterBegin(parameter) => {
                 self.sync_handle.begin_edit(parameter);
+
+                let value = self.sync_handle.get_parameter(parameter);
+
+                self.value_readout = Some((
+                    parameter,
+                    self.sync_handle.format_parameter_value(parameter, value),
+                ));
             }
             Message::ChangeSingleParameterEnd(parameter) => {
                 self.sync_handle.end_edit(parameter);
+
+                self.value_readout = None;
             }
             Message::ChangeSingleParameterSetValue(parameter, value) => {
                 self.set_value(parameter.parameter(), value, true);
 
                 self.sync_handle.set_parameter(parameter, value);
+
+                if let Some((readout_parameter, text)) = self.value_readout.as_mut() {
+                    if readout_parameter.index() == parameter.index() {
+                        *text = self.sync_handle.format_parameter_value(parameter, value);
+                    }
+                }
             }
             Message::ChangeSingleParameterImmediate(parameter, value) => {
                 self.set_value(parameter.parameter(), value, true);
@@ -586,33 +1371,27 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
             }
             Message::ChangeEnvelopeParametersEnd {
                 operator_index,
-                parameter_1,
-                parameter_2,
+                parameters,
             } => {
-                self.set_value(parameter_1.0.parameter(), parameter_1.1, true);
-
-                self.sync_handle
-                    .set_parameter_immediate(parameter_1.0, parameter_1.1);
+                for (p, v) in parameters {
+                    self.scale_linked_envelope_stages(operator_index, (p, v));
 
-                if let Some((p, v)) = parameter_2 {
                     self.set_value(p.parameter(), v, true);
 
                     self.sync_handle.set_parameter_immediate(p, v);
+
+                    self.automate_linked_envelope_stages(operator_index, p);
                 }
 
                 self.sync_envelopes(operator_index, true);
             }
             Message::ChangeEnvelopeParametersSetValue {
                 operator_index,
-                parameter_1,
-                parameter_2,
+                parameters,
             } => {
-                self.set_value(parameter_1.0.parameter(), parameter_1.1, true);
+                for (p, v) in parameters {
+                    self.scale_linked_envelope_stages(operator_index, (p, v));
 
-                self.sync_handle
-                    .set_parameter_audio_only(parameter_1.0, parameter_1.1);
-
-                if let Some((p, v)) = parameter_2 {
                     self.set_value(p.parameter(), v, true);
 
                     self.sync_handle.set_parameter_audio_only(p, v);
@@ -621,7 +1400,170 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                 self.sync_envelopes(operator_index, false);
             }
             Message::ChangePatch(index) => {
-                self.sync_handle.set_patch_index(index);
+                self.change_patch(index);
+            }
+            Message::OpenPatchSearch => {
+                let (index, patch_names) = self.sync_handle.get_patches();
+
+                self.modal_action = Some(ModalAction::PatchSearch(PatchSearch::new(
+                    patch_names,
+                    index,
+                )));
+
+                return PatchSearch::focus();
+            }
+            Message::PatchSearchQuery(query) => {
+                if let Some(ModalAction::PatchSearch(search)) = self.modal_action.as_mut() {
+                    let opt_previous_selection = search.selected_patch_index();
+
+                    search.set_query(query);
+
+                    if search.selected_patch_index() != opt_previous_selection {
+                        self.preview_patch_search_selection();
+                    }
+                }
+            }
+            Message::PatchSearchMoveSelection(offset) => {
+                if let Some(ModalAction::PatchSearch(search)) = self.modal_action.as_mut() {
+                    let opt_previous_selection = search.selected_patch_index();
+
+                    let command = search.move_selection(offset);
+
+                    if search.selected_patch_index() != opt_previous_selection {
+                        self.preview_patch_search_selection();
+                    }
+
+                    return command;
+                }
+            }
+            Message::PatchSearchLoad(index) => {
+                self.modal_action = None;
+
+                self.change_patch(index);
+            }
+            Message::OpenKeyTuning => {
+                self.modal_action = Some(ModalAction::KeyTuning(KeyTuningEditor::new(
+                    self.sync_handle.get_key_tuning(),
+                )));
+            }
+            Message::KeyTuningInput { key, text } => {
+                if let Some(ModalAction::KeyTuning(editor)) = self.modal_action.as_mut() {
+                    editor.set_text(key, text);
+                }
+            }
+            Message::KeyTuningSubmit(key) => {
+                if let Some(ModalAction::KeyTuning(editor)) = self.modal_action.as_mut() {
+                    if let Some(text) = editor.text(key) {
+                        self.sync_handle.set_key_detune_from_text(key, text);
+                    }
+
+                    // Show value actually set, or previous value if text
+                    // couldn't be parsed
+                    let cents = self.sync_handle.get_key_tuning()[key as usize];
+
+                    editor.set_cents(key, cents);
+                }
+            }
+            Message::ResetKeyTuning => {
+                self.sync_handle.reset_key_tuning();
+
+                if let Some(ModalAction::KeyTuning(editor)) = self.modal_action.as_mut() {
+                    *editor = KeyTuningEditor::new(self.sync_handle.get_key_tuning());
+                }
+            }
+            Message::TogglePatchPreview(on) => {
+                self.corner.patch_preview = on;
+                self.corner.patch_picker.patch_preview_button.set_on(on);
+
+                self.save_gui_state();
+            }
+            Message::ToggleLogging(on) => {
+                self.corner.logging.set_on(on);
+
+                self.save_settings();
+            }
+            Message::SetSimdBackendOverride(SimdBackendChoice(opt_override)) => {
+                self.simd_backend_override = opt_override;
+                self.sync_handle.set_simd_backend_override(opt_override);
+
+                self.save_settings();
+            }
+            Message::ToggleOperatorAudition { operator_index, on } => {
+                let opt_operator_index = on.then_some(operator_index);
+
+                self.sync_handle.set_audition_operator(opt_operator_index);
+
+                for (i, operator) in [
+                    &mut self.operator_1,
+                    &mut self.operator_2,
+                    &mut self.operator_3,
+                    &mut self.operator_4,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    operator
+                        .audition_button
+                        .set_on(opt_operator_index == Some(i));
+                }
+            }
+            Message::ToggleEnvelopeLink(on) => {
+                self.envelopes_linked = on;
+                self.corner.envelope_link.set_on(on);
+
+                self.save_gui_state();
+            }
+            Message::ApplyEnvelopePreset {
+                operator_index,
+                preset,
+            } => {
+                let parameters = preset.patch_values().map(|(p, v)| {
+                    let p: WrappedParameter = Parameter::Operator(operator_index, p).into();
+
+                    (p, v)
+                });
+
+                for (p, _) in parameters {
+                    self.sync_handle.begin_edit(p);
+                }
+                for (p, v) in parameters {
+                    self.set_value(p.parameter(), v, true);
+
+                    self.sync_handle.set_parameter(p, v);
+                }
+                for (p, _) in parameters {
+                    self.sync_handle.end_edit(p);
+                }
+
+                self.sync_envelopes(operator_index, true);
+            }
+            Message::ResetSection(section) => {
+                let init_values = Patch::init_parameter_values();
+
+                let parameters = section
+                    .parameters()
+                    .map(|p| {
+                        let p: WrappedParameter = p.into();
+
+                        (p, init_values[p.index() as usize])
+                    })
+                    .collect::<Vec<_>>();
+
+                for (p, _) in parameters.iter() {
+                    self.sync_handle.begin_edit(*p);
+                }
+                for (p, v) in parameters.iter() {
+                    self.set_value(p.parameter(), *v, true);
+
+                    self.sync_handle.set_parameter(*p, *v);
+                }
+                for (p, _) in parameters.iter() {
+                    self.sync_handle.end_edit(*p);
+                }
+
+                if let ParameterSection::Operator(operator_index) = section {
+                    self.sync_envelopes(operator_index, true);
+                }
             }
             Message::SwitchTheme => {
                 let style = if let Theme::Light = self.theme {
@@ -631,6 +1573,7 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                 };
 
                 self.theme = style;
+                self.theme_overridden = true;
                 self.corner.theme_changed();
                 self.lfo_1.theme_changed();
                 self.lfo_2.theme_changed();
@@ -640,167 +1583,77 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                 self.operator_2.theme_changed();
                 self.operator_3.theme_changed();
                 self.operator_4.theme_changed();
+                self.pitch_bend_strip.theme_changed();
+                self.mod_wheel_strip.theme_changed();
 
                 self.save_settings();
+                self.save_gui_state();
             }
             Message::ToggleAlternativeControls => {
-                for operator in [
-                    &mut self.operator_1,
-                    &mut self.operator_2,
-                    &mut self.operator_3,
-                    &mut self.operator_4,
-                ] {
-                    operator.alternative_controls = !operator.alternative_controls;
-                }
+                self.toggle_alternative_controls();
+                self.save_gui_state();
+            }
+            Message::ResetClipIndicator => {
+                self.sync_handle.reset_clip_indicator();
 
-                self.corner.alternative_controls = !self.corner.alternative_controls;
+                self.corner.clipping = false;
+                self.corner.clip_latched = false;
             }
-            Message::LoadBankOrPatch => {
-                const TITLE: &str = "Load OctaSine patch bank or patches";
+            Message::SendPitchBend(value) => {
+                self.sync_handle.send_pitch_bend(value);
 
-                return Command::single(Action::Future(Box::pin(async move {
-                    cfg_if!(
-                        if #[cfg(target_os = "macos")] {
-                            let mut builder = rfd::AsyncFileDialog::new()
-                                .set_title(TITLE)
-                                .add_filter("Patch", &["fxp"])
-                                .add_filter("Patch bank", &["fxb"]);
-
-                            if let Some(h) = CurrentWindowHandle::get() {
-                                builder = builder.set_parent(&h);
-                            }
+                self.pitch_bend_strip.set_value(value);
+            }
+            Message::SendModWheel(value) => {
+                self.sync_handle.send_mod_wheel(value);
 
-                            let opt_paths = builder
-                                .pick_files()
-                                .await
-                                .map(|handles|
-                                    handles.into_iter()
-                                        .map(|h| h.path().to_owned())
-                                        .collect::<Vec<PathBuf>>()
-                                );
-                        } else if #[cfg(target_os = "windows")] {
-                            let opt_paths = rfd::AsyncFileDialog::new()
-                                .set_title(TITLE)
-                                .add_filter("Patch", &["fxp"])
-                                .add_filter("Patch bank", &["fxb"])
-                                .pick_files()
-                                .await
-                                .map(|handles|
-                                    handles.into_iter()
-                                        .map(|h| h.path().to_owned())
-                                        .collect::<Vec<PathBuf>>()
-                                );
-                        } else {
-                            let opt_paths = tinyfiledialogs::open_file_dialog_multi(
-                                TITLE,
-                                "",
-                                Some((&["*.fxp", "*.fxb"], "Patch bank or patch files"))
-                            ).map(|strings|
-                                strings.into_iter()
-                                    .map(|s| s.into())
-                                    .collect::<Vec<PathBuf>>()
-                            );
-                        }
-                    );
+                self.mod_wheel_strip.set_value(value);
+            }
+            Message::LoadBankOrPatch => {
+                let opt_directory = self.import_directory.clone();
 
-                    if let Some(paths) = opt_paths {
-                        Message::LoadBankOrPatchesFromPaths(paths)
-                    } else {
-                        Message::NoOp
+                return Command::single(Action::Future(Box::pin(async move {
+                    match file_dialogs::pick_patch_or_bank_files(opt_directory).await {
+                        Some(paths) => Message::LoadBankOrPatchesFromPaths(paths),
+                        None => Message::NoOp,
                     }
                 })));
             }
             Message::SavePatch => {
-                const TITLE: &str = "Save OctaSine patch";
-
                 let (patch_filename, patch_bytes) = self.sync_handle.export_patch();
+                let opt_directory = self.export_directory.clone();
 
                 return Command::single(Action::Future(Box::pin(async move {
-                    cfg_if!(
-                        if #[cfg(target_os = "macos")] {
-                            let mut builder = rfd::AsyncFileDialog::new()
-                                .set_title(TITLE)
-                                .add_filter("Patch", &["fxp"])
-                                .set_file_name(&patch_filename);
-
-                            if let Some(h) = CurrentWindowHandle::get() {
-                                builder = builder.set_parent(&h);
-                            }
-
-                            let opt_path_buf = builder
-                                .save_file()
-                                .await
-                                .map(|handle| handle.path().to_owned());
-                        }
-                        else if #[cfg(target_os = "windows")] {
-                            let opt_path_buf = rfd::AsyncFileDialog::new()
-                                .set_title(TITLE)
-                                .add_filter("Patch", &["fxp"])
-                                .set_file_name(&patch_filename)
-                                .save_file()
-                                .await
-                                .map(|handle| handle.path().to_owned());
-                        } else {
-                            let opt_path_buf = tinyfiledialogs::save_file_dialog_with_filter(
-                                TITLE,
-                                &patch_filename,
-                                &["*.fxp"],
-                                "Patch"
-                            ).map(|s| s.into());
-                        }
-                    );
-
-                    if let Some(path_buf) = opt_path_buf {
-                        Message::SaveBankOrPatchToFile(path_buf, patch_bytes)
-                    } else {
-                        Message::NoOp
+                    let opt_path_buf = file_dialogs::pick_save_path(
+                        file_dialogs::SAVE_PATCH,
+                        patch_filename.to_string(),
+                        opt_directory,
+                    )
+                    .await;
+
+                    match opt_path_buf {
+                        Some(path_buf) => Message::SaveBankOrPatchToFile(path_buf, patch_bytes),
+                        None => Message::NoOp,
                     }
                 })));
             }
             Message::SaveBank => {
-                const TITLE: &str = "Save OctaSine bank";
                 const FILENAME: &str = "OctaSine bank.fxb";
 
                 let bank_bytes = self.sync_handle.export_bank();
+                let opt_directory = self.export_directory.clone();
 
                 return Command::single(Action::Future(Box::pin(async move {
-                    cfg_if!(
-                        if #[cfg(target_os = "macos")] {
-                            let mut builder = rfd::AsyncFileDialog::new()
-                                .set_title(TITLE)
-                                .add_filter("Patch bank", &["fxb"])
-                                .set_file_name(FILENAME);
-
-                            if let Some(h) = CurrentWindowHandle::get() {
-                                builder = builder.set_parent(&h);
-                            }
-
-                            let opt_path_buf = builder
-                                .save_file()
-                                .await
-                                .map(|handle| handle.path().to_owned());
-                        } else if #[cfg(target_os = "windows")] {
-                            let opt_path_buf = rfd::AsyncFileDialog::new()
-                                .set_title(TITLE)
-                                .add_filter("Patch bank", &["fxb"])
-                                .set_file_name(FILENAME)
-                                .save_file()
-                                .await
-                                .map(|handle| handle.path().to_owned());
-                        } else  {
-                            let opt_path_buf = tinyfiledialogs::save_file_dialog_with_filter(
-                                TITLE,
-                                FILENAME,
-                                &["*.fxb"],
-                                ""
-                            ).map(|s| s.into());
-                        }
-                    );
-
-                    if let Some(path_buf) = opt_path_buf {
-                        Message::SaveBankOrPatchToFile(path_buf, bank_bytes)
-                    } else {
-                        Message::NoOp
+                    let opt_path_buf = file_dialogs::pick_save_path(
+                        file_dialogs::SAVE_BANK,
+                        FILENAME.to_string(),
+                        opt_directory,
+                    )
+                    .await;
+
+                    match opt_path_buf {
+                        Some(path_buf) => Message::SaveBankOrPatchToFile(path_buf, bank_bytes),
+                        None => Message::NoOp,
                     }
                 })));
             }
@@ -813,6 +1666,29 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                     self.sync_handle.set_current_patch_name(&name);
                 }
             }
+            Message::MovePatch(offset) => {
+                let (index, patch_names) = self.sync_handle.get_patches();
+
+                if let Some(target_index) = index
+                    .checked_add_signed(offset)
+                    .filter(|i| *i < patch_names.len())
+                {
+                    self.sync_handle.swap_patches(index, target_index);
+                    self.sync_handle.set_patch_index(target_index);
+                }
+            }
+            Message::CopyPatch => {
+                let (index, _) = self.sync_handle.get_patches();
+
+                self.copied_patch_index = Some(index);
+            }
+            Message::PastePatch => {
+                if let Some(from_index) = self.copied_patch_index {
+                    let (index, _) = self.sync_handle.get_patches();
+
+                    self.sync_handle.copy_patch(from_index, index);
+                }
+            }
             Message::ClearPatch => {
                 self.modal_action = Some(ModalAction::ClearPatch);
             }
@@ -820,12 +1696,39 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                 self.modal_action = Some(ModalAction::ClearBank);
             }
             Message::SaveBankOrPatchToFile(path_buf, bytes) => {
+                let opt_directory = path_buf.parent().map(Path::to_path_buf);
+
                 if let Err(err) = save_data_to_file(path_buf, bytes) {
                     ::log::error!("Error saving patch/patch bank to file: {:#}", err)
+                } else if opt_directory.is_some() && opt_directory != self.export_directory {
+                    self.export_directory = opt_directory;
+                    self.save_settings();
                 }
             }
             Message::LoadBankOrPatchesFromPaths(paths) => {
-                self.sync_handle.import_bank_or_patches_from_paths(&paths);
+                let opt_directory = paths
+                    .first()
+                    .and_then(|path| path.parent())
+                    .map(Path::to_path_buf);
+
+                if opt_directory.is_some() && opt_directory != self.import_directory {
+                    self.import_directory = opt_directory;
+                    self.save_settings();
+                }
+
+                let read_files = self.import_worker.read_files(paths);
+
+                return Command::single(Action::Future(Box::pin(async move {
+                    match read_files.await {
+                        Some(files) => Message::ImportFiles(Arc::new(files)),
+                        None => Message::NoOp,
+                    }
+                })));
+            }
+            Message::ImportFiles(files) => {
+                if !files.is_empty() {
+                    self.sync_handle.import_files(&files);
+                }
             }
             Message::ChangeParameterByTextInput {
                 parameter,
@@ -850,6 +1753,10 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                 }
             }
             Message::ModalOpen(action) => {
+                if let ModalAction::Voices = action {
+                    self.voice_snapshots = self.sync_handle.get_voice_snapshots();
+                }
+
                 self.modal_action = Some(action);
             }
             Message::ModalClose => {
@@ -875,7 +1782,13 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                         self.set_value(parameter.parameter(), value_patch, true);
                     }
                 }
-                None => (),
+                Some(
+                    ModalAction::Voices
+                    | ModalAction::About
+                    | ModalAction::PatchSearch(_)
+                    | ModalAction::KeyTuning(_),
+                )
+                | None => (),
             },
             Message::ModalSetParameterByChoicesUpdate(new_choice) => {
                 if let Some(ModalAction::SetParameterByChoices { choice, .. }) =
@@ -892,6 +1805,7 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
     fn view(&self) -> Element<'_, Self::Message, Self::Theme> {
         let content = Container::new(
             Column::new()
+                .height(Length::Fill)
                 .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
                 .push(self.operator_4.view(&self.theme))
                 .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
@@ -918,11 +1832,27 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                         )
                         .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
                         .push(self.corner.view(&self.theme)),
-                ),
+                )
+                .push(Space::with_height(Length::Fill))
+                .push(value_panel::value_panel(
+                    &self.theme,
+                    self.value_panel.as_ref(),
+                    expression_strip::expression_strips(
+                        &self.theme,
+                        &self.pitch_bend_strip,
+                        &self.mod_wheel_strip,
+                    ),
+                )),
         )
         .height(Length::Fill)
         .style(ContainerStyle::L0);
 
+        let content = ValueReadout::new(
+            &self.theme,
+            content,
+            self.value_readout.as_ref().map(|(_, text)| text.clone()),
+        );
+
         Modal::new(self.modal_action.is_some(), content, || {
             let modal_action = if let Some(modal_action) = self.modal_action.as_ref() {
                 modal_action
@@ -933,6 +1863,10 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
             let heading = match modal_action {
                 ModalAction::ClearBank => "CLEAR ENTIRE PATCH BANK?".into(),
                 ModalAction::ClearPatch => "CLEAR CURRENT PATCH?".into(),
+                ModalAction::Voices => "ACTIVE VOICES".into(),
+                ModalAction::About => "ABOUT OCTASINE".into(),
+                ModalAction::PatchSearch(_) => "FIND PATCH".into(),
+                ModalAction::KeyTuning(_) => "KEY TUNING".into(),
                 ModalAction::SetParameterByChoices { parameter, .. } => {
                     format!("SET {}", parameter.parameter().name().to_uppercase())
                 }
@@ -959,6 +1893,114 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                         .padding(LINE_HEIGHT as f32)
                         .into()
                 }
+                ModalAction::Voices => {
+                    let body = Column::new()
+                        .spacing(LINE_HEIGHT)
+                        .push(voice_list::voice_list(&self.theme, &self.voice_snapshots))
+                        .push(
+                            Button::new(
+                                Text::new("CLOSE").horizontal_alignment(Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::ModalClose),
+                        );
+
+                    Card::new(Text::new(heading), body)
+                        .max_width(LINE_HEIGHT as f32 * 24.0)
+                        .padding(LINE_HEIGHT as f32)
+                        .into()
+                }
+                ModalAction::About => {
+                    let instance = self.sync_handle.get_instance_info();
+
+                    let info_text = format!(
+                        "{}\n\nSIMD backend: {}\nInstance: {:08x}\nSeed: {:016x}",
+                        corner::get_info_text(),
+                        self.sync_handle.get_simd_backend(),
+                        instance.id,
+                        instance.seed,
+                    );
+
+                    let simd_backend_picker = Row::new()
+                        .spacing(LINE_HEIGHT / 2)
+                        .align_items(Alignment::Center)
+                        .push(Text::new("FORCE SIMD BACKEND").width(Length::Fill))
+                        .push(PickList::new(
+                            SimdBackendChoice::supported(),
+                            Some(SimdBackendChoice(self.simd_backend_override)),
+                            Message::SetSimdBackendOverride,
+                        ));
+
+                    let logging_toggle = Row::new()
+                        .spacing(LINE_HEIGHT / 2)
+                        .align_items(Alignment::Center)
+                        .push(Text::new("WRITE LOG FILE (ON NEXT LOAD)").width(Length::Fill))
+                        .push(self.corner.logging.view());
+
+                    let body = Column::new()
+                        .spacing(LINE_HEIGHT)
+                        .push(Text::new(info_text).font(self.theme.font_regular()))
+                        .push(simd_backend_picker)
+                        .push(logging_toggle)
+                        .push(
+                            Button::new(
+                                Text::new("CLOSE").horizontal_alignment(Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::ModalClose),
+                        );
+
+                    Card::new(Text::new(heading), body)
+                        .max_width(LINE_HEIGHT as f32 * 24.0)
+                        .padding(LINE_HEIGHT as f32)
+                        .into()
+                }
+                ModalAction::PatchSearch(search) => {
+                    let body = Column::new()
+                        .spacing(LINE_HEIGHT)
+                        .push(search.view(&self.theme))
+                        .push(
+                            Button::new(
+                                Text::new("CLOSE").horizontal_alignment(Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::ModalClose),
+                        );
+
+                    Card::new(Text::new(heading), body)
+                        .max_width(LINE_HEIGHT as f32 * 24.0)
+                        .padding(LINE_HEIGHT as f32)
+                        .into()
+                }
+                ModalAction::KeyTuning(editor) => {
+                    let buttons = Row::new()
+                        .spacing(LINE_HEIGHT / 2)
+                        .width(Length::Fill)
+                        .push(
+                            Button::new(
+                                Text::new("RESET ALL").horizontal_alignment(Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::ResetKeyTuning),
+                        )
+                        .push(
+                            Button::new(
+                                Text::new("CLOSE").horizontal_alignment(Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::ModalClose),
+                        );
+
+                    let body = Column::new()
+                        .spacing(LINE_HEIGHT)
+                        .push(editor.view(&self.theme))
+                        .push(buttons);
+
+                    Card::new(Text::new(heading), body)
+                        .max_width(LINE_HEIGHT as f32 * 24.0)
+                        .padding(LINE_HEIGHT as f32)
+                        .into()
+                }
                 ModalAction::SetParameterByChoices {
                     options, choice, ..
                 } => {
@@ -1026,6 +2068,8 @@ pub fn get_iced_baseview_settings<H: GuiSyncHandle>(
     sync_handle: H,
     plugin_name: String,
 ) -> iced_baseview::Settings<H> {
+    let always_redraw = sync_handle.get_gui_settings().redraw_when_idle;
+
     iced_baseview::Settings {
         window: iced_baseview::baseview::WindowOpenOptions {
             size: iced_baseview::baseview::Size::new(GUI_WIDTH as f64, GUI_HEIGHT as f64),
@@ -1048,49 +2092,8 @@ pub fn get_iced_baseview_settings<H: GuiSyncHandle>(
         },
         iced_baseview: iced_baseview::settings::IcedBaseviewSettings {
             ignore_non_modifier_keys: true,
-            always_redraw: true,
+            always_redraw,
         },
         flags: sync_handle,
     }
 }
-
-#[cfg(target_os = "macos")]
-struct CurrentWindowHandle(rwh05::RawWindowHandle);
-
-#[cfg(target_os = "macos")]
-impl CurrentWindowHandle {
-    fn get() -> Option<Self> {
-        use objc::{class, msg_send, runtime::Object, sel, sel_impl};
-
-        unsafe {
-            let ns_app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
-            if ns_app.is_null() {
-                return None;
-            }
-
-            let ns_window: *mut Object = msg_send![ns_app, keyWindow];
-            if ns_window.is_null() {
-                return None;
-            }
-
-            let ns_view: *mut Object = msg_send![ns_window, contentView];
-            if ns_view.is_null() {
-                return None;
-            }
-
-            let mut handle = rwh05::AppKitWindowHandle::empty();
-
-            handle.ns_window = ns_window as *mut core::ffi::c_void;
-            handle.ns_view = ns_view as *mut core::ffi::c_void;
-
-            Some(Self(rwh05::RawWindowHandle::AppKit(handle)))
-        }
-    }
-}
-
-#[cfg(target_os = "macos")]
-unsafe impl rwh05::HasRawWindowHandle for CurrentWindowHandle {
-    fn raw_window_handle(&self) -> rwh05::RawWindowHandle {
-        self.0
-    }
-}
@@ -0,0 +1,249 @@
+use iced_baseview::alignment::{Horizontal, Vertical};
+use iced_baseview::widget::canvas::{
+    Cache, Canvas, Cursor, Frame, Geometry, Path, Program, Stroke, Text,
+};
+use iced_baseview::{Element, Length, Point, Rectangle};
+
+use crate::gui::style::Theme;
+use crate::gui::{Message, FONT_SIZE, LINE_HEIGHT};
+
+use super::{ModulationMatrixParameters, StyleSheet};
+
+pub const WIDTH: u16 = LINE_HEIGHT * 3;
+pub const HEIGHT: u16 = LINE_HEIGHT * 4;
+
+const NUM_OPERATORS: usize = 4;
+const COLUMN_WIDTH: f32 = WIDTH as f32 / NUM_OPERATORS as f32;
+const ROW_HEIGHT: f32 = 10.0;
+/// Distance from node center to where lines start and end
+const NODE_RADIUS: f32 = 4.5;
+const BOTTOM_NODE_Y: f32 = HEIGHT as f32 - 10.0;
+const OUTPUT_LINE_Y: f32 = HEIGHT as f32 - 1.5;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    center: Point,
+    /// Operator is a carrier or modulates an operator that is audible
+    active: bool,
+    carrier: bool,
+}
+
+/// Read-only classic FM algorithm graph of current operator routing, with
+/// carriers at the bottom and modulators stacked above their targets
+pub struct AlgorithmDiagram {
+    cache: Cache,
+    nodes: [Node; NUM_OPERATORS],
+    /// (from, to) operator indices
+    lines: Vec<(usize, usize)>,
+}
+
+impl AlgorithmDiagram {
+    pub fn new(parameters: &ModulationMatrixParameters) -> Self {
+        let mut diagram = Self {
+            cache: Cache::default(),
+            nodes: [Node {
+                center: Point::ORIGIN,
+                active: false,
+                carrier: false,
+            }; NUM_OPERATORS],
+            lines: Vec::new(),
+        };
+
+        diagram.update(parameters);
+
+        diagram
+    }
+
+    pub fn theme_changed(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn update(&mut self, parameters: &ModulationMatrixParameters) {
+        let mut modulates = [[false; NUM_OPERATORS]; NUM_OPERATORS];
+
+        for (from, targets) in [
+            (1, parameters.operator_2_targets),
+            (2, parameters.operator_3_targets),
+            (3, parameters.operator_4_targets),
+        ] {
+            for to in targets.active_indices() {
+                modulates[from][to] = true;
+            }
+        }
+
+        let carrier = [
+            parameters.operator_1_mix > 0.0,
+            parameters.operator_2_mix > 0.0,
+            parameters.operator_3_mix > 0.0,
+            parameters.operator_4_mix > 0.0,
+        ];
+
+        // Operators can only modulate operators with lower indices, so
+        // levels and activity can be determined in a single pass
+        let mut levels = [0usize; NUM_OPERATORS];
+        let mut active = carrier;
+
+        for from in 0..NUM_OPERATORS {
+            for to in 0..from {
+                if modulates[from][to] {
+                    levels[from] = levels[from].max(levels[to] + 1);
+                    active[from] |= active[to];
+                }
+            }
+        }
+
+        // Place modulators above their lowest-index target when possible
+        let mut columns = [0usize; NUM_OPERATORS];
+        let mut occupied = [[false; NUM_OPERATORS]; NUM_OPERATORS];
+
+        let mut order: Vec<usize> = (0..NUM_OPERATORS).collect();
+
+        order.sort_by_key(|index| (levels[*index], *index));
+
+        for index in order {
+            let level = levels[index];
+            let preferred = (0..index)
+                .find(|to| modulates[index][*to])
+                .map_or(0, |to| columns[to]);
+
+            let column = (preferred..NUM_OPERATORS)
+                .chain((0..preferred).rev())
+                .find(|column| !occupied[level][*column])
+                .unwrap_or(preferred);
+
+            occupied[level][column] = true;
+            columns[index] = column;
+        }
+
+        let num_columns = columns.iter().max().map_or(1, |max| max + 1);
+        let offset_x = (f32::from(WIDTH) - num_columns as f32 * COLUMN_WIDTH) / 2.0;
+
+        for index in 0..NUM_OPERATORS {
+            self.nodes[index] = Node {
+                center: Point::new(
+                    offset_x + (columns[index] as f32 + 0.5) * COLUMN_WIDTH,
+                    BOTTOM_NODE_Y - levels[index] as f32 * ROW_HEIGHT,
+                ),
+                active: active[index],
+                carrier: carrier[index],
+            };
+        }
+
+        self.lines.clear();
+
+        for from in 0..NUM_OPERATORS {
+            for to in 0..from {
+                if modulates[from][to] {
+                    self.lines.push((from, to));
+                }
+            }
+        }
+
+        self.cache.clear();
+    }
+
+    pub fn view(&self) -> Element<Message, Theme> {
+        Canvas::new(self)
+            .width(Length::Fixed(WIDTH.into()))
+            .height(Length::Fixed(HEIGHT.into()))
+            .into()
+    }
+
+    fn draw_background(&self, frame: &mut Frame, theme: &Theme) {
+        let appearance = theme.appearance();
+
+        let background = Path::rectangle(Point::ORIGIN, frame.size());
+
+        frame.fill(&background, appearance.background_color);
+    }
+
+    fn draw_lines(&self, frame: &mut Frame, theme: &Theme) {
+        let appearance = theme.appearance();
+
+        let mod_out_stroke = Stroke::default()
+            .with_width(1.0)
+            .with_color(appearance.mod_out_line_color);
+        let mix_out_stroke = Stroke::default()
+            .with_width(1.0)
+            .with_color(appearance.mix_out_line_color);
+
+        for (from, to) in self.lines.iter().copied() {
+            let from = self.nodes[from].center;
+            let to = self.nodes[to].center;
+
+            let path = Path::line(
+                Point::new(from.x, from.y + NODE_RADIUS),
+                Point::new(to.x, to.y - NODE_RADIUS),
+            );
+
+            frame.stroke(&path, mod_out_stroke.clone());
+        }
+
+        let mut output_left = None;
+        let mut output_right = None;
+
+        for node in self.nodes.iter().filter(|node| node.carrier) {
+            let path = Path::line(
+                Point::new(node.center.x, node.center.y + NODE_RADIUS),
+                Point::new(node.center.x, OUTPUT_LINE_Y),
+            );
+
+            frame.stroke(&path, mix_out_stroke.clone());
+
+            output_left = Some(output_left.map_or(node.center.x, |x: f32| x.min(node.center.x)));
+            output_right = Some(output_right.map_or(node.center.x, |x: f32| x.max(node.center.x)));
+        }
+
+        if let (Some(left), Some(right)) = (output_left, output_right) {
+            let path = Path::line(
+                Point::new(left - NODE_RADIUS, OUTPUT_LINE_Y),
+                Point::new(right + NODE_RADIUS, OUTPUT_LINE_Y),
+            );
+
+            frame.stroke(&path, mix_out_stroke);
+        }
+    }
+
+    fn draw_nodes(&self, frame: &mut Frame, theme: &Theme) {
+        let appearance = theme.appearance();
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let color = if node.active {
+                appearance.text_color
+            } else {
+                appearance.box_border_color
+            };
+
+            frame.fill_text(Text {
+                content: format!("{}", index + 1),
+                position: node.center,
+                font: theme.font_bold(),
+                size: f32::from(FONT_SIZE - 3),
+                color,
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Center,
+                ..Default::default()
+            });
+        }
+    }
+}
+
+impl Program<Message, Theme> for AlgorithmDiagram {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let geometry = self.cache.draw(bounds.size(), |frame| {
+            self.draw_background(frame, theme);
+            self.draw_lines(frame, theme);
+            self.draw_nodes(frame, theme);
+        });
+
+        vec![geometry]
+    }
+}
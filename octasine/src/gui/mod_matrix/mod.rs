@@ -1,3 +1,4 @@
+mod algorithm_diagram;
 mod common;
 mod mix_line;
 mod mod_box;
@@ -16,6 +17,7 @@ use crate::parameters::{
 };
 use crate::sync::GuiSyncHandle;
 
+use self::algorithm_diagram::AlgorithmDiagram;
 use self::mix_line::MixOutLine;
 use self::mod_box::{
     ModulationBox, ModulationBoxCanvasState, ModulationBoxCanvasUpdate,
@@ -371,22 +373,26 @@ pub struct ModulationMatrix {
     cache: Cache,
     parameters: ModulationMatrixParameters,
     components: ModulationMatrixComponents,
+    algorithm_diagram: AlgorithmDiagram,
 }
 
 impl ModulationMatrix {
     pub fn new<H: GuiSyncHandle>(sync_handle: &H) -> Self {
         let parameters = ModulationMatrixParameters::new(sync_handle);
         let components = ModulationMatrixComponents::new(&parameters, SIZE);
+        let algorithm_diagram = AlgorithmDiagram::new(&parameters);
 
         Self {
             cache: Cache::default(),
             parameters,
             components,
+            algorithm_diagram,
         }
     }
 
     pub fn theme_changed(&mut self) {
         self.cache.clear();
+        self.algorithm_diagram.theme_changed();
     }
 
     pub fn set_operator_2_target(&mut self, value: f32) {
@@ -454,6 +460,7 @@ impl ModulationMatrix {
 
     fn update_components(&mut self) {
         self.components.update(&self.parameters);
+        self.algorithm_diagram.update(&self.parameters);
 
         self.cache.clear();
     }
@@ -465,6 +472,11 @@ impl ModulationMatrix {
             .into()
     }
 
+    /// Read-only algorithm graph of the same routing
+    pub fn algorithm_diagram_view(&self) -> Element<Message, Theme> {
+        self.algorithm_diagram.view()
+    }
+
     fn draw_background(&self, frame: &mut Frame, theme: &Theme) {
         let mut size = frame.size();
         let appearance = theme.appearance();
@@ -0,0 +1,139 @@
+use std::time::Instant;
+
+use iced_baseview::widget::canvas::{Cache, Canvas, Cursor, Geometry, Path, Program, Stroke};
+use iced_baseview::{Color, Element, Length, Point, Rectangle, Size};
+
+use super::{style::Theme, Message, LINE_HEIGHT};
+
+const WIDTH: u16 = LINE_HEIGHT * 4;
+const HEIGHT: u16 = LINE_HEIGHT / 2;
+
+/// Modulation output shown as empty bar
+const MIN_LEVEL: f32 = 0.01;
+/// Modulation output shown as full bar
+const MAX_LEVEL: f32 = 100.0;
+/// Display falloff speed in decades of modulation output per second
+const FALLOFF_DECADES_PER_SECOND: f32 = 2.0;
+
+#[derive(Debug, Clone)]
+pub struct Appearance {
+    pub background_color: Color,
+    pub border_color: Color,
+    pub bar_color: Color,
+}
+
+pub trait StyleSheet {
+    fn appearance(&self) -> Appearance;
+}
+
+/// Bar showing peak modulation output of operator, post envelope, LFO and
+/// velocity, on a logarithmic scale
+pub struct ModOutMeter {
+    /// Displayed modulation output, falling off gradually after peaks
+    level: f32,
+    last_update: Option<Instant>,
+    cache: Cache,
+    bounds_path: Path,
+}
+
+impl Default for ModOutMeter {
+    fn default() -> Self {
+        let bounds_path = Path::rectangle(
+            Point::new(0.5, 0.5),
+            Size::new((WIDTH - 1) as f32, (HEIGHT - 1) as f32),
+        );
+
+        Self {
+            level: 0.0,
+            last_update: None,
+            cache: Cache::new(),
+            bounds_path,
+        }
+    }
+}
+
+impl ModOutMeter {
+    /// Update with peak modulation output since last call
+    pub fn update_peak(&mut self, peak: f32) {
+        let now = Instant::now();
+
+        let falloff_factor = match self.last_update {
+            Some(last_update) => {
+                let elapsed = (now - last_update).as_secs_f32();
+
+                10.0f32.powf(-FALLOFF_DECADES_PER_SECOND * elapsed)
+            }
+            None => 0.0,
+        };
+
+        self.last_update = Some(now);
+
+        let mut level = peak.max(self.level * falloff_factor);
+
+        if level < MIN_LEVEL {
+            level = 0.0;
+        }
+
+        if level != self.level {
+            self.level = level;
+
+            self.cache.clear();
+        }
+    }
+
+    pub fn theme_changed(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn view(&self) -> Element<Message, Theme> {
+        Canvas::new(self)
+            .width(Length::Fixed(WIDTH.into()))
+            .height(Length::Fixed(HEIGHT.into()))
+            .into()
+    }
+
+    /// Bar length as fraction of meter width
+    fn level_to_fraction(level: f32) -> f32 {
+        if level < MIN_LEVEL {
+            0.0
+        } else {
+            ((level / MIN_LEVEL).log10() / (MAX_LEVEL / MIN_LEVEL).log10()).min(1.0)
+        }
+    }
+}
+
+impl Program<Message, Theme> for ModOutMeter {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let geometry = self.cache.draw(bounds.size(), |frame| {
+            let appearance = theme.appearance();
+
+            frame.fill(&self.bounds_path, appearance.background_color);
+
+            let width = (Self::level_to_fraction(self.level) * (frame.size().width - 2.0)).round();
+
+            if width > 0.0 {
+                let bar = Path::rectangle(
+                    Point::new(1.0, 1.0),
+                    Size::new(width, frame.size().height - 2.0),
+                );
+
+                frame.fill(&bar, appearance.bar_color);
+            }
+
+            frame.stroke(
+                &self.bounds_path,
+                Stroke::default().with_color(appearance.border_color),
+            );
+        });
+
+        vec![geometry]
+    }
+}
@@ -6,8 +6,10 @@ use iced_baseview::{
 
 use crate::parameters::operator_mod_target::ModTargetStorage;
 use crate::parameters::{
-    Operator2ModulationTargetValue, Operator3ModulationTargetValue, Operator4ModulationTargetValue,
-    OperatorParameter, Parameter, ParameterValue, WrappedParameter,
+    Operator1BackwardModulationTargetValue, Operator2BackwardModulationTargetValue,
+    Operator2ModulationTargetValue, Operator3BackwardModulationTargetValue,
+    Operator3ModulationTargetValue, Operator4ModulationTargetValue, OperatorParameter, Parameter,
+    ParameterValue, WrappedParameter,
 };
 use crate::sync::GuiSyncHandle;
 
@@ -19,26 +21,76 @@ pub fn operator_2_target<H: GuiSyncHandle>(
     sync_handle: &H,
     operator_index: usize,
 ) -> ModTargetPicker<Operator2ModulationTargetValue> {
-    ModTargetPicker::new(sync_handle, operator_index, "TARGET", vec![0])
+    ModTargetPicker::new(
+        sync_handle,
+        operator_index,
+        OperatorParameter::ModTargets,
+        vec![0],
+    )
 }
 
 pub fn operator_3_target<H: GuiSyncHandle>(
     sync_handle: &H,
     operator_index: usize,
 ) -> ModTargetPicker<Operator3ModulationTargetValue> {
-    ModTargetPicker::new(sync_handle, operator_index, "TARGET", vec![1, 0])
+    ModTargetPicker::new(
+        sync_handle,
+        operator_index,
+        OperatorParameter::ModTargets,
+        vec![1, 0],
+    )
 }
 
 pub fn operator_4_target<H: GuiSyncHandle>(
     sync_handle: &H,
     operator_index: usize,
 ) -> ModTargetPicker<Operator4ModulationTargetValue> {
-    ModTargetPicker::new(sync_handle, operator_index, "TARGET", vec![2, 1, 0])
+    ModTargetPicker::new(
+        sync_handle,
+        operator_index,
+        OperatorParameter::ModTargets,
+        vec![2, 1, 0],
+    )
+}
+
+pub fn operator_1_backward_target<H: GuiSyncHandle>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> ModTargetPicker<Operator1BackwardModulationTargetValue> {
+    ModTargetPicker::new(
+        sync_handle,
+        operator_index,
+        OperatorParameter::BackwardModTargets,
+        vec![3, 2, 1],
+    )
+}
+
+pub fn operator_2_backward_target<H: GuiSyncHandle>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> ModTargetPicker<Operator2BackwardModulationTargetValue> {
+    ModTargetPicker::new(
+        sync_handle,
+        operator_index,
+        OperatorParameter::BackwardModTargets,
+        vec![3, 2],
+    )
+}
+
+pub fn operator_3_backward_target<H: GuiSyncHandle>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> ModTargetPicker<Operator3BackwardModulationTargetValue> {
+    ModTargetPicker::new(
+        sync_handle,
+        operator_index,
+        OperatorParameter::BackwardModTargets,
+        vec![3],
+    )
 }
 
 #[derive(Debug, Clone)]
 pub struct ModTargetPicker<P> {
-    title: String,
     parameter: WrappedParameter,
     choices: Vec<usize>,
     parameter_value: P,
@@ -51,15 +103,13 @@ where
     fn new<H: GuiSyncHandle>(
         sync_handle: &H,
         operator_index: usize,
-        title: &str,
+        operator_parameter: OperatorParameter,
         choices: Vec<usize>,
     ) -> Self {
-        let parameter =
-            Parameter::Operator(operator_index as u8, OperatorParameter::ModTargets).into();
+        let parameter = Parameter::Operator(operator_index as u8, operator_parameter).into();
         let sync_value = sync_handle.get_parameter(parameter);
 
         Self {
-            title: title.into(),
             parameter,
             choices,
             parameter_value: P::new_from_patch(sync_value),
@@ -70,50 +120,64 @@ where
         self.parameter_value = P::new_from_patch(value);
     }
 
-    pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
-        let title = Text::new(self.title.clone())
-            .horizontal_alignment(Horizontal::Center)
-            .font(theme.font_bold())
-            .height(Length::Fixed(LINE_HEIGHT.into()));
-        let title = tooltip(
-            theme,
-            "Target operators for modulation",
-            Position::Top,
-            title,
-        );
-
-        let mut checkboxes = Column::new().spacing(4);
-
-        for index in self.choices.iter().copied() {
-            let active = self.parameter_value.get().index_active(index);
-            let label = format!("{}", index + 1);
-            let v = self.parameter_value.get();
-            let parameter = self.parameter;
-
-            let checkbox = Checkbox::new(label, active, move |active| {
-                let mut v = v;
-
-                v.set_index(index, active);
-
-                let sync = P::new_from_audio(v).to_patch();
-
-                Message::ChangeSingleParameterImmediate(parameter, sync)
+    /// Checkboxes for target operators in order of choices
+    pub fn checkboxes(&self, theme: &Theme) -> Vec<Element<Message, Theme>> {
+        self.choices
+            .iter()
+            .copied()
+            .map(|index| {
+                let active = self.parameter_value.get().index_active(index);
+                let label = format!("{}", index + 1);
+                let v = self.parameter_value.get();
+                let parameter = self.parameter;
+
+                Checkbox::new(label, active, move |active| {
+                    let mut v = v;
+
+                    v.set_index(index, active);
+
+                    let sync = P::new_from_audio(v).to_patch();
+
+                    Message::ChangeSingleParameterImmediate(parameter, sync)
+                })
+                .font(theme.font_regular())
+                .size(FONT_SIZE)
+                .text_size(FONT_SIZE)
+                .spacing(4)
+                .into()
             })
-            .font(theme.font_regular())
-            .size(FONT_SIZE)
-            .text_size(FONT_SIZE)
-            .spacing(4);
-
-            checkboxes = checkboxes.push(checkbox);
-        }
-
-        Column::new()
-            .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
-            .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
-            .align_items(Alignment::Center)
-            .push(title)
-            .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
-            .push(checkboxes)
-            .into()
+            .collect()
     }
 }
+
+/// Column with a title and the checkboxes of one or more pickers
+pub fn view<'a>(
+    theme: &Theme,
+    checkboxes: impl IntoIterator<Item = Element<'a, Message, Theme>>,
+) -> Element<'a, Message, Theme> {
+    let title = Text::new("TARGET")
+        .horizontal_alignment(Horizontal::Center)
+        .font(theme.font_bold())
+        .height(Length::Fixed(LINE_HEIGHT.into()));
+    let title = tooltip(
+        theme,
+        "Target operators for modulation. Modulation of higher-numbered operators is delayed by one sample",
+        Position::Top,
+        title,
+    );
+
+    let checkboxes = checkboxes
+        .into_iter()
+        .fold(Column::new().spacing(4), |column, checkbox| {
+            column.push(checkbox)
+        });
+
+    Column::new()
+        .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+        .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
+        .align_items(Alignment::Center)
+        .push(title)
+        .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
+        .push(checkboxes)
+        .into()
+}
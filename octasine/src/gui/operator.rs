@@ -1,22 +1,35 @@
+use compact_str::CompactString;
 use iced_baseview::widget::tooltip::Position;
 use iced_baseview::{
     alignment::Horizontal, widget::Column, widget::Container, widget::Row, widget::Space,
     widget::Text, Alignment, Element, Length,
 };
 
-use crate::parameters::velocity_sensitivity::VelocitySensitivityValue;
+use crate::parameters::mod_out_scaling::{ModOutScaling, ModOutScalingValue};
+use crate::parameters::velocity_sensitivity::{
+    MixOutVelocitySensitivityValue, VelocitySensitivityValue,
+};
 use crate::parameters::{
-    Operator2ModulationTargetValue, Operator3ModulationTargetValue, Operator4ModulationTargetValue,
-    OperatorFeedbackValue, OperatorFrequencyFineValue, OperatorFrequencyFreeValue,
-    OperatorFrequencyRatioValue, OperatorMixOutValue, OperatorModOutValue, OperatorPanningValue,
-    OperatorParameter, OperatorVolumeValue, OperatorWaveTypeValue, Parameter,
+    MasterParameter, Operator1BackwardModulationTargetValue,
+    Operator2BackwardModulationTargetValue, Operator2ModulationTargetValue,
+    Operator3BackwardModulationTargetValue, Operator3ModulationTargetValue,
+    Operator4ModulationTargetValue, OperatorFeedbackToneValue, OperatorFeedbackValue,
+    OperatorFrequencyFineValue, OperatorFrequencyFreeValue, OperatorFrequencyRatioValue,
+    OperatorMixOutValue, OperatorModOutValue, OperatorPanningValue, OperatorParameter,
+    OperatorVolumeValue, OperatorWaveTypeValue, Parameter, ParameterSection, ParameterValue,
 };
 use crate::sync::GuiSyncHandle;
 
-use super::boolean_button::{operator_mute_button, BooleanButton};
-use super::common::{container_l1, container_l2, container_l3, space_l2, space_l3, tooltip};
+use super::boolean_button::{
+    operator_audition_button, operator_mute_button, operator_panning_lfo_mode_button,
+    operator_panning_mode_button, BooleanButton,
+};
+use super::common::{
+    button_with_tooltip, container_l1, container_l2, container_l3, space_l2, space_l3, tooltip,
+};
 use super::envelope::Envelope;
 use super::knob::{self, OctaSineKnob};
+use super::mod_out_meter::ModOutMeter;
 use super::mod_target_picker;
 use super::style::Theme;
 use super::wave_display::WaveDisplay;
@@ -29,34 +42,41 @@ pub enum ModTargetPicker {
     Operator2(mod_target_picker::ModTargetPicker<Operator2ModulationTargetValue>),
 }
 
+pub enum BackwardModTargetPicker {
+    Operator3(mod_target_picker::ModTargetPicker<Operator3BackwardModulationTargetValue>),
+    Operator2(mod_target_picker::ModTargetPicker<Operator2BackwardModulationTargetValue>),
+    Operator1(mod_target_picker::ModTargetPicker<Operator1BackwardModulationTargetValue>),
+}
+
 pub struct OperatorWidgets {
     index: usize,
     pub alternative_controls: bool,
     pub volume: OctaSineKnob<OperatorVolumeValue>,
     pub mute_button: BooleanButton,
+    pub audition_button: BooleanButton,
     pub mix: OctaSineKnob<OperatorMixOutValue>,
     pub panning: OctaSineKnob<OperatorPanningValue>,
+    pub panning_mode_button: BooleanButton,
+    pub panning_lfo_mode_button: BooleanButton,
     pub wave_type: WavePicker<OperatorWaveTypeValue>,
-    pub mod_index: Option<OctaSineKnob<OperatorModOutValue>>,
+    pub mod_index: OctaSineKnob<OperatorModOutValue>,
     pub mod_target: Option<ModTargetPicker>,
+    pub backward_mod_target: Option<BackwardModTargetPicker>,
     pub feedback: OctaSineKnob<OperatorFeedbackValue>,
     pub frequency_ratio: OctaSineKnob<OperatorFrequencyRatioValue>,
     pub frequency_free: OctaSineKnob<OperatorFrequencyFreeValue>,
     pub frequency_fine: OctaSineKnob<OperatorFrequencyFineValue>,
+    pub mix_out_velocity_sensitivity: OctaSineKnob<MixOutVelocitySensitivityValue>,
     pub mod_out_velocity_sensitivity: OctaSineKnob<VelocitySensitivityValue>,
     pub feedback_velocity_sensitivity: OctaSineKnob<VelocitySensitivityValue>,
+    pub feedback_tone: OctaSineKnob<OperatorFeedbackToneValue>,
     pub envelope: Envelope,
     pub wave_display: WaveDisplay,
+    pub mod_out_meter: ModOutMeter,
 }
 
 impl OperatorWidgets {
     pub fn new<H: GuiSyncHandle>(sync_handle: &H, operator_index: usize) -> Self {
-        let mod_index = if operator_index != 0 {
-            Some(knob::operator_mod_index(sync_handle, operator_index))
-        } else {
-            None
-        };
-
         let mod_target = match operator_index {
             3 => Some(ModTargetPicker::Operator4(
                 mod_target_picker::operator_4_target(sync_handle, operator_index),
@@ -70,25 +90,56 @@ impl OperatorWidgets {
             _ => None,
         };
 
+        let backward_mod_target = match operator_index {
+            2 => Some(BackwardModTargetPicker::Operator3(
+                mod_target_picker::operator_3_backward_target(sync_handle, operator_index),
+            )),
+            1 => Some(BackwardModTargetPicker::Operator2(
+                mod_target_picker::operator_2_backward_target(sync_handle, operator_index),
+            )),
+            0 => Some(BackwardModTargetPicker::Operator1(
+                mod_target_picker::operator_1_backward_target(sync_handle, operator_index),
+            )),
+            _ => None,
+        };
+
         let wave_type_parameter =
             Parameter::Operator(operator_index as u8, OperatorParameter::WaveType);
 
-        Self {
+        let dx7_mod_out_scaling = ModOutScalingValue::new_from_patch(
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::ModOutScaling).into()),
+        )
+        .get()
+            == ModOutScaling::Dx7;
+
+        let mut widgets = Self {
             index: operator_index,
             alternative_controls: false,
             volume: knob::operator_volume(sync_handle, operator_index),
             mute_button: operator_mute_button(sync_handle, operator_index),
+            audition_button: operator_audition_button(
+                operator_index,
+                sync_handle.get_audition_operator() == Some(operator_index),
+            ),
             mix: knob::operator_mix(sync_handle, operator_index),
             panning: knob::operator_panning(sync_handle, operator_index),
+            panning_mode_button: operator_panning_mode_button(sync_handle, operator_index),
+            panning_lfo_mode_button: operator_panning_lfo_mode_button(sync_handle, operator_index),
             wave_type: WavePicker::new(sync_handle, wave_type_parameter, "WAVE"),
-            mod_index,
+            mod_index: knob::operator_mod_index(sync_handle, operator_index),
             mod_target,
+            backward_mod_target,
             feedback: knob::operator_feedback(sync_handle, operator_index),
             frequency_ratio: knob::operator_frequency_ratio(sync_handle, operator_index),
             frequency_free: knob::operator_frequency_free(sync_handle, operator_index),
             frequency_fine: knob::operator_frequency_fine(sync_handle, operator_index),
             envelope: Envelope::new(sync_handle, operator_index),
             wave_display: WaveDisplay::new(sync_handle, operator_index),
+            mod_out_meter: Default::default(),
+            mix_out_velocity_sensitivity: knob::operator_mix_out_velocity_sensitivity(
+                sync_handle,
+                operator_index,
+            ),
             mod_out_velocity_sensitivity: knob::operator_mod_out_velocity_sensitivity(
                 sync_handle,
                 operator_index,
@@ -97,19 +148,60 @@ impl OperatorWidgets {
                 sync_handle,
                 operator_index,
             ),
-        }
+            feedback_tone: knob::operator_feedback_tone(sync_handle, operator_index),
+        };
+
+        widgets.set_dx7_mod_out_scaling(dx7_mod_out_scaling);
+
+        widgets
+    }
+
+    pub fn set_dx7_mod_out_scaling(&mut self, dx7_mod_out_scaling: bool) {
+        let format: Option<fn(f32) -> CompactString> = if dx7_mod_out_scaling {
+            Some(OperatorModOutValue::get_formatted_dx7)
+        } else {
+            None
+        };
+
+        self.mod_index.set_value_format(format);
+
+        self.wave_display
+            .set_dx7_mod_out_scaling(dx7_mod_out_scaling);
     }
 
     pub fn theme_changed(&mut self) {
         self.mute_button.theme_changed();
+        self.audition_button.theme_changed();
+        self.panning_mode_button.theme_changed();
+        self.panning_lfo_mode_button.theme_changed();
         self.wave_type.theme_changed();
         self.envelope.theme_changed();
         self.wave_display.theme_changed();
+        self.mod_out_meter.theme_changed();
     }
 
     pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
         let heading = {
             let mute_button = tooltip(theme, "Toggle mute", Position::Top, self.mute_button.view());
+            let audition_button = tooltip(
+                theme,
+                "Audition operator\n\nHear operator directly at a safe level,\nincluding modulation it receives,\nwith other operators silenced",
+                Position::Top,
+                self.audition_button.view(),
+            );
+            let reset_button = button_with_tooltip(
+                theme,
+                theme.font_regular(),
+                "R",
+                Message::ResetSection(ParameterSection::Operator(self.index as u8)),
+                "Reset operator to init patch values",
+            );
+            let mod_out_meter = tooltip(
+                theme,
+                "Modulation output\n\nPeak modulation reaching other operators,\nafter envelope, LFOs and velocity",
+                Position::Top,
+                self.mod_out_meter.view(),
+            );
 
             Container::new(
                 Column::new()
@@ -120,15 +212,31 @@ impl OperatorWidgets {
                     .push(
                         Row::new()
                             .width(Length::Fill)
+                            .align_items(Alignment::Center)
                             .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT))))
-                            .push(mute_button),
+                            .push(mute_button)
+                            .push(Space::with_width(Length::Fixed(3.0)))
+                            .push(reset_button)
+                            .push(Space::with_width(Length::Fill))
+                            .push(mod_out_meter)
+                            .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT)))),
                     )
                     .push(
-                        Text::new(format!("OP {}", self.index + 1))
-                            .size(FONT_SIZE + FONT_SIZE / 2)
-                            .height(Length::Fixed(f32::from(FONT_SIZE + FONT_SIZE / 2)))
-                            .font(theme.font_heading())
-                            .horizontal_alignment(Horizontal::Center),
+                        Row::new()
+                            .width(Length::Fill)
+                            .align_items(Alignment::Center)
+                            // Balance audition button, keeping title centered
+                            .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT * 2))))
+                            .push(
+                                Text::new(format!("OP {}", self.index + 1))
+                                    .size(FONT_SIZE + FONT_SIZE / 2)
+                                    .width(Length::Fill)
+                                    .height(Length::Fixed(f32::from(FONT_SIZE + FONT_SIZE / 2)))
+                                    .font(theme.font_heading())
+                                    .horizontal_alignment(Horizontal::Center),
+                            )
+                            .push(audition_button)
+                            .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT)))),
                     )
                     .push(Space::with_height(Length::Fixed(f32::from(
                         LINE_HEIGHT / 2,
@@ -149,37 +257,37 @@ impl OperatorWidgets {
         );
 
         let routing_group = {
-            let mut group = Row::new()
-                .push(container_l3(self.mix.view(theme)))
-                .push(space_l3());
-
-            if let Some(mod_index) = self.mod_index.as_ref() {
-                group = group.push(container_l3(mod_index.view(theme)));
-            } else {
-                group = group.push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT * 5))));
-            }
-
-            group = group.push(space_l3());
+            // Higher-numbered targets first, like in forward pickers
+            let mut checkboxes = match self.backward_mod_target.as_ref() {
+                Some(BackwardModTargetPicker::Operator1(picker)) => picker.checkboxes(theme),
+                Some(BackwardModTargetPicker::Operator2(picker)) => picker.checkboxes(theme),
+                Some(BackwardModTargetPicker::Operator3(picker)) => picker.checkboxes(theme),
+                None => Vec::new(),
+            };
 
             match self.mod_target.as_ref() {
                 Some(ModTargetPicker::Operator2(picker)) => {
-                    group = group.push(container_l3(picker.view(theme)))
+                    checkboxes.extend(picker.checkboxes(theme))
                 }
                 Some(ModTargetPicker::Operator3(picker)) => {
-                    group = group.push(container_l3(picker.view(theme)))
+                    checkboxes.extend(picker.checkboxes(theme))
                 }
                 Some(ModTargetPicker::Operator4(picker)) => {
-                    group = group.push(container_l3(picker.view(theme)))
-                }
-                None => {
-                    group = group.push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT * 3))))
+                    checkboxes.extend(picker.checkboxes(theme))
                 }
+                None => (),
             }
 
-            group = group.push(space_l3());
-            group = group.push(container_l3(self.feedback.view(theme)));
-
-            container_l2(group)
+            container_l2(
+                Row::new()
+                    .push(container_l3(self.mix.view(theme)))
+                    .push(space_l3())
+                    .push(container_l3(self.mod_index.view(theme)))
+                    .push(space_l3())
+                    .push(container_l3(mod_target_picker::view(theme, checkboxes)))
+                    .push(space_l3())
+                    .push(container_l3(self.feedback.view(theme))),
+            )
         };
 
         let frequency_group = container_l2(
@@ -192,17 +300,42 @@ impl OperatorWidgets {
         );
 
         let end = if self.alternative_controls {
+            let panning_mode = Column::new()
+                .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                .align_items(Alignment::Center)
+                .push(tooltip(
+                    theme,
+                    "Panning mode\n\nM/S = pan between mid and sides instead of\nleft and right. Fully right places signal only in\nthe sides, for wide textures",
+                    Position::Top,
+                    Text::new("PAN")
+                        .horizontal_alignment(Horizontal::Center)
+                        .font(theme.font_bold())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .width(LINE_HEIGHT * 4),
+                ))
+                .push(Space::with_height(LINE_HEIGHT))
+                .push(self.panning_mode_button.view())
+                .push(Space::with_height(LINE_HEIGHT / 2))
+                .push(tooltip(
+                    theme,
+                    "Pan LFO spread\n\nApply LFO panning modulation with opposite\nsigns to left and right channels, for\nrotary-like motion. No effect in M/S mode",
+                    Position::Top,
+                    self.panning_lfo_mode_button.view(),
+                ));
+
             container_l2(
                 Row::new()
                     .push(space_l3())
-                    .push(if self.index > 0 {
-                        container_l3(self.mod_out_velocity_sensitivity.view(theme))
-                    } else {
-                        container_l3(Space::with_width(LINE_HEIGHT * 4))
-                    })
+                    .push(container_l3(self.mix_out_velocity_sensitivity.view(theme)))
+                    .push(space_l3())
+                    .push(container_l3(self.mod_out_velocity_sensitivity.view(theme)))
                     .push(space_l3())
                     .push(container_l3(self.feedback_velocity_sensitivity.view(theme)))
-                    .push(space_l3().width(LINE_HEIGHT * 15)),
+                    .push(space_l3())
+                    .push(container_l3(self.feedback_tone.view(theme)))
+                    .push(space_l3())
+                    .push(container_l3(panning_mode))
+                    .push(space_l3().width(LINE_HEIGHT * 3)),
             )
         } else {
             container_l2(self.envelope.view(theme))
@@ -8,13 +8,22 @@ use iced_baseview::{
     widget::Column, widget::Container, widget::Space, widget::Text, Element, Length,
 };
 
-use super::boolean_button::{voice_mode_button, BooleanButton};
+use crate::parameters::voice_mode::{VoiceModeValue, VOICE_MODE_STEPS};
+use crate::parameters::{MasterParameter, Parameter, ParameterValue};
+
+use super::boolean_button::{patch_preview_button, BooleanButton};
 use super::common::tooltip;
 use super::LINE_HEIGHT;
 use super::{style::Theme, GuiSyncHandle, Message, FONT_SIZE};
 
 const ACTIONS: &[Action] = &[
+    Action::FindPatch,
     Action::RenamePatch,
+    Action::EditKeyTuning,
+    Action::MovePatchUp,
+    Action::MovePatchDown,
+    Action::CopyPatch,
+    Action::PastePatch,
     Action::SavePatch,
     Action::SaveBank,
     Action::OpenPatchesOrBank,
@@ -24,7 +33,13 @@ const ACTIONS: &[Action] = &[
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Action {
+    FindPatch,
     RenamePatch,
+    EditKeyTuning,
+    MovePatchUp,
+    MovePatchDown,
+    CopyPatch,
+    PastePatch,
     SavePatch,
     SaveBank,
     OpenPatchesOrBank,
@@ -35,7 +50,13 @@ enum Action {
 impl Action {
     fn to_message(self) -> Message {
         match self {
+            Self::FindPatch => Message::OpenPatchSearch,
             Self::RenamePatch => Message::RenamePatch,
+            Self::EditKeyTuning => Message::OpenKeyTuning,
+            Self::MovePatchUp => Message::MovePatch(-1),
+            Self::MovePatchDown => Message::MovePatch(1),
+            Self::CopyPatch => Message::CopyPatch,
+            Self::PastePatch => Message::PastePatch,
             Self::SavePatch => Message::SavePatch,
             Self::SaveBank => Message::SaveBank,
             Self::OpenPatchesOrBank => Message::LoadBankOrPatch,
@@ -48,10 +69,16 @@ impl Action {
 impl Display for Action {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::FindPatch => write!(f, "FIND PATCH..."),
             Self::RenamePatch => write!(f, "RENAME PATCH"),
-            Self::SavePatch => write!(f, "SAVE PATCH"),
-            Self::SaveBank => write!(f, "SAVE BANK"),
-            Self::OpenPatchesOrBank => write!(f, "OPEN PATCHES/BANK"),
+            Self::EditKeyTuning => write!(f, "EDIT KEY TUNING..."),
+            Self::MovePatchUp => write!(f, "MOVE PATCH UP"),
+            Self::MovePatchDown => write!(f, "MOVE PATCH DOWN"),
+            Self::CopyPatch => write!(f, "COPY PATCH"),
+            Self::PastePatch => write!(f, "PASTE PATCH"),
+            Self::SavePatch => write!(f, "SAVE PATCH AS..."),
+            Self::SaveBank => write!(f, "SAVE BANK AS..."),
+            Self::OpenPatchesOrBank => write!(f, "LOAD..."),
             Self::ClearPatch => write!(f, "CLEAR PATCH"),
             Self::ClearBank => write!(f, "CLEAR BANK"),
         }
@@ -73,11 +100,12 @@ impl Display for Patch {
 pub struct PatchPicker {
     patch_options: Vec<Patch>,
     patch_index: usize,
-    pub voice_mode_button: BooleanButton,
+    pub voice_mode: f32,
+    pub patch_preview_button: BooleanButton,
 }
 
 impl PatchPicker {
-    pub fn new<H: GuiSyncHandle>(sync_handle: &H) -> Self {
+    pub fn new<H: GuiSyncHandle>(sync_handle: &H, patch_preview: bool) -> Self {
         let (patch_index, patch_names) = sync_handle.get_patches();
 
         let patch_options = patch_names
@@ -86,17 +114,20 @@ impl PatchPicker {
             .map(|(index, title)| Patch { index, title })
             .collect();
 
-        let voice_mode_button = voice_mode_button(sync_handle);
+        let voice_mode =
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::VoiceMode).into());
+        let patch_preview_button = patch_preview_button(patch_preview);
 
         Self {
             patch_options,
             patch_index,
-            voice_mode_button,
+            voice_mode,
+            patch_preview_button,
         }
     }
 
     pub fn theme_changed(&mut self) {
-        self.voice_mode_button.theme_changed();
+        self.patch_preview_button.theme_changed();
     }
 
     pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
@@ -117,11 +148,11 @@ impl PatchPicker {
             .placeholder("ACTIONS..")
             .width(Length::Fill);
 
-        let voice_mode_button = tooltip(
+        let patch_preview_button = tooltip(
             theme,
-            "Toggle polyphonic / monophonic voice mode",
+            "Play short preview note of patch highlighted in patch search",
             Position::Top,
-            self.voice_mode_button.view(),
+            self.patch_preview_button.view(),
         );
 
         Container::new(
@@ -132,20 +163,19 @@ impl PatchPicker {
                 ))))
                 .push(
                     Row::new()
-                        .push(Column::new().width(LINE_HEIGHT * 3))
+                        .push(
+                            Column::new()
+                                .width(LINE_HEIGHT * 2 + LINE_HEIGHT / 2)
+                                .push(Space::with_height(3))
+                                .push(patch_preview_button),
+                        )
                         .push(
                             Text::new("Patch")
                                 .size(f32::from(FONT_SIZE * 3 / 2))
                                 .height(Length::Fixed(f32::from(FONT_SIZE * 3 / 2)))
                                 .font(theme.font_heading())
                                 .horizontal_alignment(Horizontal::Center)
-                                .width(LINE_HEIGHT * 6),
-                        )
-                        .push(Space::with_width(LINE_HEIGHT / 2))
-                        .push(
-                            Column::new()
-                                .push(Space::with_height(3))
-                                .push(voice_mode_button),
+                                .width(Length::Fill),
                         ),
                 )
                 .push(Space::with_height(Length::Fixed(f32::from(
@@ -153,8 +183,35 @@ impl PatchPicker {
                 ))))
                 .push(patch_picker),
         )
-        .width(Length::Fixed(f32::from(LINE_HEIGHT * 12)))
+        .width(Length::Fixed(f32::from(LINE_HEIGHT * 8)))
         .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
         .into()
     }
+
+    pub fn voice_mode_view(&self, theme: &Theme) -> Element<Message, Theme> {
+        let voice_mode_picker = PickList::new(
+            VOICE_MODE_STEPS,
+            Some(VoiceModeValue::new_from_patch(self.voice_mode).get()),
+            |option| {
+                let v = VoiceModeValue::new_from_audio(option).to_patch();
+
+                Message::ChangeSingleParameterImmediate(
+                    Parameter::Master(MasterParameter::VoiceMode).into(),
+                    v,
+                )
+            },
+        )
+        .font(theme.font_regular())
+        .text_size(FONT_SIZE)
+        .padding(theme.picklist_padding())
+        .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)));
+
+        tooltip(
+            theme,
+            "Voice mode. FAT is monophonic with stacked unison voices",
+            Position::Top,
+            voice_mode_picker,
+        )
+        .into()
+    }
 }
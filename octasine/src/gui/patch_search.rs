@@ -0,0 +1,162 @@
+use compact_str::CompactString;
+use iced_baseview::alignment::Horizontal;
+use iced_baseview::keyboard::{self, KeyCode};
+use iced_baseview::widget::{scrollable, text_input, Button, Column, Scrollable, Text, TextInput};
+use iced_baseview::{event, Command, Element, Event, Length};
+
+use super::style::button::ButtonStyle;
+use super::style::Theme;
+use super::{Message, FONT_SIZE, LINE_HEIGHT};
+
+/// Filter box narrowing patch slots by case-insensitive substring match on
+/// their names (including slot numbers), with highlighted match loaded on
+/// Enter
+#[derive(Debug, Clone)]
+pub struct PatchSearch {
+    query: String,
+    /// Patch names as returned by sync handle, indexed by slot
+    patch_names: Vec<CompactString>,
+    /// Indices of slots matching query
+    matches: Vec<usize>,
+    /// Index into matches of highlighted slot
+    selected: usize,
+}
+
+impl PatchSearch {
+    pub fn new(patch_names: Vec<CompactString>, current_patch_index: usize) -> Self {
+        let matches = (0..patch_names.len()).collect();
+
+        Self {
+            query: String::new(),
+            patch_names,
+            matches,
+            selected: current_patch_index,
+        }
+    }
+
+    fn input_id() -> text_input::Id {
+        text_input::Id::new("patch-search-input")
+    }
+
+    fn list_id() -> scrollable::Id {
+        scrollable::Id::new("patch-search-list")
+    }
+
+    /// Focus filter box, so that typing starts filtering right away
+    pub fn focus() -> Command<Message> {
+        text_input::focus(Self::input_id())
+    }
+
+    /// Keyboard navigation of matches. Arrow keys aren't captured by text
+    /// input, so they are picked up here while the filter box is focused.
+    pub fn handle_event(event: Event, _status: event::Status) -> Option<Message> {
+        match event {
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: KeyCode::Up,
+                ..
+            }) => Some(Message::PatchSearchMoveSelection(-1)),
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: KeyCode::Down,
+                ..
+            }) => Some(Message::PatchSearchMoveSelection(1)),
+            _ => None,
+        }
+    }
+
+    pub fn set_query(&mut self, query: String) {
+        let opt_selected_patch_index = self.selected_patch_index();
+        let query_lowercase = query.to_lowercase();
+
+        self.matches = self
+            .patch_names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| name.to_lowercase().contains(&query_lowercase))
+            .map(|(index, _)| index)
+            .collect();
+
+        // Keep highlighting previously highlighted slot if it still matches
+        self.selected = opt_selected_patch_index
+            .and_then(|index| self.matches.iter().position(|i| *i == index))
+            .unwrap_or(0);
+        self.query = query;
+    }
+
+    /// Move highlight, returning command scrolling list to keep it visible
+    pub fn move_selection(&mut self, offset: isize) -> Command<Message> {
+        if self.matches.is_empty() {
+            return Command::none();
+        }
+
+        self.selected = self
+            .selected
+            .saturating_add_signed(offset)
+            .min(self.matches.len() - 1);
+
+        let y = if self.matches.len() > 1 {
+            self.selected as f32 / (self.matches.len() - 1) as f32
+        } else {
+            0.0
+        };
+
+        scrollable::snap_to(Self::list_id(), scrollable::RelativeOffset { x: 0.0, y })
+    }
+
+    pub fn selected_patch_index(&self) -> Option<usize> {
+        self.matches.get(self.selected).copied()
+    }
+
+    pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
+        let mut input = TextInput::new("Filter by name..", &self.query, Message::PatchSearchQuery)
+            .id(Self::input_id())
+            .font(theme.font_regular())
+            .size(FONT_SIZE)
+            .padding(theme.picklist_padding());
+
+        if let Some(index) = self.selected_patch_index() {
+            input = input.on_submit(Message::PatchSearchLoad(index));
+        }
+
+        let mut rows = Column::new();
+
+        for (i, index) in self.matches.iter().copied().enumerate() {
+            let style = if i == self.selected {
+                ButtonStyle::Regular
+            } else {
+                ButtonStyle::Value
+            };
+
+            rows = rows.push(
+                Button::new(
+                    Text::new(self.patch_names[index].as_str())
+                        .font(theme.font_regular())
+                        .size(FONT_SIZE)
+                        .width(Length::Fill),
+                )
+                .width(Length::Fill)
+                .style(style)
+                .on_press(Message::PatchSearchLoad(index)),
+            );
+        }
+
+        if self.matches.is_empty() {
+            rows = rows.push(
+                Text::new("No matching patches")
+                    .size(FONT_SIZE)
+                    .font(theme.font_regular())
+                    .width(Length::Fill)
+                    .horizontal_alignment(Horizontal::Center),
+            );
+        }
+
+        Column::new()
+            .spacing(LINE_HEIGHT / 2)
+            .push(input)
+            .push(
+                Scrollable::new(rows)
+                    .id(Self::list_id())
+                    .height(Length::Fixed(f32::from(LINE_HEIGHT * 24))),
+            )
+            .into()
+    }
+}
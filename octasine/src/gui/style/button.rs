@@ -10,6 +10,12 @@ pub enum ButtonStyle {
     #[default]
     Regular,
     Value,
+    /// Output clip light, red when output exceeded full scale. Filled when
+    /// clipping happened recently.
+    ClipIndicator {
+        clipping: bool,
+        latched: bool,
+    },
 }
 
 impl StyleSheet for Theme {
@@ -69,6 +75,40 @@ impl StyleSheet for Theme {
                     }
                 }
             },
+            Self::Style::ClipIndicator { clipping, latched } => match self {
+                Self::Light => {
+                    use super::colors::light::*;
+
+                    Appearance {
+                        background: if *clipping { RED } else { SURFACE }.into(),
+                        border_radius: 3.0,
+                        border_width: 1.0,
+                        border_color: if *latched { RED } else { BORDER },
+                        text_color: match (clipping, latched) {
+                            (true, _) => SURFACE,
+                            (false, true) => RED,
+                            (false, false) => GRAY_500,
+                        },
+                        ..Default::default()
+                    }
+                }
+                Self::Dark => {
+                    use super::colors::dark::*;
+
+                    Appearance {
+                        background: if *clipping { RED } else { SURFACE }.into(),
+                        border_radius: 3.0,
+                        border_width: 1.0,
+                        border_color: if *latched { RED } else { SURFACE },
+                        text_color: match (clipping, latched) {
+                            (true, _) => BACKGROUND,
+                            (false, true) => RED,
+                            (false, false) => GRAY_700,
+                        },
+                        ..Default::default()
+                    }
+                }
+            },
         }
     }
 
@@ -112,6 +152,7 @@ impl StyleSheet for Theme {
                     }
                 }
             },
+            Self::Style::ClipIndicator { .. } => self.active(style),
         }
     }
 
@@ -17,6 +17,8 @@ impl StyleSheet for Theme {
                     text_color: TEXT,
                     time_marker_minor_color: GRAY_900,
                     time_marker_color_major: GRAY_700,
+                    beat_grid_color: GRAY_600,
+                    bar_grid_color: GREEN,
                     path_color: BLUE,
                     dragger_fill_color_active: SURFACE,
                     dragger_fill_color_hover: SURFACE_HOVER,
@@ -24,6 +26,7 @@ impl StyleSheet for Theme {
                     dragger_border_color: BORDER,
                     viewport_indicator_border: GRAY_300,
                     viewport_indicator_border_active: BLUE,
+                    selection_color: RED,
                 }
             }
             Self::Dark => {
@@ -36,6 +39,8 @@ impl StyleSheet for Theme {
                     text_color: TEXT,
                     time_marker_minor_color: GRAY_300,
                     time_marker_color_major: GRAY_500,
+                    beat_grid_color: GRAY_600,
+                    bar_grid_color: GREEN,
                     path_color: BLUE,
                     dragger_fill_color_active: TEXT,
                     dragger_fill_color_hover: HOVERED,
@@ -43,6 +48,7 @@ impl StyleSheet for Theme {
                     dragger_border_color: SURFACE,
                     viewport_indicator_border: GRAY_600,
                     viewport_indicator_border_active: BLUE,
+                    selection_color: RED,
                 }
             }
         }
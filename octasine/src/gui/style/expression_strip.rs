@@ -0,0 +1,32 @@
+use iced_baseview::Color;
+
+use crate::gui::expression_strip::{Appearance, StyleSheet};
+
+use super::Theme;
+
+impl StyleSheet for Theme {
+    fn appearance(&self) -> Appearance {
+        match self {
+            Self::Light => {
+                use super::colors::light::*;
+                Appearance {
+                    background_color: SURFACE,
+                    border_color: BORDER,
+                    border_color_hovered: BORDER,
+                    bar_color: BLUE,
+                    center_line_color: GRAY_600,
+                }
+            }
+            Self::Dark => {
+                use super::colors::dark::*;
+                Appearance {
+                    background_color: Color::TRANSPARENT,
+                    border_color: BORDER,
+                    border_color_hovered: BORDER_HOVERED,
+                    bar_color: BLUE,
+                    center_line_color: GRAY_400,
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use iced_audio::style::knob::{Appearance, StyleSheet, TickMarksAppearance};
+use iced_audio::style::knob::{Appearance, ModRangeArcAppearance, StyleSheet, TickMarksAppearance};
 
 use super::Theme;
 
@@ -104,4 +104,22 @@ impl StyleSheet for Theme {
             offset: 3.0,
         })
     }
+
+    fn mod_range_arc_appearance(&self, _style: &Self::Style) -> Option<ModRangeArcAppearance> {
+        use iced_audio::knob::{LineCap, StyleLength};
+
+        let color = match self {
+            Self::Dark => super::colors::dark::GREEN,
+            Self::Light => super::colors::light::GREEN,
+        };
+
+        Some(ModRangeArcAppearance {
+            width: StyleLength::Fixed(2.0),
+            offset: StyleLength::Fixed(2.0),
+            empty_color: None,
+            filled_color: color,
+            filled_inverse_color: color,
+            cap: LineCap::Butt,
+        })
+    }
 }
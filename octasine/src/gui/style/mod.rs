@@ -8,9 +8,11 @@ pub mod checkbox;
 pub mod colors;
 pub mod container;
 pub mod envelope;
+pub mod expression_strip;
 pub mod knob;
 pub mod menu;
 pub mod mod_matrix;
+pub mod mod_out_meter;
 pub mod modal;
 pub mod pick_list;
 pub mod radio;
@@ -0,0 +1,28 @@
+use iced_baseview::Color;
+
+use crate::gui::mod_out_meter::{Appearance, StyleSheet};
+
+use super::Theme;
+
+impl StyleSheet for Theme {
+    fn appearance(&self) -> Appearance {
+        match self {
+            Self::Light => {
+                use super::colors::light::*;
+                Appearance {
+                    background_color: SURFACE,
+                    border_color: BORDER,
+                    bar_color: BLUE,
+                }
+            }
+            Self::Dark => {
+                use super::colors::dark::*;
+                Appearance {
+                    background_color: Color::TRANSPARENT,
+                    border_color: BORDER,
+                    bar_color: BLUE,
+                }
+            }
+        }
+    }
+}
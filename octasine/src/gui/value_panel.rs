@@ -0,0 +1,55 @@
+use compact_str::CompactString;
+use iced_baseview::alignment::{Horizontal, Vertical};
+use iced_baseview::widget::{Container, Row, Space, Text};
+use iced_baseview::{Alignment, Element, Length};
+
+use crate::parameters::WrappedParameter;
+
+use super::style::container::ContainerStyle;
+use super::style::Theme;
+use super::{Message, FONT_SIZE, LINE_HEIGHT};
+
+/// Footer showing name and formatted value of most recently changed
+/// parameter, including changes made through host automation, followed by
+/// expression strips
+pub fn value_panel<'a>(
+    theme: &Theme,
+    parameter_and_value: Option<&(WrappedParameter, CompactString)>,
+    expression_strips: Element<'a, Message, Theme>,
+) -> Element<'a, Message, Theme> {
+    let (name, value) = match parameter_and_value {
+        Some((parameter, value)) => (
+            parameter.parameter().name().to_uppercase(),
+            value.to_string(),
+        ),
+        None => (String::new(), String::new()),
+    };
+
+    let row = Row::new()
+        .align_items(Alignment::Center)
+        .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
+        .push(
+            Text::new(name)
+                .size(FONT_SIZE)
+                .font(theme.font_bold())
+                .vertical_alignment(Vertical::Center),
+        )
+        .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
+        .push(
+            Text::new(value)
+                .size(FONT_SIZE)
+                .font(theme.font_regular())
+                .width(Length::Fill)
+                .horizontal_alignment(Horizontal::Left)
+                .vertical_alignment(Vertical::Center),
+        )
+        .push(expression_strips)
+        .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())));
+
+    Container::new(row)
+        .width(Length::Fill)
+        .height(Length::Fixed(f32::from(LINE_HEIGHT * 2)))
+        .center_y()
+        .style(ContainerStyle::L0)
+        .into()
+}
@@ -0,0 +1,265 @@
+use compact_str::CompactString;
+use iced_baseview::alignment::{Horizontal, Vertical};
+use iced_baseview::renderer::Renderer as _;
+use iced_baseview::text::Renderer as _;
+use iced_baseview::widget::container::StyleSheet;
+use iced_baseview::widget::tree::{self, Tree};
+use iced_baseview::widget::Operation;
+use iced_baseview::{
+    event, layout, mouse, overlay, renderer, text, Clipboard, Color, Element, Event, Font, Layout,
+    Length, Point, Rectangle, Shell, Size, Vector, Widget,
+};
+
+use super::style::container::ContainerStyle;
+use super::style::Theme;
+use super::{Message, FONT_SIZE};
+
+type Renderer = iced_baseview::Renderer<Theme>;
+
+/// Distance from cursor to top left corner of readout
+const CURSOR_OFFSET: Vector = Vector { x: 16.0, y: 8.0 };
+
+#[derive(Debug, Default)]
+struct State {
+    cursor_position: Point,
+}
+
+/// Wraps contents and shows a small box with given text next to the
+/// cursor, e.g., the formatted value of a parameter while a knob is being
+/// dragged
+pub struct ValueReadout<'a> {
+    content: Element<'a, Message, Theme>,
+    text: Option<CompactString>,
+    font: Font,
+    padding: f32,
+}
+
+impl<'a> ValueReadout<'a> {
+    pub fn new(
+        theme: &Theme,
+        content: impl Into<Element<'a, Message, Theme>>,
+        text: Option<CompactString>,
+    ) -> Self {
+        Self {
+            content: content.into(),
+            text,
+            font: theme.font_regular(),
+            padding: f32::from(theme.tooltip_padding()),
+        }
+    }
+}
+
+impl<'a> Widget<Message, Renderer> for ValueReadout<'a> {
+    fn width(&self) -> Length {
+        self.content.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.as_widget().height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.as_widget().layout(renderer, limits)
+    }
+
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content))
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation)
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        if let Event::Mouse(mouse::Event::CursorMoved { position }) = event {
+            tree.state.downcast_mut::<State>().cursor_position = position;
+        }
+
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            shell,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor_position,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor_position,
+            viewport,
+        )
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'b, Message, Renderer>> {
+        // Content overlays such as pick list menus can't be open while
+        // dragging, so there is no need to show both
+        match self.text.as_ref() {
+            Some(text) => {
+                let position = tree.state.downcast_ref::<State>().cursor_position;
+
+                Some(overlay::Element::new(
+                    position,
+                    Box::new(Readout {
+                        text: text.as_str(),
+                        font: self.font,
+                        padding: self.padding,
+                    }),
+                ))
+            }
+            None => self
+                .content
+                .as_widget_mut()
+                .overlay(&mut tree.children[0], layout, renderer),
+        }
+    }
+}
+
+impl<'a> From<ValueReadout<'a>> for Element<'a, Message, Theme> {
+    fn from(value_readout: ValueReadout<'a>) -> Self {
+        Element::new(value_readout)
+    }
+}
+
+struct Readout<'b> {
+    text: &'b str,
+    font: Font,
+    padding: f32,
+}
+
+impl<'b> overlay::Overlay<Message, Renderer> for Readout<'b> {
+    fn layout(&self, renderer: &Renderer, bounds: Size, position: Point) -> layout::Node {
+        let (text_width, text_height) =
+            renderer.measure(self.text, f32::from(FONT_SIZE), self.font, Size::INFINITY);
+
+        let size = Size::new(
+            text_width + self.padding * 2.0,
+            text_height + self.padding * 2.0,
+        );
+
+        // Keep readout inside window, flipping it to the other side of the
+        // cursor when necessary
+        let mut position = position + CURSOR_OFFSET;
+
+        if position.x + size.width > bounds.width {
+            position.x -= size.width + CURSOR_OFFSET.x * 2.0;
+        }
+        if position.y + size.height > bounds.height {
+            position.y -= size.height + CURSOR_OFFSET.y * 2.0;
+        }
+
+        let mut node = layout::Node::new(size);
+
+        node.move_to(Point::new(position.x.max(0.0), position.y.max(0.0)));
+
+        node
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        _style: &renderer::Style,
+        layout: Layout<'_>,
+        _cursor_position: Point,
+    ) {
+        let appearance = theme.appearance(&ContainerStyle::Tooltip);
+        let bounds = layout.bounds();
+
+        renderer.fill_quad(
+            renderer::Quad {
+                bounds,
+                border_radius: appearance.border_radius.into(),
+                border_width: appearance.border_width,
+                border_color: appearance.border_color,
+            },
+            appearance.background.unwrap_or(Color::TRANSPARENT.into()),
+        );
+
+        renderer.fill_text(text::Text {
+            content: self.text,
+            bounds: Rectangle {
+                x: bounds.center_x(),
+                y: bounds.center_y(),
+                ..bounds
+            },
+            size: f32::from(FONT_SIZE),
+            color: appearance.text_color.unwrap_or(Color::BLACK),
+            font: self.font,
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Center,
+        });
+    }
+
+    /// Never capture cursor, so that dragged widget keeps receiving events
+    fn is_over(&self, _layout: Layout<'_>, _cursor_position: Point) -> bool {
+        false
+    }
+}
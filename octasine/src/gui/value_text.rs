@@ -14,24 +14,39 @@ use super::{style::Theme, GuiSyncHandle, Message};
 #[derive(Debug, Clone)]
 pub struct ValueText<P: ParameterValue> {
     parameter: WrappedParameter,
+    value_patch: f32,
     value_text: CompactString,
+    format: fn(f32) -> CompactString,
     phantom_data: PhantomData<P>,
 }
 
 impl<P: ParameterValue> ValueText<P> {
     pub fn new<H: GuiSyncHandle>(sync_handle: &H, parameter: WrappedParameter) -> Self {
         let value_patch = sync_handle.get_parameter(parameter);
-        let value_text = P::new_from_patch(value_patch).get_formatted();
+        let format = Self::default_format;
 
         Self {
             parameter,
-            value_text,
+            value_patch,
+            value_text: format(value_patch),
+            format,
             phantom_data: Default::default(),
         }
     }
 
     pub fn set_value(&mut self, value: f32) {
-        self.value_text = P::new_from_patch(value).get_formatted();
+        self.value_patch = value;
+        self.value_text = (self.format)(value);
+    }
+
+    /// Override value formatting, e.g., when it depends on other parameters
+    pub fn set_format(&mut self, format: Option<fn(f32) -> CompactString>) {
+        self.format = format.unwrap_or(Self::default_format);
+        self.value_text = (self.format)(self.value_patch);
+    }
+
+    fn default_format(value_patch: f32) -> CompactString {
+        P::new_from_patch(value_patch).get_formatted()
     }
 
     pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
@@ -0,0 +1,73 @@
+use iced_baseview::alignment::Horizontal;
+use iced_baseview::widget::{Column, Row, Scrollable, Text};
+use iced_baseview::{Element, Font, Length};
+
+use crate::audio::status::VoiceSnapshot;
+use crate::common::EnvelopeStage;
+use crate::parameters::utils::format_key;
+
+use super::style::Theme;
+use super::{Message, FONT_SIZE, LINE_HEIGHT};
+
+const HEADINGS: [&str; 5] = ["KEY", "VEL", "STAGE", "AGE", "MODE"];
+
+/// Table with one row per active voice, for understanding voice stealing
+/// and stuck notes
+pub fn voice_list<'a>(theme: &Theme, voices: &[VoiceSnapshot]) -> Element<'a, Message, Theme> {
+    let mut rows = Column::new().spacing(LINE_HEIGHT / 4);
+
+    for voice in voices {
+        let stage = match voice.stage {
+            EnvelopeStage::Attack => "ATTACK",
+            EnvelopeStage::Decay => "DECAY",
+            EnvelopeStage::Sustain => "SUSTAIN",
+            EnvelopeStage::Release => "RELEASE",
+            EnvelopeStage::Ended => "ENDED",
+            EnvelopeStage::Kill => "KILL",
+            EnvelopeStage::Steal => "STEAL",
+        };
+        let mode = if voice.monophonic { "MONO" } else { "POLY" };
+
+        rows = rows.push(row(
+            theme.font_regular(),
+            [
+                format_key(voice.key).into(),
+                voice.velocity.to_string(),
+                stage.into(),
+                format!("{:.1} s", voice.age_ms as f32 / 1000.0),
+                mode.into(),
+            ],
+        ));
+    }
+
+    if voices.is_empty() {
+        rows = rows.push(
+            Text::new("No active voices")
+                .size(FONT_SIZE)
+                .font(theme.font_regular())
+                .width(Length::Fill)
+                .horizontal_alignment(Horizontal::Center),
+        );
+    }
+
+    Column::new()
+        .spacing(LINE_HEIGHT / 2)
+        .push(row(theme.font_bold(), HEADINGS.map(String::from)))
+        .push(Scrollable::new(rows).height(Length::Fixed(f32::from(LINE_HEIGHT * 16))))
+        .into()
+}
+
+fn row<'a>(font: Font, cells: [String; 5]) -> Element<'a, Message, Theme> {
+    cells
+        .into_iter()
+        .fold(Row::new(), |row, cell| {
+            row.push(
+                Text::new(cell)
+                    .size(FONT_SIZE)
+                    .font(font)
+                    .width(Length::Fill)
+                    .horizontal_alignment(Horizontal::Center),
+            )
+        })
+        .into()
+}
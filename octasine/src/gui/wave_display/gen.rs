@@ -102,6 +102,8 @@ mod gen {
     #[feature_gate]
     use std::f64::consts::TAU;
 
+    #[feature_gate]
+    use crate::math::wave::sine_variant;
     #[feature_gate]
     use crate::parameters::operator_wave_type::WaveType;
 
@@ -168,6 +170,10 @@ mod gen {
                         ((feedback * phases.triangle()) + modulation_in + phases).triangle()
                     }
                     WaveType::Saw => ((feedback * phases.saw()) + modulation_in + phases).saw(),
+                    WaveType::SineVariant(variant) => sine_variant(
+                        variant,
+                        (feedback * sine_variant(variant, phases)) + modulation_in + phases,
+                    ),
                     WaveType::WhiteNoise => {
                         let mut random_numbers = <Pd as SimdPackedDouble>::Arr::default();
 
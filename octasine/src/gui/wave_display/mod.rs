@@ -8,7 +8,8 @@ use iced_baseview::widget::canvas::{
 use iced_baseview::widget::tooltip::Position;
 use iced_baseview::{widget::Row, widget::Space, Color, Element, Length, Point, Rectangle, Size};
 
-use crate::parameters::list::OperatorParameter;
+use crate::parameters::list::{MasterParameter, OperatorParameter};
+use crate::parameters::mod_out_scaling::{ModOutScaling, ModOutScalingValue};
 use crate::parameters::operator_active::OperatorActiveValue;
 use crate::parameters::operator_feedback::OperatorFeedbackValue;
 use crate::parameters::operator_frequency_fine::OperatorFrequencyFineValue;
@@ -78,6 +79,7 @@ struct OperatorData {
     pan: OperatorPanningValue,
     constant_power_panning: [f32; 2],
     mod_out: Option<OperatorModOutValue>,
+    mod_out_patch_value: f32,
     mod_targets: Option<OperatorModTargets>,
 }
 
@@ -107,10 +109,23 @@ impl OperatorData {
             pan: Default::default(),
             constant_power_panning: OperatorPanningValue::default().calculate_left_and_right(),
             mod_out: (operator_index > 0).then_some(Default::default()),
+            mod_out_patch_value: OperatorModOutValue::default().to_patch(),
             mod_targets,
         }
     }
 
+    fn set_mod_out_from_patch(&mut self, value: f32, dx7_scaling: bool) {
+        self.mod_out_patch_value = value;
+
+        if let Some(v) = self.mod_out.as_mut() {
+            *v = if dx7_scaling {
+                OperatorModOutValue::new_from_patch_dx7(value)
+            } else {
+                OperatorModOutValue::new_from_patch(value)
+            };
+        }
+    }
+
     fn frequency(&self) -> f64 {
         self.frequency_ratio.get().value * self.frequency_free.get() * self.frequency_fine.get()
     }
@@ -121,12 +136,19 @@ pub struct WaveDisplay {
     canvas_left: WaveDisplayCanvas,
     canvas_right: WaveDisplayCanvas,
     operators: [OperatorData; 4],
+    dx7_mod_out_scaling: bool,
 }
 
 impl WaveDisplay {
     pub fn new<H: GuiSyncHandle>(sync_handle: &H, operator_index: usize) -> Self {
         let mut operators = ::std::array::from_fn(OperatorData::new);
 
+        let dx7_mod_out_scaling = ModOutScalingValue::new_from_patch(
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::ModOutScaling).into()),
+        )
+        .get()
+            == ModOutScaling::Dx7;
+
         for (i, operator) in operators.iter_mut().enumerate() {
             let i = i as u8;
 
@@ -164,12 +186,10 @@ impl WaveDisplay {
                     .get_parameter(Parameter::Operator(i, OperatorParameter::Panning).into()),
             );
             operator.constant_power_panning = operator.pan.calculate_left_and_right();
-            if let Some(v) = operator.mod_out.as_mut() {
-                v.replace_from_patch(
-                    sync_handle
-                        .get_parameter(Parameter::Operator(i, OperatorParameter::ModOut).into()),
-                )
-            }
+            operator.set_mod_out_from_patch(
+                sync_handle.get_parameter(Parameter::Operator(i, OperatorParameter::ModOut).into()),
+                dx7_mod_out_scaling,
+            );
 
             match operator.mod_targets.as_mut() {
                 None => (),
@@ -198,6 +218,7 @@ impl WaveDisplay {
             canvas_left: WaveDisplayCanvas::new(canvas_points),
             canvas_right: WaveDisplayCanvas::new(canvas_points),
             operators,
+            dx7_mod_out_scaling,
         };
 
         display.recalculate_canvas_points();
@@ -246,9 +267,7 @@ impl WaveDisplay {
                     self.operators[i as usize].pan.calculate_left_and_right();
             }
             Parameter::Operator(i, OperatorParameter::ModOut) => {
-                if let Some(v) = self.operators[i as usize].mod_out.as_mut() {
-                    v.replace_from_patch(value)
-                }
+                self.operators[i as usize].set_mod_out_from_patch(value, self.dx7_mod_out_scaling)
             }
             Parameter::Operator(i, OperatorParameter::ModTargets) => {
                 match self.operators[i as usize].mod_targets.as_mut() {
@@ -264,6 +283,20 @@ impl WaveDisplay {
         self.recalculate_canvas_points();
     }
 
+    pub fn set_dx7_mod_out_scaling(&mut self, dx7_mod_out_scaling: bool) {
+        if dx7_mod_out_scaling == self.dx7_mod_out_scaling {
+            return;
+        }
+
+        self.dx7_mod_out_scaling = dx7_mod_out_scaling;
+
+        for operator in self.operators.iter_mut() {
+            operator.set_mod_out_from_patch(operator.mod_out_patch_value, dx7_mod_out_scaling);
+        }
+
+        self.recalculate_canvas_points();
+    }
+
     pub fn theme_changed(&mut self) {
         self.canvas_left.theme_changed();
         self.canvas_right.theme_changed();
@@ -22,6 +22,9 @@ const HEIGHT: u16 = LINE_HEIGHT * 2;
 const HEIGHT_MIDDLE: f32 = HEIGHT as f32 / 2.0 - 0.5;
 const SHAPE_HEIGHT_RANGE: f32 = HEIGHT as f32 / 4.0;
 
+/// Hide playhead after this many frames without a published phase
+const PLAYHEAD_TIMEOUT_FRAMES: usize = 30;
+
 #[derive(Debug, Clone)]
 pub struct Appearance {
     pub background_color: Color,
@@ -30,6 +33,7 @@ pub struct Appearance {
     pub border_color_hovered: Color,
     pub shape_line_color_active: Color,
     pub shape_line_color_hovered: Color,
+    pub playhead_color: Color,
 }
 
 pub trait StyleSheet {
@@ -80,6 +84,11 @@ where
         }
     }
 
+    /// Update playhead with phase published by audio thread, if any
+    pub fn update_playhead(&mut self, opt_phase: Option<f32>) {
+        self.canvas.update_playhead(opt_phase);
+    }
+
     pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
         let title = Text::new(&self.title)
             .horizontal_alignment(Horizontal::Center)
@@ -110,6 +119,8 @@ struct WavePickerCanvas<P: ParameterValue> {
     cache: Cache,
     bounds_path: Path,
     shape: P::Value,
+    playhead: Option<f32>,
+    frames_without_playhead_update: usize,
 }
 
 impl<P> WavePickerCanvas<P>
@@ -128,6 +139,8 @@ where
             cache: Cache::new(),
             bounds_path,
             shape,
+            playhead: None,
+            frames_without_playhead_update: 0,
         }
     }
 
@@ -147,6 +160,23 @@ where
         self.cache.clear();
     }
 
+    pub fn update_playhead(&mut self, opt_phase: Option<f32>) {
+        match opt_phase {
+            Some(phase) => {
+                self.playhead = Some(phase);
+                self.frames_without_playhead_update = 0;
+            }
+            None if self.playhead.is_some() => {
+                self.frames_without_playhead_update += 1;
+
+                if self.frames_without_playhead_update > PLAYHEAD_TIMEOUT_FRAMES {
+                    self.playhead = None;
+                }
+            }
+            None => (),
+        }
+    }
+
     fn draw_background(&self, frame: &mut Frame, theme: &Theme) {
         let apparence = theme.appearance();
 
@@ -212,6 +242,23 @@ where
     }
 }
 
+impl<P> WavePickerCanvas<P>
+where
+    P: ParameterValue + Copy + 'static,
+    P::Value: WaveformChoices,
+{
+    fn draw_playhead(&self, frame: &mut Frame, theme: &Theme, phase: f32) {
+        let appearance = theme.appearance();
+
+        let x = (0.5 + phase * (WIDTH - 1) as f32).floor() + 0.5;
+
+        let path = Path::line(Point::new(x, 1.0), Point::new(x, HEIGHT as f32 - 1.0));
+        let stroke = Stroke::default().with_color(appearance.playhead_color);
+
+        frame.stroke(&path, stroke)
+    }
+}
+
 impl<P> Program<Message, Theme> for WavePickerCanvas<P>
 where
     P: ParameterValue + Copy + 'static,
@@ -233,7 +280,15 @@ where
             self.draw_border(state, frame, theme);
         });
 
-        vec![geometry]
+        if let Some(phase) = self.playhead {
+            let mut frame = Frame::new(bounds.size());
+
+            self.draw_playhead(&mut frame, theme, phase);
+
+            vec![geometry, frame.into_geometry()]
+        } else {
+            vec![geometry]
+        }
     }
 
     fn update(
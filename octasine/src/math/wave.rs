@@ -1,3 +1,7 @@
+use std::f64::consts::TAU;
+
+use crate::simd::{FallbackPackedDouble, SimdPackedDouble};
+
 /// Triangle wave
 #[inline]
 pub fn triangle(x: f64) -> f64 {
@@ -71,3 +75,82 @@ pub fn saw(x: f64) -> f64 {
 
     (y - 0.5) * 2.0
 }
+
+/// Sine-based waveforms of Yamaha TX81Z (W2-W8) plus OPL-style quarter sine
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SineVariant {
+    /// Sine with both halves squared, keeping their signs (W2)
+    Squared,
+    /// Positive half of sine (W3)
+    Half,
+    /// Positive half of squared sine (W4)
+    HalfSquared,
+    /// Sine at double frequency during first half of cycle (W5)
+    Alternating,
+    /// Squared sine at double frequency during first half of cycle (W6)
+    AlternatingSquared,
+    /// Rectified sine at double frequency during first half of cycle (W7)
+    AlternatingHalf,
+    /// Rectified squared sine at double frequency during first half of
+    /// cycle (W8)
+    AlternatingHalfSquared,
+    /// Rising quarters of rectified sine (first and third quarter of cycle)
+    Quarter,
+}
+
+/// Sine waveform variant for phase in cycles (not radians)
+///
+/// Implemented in terms of generic packed double operations, so that output
+/// is identical for all SIMD implementations and the scalar version. Cycle
+/// segments are gated with the smooth square wave, so that the only hard
+/// transitions are in the derivative, as with the triangle wave.
+///
+/// # Safety
+///
+/// Only call when the instruction set used by `P` is supported by the CPU
+#[inline(always)]
+pub unsafe fn sine_variant<P: SimdPackedDouble>(variant: SineVariant, phase: P) -> P {
+    match variant {
+        SineVariant::Squared => {
+            let sine = (phase * P::new(TAU)).fast_sin();
+
+            sine * sine.abs()
+        }
+        SineVariant::Half => (phase * P::new(TAU)).fast_sin().max(P::new_zeroed()),
+        SineVariant::HalfSquared => {
+            let half = (phase * P::new(TAU)).fast_sin().max(P::new_zeroed());
+
+            half * half
+        }
+        SineVariant::Alternating => (phase * P::new(2.0 * TAU)).fast_sin() * first_half_gate(phase),
+        SineVariant::AlternatingSquared => {
+            let sine = (phase * P::new(2.0 * TAU)).fast_sin();
+
+            sine * sine.abs() * first_half_gate(phase)
+        }
+        SineVariant::AlternatingHalf => {
+            (phase * P::new(2.0 * TAU)).fast_sin().abs() * first_half_gate(phase)
+        }
+        SineVariant::AlternatingHalfSquared => {
+            let sine = (phase * P::new(2.0 * TAU)).fast_sin();
+
+            sine * sine * first_half_gate(phase)
+        }
+        SineVariant::Quarter => {
+            (phase * P::new(TAU)).fast_sin().abs() * first_half_gate(phase * P::new(2.0))
+        }
+    }
+}
+
+/// Scalar version of [`sine_variant`]
+#[inline]
+pub fn sine_variant_scalar(variant: SineVariant, phase: f64) -> f64 {
+    unsafe { sine_variant(variant, FallbackPackedDouble::new(phase)).to_arr()[0] }
+}
+
+/// One during first half of cycle and zero during second half, with smooth
+/// transitions
+#[inline(always)]
+unsafe fn first_half_gate<P: SimdPackedDouble>(phase: P) -> P {
+    (phase.square() + P::new(1.0)) * P::new(0.5)
+}
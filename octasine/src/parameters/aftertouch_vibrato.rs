@@ -0,0 +1,83 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{utils::parse_valid_f32, ParameterValue, SerializableRepresentation};
+
+/// Maximum aftertouch vibrato depth in cents
+const MAX_DEPTH: f32 = 200.0;
+
+const MIN_RATE: f32 = 0.5;
+const MAX_RATE: f32 = 16.0;
+
+/// Vibrato depth in cents at full key or channel pressure
+#[derive(Debug, Clone, Copy)]
+pub struct AftertouchVibratoDepthValue(f32);
+
+impl Default for AftertouchVibratoDepthValue {
+    fn default() -> Self {
+        Self(0.0)
+    }
+}
+
+impl ParameterValue for AftertouchVibratoDepthValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, MAX_DEPTH).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value * MAX_DEPTH)
+    }
+    fn to_patch(self) -> f32 {
+        self.0 / MAX_DEPTH
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.1} cents", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Frequency in Hz of the internal vibrato LFO used for aftertouch vibrato
+#[derive(Debug, Clone, Copy)]
+pub struct AftertouchVibratoRateValue(f32);
+
+impl Default for AftertouchVibratoRateValue {
+    fn default() -> Self {
+        Self(5.0)
+    }
+}
+
+impl ParameterValue for AftertouchVibratoRateValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, MIN_RATE, MAX_RATE).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(MIN_RATE + value * (MAX_RATE - MIN_RATE))
+    }
+    fn to_patch(self) -> f32 {
+        (self.0 - MIN_RATE) / (MAX_RATE - MIN_RATE)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.2} Hz", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
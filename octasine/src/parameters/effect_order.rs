@@ -0,0 +1,102 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const NUM_EFFECTS: usize = 2;
+
+/// Master effects applied before output mode processing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Eq,
+    DcBlocker,
+}
+
+pub const EFFECT_ORDER_STEPS: &[EffectOrder] = &[EffectOrder::EqFirst, EffectOrder::DcBlockerFirst];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EffectOrder {
+    #[default]
+    EqFirst,
+    DcBlockerFirst,
+}
+
+impl EffectOrder {
+    /// Effects in processing order
+    pub fn effects(self) -> [Effect; NUM_EFFECTS] {
+        match self {
+            Self::EqFirst => [Effect::Eq, Effect::DcBlocker],
+            Self::DcBlockerFirst => [Effect::DcBlocker, Effect::Eq],
+        }
+    }
+}
+
+impl ::std::fmt::Display for EffectOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::EqFirst => "EQ > DC",
+            Self::DcBlockerFirst => "DC > EQ",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EffectOrderValue(EffectOrder);
+
+impl ParameterValue for EffectOrderValue {
+    type Value = EffectOrder;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "eq > dc" | "eq first" | "eq" => Some(Self(EffectOrder::EqFirst)),
+            "dc > eq" | "dc first" | "dc" => Some(Self(EffectOrder::DcBlockerFirst)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(EFFECT_ORDER_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(EFFECT_ORDER_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effect_orders_contain_each_effect_once() {
+        for order in EFFECT_ORDER_STEPS.iter().copied() {
+            let effects = order.effects();
+
+            for effect in [Effect::Eq, Effect::DcBlocker] {
+                assert_eq!(effects.iter().filter(|e| **e == effect).count(), 1);
+            }
+
+            let value = EffectOrderValue::new_from_audio(order);
+
+            assert_eq!(
+                EffectOrderValue::new_from_text(&value.get_formatted())
+                    .unwrap()
+                    .get(),
+                order
+            );
+        }
+    }
+}
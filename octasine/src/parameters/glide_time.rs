@@ -1,7 +1,9 @@
 use compact_str::{format_compact, CompactString};
 
 use super::{
-    utils::{map_audio_to_patch_value_with_steps, map_patch_to_audio_value_with_steps},
+    utils::{
+        map_audio_to_patch_value_with_steps, map_patch_to_audio_value_with_steps, parse_valid_f32,
+    },
     ParameterValue, SerializableRepresentation,
 };
 
@@ -23,9 +25,7 @@ impl ParameterValue for GlideTimeValue {
         Self(value)
     }
     fn new_from_text(text: &str) -> Option<Self> {
-        text.parse::<Self::Value>()
-            .ok()
-            .map(|time| Self(time.clamp(0.0, *STEPS.last().unwrap())))
+        parse_valid_f32(text, 0.0, *STEPS.last().unwrap()).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -0,0 +1,74 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{utils::parse_valid_f32, ParameterValue, SerializableRepresentation};
+
+/// Key panned to center
+const CENTER_KEY: f32 = 60.0;
+/// Keys this many semitones away from center key are panned fully left or
+/// right at full amount
+const KEY_SPAN: f32 = 60.0;
+
+/// Amount of voice panning by key, with low keys panned left and high keys
+/// panned right
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyPanValue(f32);
+
+impl KeyPanValue {
+    /// Pan offset for key, to be added to master pan
+    pub fn pan_offset(self, key: u8) -> f32 {
+        let position = ((f32::from(key) - CENTER_KEY) / KEY_SPAN).clamp(-1.0, 1.0);
+
+        position * self.0 * 0.5
+    }
+}
+
+impl ParameterValue for KeyPanValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim();
+
+        if let Some(percent) = text.strip_suffix('%') {
+            parse_valid_f32(percent, 0.0, 100.0).map(|v| Self(v / 100.0))
+        } else {
+            parse_valid_f32(text, 0.0, 1.0).map(Self)
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.0}%", self.0 * 100.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_pan_offset() {
+        let full = KeyPanValue::new_from_audio(1.0);
+
+        assert_eq!(full.pan_offset(60), 0.0);
+        assert_eq!(full.pan_offset(0), -0.5);
+        assert_eq!(full.pan_offset(127), 0.5);
+        assert_eq!(full.pan_offset(90), 0.25);
+
+        assert_eq!(KeyPanValue::default().pan_offset(0), 0.0);
+        assert_eq!(KeyPanValue::new_from_audio(0.5).pan_offset(120), 0.25);
+    }
+}
@@ -0,0 +1,79 @@
+use compact_str::CompactString;
+
+use super::utils::{format_key, parse_key};
+use super::{ParameterValue, SerializableRepresentation};
+
+/// Lowest key that is played. Notes below it are ignored, e.g. for keyboard
+/// splits across instances.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyRangeLowValue(u8);
+
+/// Highest key that is played. Notes above it are ignored.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyRangeHighValue(u8);
+
+impl Default for KeyRangeHighValue {
+    fn default() -> Self {
+        Self(127)
+    }
+}
+
+macro_rules! impl_key_range_parameter_value {
+    ($struct_name:ident) => {
+        impl ParameterValue for $struct_name {
+            type Value = u8;
+
+            fn new_from_audio(value: Self::Value) -> Self {
+                Self(value.min(127))
+            }
+            /// Parse key name such as "C4" or "F#2" (C4 being MIDI key 60) or
+            /// MIDI key number
+            fn new_from_text(text: &str) -> Option<Self> {
+                parse_key(text).map(Self)
+            }
+            fn get(self) -> Self::Value {
+                self.0
+            }
+            fn new_from_patch(value: f32) -> Self {
+                Self((value.clamp(0.0, 1.0) * 127.0).round() as u8)
+            }
+            fn to_patch(self) -> f32 {
+                f32::from(self.0) / 127.0
+            }
+            fn get_formatted(self) -> CompactString {
+                format_key(self.0)
+            }
+
+            fn get_serializable(&self) -> SerializableRepresentation {
+                SerializableRepresentation::Other(self.get_formatted())
+            }
+        }
+    };
+}
+
+impl_key_range_parameter_value!(KeyRangeLowValue);
+impl_key_range_parameter_value!(KeyRangeHighValue);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_range_text_roundtrip() {
+        for key in 0..128 {
+            let value = KeyRangeLowValue::new_from_audio(key);
+            let text = value.get_formatted();
+
+            assert_eq!(KeyRangeLowValue::new_from_text(&text).unwrap().get(), key);
+            assert_eq!(
+                KeyRangeHighValue::new_from_patch(value.to_patch()).get(),
+                key
+            );
+        }
+
+        assert_eq!(KeyRangeHighValue::new_from_text("c4").unwrap().get(), 60);
+        assert_eq!(KeyRangeHighValue::new_from_text("C-1").unwrap().get(), 0);
+        assert_eq!(KeyRangeHighValue::new_from_text("G9").unwrap().get(), 127);
+        assert!(KeyRangeHighValue::new_from_text("G#9").is_none());
+    }
+}
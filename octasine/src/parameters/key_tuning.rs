@@ -0,0 +1,117 @@
+use compact_str::{format_compact, CompactString};
+
+use super::utils::{parse_frequency, parse_valid_f32};
+
+/// Maximum detuning of a key in either direction, enough to map any key to
+/// the frequency of any other key
+pub const MAX_KEY_DETUNE_CENTS: f32 = 12_700.0;
+
+/// Detuning of a single key in cents relative to twelve-tone equal
+/// temperament. Stored in patch alongside parameters, since parameter
+/// indices don't have room for one parameter per key.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct KeyDetuneValue(f32);
+
+impl KeyDetuneValue {
+    /// Clamp cents to valid range. Non-finite values mean no detuning.
+    pub fn new(cents: f32) -> Self {
+        if cents.is_finite() {
+            Self(cents.clamp(-MAX_KEY_DETUNE_CENTS, MAX_KEY_DETUNE_CENTS))
+        } else {
+            Self(0.0)
+        }
+    }
+
+    /// Accepts cents (e.g., "+7c" or "-12.5") or frequency to map key to,
+    /// either in Hz (e.g., "261.6 Hz") or as key name with optional cent
+    /// offset (e.g., "C4 +5c"). Frequencies are relative to master
+    /// frequency, so they move along with it.
+    pub fn new_from_text(key: u8, text: &str, master_frequency: f64) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+
+        let is_frequency =
+            text.ends_with("hz") || text.starts_with(|c: char| c.is_ascii_alphabetic());
+
+        if is_frequency {
+            let frequency = parse_frequency(&text, 1.0, 100_000.0)?;
+            let equal_temperament_frequency =
+                master_frequency * ((f64::from(key.min(127)) - 69.0) / 12.0).exp2();
+
+            let cents = 1200.0 * (frequency / equal_temperament_frequency).log2();
+
+            cents.is_finite().then(|| Self::new(cents as f32))
+        } else {
+            let cents_text = ["cents", "cent", "ct", "c"]
+                .into_iter()
+                .find_map(|suffix| text.strip_suffix(suffix))
+                .unwrap_or(&text);
+
+            parse_valid_f32(
+                cents_text.trim(),
+                -MAX_KEY_DETUNE_CENTS,
+                MAX_KEY_DETUNE_CENTS,
+            )
+            .map(Self)
+        }
+    }
+
+    pub fn get(self) -> f32 {
+        self.0
+    }
+
+    pub fn get_formatted(self) -> CompactString {
+        // Prevent display of negative zero
+        if self.0.abs() < 0.05 {
+            "0.0c".into()
+        } else {
+            format_compact!("{:+.1}c", self.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_key_detune_from_text() {
+        let parse = |key, text| {
+            KeyDetuneValue::new_from_text(key, text, 440.0)
+                .unwrap()
+                .get()
+        };
+
+        assert_eq!(parse(60, "+7c"), 7.0);
+        assert_eq!(parse(60, "-12.5 cents"), -12.5);
+        assert_eq!(parse(60, "0"), 0.0);
+        assert_eq!(parse(60, "+20000c"), MAX_KEY_DETUNE_CENTS);
+
+        // Frequencies are converted to offsets from key frequency
+        assert!(parse(69, "440 Hz").abs() < 1e-3);
+        assert!((parse(69, "880 Hz") - 1200.0).abs() < 1e-3);
+        assert!((parse(60, "C#4") - 100.0).abs() < 1e-3);
+        assert!((parse(60, "B3 -5c") + 105.0).abs() < 1e-3);
+        assert!(
+            (KeyDetuneValue::new_from_text(69, "440 Hz", 432.0)
+                .unwrap()
+                .get()
+                - 1200.0 * (440.0f32 / 432.0).log2())
+            .abs()
+                < 1e-3
+        );
+
+        assert!(KeyDetuneValue::new_from_text(60, "abc", 440.0).is_none());
+        assert!(KeyDetuneValue::new_from_text(60, "inf", 440.0).is_none());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_key_detune_formatting() {
+        assert_eq!(KeyDetuneValue::default().get_formatted(), "0.0c");
+        assert_eq!(KeyDetuneValue::new(-0.01).get_formatted(), "0.0c");
+        assert_eq!(KeyDetuneValue::new(7.0).get_formatted(), "+7.0c");
+        assert_eq!(KeyDetuneValue::new(-12.56).get_formatted(), "-12.6c");
+        assert_eq!(KeyDetuneValue::new(f32::NAN).get(), 0.0);
+    }
+}
@@ -5,7 +5,7 @@ use super::utils::*;
 use super::ParameterValue;
 use super::SerializableRepresentation;
 
-const LFO_FREQUENCY_FREE_STEPS: [f32; 7] = [1.0 / 16.0, 0.5, 0.9, 1.0, 1.1, 2.0, 16.0];
+const LFO_FREQUENCY_FREE_STEPS: [f32; 7] = [1.0 / 128.0, 0.5, 0.9, 1.0, 1.1, 2.0, 16.0];
 
 #[derive(Debug, Clone, Copy)]
 pub struct LfoFrequencyFreeValue(pub f64);
@@ -1,25 +1,76 @@
-use compact_str::format_compact;
 use compact_str::CompactString;
 
 use super::utils::*;
 use super::ParameterValue;
 use super::SerializableRepresentation;
 
-const LFO_FREQUENCY_RATIO_STEPS: [f32; 9] = [
-    1.0 / 16.0,
-    1.0 / 8.0,
-    1.0 / 4.0,
-    1.0 / 2.0,
-    1.0,
-    2.0,
-    4.0,
-    8.0,
-    16.0,
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LfoFrequencyRatioStep {
+    ratio: f64,
+    /// Corresponding note length when BPM sync is on. Ratio 1.0 means one
+    /// cycle per half note (one cycle per second at 120 BPM).
+    name: &'static str,
+}
+
+impl LfoFrequencyRatioStep {
+    const fn new(ratio: f64, name: &'static str) -> Self {
+        Self { ratio, name }
+    }
+}
+
+/// Straight, dotted (D) and triplet (T) note lengths
+const LFO_FREQUENCY_RATIO_STEPS: &[LfoFrequencyRatioStep] = &[
+    LfoFrequencyRatioStep::new(1.0 / 64.0, "32/1"),
+    LfoFrequencyRatioStep::new(1.0 / 32.0, "16/1"),
+    LfoFrequencyRatioStep::new(1.0 / 16.0, "8/1"),
+    LfoFrequencyRatioStep::new(1.0 / 8.0, "4/1"),
+    LfoFrequencyRatioStep::new(1.0 / 4.0, "2/1"),
+    LfoFrequencyRatioStep::new(1.0 / 3.0, "1/1D"),
+    LfoFrequencyRatioStep::new(1.0 / 2.0, "1/1"),
+    LfoFrequencyRatioStep::new(2.0 / 3.0, "1/2D"),
+    LfoFrequencyRatioStep::new(3.0 / 4.0, "1/1T"),
+    LfoFrequencyRatioStep::new(1.0, "1/2"),
+    LfoFrequencyRatioStep::new(4.0 / 3.0, "1/4D"),
+    LfoFrequencyRatioStep::new(3.0 / 2.0, "1/2T"),
+    LfoFrequencyRatioStep::new(2.0, "1/4"),
+    LfoFrequencyRatioStep::new(8.0 / 3.0, "1/8D"),
+    LfoFrequencyRatioStep::new(3.0, "1/4T"),
+    LfoFrequencyRatioStep::new(4.0, "1/8"),
+    LfoFrequencyRatioStep::new(16.0 / 3.0, "1/16D"),
+    LfoFrequencyRatioStep::new(6.0, "1/8T"),
+    LfoFrequencyRatioStep::new(8.0, "1/16"),
+    LfoFrequencyRatioStep::new(32.0 / 3.0, "1/32D"),
+    LfoFrequencyRatioStep::new(12.0, "1/16T"),
+    LfoFrequencyRatioStep::new(16.0, "1/32"),
+    LfoFrequencyRatioStep::new(24.0, "1/32T"),
 ];
 
 #[derive(Debug, Clone, Copy)]
 pub struct LfoFrequencyRatioValue(pub f64);
 
+impl LfoFrequencyRatioValue {
+    fn step(self) -> LfoFrequencyRatioStep {
+        Self::closest_step(self.0)
+    }
+
+    fn closest_step(ratio: f64) -> LfoFrequencyRatioStep {
+        *LFO_FREQUENCY_RATIO_STEPS
+            .iter()
+            .min_by(|a, b| {
+                let diff_a = (a.ratio - ratio).abs();
+                let diff_b = (b.ratio - ratio).abs();
+
+                diff_a.total_cmp(&diff_b)
+            })
+            .unwrap()
+    }
+
+    /// Create value from step with ratio closest to given one
+    pub fn new_closest_to_value(ratio: f64) -> Self {
+        Self(Self::closest_step(ratio).ratio)
+    }
+}
+
 impl Default for LfoFrequencyRatioValue {
     fn default() -> Self {
         Self(1.0)
@@ -33,29 +84,84 @@ impl ParameterValue for LfoFrequencyRatioValue {
         Self(value)
     }
     fn new_from_text(text: &str) -> Option<Self> {
-        const MIN: f32 = LFO_FREQUENCY_RATIO_STEPS[0];
-        const MAX: f32 = LFO_FREQUENCY_RATIO_STEPS[LFO_FREQUENCY_RATIO_STEPS.len() - 1];
+        let text = text.trim();
 
-        let value = parse_valid_f32(text, MIN, MAX)?;
+        if let Some(step) = LFO_FREQUENCY_RATIO_STEPS
+            .iter()
+            .find(|step| step.name.eq_ignore_ascii_case(text))
+        {
+            return Some(Self(step.ratio));
+        }
 
-        Some(Self(
-            round_to_step(&LFO_FREQUENCY_RATIO_STEPS[..], value).into(),
-        ))
+        // Fractions are note lengths, not ratios, so only accept known ones
+        if text.contains('/') {
+            return None;
+        }
+
+        let min = LFO_FREQUENCY_RATIO_STEPS[0].ratio;
+        let max = LFO_FREQUENCY_RATIO_STEPS[LFO_FREQUENCY_RATIO_STEPS.len() - 1].ratio;
+
+        let value = parse_valid_f64(text, min, max)?;
+
+        Some(Self::new_closest_to_value(value))
     }
     fn get(self) -> Self::Value {
         self.0
     }
     fn new_from_patch(value: f32) -> Self {
-        Self(map_patch_value_to_step(&LFO_FREQUENCY_RATIO_STEPS, value) as f64)
+        Self(map_patch_value_to_step(LFO_FREQUENCY_RATIO_STEPS, value).ratio)
     }
     fn to_patch(self) -> f32 {
-        map_step_to_patch_value(&LFO_FREQUENCY_RATIO_STEPS, self.0 as f32)
+        map_step_to_patch_value(LFO_FREQUENCY_RATIO_STEPS, self.step())
     }
     fn get_formatted(self) -> CompactString {
-        format_compact!("{:.04}", self.0)
+        self.step().name.into()
     }
 
     fn get_serializable(&self) -> SerializableRepresentation {
         SerializableRepresentation::Float(self.0)
     }
+
+    fn get_text_choices() -> Option<Vec<CompactString>> {
+        Some(
+            LFO_FREQUENCY_RATIO_STEPS
+                .iter()
+                .map(|step| step.name.into())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lfo_frequency_ratio_steps_sorted() {
+        for pair in LFO_FREQUENCY_RATIO_STEPS.windows(2) {
+            assert!(pair[0].ratio < pair[1].ratio);
+        }
+    }
+
+    #[test]
+    fn test_lfo_frequency_ratio_from_text() {
+        for step in LFO_FREQUENCY_RATIO_STEPS {
+            let value = LfoFrequencyRatioValue::new_from_text(step.name).unwrap();
+
+            assert_eq!(value.get(), step.ratio);
+            assert_eq!(value.get_formatted(), step.name);
+        }
+
+        assert_eq!(
+            LfoFrequencyRatioValue::new_from_text("1/8d").unwrap().get(),
+            8.0 / 3.0
+        );
+        assert_eq!(
+            LfoFrequencyRatioValue::new_from_text("2.1")
+                .unwrap()
+                .get_formatted(),
+            "1/4"
+        );
+        assert!(LfoFrequencyRatioValue::new_from_text("1/3X").is_none());
+    }
 }
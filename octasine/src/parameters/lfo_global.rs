@@ -0,0 +1,46 @@
+use compact_str::CompactString;
+
+use super::{ParameterValue, SerializableRepresentation};
+
+/// Use a single LFO instance shared by all voices instead of one per voice
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LfoGlobalValue(pub bool);
+
+impl ParameterValue for LfoGlobalValue {
+    type Value = bool;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_ref() {
+            "true" | "on" => Some(Self(true)),
+            "false" | "off" => Some(Self(false)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value > 0.5)
+    }
+    fn to_patch(self) -> f32 {
+        if self.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+    fn get_formatted(self) -> CompactString {
+        if self.0 {
+            "ON".into()
+        } else {
+            "OFF".into()
+        }
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -18,7 +18,7 @@ impl ParameterValue for LfoKeySyncValue {
         Self(value)
     }
     fn new_from_text(text: &str) -> Option<Self> {
-        match text.to_lowercase().as_ref() {
+        match text.trim().to_lowercase().as_ref() {
             "true" | "on" => Some(Self(true)),
             "false" | "off" => Some(Self(false)),
             _ => None,
@@ -0,0 +1,36 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{utils::parse_valid_f32, ParameterValue, SerializableRepresentation};
+
+/// How much mod wheel (MIDI CC 1) position scales LFO amount. Zero (the
+/// default) means that mod wheel has no effect, while one means that amount
+/// goes from zero with mod wheel down to full with mod wheel up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LfoModWheelDepthValue(f32);
+
+impl ParameterValue for LfoModWheelDepthValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.04}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
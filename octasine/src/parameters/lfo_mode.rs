@@ -4,13 +4,38 @@ use super::utils::*;
 use super::ParameterValue;
 use super::SerializableRepresentation;
 
-const LFO_MODE_STEPS: [LfoMode; 2] = [LfoMode::Forever, LfoMode::Once];
+const LFO_MODE_STEPS: [LfoMode; 5] = [
+    LfoMode::Forever,
+    LfoMode::Once,
+    LfoMode::FirstNote,
+    LfoMode::EverySecondNote,
+    LfoMode::EveryFourthNote,
+];
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
 pub enum LfoMode {
     Once,
     #[default]
     Forever,
+    /// Loop, but only retrigger when no voices are active
+    FirstNote,
+    /// Loop, retriggering on every second note
+    EverySecondNote,
+    /// Loop, retriggering on every fourth note
+    EveryFourthNote,
+}
+
+impl LfoMode {
+    /// Whether LFO should be retriggered by note number `note_index`,
+    /// counted from last note pressed when no voices were active
+    pub fn retrigger(self, note_index: u8) -> bool {
+        match self {
+            Self::Once | Self::Forever => true,
+            Self::FirstNote => note_index == 0,
+            Self::EverySecondNote => note_index.is_multiple_of(2),
+            Self::EveryFourthNote => note_index.is_multiple_of(4),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -23,9 +48,12 @@ impl ParameterValue for LfoModeValue {
         Self(value)
     }
     fn new_from_text(text: &str) -> Option<Self> {
-        match text.to_lowercase().as_ref() {
+        match text.trim().to_lowercase().as_ref() {
             "once" => Some(Self(LfoMode::Once)),
-            "forever" => Some(Self(LfoMode::Forever)),
+            "loop" | "forever" => Some(Self(LfoMode::Forever)),
+            "first" | "first note" => Some(Self(LfoMode::FirstNote)),
+            "2nd" | "every 2nd" | "every second note" => Some(Self(LfoMode::EverySecondNote)),
+            "4th" | "every 4th" | "every fourth note" => Some(Self(LfoMode::EveryFourthNote)),
             _ => None,
         }
     }
@@ -42,6 +70,9 @@ impl ParameterValue for LfoModeValue {
         match self.0 {
             LfoMode::Once => "ONCE".into(),
             LfoMode::Forever => "LOOP".into(),
+            LfoMode::FirstNote => "FIRST".into(),
+            LfoMode::EverySecondNote => "EVERY 2ND".into(),
+            LfoMode::EveryFourthNote => "EVERY 4TH".into(),
         }
     }
 
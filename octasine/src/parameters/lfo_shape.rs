@@ -1,4 +1,4 @@
-use std::f32::consts::TAU;
+use std::f64::consts::TAU;
 
 use compact_str::CompactString;
 
@@ -148,5 +148,5 @@ fn lfo_square(phase: Phase) -> f32 {
 
 /// LFO sine wave
 fn lfo_sine(phase: Phase) -> f32 {
-    ::sleef_trig::Sleef_sinf1_u35purec_range125(phase.0 as f32 * TAU)
+    crate::simd::sin(phase.0 * TAU) as f32
 }
@@ -0,0 +1,78 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{
+        map_audio_to_patch_value_with_steps, map_patch_to_audio_value_with_steps, parse_valid_f32,
+    },
+    ParameterValue, SerializableRepresentation,
+};
+
+/// Milliseconds
+const STEPS: &[f32] = &[0.0, 10.0, 200.0];
+
+/// LFO output slew time in milliseconds
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LfoSmoothingValue(f32);
+
+impl ParameterValue for LfoSmoothingValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, *STEPS.last().unwrap()).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_to_audio_value_with_steps(STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_audio_to_patch_value_with_steps(STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.1} ms", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lfo_smoothing_value() {
+        assert_eq!(LfoSmoothingValue::default().get(), 0.0);
+        assert_eq!(LfoSmoothingValue::default().to_patch(), 0.0);
+        assert_eq!(LfoSmoothingValue::new_from_patch(1.0).get(), 200.0);
+
+        for step in STEPS.iter().copied() {
+            let patch_value = LfoSmoothingValue::new_from_audio(step).to_patch();
+
+            assert_eq!(LfoSmoothingValue::new_from_patch(patch_value).get(), step);
+        }
+
+        assert_eq!(
+            LfoSmoothingValue::new_from_text("10.0 ms").map(|v| v.get()),
+            Some(10.0)
+        );
+        assert_eq!(
+            LfoSmoothingValue::new_from_text("500").map(|v| v.get()),
+            Some(200.0)
+        );
+        assert_eq!(
+            LfoSmoothingValue::new_from_text("-1").map(|v| v.get()),
+            Some(0.0)
+        );
+        assert!(LfoSmoothingValue::new_from_text("slow").is_none());
+        assert_eq!(
+            LfoSmoothingValue::new_from_audio(10.0).get_formatted(),
+            "10.0 ms"
+        );
+    }
+}
@@ -10,6 +10,8 @@ pub const LFO_TARGETS: &[LfoTargetParameter] = &[
     LfoTargetParameter::new(Parameter::None),
     LfoTargetParameter::new(Parameter::Master(MasterParameter::Volume)),
     LfoTargetParameter::new(Parameter::Master(MasterParameter::Frequency)),
+    LfoTargetParameter::new(Parameter::Master(MasterParameter::Pan)),
+    LfoTargetParameter::new(Parameter::Master(MasterParameter::GlideTime)),
     LfoTargetParameter::new(Parameter::Operator(0, OperatorParameter::Volume)),
     LfoTargetParameter::new(Parameter::Operator(0, OperatorParameter::Panning)),
     LfoTargetParameter::new(Parameter::Operator(0, OperatorParameter::MixOut)),
@@ -17,6 +19,9 @@ pub const LFO_TARGETS: &[LfoTargetParameter] = &[
     LfoTargetParameter::new(Parameter::Operator(0, OperatorParameter::FrequencyRatio)),
     LfoTargetParameter::new(Parameter::Operator(0, OperatorParameter::FrequencyFree)),
     LfoTargetParameter::new(Parameter::Operator(0, OperatorParameter::FrequencyFine)),
+    LfoTargetParameter::new(Parameter::Operator(0, OperatorParameter::AttackDuration)),
+    LfoTargetParameter::new(Parameter::Operator(0, OperatorParameter::DecayDuration)),
+    LfoTargetParameter::new(Parameter::Operator(0, OperatorParameter::ReleaseDuration)),
     LfoTargetParameter::new(Parameter::Operator(1, OperatorParameter::Volume)),
     LfoTargetParameter::new(Parameter::Operator(1, OperatorParameter::Panning)),
     LfoTargetParameter::new(Parameter::Operator(1, OperatorParameter::MixOut)),
@@ -25,6 +30,9 @@ pub const LFO_TARGETS: &[LfoTargetParameter] = &[
     LfoTargetParameter::new(Parameter::Operator(1, OperatorParameter::FrequencyRatio)),
     LfoTargetParameter::new(Parameter::Operator(1, OperatorParameter::FrequencyFree)),
     LfoTargetParameter::new(Parameter::Operator(1, OperatorParameter::FrequencyFine)),
+    LfoTargetParameter::new(Parameter::Operator(1, OperatorParameter::AttackDuration)),
+    LfoTargetParameter::new(Parameter::Operator(1, OperatorParameter::DecayDuration)),
+    LfoTargetParameter::new(Parameter::Operator(1, OperatorParameter::ReleaseDuration)),
     LfoTargetParameter::new(Parameter::Operator(2, OperatorParameter::Volume)),
     LfoTargetParameter::new(Parameter::Operator(2, OperatorParameter::Panning)),
     LfoTargetParameter::new(Parameter::Operator(2, OperatorParameter::MixOut)),
@@ -33,6 +41,9 @@ pub const LFO_TARGETS: &[LfoTargetParameter] = &[
     LfoTargetParameter::new(Parameter::Operator(2, OperatorParameter::FrequencyRatio)),
     LfoTargetParameter::new(Parameter::Operator(2, OperatorParameter::FrequencyFree)),
     LfoTargetParameter::new(Parameter::Operator(2, OperatorParameter::FrequencyFine)),
+    LfoTargetParameter::new(Parameter::Operator(2, OperatorParameter::AttackDuration)),
+    LfoTargetParameter::new(Parameter::Operator(2, OperatorParameter::DecayDuration)),
+    LfoTargetParameter::new(Parameter::Operator(2, OperatorParameter::ReleaseDuration)),
     LfoTargetParameter::new(Parameter::Operator(3, OperatorParameter::Volume)),
     LfoTargetParameter::new(Parameter::Operator(3, OperatorParameter::Panning)),
     LfoTargetParameter::new(Parameter::Operator(3, OperatorParameter::MixOut)),
@@ -41,6 +52,9 @@ pub const LFO_TARGETS: &[LfoTargetParameter] = &[
     LfoTargetParameter::new(Parameter::Operator(3, OperatorParameter::FrequencyRatio)),
     LfoTargetParameter::new(Parameter::Operator(3, OperatorParameter::FrequencyFree)),
     LfoTargetParameter::new(Parameter::Operator(3, OperatorParameter::FrequencyFine)),
+    LfoTargetParameter::new(Parameter::Operator(3, OperatorParameter::AttackDuration)),
+    LfoTargetParameter::new(Parameter::Operator(3, OperatorParameter::DecayDuration)),
+    LfoTargetParameter::new(Parameter::Operator(3, OperatorParameter::ReleaseDuration)),
     LfoTargetParameter::new(Parameter::Lfo(0, LfoParameter::Shape)),
     LfoTargetParameter::new(Parameter::Lfo(0, LfoParameter::Amount)),
     LfoTargetParameter::new(Parameter::Lfo(0, LfoParameter::FrequencyRatio)),
@@ -57,9 +71,9 @@ pub const LFO_TARGETS: &[LfoTargetParameter] = &[
 
 pub fn get_lfo_target_parameters(lfo_index: usize) -> &'static [LfoTargetParameter] {
     let end = match lfo_index {
-        0 => 34,
-        1 => 38,
-        2 => 42,
+        0 => 48,
+        1 => 52,
+        2 => 56,
         3 => LFO_TARGETS.len(),
         _ => unreachable!(),
     };
@@ -67,6 +81,16 @@ pub fn get_lfo_target_parameters(lfo_index: usize) -> &'static [LfoTargetParamet
     &LFO_TARGETS[..end]
 }
 
+/// Find target by (case-insensitive) parameter name
+pub fn parse_lfo_target(targets: &[LfoTargetParameter], text: &str) -> Option<LfoTargetParameter> {
+    let text = text.trim();
+
+    targets
+        .iter()
+        .find(|target| target.parameter().name().eq_ignore_ascii_case(text))
+        .copied()
+}
+
 /// Parameter with index stored for performance reasons
 #[derive(Debug, Clone, Copy)]
 pub struct LfoTargetParameter {
@@ -115,8 +139,8 @@ impl ParameterValue for Lfo1TargetParameterValue {
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
-    fn new_from_text(_text: &str) -> Option<Self> {
-        None
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_lfo_target(get_lfo_target_parameters(0), text).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -151,8 +175,8 @@ impl ParameterValue for Lfo2TargetParameterValue {
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
-    fn new_from_text(_text: &str) -> Option<Self> {
-        None
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_lfo_target(get_lfo_target_parameters(1), text).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -187,8 +211,8 @@ impl ParameterValue for Lfo3TargetParameterValue {
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
-    fn new_from_text(_text: &str) -> Option<Self> {
-        None
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_lfo_target(get_lfo_target_parameters(2), text).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -223,8 +247,8 @@ impl ParameterValue for Lfo4TargetParameterValue {
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
-    fn new_from_text(_text: &str) -> Option<Self> {
-        None
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_lfo_target(get_lfo_target_parameters(3), text).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -0,0 +1,41 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{utils::parse_valid_f32, ParameterValue, SerializableRepresentation};
+
+/// Depth of additional LFO target, relative to LFO amount. Negative values
+/// invert LFO output for this target.
+#[derive(Debug, Clone, Copy)]
+pub struct LfoTargetDepthValue(f32);
+
+impl Default for LfoTargetDepthValue {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl ParameterValue for LfoTargetDepthValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, -1.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value * 2.0 - 1.0)
+    }
+    fn to_patch(self) -> f32 {
+        (self.0 + 1.0) * 0.5
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:+.04}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
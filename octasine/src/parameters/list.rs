@@ -117,6 +117,119 @@ pub const PARAMETERS: &[Parameter] = &[
     Parameter::Master(MasterParameter::GlideBpmSync),
     Parameter::Master(MasterParameter::GlideMode),
     Parameter::Master(MasterParameter::GlideRetrigger),
+    Parameter::Master(MasterParameter::Pan),
+    Parameter::Master(MasterParameter::OutputMode),
+    Parameter::Master(MasterParameter::DcBlocker),
+    Parameter::Operator(0, OperatorParameter::FeedbackTone),
+    Parameter::Operator(1, OperatorParameter::FeedbackTone),
+    Parameter::Operator(2, OperatorParameter::FeedbackTone),
+    Parameter::Operator(3, OperatorParameter::FeedbackTone),
+    Parameter::Master(MasterParameter::ModOutScaling),
+    Parameter::Master(MasterParameter::VelocityModTarget),
+    Parameter::Master(MasterParameter::VelocityModAmount),
+    Parameter::Lfo(0, LfoParameter::Global),
+    Parameter::Lfo(1, LfoParameter::Global),
+    Parameter::Lfo(2, LfoParameter::Global),
+    Parameter::Lfo(3, LfoParameter::Global),
+    Parameter::Lfo(0, LfoParameter::Smoothing),
+    Parameter::Lfo(1, LfoParameter::Smoothing),
+    Parameter::Lfo(2, LfoParameter::Smoothing),
+    Parameter::Lfo(3, LfoParameter::Smoothing),
+    Parameter::Master(MasterParameter::Scale),
+    Parameter::Master(MasterParameter::ScaleRoot),
+    Parameter::Master(MasterParameter::MonoNotePriority),
+    Parameter::Operator(0, OperatorParameter::OutputBus),
+    Parameter::Operator(1, OperatorParameter::OutputBus),
+    Parameter::Operator(2, OperatorParameter::OutputBus),
+    Parameter::Operator(3, OperatorParameter::OutputBus),
+    Parameter::Operator(0, OperatorParameter::KeyScalingBreakpoint),
+    Parameter::Operator(0, OperatorParameter::KeyScalingLeftDepth),
+    Parameter::Operator(0, OperatorParameter::KeyScalingRightDepth),
+    Parameter::Operator(0, OperatorParameter::KeyScalingLeftCurve),
+    Parameter::Operator(0, OperatorParameter::KeyScalingRightCurve),
+    Parameter::Operator(1, OperatorParameter::KeyScalingBreakpoint),
+    Parameter::Operator(1, OperatorParameter::KeyScalingLeftDepth),
+    Parameter::Operator(1, OperatorParameter::KeyScalingRightDepth),
+    Parameter::Operator(1, OperatorParameter::KeyScalingLeftCurve),
+    Parameter::Operator(1, OperatorParameter::KeyScalingRightCurve),
+    Parameter::Operator(2, OperatorParameter::KeyScalingBreakpoint),
+    Parameter::Operator(2, OperatorParameter::KeyScalingLeftDepth),
+    Parameter::Operator(2, OperatorParameter::KeyScalingRightDepth),
+    Parameter::Operator(2, OperatorParameter::KeyScalingLeftCurve),
+    Parameter::Operator(2, OperatorParameter::KeyScalingRightCurve),
+    Parameter::Operator(3, OperatorParameter::KeyScalingBreakpoint),
+    Parameter::Operator(3, OperatorParameter::KeyScalingLeftDepth),
+    Parameter::Operator(3, OperatorParameter::KeyScalingRightDepth),
+    Parameter::Operator(3, OperatorParameter::KeyScalingLeftCurve),
+    Parameter::Operator(3, OperatorParameter::KeyScalingRightCurve),
+    Parameter::Master(MasterParameter::UnisonVoices),
+    Parameter::Master(MasterParameter::UnisonDetune),
+    Parameter::Master(MasterParameter::UnisonSpread),
+    Parameter::Operator(0, OperatorParameter::VelocitySensitivityAttack),
+    Parameter::Operator(1, OperatorParameter::VelocitySensitivityAttack),
+    Parameter::Operator(2, OperatorParameter::VelocitySensitivityAttack),
+    Parameter::Operator(3, OperatorParameter::VelocitySensitivityAttack),
+    Parameter::Master(MasterParameter::KeyRangeLow),
+    Parameter::Master(MasterParameter::KeyRangeHigh),
+    Parameter::Master(MasterParameter::AftertouchVibratoDepth),
+    Parameter::Master(MasterParameter::AftertouchVibratoRate),
+    Parameter::Operator(0, OperatorParameter::ModOut),
+    Parameter::Operator(0, OperatorParameter::BackwardModTargets),
+    Parameter::Operator(1, OperatorParameter::BackwardModTargets),
+    Parameter::Operator(2, OperatorParameter::BackwardModTargets),
+    Parameter::Operator(0, OperatorParameter::PanningMode),
+    Parameter::Operator(1, OperatorParameter::PanningMode),
+    Parameter::Operator(2, OperatorParameter::PanningMode),
+    Parameter::Operator(3, OperatorParameter::PanningMode),
+    Parameter::Lfo(0, LfoParameter::Target2),
+    Parameter::Lfo(0, LfoParameter::Target2Depth),
+    Parameter::Lfo(0, LfoParameter::Target3),
+    Parameter::Lfo(0, LfoParameter::Target3Depth),
+    Parameter::Lfo(0, LfoParameter::Target4),
+    Parameter::Lfo(0, LfoParameter::Target4Depth),
+    Parameter::Lfo(1, LfoParameter::Target2),
+    Parameter::Lfo(1, LfoParameter::Target2Depth),
+    Parameter::Lfo(1, LfoParameter::Target3),
+    Parameter::Lfo(1, LfoParameter::Target3Depth),
+    Parameter::Lfo(1, LfoParameter::Target4),
+    Parameter::Lfo(1, LfoParameter::Target4Depth),
+    Parameter::Lfo(2, LfoParameter::Target2),
+    Parameter::Lfo(2, LfoParameter::Target2Depth),
+    Parameter::Lfo(2, LfoParameter::Target3),
+    Parameter::Lfo(2, LfoParameter::Target3Depth),
+    Parameter::Lfo(2, LfoParameter::Target4),
+    Parameter::Lfo(2, LfoParameter::Target4Depth),
+    Parameter::Lfo(3, LfoParameter::Target2),
+    Parameter::Lfo(3, LfoParameter::Target2Depth),
+    Parameter::Lfo(3, LfoParameter::Target3),
+    Parameter::Lfo(3, LfoParameter::Target3Depth),
+    Parameter::Lfo(3, LfoParameter::Target4),
+    Parameter::Lfo(3, LfoParameter::Target4Depth),
+    Parameter::Master(MasterParameter::Bypass),
+    Parameter::Operator(0, OperatorParameter::PanningLfoMode),
+    Parameter::Operator(1, OperatorParameter::PanningLfoMode),
+    Parameter::Operator(2, OperatorParameter::PanningLfoMode),
+    Parameter::Operator(3, OperatorParameter::PanningLfoMode),
+    Parameter::Operator(0, OperatorParameter::VelocitySensitivityMixOut),
+    Parameter::Operator(1, OperatorParameter::VelocitySensitivityMixOut),
+    Parameter::Operator(2, OperatorParameter::VelocitySensitivityMixOut),
+    Parameter::Operator(3, OperatorParameter::VelocitySensitivityMixOut),
+    Parameter::Master(MasterParameter::EqLowGain),
+    Parameter::Master(MasterParameter::EqLowFrequency),
+    Parameter::Master(MasterParameter::EqMidGain),
+    Parameter::Master(MasterParameter::EqMidFrequency),
+    Parameter::Master(MasterParameter::EqHighGain),
+    Parameter::Master(MasterParameter::EqHighFrequency),
+    Parameter::Master(MasterParameter::EqBypass),
+    Parameter::Master(MasterParameter::EffectOrder),
+    Parameter::Master(MasterParameter::KeyPan),
+    Parameter::Master(MasterParameter::ReleaseVelocitySensitivity),
+    Parameter::Master(MasterParameter::GainCompensation),
+    Parameter::Master(MasterParameter::PitchBendSmoothing),
+    Parameter::Lfo(0, LfoParameter::ModWheelDepth),
+    Parameter::Lfo(1, LfoParameter::ModWheelDepth),
+    Parameter::Lfo(2, LfoParameter::ModWheelDepth),
+    Parameter::Lfo(3, LfoParameter::ModWheelDepth),
 ];
 
 /// Parameter enum used to abstract over parameter indices
@@ -142,6 +255,44 @@ pub enum MasterParameter {
     GlideBpmSync,
     GlideMode,
     GlideRetrigger,
+    Pan,
+    OutputMode,
+    DcBlocker,
+    ModOutScaling,
+    VelocityModTarget,
+    VelocityModAmount,
+    Scale,
+    ScaleRoot,
+    MonoNotePriority,
+    UnisonVoices,
+    UnisonDetune,
+    UnisonSpread,
+    KeyRangeLow,
+    KeyRangeHigh,
+    AftertouchVibratoDepth,
+    AftertouchVibratoRate,
+    Bypass,
+    /// Master EQ low shelf gain
+    EqLowGain,
+    EqLowFrequency,
+    /// Master EQ peak band gain
+    EqMidGain,
+    EqMidFrequency,
+    /// Master EQ high shelf gain
+    EqHighGain,
+    EqHighFrequency,
+    EqBypass,
+    /// Processing order of master EQ and DC blocker
+    EffectOrder,
+    /// Voice panning by key
+    KeyPan,
+    /// Release duration sensitivity to note-off velocity
+    ReleaseVelocitySensitivity,
+    /// Compensate estimated patch loudness to reduce level jumps between
+    /// patches
+    GainCompensation,
+    /// Time constant of pitch bend smoothing
+    PitchBendSmoothing,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -164,6 +315,25 @@ pub enum OperatorParameter {
     EnvelopeLockGroup,
     VelocitySensitivityModOut,
     VelocitySensitivityFeedback,
+    VelocitySensitivityAttack,
+    FeedbackTone,
+    OutputBus,
+    KeyScalingBreakpoint,
+    KeyScalingLeftDepth,
+    KeyScalingRightDepth,
+    KeyScalingLeftCurve,
+    KeyScalingRightCurve,
+    /// Modulation targets with higher index, which receive modulation
+    /// delayed by one sample. Not available for operator 4.
+    BackwardModTargets,
+    /// Pan in left/right or mid/side space
+    PanningMode,
+    /// Apply LFO panning modulation normally or spread it with opposite
+    /// signs to left and right channels
+    PanningLfoMode,
+    /// Velocity sensitivity of mix out, independent of master volume
+    /// velocity sensitivity
+    VelocitySensitivityMixOut,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -178,4 +348,17 @@ pub enum LfoParameter {
     Active,
     /// Sync LFO phase to key presses. If turned off, start at random phase
     KeySync,
+    /// Share a single LFO between all voices
+    Global,
+    /// Output slew time, e.g., to soften square and S&H shapes
+    Smoothing,
+    /// Additional targets, with depth relative to amount
+    Target2,
+    Target2Depth,
+    Target3,
+    Target3Depth,
+    Target4,
+    Target4Depth,
+    /// How much mod wheel position scales amount
+    ModWheelDepth,
 }
@@ -0,0 +1,221 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{format_linear_as_db, parse_db_as_linear, parse_frequency},
+    ParameterValue, SerializableRepresentation,
+};
+
+/// Maximum boost or cut of master EQ bands in dB
+const MAX_GAIN_DB: f32 = 15.0;
+
+/// Boost or cut of master EQ band as linear amplitude factor. Patch values
+/// are mapped linearly to decibels, with 0.5 meaning 0 dB.
+#[derive(Debug, Clone, Copy)]
+pub struct MasterEqGainValue(f32);
+
+impl MasterEqGainValue {
+    fn min_linear() -> f32 {
+        10.0f32.powf(-MAX_GAIN_DB / 20.0)
+    }
+    fn max_linear() -> f32 {
+        10.0f32.powf(MAX_GAIN_DB / 20.0)
+    }
+}
+
+impl Default for MasterEqGainValue {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl ParameterValue for MasterEqGainValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_db_as_linear(text, Self::min_linear(), Self::max_linear()).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        let db = (value.clamp(0.0, 1.0) * 2.0 - 1.0) * MAX_GAIN_DB;
+
+        // Keep 0 dB exact, so that EQ can be skipped
+        if db == 0.0 {
+            Self(1.0)
+        } else {
+            Self(10.0f32.powf(db / 20.0))
+        }
+    }
+    fn to_patch(self) -> f32 {
+        if self.0 == 1.0 {
+            0.5
+        } else {
+            let db = 20.0 * self.0.max(f32::MIN_POSITIVE).log10();
+
+            ((db / MAX_GAIN_DB + 1.0) / 2.0).clamp(0.0, 1.0)
+        }
+    }
+    fn get_formatted(self) -> CompactString {
+        format_linear_as_db(self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Bypass master EQ, keeping band settings
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MasterEqBypassValue(bool);
+
+impl ParameterValue for MasterEqBypassValue {
+    type Value = bool;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "off" => Some(Self(false)),
+            "on" => Some(Self(true)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value > 0.5)
+    }
+    fn to_patch(self) -> f32 {
+        if self.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", if self.0 { "ON" } else { "OFF" })
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+/// Corner frequency of master EQ low shelf in Hz
+#[derive(Debug, Clone, Copy)]
+pub struct MasterEqLowFrequencyValue(f32);
+
+impl Default for MasterEqLowFrequencyValue {
+    fn default() -> Self {
+        Self(150.0)
+    }
+}
+
+/// Center frequency of master EQ peak band in Hz
+#[derive(Debug, Clone, Copy)]
+pub struct MasterEqMidFrequencyValue(f32);
+
+impl Default for MasterEqMidFrequencyValue {
+    fn default() -> Self {
+        Self(1000.0)
+    }
+}
+
+/// Corner frequency of master EQ high shelf in Hz
+#[derive(Debug, Clone, Copy)]
+pub struct MasterEqHighFrequencyValue(f32);
+
+impl Default for MasterEqHighFrequencyValue {
+    fn default() -> Self {
+        Self(6000.0)
+    }
+}
+
+/// Frequencies map exponentially to patch values, so that each octave
+/// takes up the same knob range
+macro_rules! impl_master_eq_frequency_parameter_value {
+    ($struct_name:ident, $min:expr, $max:expr) => {
+        impl $struct_name {
+            const MIN: f32 = $min;
+            const MAX: f32 = $max;
+        }
+
+        impl ParameterValue for $struct_name {
+            type Value = f32;
+
+            fn new_from_audio(value: Self::Value) -> Self {
+                Self(value)
+            }
+            fn new_from_text(text: &str) -> Option<Self> {
+                parse_frequency(text, Self::MIN.into(), Self::MAX.into()).map(|v| Self(v as f32))
+            }
+            fn get(self) -> Self::Value {
+                self.0
+            }
+            fn new_from_patch(value: f32) -> Self {
+                let value = value.clamp(0.0, 1.0);
+
+                Self(Self::MIN * (Self::MAX / Self::MIN).powf(value))
+            }
+            fn to_patch(self) -> f32 {
+                let value = (self.0 / Self::MIN).ln() / (Self::MAX / Self::MIN).ln();
+
+                value.clamp(0.0, 1.0)
+            }
+            fn get_formatted(self) -> CompactString {
+                format_compact!("{:.0} Hz", self.0)
+            }
+
+            fn get_serializable(&self) -> SerializableRepresentation {
+                SerializableRepresentation::Float(self.0.into())
+            }
+        }
+    };
+}
+
+impl_master_eq_frequency_parameter_value!(MasterEqLowFrequencyValue, 20.0, 1000.0);
+impl_master_eq_frequency_parameter_value!(MasterEqMidFrequencyValue, 100.0, 10_000.0);
+impl_master_eq_frequency_parameter_value!(MasterEqHighFrequencyValue, 1000.0, 20_000.0);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_master_eq_patch_roundtrip() {
+        for i in 0..=100 {
+            let patch_value = i as f32 / 100.0;
+
+            let roundtrip = MasterEqGainValue::new_from_patch(patch_value).to_patch();
+            assert!((roundtrip - patch_value).abs() < 1e-5);
+
+            let roundtrip = MasterEqMidFrequencyValue::new_from_patch(patch_value).to_patch();
+            assert!((roundtrip - patch_value).abs() < 1e-5);
+        }
+
+        assert_eq!(MasterEqGainValue::default().to_patch(), 0.5);
+        assert_eq!(MasterEqGainValue::new_from_patch(0.5).get(), 1.0);
+        assert_eq!(
+            MasterEqGainValue::new_from_text("-6 dB")
+                .unwrap()
+                .get_formatted(),
+            "-6.00 dB"
+        );
+        assert_eq!(
+            MasterEqHighFrequencyValue::new_from_patch(1.0).get(),
+            20_000.0
+        );
+        assert_eq!(
+            MasterEqLowFrequencyValue::new_from_text("10 Hz")
+                .unwrap()
+                .get(),
+            20.0
+        );
+    }
+}
@@ -28,7 +28,7 @@ impl ParameterValue for MasterFrequencyValue {
         const MIN: f32 = MASTER_FREQUENCY_STEPS[0];
         const MAX: f32 = MASTER_FREQUENCY_STEPS[MASTER_FREQUENCY_STEPS.len() - 1];
 
-        parse_valid_f32(text, MIN, MAX).map(|v| Self(v.into()))
+        parse_frequency(text, MIN.into(), MAX.into()).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -0,0 +1,74 @@
+use std::f32::consts::{FRAC_PI_2, SQRT_2};
+
+use compact_str::CompactString;
+
+use super::{OperatorPanningValue, ParameterValue, SerializableRepresentation};
+
+#[derive(Debug, Clone, Copy)]
+pub struct MasterPanValue(f32);
+
+impl MasterPanValue {
+    /// Constant power panning, normalized so that both channels have unity
+    /// gain when centered
+    pub fn calculate_left_and_right(&self) -> [f32; 2] {
+        let pan_phase = self.0 * FRAC_PI_2;
+
+        [
+            crate::simd::cos(pan_phase.into()) as f32 * SQRT_2,
+            crate::simd::sin(pan_phase.into()) as f32 * SQRT_2,
+        ]
+    }
+}
+
+impl Default for MasterPanValue {
+    fn default() -> Self {
+        Self(0.5)
+    }
+}
+
+impl ParameterValue for MasterPanValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        OperatorPanningValue::new_from_text(text).map(|v| Self(v.get()))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        OperatorPanningValue::new_from_audio(self.0).get_formatted()
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_calculate_left_and_right() {
+        let [l, r] = MasterPanValue::default().calculate_left_and_right();
+
+        assert_approx_eq!(l, 1.0, 0.001);
+        assert_approx_eq!(r, 1.0, 0.001);
+
+        let [l, r] = MasterPanValue::new_from_patch(0.0).calculate_left_and_right();
+
+        assert_approx_eq!(l, SQRT_2, 0.001);
+        assert_approx_eq!(r, 0.0, 0.001);
+    }
+}
@@ -1,10 +1,31 @@
-use compact_str::{format_compact, CompactString};
+use compact_str::CompactString;
 
-use super::{utils::parse_valid_f32, ParameterValue, SerializableRepresentation};
+use super::{
+    utils::{format_linear_as_db, parse_db_as_linear},
+    ParameterValue, SerializableRepresentation,
+};
 
+/// Lowest master volume above silence in dB
+const MIN_DB: f32 = -60.0;
+/// Highest master volume in dB
+const MAX_DB: f32 = 6.0;
+
+/// Master volume as linear amplitude factor. Patch values are mapped
+/// linearly to decibels, with patch value zero meaning silence.
 #[derive(Debug, Clone, Copy)]
 pub struct MasterVolumeValue(f32);
 
+impl MasterVolumeValue {
+    fn max_linear() -> f32 {
+        db_to_linear(MAX_DB)
+    }
+
+    /// Patch value mapping used before master volume was calibrated in dB
+    pub fn from_previous_patch_mapping(value: f32) -> Self {
+        Self((value * 2.0).min(Self::max_linear()))
+    }
+}
+
 impl Default for MasterVolumeValue {
     fn default() -> Self {
         Self(1.0)
@@ -18,22 +39,60 @@ impl ParameterValue for MasterVolumeValue {
         Self(value)
     }
     fn new_from_text(text: &str) -> Option<Self> {
-        parse_valid_f32(text, 0.0, 2.0).map(Self)
+        parse_db_as_linear(text, 0.0, Self::max_linear()).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
     }
     fn new_from_patch(value: f32) -> Self {
-        Self(value * 2.0)
+        if value <= 0.0 {
+            Self(0.0)
+        } else {
+            Self(db_to_linear(MIN_DB + value.min(1.0) * (MAX_DB - MIN_DB)))
+        }
     }
     fn to_patch(self) -> f32 {
-        self.0 / 2.0
+        if self.0 <= 0.0 {
+            0.0
+        } else {
+            // Keep lowest volume above silence distinct from patch value zero
+            ((20.0 * self.0.log10() - MIN_DB) / (MAX_DB - MIN_DB)).clamp(f32::MIN_POSITIVE, 1.0)
+        }
     }
     fn get_formatted(self) -> CompactString {
-        format_compact!("{:.2} dB", 20.0 * self.0.log10())
+        format_linear_as_db(self.0)
     }
 
     fn get_serializable(&self) -> SerializableRepresentation {
         SerializableRepresentation::Float(self.0.into())
     }
 }
+
+fn db_to_linear(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_master_volume_db_mapping() {
+        assert_eq!(MasterVolumeValue::new_from_patch(0.0).get(), 0.0);
+        assert_approx_eq!(MasterVolumeValue::new_from_patch(1.0).get(), 1.995262);
+        assert_approx_eq!(MasterVolumeValue::default().to_patch(), 60.0 / 66.0);
+        assert_approx_eq!(
+            MasterVolumeValue::new_from_patch(MasterVolumeValue::default().to_patch()).get(),
+            1.0
+        );
+
+        for i in 0..=100 {
+            let patch_value = i as f32 / 100.0;
+            let roundtrip = MasterVolumeValue::new_from_patch(patch_value).to_patch();
+
+            assert_approx_eq!(roundtrip, patch_value, 0.0001);
+        }
+    }
+}
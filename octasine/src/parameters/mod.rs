@@ -1,34 +1,60 @@
+pub mod aftertouch_vibrato;
+pub mod bypass;
+pub mod dc_blocker;
+pub mod effect_order;
+pub mod gain_compensation;
 pub mod glide_active;
 pub mod glide_bpm_sync;
 pub mod glide_mode;
 pub mod glide_retrigger;
 pub mod glide_time;
+pub mod key_pan;
+pub mod key_range;
+pub mod key_tuning;
 pub mod lfo_active;
 pub mod lfo_amount;
 pub mod lfo_bpm_sync;
 pub mod lfo_frequency_free;
 pub mod lfo_frequency_ratio;
+pub mod lfo_global;
 pub mod lfo_key_sync;
+pub mod lfo_mod_wheel;
 pub mod lfo_mode;
 pub mod lfo_shape;
+pub mod lfo_smoothing;
 pub mod lfo_target;
+pub mod lfo_target_depth;
 pub mod list;
+pub mod master_eq;
 pub mod master_frequency;
+pub mod master_pan;
 pub mod master_pitch_bend_range;
 pub mod master_volume;
+pub mod mod_out_scaling;
+pub mod mono_note_priority;
 pub mod operator_active;
 pub mod operator_envelope;
 pub mod operator_feedback;
+pub mod operator_feedback_tone;
 pub mod operator_frequency_fine;
 pub mod operator_frequency_free;
 pub mod operator_frequency_ratio;
+pub mod operator_key_scaling;
 pub mod operator_mix_out;
 pub mod operator_mod_out;
 pub mod operator_mod_target;
+pub mod operator_output_bus;
 pub mod operator_panning;
+pub mod operator_panning_lfo_mode;
+pub mod operator_panning_mode;
 pub mod operator_volume;
 pub mod operator_wave_type;
+pub mod output_mode;
+pub mod pitch_bend_smoothing;
+pub mod scale_quantization;
+pub mod unison;
 pub mod utils;
+pub mod velocity_mod;
 pub mod velocity_sensitivity;
 pub mod voice_mode;
 
@@ -38,24 +64,36 @@ pub use lfo_amount::LfoAmountValue;
 pub use lfo_bpm_sync::LfoBpmSyncValue;
 pub use lfo_frequency_free::LfoFrequencyFreeValue;
 pub use lfo_frequency_ratio::LfoFrequencyRatioValue;
+pub use lfo_global::LfoGlobalValue;
 pub use lfo_key_sync::LfoKeySyncValue;
+pub use lfo_mod_wheel::LfoModWheelDepthValue;
 pub use lfo_mode::LfoModeValue;
 pub use lfo_shape::LfoShapeValue;
+pub use lfo_smoothing::LfoSmoothingValue;
 pub use lfo_target::*;
+pub use lfo_target_depth::LfoTargetDepthValue;
 pub use list::*;
 pub use master_frequency::MasterFrequencyValue;
+pub use master_pan::MasterPanValue;
 pub use master_pitch_bend_range::{MasterPitchBendRangeDownValue, MasterPitchBendRangeUpValue};
 pub use master_volume::MasterVolumeValue;
 pub use operator_active::OperatorActiveValue;
 pub use operator_envelope::*;
 pub use operator_feedback::OperatorFeedbackValue;
+pub use operator_feedback_tone::OperatorFeedbackToneValue;
 pub use operator_frequency_fine::OperatorFrequencyFineValue;
 pub use operator_frequency_free::OperatorFrequencyFreeValue;
 pub use operator_frequency_ratio::OperatorFrequencyRatioValue;
+pub use operator_key_scaling::{
+    OperatorKeyScalingBreakpointValue, OperatorKeyScalingCurveValue, OperatorKeyScalingDepthValue,
+};
 pub use operator_mix_out::OperatorMixOutValue;
 pub use operator_mod_out::OperatorModOutValue;
 pub use operator_mod_target::*;
+pub use operator_output_bus::OperatorOutputBusValue;
 pub use operator_panning::OperatorPanningValue;
+pub use operator_panning_lfo_mode::OperatorPanningLfoModeValue;
+pub use operator_panning_mode::OperatorPanningModeValue;
 pub use operator_volume::OperatorVolumeValue;
 pub use operator_wave_type::OperatorWaveTypeValue;
 use serde::{Deserialize, Serialize};
@@ -116,6 +154,39 @@ impl Parameter {
             Self::Master(MasterParameter::GlideBpmSync) => "Glide bpm sync".into(),
             Self::Master(MasterParameter::GlideMode) => "Glide mode".into(),
             Self::Master(MasterParameter::GlideRetrigger) => "Glide retrigger".into(),
+            Self::Master(MasterParameter::Pan) => "Master pan".into(),
+            Self::Master(MasterParameter::OutputMode) => "Output mode".into(),
+            Self::Master(MasterParameter::DcBlocker) => "DC blocker".into(),
+            Self::Master(MasterParameter::ModOutScaling) => "Mod out scaling".into(),
+            Self::Master(MasterParameter::VelocityModTarget) => "Velocity mod target".into(),
+            Self::Master(MasterParameter::VelocityModAmount) => "Velocity mod amount".into(),
+            Self::Master(MasterParameter::Scale) => "Scale".into(),
+            Self::Master(MasterParameter::ScaleRoot) => "Scale root".into(),
+            Self::Master(MasterParameter::MonoNotePriority) => "Mono note priority".into(),
+            Self::Master(MasterParameter::UnisonVoices) => "Unison voices".into(),
+            Self::Master(MasterParameter::UnisonDetune) => "Unison detune".into(),
+            Self::Master(MasterParameter::UnisonSpread) => "Unison spread".into(),
+            Self::Master(MasterParameter::KeyRangeLow) => "Key range low".into(),
+            Self::Master(MasterParameter::KeyRangeHigh) => "Key range high".into(),
+            Self::Master(MasterParameter::AftertouchVibratoDepth) => {
+                "Aftertouch vibrato depth".into()
+            }
+            Self::Master(MasterParameter::AftertouchVibratoRate) => {
+                "Aftertouch vibrato rate".into()
+            }
+            Self::Master(MasterParameter::Bypass) => "Bypass".into(),
+            Self::Master(MasterParameter::EqLowGain) => "EQ low gain".into(),
+            Self::Master(MasterParameter::EqLowFrequency) => "EQ low freq".into(),
+            Self::Master(MasterParameter::EqMidGain) => "EQ mid gain".into(),
+            Self::Master(MasterParameter::EqMidFrequency) => "EQ mid freq".into(),
+            Self::Master(MasterParameter::EqHighGain) => "EQ high gain".into(),
+            Self::Master(MasterParameter::EqHighFrequency) => "EQ high freq".into(),
+            Self::Master(MasterParameter::EqBypass) => "EQ bypass".into(),
+            Self::Master(MasterParameter::EffectOrder) => "Effect order".into(),
+            Self::Master(MasterParameter::KeyPan) => "Key pan".into(),
+            Self::Master(MasterParameter::ReleaseVelocitySensitivity) => "Release vel sens".into(),
+            Self::Master(MasterParameter::GainCompensation) => "Gain comp".into(),
+            Self::Master(MasterParameter::PitchBendSmoothing) => "PB smoothing".into(),
             Self::Operator(index, p) => match p {
                 OperatorParameter::Volume => format_compact!("OP {} vol", index + 1),
                 OperatorParameter::Active => format_compact!("OP {} active", index + 1),
@@ -124,6 +195,13 @@ impl Parameter {
                 OperatorParameter::WaveType => format_compact!("OP {} wave", index + 1),
                 OperatorParameter::ModTargets => format_compact!("OP {} target", index + 1),
                 OperatorParameter::ModOut => format_compact!("OP {} mod out", index + 1),
+                OperatorParameter::BackwardModTargets => {
+                    format_compact!("OP {} backward target", index + 1)
+                }
+                OperatorParameter::PanningMode => format_compact!("OP {} pan mode", index + 1),
+                OperatorParameter::PanningLfoMode => {
+                    format_compact!("OP {} pan LFO mode", index + 1)
+                }
                 OperatorParameter::Feedback => format_compact!("OP {} feedback", index + 1),
                 OperatorParameter::FrequencyRatio => format_compact!("OP {} freq ratio", index + 1),
                 OperatorParameter::FrequencyFree => format_compact!("OP {} freq free", index + 1),
@@ -145,6 +223,31 @@ impl Parameter {
                 OperatorParameter::VelocitySensitivityFeedback => {
                     format_compact!("OP {} feedback vs", index + 1)
                 }
+                OperatorParameter::VelocitySensitivityMixOut => {
+                    format_compact!("OP {} mix out vs", index + 1)
+                }
+                OperatorParameter::VelocitySensitivityAttack => {
+                    format_compact!("OP {} attack vs", index + 1)
+                }
+                OperatorParameter::FeedbackTone => {
+                    format_compact!("OP {} feedback tone", index + 1)
+                }
+                OperatorParameter::OutputBus => format_compact!("OP {} output", index + 1),
+                OperatorParameter::KeyScalingBreakpoint => {
+                    format_compact!("OP {} key scale break", index + 1)
+                }
+                OperatorParameter::KeyScalingLeftDepth => {
+                    format_compact!("OP {} key scale L depth", index + 1)
+                }
+                OperatorParameter::KeyScalingRightDepth => {
+                    format_compact!("OP {} key scale R depth", index + 1)
+                }
+                OperatorParameter::KeyScalingLeftCurve => {
+                    format_compact!("OP {} key scale L curve", index + 1)
+                }
+                OperatorParameter::KeyScalingRightCurve => {
+                    format_compact!("OP {} key scale R curve", index + 1)
+                }
             },
             Self::Lfo(index, p) => match p {
                 LfoParameter::Target => format_compact!("LFO {} target", index + 1),
@@ -156,6 +259,15 @@ impl Parameter {
                 LfoParameter::Amount => format_compact!("LFO {} amount", index + 1),
                 LfoParameter::Active => format_compact!("LFO {} active", index + 1),
                 LfoParameter::KeySync => format_compact!("LFO {} key sync", index + 1),
+                LfoParameter::Global => format_compact!("LFO {} global", index + 1),
+                LfoParameter::Smoothing => format_compact!("LFO {} smoothing", index + 1),
+                LfoParameter::Target2 => format_compact!("LFO {} target 2", index + 1),
+                LfoParameter::Target2Depth => format_compact!("LFO {} target 2 depth", index + 1),
+                LfoParameter::Target3 => format_compact!("LFO {} target 3", index + 1),
+                LfoParameter::Target3Depth => format_compact!("LFO {} target 3 depth", index + 1),
+                LfoParameter::Target4 => format_compact!("LFO {} target 4", index + 1),
+                LfoParameter::Target4Depth => format_compact!("LFO {} target 4 depth", index + 1),
+                LfoParameter::ModWheelDepth => format_compact!("LFO {} mod wheel", index + 1),
             },
         }
     }
@@ -197,6 +309,41 @@ impl Parameter {
             Self::Master(MasterParameter::GlideBpmSync) => "Glide bpm sync".into(),
             Self::Master(MasterParameter::GlideMode) => "Glide mode".into(),
             Self::Master(MasterParameter::GlideRetrigger) => "Glide retrigger".into(),
+            Self::Master(MasterParameter::Pan) => "Master pan".into(),
+            Self::Master(MasterParameter::OutputMode) => "Output mode".into(),
+            Self::Master(MasterParameter::DcBlocker) => "DC blocker".into(),
+            Self::Master(MasterParameter::ModOutScaling) => "Mod out scaling".into(),
+            Self::Master(MasterParameter::VelocityModTarget) => "Velocity mod target".into(),
+            Self::Master(MasterParameter::VelocityModAmount) => "Velocity mod amount".into(),
+            Self::Master(MasterParameter::Scale) => "Scale".into(),
+            Self::Master(MasterParameter::ScaleRoot) => "Scale root".into(),
+            Self::Master(MasterParameter::MonoNotePriority) => "Mono note priority".into(),
+            Self::Master(MasterParameter::UnisonVoices) => "Unison voices".into(),
+            Self::Master(MasterParameter::UnisonDetune) => "Unison detune".into(),
+            Self::Master(MasterParameter::UnisonSpread) => "Unison spread".into(),
+            Self::Master(MasterParameter::KeyRangeLow) => "Key range low".into(),
+            Self::Master(MasterParameter::KeyRangeHigh) => "Key range high".into(),
+            Self::Master(MasterParameter::AftertouchVibratoDepth) => {
+                "Aftertouch vibrato depth".into()
+            }
+            Self::Master(MasterParameter::AftertouchVibratoRate) => {
+                "Aftertouch vibrato rate".into()
+            }
+            Self::Master(MasterParameter::Bypass) => "Bypass".into(),
+            Self::Master(MasterParameter::EqLowGain) => "EQ low gain".into(),
+            Self::Master(MasterParameter::EqLowFrequency) => "EQ low frequency".into(),
+            Self::Master(MasterParameter::EqMidGain) => "EQ mid gain".into(),
+            Self::Master(MasterParameter::EqMidFrequency) => "EQ mid frequency".into(),
+            Self::Master(MasterParameter::EqHighGain) => "EQ high gain".into(),
+            Self::Master(MasterParameter::EqHighFrequency) => "EQ high frequency".into(),
+            Self::Master(MasterParameter::EqBypass) => "EQ bypass".into(),
+            Self::Master(MasterParameter::EffectOrder) => "Effect order".into(),
+            Self::Master(MasterParameter::KeyPan) => "Key pan".into(),
+            Self::Master(MasterParameter::ReleaseVelocitySensitivity) => {
+                "Release velocity sensitivity".into()
+            }
+            Self::Master(MasterParameter::GainCompensation) => "Gain compensation".into(),
+            Self::Master(MasterParameter::PitchBendSmoothing) => "Pitch bend smoothing".into(),
             Self::Operator(index, p) => match p {
                 OperatorParameter::Volume => format!("OP {} vol", index + 1),
                 OperatorParameter::Active => format!("OP {} active", index + 1),
@@ -205,6 +352,11 @@ impl Parameter {
                 OperatorParameter::WaveType => format!("OP {} wave", index + 1),
                 OperatorParameter::ModTargets => format!("OP {} target", index + 1),
                 OperatorParameter::ModOut => format!("OP {} mod out", index + 1),
+                OperatorParameter::BackwardModTargets => {
+                    format!("OP {} backward target", index + 1)
+                }
+                OperatorParameter::PanningMode => format!("OP {} panning mode", index + 1),
+                OperatorParameter::PanningLfoMode => format!("OP {} panning LFO mode", index + 1),
                 OperatorParameter::Feedback => format!("OP {} feedback", index + 1),
                 OperatorParameter::FrequencyRatio => format!("OP {} freq ratio", index + 1),
                 OperatorParameter::FrequencyFree => format!("OP {} freq free", index + 1),
@@ -220,6 +372,29 @@ impl Parameter {
                 OperatorParameter::VelocitySensitivityFeedback => {
                     format!("OP {} feedback velocity sensitivity", index + 1)
                 }
+                OperatorParameter::VelocitySensitivityMixOut => {
+                    format!("OP {} mix out velocity sensitivity", index + 1)
+                }
+                OperatorParameter::VelocitySensitivityAttack => {
+                    format!("OP {} attack velocity sensitivity", index + 1)
+                }
+                OperatorParameter::FeedbackTone => format!("OP {} feedback tone", index + 1),
+                OperatorParameter::OutputBus => format!("OP {} output bus", index + 1),
+                OperatorParameter::KeyScalingBreakpoint => {
+                    format!("OP {} key scaling breakpoint", index + 1)
+                }
+                OperatorParameter::KeyScalingLeftDepth => {
+                    format!("OP {} key scaling left depth", index + 1)
+                }
+                OperatorParameter::KeyScalingRightDepth => {
+                    format!("OP {} key scaling right depth", index + 1)
+                }
+                OperatorParameter::KeyScalingLeftCurve => {
+                    format!("OP {} key scaling left curve", index + 1)
+                }
+                OperatorParameter::KeyScalingRightCurve => {
+                    format!("OP {} key scaling right curve", index + 1)
+                }
             },
             Self::Lfo(index, p) => match p {
                 LfoParameter::Target => format!("LFO {} target", index + 1),
@@ -231,6 +406,15 @@ impl Parameter {
                 LfoParameter::Amount => format!("LFO {} amount", index + 1),
                 LfoParameter::Active => format!("LFO {} active", index + 1),
                 LfoParameter::KeySync => format!("LFO {} key sync", index + 1),
+                LfoParameter::Global => format!("LFO {} global", index + 1),
+                LfoParameter::Smoothing => format!("LFO {} smoothing", index + 1),
+                LfoParameter::Target2 => format!("LFO {} target 2", index + 1),
+                LfoParameter::Target2Depth => format!("LFO {} target 2 depth", index + 1),
+                LfoParameter::Target3 => format!("LFO {} target 3", index + 1),
+                LfoParameter::Target3Depth => format!("LFO {} target 3 depth", index + 1),
+                LfoParameter::Target4 => format!("LFO {} target 4", index + 1),
+                LfoParameter::Target4Depth => format!("LFO {} target 4 depth", index + 1),
+                LfoParameter::ModWheelDepth => format!("LFO {} mod wheel depth", index + 1),
             },
         };
 
@@ -248,8 +432,8 @@ impl OperatorParameter {
         let mut i = 0;
 
         while i < arr.len() {
-            if let (0, Self::ModOut) = (i, self) {
-                // There is no mod out parameter for operator 1
+            if let (3, Self::BackwardModTargets) = (i, self) {
+                // There is no backward mod target parameter for operator 4
                 arr[i] = 0;
             } else {
                 arr[i] = Parameter::Operator(i as u8, self).to_index();
@@ -278,6 +462,35 @@ impl LfoParameter {
     }
 }
 
+/// Group of parameters that can be reset together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterSection {
+    Master,
+    Operator(u8),
+    Lfo(u8),
+}
+
+impl ParameterSection {
+    /// Whether parameter belongs to section. Host bypass doesn't belong to
+    /// any section, since it isn't part of the sound.
+    pub fn contains(self, parameter: Parameter) -> bool {
+        match (self, parameter) {
+            (_, Parameter::Master(MasterParameter::Bypass)) => false,
+            (Self::Master, Parameter::Master(_)) => true,
+            (Self::Operator(a), Parameter::Operator(b, _)) => a == b,
+            (Self::Lfo(a), Parameter::Lfo(b, _)) => a == b,
+            _ => false,
+        }
+    }
+
+    pub fn parameters(self) -> impl Iterator<Item = Parameter> {
+        PARAMETERS
+            .iter()
+            .copied()
+            .filter(move |p| self.contains(*p))
+    }
+}
+
 /// All metadata for a parameter
 #[derive(Debug, Clone, Copy)]
 pub struct WrappedParameter {
@@ -312,7 +525,9 @@ impl From<Parameter> for WrappedParameter {
 mod tests {
     use std::collections::HashSet;
 
-    use super::{ParameterKey, PARAMETERS};
+    use crate::common::{NUM_LFOS, NUM_OPERATORS};
+
+    use super::{MasterParameter, Parameter, ParameterKey, ParameterSection, PARAMETERS};
 
     #[test]
     fn test_parameter_key_uniqueness() {
@@ -320,4 +535,22 @@ mod tests {
 
         assert_eq!(set.len(), PARAMETERS.len());
     }
+
+    #[test]
+    fn test_parameter_sections() {
+        let sections = ::std::iter::once(ParameterSection::Master)
+            .chain((0..NUM_OPERATORS as u8).map(ParameterSection::Operator))
+            .chain((0..NUM_LFOS as u8).map(ParameterSection::Lfo))
+            .collect::<Vec<_>>();
+
+        for parameter in PARAMETERS.iter().copied() {
+            let num_sections = sections.iter().filter(|s| s.contains(parameter)).count();
+
+            if parameter == Parameter::Master(MasterParameter::Bypass) {
+                assert_eq!(num_sections, 0);
+            } else {
+                assert_eq!(num_sections, 1);
+            }
+        }
+    }
 }
@@ -0,0 +1,60 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const MOD_OUT_SCALING_STEPS: &[ModOutScaling] = &[ModOutScaling::Native, ModOutScaling::Dx7];
+
+/// Interpretation of operator mod out values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModOutScaling {
+    #[default]
+    Native,
+    /// DX7-style output levels 0-99 on exponential curve
+    Dx7,
+}
+
+impl ::std::fmt::Display for ModOutScaling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Native => "NATIVE",
+            Self::Dx7 => "DX7",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ModOutScalingValue(ModOutScaling);
+
+impl ParameterValue for ModOutScalingValue {
+    type Value = ModOutScaling;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "native" => Some(Self(ModOutScaling::Native)),
+            "dx7" => Some(Self(ModOutScaling::Dx7)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(MOD_OUT_SCALING_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(MOD_OUT_SCALING_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -0,0 +1,69 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const MONO_NOTE_PRIORITY_STEPS: &[MonoNotePriority] = &[
+    MonoNotePriority::Last,
+    MonoNotePriority::Low,
+    MonoNotePriority::High,
+];
+
+/// Which of the held keys the monophonic voice plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MonoNotePriority {
+    /// Most recently pressed key
+    #[default]
+    Last,
+    /// Lowest key
+    Low,
+    /// Highest key
+    High,
+}
+
+impl ::std::fmt::Display for MonoNotePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Last => "LAST",
+            Self::Low => "LOW",
+            Self::High => "HIGH",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MonoNotePriorityValue(MonoNotePriority);
+
+impl ParameterValue for MonoNotePriorityValue {
+    type Value = MonoNotePriority;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "last" => Some(Self(MonoNotePriority::Last)),
+            "low" => Some(Self(MonoNotePriority::Low)),
+            "high" => Some(Self(MonoNotePriority::High)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(MONO_NOTE_PRIORITY_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(MONO_NOTE_PRIORITY_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
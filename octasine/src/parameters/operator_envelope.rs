@@ -3,13 +3,18 @@ use std::str::FromStr;
 use compact_str::{format_compact, CompactString};
 
 use super::{
+    list::OperatorParameter,
     utils::{map_patch_value_to_step, map_step_to_patch_value, parse_valid_f32, parse_valid_f64},
     ParameterValue, SerializableRepresentation,
 };
 
-pub const ENVELOPE_MAX_DURATION: f64 = 4.0;
+pub const ENVELOPE_MAX_DURATION: f64 = 32.0;
 pub const ENVELOPE_MIN_DURATION: f64 = 0.00333;
 
+/// Exponent for mapping patch values to durations. Gives finer control over
+/// short durations while still allowing very long ones.
+const ENVELOPE_DURATION_CURVE_EXPONENT: f64 = 3.0;
+
 /// After this duration, the envelope slope does not get mixed with linear
 /// slope at all
 pub const ENVELOPE_CURVE_TAKEOVER: f64 = 0.05;
@@ -20,6 +25,19 @@ const DEFAULT_DECAY: f64 = ENVELOPE_MIN_DURATION;
 const DEFAULT_SUSTAIN: f32 = 1.0;
 const DEFAULT_RELEASE: f64 = 0.25;
 
+pub fn envelope_duration_from_patch(value: f32) -> f64 {
+    let duration = (value as f64).powf(ENVELOPE_DURATION_CURVE_EXPONENT) * ENVELOPE_MAX_DURATION;
+
+    // Force some decay to avoid clicks
+    duration.max(ENVELOPE_MIN_DURATION)
+}
+
+pub fn envelope_duration_to_patch(duration: f64) -> f32 {
+    (duration / ENVELOPE_MAX_DURATION)
+        .max(0.0)
+        .powf(1.0 / ENVELOPE_DURATION_CURVE_EXPONENT) as f32
+}
+
 macro_rules! impl_duration_parameter_value {
     ($struct_name:ident) => {
         impl ParameterValue for $struct_name {
@@ -33,11 +51,10 @@ macro_rules! impl_duration_parameter_value {
                 self.0
             }
             fn new_from_patch(value: f32) -> Self {
-                // Force some decay to avoid clicks
-                Self((value as f64 * ENVELOPE_MAX_DURATION).max(ENVELOPE_MIN_DURATION))
+                Self(envelope_duration_from_patch(value))
             }
             fn to_patch(self) -> f32 {
-                (self.0 / ENVELOPE_MAX_DURATION) as f32
+                envelope_duration_to_patch(self.0)
             }
 
             fn get_formatted(self) -> CompactString {
@@ -186,6 +203,73 @@ impl ParameterValue for OperatorEnvelopeGroupValue {
     }
 }
 
+pub const ENVELOPE_PRESETS: &[OperatorEnvelopePreset] = &[
+    OperatorEnvelopePreset::Pluck,
+    OperatorEnvelopePreset::Organ,
+    OperatorEnvelopePreset::Pad,
+    OperatorEnvelopePreset::Gate,
+    OperatorEnvelopePreset::Swell,
+];
+
+/// Envelope stage settings that can be applied to an operator in one go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorEnvelopePreset {
+    Pluck,
+    Organ,
+    Pad,
+    Gate,
+    Swell,
+}
+
+impl OperatorEnvelopePreset {
+    /// Attack, decay and release durations in seconds and sustain volume
+    fn values(self) -> (f64, f64, f32, f64) {
+        match self {
+            Self::Pluck => (ENVELOPE_MIN_DURATION, 0.6, 0.0, 0.3),
+            Self::Organ => (0.01, ENVELOPE_MIN_DURATION, 1.0, 0.05),
+            Self::Pad => (1.5, 2.0, 0.7, 3.0),
+            Self::Gate => (ENVELOPE_MIN_DURATION, ENVELOPE_MIN_DURATION, 1.0, 0.01),
+            Self::Swell => (4.0, ENVELOPE_MIN_DURATION, 1.0, 1.5),
+        }
+    }
+
+    /// Patch values for all stage parameters
+    pub fn patch_values(self) -> [(OperatorParameter, f32); 4] {
+        let (attack, decay, sustain, release) = self.values();
+
+        [
+            (
+                OperatorParameter::AttackDuration,
+                OperatorAttackDurationValue(attack).to_patch(),
+            ),
+            (
+                OperatorParameter::DecayDuration,
+                OperatorDecayDurationValue(decay).to_patch(),
+            ),
+            (
+                OperatorParameter::SustainVolume,
+                OperatorSustainVolumeValue(sustain).to_patch(),
+            ),
+            (
+                OperatorParameter::ReleaseDuration,
+                OperatorReleaseDurationValue(release).to_patch(),
+            ),
+        ]
+    }
+}
+
+impl ::std::fmt::Display for OperatorEnvelopePreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Pluck => "PLUCK",
+            Self::Organ => "ORGAN",
+            Self::Pad => "PAD",
+            Self::Gate => "GATE",
+            Self::Swell => "SWELL",
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,10 +291,48 @@ mod tests {
         );
 
         assert_eq!(
-            OperatorAttackDurationValue::new_from_text("10.0")
+            OperatorAttackDurationValue::new_from_text("30.0")
+                .unwrap()
+                .get(),
+            30.0
+        );
+
+        assert_eq!(
+            OperatorAttackDurationValue::new_from_text("100.0")
                 .unwrap()
                 .get(),
             ENVELOPE_MAX_DURATION
         );
     }
+
+    #[test]
+    fn test_duration_patch_value_roundtrip() {
+        for i in 0..=100 {
+            let patch_value = i as f32 / 100.0;
+            let duration = envelope_duration_from_patch(patch_value);
+
+            assert!((ENVELOPE_MIN_DURATION..=ENVELOPE_MAX_DURATION).contains(&duration));
+
+            if duration > ENVELOPE_MIN_DURATION {
+                let diff = (envelope_duration_to_patch(duration) - patch_value).abs();
+
+                assert!(diff < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_envelope_preset_patch_values() {
+        for preset in ENVELOPE_PRESETS {
+            for (parameter, patch_value) in preset.patch_values() {
+                assert!(
+                    (0.0..=1.0).contains(&patch_value),
+                    "{:?} {:?}: {}",
+                    preset,
+                    parameter,
+                    patch_value
+                );
+            }
+        }
+    }
 }
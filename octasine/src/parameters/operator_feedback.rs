@@ -3,6 +3,22 @@ use compact_str::{format_compact, CompactString};
 use super::{utils::*, ParameterValue, SerializableRepresentation};
 use crate::common::OPERATOR_MOD_INDEX_STEPS;
 
+/// Highest feedback amount. Roughly two and a half times DX7 maximum
+/// feedback, which is already quite noisy.
+pub const OPERATOR_FEEDBACK_MAX: f32 = 8.0;
+/// Exponent of patch value to feedback amount curve. Brightness increases
+/// rapidly with small feedback amounts and saturates into noise with large
+/// ones, so this dedicates most of the knob range to the low end.
+const OPERATOR_FEEDBACK_CURVE_EXPONENT: f32 = 3.0;
+
+/// Mix out level factor for feedback amount. Feedback moves energy to
+/// higher harmonics and eventually noise, which sounds a lot louder at the
+/// same peak level, so output is attenuated as feedback increases: by about
+/// 3 dB at feedback 1.0 and 9.5 dB at maximum feedback.
+pub fn feedback_gain_compensation(feedback: f32) -> f32 {
+    (1.0 + feedback.max(0.0)).sqrt().recip()
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct OperatorFeedbackValue(f32);
 
@@ -12,35 +28,125 @@ impl Default for OperatorFeedbackValue {
     }
 }
 
+impl OperatorFeedbackValue {
+    /// Feedback amount for patch values prior to curve mapping, where patch
+    /// values were mapped linearly between modulation index steps
+    pub fn from_previous_patch_mapping(value: f32) -> Self {
+        Self::new_from_audio(
+            map_patch_to_audio_value_with_steps(&OPERATOR_MOD_INDEX_STEPS[..], value)
+                .min(OPERATOR_FEEDBACK_MAX),
+        )
+    }
+}
+
 impl ParameterValue for OperatorFeedbackValue {
     type Value = f32;
 
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
+    /// Accepts both percent of knob range (e.g., "25%") and feedback
+    /// amount (e.g., "0.125")
     fn new_from_text(text: &str) -> Option<Self> {
-        const MIN: f32 = OPERATOR_MOD_INDEX_STEPS[0];
-        const MAX: f32 = OPERATOR_MOD_INDEX_STEPS[OPERATOR_MOD_INDEX_STEPS.len() - 1];
+        let text = text.trim();
 
-        parse_valid_f32(text, MIN, MAX).map(Self)
+        if let Some(percent) = text.strip_suffix('%') {
+            parse_valid_f32(percent, 0.0, 100.0).map(|v| Self::new_from_patch(v / 100.0))
+        } else {
+            parse_valid_f32(text, 0.0, OPERATOR_FEEDBACK_MAX).map(Self)
+        }
     }
     fn get(self) -> Self::Value {
         self.0
     }
     fn new_from_patch(value: f32) -> Self {
-        Self(map_patch_to_audio_value_with_steps(
-            &OPERATOR_MOD_INDEX_STEPS[..],
-            value,
-        ))
+        let value = value.clamp(0.0, 1.0);
+
+        Self(OPERATOR_FEEDBACK_MAX * value.powf(OPERATOR_FEEDBACK_CURVE_EXPONENT))
     }
     fn to_patch(self) -> f32 {
-        map_audio_to_patch_value_with_steps(&OPERATOR_MOD_INDEX_STEPS[..], self.0)
+        let value = (self.0 / OPERATOR_FEEDBACK_MAX).clamp(0.0, 1.0);
+
+        value.powf(1.0 / OPERATOR_FEEDBACK_CURVE_EXPONENT)
     }
     fn get_formatted(self) -> CompactString {
-        format_compact!("{:.04}", self.0)
+        format_compact!("{:.1}%", self.to_patch() * 100.0)
     }
 
     fn get_serializable(&self) -> SerializableRepresentation {
         SerializableRepresentation::Float(self.0.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operator_feedback_patch_roundtrip() {
+        for i in 0..=100 {
+            let patch_value = i as f32 / 100.0;
+            let roundtrip = OperatorFeedbackValue::new_from_patch(patch_value).to_patch();
+
+            assert!((roundtrip - patch_value).abs() < 1e-5);
+        }
+
+        assert_eq!(OperatorFeedbackValue::new_from_patch(0.0).get(), 0.0);
+        assert_eq!(
+            OperatorFeedbackValue::new_from_patch(1.0).get(),
+            OPERATOR_FEEDBACK_MAX
+        );
+        assert_eq!(OperatorFeedbackValue::new_from_patch(0.5).get(), 1.0);
+    }
+
+    #[test]
+    fn test_operator_feedback_from_text() {
+        assert_eq!(
+            OperatorFeedbackValue::new_from_text("50%").unwrap().get(),
+            1.0
+        );
+        assert_eq!(
+            OperatorFeedbackValue::new_from_text("0.25").unwrap().get(),
+            0.25
+        );
+        assert_eq!(
+            OperatorFeedbackValue::new_from_text("1000").unwrap().get(),
+            OPERATOR_FEEDBACK_MAX
+        );
+        assert_eq!(
+            OperatorFeedbackValue::new_from_patch(0.25).get_formatted(),
+            "25.0%"
+        );
+    }
+
+    #[test]
+    fn test_feedback_gain_compensation() {
+        assert_eq!(feedback_gain_compensation(0.0), 1.0);
+        assert_eq!(feedback_gain_compensation(-1.0), 1.0);
+
+        let gain_db = |feedback| 20.0 * feedback_gain_compensation(feedback).log10();
+
+        assert!((gain_db(1.0) + 3.01).abs() < 0.01);
+        assert!((gain_db(OPERATOR_FEEDBACK_MAX) + 9.54).abs() < 0.01);
+
+        for i in 0..100 {
+            let feedback = i as f32 * OPERATOR_FEEDBACK_MAX / 100.0;
+
+            assert!(
+                feedback_gain_compensation(feedback + 0.01) < feedback_gain_compensation(feedback)
+            );
+        }
+    }
+
+    #[test]
+    fn test_operator_feedback_previous_patch_mapping() {
+        // Previous mapping put 1.0 at step 5 of 15
+        let feedback = OperatorFeedbackValue::from_previous_patch_mapping(5.0 / 15.0);
+
+        assert!((feedback.get() - 1.0).abs() < 1e-5);
+
+        let feedback = OperatorFeedbackValue::from_previous_patch_mapping(1.0);
+
+        assert_eq!(feedback.get(), OPERATOR_FEEDBACK_MAX);
+    }
+}
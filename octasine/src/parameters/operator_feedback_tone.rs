@@ -0,0 +1,66 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{utils::*, ParameterValue, SerializableRepresentation};
+
+/// Feedback lowpass cutoff frequency with tone at zero
+const MAX_CUTOFF: f64 = 20_000.0;
+/// Feedback lowpass cutoff frequency with tone at max
+const MIN_CUTOFF: f64 = 100.0;
+
+/// Amount of lowpass filtering in operator feedback path. Zero means no
+/// filtering.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperatorFeedbackToneValue(f32);
+
+impl OperatorFeedbackToneValue {
+    /// Lowpass cutoff frequency, or None if filter is disabled
+    pub fn cutoff_frequency(tone: f32) -> Option<f64> {
+        if tone <= 0.0 {
+            None
+        } else {
+            Some(MAX_CUTOFF * (MIN_CUTOFF / MAX_CUTOFF).powf(f64::from(tone.min(1.0))))
+        }
+    }
+}
+
+impl ParameterValue for OperatorFeedbackToneValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.02}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cutoff_frequency() {
+        assert_eq!(OperatorFeedbackToneValue::cutoff_frequency(0.0), None);
+        assert_eq!(
+            OperatorFeedbackToneValue::cutoff_frequency(1.0),
+            Some(MIN_CUTOFF)
+        );
+        assert!(OperatorFeedbackToneValue::cutoff_frequency(0.5).unwrap() < MAX_CUTOFF);
+    }
+}
@@ -25,11 +25,25 @@ impl ParameterValue for OperatorFrequencyFineValue {
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
+    /// Accepts cents (e.g., "+7c" or "-12 cents") or frequency factor
     fn new_from_text(text: &str) -> Option<Self> {
         const MIN: f32 = OPERATOR_FINE_STEPS[0];
         const MAX: f32 = OPERATOR_FINE_STEPS[OPERATOR_FINE_STEPS.len() - 1];
 
-        parse_valid_f32(text, MIN, MAX).map(|v| Self(v.into()))
+        let text = text.trim().to_lowercase();
+
+        let opt_cents_text = ["cents", "cent", "ct", "c"]
+            .into_iter()
+            .find_map(|suffix| text.strip_suffix(suffix));
+
+        if let Some(cents_text) = opt_cents_text {
+            let cents = parse_valid_f64(cents_text.trim(), f64::MIN, f64::MAX)?;
+            let factor = (cents / 1200.0).exp2();
+
+            Some(Self(factor.clamp(MIN.into(), MAX.into())))
+        } else {
+            parse_valid_f32(&text, MIN, MAX).map(|v| Self(v.into()))
+        }
     }
     fn get(self) -> Self::Value {
         self.0
@@ -41,10 +55,55 @@ impl ParameterValue for OperatorFrequencyFineValue {
         map_audio_to_patch_value_with_steps(&OPERATOR_FINE_STEPS, self.0 as f32)
     }
     fn get_formatted(self) -> CompactString {
-        format_compact!("{:.04}", self.0)
+        let cents = 1200.0 * self.0.log2();
+
+        // Prevent display of negative zero
+        if cents.abs() < 0.05 {
+            "0.0c".into()
+        } else {
+            format_compact!("{:+.1}c", cents)
+        }
     }
 
     fn get_serializable(&self) -> SerializableRepresentation {
         SerializableRepresentation::Float(self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cents() {
+        assert_eq!(
+            OperatorFrequencyFineValue::default().get_formatted(),
+            "0.0c"
+        );
+        assert_eq!(
+            OperatorFrequencyFineValue::new_from_audio(2.0f64.powf(7.0 / 1200.0)).get_formatted(),
+            "+7.0c"
+        );
+
+        for (text, cents) in [("+7c", 7.0), ("-12.5 cents", -12.5), ("3 ct", 3.0)] {
+            let value = OperatorFrequencyFineValue::new_from_text(text)
+                .unwrap()
+                .get();
+
+            assert!((value - (cents / 1200.0f64).exp2()).abs() < 1e-9);
+        }
+
+        assert_eq!(
+            OperatorFrequencyFineValue::new_from_text("1.01")
+                .unwrap()
+                .get(),
+            1.01f32 as f64
+        );
+        assert_eq!(
+            OperatorFrequencyFineValue::new_from_text("+10000c")
+                .unwrap()
+                .get(),
+            1.2f32 as f64
+        );
+    }
+}
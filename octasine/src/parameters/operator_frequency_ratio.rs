@@ -1,6 +1,8 @@
 use std::f64::consts::PI;
 use std::str::FromStr;
+use std::sync::Arc;
 
+use arc_swap::ArcSwap;
 use arrayvec::ArrayString;
 use compact_str::CompactString;
 use once_cell::sync::Lazy;
@@ -26,7 +28,83 @@ impl Ratio {
     }
 }
 
-static OPERATOR_RATIO_STEPS: Lazy<Vec<Ratio>> = Lazy::new(|| {
+/// Number of ratio steps following built-in ones that are reserved for
+/// ratios defined by user in settings. They are always present, so that
+/// patch values of built-in ratios don't depend on settings.
+const NUM_USER_RATIOS: usize = 8;
+
+/// Set ratios defined by user, e.g., "7/4", "3:2" or "1.1", replacing any
+/// previously set ones. They are placed in the user ratio steps in the given
+/// order. Unused user ratio steps repeat the last ratio.
+pub fn set_user_ratios(texts: &[String]) {
+    let steps = ratio_steps(&parse_user_ratios(texts));
+
+    // Avoid replacing list needlessly, e.g., when creating plugin instances
+    if **OPERATOR_RATIO_STEPS.load() != steps {
+        OPERATOR_RATIO_STEPS.store(Arc::new(steps));
+    }
+}
+
+/// Parse user ratios, skipping invalid ones, ones already among built-in
+/// ratios and ones exceeding [NUM_USER_RATIOS]
+fn parse_user_ratios(texts: &[String]) -> Vec<Ratio> {
+    let mut ratios = Vec::with_capacity(texts.len());
+
+    for text in texts {
+        let name = text.trim().replace(':', "/");
+
+        match (parse_ratio(&name), ArrayString::from_str(&name)) {
+            (Some(value), Ok(name)) if value > 0.0 => {
+                if BUILT_IN_RATIOS.iter().any(|r| r.value == value) {
+                    continue;
+                }
+                if ratios.len() == NUM_USER_RATIOS {
+                    ::log::warn!("Ignoring user frequency ratio, max count reached: {}", text);
+
+                    continue;
+                }
+
+                ratios.push(Ratio { name, value })
+            }
+            _ => ::log::warn!("Ignoring invalid user frequency ratio: {}", text),
+        }
+    }
+
+    ratios
+}
+
+/// Parse ratio written as fraction (e.g., "3/2" or "3:2") or number
+fn parse_ratio(text: &str) -> Option<f64> {
+    let value = if let Some((a, b)) = text.split_once(['/', ':']) {
+        a.trim().parse::<f64>().ok()? / b.trim().parse::<f64>().ok()?
+    } else {
+        text.trim().parse::<f64>().ok()?
+    };
+
+    value.is_finite().then_some(value)
+}
+
+/// Built-in ratios followed by user ratios
+static OPERATOR_RATIO_STEPS: Lazy<ArcSwap<Vec<Ratio>>> =
+    Lazy::new(|| ArcSwap::from_pointee(ratio_steps(&[])));
+
+fn ratio_steps(user_ratios: &[Ratio]) -> Vec<Ratio> {
+    let mut steps = BUILT_IN_RATIOS.clone();
+
+    for index in 0..NUM_USER_RATIOS {
+        let ratio = *user_ratios
+            .get(index)
+            .or(steps.last())
+            .expect("ratio steps are not empty");
+
+        steps.push(ratio);
+    }
+
+    steps
+}
+
+/// Built-in ratios in ascending order
+static BUILT_IN_RATIOS: Lazy<Vec<Ratio>> = Lazy::new(|| {
     let mut ratios = vec![
         Ratio::new(1.0 / 8.0, "1/8"), // 0.125
         Ratio::new(1.0 / 6.0, "1/6"), // 0.167
@@ -116,14 +194,26 @@ static OPERATOR_RATIO_STEPS: Lazy<Vec<Ratio>> = Lazy::new(|| {
 #[derive(Debug, Clone, Copy)]
 pub struct OperatorFrequencyRatioValue(Ratio);
 
+impl OperatorFrequencyRatioValue {
+    /// Ratio step with value closest to given one
+    pub fn new_closest_to_value(value: f64) -> Option<Self> {
+        OPERATOR_RATIO_STEPS
+            .load()
+            .iter()
+            .min_by(|a, b| {
+                (a.value - value)
+                    .abs()
+                    .partial_cmp(&(b.value - value).abs())
+                    .unwrap()
+            })
+            .copied()
+            .map(Self)
+    }
+}
+
 impl Default for OperatorFrequencyRatioValue {
     fn default() -> Self {
-        Self(
-            *OPERATOR_RATIO_STEPS
-                .iter()
-                .find(|r| r.value == 1.0)
-                .unwrap(),
-        )
+        Self(*BUILT_IN_RATIOS.iter().find(|r| r.value == 1.0).unwrap())
     }
 }
 
@@ -133,33 +223,50 @@ impl ParameterValue for OperatorFrequencyRatioValue {
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
+    /// Accepts ratio names (e.g., "3/2" or "2π"), values (e.g., "1.5 ratio"
+    /// or "1.33") and fractions (e.g., "3:2" or "9/6"), the latter two
+    /// snapped to the closest ratio
     fn new_from_text(text: &str) -> Option<Self> {
+        /// Maximum relative difference when snapping value to ratio
+        const MAX_SNAP_DIFFERENCE: f64 = 0.005;
+
+        let text = text.trim().to_lowercase();
         let text = text
+            .strip_suffix("ratio")
+            .or_else(|| text.strip_suffix('x'))
+            .unwrap_or(&text)
             .trim()
-            .to_lowercase()
             .replace(" pi", "π")
             .replace("pi", "π");
 
-        for ratio in OPERATOR_RATIO_STEPS.iter() {
+        let steps = OPERATOR_RATIO_STEPS.load();
+
+        for ratio in steps.iter() {
             if ratio.name.as_str() == text {
                 return Some(Self(*ratio));
             }
         }
 
-        if let Ok(value) = text.parse::<f64>() {
+        if let Some(value) = parse_ratio(&text) {
             if value == 3.14 {
-                return OPERATOR_RATIO_STEPS
+                return BUILT_IN_RATIOS
                     .iter()
                     .find(|r| r.value == PI)
                     .copied()
                     .map(Self);
             }
 
-            for ratio in OPERATOR_RATIO_STEPS.iter() {
+            for ratio in steps.iter() {
                 if value == ratio.value {
                     return Some(Self(*ratio));
                 }
             }
+
+            let closest = Self::new_closest_to_value(value)?;
+
+            if ((closest.0.value - value) / closest.0.value).abs() <= MAX_SNAP_DIFFERENCE {
+                return Some(closest);
+            }
         }
 
         None
@@ -168,10 +275,13 @@ impl ParameterValue for OperatorFrequencyRatioValue {
         self.0
     }
     fn new_from_patch(value: f32) -> Self {
-        Self(map_patch_value_to_step(&OPERATOR_RATIO_STEPS[..], value))
+        Self(map_patch_value_to_step(
+            &OPERATOR_RATIO_STEPS.load()[..],
+            value,
+        ))
     }
     fn to_patch(self) -> f32 {
-        map_step_to_patch_value(&OPERATOR_RATIO_STEPS[..], self.0)
+        map_step_to_patch_value(&OPERATOR_RATIO_STEPS.load()[..], self.0)
     }
     fn get_formatted(self) -> CompactString {
         self.0.name.as_str().into()
@@ -183,6 +293,7 @@ impl ParameterValue for OperatorFrequencyRatioValue {
     fn get_text_choices() -> Option<Vec<CompactString>> {
         Some(
             OPERATOR_RATIO_STEPS
+                .load()
                 .iter()
                 .map(|ratio| CompactString::new(ratio.name.as_str()))
                 .collect(),
@@ -196,9 +307,67 @@ mod tests {
 
     #[test]
     fn test_ratios() {
-        let ratios = &OPERATOR_RATIO_STEPS;
+        let ratios = OPERATOR_RATIO_STEPS.load();
 
         assert!(!ratios.is_empty());
         assert!(ratios.contains(&OperatorFrequencyRatioValue::default().get()));
     }
+
+    #[test]
+    fn test_ratio_text_input() {
+        for (text, name) in [
+            ("3/2", "3/2"),
+            ("1.5 ratio", "3/2"),
+            ("1.5x", "3/2"),
+            ("1.33", "4/3"),
+            ("2 pi", "2π"),
+            ("3:2", "3/2"),
+            ("9/6", "3/2"),
+            ("10:3", "10/3"),
+        ] {
+            assert_eq!(
+                OperatorFrequencyRatioValue::new_from_text(text)
+                    .unwrap()
+                    .get_formatted(),
+                name
+            );
+        }
+
+        assert!(OperatorFrequencyRatioValue::new_from_text("1.45").is_none());
+        assert!(OperatorFrequencyRatioValue::new_from_text("3:0").is_none());
+    }
+
+    #[test]
+    fn test_user_ratios() {
+        let texts = ["7/4", "3:2", "invalid", "-1", "9:8"].map(String::from);
+
+        let user_ratios = parse_user_ratios(&texts);
+
+        // Built-in and invalid ratios are skipped
+        assert_eq!(
+            user_ratios
+                .iter()
+                .map(|r| r.name.as_str())
+                .collect::<Vec<_>>(),
+            ["7/4", "9/8"]
+        );
+
+        let steps = ratio_steps(&user_ratios);
+        let num_built_in = BUILT_IN_RATIOS.len();
+
+        // Built-in ratios keep their positions regardless of user ratios
+        assert_eq!(steps.len(), ratio_steps(&[]).len());
+        assert_eq!(steps.len(), num_built_in + NUM_USER_RATIOS);
+        assert_eq!(steps[..num_built_in], BUILT_IN_RATIOS[..]);
+        assert_eq!(steps[num_built_in].name.as_str(), "7/4");
+        assert!(steps[num_built_in + 1..]
+            .iter()
+            .all(|r| r.name.as_str() == "9/8"));
+
+        let texts = (2..20)
+            .map(|i| format!("{}/19", i * 2 + 1))
+            .collect::<Vec<_>>();
+
+        assert_eq!(parse_user_ratios(&texts).len(), NUM_USER_RATIOS);
+    }
 }
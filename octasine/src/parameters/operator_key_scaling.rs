@@ -0,0 +1,254 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{
+        format_key, map_patch_value_to_step, map_step_to_patch_value, parse_key, parse_valid_f32,
+    },
+    ParameterValue, SerializableRepresentation,
+};
+
+/// Maximum key scaling depth in dB per octave
+const MAX_DEPTH: f32 = 24.0;
+/// Key scaling attenuation is limited to this, in dB
+const MIN_GAIN_DB: f32 = -96.0;
+/// Key scaling boost (with positive curves) is limited to this, in dB
+const MAX_GAIN_DB: f32 = 12.0;
+
+pub const KEY_SCALING_CURVE_STEPS: &[KeyScalingCurve] = &[
+    KeyScalingCurve::NegativeLinear,
+    KeyScalingCurve::NegativeExponential,
+    KeyScalingCurve::PositiveExponential,
+    KeyScalingCurve::PositiveLinear,
+];
+
+/// Shape of operator level change by distance from key scaling breakpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyScalingCurve {
+    /// Attenuate by depth for each octave
+    #[default]
+    NegativeLinear,
+    /// Attenuate slowly near breakpoint and increasingly quickly further away
+    NegativeExponential,
+    PositiveExponential,
+    PositiveLinear,
+}
+
+impl KeyScalingCurve {
+    /// Level change in dB at distance from breakpoint
+    fn db(self, depth: f32, octaves: f32) -> f32 {
+        match self {
+            Self::NegativeLinear => -depth * octaves,
+            Self::NegativeExponential => -depth * (octaves.exp2() - 1.0),
+            Self::PositiveExponential => depth * (octaves.exp2() - 1.0),
+            Self::PositiveLinear => depth * octaves,
+        }
+    }
+}
+
+impl ::std::fmt::Display for KeyScalingCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::NegativeLinear => "-LIN",
+            Self::NegativeExponential => "-EXP",
+            Self::PositiveExponential => "+EXP",
+            Self::PositiveLinear => "+LIN",
+        })
+    }
+}
+
+/// Operator output level factor for key, DX7-style. Keys below breakpoint
+/// are scaled by left depth and curve, keys above it by right depth and
+/// curve. Depths are in dB per octave.
+pub fn key_scaling_gain(
+    key: u8,
+    breakpoint: u8,
+    left_depth: f32,
+    left_curve: KeyScalingCurve,
+    right_depth: f32,
+    right_curve: KeyScalingCurve,
+) -> f32 {
+    let (semitones, depth, curve) = if key < breakpoint {
+        (breakpoint - key, left_depth, left_curve)
+    } else {
+        (key - breakpoint, right_depth, right_curve)
+    };
+
+    if semitones == 0 || depth == 0.0 {
+        return 1.0;
+    }
+
+    let db = curve
+        .db(depth, f32::from(semitones) / 12.0)
+        .clamp(MIN_GAIN_DB, MAX_GAIN_DB);
+
+    10.0f32.powf(db / 20.0)
+}
+
+/// Key at which operator level is not affected by key scaling
+#[derive(Debug, Clone, Copy)]
+pub struct OperatorKeyScalingBreakpointValue(u8);
+
+impl Default for OperatorKeyScalingBreakpointValue {
+    fn default() -> Self {
+        Self(60)
+    }
+}
+
+impl ParameterValue for OperatorKeyScalingBreakpointValue {
+    type Value = u8;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value.min(127))
+    }
+    /// Parse key name such as "C4" or "F#2" (C4 being MIDI key 60) or MIDI
+    /// key number
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_key(text).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self((value.clamp(0.0, 1.0) * 127.0).round() as u8)
+    }
+    fn to_patch(self) -> f32 {
+        f32::from(self.0) / 127.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_key(self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+/// Key scaling depth in dB per octave on one side of breakpoint
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperatorKeyScalingDepthValue(f32);
+
+impl ParameterValue for OperatorKeyScalingDepthValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+        let text = text.strip_suffix("db/oct").unwrap_or(&text).trim();
+
+        parse_valid_f32(text, 0.0, MAX_DEPTH).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value.clamp(0.0, 1.0) * MAX_DEPTH)
+    }
+    fn to_patch(self) -> f32 {
+        self.0 / MAX_DEPTH
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.1} dB/oct", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperatorKeyScalingCurveValue(KeyScalingCurve);
+
+impl ParameterValue for OperatorKeyScalingCurveValue {
+    type Value = KeyScalingCurve;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim().to_uppercase();
+
+        KEY_SCALING_CURVE_STEPS
+            .iter()
+            .find(|curve| curve.to_string() == text)
+            .map(|curve| Self(*curve))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(KEY_SCALING_CURVE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(KEY_SCALING_CURVE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_scaling_gain() {
+        use KeyScalingCurve::*;
+
+        let gain = |key, left_depth, left_curve, right_depth, right_curve| {
+            key_scaling_gain(key, 60, left_depth, left_curve, right_depth, right_curve)
+        };
+
+        // No scaling at breakpoint or with zero depth
+        assert_eq!(gain(60, 12.0, NegativeLinear, 12.0, NegativeLinear), 1.0);
+        assert_eq!(gain(84, 12.0, NegativeLinear, 0.0, NegativeLinear), 1.0);
+
+        // Each side uses its own depth and curve
+        let right = gain(72, 0.0, PositiveLinear, 6.0, NegativeLinear);
+        let left = gain(48, 6.0, PositiveLinear, 0.0, NegativeLinear);
+
+        assert!((20.0 * right.log10() + 6.0).abs() < 0.001);
+        assert!((20.0 * left.log10() - 6.0).abs() < 0.001);
+
+        // Exponential curves match linear ones one octave from breakpoint,
+        // but change level faster further away
+        let linear = gain(84, 0.0, NegativeLinear, 6.0, NegativeLinear);
+        let exponential = gain(84, 0.0, NegativeLinear, 6.0, NegativeExponential);
+
+        assert!(exponential < linear);
+        assert!((gain(72, 0.0, NegativeLinear, 6.0, NegativeExponential) - right).abs() < 0.0001);
+
+        // Boost is limited
+        assert!(gain(127, 0.0, NegativeLinear, 24.0, PositiveExponential) <= 4.0);
+    }
+
+    #[test]
+    fn test_breakpoint_text() {
+        for key in 0..128 {
+            let value = OperatorKeyScalingBreakpointValue(key);
+            let parsed =
+                OperatorKeyScalingBreakpointValue::new_from_text(&value.get_formatted()).unwrap();
+
+            assert_eq!(parsed.get(), key);
+        }
+
+        assert_eq!(
+            OperatorKeyScalingBreakpointValue::new_from_text("c4")
+                .unwrap()
+                .get(),
+            60
+        );
+        assert_eq!(
+            OperatorKeyScalingBreakpointValue::new_from_text("C-1")
+                .unwrap()
+                .get(),
+            0
+        );
+        assert!(OperatorKeyScalingBreakpointValue::new_from_text("H2").is_none());
+    }
+}
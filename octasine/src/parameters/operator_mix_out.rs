@@ -1,6 +1,9 @@
-use compact_str::{format_compact, CompactString};
+use compact_str::CompactString;
 
-use super::{utils::parse_valid_f32, ParameterValue, SerializableRepresentation};
+use super::{
+    utils::{format_linear_as_db, parse_db_as_linear},
+    ParameterValue, SerializableRepresentation,
+};
 
 #[derive(Default, Debug, Clone, Copy)]
 pub struct OperatorMixOutValue(f32);
@@ -22,7 +25,7 @@ impl ParameterValue for OperatorMixOutValue {
         Self(value)
     }
     fn new_from_text(text: &str) -> Option<Self> {
-        parse_valid_f32(text, 0.0, 2.0).map(Self)
+        parse_db_as_linear(text, 0.0, 2.0).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -34,7 +37,7 @@ impl ParameterValue for OperatorMixOutValue {
         self.0 / 2.0
     }
     fn get_formatted(self) -> CompactString {
-        format_compact!("{:.04}", self.0)
+        format_linear_as_db(self.0)
     }
 
     fn get_serializable(&self) -> SerializableRepresentation {
@@ -1,11 +1,56 @@
+use std::f32::consts::PI;
+
 use compact_str::{format_compact, CompactString};
 
 use super::{utils::*, ParameterValue, SerializableRepresentation};
 use crate::common::OPERATOR_MOD_INDEX_STEPS;
 
+/// Highest DX7 output level
+const DX7_MAX_LEVEL: f32 = 99.0;
+/// Modulation index at highest DX7 output level
+const DX7_MAX_MOD_INDEX: f32 = 4.0 * PI;
+/// Number of DX7 output level steps per doubling of modulation index
+/// (0.75 dB per step)
+const DX7_LEVEL_STEPS_PER_OCTAVE: f32 = 8.0;
+
 #[derive(Debug, Clone, Copy)]
 pub struct OperatorModOutValue(f32);
 
+/// DX7-style interpretation of patch values, used when mod out scaling is
+/// set to DX7. Patch values map linearly to output levels 0-99, which in
+/// turn map exponentially to modulation index.
+impl OperatorModOutValue {
+    pub fn new_from_patch_dx7(value: f32) -> Self {
+        let level = Self::patch_to_dx7_level(value);
+
+        if level <= 0.0 {
+            Self(0.0)
+        } else {
+            Self(DX7_MAX_MOD_INDEX * ((level - DX7_MAX_LEVEL) / DX7_LEVEL_STEPS_PER_OCTAVE).exp2())
+        }
+    }
+    pub fn to_patch_dx7(self) -> f32 {
+        if self.0 <= 0.0 {
+            0.0
+        } else {
+            let level =
+                DX7_MAX_LEVEL + DX7_LEVEL_STEPS_PER_OCTAVE * (self.0 / DX7_MAX_MOD_INDEX).log2();
+
+            (level / DX7_MAX_LEVEL).clamp(0.0, 1.0)
+        }
+    }
+    pub fn new_from_text_dx7(text: &str) -> Option<f32> {
+        parse_valid_f32(text, 0.0, DX7_MAX_LEVEL).map(|level| level.round() / DX7_MAX_LEVEL)
+    }
+    pub fn get_formatted_dx7(patch_value: f32) -> CompactString {
+        format_compact!("{}", Self::patch_to_dx7_level(patch_value))
+    }
+
+    fn patch_to_dx7_level(value: f32) -> f32 {
+        (value.clamp(0.0, 1.0) * DX7_MAX_LEVEL).round()
+    }
+}
+
 impl Default for OperatorModOutValue {
     fn default() -> Self {
         Self(0.0)
@@ -44,3 +89,30 @@ impl ParameterValue for OperatorModOutValue {
         SerializableRepresentation::Float(self.0.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dx7_scaling() {
+        assert_eq!(OperatorModOutValue::new_from_patch_dx7(0.0).get(), 0.0);
+        assert_eq!(
+            OperatorModOutValue::new_from_patch_dx7(1.0).get(),
+            DX7_MAX_MOD_INDEX
+        );
+        assert_eq!(OperatorModOutValue::get_formatted_dx7(1.0), "99");
+        assert_eq!(OperatorModOutValue::new_from_text_dx7("99"), Some(1.0));
+        assert_eq!(OperatorModOutValue::new_from_text_dx7("100"), Some(1.0));
+
+        for level in 1..=99 {
+            let patch_value = level as f32 / DX7_MAX_LEVEL;
+            let value = OperatorModOutValue::new_from_patch_dx7(patch_value);
+
+            assert_eq!(
+                OperatorModOutValue::get_formatted_dx7(value.to_patch_dx7()),
+                format_compact!("{}", level)
+            );
+        }
+    }
+}
@@ -57,6 +57,10 @@ impl ModTargetStorage {
         self.indices = Self::set_bit(self.indices, index, value);
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.indices == 0
+    }
+
     pub fn index_active(&self, index: usize) -> bool {
         (self.indices & (1 << index)) != 0
     }
@@ -71,6 +75,16 @@ impl ModTargetStorage {
         })
     }
 
+    /// Find permutation matching text formatted like "1, 2"
+    fn parse_permutation(permutations: &[Self], text: &str) -> Option<Self> {
+        let text = text.replace(' ', "");
+
+        permutations
+            .iter()
+            .find(|permutation| permutation.to_string().replace(' ', "") == text)
+            .copied()
+    }
+
     const fn set_bit(mut data: u8, index: usize, value: bool) -> u8 {
         if value {
             data |= 1 << index;
@@ -117,8 +131,8 @@ impl ParameterValue for Operator2ModulationTargetValue {
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
-    fn new_from_text(_text: &str) -> Option<Self> {
-        None
+    fn new_from_text(text: &str) -> Option<Self> {
+        ModTargetStorage::parse_permutation(OPERATOR_2_PERMUTATIONS, text).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -159,8 +173,8 @@ impl ParameterValue for Operator3ModulationTargetValue {
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
-    fn new_from_text(_text: &str) -> Option<Self> {
-        None
+    fn new_from_text(text: &str) -> Option<Self> {
+        ModTargetStorage::parse_permutation(OPERATOR_3_PERMUTATIONS, text).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -201,8 +215,8 @@ impl ParameterValue for Operator4ModulationTargetValue {
     fn new_from_audio(value: Self::Value) -> Self {
         Self(value)
     }
-    fn new_from_text(_text: &str) -> Option<Self> {
-        None
+    fn new_from_text(text: &str) -> Option<Self> {
+        ModTargetStorage::parse_permutation(OPERATOR_4_PERMUTATIONS, text).map(Self)
     }
     fn get(self) -> Self::Value {
         self.0
@@ -221,3 +235,124 @@ impl ParameterValue for Operator4ModulationTargetValue {
         SerializableRepresentation::Other(self.get_formatted())
     }
 }
+
+const OPERATOR_1_BACKWARD_PERMUTATIONS: &[ModTargetStorage] = &[
+    ModTargetStorage::new(&[false, false, false, false]),
+    ModTargetStorage::new(&[false, true, false, false]),
+    ModTargetStorage::new(&[false, false, true, false]),
+    ModTargetStorage::new(&[false, false, false, true]),
+    ModTargetStorage::new(&[false, true, true, false]),
+    ModTargetStorage::new(&[false, true, false, true]),
+    ModTargetStorage::new(&[false, false, true, true]),
+    ModTargetStorage::new(&[false, true, true, true]),
+];
+const OPERATOR_2_BACKWARD_PERMUTATIONS: &[ModTargetStorage] = &[
+    ModTargetStorage::new(&[false, false, false, false]),
+    ModTargetStorage::new(&[false, false, true, false]),
+    ModTargetStorage::new(&[false, false, false, true]),
+    ModTargetStorage::new(&[false, false, true, true]),
+];
+const OPERATOR_3_BACKWARD_PERMUTATIONS: &[ModTargetStorage] = &[
+    ModTargetStorage::new(&[false, false, false, false]),
+    ModTargetStorage::new(&[false, false, false, true]),
+];
+
+/// Modulation targets with higher operator index than the modulating
+/// operator. Since these operators are generated first, modulation reaches
+/// them with a delay of one sample. Indices are absolute operator indices.
+macro_rules! impl_backward_modulation_target_value {
+    ($struct_name:ident, $permutations:ident) => {
+        #[derive(Debug, Clone, Copy)]
+        pub struct $struct_name(ModTargetStorage);
+
+        impl Default for $struct_name {
+            fn default() -> Self {
+                Self($permutations[0])
+            }
+        }
+
+        impl Borrow<ModTargetStorage> for $struct_name {
+            fn borrow(&self) -> &ModTargetStorage {
+                &self.0
+            }
+        }
+
+        impl ParameterValue for $struct_name {
+            type Value = ModTargetStorage;
+
+            fn new_from_audio(value: Self::Value) -> Self {
+                Self(value)
+            }
+            fn new_from_text(text: &str) -> Option<Self> {
+                ModTargetStorage::parse_permutation($permutations, text).map(Self)
+            }
+            fn get(self) -> Self::Value {
+                self.0
+            }
+            fn new_from_patch(value: f32) -> Self {
+                Self(map_patch_value_to_step($permutations, value))
+            }
+            fn to_patch(self) -> f32 {
+                map_step_to_patch_value($permutations, self.0)
+            }
+            fn get_formatted(self) -> CompactString {
+                format_compact!("{}", self.0)
+            }
+
+            fn get_serializable(&self) -> SerializableRepresentation {
+                SerializableRepresentation::Other(self.get_formatted())
+            }
+        }
+    };
+}
+
+impl_backward_modulation_target_value!(
+    Operator1BackwardModulationTargetValue,
+    OPERATOR_1_BACKWARD_PERMUTATIONS
+);
+impl_backward_modulation_target_value!(
+    Operator2BackwardModulationTargetValue,
+    OPERATOR_2_BACKWARD_PERMUTATIONS
+);
+impl_backward_modulation_target_value!(
+    Operator3BackwardModulationTargetValue,
+    OPERATOR_3_BACKWARD_PERMUTATIONS
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backward_modulation_targets() {
+        for permutation in OPERATOR_1_BACKWARD_PERMUTATIONS {
+            let value = Operator1BackwardModulationTargetValue::new_from_audio(*permutation);
+
+            assert!(!permutation.index_active(0));
+            assert_eq!(
+                Operator1BackwardModulationTargetValue::new_from_patch(value.to_patch()).get(),
+                *permutation
+            );
+            assert_eq!(
+                Operator1BackwardModulationTargetValue::new_from_text(&value.get_formatted())
+                    .unwrap()
+                    .get(),
+                *permutation
+            );
+        }
+
+        assert!(Operator2BackwardModulationTargetValue::default()
+            .get()
+            .active_indices()
+            .next()
+            .is_none());
+        assert_eq!(
+            Operator3BackwardModulationTargetValue::new_from_text("4")
+                .unwrap()
+                .get()
+                .active_indices()
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+    }
+}
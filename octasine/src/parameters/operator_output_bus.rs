@@ -0,0 +1,48 @@
+use compact_str::CompactString;
+
+use super::{ParameterValue, SerializableRepresentation};
+
+/// Whether operator mix output is sent to its own output bus instead of the
+/// main output. Only has an effect when operator bus outputs are enabled in
+/// settings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperatorOutputBusValue(bool);
+
+impl ParameterValue for OperatorOutputBusValue {
+    type Value = bool;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "main" => Some(Self(false)),
+            "bus" => Some(Self(true)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value > 0.5)
+    }
+    fn to_patch(self) -> f32 {
+        if self.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+    fn get_formatted(self) -> CompactString {
+        if self.0 {
+            "BUS".into()
+        } else {
+            "MAIN".into()
+        }
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -12,8 +12,8 @@ impl OperatorPanningValue {
         let pan_phase = self.0 * FRAC_PI_2;
 
         [
-            ::sleef_trig::Sleef_cosf1_u35purec_range125(pan_phase),
-            ::sleef_trig::Sleef_sinf1_u35purec_range125(pan_phase),
+            crate::simd::cos(pan_phase.into()) as f32,
+            crate::simd::sin(pan_phase.into()) as f32,
         ]
     }
 }
@@ -0,0 +1,63 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const PANNING_LFO_MODE_STEPS: &[PanningLfoMode] =
+    &[PanningLfoMode::Normal, PanningLfoMode::Spread];
+
+/// How LFO modulation of operator panning is applied. In spread mode, the
+/// modulation is applied with opposite signs to the left and right
+/// channels, so that they move in opposite directions, for rotary-like
+/// motion. Spread mode has no effect when panning in mid/side space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanningLfoMode {
+    #[default]
+    Normal,
+    Spread,
+}
+
+impl ::std::fmt::Display for PanningLfoMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Normal => "Normal",
+            Self::Spread => "Spread",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperatorPanningLfoModeValue(PanningLfoMode);
+
+impl ParameterValue for OperatorPanningLfoModeValue {
+    type Value = PanningLfoMode;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "normal" => Some(Self(PanningLfoMode::Normal)),
+            "spread" => Some(Self(PanningLfoMode::Spread)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(PANNING_LFO_MODE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(PANNING_LFO_MODE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
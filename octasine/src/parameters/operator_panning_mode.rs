@@ -0,0 +1,61 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const PANNING_MODE_STEPS: &[PanningMode] = &[PanningMode::LeftRight, PanningMode::MidSide];
+
+/// Space that operator panning moves the signal in. In mid/side mode,
+/// panning fully left only keeps the mid component, while panning fully
+/// right moves the whole signal to the sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanningMode {
+    #[default]
+    LeftRight,
+    MidSide,
+}
+
+impl ::std::fmt::Display for PanningMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::LeftRight => "L/R",
+            Self::MidSide => "M/S",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperatorPanningModeValue(PanningMode);
+
+impl ParameterValue for OperatorPanningModeValue {
+    type Value = PanningMode;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "l/r" | "lr" => Some(Self(PanningMode::LeftRight)),
+            "m/s" | "ms" => Some(Self(PanningMode::MidSide)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(PANNING_MODE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(PANNING_MODE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -1,8 +1,9 @@
-use std::f32::consts::TAU;
+use std::f64::consts::TAU;
 
 use compact_str::CompactString;
 
 use crate::common::*;
+use crate::math::wave::{sine_variant_scalar, SineVariant};
 
 use super::{
     utils::{map_patch_value_to_step, map_step_to_patch_value},
@@ -15,6 +16,14 @@ const OPERATOR_WAVEFORMS: &[WaveType] = &[
     WaveType::Triangle,
     WaveType::Saw,
     WaveType::WhiteNoise,
+    WaveType::SineVariant(SineVariant::Squared),
+    WaveType::SineVariant(SineVariant::Half),
+    WaveType::SineVariant(SineVariant::HalfSquared),
+    WaveType::SineVariant(SineVariant::Alternating),
+    WaveType::SineVariant(SineVariant::AlternatingSquared),
+    WaveType::SineVariant(SineVariant::AlternatingHalf),
+    WaveType::SineVariant(SineVariant::AlternatingHalfSquared),
+    WaveType::SineVariant(SineVariant::Quarter),
 ];
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
@@ -25,15 +34,34 @@ pub enum WaveType {
     Triangle,
     Saw,
     WhiteNoise,
+    /// TX81Z-style sine variants
+    SineVariant(SineVariant),
+}
+
+impl WaveType {
+    /// Get wave type corresponding to TX81Z waveform (0-7 for W1-W8)
+    pub fn from_tx81z(wave: u8) -> Self {
+        match wave {
+            1 => Self::SineVariant(SineVariant::Squared),
+            2 => Self::SineVariant(SineVariant::Half),
+            3 => Self::SineVariant(SineVariant::HalfSquared),
+            4 => Self::SineVariant(SineVariant::Alternating),
+            5 => Self::SineVariant(SineVariant::AlternatingSquared),
+            6 => Self::SineVariant(SineVariant::AlternatingHalf),
+            7 => Self::SineVariant(SineVariant::AlternatingHalfSquared),
+            _ => Self::Sine,
+        }
+    }
 }
 
 impl WaveformChoices for WaveType {
     fn calculate_for_current(self, phase: Phase) -> f32 {
         match self {
-            Self::Sine => ::sleef_trig::Sleef_sinf1_u35purec_range125(phase.0 as f32 * TAU),
+            Self::Sine => crate::simd::sin(phase.0 * TAU) as f32,
             Self::Saw => crate::math::wave::saw(phase.0) as f32,
             Self::Triangle => crate::math::wave::triangle(phase.0) as f32,
             Self::Square => crate::math::wave::square(phase.0) as f32,
+            Self::SineVariant(variant) => sine_variant_scalar(variant, phase.0) as f32,
             Self::WhiteNoise => {
                 // Ensure same numbers are generated each time for GUI
                 // consistency. This will however break if fastrand changes
@@ -66,6 +94,26 @@ impl ParameterValue for OperatorWaveTypeValue {
             "triangle" => Some(Self(WaveType::Triangle)),
             "saw" => Some(Self(WaveType::Saw)),
             "noise" => Some(Self(WaveType::WhiteNoise)),
+            "sine squared" | "sine sq" | "w2" => {
+                Some(Self(WaveType::SineVariant(SineVariant::Squared)))
+            }
+            "half sine" | "w3" => Some(Self(WaveType::SineVariant(SineVariant::Half))),
+            "half sine squared" | "half sine sq" | "w4" => {
+                Some(Self(WaveType::SineVariant(SineVariant::HalfSquared)))
+            }
+            "alt sine" | "alternating sine" | "w5" => {
+                Some(Self(WaveType::SineVariant(SineVariant::Alternating)))
+            }
+            "alt sine squared" | "alt sine sq" | "w6" => {
+                Some(Self(WaveType::SineVariant(SineVariant::AlternatingSquared)))
+            }
+            "alt half sine" | "alternating half sine" | "w7" => {
+                Some(Self(WaveType::SineVariant(SineVariant::AlternatingHalf)))
+            }
+            "alt half sine squared" | "alt half sine sq" | "w8" => Some(Self(
+                WaveType::SineVariant(SineVariant::AlternatingHalfSquared),
+            )),
+            "quarter sine" => Some(Self(WaveType::SineVariant(SineVariant::Quarter))),
             _ => None,
         }
     }
@@ -85,6 +133,16 @@ impl ParameterValue for OperatorWaveTypeValue {
             WaveType::Triangle => "TRIANGLE".into(),
             WaveType::Saw => "SAW".into(),
             WaveType::WhiteNoise => "NOISE".into(),
+            WaveType::SineVariant(variant) => match variant {
+                SineVariant::Squared => "SINE SQ".into(),
+                SineVariant::Half => "HALF SINE".into(),
+                SineVariant::HalfSquared => "HALF SINE SQ".into(),
+                SineVariant::Alternating => "ALT SINE".into(),
+                SineVariant::AlternatingSquared => "ALT SINE SQ".into(),
+                SineVariant::AlternatingHalf => "ALT HALF SINE".into(),
+                SineVariant::AlternatingHalfSquared => "ALT HALF SINE SQ".into(),
+                SineVariant::Quarter => "QUARTER SINE".into(),
+            },
         }
     }
 
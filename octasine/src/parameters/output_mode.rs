@@ -0,0 +1,69 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const OUTPUT_MODE_STEPS: &[OutputMode] = &[
+    OutputMode::HardClip,
+    OutputMode::SoftClip,
+    OutputMode::Limiter,
+];
+
+/// Processing applied to final mix before it is sent to host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    /// Clamp output to a generous range to protect against extreme values
+    #[default]
+    HardClip,
+    /// Saturate output with tanh
+    SoftClip,
+    /// Lookahead limiter with ceiling at 0 dBFS
+    Limiter,
+}
+
+impl ::std::fmt::Display for OutputMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::HardClip => "CLIP",
+            Self::SoftClip => "SOFT",
+            Self::Limiter => "LIMIT",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputModeValue(OutputMode);
+
+impl ParameterValue for OutputModeValue {
+    type Value = OutputMode;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "clip" | "hard" | "hard clip" => Some(Self(OutputMode::HardClip)),
+            "soft" | "soft clip" | "tanh" => Some(Self(OutputMode::SoftClip)),
+            "limit" | "limiter" => Some(Self(OutputMode::Limiter)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(OUTPUT_MODE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(OUTPUT_MODE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -0,0 +1,42 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{
+        map_audio_to_patch_value_with_steps, map_patch_to_audio_value_with_steps, parse_valid_f32,
+    },
+    ParameterValue, SerializableRepresentation,
+};
+
+/// Milliseconds
+const STEPS: &[f32] = &[0.0, 10.0, 200.0];
+
+/// Pitch bend smoothing time constant in milliseconds
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PitchBendSmoothingValue(f32);
+
+impl ParameterValue for PitchBendSmoothingValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, *STEPS.last().unwrap()).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_to_audio_value_with_steps(STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_audio_to_patch_value_with_steps(STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.1} ms", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
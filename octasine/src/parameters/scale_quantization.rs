@@ -0,0 +1,233 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const SCALE_STEPS: &[Scale] = &[
+    Scale::Off,
+    Scale::Major,
+    Scale::NaturalMinor,
+    Scale::HarmonicMinor,
+    Scale::MelodicMinor,
+    Scale::Dorian,
+    Scale::Phrygian,
+    Scale::Lydian,
+    Scale::Mixolydian,
+    Scale::MajorPentatonic,
+    Scale::MinorPentatonic,
+    Scale::Blues,
+    Scale::WholeTone,
+];
+
+pub(super) const ROOT_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Scale that incoming notes are quantized to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scale {
+    #[default]
+    Off,
+    Major,
+    NaturalMinor,
+    HarmonicMinor,
+    MelodicMinor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+    WholeTone,
+}
+
+impl Scale {
+    /// Semitones above root included in scale, as bit mask
+    const fn pitch_classes(self) -> u16 {
+        const fn mask(semitones: &[u8]) -> u16 {
+            let mut mask = 0;
+            let mut i = 0;
+
+            while i < semitones.len() {
+                mask |= 1 << semitones[i];
+                i += 1;
+            }
+
+            mask
+        }
+
+        match self {
+            Self::Off => mask(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]),
+            Self::Major => mask(&[0, 2, 4, 5, 7, 9, 11]),
+            Self::NaturalMinor => mask(&[0, 2, 3, 5, 7, 8, 10]),
+            Self::HarmonicMinor => mask(&[0, 2, 3, 5, 7, 8, 11]),
+            Self::MelodicMinor => mask(&[0, 2, 3, 5, 7, 9, 11]),
+            Self::Dorian => mask(&[0, 2, 3, 5, 7, 9, 10]),
+            Self::Phrygian => mask(&[0, 1, 3, 5, 7, 8, 10]),
+            Self::Lydian => mask(&[0, 2, 4, 6, 7, 9, 11]),
+            Self::Mixolydian => mask(&[0, 2, 4, 5, 7, 9, 10]),
+            Self::MajorPentatonic => mask(&[0, 2, 4, 7, 9]),
+            Self::MinorPentatonic => mask(&[0, 3, 5, 7, 10]),
+            Self::Blues => mask(&[0, 3, 5, 6, 7, 10]),
+            Self::WholeTone => mask(&[0, 2, 4, 6, 8, 10]),
+        }
+    }
+
+    fn contains(self, root: u8, key: i16) -> bool {
+        let pitch_class = (key - i16::from(root)).rem_euclid(12);
+
+        self.pitch_classes() & (1 << pitch_class) != 0
+    }
+
+    /// Move key to nearest key in scale. On ties, the lower key is chosen.
+    pub fn quantize(self, root: u8, key: u8) -> u8 {
+        if self == Self::Off {
+            return key;
+        }
+
+        let key = i16::from(key);
+
+        for distance in 0..12 {
+            for candidate in [key - distance, key + distance] {
+                if (0..128).contains(&candidate) && self.contains(root, candidate) {
+                    return candidate as u8;
+                }
+            }
+        }
+
+        key as u8
+    }
+}
+
+impl ::std::fmt::Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "OFF",
+            Self::Major => "MAJOR",
+            Self::NaturalMinor => "MINOR",
+            Self::HarmonicMinor => "HARM MINOR",
+            Self::MelodicMinor => "MEL MINOR",
+            Self::Dorian => "DORIAN",
+            Self::Phrygian => "PHRYGIAN",
+            Self::Lydian => "LYDIAN",
+            Self::Mixolydian => "MIXOLYDIAN",
+            Self::MajorPentatonic => "MAJOR PENT",
+            Self::MinorPentatonic => "MINOR PENT",
+            Self::Blues => "BLUES",
+            Self::WholeTone => "WHOLE TONE",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScaleValue(Scale);
+
+impl ParameterValue for ScaleValue {
+    type Value = Scale;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim().to_uppercase();
+
+        SCALE_STEPS
+            .iter()
+            .find(|scale| scale.to_string() == text)
+            .map(|scale| Self(*scale))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(SCALE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(SCALE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+/// Root note of scale quantization, as semitones above C
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScaleRootValue(u8);
+
+impl ScaleRootValue {
+    const STEPS: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+}
+
+impl ParameterValue for ScaleRootValue {
+    type Value = u8;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value % 12)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim().to_uppercase();
+
+        ROOT_NAMES
+            .iter()
+            .position(|name| *name == text)
+            .map(|index| Self(index as u8))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(&Self::STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(&Self::STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        ROOT_NAMES[self.0 as usize].into()
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize() {
+        for key in 0..128 {
+            assert_eq!(Scale::Off.quantize(3, key), key);
+        }
+
+        // C major
+        assert_eq!(Scale::Major.quantize(0, 60), 60);
+        assert_eq!(Scale::Major.quantize(0, 61), 60);
+        assert_eq!(Scale::Major.quantize(0, 66), 65);
+        assert_eq!(Scale::Major.quantize(0, 127), 127);
+
+        // A minor pentatonic: A C D E G
+        assert_eq!(Scale::MinorPentatonic.quantize(9, 58), 57);
+        assert_eq!(Scale::MinorPentatonic.quantize(9, 59), 60);
+        assert_eq!(Scale::MinorPentatonic.quantize(9, 65), 64);
+        assert_eq!(Scale::MinorPentatonic.quantize(9, 66), 67);
+
+        for scale in SCALE_STEPS.iter().copied() {
+            for root in 0..12 {
+                for key in 0..128 {
+                    let quantized = scale.quantize(root, key);
+
+                    assert!(scale.contains(root, quantized.into()));
+                    assert!((i16::from(key) - i16::from(quantized)).abs() <= 2);
+                }
+            }
+        }
+    }
+}
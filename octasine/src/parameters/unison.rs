@@ -0,0 +1,169 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value, parse_valid_f32},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const MAX_UNISON_VOICES: usize = 8;
+
+const UNISON_VOICES_STEPS: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8];
+
+/// Maximum unison detune in cents
+const MAX_DETUNE: f32 = 100.0;
+
+/// Number of voices stacked on the active note in fat mono voice mode
+#[derive(Debug, Clone, Copy)]
+pub struct UnisonVoicesValue(u8);
+
+impl Default for UnisonVoicesValue {
+    fn default() -> Self {
+        Self(3)
+    }
+}
+
+impl ParameterValue for UnisonVoicesValue {
+    type Value = u8;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let value: u8 = text.split_whitespace().next()?.parse().ok()?;
+
+        Some(Self(value.clamp(1, MAX_UNISON_VOICES as u8)))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(UNISON_VOICES_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(UNISON_VOICES_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Pitch difference in cents between outermost unison voices
+#[derive(Debug, Clone, Copy)]
+pub struct UnisonDetuneValue(f32);
+
+impl Default for UnisonDetuneValue {
+    fn default() -> Self {
+        Self(20.0)
+    }
+}
+
+impl ParameterValue for UnisonDetuneValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, MAX_DETUNE).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value * MAX_DETUNE)
+    }
+    fn to_patch(self) -> f32 {
+        self.0 / MAX_DETUNE
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.1} cents", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Stereo width of unison voices. At 1.0, outermost voices are panned
+/// hard left and right.
+#[derive(Debug, Clone, Copy)]
+pub struct UnisonSpreadValue(f32);
+
+impl Default for UnisonSpreadValue {
+    fn default() -> Self {
+        Self(0.5)
+    }
+}
+
+impl ParameterValue for UnisonSpreadValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim();
+
+        if let Some(percent) = text.strip_suffix('%') {
+            parse_valid_f32(percent, 0.0, 100.0).map(|v| Self(v / 100.0))
+        } else {
+            parse_valid_f32(text, 0.0, 1.0).map(Self)
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.0}%", self.0 * 100.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Position of unison voice within stack, from -1.0 to 1.0
+pub fn unison_voice_position(voice_index: usize, num_voices: usize) -> f32 {
+    if num_voices <= 1 {
+        0.0
+    } else {
+        (voice_index as f32 / (num_voices - 1) as f32) * 2.0 - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unison_voice_position() {
+        assert_eq!(unison_voice_position(0, 1), 0.0);
+
+        assert_eq!(unison_voice_position(0, 2), -1.0);
+        assert_eq!(unison_voice_position(1, 2), 1.0);
+
+        assert_eq!(unison_voice_position(0, 3), -1.0);
+        assert_eq!(unison_voice_position(1, 3), 0.0);
+        assert_eq!(unison_voice_position(2, 3), 1.0);
+    }
+
+    #[test]
+    fn test_unison_spread_from_text() {
+        assert_eq!(UnisonSpreadValue::new_from_text("50%").unwrap().get(), 0.5);
+        assert_eq!(
+            UnisonSpreadValue::new_from_text("0.25").unwrap().get(),
+            0.25
+        );
+        assert_eq!(UnisonSpreadValue::new_from_text("200%").unwrap().get(), 1.0);
+    }
+}
@@ -1,3 +1,10 @@
+use std::ops::Div;
+use std::str::FromStr;
+
+use compact_str::{format_compact, CompactString};
+
+use super::scale_quantization::ROOT_NAMES;
+
 pub fn map_patch_value_to_step<T: Copy>(steps: &[T], value: f32) -> T {
     let value = value.max(0.0).min(1.0);
     let len = steps.len();
@@ -75,8 +82,31 @@ pub fn round_to_step(steps: &[f32], value: f32) -> f32 {
     *steps.last().expect("steps are empty")
 }
 
+/// Parse leading number of text, ignoring trailing unit, e.g., "Hz" in
+/// "440.00 Hz" or "440Hz". Fractions such as "1/4" are accepted too.
+fn parse_number<T>(text: &str) -> Option<T>
+where
+    T: FromStr + Div<Output = T>,
+{
+    let token = text.split_whitespace().next()?;
+
+    if let Ok(value) = token.parse() {
+        return Some(value);
+    }
+
+    let number_end = token
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | '/')))
+        .unwrap_or(token.len());
+    let number = &token[..number_end];
+
+    match number.split_once('/') {
+        Some((a, b)) => Some(a.parse::<T>().ok()? / b.parse::<T>().ok()?),
+        None => number.parse().ok(),
+    }
+}
+
 pub fn parse_valid_f32(text: &str, min: f32, max: f32) -> Option<f32> {
-    let value: f32 = text.parse().ok()?;
+    let value: f32 = parse_number(text)?;
 
     if value.is_infinite() | value.is_nan() {
         None
@@ -86,7 +116,7 @@ pub fn parse_valid_f32(text: &str, min: f32, max: f32) -> Option<f32> {
 }
 
 pub fn parse_valid_f64(text: &str, min: f64, max: f64) -> Option<f64> {
-    let value: f64 = text.parse().ok()?;
+    let value: f64 = parse_number(text)?;
 
     if value.is_infinite() | value.is_nan() {
         None
@@ -95,6 +125,152 @@ pub fn parse_valid_f64(text: &str, min: f64, max: f64) -> Option<f64> {
     }
 }
 
+/// Parse frequency in Hz, given either as a number (e.g., "440 Hz") or as a
+/// key name with optional cent offset (e.g., "A4" or "C#3 +12c"), clamped to
+/// given range
+pub fn parse_frequency(text: &str, min: f64, max: f64) -> Option<f64> {
+    match parse_key_name_with_cents(text) {
+        Some((key, cents)) => {
+            let frequency = key_to_frequency(key) * (cents / 1200.0).exp2();
+
+            Some(frequency.clamp(min, max))
+        }
+        None => parse_valid_f64(text, min, max),
+    }
+}
+
+/// Format frequency in Hz as closest key name with cent offset, e.g.,
+/// "A4 +8c" for 442 Hz
+pub fn format_frequency_as_key(frequency: f64) -> CompactString {
+    let key_float = 69.0 + 12.0 * (frequency / 440.0).log2();
+    let key = key_float.round().clamp(0.0, 127.0);
+    let cents = ((key_float - key) * 100.0).round();
+
+    let key_name = format_key(key as u8);
+
+    // Prevent display of negative zero
+    if cents.abs() < 0.5 {
+        key_name
+    } else {
+        format_compact!("{} {:+.0}c", key_name, cents)
+    }
+}
+
+/// Frequency of MIDI key in Hz in twelve-tone equal temperament, with A4
+/// (key 69) at 440 Hz
+fn key_to_frequency(key: u8) -> f64 {
+    440.0 * ((f64::from(key) - 69.0) / 12.0).exp2()
+}
+
+/// Parse key name with optional cent offset, e.g., "A4", "C#3 +12c" or
+/// "E2-5 cents"
+fn parse_key_name_with_cents(text: &str) -> Option<(u8, f64)> {
+    let text = text.trim().to_lowercase();
+
+    let octave_start = text.find(|c: char| c == '-' || c.is_ascii_digit())?;
+    let digits_start = octave_start + usize::from(text[octave_start..].starts_with('-'));
+    let octave_end = text[digits_start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(text.len(), |i| digits_start + i);
+
+    let key = parse_key_name(&text[..octave_end])?;
+
+    let cents_text = text[octave_end..].trim();
+
+    if cents_text.is_empty() {
+        return Some((key, 0.0));
+    }
+
+    let cents_text = ["cents", "cent", "ct", "c"]
+        .into_iter()
+        .find_map(|suffix| cents_text.strip_suffix(suffix))
+        .unwrap_or(cents_text);
+    let cents: f64 = cents_text.trim().parse().ok()?;
+
+    cents.is_finite().then_some((key, cents))
+}
+
+/// Parse key name such as "C4" or "F#2" (C4 being MIDI key 60) or MIDI key
+/// number
+pub fn parse_key(text: &str) -> Option<u8> {
+    if let Ok(key) = text.trim().parse::<u8>() {
+        return Some(key.min(127));
+    }
+
+    parse_key_name(text)
+}
+
+/// Parse key name such as "C4" or "F#2", with C4 being MIDI key 60
+fn parse_key_name(text: &str) -> Option<u8> {
+    let text = text.trim().to_uppercase();
+
+    let octave_start = text.find(|c: char| c == '-' || c.is_ascii_digit())?;
+    let (name, octave) = text.split_at(octave_start);
+
+    let pitch_class = ROOT_NAMES.iter().position(|n| *n == name)? as i16;
+    let octave: i16 = octave.parse().ok()?;
+
+    let key = (octave + 1) * 12 + pitch_class;
+
+    if (0..128).contains(&key) {
+        Some(key as u8)
+    } else {
+        None
+    }
+}
+
+/// Format MIDI key as key name, with C4 being key 60
+pub fn format_key(key: u8) -> CompactString {
+    format_compact!(
+        "{}{}",
+        ROOT_NAMES[(key % 12) as usize],
+        i16::from(key / 12) - 1
+    )
+}
+
+/// Format linear amplitude factor as decibels
+pub fn format_linear_as_db(value: f32) -> CompactString {
+    if value <= 0.0 {
+        return "-inf dB".into();
+    }
+
+    let db = 20.0 * value.log10();
+
+    // Prevent display of negative zero
+    if db.abs() < 0.005 {
+        "0.00 dB".into()
+    } else if db.abs() < 10.0 {
+        format_compact!("{:.2} dB", db)
+    } else {
+        format_compact!("{:.1} dB", db)
+    }
+}
+
+/// Parse decibel text such as "-6" or "-12.5 dB" into a linear amplitude
+/// factor, clamped to given range
+pub fn parse_db_as_linear(text: &str, min: f32, max: f32) -> Option<f32> {
+    let text = text.trim().to_lowercase();
+    let text = text.strip_suffix("db").unwrap_or(&text).trim();
+
+    let db: f32 = text.parse().ok()?;
+
+    if db.is_nan() {
+        return None;
+    }
+
+    let value = if db == f32::NEG_INFINITY {
+        0.0
+    } else {
+        10.0f32.powf(db / 20.0)
+    };
+
+    if value.is_infinite() {
+        Some(max)
+    } else {
+        Some(value.min(max).max(min))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_approx_eq::assert_approx_eq;
@@ -289,4 +465,72 @@ mod tests {
 
         quickcheck(prop as fn(f32, f32, f32) -> TestResult);
     }
+
+    #[test]
+    fn test_parse_number_with_unit_or_fraction() {
+        assert_eq!(parse_valid_f32("440.00 Hz", 0.0, 1000.0), Some(440.0));
+        assert_eq!(parse_valid_f32("440Hz", 0.0, 1000.0), Some(440.0));
+        assert_eq!(parse_valid_f32("  12.5cents ", 0.0, 100.0), Some(12.5));
+        assert_eq!(parse_valid_f32("-3 SEMIS", -12.0, 12.0), Some(-3.0));
+        assert_eq!(parse_valid_f64("1/4", 0.0, 1.0), Some(0.25));
+        assert_eq!(parse_valid_f64("3/2x", 0.0, 2.0), Some(1.5));
+        assert_eq!(parse_valid_f64("1/0", 0.0, 2.0), None);
+        assert_eq!(parse_valid_f32("Hz", 0.0, 1000.0), None);
+    }
+
+    #[test]
+    fn test_parse_frequency() {
+        assert_eq!(parse_frequency("A4", 20.0, 20_000.0), Some(440.0));
+        assert_eq!(parse_frequency("a3", 20.0, 20_000.0), Some(220.0));
+        assert_approx_eq!(
+            parse_frequency("C4", 20.0, 20_000.0).unwrap(),
+            261.6256,
+            0.001
+        );
+        assert_eq!(parse_frequency("442 Hz", 20.0, 20_000.0), Some(442.0));
+        assert_eq!(parse_frequency("C-1", 20.0, 20_000.0), Some(20.0));
+        assert_eq!(parse_frequency("H4", 20.0, 20_000.0), None);
+        assert_approx_eq!(
+            parse_frequency("A4 +8c", 20.0, 20_000.0).unwrap(),
+            442.0,
+            0.05
+        );
+        assert_approx_eq!(
+            parse_frequency("a4-100cents", 20.0, 20_000.0).unwrap(),
+            415.3047,
+            0.001
+        );
+        assert_eq!(parse_frequency("A4 +12 semis", 20.0, 20_000.0), None);
+    }
+
+    #[test]
+    fn test_format_frequency_as_key() {
+        assert_eq!(format_frequency_as_key(440.0), "A4");
+        assert_eq!(format_frequency_as_key(442.0), "A4 +8c");
+        assert_eq!(format_frequency_as_key(435.0), "A4 -20c");
+        assert_eq!(format_frequency_as_key(261.6256), "C4");
+
+        for text in ["A4", "A4 +8c", "C#3 -45c", "G-1 +3c"] {
+            let frequency = parse_frequency(text, 0.0, 20_000.0).unwrap();
+
+            assert_eq!(format_frequency_as_key(frequency), text);
+        }
+    }
+
+    #[test]
+    fn test_db_formatting_and_parsing() {
+        assert_eq!(format_linear_as_db(1.0), "0.00 dB");
+        assert_eq!(format_linear_as_db(0.9999), "0.00 dB");
+        assert_eq!(format_linear_as_db(0.5), "-6.02 dB");
+        assert_eq!(format_linear_as_db(0.1), "-20.0 dB");
+        assert_eq!(format_linear_as_db(0.0), "-inf dB");
+
+        assert_approx_eq!(parse_db_as_linear("0", 0.0, 2.0).unwrap(), 1.0);
+        assert_approx_eq!(parse_db_as_linear("-6", 0.0, 2.0).unwrap(), 0.501187);
+        assert_approx_eq!(parse_db_as_linear("-12.5 dB", 0.0, 2.0).unwrap(), 0.237137);
+        assert_approx_eq!(parse_db_as_linear("-6.02dB", 0.0, 2.0).unwrap(), 0.5, 0.001);
+        assert_eq!(parse_db_as_linear("-inf dB", 0.0, 2.0), Some(0.0));
+        assert_eq!(parse_db_as_linear("+20", 0.0, 2.0), Some(2.0));
+        assert_eq!(parse_db_as_linear("abc", 0.0, 2.0), None);
+    }
 }
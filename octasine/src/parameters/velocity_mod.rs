@@ -0,0 +1,170 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    lfo_target::{parse_lfo_target, LfoTargetParameter, LFO_TARGETS},
+    utils::*,
+    Parameter, ParameterValue, SerializableRepresentation,
+};
+
+/// Parameter modulated by note velocity. Uses the same target list as LFOs.
+#[derive(Debug, Clone, Copy)]
+pub struct VelocityModTargetValue(pub LfoTargetParameter);
+
+impl Default for VelocityModTargetValue {
+    fn default() -> Self {
+        Self(LfoTargetParameter::new(Parameter::None))
+    }
+}
+
+impl ParameterValue for VelocityModTargetValue {
+    type Value = LfoTargetParameter;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_lfo_target(LFO_TARGETS, text).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(LFO_TARGETS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(LFO_TARGETS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        self.0.parameter().name()
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+/// Bipolar velocity modulation depth. At full velocity, the target patch
+/// value is offset by this amount.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VelocityModAmountValue(f32);
+
+impl ParameterValue for VelocityModAmountValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, -1.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value * 2.0 - 1.0)
+    }
+    fn to_patch(self) -> f32 {
+        (self.0 + 1.0) * 0.5
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:+.04}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parameters::{LfoParameter, MasterParameter};
+
+    use super::*;
+
+    #[test]
+    fn test_velocity_mod_target() {
+        assert_eq!(
+            VelocityModTargetValue::default().get().parameter(),
+            Parameter::None
+        );
+
+        for target in LFO_TARGETS.iter().copied() {
+            let value = VelocityModTargetValue::new_from_audio(target);
+
+            assert_eq!(
+                VelocityModTargetValue::new_from_patch(value.to_patch()).get(),
+                target
+            );
+            assert_eq!(
+                VelocityModTargetValue::new_from_text(&value.get_formatted())
+                    .unwrap()
+                    .get(),
+                target
+            );
+        }
+
+        assert_eq!(
+            VelocityModTargetValue::new_from_text(" master PAN ")
+                .unwrap()
+                .get()
+                .parameter(),
+            Parameter::Master(MasterParameter::Pan)
+        );
+        assert_eq!(
+            VelocityModTargetValue::new_from_text("LFO 1 amount").map(|v| v.get().parameter()),
+            Some(Parameter::Lfo(0, LfoParameter::Amount))
+        );
+        assert!(VelocityModTargetValue::new_from_text("Not a parameter").is_none());
+    }
+
+    #[test]
+    fn test_velocity_mod_amount() {
+        assert_eq!(VelocityModAmountValue::default().get(), 0.0);
+        assert_eq!(VelocityModAmountValue::default().to_patch(), 0.5);
+
+        assert_eq!(VelocityModAmountValue::new_from_patch(0.0).get(), -1.0);
+        assert_eq!(VelocityModAmountValue::new_from_patch(0.5).get(), 0.0);
+        assert_eq!(VelocityModAmountValue::new_from_patch(1.0).get(), 1.0);
+
+        for value in [-1.0, -0.25, 0.0, 0.75, 1.0] {
+            let patch_value = VelocityModAmountValue::new_from_audio(value).to_patch();
+
+            assert_eq!(
+                VelocityModAmountValue::new_from_patch(patch_value).get(),
+                value
+            );
+        }
+
+        assert_eq!(
+            VelocityModAmountValue::new_from_audio(-0.5).get_formatted(),
+            "-0.5000"
+        );
+        assert_eq!(
+            VelocityModAmountValue::new_from_audio(0.5).get_formatted(),
+            "+0.5000"
+        );
+
+        assert_eq!(
+            VelocityModAmountValue::new_from_text("+0.5000").map(|v| v.get()),
+            Some(0.5)
+        );
+        assert_eq!(
+            VelocityModAmountValue::new_from_text("-1").map(|v| v.get()),
+            Some(-1.0)
+        );
+        assert_eq!(
+            VelocityModAmountValue::new_from_text("1.0").map(|v| v.get()),
+            Some(1.0)
+        );
+        assert_eq!(
+            VelocityModAmountValue::new_from_text("1.5").map(|v| v.get()),
+            Some(1.0)
+        );
+        assert_eq!(
+            VelocityModAmountValue::new_from_text("-1.01").map(|v| v.get()),
+            Some(-1.0)
+        );
+        assert!(VelocityModAmountValue::new_from_text("inf").is_none());
+        assert!(VelocityModAmountValue::new_from_text("abc").is_none());
+    }
+}
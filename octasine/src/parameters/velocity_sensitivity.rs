@@ -37,3 +37,134 @@ impl ParameterValue for VelocitySensitivityValue {
         SerializableRepresentation::Float(self.0.into())
     }
 }
+
+/// Velocity sensitivity of envelope attack peak. Defaults to zero, meaning
+/// that attack always reaches full volume.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AttackVelocitySensitivityValue(f32);
+
+impl ParameterValue for AttackVelocitySensitivityValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.04}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Velocity sensitivity of operator mix out. Defaults to zero, so that
+/// carrier loudness only depends on master volume velocity sensitivity.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MixOutVelocitySensitivityValue(f32);
+
+impl ParameterValue for MixOutVelocitySensitivityValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.04}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Maximum factor that release velocity can shorten or lengthen release
+/// duration by
+const MAX_RELEASE_DURATION_FACTOR: f32 = 4.0;
+
+/// Sensitivity of release duration to note-off velocity. Fast key releases
+/// shorten release and slow ones lengthen it, with velocity 64 (default for
+/// keyboards that don't transmit release velocity) leaving it unchanged.
+/// Defaults to zero.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReleaseVelocitySensitivityValue(f32);
+
+impl ReleaseVelocitySensitivityValue {
+    /// Factor to multiply release durations by for release velocity in
+    /// range 0.0 to 1.0
+    pub fn release_duration_factor(self, release_velocity: f32) -> f32 {
+        let exponent = self.0 * (1.0 - release_velocity.clamp(0.0, 1.0) * 2.0);
+
+        MAX_RELEASE_DURATION_FACTOR.powf(exponent)
+    }
+}
+
+impl ParameterValue for ReleaseVelocitySensitivityValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.04}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_duration_factor() {
+        let full = ReleaseVelocitySensitivityValue::new_from_audio(1.0);
+
+        assert_eq!(full.release_duration_factor(0.0), 4.0);
+        assert_eq!(full.release_duration_factor(0.5), 1.0);
+        assert_eq!(full.release_duration_factor(1.0), 0.25);
+
+        let off = ReleaseVelocitySensitivityValue::default();
+
+        assert_eq!(off.release_duration_factor(0.0), 1.0);
+        assert_eq!(off.release_duration_factor(1.0), 1.0);
+    }
+}
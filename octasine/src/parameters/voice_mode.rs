@@ -1,17 +1,39 @@
-use compact_str::CompactString;
+use compact_str::{format_compact, CompactString};
 
 use super::{
     utils::{map_patch_value_to_step, map_step_to_patch_value},
     ParameterValue, SerializableRepresentation,
 };
 
-const STEPS: &[VoiceMode] = &[VoiceMode::Polyphonic, VoiceMode::Monophonic];
+pub const VOICE_MODE_STEPS: &[VoiceMode] = &[
+    VoiceMode::Polyphonic,
+    VoiceMode::Monophonic,
+    VoiceMode::FatMono,
+];
 
-#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum VoiceMode {
     #[default]
     Polyphonic,
     Monophonic,
+    /// Monophonic, with unison voices stacked on the active note
+    FatMono,
+}
+
+impl VoiceMode {
+    pub fn is_monophonic(self) -> bool {
+        matches!(self, Self::Monophonic | Self::FatMono)
+    }
+}
+
+impl ::std::fmt::Display for VoiceMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Polyphonic => "POLY",
+            Self::Monophonic => "MONO",
+            Self::FatMono => "FAT",
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -26,7 +48,9 @@ impl ParameterValue for VoiceModeValue {
     fn new_from_text(text: &str) -> Option<Self> {
         let text = text.to_lowercase();
 
-        if text.contains("poly") {
+        if text.contains("fat") {
+            Some(Self(VoiceMode::FatMono))
+        } else if text.contains("poly") {
             Some(Self(VoiceMode::Polyphonic))
         } else if text.contains("mono") {
             Some(Self(VoiceMode::Monophonic))
@@ -38,16 +62,13 @@ impl ParameterValue for VoiceModeValue {
         self.0
     }
     fn new_from_patch(value: f32) -> Self {
-        Self(map_patch_value_to_step(&STEPS[..], value))
+        Self(map_patch_value_to_step(VOICE_MODE_STEPS, value))
     }
     fn to_patch(self) -> f32 {
-        map_step_to_patch_value(&STEPS[..], self.0)
+        map_step_to_patch_value(VOICE_MODE_STEPS, self.0)
     }
     fn get_formatted(self) -> CompactString {
-        match self.0 {
-            VoiceMode::Polyphonic => "POLY".into(),
-            VoiceMode::Monophonic => "MONO".into(),
-        }
+        format_compact!("{}", self.0)
     }
 
     fn get_serializable(&self) -> SerializableRepresentation {
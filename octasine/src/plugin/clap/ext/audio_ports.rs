@@ -6,32 +6,48 @@ use clap_sys::{
     plugin::clap_plugin,
 };
 
-pub extern "C" fn count(_plugin: *const clap_plugin, is_input: bool) -> u32 {
+use crate::{common::NUM_OPERATORS, plugin::clap::plugin::OctaSine};
+
+use super::params::make_c_char_arr;
+
+pub unsafe extern "C" fn count(plugin: *const clap_plugin, is_input: bool) -> u32 {
+    let plugin = &*((*plugin).plugin_data as *const OctaSine);
+
     if is_input {
         0
+    } else if plugin.operator_bus_outputs {
+        // Main output and one bus per operator
+        1 + NUM_OPERATORS as u32
     } else {
         1
     }
 }
 pub unsafe extern "C" fn get(
-    _plugin: *const clap_plugin,
+    plugin: *const clap_plugin,
     index: u32,
     is_input: bool,
     info: *mut clap_audio_port_info,
 ) -> bool {
-    if index == 0 && !is_input {
-        let info = &mut *info;
+    if is_input || index >= count(plugin, is_input) {
+        return false;
+    }
 
-        info.id = 0;
-        info.channel_count = 2;
-        info.flags = CLAP_AUDIO_PORT_IS_MAIN;
-        info.port_type = CLAP_PORT_STEREO.as_ptr();
-        info.in_place_pair = CLAP_INVALID_ID;
+    let info = &mut *info;
+
+    info.id = index;
+    info.channel_count = 2;
+    info.port_type = CLAP_PORT_STEREO.as_ptr();
+    info.in_place_pair = CLAP_INVALID_ID;
 
-        true
+    if index == 0 {
+        info.name = make_c_char_arr("Main");
+        info.flags = CLAP_AUDIO_PORT_IS_MAIN;
     } else {
-        return false;
+        info.name = make_c_char_arr(&format!("OP {}", index));
+        info.flags = 0;
     }
+
+    true
 }
 
 pub const CONFIG: clap_plugin_audio_ports = clap_plugin_audio_ports {
@@ -0,0 +1,13 @@
+use std::sync::atomic::Ordering;
+
+use clap_sys::{ext::latency::clap_plugin_latency, plugin::clap_plugin};
+
+use crate::plugin::clap::plugin::OctaSine;
+
+pub const CONFIG: clap_plugin_latency = clap_plugin_latency { get: Some(get) };
+
+unsafe extern "C" fn get(plugin: *const clap_plugin) -> u32 {
+    let plugin = &*((*plugin).plugin_data as *const OctaSine);
+
+    plugin.reported_latency.load(Ordering::Relaxed)
+}
@@ -1,6 +1,8 @@
 pub mod audio_ports;
 pub mod gui;
+pub mod latency;
 pub mod note_ports;
 pub mod params;
 pub mod state;
+pub mod thread_pool;
 pub mod voice_info;
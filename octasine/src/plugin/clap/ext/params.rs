@@ -2,13 +2,19 @@ use std::ffi::{c_char, c_void, CStr, CString};
 
 use clap_sys::{
     events::{clap_input_events, clap_output_events},
-    ext::params::{clap_param_info, clap_plugin_params, CLAP_PARAM_IS_AUTOMATABLE},
+    ext::params::{
+        clap_param_info, clap_plugin_params, CLAP_PARAM_IS_AUTOMATABLE, CLAP_PARAM_IS_BYPASS,
+        CLAP_PARAM_IS_STEPPED,
+    },
     plugin::clap_plugin,
 };
 
-use crate::{parameters::ParameterKey, plugin::clap::plugin::OctaSine};
+use crate::{
+    parameters::{MasterParameter, Parameter, ParameterKey},
+    plugin::clap::plugin::OctaSine,
+};
 
-fn make_c_char_arr<const N: usize>(text: &str) -> [c_char; N] {
+pub(super) fn make_c_char_arr<const N: usize>(text: &str) -> [c_char; N] {
     let text = CString::new(text).unwrap();
     let text: &[c_char] = bytemuck::cast_slice(text.as_bytes_with_nul());
 
@@ -39,9 +45,18 @@ pub unsafe extern "C" fn get_info(
         .patches
         .get_parameter_by_index(param_index as usize)
     {
+        // Let host use bypass parameter instead of its own bypass, so that
+        // output is faded out and in
+        let flags = if parameter.parameter.parameter() == Parameter::Master(MasterParameter::Bypass)
+        {
+            CLAP_PARAM_IS_AUTOMATABLE | CLAP_PARAM_IS_BYPASS | CLAP_PARAM_IS_STEPPED
+        } else {
+            CLAP_PARAM_IS_AUTOMATABLE
+        };
+
         *param_info = clap_param_info {
             id: parameter.parameter.key().0,
-            flags: CLAP_PARAM_IS_AUTOMATABLE,
+            flags,
             cookie: param_index as usize as *mut c_void,
             name: make_c_char_arr(&parameter.name),
             module: make_c_char_arr(&parameter.clap_path),
@@ -94,7 +109,13 @@ pub unsafe extern "C" fn value_to_text(
         .patches
         .get_parameter_by_key(&ParameterKey(param_id))
     {
-        if let Ok(text) = CString::new((parameter.format)(value as f32).as_str()) {
+        let text = plugin
+            .sync
+            .patches
+            .get_current_patch()
+            .format_parameter_value(parameter, value as f32);
+
+        if let Ok(text) = CString::new(text.as_str()) {
             let bytes = text.as_bytes_with_nul();
 
             if bytes.len() > c_str_len as usize {
@@ -126,7 +147,12 @@ pub unsafe extern "C" fn text_to_value(
         .get_parameter_by_key(&ParameterKey(param_id))
     {
         if let Ok(text) = CStr::from_ptr(text).to_str() {
-            if let Some(v) = (parameter.value_from_text)(text) {
+            if let Some(v) = plugin
+                .sync
+                .patches
+                .get_current_patch()
+                .parse_parameter_text(parameter, text)
+            {
                 *value = v as f64;
             }
 
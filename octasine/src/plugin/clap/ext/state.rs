@@ -30,6 +30,8 @@ unsafe extern "C" fn save(plugin: *const clap_plugin, stream: *const clap_ostrea
 
     let mut bytes = plugin.sync.patches.export_plain_bytes();
 
+    plugin.sync.append_gui_state(&mut bytes);
+
     // Add format version as first byte for future proofing
     bytes.insert(0, VERSION);
 
@@ -93,7 +95,7 @@ unsafe extern "C" fn load(plugin: *const clap_plugin, stream: *const clap_istrea
     // Remove first byte, it is the version signifier
     let full_buffer = &full_buffer[1..];
 
-    match plugin.sync.patches.import_bank_from_bytes(full_buffer) {
+    match plugin.sync.import_state_from_bytes(full_buffer) {
         Ok(()) => true,
         Err(err) => {
             ::log::error!("load OctaSineClapState: {:#}", err);
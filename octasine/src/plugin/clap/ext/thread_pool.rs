@@ -0,0 +1,106 @@
+use std::{
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use clap_sys::{
+    ext::thread_pool::{clap_host_thread_pool, clap_plugin_thread_pool},
+    host::clap_host,
+    plugin::clap_plugin,
+};
+
+use crate::{audio::tasks::TaskExecutor, plugin::clap::plugin::OctaSine};
+
+pub const CONFIG: clap_plugin_thread_pool = clap_plugin_thread_pool { exec: Some(exec) };
+
+/// Called by host worker threads while `request_exec` is blocking in
+/// `ClapThreadPool::execute`
+unsafe extern "C" fn exec(plugin: *const clap_plugin, task_index: u32) {
+    let plugin = &*((*plugin).plugin_data as *const OctaSine);
+
+    plugin.exec_thread_pool_task(task_index as usize);
+}
+
+/// Tasks of request currently being executed on host thread pool
+struct ThreadPoolRequest<'a> {
+    task: &'a (dyn Fn(usize) + Sync),
+}
+
+/// Task executor running tasks on host thread pool, used for rendering
+/// voices and timbres in parallel. Declines to run tasks if host doesn't
+/// provide the thread pool extension.
+pub struct ClapThreadPool {
+    host: *const clap_host,
+    /// Host thread pool extension, if provided. Set in plugin `init`.
+    host_thread_pool: AtomicPtr<clap_host_thread_pool>,
+    /// Request currently being executed, if any
+    request: AtomicPtr<ThreadPoolRequest<'static>>,
+}
+
+// Host pointer is only used for requesting execution, which host must
+// support from the audio thread
+unsafe impl Send for ClapThreadPool {}
+unsafe impl Sync for ClapThreadPool {}
+
+impl ClapThreadPool {
+    pub fn new(host: *const clap_host) -> Self {
+        Self {
+            host,
+            host_thread_pool: AtomicPtr::new(null_mut()),
+            request: AtomicPtr::new(null_mut()),
+        }
+    }
+
+    pub fn set_host_thread_pool(&self, host_thread_pool: *mut clap_host_thread_pool) {
+        self.host_thread_pool
+            .store(host_thread_pool, Ordering::Relaxed);
+    }
+
+    /// Run task with given index of request currently being executed
+    ///
+    /// # Safety
+    ///
+    /// Must only be called by host while request is being executed
+    pub unsafe fn exec(&self, task_index: usize) {
+        let request = self.request.load(Ordering::Acquire);
+
+        if request.is_null() {
+            ::log::error!("thread pool task executed outside of request");
+
+            return;
+        }
+
+        ((*request).task)(task_index);
+    }
+}
+
+impl TaskExecutor for ClapThreadPool {
+    fn execute(&self, num_tasks: usize, task: &(dyn Fn(usize) + Sync)) -> bool {
+        let host_thread_pool = self.host_thread_pool.load(Ordering::Relaxed);
+
+        if host_thread_pool.is_null() || num_tasks == 0 {
+            return false;
+        }
+
+        let request_exec = if let Some(request_exec) = unsafe { (*host_thread_pool).request_exec } {
+            request_exec
+        } else {
+            return false;
+        };
+
+        let mut request = ThreadPoolRequest { task };
+
+        let request_ptr: *mut ThreadPoolRequest = &mut request;
+
+        // Request only lives for the duration of this call, so pointer is
+        // cleared before returning
+        self.request.store(request_ptr.cast(), Ordering::Release);
+
+        // Blocks until all tasks have been executed
+        let executed = unsafe { request_exec(self.host, num_tasks as u32) };
+
+        self.request.store(null_mut(), Ordering::Release);
+
+        executed
+    }
+}
@@ -1,8 +1,13 @@
 use std::{
     ffi::{c_char, c_void, CStr},
     mem::{size_of, MaybeUninit},
+    panic::{catch_unwind, AssertUnwindSafe},
     ptr::{null, null_mut},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    time::Instant,
 };
 
 use atomic_refcell::AtomicRefCell;
@@ -10,8 +15,8 @@ use clap_sys::{
     events::{
         clap_event_header, clap_event_midi, clap_event_note, clap_event_note_expression,
         clap_event_param_gesture, clap_event_param_value, clap_event_transport, clap_output_events,
-        CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_IS_LIVE, CLAP_EVENT_MIDI, CLAP_EVENT_NOTE_END,
-        CLAP_EVENT_NOTE_EXPRESSION, CLAP_EVENT_NOTE_OFF, CLAP_EVENT_NOTE_ON,
+        CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_IS_LIVE, CLAP_EVENT_MIDI, CLAP_EVENT_NOTE_CHOKE,
+        CLAP_EVENT_NOTE_END, CLAP_EVENT_NOTE_EXPRESSION, CLAP_EVENT_NOTE_OFF, CLAP_EVENT_NOTE_ON,
         CLAP_EVENT_PARAM_GESTURE_BEGIN, CLAP_EVENT_PARAM_GESTURE_END, CLAP_EVENT_PARAM_VALUE,
         CLAP_EVENT_TRANSPORT, CLAP_NOTE_EXPRESSION_PRESSURE, CLAP_TRANSPORT_HAS_TEMPO,
     },
@@ -19,9 +24,11 @@ use clap_sys::{
         audio_ports::CLAP_EXT_AUDIO_PORTS,
         draft::voice_info::CLAP_EXT_VOICE_INFO,
         gui::CLAP_EXT_GUI,
+        latency::{clap_host_latency, CLAP_EXT_LATENCY},
         note_ports::CLAP_EXT_NOTE_PORTS,
         params::{clap_host_params, CLAP_EXT_PARAMS, CLAP_PARAM_RESCAN_VALUES},
         state::{clap_host_state, CLAP_EXT_STATE},
+        thread_pool::{clap_host_thread_pool, CLAP_EXT_THREAD_POOL},
     },
     host::clap_host,
     plugin::clap_plugin,
@@ -33,14 +40,24 @@ use parking_lot::Mutex;
 use ringbuf::{Consumer, Producer, Rb, SharedRb};
 
 use crate::{
-    audio::{gen::process_f32_runtime_select, AudioState},
-    common::{BeatsPerMinute, EventToHost, NoteEvent, NoteEventInner, SampleRate},
-    parameters::ParameterKey,
-    sync::SyncState,
-    utils::{init_logging, update_audio_parameters},
+    audio::{
+        gen::process_f32_runtime_select_with_operator_buses,
+        tasks::{TaskExecutor, TaskSlice},
+        timbres::Timbre,
+        AudioState,
+    },
+    common::{BeatsPerMinute, EventToHost, NoteEvent, NoteEventInner, SampleRate, NUM_OPERATORS},
+    parameters::{operator_frequency_ratio::set_user_ratios, ParameterKey},
+    settings::Settings,
+    sync::{patch_defaults::set_patch_defaults, SyncState},
+    utils::{init_logging, log_instance_info, update_audio_parameters, update_timbre_parameters},
 };
 
-use super::{descriptor::DESCRIPTOR, ext::gui::ParentWindow, sync::ClapGuiSyncHandle};
+use super::{
+    descriptor::DESCRIPTOR,
+    ext::{gui::ParentWindow, thread_pool::ClapThreadPool},
+    sync::ClapGuiSyncHandle,
+};
 
 pub type EventToHostConsumer =
     Consumer<EventToHost, Arc<SharedRb<EventToHost, Vec<MaybeUninit<EventToHost>>>>>;
@@ -55,11 +72,26 @@ pub struct OctaSine {
     pub gui_parent: Mutex<Option<ParentWindow>>,
     pub gui_window_handle: Mutex<Option<WindowHandle<crate::gui::Message>>>,
     pub clap_plugin: AtomicRefCell<clap_plugin>,
+    /// Latency in samples last reported to host
+    pub reported_latency: AtomicU32,
+    /// Set when host has been asked to restart plugin due to latency change
+    latency_restart_requested: AtomicBool,
+    /// Expose one additional stereo output port per operator
+    pub operator_bus_outputs: bool,
+    /// Renders voices and timbres on host thread pool, if provided
+    thread_pool: Arc<ClapThreadPool>,
 }
 
 impl OctaSine {
     pub fn new(host: *const clap_host) -> Arc<Self> {
-        let _ = init_logging("clap");
+        let settings = Settings::load_or_default();
+
+        if !settings.disable_logging {
+            let _ = init_logging("clap");
+        }
+
+        set_user_ratios(&settings.user_frequency_ratios);
+        set_patch_defaults(&settings.patch_defaults);
 
         let (gui_event_producer, gui_event_consumer) = SharedRb::new(1024).split();
 
@@ -68,13 +100,41 @@ impl OctaSine {
             host,
         };
 
+        let sync = Arc::new(SyncState::new(Some(gui_sync_handle)));
+
+        let mut audio: Box<AudioState> = Default::default();
+
+        let thread_pool = Arc::new(ClapThreadPool::new(host));
+
+        sync.set_simd_backend_override(settings.audio.simd_backend);
+
+        if !settings.disable_logging {
+            log_instance_info(&sync.instance, sync.get_simd_backend());
+        }
+
+        audio.set_rng_seed(sync.instance.seed);
+        audio.set_lfo_phases(sync.lfo_phases.clone());
+        audio.set_lfo_target_modulations(sync.lfo_target_modulations.clone());
+        audio.set_status(sync.audio_status.clone());
+        audio.set_task_executor(thread_pool.clone());
+
+        if let Some(player) = sync.take_patch_preview_player() {
+            audio.set_patch_preview_player(player);
+        }
+
+        audio.apply_settings(&settings.audio);
+
         let plugin = Self {
             host,
-            audio: Default::default(),
-            sync: Arc::new(SyncState::new(Some(gui_sync_handle))),
+            audio: Mutex::new(audio),
+            sync,
             gui_event_consumer: Mutex::new(gui_event_consumer),
             gui_parent: Default::default(),
             gui_window_handle: Default::default(),
+            reported_latency: AtomicU32::new(0),
+            latency_restart_requested: AtomicBool::new(false),
+            operator_bus_outputs: settings.audio.operator_bus_outputs,
+            thread_pool,
             clap_plugin: AtomicRefCell::new(clap_plugin {
                 desc: Lazy::force(&DESCRIPTOR) as *const _,
                 plugin_data: null_mut(),
@@ -98,7 +158,21 @@ impl OctaSine {
         plugin
     }
 
-    unsafe extern "C" fn init(_plugin: *const clap_plugin) -> bool {
+    unsafe extern "C" fn init(plugin: *const clap_plugin) -> bool {
+        if plugin.is_null() || (*plugin).plugin_data.is_null() {
+            return false;
+        }
+
+        let plugin = &*((*plugin).plugin_data as *const Self);
+        let host = &*(plugin.host);
+
+        if let Some(get_extension) = host.get_extension {
+            let ext = get_extension(plugin.host, CLAP_EXT_THREAD_POOL.as_ptr())
+                as *mut clap_host_thread_pool;
+
+            plugin.thread_pool.set_host_thread_pool(ext);
+        }
+
         true
     }
 
@@ -112,11 +186,31 @@ impl OctaSine {
         plugin: *const clap_plugin,
         sample_rate: f64,
         _min_frames_count: u32,
-        _max_frames_count: u32,
+        max_frames_count: u32,
     ) -> bool {
         let plugin = &*((*plugin).plugin_data as *const Self);
 
-        plugin.audio.lock().set_sample_rate(SampleRate(sample_rate));
+        let latency = {
+            let mut audio = plugin.audio.lock();
+
+            audio.set_sample_rate(SampleRate(sample_rate));
+            audio.set_max_block_size(max_frames_count as usize);
+
+            // Make sure latency reflects current output mode
+            update_audio_parameters(&mut audio, &plugin.sync);
+
+            audio.get_latency() as u32
+        };
+
+        plugin.sync.set_sample_rate(SampleRate(sample_rate));
+
+        plugin
+            .latency_restart_requested
+            .store(false, Ordering::Relaxed);
+
+        if plugin.reported_latency.swap(latency, Ordering::Relaxed) != latency {
+            plugin.tell_host_latency_changed();
+        }
 
         true
     }
@@ -142,11 +236,63 @@ impl OctaSine {
         let plugin = &*((*plugin).plugin_data as *const Self);
         let process = &*process;
 
-        if process.audio_outputs_count != 1 || process.audio_outputs.is_null() {
+        if !plugin.sync.audio_status.is_bypassed_due_to_error() {
+            match catch_unwind(AssertUnwindSafe(|| Self::process_inner(plugin, process))) {
+                Ok(status) => return status,
+                Err(_) => {
+                    ::log::error!("Audio processing panicked, bypassing from now on");
+
+                    plugin.sync.audio_status.set_bypassed_due_to_error();
+                }
+            }
+        }
+
+        Self::silence_outputs(process)
+    }
+
+    /// Write silence to outputs, used when bypassed due to error
+    unsafe fn silence_outputs(process: &clap_process) -> clap_process_status {
+        if process.audio_outputs_count == 0 || process.audio_outputs.is_null() {
             return CLAP_PROCESS_ERROR;
         }
 
-        let audio_outputs = &*process.audio_outputs;
+        let audio_outputs = ::std::slice::from_raw_parts(
+            process.audio_outputs,
+            process.audio_outputs_count as usize,
+        );
+
+        for audio_output in audio_outputs {
+            if (audio_output.channel_count != 2) | audio_output.data32.is_null() {
+                return CLAP_PROCESS_ERROR;
+            }
+
+            let channels = ::std::slice::from_raw_parts(audio_output.data32 as *const *mut f32, 2);
+
+            for channel in channels {
+                if channel.is_null() {
+                    return CLAP_PROCESS_ERROR;
+                }
+
+                ::std::slice::from_raw_parts_mut(*channel, process.frames_count as usize).fill(0.0);
+            }
+        }
+
+        CLAP_PROCESS_CONTINUE
+    }
+
+    unsafe fn process_inner(plugin: &Self, process: &clap_process) -> clap_process_status {
+        let process_start = Instant::now();
+
+        if process.audio_outputs_count == 0 || process.audio_outputs.is_null() {
+            return CLAP_PROCESS_ERROR;
+        }
+
+        let all_audio_outputs = ::std::slice::from_raw_parts(
+            process.audio_outputs,
+            process.audio_outputs_count as usize,
+        );
+
+        let audio_outputs = &all_audio_outputs[0];
 
         if (audio_outputs.channel_count != 2) | audio_outputs.data32.is_null() {
             return CLAP_PROCESS_ERROR;
@@ -164,6 +310,28 @@ impl OctaSine {
         let rights =
             ::std::slice::from_raw_parts_mut(audio_outputs[1], process.frames_count as usize);
 
+        // Left and right channel for each operator bus port
+        let mut operator_bus_channels: [&mut [f32]; NUM_OPERATORS * 2] = Default::default();
+
+        for (channels, bus_output) in operator_bus_channels
+            .chunks_exact_mut(2)
+            .zip(all_audio_outputs[1..].iter())
+        {
+            if (bus_output.channel_count != 2) | bus_output.data32.is_null() {
+                continue;
+            }
+
+            let bus_channels =
+                ::std::slice::from_raw_parts(bus_output.data32 as *const *mut f32, 2);
+
+            for (channel, pointer) in channels.iter_mut().zip(bus_channels) {
+                if !pointer.is_null() {
+                    *channel =
+                        ::std::slice::from_raw_parts_mut(*pointer, process.frames_count as usize);
+                }
+            }
+        }
+
         let opt_in_event_data = if !process.in_events.is_null() {
             match ((*(process.in_events)).size, (*(process.in_events)).get) {
                 (Some(size_fn), Some(get_fn)) => {
@@ -187,6 +355,14 @@ impl OctaSine {
             plugin.handle_transport_event_from_host(&*(process.transport));
         }
 
+        {
+            let mut audio = plugin.audio.lock();
+
+            audio.set_simd_backend(plugin.sync.get_simd_backend());
+            audio.set_audition_operator(plugin.sync.get_audition_operator());
+            audio.enqueue_note_events(plugin.sync.take_gui_expression_events());
+        }
+
         let opt_process_out_events = if !process.out_events.is_null() {
             Some(&*(process.out_events))
         } else {
@@ -218,13 +394,28 @@ impl OctaSine {
             {
                 let mut audio = plugin.audio.lock();
 
-                let lefts = &mut lefts[process_start_index as usize..process_end_index as usize];
-                let rights = &mut rights[process_start_index as usize..process_end_index as usize];
+                let segment = process_start_index as usize..process_end_index as usize;
+
+                let lefts = &mut lefts[segment.clone()];
+                let rights = &mut rights[segment.clone()];
+
+                let mut segment_operator_bus_channels: [&mut [f32]; NUM_OPERATORS * 2] =
+                    Default::default();
 
-                process_f32_runtime_select(
+                for (segment_channel, channel) in segment_operator_bus_channels
+                    .iter_mut()
+                    .zip(operator_bus_channels.iter_mut())
+                {
+                    if !channel.is_empty() {
+                        *segment_channel = &mut channel[segment.clone()];
+                    }
+                }
+
+                process_f32_runtime_select_with_operator_buses(
                     &mut audio,
                     lefts,
                     rights,
+                    &mut segment_operator_bus_channels,
                     process_start_index as usize,
                     |audio| {
                         if let Some(process_out_events) = opt_process_out_events {
@@ -248,6 +439,33 @@ impl OctaSine {
             process_end_index = process.frames_count;
         }
 
+        {
+            let mut audio = plugin.audio.lock();
+
+            update_timbre_parameters(&mut audio, &plugin.sync);
+
+            audio.mix_timbres_with(lefts, rights, |timbres, frame_offset, num_samples| {
+                plugin.render_timbres(timbres, frame_offset, num_samples)
+            });
+            audio.mix_patch_preview(lefts, rights);
+            audio.apply_output_stage(lefts, rights);
+            audio.apply_bypass_fade(lefts, rights);
+
+            audio.report_process_duration(process_start.elapsed(), lefts.len());
+            audio.publish_voice_snapshots();
+            audio.publish_operator_mod_out_peaks();
+
+            // Latency may only change while plugin is deactivated, so ask
+            // host to restart it
+            if audio.get_latency() as u32 != plugin.reported_latency.load(Ordering::Relaxed)
+                && !plugin
+                    .latency_restart_requested
+                    .swap(true, Ordering::Relaxed)
+            {
+                plugin.request_restart();
+            }
+        }
+
         // Log any unhandled events. Should never happen.
         if let Some((num_events, get_fn)) = opt_in_event_data {
             while event_index < num_events {
@@ -282,6 +500,10 @@ impl OctaSine {
             &super::ext::voice_info::CONFIG as *const _ as *const c_void
         } else if id == CLAP_EXT_STATE {
             &super::ext::state::CONFIG as *const _ as *const c_void
+        } else if id == CLAP_EXT_LATENCY {
+            &super::ext::latency::CONFIG as *const _ as *const c_void
+        } else if id == CLAP_EXT_THREAD_POOL {
+            &super::ext::thread_pool::CONFIG as *const _ as *const c_void
         } else {
             null()
         }
@@ -289,6 +511,43 @@ impl OctaSine {
 
     unsafe extern "C" fn on_main_thread(_plugin: *const clap_plugin) {}
 
+    /// Render block for each timbre on host thread pool if available,
+    /// otherwise on current thread. Timbre buffers are sized to the maximum
+    /// block size passed to `activate`, so this is done once per processed
+    /// block.
+    fn render_timbres(&self, timbres: &mut [Timbre], frame_offset: usize, num_samples: usize) {
+        if timbres.len() > 1 {
+            let task_slice = TaskSlice::new(timbres);
+
+            let executed = self.thread_pool.execute(task_slice.len(), &|task_index| {
+                // Each task index refers to a different timbre, so no other
+                // thread accesses this one
+                if let Some(timbre) = unsafe { task_slice.get_mut(task_index) } {
+                    timbre.render(frame_offset, num_samples);
+                }
+            });
+
+            if executed {
+                return;
+            }
+        }
+
+        for timbre in timbres.iter_mut() {
+            timbre.render(frame_offset, num_samples);
+        }
+    }
+
+    /// Run task of current thread pool request on host worker thread
+    pub unsafe fn exec_thread_pool_task(&self, task_index: usize) {
+        let result = catch_unwind(AssertUnwindSafe(|| self.thread_pool.exec(task_index)));
+
+        if result.is_err() {
+            ::log::error!("Audio processing panicked on host worker thread, bypassing from now on");
+
+            self.sync.audio_status.set_bypassed_due_to_error();
+        }
+    }
+
     pub unsafe fn handle_event_from_host(&self, event_header: *const clap_event_header) {
         if (*event_header).space_id != CLAP_CORE_EVENT_SPACE_ID {
             return;
@@ -315,7 +574,21 @@ impl OctaSine {
                 let event = NoteEvent {
                     delta_frames: event.header.time,
                     event: NoteEventInner::ClapNoteOff {
-                        key: event.key as u8,
+                        key: clap_key(event.key),
+                        clap_note_id: clap_note_id(event.note_id),
+                    },
+                };
+
+                self.audio.lock().enqueue_note_event(event);
+            }
+            CLAP_EVENT_NOTE_CHOKE => {
+                let event = &*(event_header as *const clap_event_note);
+
+                let event = NoteEvent {
+                    delta_frames: event.header.time,
+                    event: NoteEventInner::ClapNoteChoke {
+                        key: clap_key(event.key),
+                        clap_note_id: clap_note_id(event.note_id),
                     },
                 };
 
@@ -395,6 +668,8 @@ impl OctaSine {
         }
 
         if event.flags & CLAP_TRANSPORT_HAS_TEMPO != 0 {
+            self.sync.set_host_bpm(BeatsPerMinute(event.tempo));
+
             let event = NoteEvent {
                 delta_frames: event.header.time,
                 event: NoteEventInner::ClapBpm {
@@ -524,4 +799,34 @@ impl OctaSine {
             (&*(ext)).mark_dirty.unwrap()(self.host);
         }
     }
+
+    unsafe fn tell_host_latency_changed(&self) {
+        let host = &*(self.host);
+
+        let get_extension = host.get_extension.unwrap();
+
+        let ext = get_extension(self.host, CLAP_EXT_LATENCY.as_ptr()) as *const clap_host_latency;
+
+        if ext.is_null() {
+            ::log::error!("host doesn't implement latency extension");
+        } else {
+            (&*(ext)).changed.unwrap()(self.host);
+        }
+    }
+
+    unsafe fn request_restart(&self) {
+        let host = &*(self.host);
+
+        host.request_restart.unwrap()(self.host);
+    }
+}
+
+/// Negative key means any key
+fn clap_key(key: i16) -> Option<u8> {
+    (0..128).contains(&key).then_some(key as u8)
+}
+
+/// Negative note id means any note id
+fn clap_note_id(note_id: i32) -> Option<i32> {
+    (note_id >= 0).then_some(note_id)
 }
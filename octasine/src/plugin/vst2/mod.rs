@@ -2,7 +2,9 @@
 pub mod editor;
 mod sync;
 
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::Arc;
+use std::time::Instant;
 
 use vst::api::{Events, Supported};
 use vst::event::Event;
@@ -10,10 +12,15 @@ use vst::host::Host;
 #[allow(deprecated)]
 use vst::plugin::{CanDo, Category, HostCallback, Info, Plugin, PluginParameters};
 
-use crate::audio::gen::process_f32_runtime_select;
+use crate::audio::gen::process_f32_runtime_select_with_operator_buses;
 use crate::audio::AudioState;
+use crate::parameters::operator_frequency_ratio::set_user_ratios;
+use crate::settings::Settings;
+use crate::sync::patch_defaults::set_patch_defaults;
 use crate::sync::SyncState;
-use crate::utils::{init_logging, update_audio_parameters};
+use crate::utils::{
+    init_logging, log_instance_info, update_audio_parameters, update_timbre_parameters,
+};
 use crate::{common::*, crate_version};
 
 use super::common::{crate_version_to_vst2_format, PLUGIN_SEMVER_NAME, PLUGIN_UNIQUE_VST2_ID};
@@ -21,6 +28,8 @@ use super::common::{crate_version_to_vst2_format, PLUGIN_SEMVER_NAME, PLUGIN_UNI
 pub struct OctaSine {
     pub audio: Box<AudioState>,
     pub sync: Arc<SyncState<vst::plugin::HostCallback>>,
+    /// Expose one additional stereo output per operator
+    operator_bus_outputs: bool,
     #[cfg(feature = "gui")]
     editor: Option<editor::Editor<Arc<SyncState<vst::plugin::HostCallback>>>>,
 }
@@ -33,18 +42,45 @@ impl Default for OctaSine {
 
 impl OctaSine {
     fn create(host: Option<HostCallback>) -> Self {
-        // If initialization of logging fails, we can't do much about it, but
-        // we shouldn't panic
-        let _ = init_logging("vst2");
+        let settings = Settings::load_or_default();
+
+        if !settings.disable_logging {
+            // If initialization of logging fails, we can't do much about it,
+            // but we shouldn't panic
+            let _ = init_logging("vst2");
+        }
+
+        set_user_ratios(&settings.user_frequency_ratios);
+        set_patch_defaults(&settings.patch_defaults);
 
         let sync = Arc::new(SyncState::new(host));
 
+        let mut audio: Box<AudioState> = Default::default();
+
+        sync.set_simd_backend_override(settings.audio.simd_backend);
+
+        if !settings.disable_logging {
+            log_instance_info(&sync.instance, sync.get_simd_backend());
+        }
+
+        audio.set_rng_seed(sync.instance.seed);
+        audio.set_lfo_phases(sync.lfo_phases.clone());
+        audio.set_lfo_target_modulations(sync.lfo_target_modulations.clone());
+        audio.set_status(sync.audio_status.clone());
+
+        if let Some(player) = sync.take_patch_preview_player() {
+            audio.set_patch_preview_player(player);
+        }
+
+        audio.apply_settings(&settings.audio);
+
         #[cfg(feature = "gui")]
         let editor = editor::Editor::new(sync.clone());
 
         Self {
-            audio: Default::default(),
+            audio,
             sync,
+            operator_bus_outputs: settings.audio.operator_bus_outputs,
             #[cfg(feature = "gui")]
             editor: Some(editor),
         }
@@ -63,26 +99,92 @@ impl OctaSine {
             None
         }
     }
-}
 
-#[allow(deprecated)]
-impl Plugin for OctaSine {
-    fn process(&mut self, buffer: &mut vst::buffer::AudioBuffer<f32>) {
-        let (l, r) = &mut buffer.split().1.split_at_mut(1);
+    fn process_inner(
+        &mut self,
+        lefts: &mut [f32],
+        rights: &mut [f32],
+        operator_bus_channels: &mut [&mut [f32]],
+    ) {
+        let process_start = Instant::now();
 
-        let lefts = l.get_mut(0);
-        let rights = r.get_mut(0);
+        self.audio
+            .enqueue_note_events(self.sync.take_gui_expression_events());
 
         // VST2 spec does not guarantee that events are sent in order
         self.audio.sort_note_events();
 
         if let Some(bpm) = self.get_bpm_from_host() {
             self.audio.set_bpm(bpm);
+            self.sync.set_host_bpm(bpm);
+        }
+
+        self.audio.set_simd_backend(self.sync.get_simd_backend());
+        self.audio
+            .set_audition_operator(self.sync.get_audition_operator());
+
+        process_f32_runtime_select_with_operator_buses(
+            &mut self.audio,
+            lefts,
+            rights,
+            operator_bus_channels,
+            0,
+            |audio_state| {
+                update_audio_parameters(audio_state, &self.sync);
+            },
+        );
+
+        update_timbre_parameters(&mut self.audio, &self.sync);
+
+        self.audio.mix_timbres(lefts, rights);
+        self.audio.mix_patch_preview(lefts, rights);
+        self.audio.apply_output_stage(lefts, rights);
+        self.audio.apply_bypass_fade(lefts, rights);
+
+        self.audio
+            .report_process_duration(process_start.elapsed(), lefts.len());
+        self.audio.publish_voice_snapshots();
+        self.audio.publish_operator_mod_out_peaks();
+    }
+}
+
+#[allow(deprecated)]
+impl Plugin for OctaSine {
+    fn process(&mut self, buffer: &mut vst::buffer::AudioBuffer<f32>) {
+        let mut channels = buffer.split().1.into_iter();
+
+        let (lefts, rights) = match (channels.next(), channels.next()) {
+            (Some(lefts), Some(rights)) => (lefts, rights),
+            _ => return,
+        };
+
+        // Left and right channel for each operator bus
+        let mut operator_bus_channels: [&mut [f32]; NUM_OPERATORS * 2] = Default::default();
+
+        for (slot, channel) in operator_bus_channels.iter_mut().zip(channels) {
+            *slot = channel;
+        }
+
+        if !self.sync.audio_status.is_bypassed_due_to_error() {
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                self.process_inner(lefts, rights, &mut operator_bus_channels)
+            }));
+
+            if result.is_err() {
+                ::log::error!("Audio processing panicked, bypassing from now on");
+
+                self.sync.audio_status.set_bypassed_due_to_error();
+            }
         }
 
-        process_f32_runtime_select(&mut self.audio, lefts, rights, 0, |audio_state| {
-            update_audio_parameters(audio_state, &self.sync);
-        });
+        if self.sync.audio_status.is_bypassed_due_to_error() {
+            lefts.fill(0.0);
+            rights.fill(0.0);
+
+            for channel in operator_bus_channels.iter_mut() {
+                channel.fill(0.0);
+            }
+        }
     }
 
     fn new(host: HostCallback) -> Self {
@@ -97,10 +199,14 @@ impl Plugin for OctaSine {
             unique_id: PLUGIN_UNIQUE_VST2_ID,
             category: Category::Synth,
             inputs: 0,
-            outputs: 2,
+            outputs: if self.operator_bus_outputs {
+                2 + NUM_OPERATORS as i32 * 2
+            } else {
+                2
+            },
             presets: self.sync.patches.num_patches() as i32,
             parameters: self.sync.patches.num_parameters() as i32,
-            initial_delay: 0,
+            initial_delay: self.audio.get_latency() as i32,
             preset_chunks: true,
             f64_precision: false,
             ..Info::default()
@@ -125,6 +231,13 @@ impl Plugin for OctaSine {
 
     fn set_sample_rate(&mut self, rate: f32) {
         self.audio.set_sample_rate(SampleRate(f64::from(rate)));
+        self.sync.set_sample_rate(SampleRate(f64::from(rate)));
+    }
+
+    fn set_block_size(&mut self, size: i64) {
+        if size > 0 {
+            self.audio.set_max_block_size(size as usize);
+        }
     }
 
     fn can_do(&self, can_do: CanDo) -> Supported {
@@ -133,6 +246,9 @@ impl Plugin for OctaSine {
             | CanDo::ReceiveTimeInfo
             | CanDo::SendEvents
             | CanDo::ReceiveEvents => Supported::Yes,
+            // Soft bypass (effSetBypass) isn't forwarded by the vst crate,
+            // so bypass is only available through the bypass parameter
+            CanDo::Bypass => Supported::No,
             _ => Supported::Maybe,
         }
     }
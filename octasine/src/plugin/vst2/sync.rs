@@ -1,4 +1,3 @@
-use std::path::PathBuf;
 #[cfg(feature = "gui")]
 use std::sync::Arc;
 
@@ -6,9 +5,13 @@ use compact_str::CompactString;
 #[cfg(feature = "gui")]
 use vst::host::Host;
 
-use crate::{parameters::WrappedParameter, sync::SyncState};
 #[cfg(feature = "gui")]
-use crate::{settings::Settings, sync::change_info::MAX_NUM_PARAMETERS};
+use crate::{
+    audio::gen::SimdBackend,
+    settings::Settings,
+    sync::{change_info::MAX_NUM_PARAMETERS, ImportFiles, InstanceInfo},
+};
+use crate::{parameters::WrappedParameter, sync::SyncState};
 
 impl vst::plugin::PluginParameters for SyncState<vst::plugin::HostCallback> {
     /// Get parameter label for parameter at `index` (e.g. "db", "sec", "ms", "%").
@@ -91,7 +94,11 @@ impl vst::plugin::PluginParameters for SyncState<vst::plugin::HostCallback> {
     /// If `preset_chunks` is set to true in plugin info, this should return the raw chunk data for
     /// the current plugin bank.
     fn get_bank_data(&self) -> Vec<u8> {
-        self.patches.export_fxb_bytes()
+        let mut bytes = self.patches.export_fxb_bytes();
+
+        self.append_gui_state(&mut bytes);
+
+        bytes
     }
 
     /// If `preset_chunks` is set to true in plugin info, this should load a preset from the given
@@ -103,7 +110,7 @@ impl vst::plugin::PluginParameters for SyncState<vst::plugin::HostCallback> {
     /// If `preset_chunks` is set to true in plugin info, this should load a preset bank from the
     /// given chunk data.
     fn load_bank_data(&self, data: &[u8]) {
-        if let Err(err) = self.patches.import_bank_from_bytes(data) {
+        if let Err(err) = self.import_state_from_bytes(data) {
             ::log::error!("Couldn't load bank data: {}", err)
         }
     }
@@ -208,9 +215,74 @@ impl crate::sync::GuiSyncHandle for Arc<SyncState<vst::plugin::HostCallback>> {
     fn have_patches_changed(&self) -> bool {
         self.patches.have_patches_changed()
     }
+    fn get_host_bpm(&self) -> Option<crate::common::BeatsPerMinute> {
+        (**self).get_host_bpm()
+    }
+    fn get_lfo_phase(&self, lfo_index: usize) -> Option<f32> {
+        self.lfo_phases.take(lfo_index)
+    }
+    fn get_lfo_target_modulation(&self, parameter: WrappedParameter) -> Option<f32> {
+        self.lfo_target_modulations.take(parameter.index() as usize)
+    }
+    fn get_dsp_load(&self) -> f32 {
+        self.audio_status.get_dsp_load()
+    }
+    fn get_num_active_voices(&self) -> usize {
+        self.audio_status.get_num_active_voices()
+    }
+    fn get_voice_snapshots(&self) -> Vec<crate::audio::status::VoiceSnapshot> {
+        self.audio_status.get_voice_snapshots()
+    }
+    fn take_operator_mod_out_peaks(&self) -> [f32; crate::common::NUM_OPERATORS] {
+        self.audio_status.take_operator_mod_out_peaks()
+    }
+    fn is_bypassed_due_to_error(&self) -> bool {
+        self.audio_status.is_bypassed_due_to_error()
+    }
+    fn get_clip_status(&self) -> (bool, bool) {
+        (
+            self.audio_status.is_clipping(),
+            self.audio_status.is_clip_latched(),
+        )
+    }
+    fn reset_clip_indicator(&self) {
+        self.audio_status.reset_clip_status();
+    }
+    fn get_instance_info(&self) -> InstanceInfo {
+        self.instance
+    }
+    fn get_simd_backend(&self) -> SimdBackend {
+        (**self).get_simd_backend()
+    }
+    fn set_simd_backend_override(&self, opt_override: Option<SimdBackend>) {
+        (**self).set_simd_backend_override(opt_override)
+    }
+    fn get_audition_operator(&self) -> Option<usize> {
+        (**self).get_audition_operator()
+    }
+    fn set_audition_operator(&self, opt_operator_index: Option<usize>) {
+        (**self).set_audition_operator(opt_operator_index)
+    }
+    fn preview_patch(&self, index: usize) {
+        (**self).preview_patch(index)
+    }
+    fn send_pitch_bend(&self, value: f32) {
+        self.send_gui_pitch_bend(value)
+    }
+    fn send_mod_wheel(&self, value: f32) {
+        self.send_gui_mod_wheel(value)
+    }
     fn get_gui_settings(&self) -> crate::gui::GuiSettings {
         Settings::load_or_default().gui
     }
+    fn get_gui_state(&self) -> crate::gui::GuiState {
+        self.get_gui_state_json()
+            .map(|json| crate::gui::GuiState::from_json(&json))
+            .unwrap_or_default()
+    }
+    fn set_gui_state(&self, state: &crate::gui::GuiState) {
+        self.set_gui_state_json(state.to_json());
+    }
     fn export_patch(&self) -> (CompactString, Vec<u8>) {
         let name = self.patches.get_current_patch().get_fxp_filename();
         let data = self.patches.get_current_patch().export_fxp_bytes();
@@ -220,8 +292,8 @@ impl crate::sync::GuiSyncHandle for Arc<SyncState<vst::plugin::HostCallback>> {
     fn export_bank(&self) -> Vec<u8> {
         self.patches.export_fxb_bytes()
     }
-    fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf]) {
-        self.patches.import_bank_or_patches_from_paths(paths);
+    fn import_files(&self, files: &ImportFiles) {
+        self.patches.import_files(files);
 
         if let Some(host) = self.host {
             host.update_display();
@@ -233,4 +305,39 @@ impl crate::sync::GuiSyncHandle for Arc<SyncState<vst::plugin::HostCallback>> {
     fn clear_bank(&self) {
         self.patches.clear_bank();
     }
+    fn swap_patches(&self, index_a: usize, index_b: usize) {
+        self.patches.swap_patches(index_a, index_b);
+
+        if let Some(host) = self.host {
+            host.update_display();
+        }
+    }
+    fn copy_patch(&self, from_index: usize, to_index: usize) {
+        self.patches.copy_patch(from_index, to_index);
+
+        if let Some(host) = self.host {
+            host.update_display();
+        }
+    }
+    fn get_key_tuning(&self) -> [f32; 128] {
+        self.patches.get_key_tuning()
+    }
+    fn set_key_detune_from_text(&self, key: u8, text: &str) -> bool {
+        let changed = self.patches.set_key_detune_text_from_gui(key, text);
+
+        if changed {
+            if let Some(host) = self.host {
+                host.update_display();
+            }
+        }
+
+        changed
+    }
+    fn reset_key_tuning(&self) {
+        self.patches.reset_key_tuning_from_gui();
+
+        if let Some(host) = self.host {
+            host.update_display();
+        }
+    }
 }
@@ -2,11 +2,26 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use crate::audio::AudioSettings;
+use crate::sync::patch_defaults::PatchDefaults;
 use crate::utils::get_file_storage_dir;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub schema_version: usize,
+    #[serde(default)]
+    pub audio: AudioSettings,
+    /// Don't initialize logging, so no log file is written
+    #[serde(default)]
+    pub disable_logging: bool,
+    /// Additional operator frequency ratio steps, e.g., "7/4" or "3:2". At
+    /// most eight are used. Changes take effect when a plugin instance is
+    /// next created.
+    #[serde(default)]
+    pub user_frequency_ratios: Vec<String>,
+    /// Voice settings that new and cleared patches start with
+    #[serde(default)]
+    pub patch_defaults: PatchDefaults,
     #[cfg(feature = "gui")]
     pub gui: super::gui::GuiSettings,
 }
@@ -15,6 +30,10 @@ impl Default for Settings {
     fn default() -> Self {
         Self {
             schema_version: 1,
+            audio: Default::default(),
+            disable_logging: false,
+            user_frequency_ratios: Vec::new(),
+            patch_defaults: Default::default(),
             #[cfg(feature = "gui")]
             gui: Default::default(),
         }
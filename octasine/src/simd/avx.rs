@@ -100,7 +100,7 @@ impl SimdPackedDouble for AvxPackedDouble {
     #[target_feature(enable = "avx")]
     #[inline]
     unsafe fn fast_sin(self) -> Self {
-        Self(sleef_trig::Sleef_sind4_u35avx(self.0))
+        super::sin_approx(self, |v| Self::new(v))
     }
     #[target_feature(enable = "avx")]
     #[inline]
@@ -84,7 +84,7 @@ impl SimdPackedDouble for FallbackPackedDouble {
     }
     #[inline(always)]
     unsafe fn fast_sin(self) -> Self {
-        Self(apply_to_arrays!(sleef_trig::Sleef_sind1_u35purec, self.0))
+        Self(apply_to_arrays!(super::sin, self.0))
     }
     #[inline(always)]
     unsafe fn triangle(self) -> Self {
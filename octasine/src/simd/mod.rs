@@ -18,7 +18,9 @@ pub trait Simd {
     type Pd: SimdPackedDouble;
 }
 
-pub trait SimdPackedDouble: Copy + Add + AddAssign + Sub + Mul {
+pub trait SimdPackedDouble:
+    Copy + Add<Output = Self> + AddAssign + Sub<Output = Self> + Mul<Output = Self>
+{
     // Number of doubles that this packed double fits
     const WIDTH: usize;
     /// Number of stereo audio samples that this packed double fits
@@ -39,12 +41,91 @@ pub trait SimdPackedDouble: Copy + Add + AddAssign + Sub + Mul {
     unsafe fn any_over_zero(self) -> bool;
     unsafe fn floor(self) -> Self;
     unsafe fn abs(self) -> Self;
+    /// Sine approximation (see [`sin_approx`])
     unsafe fn fast_sin(self) -> Self;
     unsafe fn triangle(self) -> Self;
     unsafe fn square(self) -> Self;
     unsafe fn saw(self) -> Self;
 }
 
+/// Cody-Waite split of pi, with trailing zeros so that multiplication by the
+/// quadrant is exact
+#[allow(clippy::excessive_precision)]
+const PI_A: f64 = 3.1415926218032836914;
+#[allow(clippy::excessive_precision)]
+const PI_B: f64 = 3.1786509424591713469e-08;
+#[allow(clippy::excessive_precision)]
+const PI_C: f64 = 1.2246467864107188502e-16;
+
+/// Adding and subtracting this rounds values with magnitude below 2^51 to
+/// nearest integer
+const ROUNDING_MAGIC: f64 = 6755399441055744.0;
+
+/// Minimax polynomial coefficients for sin(x) / x - 1 on [-pi/2, pi/2] in
+/// terms of x^2
+#[allow(clippy::excessive_precision)]
+const SIN_COEFFICIENTS: [f64; 8] = [
+    2.72052416138529567917983e-15,
+    -7.64292594113954471900203e-13,
+    1.60589370117277896211623e-10,
+    -2.5052106814843123359368e-08,
+    2.75573192104428224777379e-06,
+    -0.000198412698412046454654947,
+    0.00833333333333318056201922,
+    -0.166666666666666657414808,
+];
+
+/// Polynomial sine approximation, generic over scalars and packed doubles
+///
+/// Only uses addition, subtraction and multiplication (no FMA), so results
+/// are bit-identical for all SIMD implementations and the scalar version.
+/// Maximum error is around 1e-15 for inputs with magnitude below a few
+/// thousand, degrading slowly for larger inputs.
+#[inline(always)]
+pub fn sin_approx<T, F>(x: T, splat: F) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+    F: Fn(f64) -> T,
+{
+    let magic = splat(ROUNDING_MAGIC);
+
+    // Nearest multiple of pi
+    let q = (x * splat(::std::f64::consts::FRAC_1_PI) + magic) - magic;
+
+    // Reduce argument to [-pi/2, pi/2]
+    let r = x - q * splat(PI_A);
+    let r = r - q * splat(PI_B);
+    let r = r - q * splat(PI_C);
+
+    // Result should be negated for odd q. q - 2 * round(q / 2) is zero for
+    // even q and +/- one for odd q.
+    let half_q = q * splat(0.5);
+    let q_parity = q - splat(2.0) * ((half_q + magic) - magic);
+    let sign = splat(1.0) - splat(2.0) * q_parity * q_parity;
+
+    let s = r * r;
+
+    let mut u = splat(SIN_COEFFICIENTS[0]);
+
+    for c in SIN_COEFFICIENTS[1..].iter().copied() {
+        u = u * s + splat(c);
+    }
+
+    (s * (u * r) + r) * sign
+}
+
+/// Scalar version of [`sin_approx`]
+#[inline]
+pub fn sin(x: f64) -> f64 {
+    sin_approx(x, |v| v)
+}
+
+/// Cosine calculated with [`sin_approx`]
+#[inline]
+pub fn cos(x: f64) -> f64 {
+    sin_approx(x + ::std::f64::consts::FRAC_PI_2, |v| v)
+}
+
 #[cfg(test)]
 mod tests {
     macro_rules! wave_test {
@@ -91,6 +172,94 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_sin_accuracy() {
+        use crate::simd::SimdPackedDouble;
+
+        for i in -200_000..200_000 {
+            let x = f64::from(i) * 0.001;
+
+            let expected = x.sin();
+            let scalar = super::sin(x);
+            let fallback = unsafe { super::FallbackPackedDouble::new(x).fast_sin().to_arr() };
+
+            assert!((scalar - expected).abs() < 1e-14, "x: {}", x);
+            assert_eq!(fallback, [scalar, scalar]);
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                let sse2 = unsafe { super::Sse2PackedDouble::new(x).fast_sin().to_arr() };
+
+                assert_eq!(sse2, [scalar; 2]);
+
+                if is_x86_feature_detected!("avx") {
+                    let avx = unsafe { super::AvxPackedDouble::new(x).fast_sin().to_arr() };
+
+                    assert_eq!(avx, [scalar; 4]);
+                }
+            }
+        }
+
+        for x in [0.0, 1e-300, 1e-9, 1000.5, -54321.0] {
+            assert!((super::sin(x) - x.sin()).abs() < 1e-12, "x: {}", x);
+        }
+        for x in [0.0, 1.0, -2.5, 100.0] {
+            assert!((super::cos(x) - x.cos()).abs() < 1e-14, "x: {}", x);
+        }
+    }
+
+    #[test]
+    fn test_sine_variants() {
+        use crate::math::wave::{sine_variant, sine_variant_scalar, SineVariant};
+        use crate::simd::SimdPackedDouble;
+
+        let variants = [
+            SineVariant::Squared,
+            SineVariant::Half,
+            SineVariant::HalfSquared,
+            SineVariant::Alternating,
+            SineVariant::AlternatingSquared,
+            SineVariant::AlternatingHalf,
+            SineVariant::AlternatingHalfSquared,
+            SineVariant::Quarter,
+        ];
+
+        for variant in variants {
+            for i in -2_000..2_000 {
+                let x = f64::from(i) * 0.00123;
+
+                let scalar = sine_variant_scalar(variant, x);
+
+                assert!((-1.0..=1.0).contains(&scalar), "{:?} {}", variant, x);
+
+                #[cfg(target_arch = "x86_64")]
+                {
+                    let sse2 =
+                        unsafe { sine_variant(variant, super::Sse2PackedDouble::new(x)).to_arr() };
+
+                    assert_eq!(sse2, [scalar; 2]);
+
+                    if is_x86_feature_detected!("avx") {
+                        let avx = unsafe {
+                            sine_variant(variant, super::AvxPackedDouble::new(x)).to_arr()
+                        };
+
+                        assert_eq!(avx, [scalar; 4]);
+                    }
+                }
+            }
+
+            // Last quarter of cycle is silent for all but squared sine
+            let last_quarter = sine_variant_scalar(variant, 0.875);
+
+            match variant {
+                SineVariant::Squared => assert!((last_quarter + 0.5).abs() < 1e-10),
+                _ => assert!(last_quarter.abs() < 1e-10, "{:?}", variant),
+            }
+        }
+    }
+
+    wave_test!(test_fast_sin, fast_sin);
     wave_test!(test_triangle, triangle);
     wave_test!(test_square, square);
     wave_test!(test_saw, saw);
@@ -76,7 +76,7 @@ impl SimdPackedDouble for Sse2PackedDouble {
     }
     #[inline(always)]
     unsafe fn fast_sin(self) -> Self {
-        Self(sleef_trig::Sleef_sind2_u35sse2(self.0))
+        super::sin_approx(self, |v| Self::new(v))
     }
     #[inline(always)]
     unsafe fn triangle(mut self) -> Self {
@@ -1,14 +1,20 @@
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 
 use array_init::array_init;
 
 use crate::{common::IndexMap, parameters::ParameterKey};
 
+use super::atomic_float::AtomicFloat;
 use super::parameters::PatchParameter;
 
-const NUM_ATOMIC_U64S: usize = 2;
+const NUM_ATOMIC_U64S: usize = 4;
 pub const MAX_NUM_PARAMETERS: usize = NUM_ATOMIC_U64S * 64;
 
+/// Power of two, so that slot positions stay consistent when write position
+/// wraps around
+const QUEUE_LEN: usize = MAX_NUM_PARAMETERS.next_power_of_two();
+const EMPTY_SLOT: u32 = u32::MAX;
+
 /// Cache for marking parameters as changed and listing them.
 pub struct ParameterChangeInfo {
     atomic_u64s: [AtomicU64; NUM_ATOMIC_U64S],
@@ -93,6 +99,126 @@ impl Default for ParameterChangeInfo {
     }
 }
 
+/// Lock-free bounded queue of changed parameter indices, with any number of
+/// producers and a single consumer (the audio thread).
+///
+/// Each parameter index is queued at most once at a time, so the queue can
+/// never overflow. Values are read from the patch parameters when the queue
+/// is drained, so only the latest value is passed on. After patch changes,
+/// the consumer instead receives all parameters.
+///
+/// Changes to the per-key tuning of the patch are tracked with a flag, since
+/// keys don't have parameter indices.
+pub struct ParameterChangeQueue {
+    queued: [AtomicU64; NUM_ATOMIC_U64S],
+    slots: [AtomicU32; QUEUE_LEN],
+    write_position: AtomicUsize,
+    read_position: AtomicUsize,
+    all_changed: AtomicBool,
+    key_tuning_changed: AtomicBool,
+}
+
+impl ParameterChangeQueue {
+    pub fn push(&self, parameter_index: usize) {
+        if parameter_index > MAX_NUM_PARAMETERS - 1 {
+            return;
+        }
+
+        let mask = 1u64 << (parameter_index % 64);
+
+        let previous = self.queued[parameter_index / 64].fetch_or(mask, Ordering::SeqCst);
+
+        // Index is already in queue and consumer hasn't yet read its value
+        if previous & mask != 0 {
+            return;
+        }
+
+        let position = self.write_position.fetch_add(1, Ordering::SeqCst);
+
+        self.slots[position % QUEUE_LEN].store(parameter_index as u32, Ordering::SeqCst);
+    }
+
+    pub fn mark_all_as_changed(&self) {
+        self.all_changed.store(true, Ordering::SeqCst);
+        self.key_tuning_changed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn push_key_tuning(&self) {
+        self.key_tuning_changed.store(true, Ordering::SeqCst);
+    }
+
+    /// Call `f` with index and current value of each changed parameter.
+    ///
+    /// Must only be called from a single thread.
+    pub fn drain<F: FnMut(usize, f32)>(
+        &self,
+        parameters: &IndexMap<ParameterKey, PatchParameter>,
+        mut f: F,
+    ) {
+        if self.all_changed.swap(false, Ordering::SeqCst) {
+            for (parameter_index, (_, p)) in parameters.iter().enumerate() {
+                f(parameter_index, p.get_value());
+            }
+        }
+
+        let mut position = self.read_position.load(Ordering::Relaxed);
+
+        loop {
+            let parameter_index =
+                self.slots[position % QUEUE_LEN].swap(EMPTY_SLOT, Ordering::SeqCst);
+
+            // Queue is empty or producer hasn't finished writing to slot yet.
+            // In the latter case, the slot will be read on next call.
+            if parameter_index == EMPTY_SLOT {
+                break;
+            }
+
+            position = position.wrapping_add(1);
+
+            let parameter_index = parameter_index as usize;
+
+            // Unmark before reading value, so that concurrent changes are
+            // queued again instead of getting lost
+            self.queued[parameter_index / 64]
+                .fetch_and(!(1u64 << (parameter_index % 64)), Ordering::SeqCst);
+
+            if let Some((_, p)) = parameters.get_index(parameter_index) {
+                f(parameter_index, p.get_value());
+            }
+        }
+
+        self.read_position.store(position, Ordering::Relaxed);
+    }
+
+    /// Call `f` with key and current detuning in cents of each key if key
+    /// tuning changed.
+    ///
+    /// Must only be called from a single thread.
+    pub fn drain_key_tuning<F: FnMut(u8, f32)>(&self, key_tuning: &[AtomicFloat; 128], mut f: F) {
+        // Avoid atomic write in common case, since this is called often
+        if self.key_tuning_changed.load(Ordering::Relaxed)
+            && self.key_tuning_changed.swap(false, Ordering::SeqCst)
+        {
+            for (key, cents) in key_tuning.iter().enumerate() {
+                f(key as u8, cents.get());
+            }
+        }
+    }
+}
+
+impl Default for ParameterChangeQueue {
+    fn default() -> Self {
+        Self {
+            queued: array_init(|_| AtomicU64::new(0)),
+            slots: array_init(|_| AtomicU32::new(EMPTY_SLOT)),
+            write_position: AtomicUsize::new(0),
+            read_position: AtomicUsize::new(0),
+            all_changed: AtomicBool::new(false),
+            key_tuning_changed: AtomicBool::new(false),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -237,4 +363,110 @@ mod tests {
 
         quickcheck(prop as fn(Vec<(usize, f32)>) -> TestResult);
     }
+
+    #[test]
+    fn test_change_queue_quickcheck() {
+        fn prop(data: Vec<(u8, f32)>) -> TestResult {
+            let patch_parameters = PatchParameter::all();
+            let q = ParameterChangeQueue::default();
+
+            for _ in 0..2 {
+                let mut set_parameters = HashMap::new();
+
+                for (index, value) in data.iter() {
+                    let index = *index as usize;
+
+                    if let Some((_, p)) = patch_parameters.get_index(index) {
+                        let value = value.abs().min(1.0);
+
+                        if value.is_nan() {
+                            return TestResult::discard();
+                        }
+
+                        p.set_value(value);
+
+                        set_parameters.insert(index, value);
+                    }
+
+                    q.push(index);
+                }
+
+                let mut results = HashMap::new();
+                let mut num_results = 0;
+
+                q.drain(&patch_parameters, |index, value| {
+                    results.insert(index, value);
+
+                    num_results += 1;
+                });
+
+                if results != set_parameters || num_results != set_parameters.len() {
+                    println!("results: {:#?}", results);
+                    println!("reference: {:#?}", set_parameters);
+
+                    return TestResult::from_bool(false);
+                }
+            }
+
+            let mut changes_exist = false;
+
+            q.drain(&patch_parameters, |_, _| changes_exist = true);
+
+            TestResult::from_bool(!changes_exist)
+        }
+
+        quickcheck(prop as fn(Vec<(u8, f32)>) -> TestResult);
+    }
+
+    #[test]
+    fn test_change_queue_all_changed() {
+        let patch_parameters = PatchParameter::all();
+        let q = ParameterChangeQueue::default();
+
+        q.mark_all_as_changed();
+
+        let mut num_results = 0;
+
+        q.drain(&patch_parameters, |_, _| num_results += 1);
+
+        assert_eq!(num_results, patch_parameters.len());
+
+        num_results = 0;
+
+        q.drain(&patch_parameters, |_, _| num_results += 1);
+
+        assert_eq!(num_results, 0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_change_queue_key_tuning() {
+        let key_tuning: [AtomicFloat; 128] = array_init(|_| AtomicFloat::new(0.0));
+        let q = ParameterChangeQueue::default();
+
+        let drain = || {
+            let mut changes = Vec::new();
+
+            q.drain_key_tuning(&key_tuning, |key, cents| changes.push((key, cents)));
+
+            changes
+        };
+
+        assert!(drain().is_empty());
+
+        key_tuning[60].set(12.5);
+        q.push_key_tuning();
+
+        let changes = drain();
+
+        assert_eq!(changes.len(), 128);
+        assert_eq!(changes[60], (60, 12.5));
+        assert!(drain().is_empty());
+
+        // Patch changes include key tuning
+        q.mark_all_as_changed();
+
+        assert_eq!(drain().len(), 128);
+        assert!(drain().is_empty());
+    }
 }
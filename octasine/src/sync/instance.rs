@@ -0,0 +1,29 @@
+/// Identifies plugin instance in logs and bug reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstanceInfo {
+    /// Random id distinguishing instances, e.g., in the same host session
+    pub id: u32,
+    /// Seed of audio thread random number generator
+    pub seed: u64,
+}
+
+impl InstanceInfo {
+    pub fn new() -> Self {
+        Self {
+            id: fastrand::u32(..),
+            seed: fastrand::u64(..),
+        }
+    }
+}
+
+impl Default for InstanceInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ::std::fmt::Display for InstanceInfo {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "instance {:08x}, seed {:016x}", self.id, self.seed)
+    }
+}
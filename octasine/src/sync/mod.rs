@@ -1,13 +1,36 @@
 mod atomic_float;
 pub mod change_info;
+mod instance;
 mod parameters;
 mod patch_bank;
+pub mod patch_defaults;
+mod preview_worker;
 mod serde;
+pub mod sysex;
 
-use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
 
+use arc_swap::ArcSwapOption;
 use compact_str::CompactString;
-pub use patch_bank::PatchBank;
+pub use instance::InstanceInfo;
+pub use patch_bank::{ImportFiles, Patch, PatchBank};
+
+use crate::audio::gen::lfo::{LfoPhases, LfoTargetModulations};
+use crate::audio::gen::SimdBackend;
+use crate::audio::preview::{patch_preview_queues, PatchPreviewPlayer};
+use crate::audio::status::AudioStatus;
+use crate::common::{BeatsPerMinute, NoteEvent, NoteEventInner, SampleRate};
+
+use self::atomic_float::AtomicFloat;
+use self::preview_worker::PreviewWorker;
+
+const GUI_STATE_PREFIX: &[u8] = b"\n\nOCTASINE-GUI-STATE\n\n";
+
+/// Marks absence of pending GUI expression value
+const NO_PENDING_EXPRESSION: u32 = u32::MAX;
+/// Marks absence of auditioned operator
+const NO_AUDITION_OPERATOR: u8 = u8::MAX;
 
 /// Thread-safe state used for parameter and preset calls
 pub struct SyncState<H> {
@@ -15,15 +38,241 @@ pub struct SyncState<H> {
     /// option of leaving this field empty is useful when benchmarking.
     pub host: Option<H>,
     pub patches: PatchBank,
+    /// Instance id and RNG seed. Pass seed to AudioState with
+    /// `AudioState::set_rng_seed`
+    pub instance: InstanceInfo,
+    /// Latest tempo reported by host. Zero means no tempo has been reported.
+    host_bpm: AtomicFloat,
+    /// LFO phases published by audio thread. Pass to AudioState with
+    /// `AudioState::set_lfo_phases`
+    pub lfo_phases: Arc<LfoPhases>,
+    /// LFO target modulation published by audio thread. Pass to AudioState
+    /// with `AudioState::set_lfo_target_modulations`
+    pub lfo_target_modulations: Arc<LfoTargetModulations>,
+    /// Status published by audio thread. Pass to AudioState with
+    /// `AudioState::set_status`
+    pub audio_status: Arc<AudioStatus>,
+    sample_rate: AtomicFloat,
+    /// Renders patch previews off the GUI thread
+    patch_preview_worker: Mutex<PreviewWorker>,
+    /// Receiving side of patch preview queues. Pass to AudioState with
+    /// `AudioState::set_patch_preview_player`
+    patch_preview_player: Mutex<Option<PatchPreviewPlayer>>,
+    /// Per-instance GUI state as JSON, stored together with patch bank in
+    /// plugin state chunk
+    gui_state: ArcSwapOption<String>,
+    /// 14-bit MIDI pitch bend value from GUI expression strip waiting to be
+    /// picked up by audio thread
+    gui_pitch_bend: AtomicU32,
+    /// MIDI mod wheel (CC 1) value from GUI expression strip waiting to be
+    /// picked up by audio thread
+    gui_mod_wheel: AtomicU32,
+    /// Audio generation code path. Pass to AudioState with
+    /// `AudioState::set_simd_backend`
+    simd_backend: AtomicU8,
+    /// Index of operator auditioned from GUI. Pass to AudioState with
+    /// `AudioState::set_audition_operator`
+    audition_operator: AtomicU8,
 }
 
 impl<H> SyncState<H> {
     pub fn new(host: Option<H>) -> Self {
+        let (patch_preview_sender, patch_preview_player) = patch_preview_queues();
+
         Self {
             host,
             patches: built_in_patch_bank(),
+            instance: InstanceInfo::new(),
+            host_bpm: AtomicFloat::new(0.0),
+            lfo_phases: Default::default(),
+            lfo_target_modulations: Default::default(),
+            audio_status: Default::default(),
+            sample_rate: AtomicFloat::new(SampleRate::default().0 as f32),
+            patch_preview_worker: Mutex::new(PreviewWorker::new(patch_preview_sender)),
+            patch_preview_player: Mutex::new(Some(patch_preview_player)),
+            gui_state: ArcSwapOption::empty(),
+            gui_pitch_bend: AtomicU32::new(NO_PENDING_EXPRESSION),
+            gui_mod_wheel: AtomicU32::new(NO_PENDING_EXPRESSION),
+            simd_backend: AtomicU8::new(simd_backend_to_u8(SimdBackend::detect())),
+            audition_operator: AtomicU8::new(NO_AUDITION_OPERATOR),
+        }
+    }
+
+    pub fn set_sample_rate(&self, sample_rate: SampleRate) {
+        self.sample_rate.set(sample_rate.0 as f32);
+    }
+
+    /// Render preview note with patch at index on worker thread and queue
+    /// it for playback. Current patch is left unchanged.
+    pub fn preview_patch(&self, index: usize) {
+        if let Some(patch) = self.patches.patches.get(index) {
+            let parameter_values = patch
+                .parameters
+                .values()
+                .map(|p| p.get_value())
+                .collect::<Vec<f32>>();
+
+            let sample_rate = SampleRate(self.sample_rate.get().into());
+
+            match self.patch_preview_worker.lock() {
+                Ok(mut worker) => worker.render(parameter_values, sample_rate),
+                Err(_) => ::log::error!("Patch preview worker lock poisoned"),
+            }
+        }
+    }
+
+    /// Take receiving side of patch preview queues. Only returns it on
+    /// first call.
+    pub fn take_patch_preview_player(&self) -> Option<PatchPreviewPlayer> {
+        self.patch_preview_player
+            .lock()
+            .ok()
+            .and_then(|mut opt_player| opt_player.take())
+    }
+
+    /// Send pitch bend (-1.0 to 1.0) from GUI to audio thread. Only latest
+    /// value is kept.
+    pub fn send_gui_pitch_bend(&self, value: f32) {
+        let value = value.clamp(-1.0, 1.0);
+
+        let value = if value >= 0.0 {
+            8192.0 + value * 8191.0
+        } else {
+            8192.0 + value * 8192.0
+        };
+
+        self.gui_pitch_bend
+            .store(value.round() as u32, Ordering::Relaxed);
+    }
+
+    /// Send mod wheel value (0.0 to 1.0) from GUI to audio thread. Only
+    /// latest value is kept.
+    pub fn send_gui_mod_wheel(&self, value: f32) {
+        let value = (value.clamp(0.0, 1.0) * 127.0).round() as u32;
+
+        self.gui_mod_wheel.store(value, Ordering::Relaxed);
+    }
+
+    /// Take pitch bend and mod wheel MIDI events sent from GUI since last
+    /// call. Pass to `AudioState::enqueue_note_events`.
+    pub fn take_gui_expression_events(&self) -> impl Iterator<Item = NoteEvent> {
+        let pitch_bend = self
+            .gui_pitch_bend
+            .swap(NO_PENDING_EXPRESSION, Ordering::Relaxed);
+        let mod_wheel = self
+            .gui_mod_wheel
+            .swap(NO_PENDING_EXPRESSION, Ordering::Relaxed);
+
+        let pitch_bend = (pitch_bend != NO_PENDING_EXPRESSION).then_some([
+            0b_1110_0000,
+            (pitch_bend & 0x7f) as u8,
+            (pitch_bend >> 7) as u8,
+        ]);
+        let mod_wheel =
+            (mod_wheel != NO_PENDING_EXPRESSION).then_some([0b_1011_0000, 1, mod_wheel as u8]);
+
+        pitch_bend
+            .into_iter()
+            .chain(mod_wheel)
+            .map(|data| NoteEvent {
+                delta_frames: 0,
+                event: NoteEventInner::Midi { data },
+            })
+    }
+
+    pub fn get_gui_state_json(&self) -> Option<Arc<String>> {
+        self.gui_state.load_full()
+    }
+
+    pub fn set_gui_state_json(&self, json: String) {
+        self.gui_state.store(Some(Arc::new(json)));
+    }
+
+    /// Append GUI state (if any) to patch bank bytes for plugin state chunk
+    pub fn append_gui_state(&self, bank_bytes: &mut Vec<u8>) {
+        if let Some(json) = self.gui_state.load().as_ref() {
+            bank_bytes.extend_from_slice(GUI_STATE_PREFIX);
+            bank_bytes.extend_from_slice(json.as_bytes());
         }
     }
+
+    /// Import plugin state chunk, i.e., patch bank bytes optionally
+    /// followed by GUI state
+    ///
+    /// Older versions ignore the trailing GUI state when parsing the bank.
+    pub fn import_state_from_bytes(&self, bytes: &[u8]) -> anyhow::Result<()> {
+        let opt_offset = memchr::memmem::rfind(bytes, GUI_STATE_PREFIX);
+
+        let (bank_bytes, opt_gui_state_bytes) = match opt_offset {
+            Some(offset) => (
+                &bytes[..offset],
+                Some(&bytes[offset + GUI_STATE_PREFIX.len()..]),
+            ),
+            None => (bytes, None),
+        };
+
+        self.patches.import_bank_from_bytes(bank_bytes)?;
+
+        let opt_gui_state = opt_gui_state_bytes
+            .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+            .map(Arc::new);
+
+        self.gui_state.store(opt_gui_state);
+
+        Ok(())
+    }
+
+    pub fn set_host_bpm(&self, bpm: BeatsPerMinute) {
+        self.host_bpm.set(bpm.0 as f32);
+    }
+
+    pub fn get_host_bpm(&self) -> Option<BeatsPerMinute> {
+        let bpm = self.host_bpm.get();
+
+        if bpm > 0.0 {
+            Some(BeatsPerMinute(bpm.into()))
+        } else {
+            None
+        }
+    }
+
+    /// Set audio generation code path from settings override, falling back
+    /// to fastest one supported by CPU
+    pub fn set_simd_backend_override(&self, opt_override: Option<SimdBackend>) {
+        let backend = SimdBackend::select(opt_override);
+
+        self.simd_backend
+            .store(simd_backend_to_u8(backend), Ordering::Relaxed);
+    }
+
+    pub fn get_simd_backend(&self) -> SimdBackend {
+        match self.simd_backend.load(Ordering::Relaxed) {
+            0 => SimdBackend::Fallback,
+            1 => SimdBackend::Sse2,
+            _ => SimdBackend::Avx,
+        }
+    }
+
+    pub fn set_audition_operator(&self, opt_operator_index: Option<usize>) {
+        let value = opt_operator_index.map_or(NO_AUDITION_OPERATOR, |index| index as u8);
+
+        self.audition_operator.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get_audition_operator(&self) -> Option<usize> {
+        match self.audition_operator.load(Ordering::Relaxed) {
+            NO_AUDITION_OPERATOR => None,
+            index => Some(index.into()),
+        }
+    }
+}
+
+fn simd_backend_to_u8(backend: SimdBackend) -> u8 {
+    match backend {
+        SimdBackend::Fallback => 0,
+        SimdBackend::Sse2 => 1,
+        SimdBackend::Avx => 2,
+    }
 }
 
 cfg_if::cfg_if! {
@@ -33,7 +282,10 @@ cfg_if::cfg_if! {
 
         /// Trait passed to GUI code for encapsulation
         pub trait GuiSyncHandle: Clone + Send + Sync + 'static {
+            /// Start automation gesture, e.g., when knob is grabbed. Calls
+            /// host.begin_edit (VST2) or sends param gesture begin (CLAP).
             fn begin_edit(&self, parameter: WrappedParameter);
+            /// End automation gesture started with `begin_edit`
             fn end_edit(&self, parameter: WrappedParameter);
             fn set_parameter(&self, parameter: WrappedParameter, value: f32);
             /// Set parameter immediately. Wrap in begin and end edit commands if necessary
@@ -50,16 +302,153 @@ cfg_if::cfg_if! {
             fn set_current_patch_name(&self, name: &str);
             fn get_changed_parameters(&self) -> Option<[Option<f32>; MAX_NUM_PARAMETERS]>;
             fn have_patches_changed(&self) -> bool;
+            /// Get latest tempo reported by host, if any
+            fn get_host_bpm(&self) -> Option<crate::common::BeatsPerMinute>;
+            /// Get LFO phase published by audio thread since last call, if any
+            fn get_lfo_phase(&self, lfo_index: usize) -> Option<f32>;
+            /// Get patch value addition from LFO (and velocity) modulation of
+            /// parameter published by audio thread since last call, if any
+            fn get_lfo_target_modulation(&self, parameter: WrappedParameter) -> Option<f32>;
+            /// Get smoothed DSP load published by audio thread
+            fn get_dsp_load(&self) -> f32;
+            /// Get number of active voices published by audio thread
+            fn get_num_active_voices(&self) -> usize;
+            /// Get snapshot of active voices published by audio thread
+            fn get_voice_snapshots(&self) -> Vec<crate::audio::status::VoiceSnapshot>;
+            /// Get peak modulation output per operator published by audio
+            /// thread since last call
+            fn take_operator_mod_out_peaks(&self) -> [f32; crate::common::NUM_OPERATORS];
+            /// Audio processing panicked and plugin is outputting silence
+            fn is_bypassed_due_to_error(&self) -> bool;
+            /// Get output clip status published by audio thread as (clipping
+            /// recently, clipped since last reset)
+            fn get_clip_status(&self) -> (bool, bool);
+            fn reset_clip_indicator(&self);
+            /// Get instance id and RNG seed for bug reports
+            fn get_instance_info(&self) -> InstanceInfo;
+            /// Get audio generation code path currently in use
+            fn get_simd_backend(&self) -> crate::audio::gen::SimdBackend;
+            /// Force audio generation code path, or use fastest one if None
+            fn set_simd_backend_override(&self, opt_override: Option<crate::audio::gen::SimdBackend>);
+            /// Get index of operator currently auditioned, if any
+            fn get_audition_operator(&self) -> Option<usize>;
+            /// Output operator directly at a safe level, silencing mix
+            /// outputs of other operators, or stop auditioning if None
+            fn set_audition_operator(&self, opt_operator_index: Option<usize>);
+            /// Render short note with patch and play it back through plugin output
+            fn preview_patch(&self, index: usize);
+            /// Send pitch bend (-1.0 to 1.0) to audio thread as MIDI event
+            fn send_pitch_bend(&self, value: f32);
+            /// Send mod wheel value (0.0 to 1.0) to audio thread as MIDI event
+            fn send_mod_wheel(&self, value: f32);
             fn get_gui_settings(&self) -> crate::gui::GuiSettings;
+            /// Get per-instance GUI state stored in plugin state chunk
+            fn get_gui_state(&self) -> crate::gui::GuiState;
+            fn set_gui_state(&self, state: &crate::gui::GuiState);
             fn export_patch(&self) -> (CompactString, Vec<u8>);
             fn export_bank(&self) -> Vec<u8>;
-            fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf]);
+            /// Import files read with [ImportFiles::read], e.g., on a background thread
+            fn import_files(&self, files: &ImportFiles);
             fn clear_patch(&self);
             fn clear_bank(&self);
+            /// Swap contents of two patch slots
+            fn swap_patches(&self, index_a: usize, index_b: usize);
+            /// Overwrite contents of patch slot with contents of another one
+            fn copy_patch(&self, from_index: usize, to_index: usize);
+            /// Get detuning in cents of each key in current patch
+            fn get_key_tuning(&self) -> [f32; 128];
+            /// Set detuning of key in current patch from text in cents or as
+            /// frequency. Returns false if text couldn't be parsed.
+            fn set_key_detune_from_text(&self, key: u8, text: &str) -> bool;
+            /// Reset detuning of all keys in current patch
+            fn reset_key_tuning(&self);
         }
     }
 }
 
 fn built_in_patch_bank() -> PatchBank {
-    PatchBank::default()
+    let bank = PatchBank::default();
+
+    for patch in bank.patches.iter() {
+        patch.set_from_patch_defaults();
+    }
+
+    bank
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_with_gui_state() {
+        let sync_1 = SyncState::<()>::new(None);
+
+        sync_1.patches.set_patch_name("with gui state");
+        sync_1.set_gui_state_json("{\"patch_preview\":true}".into());
+
+        for mut bytes in [
+            sync_1.patches.export_fxb_bytes(),
+            sync_1.patches.export_plain_bytes(),
+        ] {
+            sync_1.append_gui_state(&mut bytes);
+
+            // Versions without GUI state support ignore trailing data
+            let bank = PatchBank::default();
+
+            bank.import_bank_from_bytes(&bytes).unwrap();
+
+            assert_eq!(bank.get_current_patch_name(), "with gui state");
+
+            let sync_2 = SyncState::<()>::new(None);
+
+            sync_2.import_state_from_bytes(&bytes).unwrap();
+
+            assert_eq!(sync_2.patches.get_current_patch_name(), "with gui state");
+            assert_eq!(
+                sync_2.get_gui_state_json().as_deref(),
+                sync_1.get_gui_state_json().as_deref()
+            );
+        }
+
+        // State without GUI state clears it
+        sync_1
+            .import_state_from_bytes(&sync_1.patches.export_plain_bytes())
+            .unwrap();
+
+        assert!(sync_1.get_gui_state_json().is_none());
+    }
+
+    #[test]
+    fn test_gui_expression_events() {
+        let sync = SyncState::<()>::new(None);
+
+        assert_eq!(sync.take_gui_expression_events().count(), 0);
+
+        sync.send_gui_mod_wheel(0.5);
+        sync.send_gui_pitch_bend(1.0);
+        sync.send_gui_pitch_bend(0.0);
+
+        let data: Vec<[u8; 3]> = sync
+            .take_gui_expression_events()
+            .map(|event| match event.event {
+                NoteEventInner::Midi { data } => data,
+                _ => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(data, vec![[0b_1110_0000, 0, 64], [0b_1011_0000, 1, 64]]);
+        assert_eq!(sync.take_gui_expression_events().count(), 0);
+
+        sync.send_gui_pitch_bend(-1.0);
+
+        let event = sync.take_gui_expression_events().next().unwrap();
+
+        assert!(matches!(
+            event.event,
+            NoteEventInner::Midi {
+                data: [0b_1110_0000, 0, 0]
+            }
+        ));
+    }
 }
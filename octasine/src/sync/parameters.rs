@@ -3,10 +3,35 @@ use compact_str::CompactString;
 use crate::{
     common::IndexMap,
     parameters::{
-        glide_active::GlideActiveValue, glide_bpm_sync::GlideBpmSyncValue,
-        glide_mode::GlideModeValue, glide_retrigger::GlideRetriggerValue,
-        glide_time::GlideTimeValue, velocity_sensitivity::VelocitySensitivityValue,
-        voice_mode::VoiceModeValue, *,
+        aftertouch_vibrato::{AftertouchVibratoDepthValue, AftertouchVibratoRateValue},
+        bypass::BypassValue,
+        dc_blocker::DcBlockerValue,
+        effect_order::EffectOrderValue,
+        gain_compensation::GainCompensationValue,
+        glide_active::GlideActiveValue,
+        glide_bpm_sync::GlideBpmSyncValue,
+        glide_mode::GlideModeValue,
+        glide_retrigger::GlideRetriggerValue,
+        glide_time::GlideTimeValue,
+        key_pan::KeyPanValue,
+        key_range::{KeyRangeHighValue, KeyRangeLowValue},
+        master_eq::{
+            MasterEqBypassValue, MasterEqGainValue, MasterEqHighFrequencyValue,
+            MasterEqLowFrequencyValue, MasterEqMidFrequencyValue,
+        },
+        mod_out_scaling::ModOutScalingValue,
+        mono_note_priority::MonoNotePriorityValue,
+        output_mode::OutputModeValue,
+        pitch_bend_smoothing::PitchBendSmoothingValue,
+        scale_quantization::{ScaleRootValue, ScaleValue},
+        unison::{UnisonDetuneValue, UnisonSpreadValue, UnisonVoicesValue},
+        velocity_mod::{VelocityModAmountValue, VelocityModTargetValue},
+        velocity_sensitivity::{
+            AttackVelocitySensitivityValue, MixOutVelocitySensitivityValue,
+            ReleaseVelocitySensitivityValue, VelocitySensitivityValue,
+        },
+        voice_mode::VoiceModeValue,
+        *,
     },
 };
 
@@ -24,6 +49,9 @@ pub struct PatchParameter {
     pub default_value: f32,
     pub clap_path: CompactString,
     pub parameter: WrappedParameter,
+    /// Convert patch value to parameter value and back
+    #[cfg(test)]
+    patch_value_roundtrip: fn(f32) -> f32,
 }
 
 impl PatchParameter {
@@ -60,6 +88,53 @@ impl PatchParameter {
                 MasterParameter::GlideBpmSync => Self::new::<GlideBpmSyncValue>(parameter),
                 MasterParameter::GlideMode => Self::new::<GlideModeValue>(parameter),
                 MasterParameter::GlideRetrigger => Self::new::<GlideRetriggerValue>(parameter),
+                MasterParameter::Pan => Self::new::<MasterPanValue>(parameter),
+                MasterParameter::OutputMode => Self::new::<OutputModeValue>(parameter),
+                MasterParameter::DcBlocker => Self::new::<DcBlockerValue>(parameter),
+                MasterParameter::ModOutScaling => Self::new::<ModOutScalingValue>(parameter),
+                MasterParameter::VelocityModTarget => {
+                    Self::new::<VelocityModTargetValue>(parameter)
+                }
+                MasterParameter::VelocityModAmount => {
+                    Self::new::<VelocityModAmountValue>(parameter)
+                }
+                MasterParameter::Scale => Self::new::<ScaleValue>(parameter),
+                MasterParameter::ScaleRoot => Self::new::<ScaleRootValue>(parameter),
+                MasterParameter::MonoNotePriority => Self::new::<MonoNotePriorityValue>(parameter),
+                MasterParameter::UnisonVoices => Self::new::<UnisonVoicesValue>(parameter),
+                MasterParameter::UnisonDetune => Self::new::<UnisonDetuneValue>(parameter),
+                MasterParameter::UnisonSpread => Self::new::<UnisonSpreadValue>(parameter),
+                MasterParameter::KeyRangeLow => Self::new::<KeyRangeLowValue>(parameter),
+                MasterParameter::KeyRangeHigh => Self::new::<KeyRangeHighValue>(parameter),
+                MasterParameter::AftertouchVibratoDepth => {
+                    Self::new::<AftertouchVibratoDepthValue>(parameter)
+                }
+                MasterParameter::AftertouchVibratoRate => {
+                    Self::new::<AftertouchVibratoRateValue>(parameter)
+                }
+                MasterParameter::Bypass => Self::new::<BypassValue>(parameter),
+                MasterParameter::EqLowGain => Self::new::<MasterEqGainValue>(parameter),
+                MasterParameter::EqLowFrequency => {
+                    Self::new::<MasterEqLowFrequencyValue>(parameter)
+                }
+                MasterParameter::EqMidGain => Self::new::<MasterEqGainValue>(parameter),
+                MasterParameter::EqMidFrequency => {
+                    Self::new::<MasterEqMidFrequencyValue>(parameter)
+                }
+                MasterParameter::EqHighGain => Self::new::<MasterEqGainValue>(parameter),
+                MasterParameter::EqHighFrequency => {
+                    Self::new::<MasterEqHighFrequencyValue>(parameter)
+                }
+                MasterParameter::EqBypass => Self::new::<MasterEqBypassValue>(parameter),
+                MasterParameter::EffectOrder => Self::new::<EffectOrderValue>(parameter),
+                MasterParameter::KeyPan => Self::new::<KeyPanValue>(parameter),
+                MasterParameter::ReleaseVelocitySensitivity => {
+                    Self::new::<ReleaseVelocitySensitivityValue>(parameter)
+                }
+                MasterParameter::GainCompensation => Self::new::<GainCompensationValue>(parameter),
+                MasterParameter::PitchBendSmoothing => {
+                    Self::new::<PitchBendSmoothingValue>(parameter)
+                }
             },
             Parameter::Operator(index, operator_parameter) => {
                 use OperatorParameter::*;
@@ -87,13 +162,35 @@ impl PatchParameter {
                         3 => Self::new::<Operator4ModulationTargetValue>(parameter),
                         _ => panic!("Unsupported parameter"),
                     },
-                    ModOut => match index {
-                        1 | 2 | 3 => Self::new::<OperatorModOutValue>(parameter),
+                    ModOut => Self::new::<OperatorModOutValue>(parameter),
+                    BackwardModTargets => match index {
+                        0 => Self::new::<Operator1BackwardModulationTargetValue>(parameter),
+                        1 => Self::new::<Operator2BackwardModulationTargetValue>(parameter),
+                        2 => Self::new::<Operator3BackwardModulationTargetValue>(parameter),
                         _ => panic!("Unsupported parameter"),
                     },
                     VelocitySensitivityFeedback | VelocitySensitivityModOut => {
                         Self::new::<VelocitySensitivityValue>(parameter)
                     }
+                    VelocitySensitivityAttack => {
+                        Self::new::<AttackVelocitySensitivityValue>(parameter)
+                    }
+                    VelocitySensitivityMixOut => {
+                        Self::new::<MixOutVelocitySensitivityValue>(parameter)
+                    }
+                    FeedbackTone => Self::new::<OperatorFeedbackToneValue>(parameter),
+                    OutputBus => Self::new::<OperatorOutputBusValue>(parameter),
+                    PanningMode => Self::new::<OperatorPanningModeValue>(parameter),
+                    PanningLfoMode => Self::new::<OperatorPanningLfoModeValue>(parameter),
+                    KeyScalingBreakpoint => {
+                        Self::new::<OperatorKeyScalingBreakpointValue>(parameter)
+                    }
+                    KeyScalingLeftDepth | KeyScalingRightDepth => {
+                        Self::new::<OperatorKeyScalingDepthValue>(parameter)
+                    }
+                    KeyScalingLeftCurve | KeyScalingRightCurve => {
+                        Self::new::<OperatorKeyScalingCurveValue>(parameter)
+                    }
                 }
             }
             Parameter::Lfo(index, lfo_parameter) => {
@@ -107,7 +204,7 @@ impl PatchParameter {
                     Shape => Self::new::<LfoShapeValue>(parameter),
                     Amount => Self::new::<LfoAmountValue>(parameter),
                     Active => Self::new::<LfoActiveValue>(parameter),
-                    Target => match index {
+                    Target | Target2 | Target3 | Target4 => match index {
                         0 => Self::new::<Lfo1TargetParameterValue>(parameter),
                         1 => Self::new::<Lfo2TargetParameterValue>(parameter),
                         2 => Self::new::<Lfo3TargetParameterValue>(parameter),
@@ -115,6 +212,12 @@ impl PatchParameter {
                         _ => panic!("Unsupported parameter"),
                     },
                     KeySync => Self::new::<LfoKeySyncValue>(parameter),
+                    Global => Self::new::<LfoGlobalValue>(parameter),
+                    Smoothing => Self::new::<LfoSmoothingValue>(parameter),
+                    Target2Depth | Target3Depth | Target4Depth => {
+                        Self::new::<LfoTargetDepthValue>(parameter)
+                    }
+                    ModWheelDepth => Self::new::<LfoModWheelDepthValue>(parameter),
                 }
             }
         }
@@ -131,6 +234,8 @@ impl PatchParameter {
             default_value: V::default().to_patch(),
             clap_path: parameter.parameter().clap_path(),
             parameter,
+            #[cfg(test)]
+            patch_value_roundtrip: |v| V::new_from_patch(v).to_patch(),
         }
     }
     fn new_with_value<V: ParameterValue>(parameter: WrappedParameter, v: V) -> Self {
@@ -156,20 +261,12 @@ impl PatchParameter {
     pub fn get_serializable(&self) -> SerializableRepresentation {
         (self.get_serializable)(self.value.get())
     }
-
-    pub fn set_from_text(&self, text: &str) -> bool {
-        if let Some(value) = (self.value_from_text)(text) {
-            self.value.set(value);
-
-            true
-        } else {
-            false
-        }
-    }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use crate::sync::change_info::MAX_NUM_PARAMETERS;
 
     use super::PatchParameter;
@@ -178,4 +275,73 @@ mod tests {
     fn test_patch_parameters_len() {
         assert!(PatchParameter::all().len() <= MAX_NUM_PARAMETERS);
     }
+
+    proptest! {
+        /// Converting a patch value to a parameter value and back should
+        /// land in range and be stable, i.e., converting the result again
+        /// should not change it
+        #[test]
+        fn test_patch_value_roundtrip(value in 0.0f32..=1.0) {
+            for parameter in PatchParameter::all().values() {
+                let once = (parameter.patch_value_roundtrip)(value);
+                let twice = (parameter.patch_value_roundtrip)(once);
+
+                prop_assert!(
+                    (0.0..=1.0).contains(&once),
+                    "{}: {} converted to out-of-range {}", parameter.name, value, once
+                );
+                prop_assert!(
+                    (once - twice).abs() < 1.0e-5,
+                    "{}: {} converted to {}, then to {}", parameter.name, value, once, twice
+                );
+            }
+        }
+
+        /// Parsing formatted value should succeed and yield a value with the
+        /// same formatting. Continuous parameters may be displayed with more
+        /// precision than survives conversion to patch value, so a parsed
+        /// value very close to the original is accepted too.
+        #[test]
+        fn test_text_roundtrip(value in 0.0f32..=1.0) {
+            for parameter in PatchParameter::all().values() {
+                let text = (parameter.format)(value);
+                let parsed = (parameter.value_from_text)(&text);
+
+                prop_assert!(parsed.is_some(), "{}: couldn't parse {:?}", parameter.name, text);
+
+                let parsed = parsed.unwrap();
+                let reformatted = (parameter.format)(parsed);
+                let expected = (parameter.patch_value_roundtrip)(value);
+
+                prop_assert!(
+                    reformatted == text || (parsed - expected).abs() < 1.0e-4,
+                    "{}: {} formatted as {:?}, parsed and reformatted as {:?}",
+                    parameter.name,
+                    value,
+                    text,
+                    reformatted
+                );
+            }
+        }
+
+        /// Parsing should not depend on case or surrounding whitespace
+        #[test]
+        fn test_text_variants(value in 0.0f32..=1.0) {
+            for parameter in PatchParameter::all().values() {
+                let text = (parameter.format)(value);
+                let expected = (parameter.value_from_text)(&text);
+
+                for variant in [text.to_lowercase(), text.to_uppercase(), format!(" {} ", text)] {
+                    prop_assert_eq!(
+                        (parameter.value_from_text)(&variant),
+                        expected,
+                        "{}: {:?} parsed differently from {:?}",
+                        parameter.name,
+                        variant,
+                        text
+                    );
+                }
+            }
+        }
+    }
 }
@@ -1,5 +1,4 @@
 use std::{
-    collections::VecDeque,
     io::Read,
     path::PathBuf,
     sync::{
@@ -12,15 +11,30 @@ use arc_swap::ArcSwap;
 use array_init::array_init;
 use compact_str::{format_compact, CompactString};
 
-use crate::{common::IndexMap, parameters::ParameterKey};
+use crate::{
+    audio::timbres::NUM_TIMBRES,
+    common::IndexMap,
+    parameters::{
+        key_tuning::KeyDetuneValue,
+        master_frequency::MasterFrequencyValue,
+        mod_out_scaling::{ModOutScaling, ModOutScalingValue},
+        MasterParameter, OperatorModOutValue, OperatorParameter, Parameter, ParameterKey,
+        ParameterValue,
+    },
+};
 
-use super::change_info::{ParameterChangeInfo, MAX_NUM_PARAMETERS};
+use super::atomic_float::AtomicFloat;
+use super::change_info::{ParameterChangeInfo, ParameterChangeQueue, MAX_NUM_PARAMETERS};
 use super::parameters::PatchParameter;
+use super::patch_defaults::patch_default_overrides;
 use super::serde::*;
+use super::sysex::ConvertedVoice;
 
 pub struct Patch {
     name: ArcSwap<String>,
     pub parameters: IndexMap<ParameterKey, PatchParameter>,
+    /// Detuning of each key in cents
+    key_tuning: [AtomicFloat; 128],
 }
 
 impl Default for Patch {
@@ -34,9 +48,20 @@ impl Patch {
         Self {
             name: ArcSwap::new(Arc::new(Self::process_name(name))),
             parameters,
+            key_tuning: array_init(|_| AtomicFloat::new(0.0)),
         }
     }
 
+    /// Parameter values of init patch, i.e., of cleared patch, in parameter
+    /// index order
+    pub fn init_parameter_values() -> Vec<f32> {
+        let patch = Self::default();
+
+        patch.set_from_patch_defaults();
+
+        patch.parameters.values().map(|p| p.get_value()).collect()
+    }
+
     pub fn get_fxp_filename(&self) -> CompactString {
         match self.name.load_full().as_str() {
             "" => "-.fxp".into(),
@@ -48,6 +73,29 @@ impl Patch {
         serialize_patch_fxp_bytes(self).expect("serialize patch")
     }
 
+    /// Export patch as human-readable JSON, e.g. for editing with scripts
+    pub fn export_json(&self) -> String {
+        serialize_patch_json(self).expect("serialize patch")
+    }
+
+    /// Create patch from fxp or plain patch bytes
+    pub fn new_from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let patch = Self::default();
+
+        patch.update_from_bytes(bytes)?;
+
+        Ok(patch)
+    }
+
+    /// Create patch from JSON exported with `export_json`
+    pub fn new_from_json(json: &str) -> anyhow::Result<Self> {
+        let patch = Self::default();
+
+        update_patch_from_json(&patch, json)?;
+
+        Ok(patch)
+    }
+
     pub fn get_name(&self) -> String {
         (*self.name.load_full()).clone()
     }
@@ -56,6 +104,62 @@ impl Patch {
         self.name.store(Arc::new(Self::process_name(name)));
     }
 
+    /// Get detuning of key in cents relative to twelve-tone equal
+    /// temperament
+    pub fn get_key_detune(&self, key: u8) -> f32 {
+        self.key_tuning
+            .get(key as usize)
+            .map_or(0.0, AtomicFloat::get)
+    }
+
+    pub(super) fn set_key_detune(&self, key: u8, cents: f32) {
+        if let Some(key_detune) = self.key_tuning.get(key as usize) {
+            key_detune.set(KeyDetuneValue::new(cents).get());
+        }
+    }
+
+    fn reset_key_tuning(&self) {
+        for key_detune in self.key_tuning.iter() {
+            key_detune.set(0.0);
+        }
+    }
+
+    /// Format parameter value, taking patch-wide settings into account
+    pub fn format_parameter_value(&self, parameter: &PatchParameter, value: f32) -> CompactString {
+        if self.uses_dx7_mod_out_scaling(parameter) {
+            OperatorModOutValue::get_formatted_dx7(value)
+        } else {
+            (parameter.format)(value)
+        }
+    }
+
+    /// Parse parameter value text, taking patch-wide settings into account
+    pub fn parse_parameter_text(&self, parameter: &PatchParameter, text: &str) -> Option<f32> {
+        if self.uses_dx7_mod_out_scaling(parameter) {
+            OperatorModOutValue::new_from_text_dx7(text)
+        } else {
+            (parameter.value_from_text)(text)
+        }
+    }
+
+    fn uses_dx7_mod_out_scaling(&self, parameter: &PatchParameter) -> bool {
+        const SCALING_INDEX: usize =
+            Parameter::Master(MasterParameter::ModOutScaling).to_index() as usize;
+
+        if !matches!(
+            parameter.parameter.parameter(),
+            Parameter::Operator(_, OperatorParameter::ModOut)
+        ) {
+            return false;
+        }
+
+        self.parameters
+            .get_index(SCALING_INDEX)
+            .is_some_and(|(_, p)| {
+                ModOutScalingValue::new_from_patch(p.get_value()).get() == ModOutScaling::Dx7
+            })
+    }
+
     fn process_name(name: &str) -> String {
         name.chars()
             .filter(|c| c.is_ascii_graphic() || *c == ' ')
@@ -66,6 +170,32 @@ impl Patch {
         update_patch_from_bytes(self, bytes)
     }
 
+    /// Reset patch and apply parameter values of converted SysEx voice
+    fn set_from_converted_voice(&self, voice: &ConvertedVoice) {
+        self.set_from_patch_parameters(&PatchParameter::all());
+        self.set_name(&voice.name);
+
+        for (parameter, value) in voice.parameter_values.iter() {
+            if let Some(patch_parameter) = self.parameters.get(&parameter.key()) {
+                patch_parameter.set_value(*value);
+            }
+        }
+    }
+
+    /// Set name, parameter values and key tuning from other patch
+    fn set_from_patch(&self, other: &Patch) {
+        self.set_name(&other.get_name());
+
+        for (parameter, other_parameter) in self.parameters.values().zip(other.parameters.values())
+        {
+            parameter.set_value(other_parameter.get_value());
+        }
+
+        for (key_detune, other_key_detune) in self.key_tuning.iter().zip(other.key_tuning.iter()) {
+            key_detune.set(other_key_detune.get());
+        }
+    }
+
     fn set_from_patch_parameters(&self, parameters: &IndexMap<ParameterKey, PatchParameter>) {
         self.set_name("-");
 
@@ -76,16 +206,39 @@ impl Patch {
         {
             parameter.set_value(default_value);
         }
+
+        self.reset_key_tuning();
+        self.set_from_patch_defaults();
+    }
+
+    /// Apply voice settings that new patches should start with according to
+    /// settings
+    pub(super) fn set_from_patch_defaults(&self) {
+        for (parameter, value) in patch_default_overrides() {
+            if let Some((_, patch_parameter)) =
+                self.parameters.get_index(parameter.to_index() as usize)
+            {
+                patch_parameter.set_value(*value);
+            }
+        }
     }
 }
 
 pub struct PatchBank {
     pub patches: [Patch; 128],
     patch_index: AtomicUsize,
-    parameter_change_info_audio: ParameterChangeInfo,
+    parameter_change_queue_audio: ParameterChangeQueue,
+    /// Change queues for patches following current one, which are played by
+    /// timbres in multitimbral mode
+    parameter_change_queues_timbres: [ParameterChangeQueue; NUM_TIMBRES],
     pub parameter_change_info_gui: ParameterChangeInfo,
     patches_changed: AtomicBool,
+    /// Set when patch index changes, so that audio thread can crossfade
+    /// parameter values
+    patch_switched_audio: AtomicBool,
     envelope_viewports_changed: AtomicBool,
+    /// Parameters excluded from randomization. Not stored in patches.
+    parameter_locks: [AtomicBool; MAX_NUM_PARAMETERS],
 }
 
 impl Default for PatchBank {
@@ -99,10 +252,13 @@ impl PatchBank {
         Self {
             patches: array_init(|_| Patch::new("-", parameters())),
             patch_index: AtomicUsize::new(0),
-            parameter_change_info_audio: ParameterChangeInfo::default(),
+            parameter_change_queue_audio: ParameterChangeQueue::default(),
+            parameter_change_queues_timbres: array_init(|_| ParameterChangeQueue::default()),
             parameter_change_info_gui: ParameterChangeInfo::default(),
             patches_changed: AtomicBool::new(false),
+            patch_switched_audio: AtomicBool::new(false),
             envelope_viewports_changed: AtomicBool::new(false),
+            parameter_locks: array_init(|_| AtomicBool::new(false)),
         }
     }
 
@@ -133,9 +289,20 @@ impl PatchBank {
         &self.patches[self.get_patch_index()]
     }
 
+    /// Mark all parameters of current patch as changed. Since the patches
+    /// played by timbres follow the current one, this also marks them.
     fn mark_parameters_as_changed(&self) {
-        self.parameter_change_info_audio.mark_all_as_changed();
+        self.parameter_change_queue_audio.mark_all_as_changed();
         self.parameter_change_info_gui.mark_all_as_changed();
+
+        for queue in self.parameter_change_queues_timbres.iter() {
+            queue.mark_all_as_changed();
+        }
+    }
+
+    /// Index of patch played by timbre with given index
+    fn get_timbre_patch_index(&self, timbre_index: usize) -> usize {
+        (self.get_patch_index() + timbre_index + 1) % self.patches.len()
     }
 
     // Number of patches / parameters
@@ -162,6 +329,7 @@ impl PatchBank {
 
         self.patch_index.store(index, Ordering::SeqCst);
         self.patches_changed.store(true, Ordering::SeqCst);
+        self.patch_switched_audio.store(true, Ordering::SeqCst);
         self.mark_parameters_as_changed();
         self.envelope_viewports_changed
             .store(true, Ordering::SeqCst);
@@ -194,13 +362,120 @@ impl PatchBank {
     pub fn have_patches_changed(&self) -> bool {
         self.patches_changed.fetch_and(false, Ordering::SeqCst)
     }
+
+    /// Swap contents of two patch slots, e.g., when reordering patches.
+    /// Current patch index is left unchanged.
+    pub fn swap_patches(&self, index_a: usize, index_b: usize) {
+        let (patch_a, patch_b) = match (self.patches.get(index_a), self.patches.get(index_b)) {
+            (Some(patch_a), Some(patch_b)) if index_a != index_b => (patch_a, patch_b),
+            _ => return,
+        };
+
+        let name_a = patch_a.get_name();
+        let values_a = patch_a
+            .parameters
+            .values()
+            .map(PatchParameter::get_value)
+            .collect::<Vec<_>>();
+        let key_tuning_a = patch_a.key_tuning.each_ref().map(AtomicFloat::get);
+
+        patch_a.set_from_patch(patch_b);
+
+        patch_b.set_name(&name_a);
+
+        for (parameter, value) in patch_b.parameters.values().zip(values_a) {
+            parameter.set_value(value);
+        }
+        for (key_detune, cents) in patch_b.key_tuning.iter().zip(key_tuning_a) {
+            key_detune.set(cents);
+        }
+
+        self.mark_patch_contents_as_changed(&[index_a, index_b]);
+    }
+
+    /// Overwrite contents of patch slot with contents of another one
+    pub fn copy_patch(&self, from_index: usize, to_index: usize) {
+        let (from, to) = match (self.patches.get(from_index), self.patches.get(to_index)) {
+            (Some(from), Some(to)) if from_index != to_index => (from, to),
+            _ => return,
+        };
+
+        to.set_from_patch(from);
+
+        self.mark_patch_contents_as_changed(&[to_index]);
+    }
+
+    /// Set change flags after contents of patch slots with given indices
+    /// were replaced
+    fn mark_patch_contents_as_changed(&self, indices: &[usize]) {
+        if indices.contains(&self.get_patch_index()) {
+            self.mark_parameters_as_changed();
+            self.envelope_viewports_changed
+                .store(true, Ordering::SeqCst);
+        }
+
+        for (timbre_index, queue) in self.parameter_change_queues_timbres.iter().enumerate() {
+            if indices.contains(&self.get_timbre_patch_index(timbre_index)) {
+                queue.mark_all_as_changed();
+            }
+        }
+
+        self.patches_changed.store(true, Ordering::SeqCst);
+    }
 }
 
 // Get parameter changes
 impl PatchBank {
-    pub fn get_changed_parameters_from_audio(&self) -> Option<[Option<f32>; MAX_NUM_PARAMETERS]> {
-        self.parameter_change_info_audio
-            .get_changed_parameters(&self.get_current_patch().parameters)
+    /// Call `f` with index and value of each parameter changed since last
+    /// call. Only call from audio thread.
+    pub fn drain_changed_parameters_from_audio<F: FnMut(usize, f32)>(&self, f: F) {
+        self.parameter_change_queue_audio
+            .drain(&self.get_current_patch().parameters, f)
+    }
+
+    /// Call `f` with index and value of each parameter changed since last
+    /// call in patch played by timbre with given index in multitimbral mode.
+    /// Only call from audio thread.
+    pub fn drain_changed_timbre_parameters_from_audio<F: FnMut(usize, f32)>(
+        &self,
+        timbre_index: usize,
+        f: F,
+    ) {
+        if let Some(queue) = self.parameter_change_queues_timbres.get(timbre_index) {
+            let patch = &self.patches[self.get_timbre_patch_index(timbre_index)];
+
+            queue.drain(&patch.parameters, f)
+        }
+    }
+
+    /// Call `f` with key and detuning in cents of each key if key tuning of
+    /// current patch changed since last call. Only call from audio thread.
+    pub fn drain_changed_key_tuning_from_audio<F: FnMut(u8, f32)>(&self, f: F) {
+        self.parameter_change_queue_audio
+            .drain_key_tuning(&self.get_current_patch().key_tuning, f)
+    }
+
+    /// Call `f` with key and detuning in cents of each key if key tuning of
+    /// patch played by timbre with given index changed since last call. Only
+    /// call from audio thread.
+    pub fn drain_changed_timbre_key_tuning_from_audio<F: FnMut(u8, f32)>(
+        &self,
+        timbre_index: usize,
+        f: F,
+    ) {
+        if let Some(queue) = self.parameter_change_queues_timbres.get(timbre_index) {
+            let patch = &self.patches[self.get_timbre_patch_index(timbre_index)];
+
+            queue.drain_key_tuning(&patch.key_tuning, f)
+        }
+    }
+
+    /// Whether patch index changed since last call. Only call from audio
+    /// thread.
+    pub fn take_patch_switched_from_audio(&self) -> bool {
+        // Avoid atomic write in common case, since this is called often
+        self.patch_switched_audio.load(Ordering::Relaxed)
+            && self.patch_switched_audio.swap(false, Ordering::SeqCst)
     }
 
     pub fn get_changed_parameters_from_gui(&self) -> Option<[Option<f32>; MAX_NUM_PARAMETERS]> {
@@ -219,10 +494,12 @@ impl PatchBank {
     }
 
     pub fn get_parameter_value_text(&self, index: usize) -> Option<CompactString> {
-        self.get_current_patch()
+        let patch = self.get_current_patch();
+
+        patch
             .parameters
             .get_index(index)
-            .map(|(_, p)| (p.get_value_text()))
+            .map(|(_, p)| patch.format_parameter_value(p, p.get_value()))
     }
 
     pub fn get_parameter_name(&self, index: usize) -> Option<CompactString> {
@@ -233,10 +510,12 @@ impl PatchBank {
     }
 
     pub fn format_parameter_value(&self, index: usize, value: f32) -> Option<CompactString> {
-        self.get_current_patch()
+        let patch = self.get_current_patch();
+
+        patch
             .parameters
             .get_index(index)
-            .map(|(_, p)| (p.format)(value))
+            .map(|(_, p)| patch.format_parameter_value(p, value))
     }
 }
 
@@ -248,7 +527,7 @@ impl PatchBank {
         if let Some(parameter) = opt_parameter {
             parameter.set_value(value.min(1.0).max(0.0));
 
-            self.parameter_change_info_audio.mark_as_changed(index);
+            self.parameter_change_queue_audio.push(index);
         }
     }
 
@@ -258,7 +537,7 @@ impl PatchBank {
         if let Some(parameter) = opt_parameter {
             parameter.set_value(value);
 
-            self.parameter_change_info_audio.mark_as_changed(index);
+            self.parameter_change_queue_audio.push(index);
             self.parameter_change_info_gui.mark_as_changed(index);
         }
     }
@@ -267,8 +546,13 @@ impl PatchBank {
         let opt_parameter = self.get_parameter_by_index(index);
 
         if let Some(parameter) = opt_parameter {
-            if parameter.set_from_text(value) {
-                self.parameter_change_info_audio.mark_as_changed(index);
+            if let Some(value) = self
+                .get_current_patch()
+                .parse_parameter_text(parameter, value)
+            {
+                parameter.set_value(value);
+
+                self.parameter_change_queue_audio.push(index);
                 self.parameter_change_info_gui.mark_as_changed(index);
 
                 return true;
@@ -282,8 +566,13 @@ impl PatchBank {
         let opt_parameter = self.get_parameter_by_index(index);
 
         if let Some(parameter) = opt_parameter {
-            if parameter.set_from_text(value) {
-                self.parameter_change_info_audio.mark_as_changed(index);
+            if let Some(value) = self
+                .get_current_patch()
+                .parse_parameter_text(parameter, value)
+            {
+                parameter.set_value(value);
+
+                self.parameter_change_queue_audio.push(index);
 
                 return true;
             }
@@ -295,63 +584,36 @@ impl PatchBank {
 
 // Import / export
 impl PatchBank {
-    pub fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf]) {
-        let mut bank_file_bytes = Vec::new();
-        let mut patch_file_bytes = VecDeque::new();
-
-        for path in paths {
-            match read_file(path) {
-                Ok(bytes) => match path.extension().and_then(|s| s.to_str()) {
-                    Some("fxb") => {
-                        bank_file_bytes.push(bytes);
+    /// Import files read with [ImportFiles::read]. If they include a bank,
+    /// replace current bank with it. Otherwise, import patches into current
+    /// and following patches, then converted SysEx voices into the patches
+    /// after those.
+    pub fn import_files(&self, files: &ImportFiles) {
+        if let Some(bank) = files.bank.as_ref() {
+            update_bank_from_parsed(self, bank);
+
+            self.set_patch_index(0);
+        } else {
+            let mut patch_index = self.get_patch_index();
+
+            for parsed_patch in files.patches.iter() {
+                match self.patches.get(patch_index) {
+                    Some(patch) => {
+                        update_patch_from_parsed(patch, parsed_patch);
+
+                        patch_index += 1;
                     }
-                    Some("fxp") => {
-                        patch_file_bytes.push_back(bytes);
-                    }
-                    _ => {
-                        ::log::warn!("Ignored file without fxp or fxb file extension");
-                    }
-                },
-                Err(err) => ::log::warn!(
-                    "Failed loading bank / patch bank from file {}: {:#}",
-                    path.display(),
-                    err
-                ),
-            };
-        }
-
-        match bank_file_bytes.pop() {
-            Some(bank_bytes) => {
-                if let Err(err) = self.import_bank_from_bytes(&bank_bytes) {
-                    ::log::error!("failed importing patch bank: {:#}", err);
+                    None => break,
                 }
             }
-            None => {
-                // Import serde patches into current and following patches
-                let mut patch_iterator = self.patches[self.get_patch_index()..].iter().peekable();
-
-                for patch_bytes in patch_file_bytes {
-                    if patch_iterator.peek().is_none() {
-                        break;
-                    }
-
-                    patch_iterator.next_if(|patch| {
-                        if let Err(err) = patch.update_from_bytes(&patch_bytes) {
-                            ::log::error!("failed importing patch: {:#}", err);
 
-                            false
-                        } else {
-                            true
-                        }
-                    });
-                }
-
-                self.mark_parameters_as_changed();
-                self.patches_changed.store(true, Ordering::SeqCst);
-                self.envelope_viewports_changed
-                    .store(true, Ordering::SeqCst);
-            }
+            self.set_patches_from_converted_voices(patch_index, &files.voices);
         }
+
+        self.mark_parameters_as_changed();
+        self.patches_changed.store(true, Ordering::SeqCst);
+        self.envelope_viewports_changed
+            .store(true, Ordering::SeqCst);
     }
 
     /// Import bytes into current bank, set sync parameters
@@ -370,6 +632,35 @@ impl PatchBank {
         }
     }
 
+    /// Import TX81Z / DX21 / DX11 voice SysEx into current and following
+    /// patches
+    pub fn import_tx81z_sysex_from_bytes(&self, bytes: &[u8]) -> anyhow::Result<()> {
+        let voices = super::sysex::tx81z::convert_voices(bytes)?;
+
+        self.set_patches_from_converted_voices(self.get_patch_index(), &voices);
+
+        self.mark_parameters_as_changed();
+        self.patches_changed.store(true, Ordering::SeqCst);
+        self.envelope_viewports_changed
+            .store(true, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    /// Set patches from start index onwards from converted voices. Voices
+    /// that don't fit in bank are ignored.
+    fn set_patches_from_converted_voices(&self, start_index: usize, voices: &[ConvertedVoice]) {
+        let patches = self.patches.get(start_index..).unwrap_or_default();
+
+        for (patch, voice) in patches.iter().zip(voices.iter()) {
+            for warning in voice.warnings.iter() {
+                ::log::info!("SysEx voice \"{}\": {}", voice.name, warning);
+            }
+
+            patch.set_from_converted_voice(voice);
+        }
+    }
+
     pub fn import_bytes_into_current_patch(&self, bytes: &[u8]) {
         match self.get_current_patch().update_from_bytes(bytes) {
             Ok(()) => {
@@ -396,6 +687,35 @@ impl PatchBank {
         serialize_bank_fxb_bytes(self).expect("serialize preset bank")
     }
 
+    /// Export bank as human-readable JSON, e.g. for editing with scripts
+    pub fn export_json(&self) -> String {
+        serialize_bank_json(self).expect("serialize preset bank")
+    }
+
+    /// Import JSON exported with `export_json` into current bank, set sync
+    /// parameters
+    pub fn import_bank_from_json(&self, json: &str) -> anyhow::Result<()> {
+        update_bank_from_json(self, json)?;
+
+        self.set_patch_index(0);
+        self.mark_parameters_as_changed();
+        self.patches_changed.store(true, Ordering::SeqCst);
+        self.envelope_viewports_changed
+            .store(true, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    /// Create bank from converted SysEx voices. Patches without a
+    /// corresponding voice keep default values.
+    pub fn new_from_converted_voices(voices: &[ConvertedVoice]) -> Self {
+        let bank = Self::default();
+
+        bank.set_patches_from_converted_voices(0, voices);
+
+        bank
+    }
+
     pub fn new_from_bytes(bytes: &[u8]) -> Self {
         let preset_bank = Self::default();
 
@@ -435,6 +755,176 @@ impl PatchBank {
     }
 }
 
+// Key tuning
+impl PatchBank {
+    /// Get detuning in cents of each key in current patch
+    pub fn get_key_tuning(&self) -> [f32; 128] {
+        self.get_current_patch()
+            .key_tuning
+            .each_ref()
+            .map(AtomicFloat::get)
+    }
+
+    pub fn set_key_detune_from_gui(&self, key: u8, cents: f32) {
+        self.get_current_patch().set_key_detune(key, cents);
+        self.parameter_change_queue_audio.push_key_tuning();
+    }
+
+    /// Set detuning of key in current patch from text in cents or as
+    /// frequency relative to master frequency of patch. Returns false if
+    /// text couldn't be parsed.
+    pub fn set_key_detune_text_from_gui(&self, key: u8, text: &str) -> bool {
+        let master_frequency = self
+            .get_parameter_by_key(&Parameter::Master(MasterParameter::Frequency).key())
+            .map(|p| MasterFrequencyValue::new_from_patch(p.get_value()).get())
+            .unwrap_or(MasterFrequencyValue::default().get());
+
+        match KeyDetuneValue::new_from_text(key, text, master_frequency) {
+            Some(value) => {
+                self.set_key_detune_from_gui(key, value.get());
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reset detuning of all keys in current patch
+    pub fn reset_key_tuning_from_gui(&self) {
+        self.get_current_patch().reset_key_tuning();
+        self.parameter_change_queue_audio.push_key_tuning();
+    }
+}
+
+// Parameter locks
+impl PatchBank {
+    pub fn set_parameter_locked(&self, index: usize, locked: bool) {
+        if let Some(lock) = self.parameter_locks.get(index) {
+            lock.store(locked, Ordering::SeqCst);
+        }
+    }
+
+    pub fn is_parameter_locked(&self, index: usize) -> bool {
+        self.parameter_locks
+            .get(index)
+            .is_some_and(|lock| lock.load(Ordering::SeqCst))
+    }
+
+    /// Set random values for all parameters in current patch that are not
+    /// locked
+    pub fn randomize_current_patch(&self) {
+        for (index, parameter) in self.get_current_patch().parameters.values().enumerate() {
+            if !self.is_parameter_locked(index) {
+                parameter.set_value(fastrand::f32());
+            }
+        }
+
+        self.mark_parameters_as_changed();
+        self.envelope_viewports_changed
+            .store(true, Ordering::SeqCst);
+    }
+}
+
+/// Contents of bank, patch and SysEx files, read and decoded ahead of
+/// importing them with [PatchBank::import_files]. Since reading and
+/// decoding can be slow for large files, this is suitable for doing on a
+/// background thread.
+#[derive(Default)]
+pub struct ImportFiles {
+    /// Last successfully decoded bank, if any bank files were given
+    bank: Option<ParsedBank>,
+    patches: Vec<ParsedPatch>,
+    voices: Vec<ConvertedVoice>,
+}
+
+impl ImportFiles {
+    /// Read and decode files based on their extensions. Errors are logged.
+    pub fn read(paths: &[PathBuf]) -> Self {
+        let mut bank_file_bytes = Vec::new();
+        let mut patch_file_bytes = Vec::new();
+        let mut sysex_file_bytes = Vec::new();
+
+        for path in paths {
+            match read_file(path) {
+                Ok(bytes) => match path.extension().and_then(|s| s.to_str()) {
+                    Some("fxb") => {
+                        bank_file_bytes.push(bytes);
+                    }
+                    Some("fxp") => {
+                        patch_file_bytes.push(bytes);
+                    }
+                    Some("syx") => {
+                        sysex_file_bytes.push(bytes);
+                    }
+                    _ => {
+                        ::log::warn!("Ignored file without fxp, fxb or syx file extension");
+                    }
+                },
+                Err(err) => ::log::warn!(
+                    "Failed loading bank / patch bank from file {}: {:#}",
+                    path.display(),
+                    err
+                ),
+            };
+        }
+
+        // Patch and SysEx files are ignored if a bank file is present
+        if let Some(bank_bytes) = bank_file_bytes.pop() {
+            return match parse_bank_bytes(&bank_bytes) {
+                Ok(bank) => Self {
+                    bank: Some(bank),
+                    ..Default::default()
+                },
+                Err(err) => {
+                    ::log::error!("failed importing patch bank: {:#}", err);
+
+                    Default::default()
+                }
+            };
+        }
+
+        let patches = patch_file_bytes
+            .iter()
+            .filter_map(|bytes| {
+                parse_patch_bytes(bytes)
+                    .map_err(|err| ::log::error!("failed importing patch: {:#}", err))
+                    .ok()
+            })
+            .collect();
+
+        let voices = sysex_file_bytes
+            .iter()
+            .flat_map(|bytes| {
+                super::sysex::convert_voices(bytes).unwrap_or_else(|err| {
+                    ::log::error!("failed importing SysEx file: {:#}", err);
+
+                    Vec::new()
+                })
+            })
+            .collect();
+
+        Self {
+            bank: None,
+            patches,
+            voices,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bank.is_none() && self.patches.is_empty() && self.voices.is_empty()
+    }
+}
+
+impl ::std::fmt::Debug for ImportFiles {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.debug_struct("ImportFiles")
+            .field("bank", &self.bank.is_some())
+            .field("patches", &self.patches.len())
+            .field("voices", &self.voices.len())
+            .finish()
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::sync::built_in_patch_bank;
@@ -467,31 +957,199 @@ pub mod tests {
 
             let bank_2 = PatchBank::new_from_bytes(&bank_1.export_fxb_bytes());
             let bank_3 = PatchBank::new_from_bytes(&bank_1.export_plain_bytes());
+            let bank_4 = PatchBank::default();
 
-            for ((patch_1, patch_2), patch_3) in bank_1
+            bank_4.import_bank_from_json(&bank_1.export_json()).unwrap();
+
+            for (((patch_1, patch_2), patch_3), patch_4) in bank_1
                 .patches
                 .iter()
                 .zip(bank_2.patches.iter())
                 .zip(bank_3.patches.iter())
+                .zip(bank_4.patches.iter())
             {
-                for ((p1, p2), p3) in patch_1
+                for (((p1, p2), p3), p4) in patch_1
                     .parameters
                     .values()
                     .zip(patch_2.parameters.values())
                     .zip(patch_3.parameters.values())
+                    .zip(patch_4.parameters.values())
                 {
-                    let values = [p1, p2, p3]
+                    let values = [p1, p2, p3, p4]
                         .into_iter()
                         .map(|p| (p.get_value(), p.get_value_text()))
                         .collect::<Vec<_>>();
 
                     assert_eq!(values[0], values[1]);
                     assert_eq!(values[0], values[2]);
+                    assert_eq!(values[0], values[3]);
                 }
             }
         }
     }
 
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_randomize_respects_locks() {
+        let bank = PatchBank::default();
+
+        bank.set_parameter_locked(0, true);
+        bank.set_parameter_locked(3, true);
+
+        let values_before = bank
+            .get_current_patch()
+            .parameters
+            .values()
+            .map(|p| p.get_value())
+            .collect::<Vec<_>>();
+
+        bank.randomize_current_patch();
+
+        let patch = bank.get_current_patch();
+
+        assert_eq!(patch.parameters[0].get_value(), values_before[0]);
+        assert_eq!(patch.parameters[3].get_value(), values_before[3]);
+
+        assert!(patch
+            .parameters
+            .values()
+            .zip(values_before.iter())
+            .enumerate()
+            .any(|(index, (p, v))| !bank.is_parameter_locked(index) && p.get_value() != *v));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_swap_and_copy_patches() {
+        let bank = PatchBank::default();
+
+        bank.patches[0].set_name("a");
+        bank.patches[0].parameters[0].set_value(0.25);
+        bank.patches[1].set_name("b");
+        bank.patches[1].parameters[0].set_value(0.75);
+
+        bank.have_patches_changed();
+        bank.get_changed_parameters_from_gui();
+
+        // Swapping patches not including current one only changes names
+        bank.swap_patches(1, 2);
+
+        assert_eq!(bank.patches[2].get_name(), "b");
+        assert_eq!(bank.patches[2].parameters[0].get_value(), 0.75);
+        assert!(bank.have_patches_changed());
+        assert!(bank.get_changed_parameters_from_gui().is_none());
+
+        bank.swap_patches(0, 2);
+
+        assert_eq!(bank.get_current_patch_name(), "b");
+        assert_eq!(bank.get_parameter_value(0), Some(0.75));
+        assert_eq!(bank.patches[2].get_name(), "a");
+        assert_eq!(bank.patches[2].parameters[0].get_value(), 0.25);
+        assert!(bank.have_patches_changed());
+        assert!(bank.get_changed_parameters_from_gui().is_some());
+
+        bank.copy_patch(2, 0);
+
+        assert_eq!(bank.get_current_patch_name(), "a");
+        assert_eq!(bank.get_parameter_value(0), Some(0.25));
+        assert_eq!(bank.patches[2].get_name(), "a");
+        assert!(bank.have_patches_changed());
+        assert!(bank.get_changed_parameters_from_gui().is_some());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_timbre_parameter_changes() {
+        let bank = PatchBank::default();
+
+        let drain = |timbre_index| {
+            let mut changes = Vec::new();
+
+            bank.drain_changed_timbre_parameters_from_audio(timbre_index, |i, v| {
+                changes.push((i, v))
+            });
+
+            changes
+        };
+
+        for timbre_index in 0..NUM_TIMBRES {
+            drain(timbre_index);
+        }
+
+        bank.patches[0].parameters[0].set_value(0.25);
+
+        // Copying to patch slot 3 only changes parameters of second timbre
+        bank.copy_patch(0, 2);
+
+        assert!(drain(0).is_empty());
+        assert!(drain(1).contains(&(0, 0.25)));
+        assert!(drain(1).is_empty());
+
+        // Switching patch moves all timbres to other patch slots
+        bank.set_patch_index(1);
+
+        assert!(drain(0).contains(&(0, 0.25)));
+
+        for timbre_index in 1..NUM_TIMBRES {
+            assert!(!drain(timbre_index).is_empty());
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_init_parameter_values_match_cleared_patch() {
+        let bank = PatchBank::default();
+
+        bank.randomize_current_patch();
+        bank.clear_current_patch();
+
+        let values = bank
+            .get_current_patch()
+            .parameters
+            .values()
+            .map(|p| p.get_value())
+            .collect::<Vec<_>>();
+
+        assert_eq!(values, Patch::init_parameter_values());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_import_files() {
+        let dir =
+            ::std::env::temp_dir().join(format!("octasine-import-test-{}", fastrand::u64(..)));
+
+        ::std::fs::create_dir_all(&dir).unwrap();
+
+        let source = PatchBank::default();
+
+        source.patches[0].set_name("imported");
+        source.patches[0].parameters[0].set_value(0.25);
+
+        let patch_path = dir.join("imported.fxp");
+        let other_path = dir.join("ignored.txt");
+
+        ::std::fs::write(&patch_path, source.patches[0].export_fxp_bytes()).unwrap();
+        ::std::fs::write(&other_path, b"ignored").unwrap();
+
+        let files = ImportFiles::read(&[other_path, patch_path, dir.join("missing.fxp")]);
+
+        ::std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!files.is_empty());
+
+        let bank = PatchBank::default();
+
+        bank.set_patch_index(1);
+        bank.have_patches_changed();
+        bank.import_files(&files);
+
+        assert_eq!(bank.get_current_patch_name(), "imported");
+        assert_eq!(bank.get_parameter_value(0), Some(0.25));
+        assert_eq!(bank.patches[0].get_name(), "-");
+        assert!(bank.have_patches_changed());
+    }
+
     #[test]
     fn test_load_built_in_patches() {
         let preset_bank = built_in_patch_bank();
@@ -0,0 +1,97 @@
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use crate::parameters::{
+    glide_active::GlideActiveValue, glide_mode::GlideModeValue, voice_mode::VoiceModeValue,
+    MasterParameter, Parameter, ParameterValue,
+};
+
+/// Voice settings that new and cleared patches start with instead of
+/// built-in defaults, given as parameter value text, e.g., "MONO" for voice
+/// mode (which also selects polyphony) or "LEG" for glide. Fields that are
+/// not set keep built-in defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatchDefaults {
+    #[serde(default)]
+    pub voice_mode: Option<String>,
+    #[serde(default)]
+    pub glide_active: Option<String>,
+    #[serde(default)]
+    pub glide_mode: Option<String>,
+}
+
+/// Parameters and patch values overridden by patch defaults in settings
+static PATCH_DEFAULT_OVERRIDES: OnceCell<Vec<(Parameter, f32)>> = OnceCell::new();
+
+/// Set patch defaults from settings. Calls after the first one (e.g., from
+/// other plugin instances) have no effect.
+pub fn set_patch_defaults(defaults: &PatchDefaults) {
+    if PATCH_DEFAULT_OVERRIDES.get().is_some() {
+        return;
+    }
+
+    fn parse<V: ParameterValue>(
+        overrides: &mut Vec<(Parameter, f32)>,
+        parameter: MasterParameter,
+        opt_text: &Option<String>,
+    ) {
+        if let Some(text) = opt_text {
+            match V::new_from_text(text) {
+                Some(value) => overrides.push((Parameter::Master(parameter), value.to_patch())),
+                None => ::log::warn!("Ignoring invalid patch default {:?}: {}", parameter, text),
+            }
+        }
+    }
+
+    let mut overrides = Vec::new();
+
+    parse::<VoiceModeValue>(
+        &mut overrides,
+        MasterParameter::VoiceMode,
+        &defaults.voice_mode,
+    );
+    parse::<GlideActiveValue>(
+        &mut overrides,
+        MasterParameter::GlideActive,
+        &defaults.glide_active,
+    );
+    parse::<GlideModeValue>(
+        &mut overrides,
+        MasterParameter::GlideMode,
+        &defaults.glide_mode,
+    );
+
+    let _ = PATCH_DEFAULT_OVERRIDES.set(overrides);
+}
+
+/// Parameters with defaults set in settings, together with their patch
+/// values
+pub fn patch_default_overrides() -> &'static [(Parameter, f32)] {
+    PATCH_DEFAULT_OVERRIDES
+        .get()
+        .map_or(&[], |overrides| overrides.as_slice())
+}
+
+/// Whether patch value differs from default set for parameter in settings.
+/// Returns false if no default is set.
+pub fn overrides_patch_default(parameter: Parameter, value: f32) -> bool {
+    patch_default_overrides()
+        .iter()
+        .any(|(p, default)| *p == parameter && normalize(parameter, value) != *default)
+}
+
+/// Map patch value to that of the step it selects
+fn normalize(parameter: Parameter, value: f32) -> f32 {
+    match parameter {
+        Parameter::Master(MasterParameter::VoiceMode) => {
+            VoiceModeValue::new_from_patch(value).to_patch()
+        }
+        Parameter::Master(MasterParameter::GlideActive) => {
+            GlideActiveValue::new_from_patch(value).to_patch()
+        }
+        Parameter::Master(MasterParameter::GlideMode) => {
+            GlideModeValue::new_from_patch(value).to_patch()
+        }
+        _ => value,
+    }
+}
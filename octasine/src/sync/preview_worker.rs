@@ -0,0 +1,92 @@
+//! Background worker for rendering patch previews
+//!
+//! Rendering a preview note takes a while, so it is done on a separate
+//! thread instead of on the GUI thread. Finished previews are passed to the
+//! audio thread, which hands them back once they have been played, so that
+//! they are dropped here too.
+
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+use crate::audio::preview::{PatchPreview, PatchPreviewSender};
+use crate::common::SampleRate;
+
+/// How often worker drops previews handed back by audio thread while
+/// waiting for new jobs
+const DROP_RETURNED_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Job {
+    parameter_values: Vec<f32>,
+    sample_rate: SampleRate,
+}
+
+/// Worker thread is spawned on first use and exits when the worker is
+/// dropped along with the sync state
+pub struct PreviewWorker {
+    /// Moved to worker thread when it is spawned
+    opt_preview_sender: Option<PatchPreviewSender>,
+    opt_sender: Option<mpsc::Sender<Job>>,
+}
+
+impl PreviewWorker {
+    pub fn new(preview_sender: PatchPreviewSender) -> Self {
+        Self {
+            opt_preview_sender: Some(preview_sender),
+            opt_sender: None,
+        }
+    }
+
+    /// Render preview on worker thread and queue it for playback. Parameter
+    /// values are patch values in the same order as `PARAMETERS`.
+    pub fn render(&mut self, parameter_values: Vec<f32>, sample_rate: SampleRate) {
+        if let Some(sender) = self.get_or_spawn_sender() {
+            let job = Job {
+                parameter_values,
+                sample_rate,
+            };
+
+            if sender.send(job).is_err() {
+                ::log::error!("Patch preview worker thread has exited");
+
+                self.opt_sender = None;
+            }
+        }
+    }
+
+    fn get_or_spawn_sender(&mut self) -> Option<&mpsc::Sender<Job>> {
+        if self.opt_sender.is_none() {
+            // Preview queues can't be recovered if thread exited
+            let mut preview_sender = self.opt_preview_sender.take()?;
+
+            let (sender, receiver) = mpsc::channel::<Job>();
+
+            let spawn_result = ::std::thread::Builder::new()
+                .name("octasine-preview".into())
+                .spawn(move || loop {
+                    match receiver.recv_timeout(DROP_RETURNED_INTERVAL) {
+                        Ok(mut job) => {
+                            // Only render latest request, e.g., when quickly
+                            // moving through patches
+                            while let Ok(newer_job) = receiver.try_recv() {
+                                job = newer_job;
+                            }
+
+                            let preview =
+                                PatchPreview::render(&job.parameter_values, job.sample_rate);
+
+                            preview_sender.send(Box::new(preview));
+                        }
+                        Err(RecvTimeoutError::Timeout) => preview_sender.drop_returned(),
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                });
+
+            match spawn_result {
+                Ok(_) => self.opt_sender = Some(sender),
+                Err(err) => ::log::error!("Couldn't spawn patch preview worker thread: {:#}", err),
+            }
+        }
+
+        self.opt_sender.as_ref()
+    }
+}
@@ -6,52 +6,66 @@ use std::io::Write;
 
 use super::patch_bank::{Patch, PatchBank};
 
-/// Remember to update relevant metadata if changes were indeed made
-pub fn update_bank_from_bytes(bank: &PatchBank, bytes: &[u8]) -> anyhow::Result<()> {
+/// Bank decoded from bytes, which is cheap to apply to a [PatchBank]
+pub struct ParsedBank(v2::SerdePatchBank);
+
+/// Patch decoded from bytes, which is cheap to apply to a [Patch]
+pub struct ParsedPatch(v2::SerdePatch);
+
+pub fn parse_bank_bytes(bytes: &[u8]) -> anyhow::Result<ParsedBank> {
     let serde_bank = if v2::bytes_are_v2(bytes) {
         v2::SerdePatchBank::from_bytes(bytes)?
     } else {
         v2::SerdePatchBank::from_v1(v1::SerdePatchBank::from_bytes(bytes)?)?
     };
 
-    let default_serde_patch = v2::SerdePatch::new(&Patch::default());
-
-    for (index, patch) in bank.patches.iter().enumerate() {
-        let serde_patch = if let Some(serde_patch) = serde_bank.patches.get(index) {
-            patch.set_name(serde_patch.name.as_str());
+    Ok(ParsedBank(serde_bank))
+}
 
-            serde_patch
-        } else {
-            patch.set_name("");
+pub fn parse_patch_bytes(bytes: &[u8]) -> anyhow::Result<ParsedPatch> {
+    let serde_patch = if v2::bytes_are_v2(bytes) {
+        v2::SerdePatch::from_bytes(bytes)?
+    } else {
+        v2::SerdePatch::from_v1(v1::SerdePatch::from_bytes(bytes)?)?
+    };
 
-            &default_serde_patch
-        };
+    Ok(ParsedPatch(serde_patch))
+}
 
-        for (key, parameter) in patch.parameters.iter() {
-            if let Some(serde_parameter) = serde_patch.parameters.get(key) {
-                parameter.set_value(serde_parameter.value_patch);
-            }
-        }
-    }
+/// Remember to update relevant metadata if changes were indeed made
+pub fn update_bank_from_bytes(bank: &PatchBank, bytes: &[u8]) -> anyhow::Result<()> {
+    update_bank_from_parsed(bank, &parse_bank_bytes(bytes)?);
 
     Ok(())
 }
 
 /// Remember to update relevant metadata if changes were indeed made
 pub fn update_patch_from_bytes(patch: &Patch, bytes: &[u8]) -> anyhow::Result<()> {
-    let serde_patch = if v2::bytes_are_v2(bytes) {
-        v2::SerdePatch::from_bytes(bytes)?
-    } else {
-        v2::SerdePatch::from_v1(v1::SerdePatch::from_bytes(bytes)?)?
-    };
+    update_patch_from_parsed(patch, &parse_patch_bytes(bytes)?);
 
-    patch.set_name(serde_patch.name.as_str());
+    Ok(())
+}
 
-    for (key, parameter) in patch.parameters.iter() {
-        if let Some(serde_parameter) = serde_patch.parameters.get(key) {
-            parameter.set_value(serde_parameter.value_patch);
-        }
-    }
+/// Remember to update relevant metadata if changes were indeed made
+pub fn update_bank_from_parsed(bank: &PatchBank, parsed: &ParsedBank) {
+    update_bank_from_serde(bank, &parsed.0);
+}
+
+/// Remember to update relevant metadata if changes were indeed made
+pub fn update_patch_from_parsed(patch: &Patch, parsed: &ParsedPatch) {
+    update_patch_from_serde(patch, &parsed.0);
+}
+
+/// Remember to update relevant metadata if changes were indeed made
+pub fn update_bank_from_json(bank: &PatchBank, json: &str) -> anyhow::Result<()> {
+    update_bank_from_serde(bank, &v2::SerdePatchBank::from_json(json)?);
+
+    Ok(())
+}
+
+/// Remember to update relevant metadata if changes were indeed made
+pub fn update_patch_from_json(patch: &Patch, json: &str) -> anyhow::Result<()> {
+    update_patch_from_serde(patch, &v2::SerdePatch::from_json(json)?);
 
     Ok(())
 }
@@ -70,3 +84,129 @@ pub fn serialize_bank_fxb_bytes(bank: &PatchBank) -> anyhow::Result<Vec<u8>> {
 pub fn serialize_patch_fxp_bytes(patch: &Patch) -> anyhow::Result<Vec<u8>> {
     v2::SerdePatch::new(patch).serialize_fxp_bytes()
 }
+
+pub fn serialize_bank_json(bank: &PatchBank) -> anyhow::Result<String> {
+    v2::SerdePatchBank::new(bank).serialize_json()
+}
+
+pub fn serialize_patch_json(patch: &Patch) -> anyhow::Result<String> {
+    v2::SerdePatch::new(patch).serialize_json()
+}
+
+fn update_bank_from_serde(bank: &PatchBank, serde_bank: &v2::SerdePatchBank) {
+    let default_serde_patch = v2::SerdePatch::new(&Patch::default());
+
+    for (index, patch) in bank.patches.iter().enumerate() {
+        if let Some(serde_patch) = serde_bank.patches.get(index) {
+            update_patch_from_serde(patch, serde_patch);
+        } else {
+            update_patch_from_serde(patch, &default_serde_patch);
+
+            patch.set_name("");
+        }
+    }
+}
+
+fn update_patch_from_serde(patch: &Patch, serde_patch: &v2::SerdePatch) {
+    patch.set_name(serde_patch.name.as_str());
+
+    for (key, parameter) in patch.parameters.iter() {
+        if let Some(serde_parameter) = serde_patch.parameters.get(key) {
+            // Guard against corrupt or hand-edited data
+            if serde_parameter.value_patch.is_finite() {
+                parameter.set_value(serde_parameter.value_patch.clamp(0.0, 1.0));
+            }
+        }
+    }
+
+    for key in 0..128 {
+        let cents = serde_patch.key_tuning.get(&key).copied().unwrap_or(0.0);
+
+        // Non-finite values are reset by Patch::set_key_detune
+        patch.set_key_detune(key, cents);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{write::GzEncoder, Compression};
+
+    use crate::parameters::{OperatorParameter, Parameter};
+
+    use super::*;
+
+    #[test]
+    fn test_malformed_data_returns_error() {
+        let patch = Patch::default();
+        let bank = PatchBank::default();
+
+        assert!(update_patch_from_bytes(&patch, b"not a patch").is_err());
+        assert!(update_bank_from_bytes(&bank, b"not a bank").is_err());
+        assert!(update_patch_from_json(&patch, "{}").is_err());
+
+        // v1 data with invalid parameter value
+        let json = r#"{"octasine_version":"v0.5.0","name":"","parameters":[{"name":"","value_float":"x","value_text":""}]}"#;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+
+        encoder.write_all(json.as_bytes()).unwrap();
+
+        assert!(update_patch_from_bytes(&patch, &encoder.finish().unwrap()).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_key_tuning() {
+        let patch = Patch::default();
+
+        patch.set_key_detune(60, 12.5);
+        patch.set_key_detune(127, -1200.0);
+
+        // Only detuned keys are stored
+        let serde_patch = v2::SerdePatch::new(&patch);
+
+        assert_eq!(serde_patch.key_tuning.len(), 2);
+
+        let from_fxp = Patch::default();
+        let from_json = Patch::default();
+
+        from_fxp.set_key_detune(61, 5.0);
+        from_json.set_key_detune(61, 5.0);
+
+        update_patch_from_bytes(&from_fxp, &serde_patch.serialize_fxp_bytes().unwrap()).unwrap();
+        update_patch_from_json(&from_json, &serde_patch.serialize_json().unwrap()).unwrap();
+
+        for imported in [from_fxp, from_json] {
+            assert_eq!(imported.get_key_detune(60), 12.5);
+            assert_eq!(imported.get_key_detune(61), 0.0);
+            assert_eq!(imported.get_key_detune(127), -1200.0);
+        }
+
+        // Patches saved before key tuning was added have no detuned keys
+        let mut json: serde_json::Value =
+            serde_json::from_str(&serde_patch.serialize_json().unwrap()).unwrap();
+
+        json.as_object_mut().unwrap().remove("key_tuning");
+
+        update_patch_from_json(&patch, &json.to_string()).unwrap();
+
+        assert_eq!(patch.get_key_detune(60), 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_non_finite_values_are_ignored() {
+        let key = Parameter::Operator(0, OperatorParameter::Panning).key();
+
+        let patch = Patch::default();
+        let default_value = patch.parameters[&key].get_value();
+
+        let mut serde_patch = v2::SerdePatch::new(&patch);
+
+        serde_patch.parameters.get_mut(&key).unwrap().value_patch = f32::NAN;
+
+        update_patch_from_bytes(&patch, &serde_patch.serialize_fxp_bytes().unwrap()).unwrap();
+
+        assert_eq!(patch.parameters[&key].get_value(), default_value);
+    }
+}
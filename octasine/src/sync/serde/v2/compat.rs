@@ -1,16 +1,32 @@
 use semver::Version;
 
-use crate::parameters::{OperatorParameter, Parameter, SerializableRepresentation};
+use crate::parameters::operator_envelope::{
+    envelope_duration_from_patch, envelope_duration_to_patch,
+};
+use crate::parameters::operator_wave_type::OperatorWaveTypeValue;
+use crate::parameters::utils::{
+    map_patch_to_audio_value_with_steps, map_patch_value_to_step, map_step_to_patch_value,
+};
+use crate::parameters::voice_mode::VoiceModeValue;
+use crate::parameters::{
+    get_lfo_target_parameters, LfoFrequencyFreeValue, LfoFrequencyRatioValue, LfoModeValue,
+    LfoParameter, LfoTargetParameter, MasterParameter, MasterVolumeValue, OperatorFeedbackValue,
+    OperatorFrequencyRatioValue, OperatorParameter, Parameter, ParameterKey, ParameterValue,
+    SerializableRepresentation, LFO_TARGETS,
+};
 
 use super::SerdePatch;
 
-pub const COMPATIBILITY_CHANGES: &[(Version, fn(&mut SerdePatch))] =
-    &[(Version::new(0, 8, 5), compat_0_8_5)];
+type CompatibilityChange = (Version, fn(&mut SerdePatch));
+
+pub const COMPATIBILITY_CHANGES: &[CompatibilityChange] = &[
+    (Version::new(0, 8, 5), compat_0_8_5),
+    (Version::new(0, 9, 1), compat_0_9_1),
+];
 
 /// New operator wave forms
 ///
 /// Prior versions only had sine and white noise variants
-#[allow(dead_code)]
 pub fn compat_0_8_5(patch: &mut SerdePatch) {
     let parameter_keys = [
         Parameter::Operator(0, OperatorParameter::WaveType).key(),
@@ -20,7 +36,11 @@ pub fn compat_0_8_5(patch: &mut SerdePatch) {
     ];
 
     for key in parameter_keys {
-        let p = patch.parameters.get_mut(&key).unwrap();
+        let p = if let Some(p) = patch.parameters.get_mut(&key) {
+            p
+        } else {
+            continue;
+        };
 
         match &p.value_serializable {
             SerializableRepresentation::Other(s) => {
@@ -49,3 +69,374 @@ pub fn compat_0_8_5(patch: &mut SerdePatch) {
         }
     }
 }
+
+/// Longer envelope durations, new LFO targets, master volume in dB, fat
+/// mono voice mode, extended LFO frequency ranges, new operator wave forms,
+/// LFO retrigger modes and operator feedback curve
+///
+/// Only run for patches saved with prior versions. Conversions are based on
+/// serialized representations and leave patch values that already match
+/// them as-is.
+pub fn compat_0_9_1(patch: &mut SerdePatch) {
+    convert_envelope_durations_0_9_1(patch);
+    convert_lfo_targets_0_9_1(patch);
+    convert_master_volume_0_9_1(patch);
+    convert_voice_mode_0_9_1(patch);
+    convert_lfo_frequencies_0_9_1(patch);
+    convert_operator_wave_types_0_9_1(patch);
+    convert_lfo_modes_0_9_1(patch);
+    convert_operator_feedback_0_9_1(patch);
+}
+
+/// Prior versions mapped patch values linearly to 0-4 seconds
+fn convert_envelope_durations_0_9_1(patch: &mut SerdePatch) {
+    const PREVIOUS_ENVELOPE_MAX_DURATION: f64 = 4.0;
+
+    for operator_index in 0..4 {
+        for operator_parameter in [
+            OperatorParameter::AttackDuration,
+            OperatorParameter::DecayDuration,
+            OperatorParameter::ReleaseDuration,
+        ] {
+            let key = Parameter::Operator(operator_index, operator_parameter).key();
+
+            if let Some(p) = patch.parameters.get_mut(&key) {
+                let duration = match &p.value_serializable {
+                    SerializableRepresentation::Float(v) => {
+                        if envelope_duration_from_patch(p.value_patch) == *v {
+                            continue;
+                        }
+
+                        *v
+                    }
+                    // Patches converted from v1 format have text
+                    // representations, so use previous mapping
+                    SerializableRepresentation::Other(_) => {
+                        f64::from(p.value_patch) * PREVIOUS_ENVELOPE_MAX_DURATION
+                    }
+                };
+
+                p.value_patch = envelope_duration_to_patch(duration);
+            }
+        }
+    }
+}
+
+/// Prior versions mapped patch values linearly to a 0-2 volume factor
+fn convert_master_volume_0_9_1(patch: &mut SerdePatch) {
+    let key = Parameter::Master(MasterParameter::Volume).key();
+
+    if let Some(p) = patch.parameters.get_mut(&key) {
+        let volume = match &p.value_serializable {
+            SerializableRepresentation::Float(v) => {
+                if f64::from(MasterVolumeValue::new_from_patch(p.value_patch).get()) == *v {
+                    return;
+                }
+
+                MasterVolumeValue::new_from_audio(*v as f32)
+            }
+            // Patches converted from v1 format have text representations,
+            // so use previous mapping
+            SerializableRepresentation::Other(_) => {
+                MasterVolumeValue::from_previous_patch_mapping(p.value_patch)
+            }
+        };
+
+        p.value_patch = volume.to_patch();
+    }
+}
+
+/// Targets were inserted into LFO target list (master pan, glide time,
+/// operator envelope durations), shifting later targets. Velocity modulation uses the same list.
+fn convert_lfo_targets_0_9_1(patch: &mut SerdePatch) {
+    for lfo_index in 0..4 {
+        let key = Parameter::Lfo(lfo_index as u8, LfoParameter::Target).key();
+
+        convert_lfo_target_0_9_1(patch, key, get_lfo_target_parameters(lfo_index));
+    }
+
+    let key = Parameter::Master(MasterParameter::VelocityModTarget).key();
+
+    convert_lfo_target_0_9_1(patch, key, LFO_TARGETS);
+}
+
+fn convert_lfo_target_0_9_1(
+    patch: &mut SerdePatch,
+    key: ParameterKey,
+    targets: &[LfoTargetParameter],
+) {
+    let p = if let Some(p) = patch.parameters.get_mut(&key) {
+        p
+    } else {
+        return;
+    };
+
+    match &p.value_serializable {
+        SerializableRepresentation::Other(name) => {
+            let current_target = map_patch_value_to_step(targets, p.value_patch);
+
+            if current_target.parameter().name() == *name {
+                return;
+            }
+
+            if let Some(target) = targets.iter().find(|t| t.parameter().name() == *name) {
+                p.value_patch = map_step_to_patch_value(targets, *target);
+            } else {
+                ::log::error!(
+                    "converting patch for 0.9.1 compatibility: unrecognized lfo target: {}",
+                    name
+                );
+            }
+        }
+        SerializableRepresentation::Float(v) => {
+            ::log::error!(
+                "converting patch for 0.9.1 compatibility: incorrect serializable representation for lfo target: {}",
+                v
+            );
+        }
+    }
+}
+
+/// Fat mono voice mode was added, changing patch value of monophonic mode
+fn convert_voice_mode_0_9_1(patch: &mut SerdePatch) {
+    let key = Parameter::Master(MasterParameter::VoiceMode).key();
+
+    let p = if let Some(p) = patch.parameters.get_mut(&key) {
+        p
+    } else {
+        return;
+    };
+
+    match &p.value_serializable {
+        SerializableRepresentation::Other(name) => {
+            if VoiceModeValue::new_from_patch(p.value_patch).get_formatted() == *name {
+                return;
+            }
+
+            if let Some(mode) = VoiceModeValue::new_from_text(name) {
+                p.value_patch = mode.to_patch();
+            } else {
+                ::log::error!(
+                    "converting patch for 0.9.1 compatibility: unrecognized voice mode: {}",
+                    name
+                );
+            }
+        }
+        SerializableRepresentation::Float(v) => {
+            ::log::error!(
+                "converting patch for 0.9.1 compatibility: incorrect serializable representation for voice mode: {}",
+                v
+            );
+        }
+    }
+}
+
+/// TX81Z-style sine variants were added, changing patch values of noise and
+/// other previous wave forms
+fn convert_operator_wave_types_0_9_1(patch: &mut SerdePatch) {
+    for operator_index in 0..4 {
+        let key = Parameter::Operator(operator_index, OperatorParameter::WaveType).key();
+
+        let p = if let Some(p) = patch.parameters.get_mut(&key) {
+            p
+        } else {
+            continue;
+        };
+
+        match &p.value_serializable {
+            SerializableRepresentation::Other(name) => {
+                if OperatorWaveTypeValue::new_from_patch(p.value_patch).get_formatted() == *name {
+                    continue;
+                }
+
+                if let Some(wave_type) = OperatorWaveTypeValue::new_from_text(name) {
+                    p.value_patch = wave_type.to_patch();
+                } else {
+                    ::log::error!(
+                        "converting patch for 0.9.1 compatibility: unrecognized operator wave type: {}",
+                        name
+                    );
+                }
+            }
+            SerializableRepresentation::Float(v) => {
+                ::log::error!(
+                    "converting patch for 0.9.1 compatibility: incorrect serializable representation for operator wave type: {}",
+                    v
+                );
+            }
+        }
+    }
+}
+
+/// LFO retrigger modes were added, changing patch value of oneshot mode
+fn convert_lfo_modes_0_9_1(patch: &mut SerdePatch) {
+    for lfo_index in 0..4 {
+        let key = Parameter::Lfo(lfo_index, LfoParameter::Mode).key();
+
+        let p = if let Some(p) = patch.parameters.get_mut(&key) {
+            p
+        } else {
+            continue;
+        };
+
+        match &p.value_serializable {
+            SerializableRepresentation::Other(name) => {
+                if LfoModeValue::new_from_patch(p.value_patch).get_formatted() == *name {
+                    continue;
+                }
+
+                if let Some(mode) = LfoModeValue::new_from_text(name) {
+                    p.value_patch = mode.to_patch();
+                } else {
+                    ::log::error!(
+                        "converting patch for 0.9.1 compatibility: unrecognized lfo mode: {}",
+                        name
+                    );
+                }
+            }
+            SerializableRepresentation::Float(v) => {
+                ::log::error!(
+                    "converting patch for 0.9.1 compatibility: incorrect serializable representation for lfo mode: {}",
+                    v
+                );
+            }
+        }
+    }
+}
+
+/// Operator feedback was mapped linearly between modulation index steps,
+/// reaching 1000.0. It now follows a curve ending at
+/// [OPERATOR_FEEDBACK_MAX](crate::parameters::operator_feedback::OPERATOR_FEEDBACK_MAX).
+///
+/// This conversion is lossy: larger values are clamped to the maximum, so
+/// patches using them sound less noisy than before. Such amounts turn any
+/// waveform into near-white noise, so the difference is mostly one of
+/// noise color. Output of operators with feedback is also attenuated now
+/// (see [feedback_gain_compensation](crate::parameters::operator_feedback::feedback_gain_compensation)),
+/// which isn't compensated for here.
+fn convert_operator_feedback_0_9_1(patch: &mut SerdePatch) {
+    for operator_index in 0..4 {
+        let key = Parameter::Operator(operator_index, OperatorParameter::Feedback).key();
+
+        if let Some(p) = patch.parameters.get_mut(&key) {
+            let feedback = match &p.value_serializable {
+                SerializableRepresentation::Float(v) => {
+                    if !v.is_finite()
+                        || f64::from(OperatorFeedbackValue::new_from_patch(p.value_patch).get())
+                            == *v
+                    {
+                        continue;
+                    }
+
+                    OperatorFeedbackValue::new_from_audio(*v as f32)
+                }
+                // Patches converted from v1 format have text
+                // representations, so use previous mapping
+                SerializableRepresentation::Other(_) => {
+                    OperatorFeedbackValue::from_previous_patch_mapping(p.value_patch)
+                }
+            };
+
+            p.value_patch = feedback.to_patch();
+        }
+    }
+}
+
+/// Slower LFO rates and dotted/triplet ratio steps were added, changing
+/// mapping of patch values
+fn convert_lfo_frequencies_0_9_1(patch: &mut SerdePatch) {
+    const PREVIOUS_RATIO_STEPS: [f32; 9] = [
+        1.0 / 16.0,
+        1.0 / 8.0,
+        1.0 / 4.0,
+        1.0 / 2.0,
+        1.0,
+        2.0,
+        4.0,
+        8.0,
+        16.0,
+    ];
+    const PREVIOUS_FREE_STEPS: [f32; 7] = [1.0 / 16.0, 0.5, 0.9, 1.0, 1.1, 2.0, 16.0];
+
+    for lfo_index in 0..4 {
+        let key = Parameter::Lfo(lfo_index, LfoParameter::FrequencyRatio).key();
+
+        if let Some(p) = patch.parameters.get_mut(&key) {
+            let ratio = match &p.value_serializable {
+                SerializableRepresentation::Float(v) => {
+                    if !v.is_finite()
+                        || LfoFrequencyRatioValue::new_from_patch(p.value_patch).get() == *v
+                    {
+                        continue;
+                    }
+
+                    *v
+                }
+                // Patches converted from v1 format have text
+                // representations, so use previous mapping
+                SerializableRepresentation::Other(_) => {
+                    map_patch_value_to_step(&PREVIOUS_RATIO_STEPS, p.value_patch).into()
+                }
+            };
+
+            p.value_patch = LfoFrequencyRatioValue::new_closest_to_value(ratio).to_patch();
+        }
+
+        let key = Parameter::Lfo(lfo_index, LfoParameter::FrequencyFree).key();
+
+        if let Some(p) = patch.parameters.get_mut(&key) {
+            let frequency = match &p.value_serializable {
+                SerializableRepresentation::Float(v) => {
+                    if LfoFrequencyFreeValue::new_from_patch(p.value_patch).get() == *v {
+                        continue;
+                    }
+
+                    *v
+                }
+                // Patches converted from v1 format have text
+                // representations, so use previous mapping
+                SerializableRepresentation::Other(_) => {
+                    map_patch_to_audio_value_with_steps(&PREVIOUS_FREE_STEPS, p.value_patch).into()
+                }
+            };
+
+            p.value_patch = LfoFrequencyFreeValue::new_from_audio(frequency).to_patch();
+        }
+    }
+}
+
+/// Ratios in user ratio steps depend on settings, and prior versions had
+/// fewer ratio steps, so map operator frequency ratios from serialized ratio
+/// values. Run for all patches regardless of version.
+pub fn remap_frequency_ratios(patch: &mut SerdePatch) {
+    for operator_index in 0..4 {
+        let key = Parameter::Operator(operator_index, OperatorParameter::FrequencyRatio).key();
+
+        let p = if let Some(p) = patch.parameters.get_mut(&key) {
+            p
+        } else {
+            continue;
+        };
+
+        let opt_ratio = match &p.value_serializable {
+            SerializableRepresentation::Float(value) => {
+                if !value.is_finite() {
+                    continue;
+                }
+
+                OperatorFrequencyRatioValue::new_closest_to_value(*value)
+            }
+            // Patches converted from v1 format have text representations
+            SerializableRepresentation::Other(name) => {
+                OperatorFrequencyRatioValue::new_from_text(name)
+            }
+        };
+
+        if let Some(ratio) = opt_ratio {
+            // Leave patch values that already map to the ratio as-is
+            if OperatorFrequencyRatioValue::new_from_patch(p.value_patch).get() != ratio.get() {
+                p.value_patch = ratio.to_patch();
+            }
+        }
+    }
+}
@@ -13,7 +13,7 @@ use crate::{
     sync::patch_bank::{Patch, PatchBank},
 };
 
-use self::compat::COMPATIBILITY_CHANGES;
+use self::compat::{remap_frequency_ratios, COMPATIBILITY_CHANGES};
 
 use super::common::{make_fxb, make_fxp};
 
@@ -71,6 +71,20 @@ impl SerdePatchBank {
 
         make_fxb(&buffer, self.patches.len())
     }
+
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let mut bank: Self = serde_json::from_str(json)?;
+
+        for patch in bank.patches.iter_mut() {
+            patch.run_compatibility_changes();
+        }
+
+        Ok(bank)
+    }
+
+    pub fn serialize_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -78,6 +92,10 @@ pub struct SerdePatch {
     octasine_version: Version,
     pub name: CompactString,
     pub parameters: IndexMap<ParameterKey, SerdePatchParameter>,
+    /// Detuning in cents of detuned keys. Missing in patches saved with
+    /// versions before per-key tuning was added.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub key_tuning: IndexMap<u8, f32>,
 }
 
 impl SerdePatch {
@@ -97,10 +115,16 @@ impl SerdePatch {
             })
             .collect();
 
+        let key_tuning = (0..128u8)
+            .map(|key| (key, patch.get_key_detune(key)))
+            .filter(|(_, cents)| *cents != 0.0)
+            .collect();
+
         Self {
             octasine_version: get_octasine_version(),
             name: patch.get_name().into(),
             parameters,
+            key_tuning,
         }
     }
 
@@ -110,7 +134,8 @@ impl SerdePatch {
         let mut v2_parameters = Self::new(&Patch::default()).parameters;
 
         for (index, v1_parameter) in v1.parameters.into_iter().enumerate() {
-            let parameter = Parameter::from_index(index).ok_or_else(|| anyhow::anyhow!(""))?;
+            let parameter = Parameter::from_index(index)
+                .ok_or_else(|| anyhow::anyhow!("no parameter with index {}", index))?;
 
             let v2_parameter = v2_parameters
                 .get_mut(&parameter.key())
@@ -118,7 +143,7 @@ impl SerdePatch {
 
             *v2_parameter = SerdePatchParameter {
                 index,
-                value_patch: v1_parameter.value_float.as_f32(),
+                value_patch: v1_parameter.value_float.as_f32()?,
                 value_serializable: SerializableRepresentation::Other(
                     v1_parameter.value_text.into(),
                 ),
@@ -129,6 +154,7 @@ impl SerdePatch {
             octasine_version,
             name: v1.name.into(),
             parameters: v2_parameters,
+            key_tuning: Default::default(),
         };
 
         patch.run_compatibility_changes();
@@ -152,14 +178,28 @@ impl SerdePatch {
         make_fxp(&buffer, &self.name, self.parameters.len())
     }
 
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let mut patch: Self = serde_json::from_str(json)?;
+
+        patch.run_compatibility_changes();
+
+        Ok(patch)
+    }
+
+    pub fn serialize_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
     fn run_compatibility_changes(&mut self) {
+        // Run all changes made after the version that saved the patch, not
+        // just the first one
         for (changed_in_version, f) in COMPATIBILITY_CHANGES {
             if self.octasine_version < *changed_in_version {
                 f(self);
-            } else {
-                break;
             }
         }
+
+        remap_frequency_ratios(self);
     }
 }
 
@@ -213,3 +253,127 @@ fn serialize_bytes_gz<W: Write, T: Serialize>(writer: &mut W, value: &T) -> anyh
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::parameters::{
+        MasterParameter, OperatorFrequencyRatioValue, OperatorParameter, ParameterValue,
+    };
+
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_compatibility_changes_are_gated_on_version() {
+        let key = Parameter::Master(MasterParameter::Volume).key();
+
+        let patch = Patch::default();
+        let default_value = patch.parameters[&key].get_value();
+
+        let mut serde_patch = SerdePatch::new(&patch);
+
+        // Patch value no longer matching serialized representation, as if
+        // saved with previous mapping
+        serde_patch.parameters.get_mut(&key).unwrap().value_patch = 0.25;
+        serde_patch.octasine_version = Version::new(0, 9, 0);
+        serde_patch.run_compatibility_changes();
+
+        assert_eq!(serde_patch.parameters[&key].value_patch, default_value);
+
+        serde_patch.parameters.get_mut(&key).unwrap().value_patch = 0.25;
+        serde_patch.octasine_version = get_octasine_version();
+        serde_patch.run_compatibility_changes();
+
+        assert_eq!(serde_patch.parameters[&key].value_patch, 0.25);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_convert_operator_feedback() {
+        use crate::parameters::operator_feedback::OPERATOR_FEEDBACK_MAX;
+        use crate::parameters::OperatorFeedbackValue;
+
+        let key = Parameter::Operator(0, OperatorParameter::Feedback).key();
+
+        let convert = |value_patch, value_serializable| {
+            let mut serde_patch = SerdePatch::new(&Patch::default());
+            let p = serde_patch.parameters.get_mut(&key).unwrap();
+
+            p.value_patch = value_patch;
+            p.value_serializable = value_serializable;
+
+            serde_patch.octasine_version = Version::new(0, 9, 0);
+            serde_patch.run_compatibility_changes();
+
+            OperatorFeedbackValue::new_from_patch(serde_patch.parameters[&key].value_patch).get()
+        };
+
+        // Feedback amounts within new range are kept
+        let feedback = convert(5.0 / 15.0, SerializableRepresentation::Float(1.0));
+
+        assert!((feedback - 1.0).abs() < 1e-5);
+
+        // Larger amounts are clamped to new maximum
+        assert_eq!(
+            convert(13.0 / 15.0, SerializableRepresentation::Float(75.0)),
+            OPERATOR_FEEDBACK_MAX
+        );
+        assert_eq!(
+            convert(1.0, SerializableRepresentation::Float(1000.0)),
+            OPERATOR_FEEDBACK_MAX
+        );
+
+        // Patches converted from v1 format use previous patch value mapping
+        assert_eq!(
+            convert(
+                13.0 / 15.0,
+                SerializableRepresentation::Other("75.00".into())
+            ),
+            OPERATOR_FEEDBACK_MAX
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_remap_frequency_ratios() {
+        let key = Parameter::Operator(0, OperatorParameter::FrequencyRatio).key();
+
+        let ratio = OperatorFrequencyRatioValue::new_from_text("3/2").unwrap();
+        let other_ratio = OperatorFrequencyRatioValue::new_from_text("5/2").unwrap();
+
+        let mut serde_patch = SerdePatch::new(&Patch::default());
+
+        // Patch value mapped with different ratio steps
+        let p = serde_patch.parameters.get_mut(&key).unwrap();
+
+        p.value_patch = other_ratio.to_patch();
+        p.value_serializable = ratio.get_serializable();
+
+        remap_frequency_ratios(&mut serde_patch);
+
+        assert_eq!(serde_patch.parameters[&key].value_patch, ratio.to_patch());
+
+        // Patches converted from v1 format have text representations
+        let p = serde_patch.parameters.get_mut(&key).unwrap();
+
+        p.value_patch = other_ratio.to_patch();
+        p.value_serializable = SerializableRepresentation::Other("3/2".into());
+
+        remap_frequency_ratios(&mut serde_patch);
+
+        assert_eq!(serde_patch.parameters[&key].value_patch, ratio.to_patch());
+
+        // Patch values matching serialized representation are left as-is
+        let p = serde_patch.parameters.get_mut(&key).unwrap();
+
+        p.value_patch = other_ratio.to_patch();
+        p.value_serializable = other_ratio.get_serializable();
+
+        remap_frequency_ratios(&mut serde_patch);
+
+        assert_eq!(
+            serde_patch.parameters[&key].value_patch,
+            other_ratio.to_patch()
+        );
+    }
+}
@@ -0,0 +1,717 @@
+//! Yamaha DX7 voice SysEx import
+//!
+//! Supports 32 voice bulk dumps (VMEM) and single voice dumps (VCED). The
+//! DX7 has six operators while OctaSine has four, so at most four operators
+//! are kept: audible carriers first, then the loudest modulators. Modulation
+//! of dropped operators is rerouted to the operators they modulate.
+
+use std::cmp::Reverse;
+
+use compact_str::format_compact;
+
+use crate::parameters::mod_out_scaling::{ModOutScaling, ModOutScalingValue};
+use crate::parameters::operator_envelope::ENVELOPE_MIN_DURATION;
+use crate::parameters::operator_key_scaling::KEY_SCALING_CURVE_STEPS;
+use crate::parameters::operator_wave_type::WaveType;
+use crate::parameters::velocity_sensitivity::VelocitySensitivityValue;
+use crate::parameters::*;
+
+use super::{
+    attack_duration, decay_duration, feedback, output_level_to_amplitude, parse_name,
+    sysex_messages, ConvertedVoice, DETUNE_CENTS_PER_STEP,
+};
+
+const YAMAHA_ID: u8 = 0x43;
+
+const FORMAT_VCED: u8 = 0x00;
+const FORMAT_VMEM: u8 = 0x09;
+
+const VCED_LEN: usize = 155;
+const VMEM_VOICE_LEN: usize = 128;
+const VMEM_NUM_VOICES: usize = 32;
+
+const NUM_OCTASINE_OPERATORS: usize = 4;
+
+/// Break point 0 is key A-1
+const BREAKPOINT_KEY_OFFSET: u8 = 21;
+/// Approximate key scaling depth at depth 99 in dB per octave
+const MAX_KEY_SCALING_DEPTH: f32 = 24.0;
+
+/// Operators modulated by each operator 1-6 (Yamaha numbering) for each
+/// algorithm. Operators not modulating anything are carriers.
+const ALGORITHMS: [[&[usize]; 6]; 32] = [
+    [&[], &[1], &[], &[3], &[4], &[5]],
+    [&[], &[1], &[], &[3], &[4], &[5]],
+    [&[], &[1], &[2], &[], &[4], &[5]],
+    [&[], &[1], &[2], &[], &[4], &[5]],
+    [&[], &[1], &[], &[3], &[], &[5]],
+    [&[], &[1], &[], &[3], &[], &[5]],
+    [&[], &[1], &[], &[3], &[3], &[5]],
+    [&[], &[1], &[], &[3], &[3], &[5]],
+    [&[], &[1], &[], &[3], &[3], &[5]],
+    [&[], &[1], &[2], &[], &[4], &[4]],
+    [&[], &[1], &[2], &[], &[4], &[4]],
+    [&[], &[1], &[], &[3], &[3], &[3]],
+    [&[], &[1], &[], &[3], &[3], &[3]],
+    [&[], &[1], &[], &[3], &[4], &[4]],
+    [&[], &[1], &[], &[3], &[4], &[4]],
+    [&[], &[1], &[1], &[3], &[1], &[5]],
+    [&[], &[1], &[1], &[3], &[1], &[5]],
+    [&[], &[1], &[1], &[1], &[4], &[5]],
+    [&[], &[1], &[2], &[], &[], &[4, 5]],
+    [&[], &[], &[1, 2], &[], &[4], &[4]],
+    [&[], &[], &[1, 2], &[], &[], &[4, 5]],
+    [&[], &[1], &[], &[], &[], &[3, 4, 5]],
+    [&[], &[], &[2], &[], &[], &[4, 5]],
+    [&[], &[], &[], &[], &[], &[3, 4, 5]],
+    [&[], &[], &[], &[], &[], &[4, 5]],
+    [&[], &[], &[2], &[], &[4], &[4]],
+    [&[], &[], &[2], &[], &[4], &[4]],
+    [&[], &[1], &[], &[3], &[4], &[]],
+    [&[], &[], &[], &[3], &[], &[5]],
+    [&[], &[], &[], &[3], &[4], &[]],
+    [&[], &[], &[], &[], &[], &[5]],
+    [&[], &[], &[], &[], &[], &[]],
+];
+
+/// Feedback source and destination operator for each algorithm
+const FEEDBACK_LOOPS: [(usize, usize); 32] = [
+    (6, 6),
+    (2, 2),
+    (6, 6),
+    (4, 6),
+    (6, 6),
+    (5, 6),
+    (6, 6),
+    (4, 4),
+    (2, 2),
+    (3, 3),
+    (6, 6),
+    (2, 2),
+    (6, 6),
+    (6, 6),
+    (2, 2),
+    (6, 6),
+    (2, 2),
+    (3, 3),
+    (6, 6),
+    (3, 3),
+    (3, 3),
+    (6, 6),
+    (6, 6),
+    (6, 6),
+    (6, 6),
+    (6, 6),
+    (3, 3),
+    (5, 5),
+    (6, 6),
+    (5, 5),
+    (6, 6),
+    (6, 6),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dx7Operator {
+    /// Envelope rates 1-4, 0-99
+    pub rates: [u8; 4],
+    /// Envelope levels 1-4, 0-99
+    pub levels: [u8; 4],
+    /// Key scaling break point 0-99, 39 is C3 (MIDI key 60)
+    pub breakpoint: u8,
+    pub left_depth: u8,
+    pub right_depth: u8,
+    /// 0-3: -LIN, -EXP, +EXP, +LIN
+    pub left_curve: u8,
+    pub right_curve: u8,
+    pub rate_scaling: u8,
+    pub key_velocity_sensitivity: u8,
+    pub output_level: u8,
+    pub fixed_frequency: bool,
+    pub coarse: u8,
+    pub fine: u8,
+    /// 0-14, 7 is center
+    pub detune: u8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dx7Voice {
+    pub name: String,
+    /// Operators 1-6
+    pub operators: [Dx7Operator; 6],
+    pub pitch_envelope_levels: [u8; 4],
+    /// Algorithm 0-31
+    pub algorithm: u8,
+    pub feedback: u8,
+    pub lfo_pitch_modulation_depth: u8,
+    pub lfo_amplitude_modulation_depth: u8,
+    /// 0-48, 24 is center
+    pub transpose: u8,
+}
+
+impl Dx7Voice {
+    fn parse_vmem(data: &[u8]) -> Self {
+        let mut operators = [Dx7Operator::default(); 6];
+
+        // Operator 6 is stored first
+        for (i, operator) in operators.iter_mut().rev().enumerate() {
+            let op = &data[i * 17..];
+
+            *operator = Dx7Operator {
+                rates: parse_rates_or_levels(&op[0..4]),
+                levels: parse_rates_or_levels(&op[4..8]),
+                breakpoint: op[8].min(99),
+                left_depth: op[9].min(99),
+                right_depth: op[10].min(99),
+                left_curve: op[11] & 0x03,
+                right_curve: (op[11] >> 2) & 0x03,
+                rate_scaling: op[12] & 0x07,
+                key_velocity_sensitivity: (op[13] >> 2) & 0x07,
+                output_level: op[14].min(99),
+                fixed_frequency: op[15] & 0x01 != 0,
+                coarse: (op[15] >> 1) & 0x1F,
+                fine: op[16].min(99),
+                detune: ((op[12] >> 3) & 0x0F).min(14),
+            };
+        }
+
+        Self {
+            name: parse_name(&data[118..128]),
+            operators,
+            pitch_envelope_levels: parse_rates_or_levels(&data[106..110]),
+            algorithm: data[110] & 0x1F,
+            feedback: data[111] & 0x07,
+            lfo_pitch_modulation_depth: data[114].min(99),
+            lfo_amplitude_modulation_depth: data[115].min(99),
+            transpose: data[117].min(48),
+        }
+    }
+
+    fn parse_vced(data: &[u8]) -> Self {
+        let mut operators = [Dx7Operator::default(); 6];
+
+        // Operator 6 is stored first
+        for (i, operator) in operators.iter_mut().rev().enumerate() {
+            let op = &data[i * 21..];
+
+            *operator = Dx7Operator {
+                rates: parse_rates_or_levels(&op[0..4]),
+                levels: parse_rates_or_levels(&op[4..8]),
+                breakpoint: op[8].min(99),
+                left_depth: op[9].min(99),
+                right_depth: op[10].min(99),
+                left_curve: op[11] & 0x03,
+                right_curve: op[12] & 0x03,
+                rate_scaling: op[13] & 0x07,
+                key_velocity_sensitivity: op[15] & 0x07,
+                output_level: op[16].min(99),
+                fixed_frequency: op[17] & 0x01 != 0,
+                coarse: op[18] & 0x1F,
+                fine: op[19].min(99),
+                detune: op[20].min(14),
+            };
+        }
+
+        Self {
+            name: parse_name(&data[145..155]),
+            operators,
+            pitch_envelope_levels: parse_rates_or_levels(&data[130..134]),
+            algorithm: data[134] & 0x1F,
+            feedback: data[135] & 0x07,
+            lfo_pitch_modulation_depth: data[139].min(99),
+            lfo_amplitude_modulation_depth: data[140].min(99),
+            transpose: data[144].min(48),
+        }
+    }
+
+    fn routing(&self) -> &'static [&'static [usize]; 6] {
+        &ALGORITHMS[usize::from(self.algorithm)]
+    }
+
+    /// Operators (Yamaha numbering) to keep, in ascending order
+    fn select_operators(&self) -> Vec<usize> {
+        let routing = self.routing();
+
+        let mut candidates = (1..=6)
+            .filter(|n| self.operators[n - 1].output_level > 0)
+            .collect::<Vec<_>>();
+
+        // Carriers first, then louder operators, then lower operator numbers
+        candidates.sort_by_key(|n| {
+            (
+                !routing[n - 1].is_empty(),
+                Reverse(self.operators[n - 1].output_level),
+                *n,
+            )
+        });
+
+        let mut kept = Vec::with_capacity(NUM_OCTASINE_OPERATORS);
+
+        for n in candidates {
+            if kept.len() == NUM_OCTASINE_OPERATORS {
+                break;
+            }
+            // Skip modulators that don't (indirectly) modulate kept carriers
+            if routing[n - 1].is_empty() || !effective_targets(routing, n, &kept).is_empty() {
+                kept.push(n);
+            }
+        }
+
+        kept.sort_unstable();
+
+        kept
+    }
+
+    pub fn convert(&self) -> ConvertedVoice {
+        let mut voice = ConvertedVoice::new(self.name.clone());
+
+        voice.set(
+            Parameter::Master(MasterParameter::ModOutScaling),
+            ModOutScalingValue::new_from_audio(ModOutScaling::Dx7),
+        );
+
+        if self.transpose != 24 {
+            let semitones = f64::from(self.transpose) - 24.0;
+
+            voice.set(
+                Parameter::Master(MasterParameter::Frequency),
+                MasterFrequencyValue::new_from_audio(440.0 * (semitones / 12.0).exp2()),
+            );
+        }
+
+        if self.lfo_pitch_modulation_depth > 0 || self.lfo_amplitude_modulation_depth > 0 {
+            voice.warn("LFO modulation is not converted".into());
+        }
+        if self.pitch_envelope_levels.iter().any(|level| *level != 50) {
+            voice.warn("pitch envelope is not converted".into());
+        }
+
+        let routing = self.routing();
+        let kept = self.select_operators();
+
+        for (n, operator) in (1..=6).zip(self.operators.iter()) {
+            if operator.output_level > 0 && !kept.contains(&n) {
+                voice.warn(format_compact!(
+                    "operator {}: dropped, only four operators are supported",
+                    n
+                ));
+            }
+        }
+
+        let mut max_carrier_velocity_sensitivity = 0;
+
+        for index in 0..NUM_OCTASINE_OPERATORS {
+            let n = match kept.get(index) {
+                Some(n) => *n,
+                None => {
+                    set_unused_operator(&mut voice, index as u8);
+
+                    continue;
+                }
+            };
+
+            let operator = &self.operators[n - 1];
+            let carrier = routing[n - 1].is_empty();
+            let index = index as u8;
+
+            let mix_out = if carrier {
+                max_carrier_velocity_sensitivity =
+                    max_carrier_velocity_sensitivity.max(operator.key_velocity_sensitivity);
+
+                output_level_to_amplitude(operator.output_level)
+            } else {
+                0.0
+            };
+
+            voice.set(
+                Parameter::Operator(index, OperatorParameter::MixOut),
+                OperatorMixOutValue::new_from_audio(mix_out),
+            );
+
+            if index > 0 {
+                let mut target_flags = [false; 3];
+
+                for target in effective_targets(routing, n, &kept) {
+                    if let Some(target_index) = kept.iter().position(|k| *k == target) {
+                        target_flags[target_index] = true;
+                    }
+                }
+
+                voice.set_mod_targets(index, &target_flags[..usize::from(index)]);
+
+                // Mod out scaling is set to DX7, so patch values map linearly
+                // to output levels
+                let mod_out = if carrier {
+                    0.0
+                } else {
+                    f32::from(operator.output_level) / 99.0
+                };
+
+                voice.set_patch_value(
+                    Parameter::Operator(index, OperatorParameter::ModOut),
+                    mod_out,
+                );
+                voice.set(
+                    Parameter::Operator(index, OperatorParameter::VelocitySensitivityModOut),
+                    VelocitySensitivityValue::new_from_audio(
+                        f32::from(operator.key_velocity_sensitivity) / 7.0,
+                    ),
+                );
+            }
+
+            voice.set(
+                Parameter::Operator(index, OperatorParameter::VelocitySensitivityFeedback),
+                VelocitySensitivityValue::new_from_audio(0.0),
+            );
+            voice.set(
+                Parameter::Operator(index, OperatorParameter::WaveType),
+                OperatorWaveTypeValue::new_from_audio(WaveType::Sine),
+            );
+
+            convert_feedback(&mut voice, index, n, self);
+            convert_frequency(&mut voice, index, n, operator);
+            convert_envelope(&mut voice, index, n, operator);
+            convert_key_scaling(&mut voice, index, operator);
+        }
+
+        voice.set(
+            Parameter::Master(MasterParameter::VelocitySensitivityVolume),
+            VelocitySensitivityValue::new_from_audio(
+                f32::from(max_carrier_velocity_sensitivity) / 7.0,
+            ),
+        );
+
+        voice
+    }
+}
+
+/// Kept operators modulated by operator, looking through dropped operators
+fn effective_targets(routing: &[&[usize]; 6], operator: usize, kept: &[usize]) -> Vec<usize> {
+    let mut targets = Vec::new();
+
+    for target in routing[operator - 1].iter().copied() {
+        if kept.contains(&target) {
+            targets.push(target);
+        } else {
+            targets.extend(effective_targets(routing, target, kept));
+        }
+    }
+
+    targets
+}
+
+fn set_unused_operator(voice: &mut ConvertedVoice, index: u8) {
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::Active),
+        OperatorActiveValue::new_from_audio(0.0),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::MixOut),
+        OperatorMixOutValue::new_from_audio(0.0),
+    );
+
+    if index > 0 {
+        voice.set_patch_value(Parameter::Operator(index, OperatorParameter::ModOut), 0.0);
+    }
+}
+
+fn convert_feedback(voice: &mut ConvertedVoice, index: u8, operator_number: usize, dx7: &Dx7Voice) {
+    let (source, destination) = FEEDBACK_LOOPS[usize::from(dx7.algorithm)];
+
+    if dx7.feedback == 0 || operator_number != destination {
+        return;
+    }
+
+    if source != destination {
+        voice.warn(format_compact!(
+            "feedback from operator {} to operator {} approximated with self-feedback",
+            source,
+            destination
+        ));
+    }
+
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::Feedback),
+        OperatorFeedbackValue::new_from_audio(feedback(dx7.feedback)),
+    );
+}
+
+fn convert_frequency(
+    voice: &mut ConvertedVoice,
+    index: u8,
+    operator_number: usize,
+    operator: &Dx7Operator,
+) {
+    if operator.fixed_frequency {
+        voice.warn(format_compact!(
+            "operator {}: fixed frequency mode is not supported",
+            operator_number
+        ));
+    }
+
+    let coarse = if operator.coarse == 0 {
+        0.5
+    } else {
+        f64::from(operator.coarse)
+    };
+    let target = coarse * (1.0 + f64::from(operator.fine) / 100.0);
+
+    // Use closest OctaSine ratio and make up the difference with free
+    // frequency parameter
+    let ratio = OperatorFrequencyRatioValue::new_from_text(&format_compact!("{}", coarse))
+        .unwrap_or_default();
+
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::FrequencyRatio),
+        ratio,
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::FrequencyFree),
+        OperatorFrequencyFreeValue::new_from_audio(target / ratio.get().value),
+    );
+
+    if operator.detune != 7 {
+        let cents = (f64::from(operator.detune) - 7.0) * DETUNE_CENTS_PER_STEP;
+
+        voice.set(
+            Parameter::Operator(index, OperatorParameter::FrequencyFine),
+            OperatorFrequencyFineValue::new_from_audio((cents / 1200.0).exp2()),
+        );
+    }
+}
+
+/// Approximate rate/level envelope with attack to full level, decay to
+/// level 3 and release
+fn convert_envelope(
+    voice: &mut ConvertedVoice,
+    index: u8,
+    operator_number: usize,
+    operator: &Dx7Operator,
+) {
+    let [rate_1, rate_2, _, rate_4] = operator.rates.map(effective_rate);
+    let [level_1, level_2, level_3, level_4] = operator.levels;
+
+    if level_1 != 99 || level_2 != level_3 || level_4 != 0 {
+        voice.warn(format_compact!(
+            "operator {}: envelope approximated as ADSR",
+            operator_number
+        ));
+    }
+    if operator.rate_scaling > 0 {
+        voice.warn(format_compact!(
+            "operator {}: rate scaling is not supported",
+            operator_number
+        ));
+    }
+
+    let (decay, sustain) = if level_3 >= 99 {
+        (ENVELOPE_MIN_DURATION, 1.0)
+    } else {
+        (decay_duration(rate_2), output_level_to_amplitude(level_3))
+    };
+
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::AttackDuration),
+        OperatorAttackDurationValue::new_from_audio(attack_duration(rate_1)),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::DecayDuration),
+        OperatorDecayDurationValue::new_from_audio(decay),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::SustainVolume),
+        OperatorSustainVolumeValue::new_from_audio(sustain),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::ReleaseDuration),
+        OperatorReleaseDurationValue::new_from_audio(decay_duration(rate_4)),
+    );
+}
+
+fn convert_key_scaling(voice: &mut ConvertedVoice, index: u8, operator: &Dx7Operator) {
+    if operator.left_depth == 0 && operator.right_depth == 0 {
+        return;
+    }
+
+    let key = operator.breakpoint + BREAKPOINT_KEY_OFFSET;
+
+    let depth = |depth: u8| {
+        OperatorKeyScalingDepthValue::new_from_audio(
+            f32::from(depth) / 99.0 * MAX_KEY_SCALING_DEPTH,
+        )
+    };
+    let curve = |curve: u8| {
+        OperatorKeyScalingCurveValue::new_from_audio(KEY_SCALING_CURVE_STEPS[usize::from(curve)])
+    };
+
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::KeyScalingBreakpoint),
+        OperatorKeyScalingBreakpointValue::new_from_audio(key),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::KeyScalingLeftDepth),
+        depth(operator.left_depth),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::KeyScalingRightDepth),
+        depth(operator.right_depth),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::KeyScalingLeftCurve),
+        curve(operator.left_curve),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::KeyScalingRightCurve),
+        curve(operator.right_curve),
+    );
+}
+
+/// Envelope rate 0-99 to effective rate 0-63
+fn effective_rate(rate: u8) -> u8 {
+    (u16::from(rate) * 41 / 64) as u8
+}
+
+fn parse_rates_or_levels(bytes: &[u8]) -> [u8; 4] {
+    [bytes[0], bytes[1], bytes[2], bytes[3]].map(|v| v.min(99))
+}
+
+/// Parse all DX7 voices in SysEx data
+pub fn parse_voices(bytes: &[u8]) -> anyhow::Result<Vec<Dx7Voice>> {
+    let mut voices = Vec::new();
+
+    for message in sysex_messages(bytes) {
+        // F0 43 0n ff bb bb <data> cc F7
+        if message.len() < 8 || message[1] != YAMAHA_ID || message[2] & 0xF0 != 0 {
+            continue;
+        }
+
+        let data = &message[6..message.len() - 2];
+
+        match message[3] {
+            FORMAT_VMEM if data.len() == VMEM_VOICE_LEN * VMEM_NUM_VOICES => {
+                voices.extend(data.chunks_exact(VMEM_VOICE_LEN).map(Dx7Voice::parse_vmem));
+            }
+            FORMAT_VCED if data.len() == VCED_LEN => {
+                voices.push(Dx7Voice::parse_vced(data));
+            }
+            _ => (),
+        }
+    }
+
+    if voices.is_empty() {
+        Err(anyhow::anyhow!("no DX7 voice data found"))
+    } else {
+        Ok(voices)
+    }
+}
+
+/// Parse and convert all DX7 voices in SysEx data
+pub fn convert_voices(bytes: &[u8]) -> anyhow::Result<Vec<ConvertedVoice>> {
+    Ok(parse_voices(bytes)?.iter().map(Dx7Voice::convert).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vmem_voice_bytes() -> [u8; VMEM_VOICE_LEN] {
+        let mut data = [0u8; VMEM_VOICE_LEN];
+
+        for i in 0..6 {
+            let op = &mut data[i * 17..];
+
+            op[0..4].copy_from_slice(&[99, 50, 50, 60]); // R1-R4
+            op[4..8].copy_from_slice(&[99, 80, 80, 0]); // L1-L4
+            op[8] = 39; // BP (C3)
+            op[12] = 7 << 3; // DET
+            op[14] = 80 + i as u8; // OL, operator 1 loudest
+            op[15] = 1 << 1; // FC (ratio 1)
+        }
+
+        data[106..110].copy_from_slice(&[50; 4]); // PL1-PL4
+        data[110] = 0; // ALG 1
+        data[111] = 7; // FB
+        data[117] = 24;
+        data[118..128].copy_from_slice(b"DX7 VOICE ");
+
+        data
+    }
+
+    fn vmem_bank_bytes(voice: [u8; VMEM_VOICE_LEN]) -> Vec<u8> {
+        let mut bytes = vec![0xF0, YAMAHA_ID, 0x00, FORMAT_VMEM, 0x20, 0x00];
+
+        for _ in 0..VMEM_NUM_VOICES {
+            bytes.extend_from_slice(&voice);
+        }
+
+        let checksum = bytes[6..]
+            .iter()
+            .fold(0u8, |acc, b| acc.wrapping_add(*b))
+            .wrapping_neg()
+            & 0x7F;
+
+        bytes.push(checksum);
+        bytes.push(0xF7);
+
+        bytes
+    }
+
+    #[test]
+    fn test_parse_vmem() {
+        let voices = parse_voices(&vmem_bank_bytes(vmem_voice_bytes())).unwrap();
+
+        assert_eq!(voices.len(), VMEM_NUM_VOICES);
+
+        let voice = &voices[0];
+
+        assert_eq!(voice.name, "DX7 VOICE");
+        assert_eq!(voice.operators[0].output_level, 85);
+        assert_eq!(voice.operators[5].output_level, 80);
+        assert_eq!(voice.operators[0].coarse, 1);
+        assert_eq!(voice.operators[0].detune, 7);
+
+        // Algorithm 1: 2 -> 1 and 6 -> 5 -> 4 -> 3. Both carriers are kept,
+        // as well as the loudest modulators.
+        assert_eq!(voice.select_operators(), vec![1, 2, 3, 4]);
+
+        let converted = voice.convert();
+
+        assert_eq!(
+            converted
+                .warnings
+                .iter()
+                .filter(|w| w.contains("dropped"))
+                .count(),
+            2
+        );
+
+        // Feedback is on dropped operator 6, so it is not converted
+        assert!(!converted
+            .parameter_values
+            .iter()
+            .any(|(p, _)| matches!(p, Parameter::Operator(_, OperatorParameter::Feedback))));
+    }
+
+    #[test]
+    fn test_modulation_rerouted_through_dropped_operators() {
+        let mut voice = Dx7Voice {
+            algorithm: 0,
+            ..Default::default()
+        };
+
+        // Operator 5 is silent, so operator 6 should modulate operator 4
+        for (operator, output_level) in voice.operators.iter_mut().zip([99, 0, 99, 90, 0, 95]) {
+            operator.output_level = output_level;
+        }
+
+        let kept = voice.select_operators();
+
+        assert_eq!(kept, vec![1, 3, 4, 6]);
+        assert_eq!(effective_targets(voice.routing(), 6, &kept), vec![4]);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse_voices(&[]).is_err());
+        assert!(parse_voices(&[0xF0, YAMAHA_ID, 0x00, FORMAT_VMEM, 0x20, 0x00, 0xF7]).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Conversion of Yamaha FM synthesizer voice SysEx dumps into patches
+
+pub mod dx7;
+pub mod tx81z;
+
+use compact_str::CompactString;
+
+use crate::parameters::operator_envelope::{ENVELOPE_MAX_DURATION, ENVELOPE_MIN_DURATION};
+use crate::parameters::{
+    ModTargetStorage, Operator2ModulationTargetValue, Operator3ModulationTargetValue,
+    Operator4ModulationTargetValue, OperatorParameter, Parameter, ParameterValue,
+};
+
+/// Approximate duration of a full attack at effective rate 4
+const ATTACK_BASE_DURATION: f64 = 7.0;
+/// Approximate duration of a full decay at effective rate 4
+const DECAY_BASE_DURATION: f64 = 80.0;
+/// Approximate detune per detune step in cents
+const DETUNE_CENTS_PER_STEP: f64 = 1.0;
+
+/// Voice converted from SysEx data, ready to be applied to a patch
+#[derive(Debug, Clone)]
+pub struct ConvertedVoice {
+    pub name: String,
+    /// Patch values of parameters that differ from patch defaults
+    pub parameter_values: Vec<(Parameter, f32)>,
+    /// Voice features that could not be converted or were approximated
+    pub warnings: Vec<CompactString>,
+}
+
+impl ConvertedVoice {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            parameter_values: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    fn set<V: ParameterValue>(&mut self, parameter: Parameter, value: V) {
+        self.parameter_values.push((parameter, value.to_patch()));
+    }
+
+    fn set_patch_value(&mut self, parameter: Parameter, value: f32) {
+        self.parameter_values.push((parameter, value));
+    }
+
+    /// Set modulation targets of operator with index 1-3. Targets are
+    /// given as flags for lower operators.
+    fn set_mod_targets(&mut self, index: u8, targets: &[bool]) {
+        let storage = ModTargetStorage::new(targets);
+        let parameter = Parameter::Operator(index, OperatorParameter::ModTargets);
+
+        match index {
+            1 => self.set(
+                parameter,
+                Operator2ModulationTargetValue::new_from_audio(storage),
+            ),
+            2 => self.set(
+                parameter,
+                Operator3ModulationTargetValue::new_from_audio(storage),
+            ),
+            _ => self.set(
+                parameter,
+                Operator4ModulationTargetValue::new_from_audio(storage),
+            ),
+        }
+    }
+
+    fn warn(&mut self, warning: CompactString) {
+        self.warnings.push(warning);
+    }
+}
+
+/// Convert voices in SysEx data of any supported synth family
+pub fn convert_voices(bytes: &[u8]) -> anyhow::Result<Vec<ConvertedVoice>> {
+    tx81z::convert_voices(bytes)
+        .or_else(|_| dx7::convert_voices(bytes))
+        .map_err(|_| anyhow::anyhow!("no DX7 or TX81Z / DX21 / DX11 voice data found"))
+}
+
+/// Iterate over SysEx messages (F0 ... F7) in bytes, skipping anything else
+fn sysex_messages(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut remaining = bytes;
+
+    ::std::iter::from_fn(move || {
+        let start = remaining.iter().position(|b| *b == 0xF0)?;
+        let end = start + remaining[start..].iter().position(|b| *b == 0xF7)?;
+
+        let message = &remaining[start..=end];
+
+        remaining = &remaining[end + 1..];
+
+        Some(message)
+    })
+}
+
+/// Parse voice name from 7-bit ASCII bytes
+fn parse_name(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| match b & 0x7F {
+            b if b.is_ascii_graphic() => b as char,
+            _ => ' ',
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Duration of attack at effective rate 0-63. Duration halves every four
+/// effective rate steps.
+fn attack_duration(effective_rate: u8) -> f64 {
+    rate_to_duration(ATTACK_BASE_DURATION, effective_rate)
+}
+
+/// Duration of decay at effective rate 0-63
+fn decay_duration(effective_rate: u8) -> f64 {
+    rate_to_duration(DECAY_BASE_DURATION, effective_rate)
+}
+
+fn rate_to_duration(base_duration: f64, effective_rate: u8) -> f64 {
+    let duration = base_duration * (-(f64::from(effective_rate) - 4.0) / 4.0).exp2();
+
+    duration.clamp(ENVELOPE_MIN_DURATION, ENVELOPE_MAX_DURATION)
+}
+
+/// Output level 0-99 to amplitude, with 0.75 dB per step
+fn output_level_to_amplitude(level: u8) -> f32 {
+    if level == 0 {
+        0.0
+    } else {
+        ((f32::from(level) - 99.0) / 8.0).exp2()
+    }
+}
+
+/// Feedback level 0-7 to operator feedback value
+fn feedback(level: u8) -> f32 {
+    ::std::f32::consts::PI * (f32::from(level) - 7.0).exp2()
+}
@@ -0,0 +1,547 @@
+//! Yamaha TX81Z / DX21 / DX11 / DX27 / DX100 voice SysEx import
+//!
+//! Supports 32 voice bulk dumps (VMEM) as well as single voice dumps (VCED,
+//! optionally preceded by TX81Z additional voice data, ACED). These synths
+//! are 4-operator, so operators map one-to-one to OctaSine operators.
+
+use compact_str::format_compact;
+
+use crate::parameters::mod_out_scaling::{ModOutScaling, ModOutScalingValue};
+use crate::parameters::operator_envelope::ENVELOPE_MIN_DURATION;
+use crate::parameters::operator_wave_type::WaveType;
+use crate::parameters::velocity_sensitivity::VelocitySensitivityValue;
+use crate::parameters::*;
+
+use super::{
+    attack_duration, decay_duration, feedback, output_level_to_amplitude, parse_name,
+    sysex_messages, ConvertedVoice, DETUNE_CENTS_PER_STEP,
+};
+
+const YAMAHA_ID: u8 = 0x43;
+
+const FORMAT_VCED: u8 = 0x03;
+const FORMAT_VMEM: u8 = 0x04;
+const FORMAT_ACED: u8 = 0x7E;
+
+const VCED_LEN: usize = 93;
+const VMEM_VOICE_LEN: usize = 128;
+const VMEM_NUM_VOICES: usize = 32;
+const ACED_HEADER: &[u8] = b"LM  8976AE";
+const ACED_LEN: usize = 23;
+
+/// Storage order of operators in SysEx data, as Yamaha operator numbers
+const OPERATOR_ORDER: [usize; 4] = [4, 2, 3, 1];
+
+/// Frequency ratios for coarse values 0-63, as listed in the TX81Z manual
+#[allow(clippy::approx_constant)]
+const COARSE_RATIOS: [f64; 64] = [
+    0.50, 0.71, 0.78, 0.87, 1.00, 1.41, 1.57, 1.73, 2.00, 2.82, 3.00, 3.14, 3.46, 4.00, 4.24, 4.71,
+    5.00, 5.19, 5.65, 6.00, 6.28, 6.92, 7.00, 7.07, 7.85, 8.00, 8.48, 8.65, 9.00, 9.42, 9.89,
+    10.00, 10.38, 10.99, 11.00, 11.30, 12.00, 12.11, 12.56, 12.72, 13.00, 13.84, 14.00, 14.10,
+    14.13, 15.00, 15.55, 15.57, 15.70, 16.96, 17.27, 17.30, 18.37, 18.84, 19.03, 19.78, 20.41,
+    20.76, 21.20, 21.98, 22.49, 23.55, 24.22, 25.95,
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tx81zOperator {
+    pub attack_rate: u8,
+    pub decay_1_rate: u8,
+    pub decay_2_rate: u8,
+    pub release_rate: u8,
+    pub decay_1_level: u8,
+    pub key_velocity_sensitivity: u8,
+    pub output_level: u8,
+    pub coarse: u8,
+    /// 0-6, 3 is center
+    pub detune: u8,
+    pub fixed_frequency: bool,
+    pub fine: u8,
+    /// Waveform 0-7 (W1-W8)
+    pub wave: u8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tx81zVoice {
+    pub name: String,
+    /// Operators 1-4
+    pub operators: [Tx81zOperator; 4],
+    /// Algorithm 0-7
+    pub algorithm: u8,
+    pub feedback: u8,
+    pub lfo_pitch_modulation_depth: u8,
+    pub lfo_amplitude_modulation_depth: u8,
+    /// 0-48, 24 is center
+    pub transpose: u8,
+}
+
+impl Tx81zVoice {
+    fn parse_vmem(data: &[u8]) -> Self {
+        let mut operators = [Tx81zOperator::default(); 4];
+
+        for (i, operator_number) in OPERATOR_ORDER.into_iter().enumerate() {
+            let op = &data[i * 10..];
+            let aced = &data[73 + i * 2..];
+
+            operators[operator_number - 1] = Tx81zOperator {
+                attack_rate: op[0] & 0x1F,
+                decay_1_rate: op[1] & 0x1F,
+                decay_2_rate: op[2] & 0x1F,
+                release_rate: op[3] & 0x0F,
+                decay_1_level: op[4] & 0x0F,
+                key_velocity_sensitivity: op[6] & 0x07,
+                output_level: op[7].min(99),
+                coarse: op[8] & 0x3F,
+                detune: (op[9] & 0x07).min(6),
+                fixed_frequency: aced[0] & 0x08 != 0,
+                fine: aced[1] & 0x0F,
+                wave: (aced[1] >> 4) & 0x07,
+            };
+        }
+
+        Self {
+            name: parse_name(&data[57..67]),
+            operators,
+            algorithm: data[40] & 0x07,
+            feedback: (data[40] >> 3) & 0x07,
+            lfo_pitch_modulation_depth: data[43].min(99),
+            lfo_amplitude_modulation_depth: data[44].min(99),
+            transpose: data[46].min(48),
+        }
+    }
+
+    fn parse_vced(data: &[u8], opt_aced: Option<&[u8]>) -> Self {
+        let mut operators = [Tx81zOperator::default(); 4];
+
+        for (i, operator_number) in OPERATOR_ORDER.into_iter().enumerate() {
+            let op = &data[i * 13..];
+
+            let (fixed_frequency, fine, wave) = match opt_aced {
+                Some(aced) => {
+                    let aced = &aced[i * 5..];
+
+                    (aced[0] != 0, aced[2] & 0x0F, aced[3] & 0x07)
+                }
+                None => (false, 0, 0),
+            };
+
+            operators[operator_number - 1] = Tx81zOperator {
+                attack_rate: op[0] & 0x1F,
+                decay_1_rate: op[1] & 0x1F,
+                decay_2_rate: op[2] & 0x1F,
+                release_rate: op[3] & 0x0F,
+                decay_1_level: op[4] & 0x0F,
+                key_velocity_sensitivity: op[9] & 0x07,
+                output_level: op[10].min(99),
+                coarse: op[11] & 0x3F,
+                detune: op[12].min(6),
+                fixed_frequency,
+                fine,
+                wave,
+            };
+        }
+
+        Self {
+            name: parse_name(&data[77..87]),
+            operators,
+            algorithm: data[52] & 0x07,
+            feedback: data[53] & 0x07,
+            lfo_pitch_modulation_depth: data[56].min(99),
+            lfo_amplitude_modulation_depth: data[57].min(99),
+            transpose: data[62].min(48),
+        }
+    }
+
+    /// Operators modulated by each operator (Yamaha numbering) and whether
+    /// it is a carrier
+    fn routing(&self) -> [(&'static [usize], bool); 4] {
+        match self.algorithm {
+            0 => [(&[], true), (&[1], false), (&[2], false), (&[3], false)],
+            1 => [(&[], true), (&[1], false), (&[2], false), (&[2], false)],
+            2 => [(&[], true), (&[1], false), (&[2], false), (&[1], false)],
+            3 => [(&[], true), (&[1], false), (&[1], false), (&[3], false)],
+            4 => [(&[], true), (&[1], false), (&[], true), (&[3], false)],
+            5 => [(&[], true), (&[], true), (&[], true), (&[1, 2, 3], false)],
+            6 => [(&[], true), (&[], true), (&[], true), (&[3], false)],
+            _ => [(&[], true), (&[], true), (&[], true), (&[], true)],
+        }
+    }
+
+    pub fn convert(&self) -> ConvertedVoice {
+        let mut voice = ConvertedVoice::new(self.name.clone());
+
+        voice.set(
+            Parameter::Master(MasterParameter::ModOutScaling),
+            ModOutScalingValue::new_from_audio(ModOutScaling::Dx7),
+        );
+
+        if self.transpose != 24 {
+            let semitones = f64::from(self.transpose) - 24.0;
+
+            voice.set(
+                Parameter::Master(MasterParameter::Frequency),
+                MasterFrequencyValue::new_from_audio(440.0 * (semitones / 12.0).exp2()),
+            );
+        }
+
+        if self.lfo_pitch_modulation_depth > 0 || self.lfo_amplitude_modulation_depth > 0 {
+            voice.warn("LFO modulation is not converted".into());
+        }
+
+        let routing = self.routing();
+
+        let mut max_carrier_velocity_sensitivity = 0;
+
+        for (index, (operator, (targets, carrier))) in
+            self.operators.iter().zip(routing).enumerate()
+        {
+            let operator_number = index + 1;
+            let index = index as u8;
+
+            // Mix out defaults to 1.0 for operator 1 and 0.0 for the others,
+            // so always set it
+            let mix_out = if carrier {
+                max_carrier_velocity_sensitivity =
+                    max_carrier_velocity_sensitivity.max(operator.key_velocity_sensitivity);
+
+                output_level_to_amplitude(operator.output_level)
+            } else {
+                0.0
+            };
+
+            voice.set(
+                Parameter::Operator(index, OperatorParameter::MixOut),
+                OperatorMixOutValue::new_from_audio(mix_out),
+            );
+
+            if index > 0 {
+                let mut target_slice = [false; 3];
+
+                for target in targets.iter() {
+                    target_slice[target - 1] = true;
+                }
+
+                voice.set_mod_targets(index, &target_slice[..usize::from(index)]);
+
+                // Mod out scaling is set to DX7, so patch values map linearly
+                // to output levels
+                let mod_out = if carrier {
+                    0.0
+                } else {
+                    f32::from(operator.output_level) / 99.0
+                };
+
+                voice.set_patch_value(
+                    Parameter::Operator(index, OperatorParameter::ModOut),
+                    mod_out,
+                );
+                voice.set(
+                    Parameter::Operator(index, OperatorParameter::VelocitySensitivityModOut),
+                    VelocitySensitivityValue::new_from_audio(
+                        f32::from(operator.key_velocity_sensitivity) / 7.0,
+                    ),
+                );
+            }
+
+            voice.set(
+                Parameter::Operator(index, OperatorParameter::VelocitySensitivityFeedback),
+                VelocitySensitivityValue::new_from_audio(0.0),
+            );
+
+            // Only operator 4 has feedback
+            if operator_number == 4 && self.feedback > 0 {
+                voice.set(
+                    Parameter::Operator(index, OperatorParameter::Feedback),
+                    OperatorFeedbackValue::new_from_audio(feedback(self.feedback)),
+                );
+            }
+
+            voice.set(
+                Parameter::Operator(index, OperatorParameter::WaveType),
+                OperatorWaveTypeValue::new_from_audio(WaveType::from_tx81z(operator.wave)),
+            );
+
+            convert_frequency(&mut voice, index, operator);
+            convert_envelope(&mut voice, index, operator);
+        }
+
+        voice.set(
+            Parameter::Master(MasterParameter::VelocitySensitivityVolume),
+            VelocitySensitivityValue::new_from_audio(
+                f32::from(max_carrier_velocity_sensitivity) / 7.0,
+            ),
+        );
+
+        voice
+    }
+}
+
+fn convert_frequency(voice: &mut ConvertedVoice, index: u8, operator: &Tx81zOperator) {
+    let operator_number = index + 1;
+
+    if operator.fixed_frequency {
+        voice.warn(format_compact!(
+            "operator {}: fixed frequency mode is not supported",
+            operator_number
+        ));
+    }
+
+    let coarse = COARSE_RATIOS[usize::from(operator.coarse)];
+    let target = coarse * (1.0 + f64::from(operator.fine) / 16.0);
+
+    // Use closest OctaSine ratio and make up the difference with free
+    // frequency parameter
+    let ratio = OperatorFrequencyRatioValue::new_from_text(&format_compact!("{}", coarse))
+        .unwrap_or_default();
+
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::FrequencyRatio),
+        ratio,
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::FrequencyFree),
+        OperatorFrequencyFreeValue::new_from_audio(target / ratio.get().value),
+    );
+
+    if operator.detune != 3 {
+        let cents = (f64::from(operator.detune) - 3.0) * DETUNE_CENTS_PER_STEP;
+
+        voice.set(
+            Parameter::Operator(index, OperatorParameter::FrequencyFine),
+            OperatorFrequencyFineValue::new_from_audio((cents / 1200.0).exp2()),
+        );
+    }
+}
+
+fn convert_envelope(voice: &mut ConvertedVoice, index: u8, operator: &Tx81zOperator) {
+    let operator_number = index + 1;
+
+    let attack = attack_duration(operator.attack_rate * 2);
+
+    // Without first decay, level stays at maximum until second decay
+    let (decay, sustain) = if operator.decay_1_rate == 0 {
+        (ENVELOPE_MIN_DURATION, 1.0)
+    } else {
+        (
+            decay_duration(operator.decay_1_rate * 2),
+            decay_1_level_to_amplitude(operator.decay_1_level),
+        )
+    };
+
+    let release = decay_duration(operator.release_rate * 4 + 2);
+
+    if operator.decay_2_rate > 0 {
+        voice.warn(format_compact!(
+            "operator {}: second decay rate is not supported",
+            operator_number
+        ));
+    }
+
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::AttackDuration),
+        OperatorAttackDurationValue::new_from_audio(attack),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::DecayDuration),
+        OperatorDecayDurationValue::new_from_audio(decay),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::SustainVolume),
+        OperatorSustainVolumeValue::new_from_audio(sustain),
+    );
+    voice.set(
+        Parameter::Operator(index, OperatorParameter::ReleaseDuration),
+        OperatorReleaseDurationValue::new_from_audio(release),
+    );
+}
+
+/// Decay 1 level 0-15 to amplitude, with 3 dB per step
+fn decay_1_level_to_amplitude(level: u8) -> f32 {
+    10.0f32.powf(-3.0 * (15.0 - f32::from(level)) / 20.0)
+}
+
+/// Parse all TX81Z family voices in SysEx data
+pub fn parse_voices(bytes: &[u8]) -> anyhow::Result<Vec<Tx81zVoice>> {
+    let mut voices = Vec::new();
+    let mut opt_aced: Option<&[u8]> = None;
+
+    for message in sysex_messages(bytes) {
+        // F0 43 0n ff bb bb <data> cc F7
+        if message.len() < 8 || message[1] != YAMAHA_ID || message[2] & 0xF0 != 0 {
+            continue;
+        }
+
+        let data = &message[6..message.len() - 2];
+
+        match message[3] {
+            FORMAT_VMEM if data.len() == VMEM_VOICE_LEN * VMEM_NUM_VOICES => {
+                voices.extend(
+                    data.chunks_exact(VMEM_VOICE_LEN)
+                        .map(Tx81zVoice::parse_vmem),
+                );
+            }
+            FORMAT_VCED if data.len() == VCED_LEN => {
+                voices.push(Tx81zVoice::parse_vced(data, opt_aced.take()));
+            }
+            FORMAT_ACED
+                if data.len() == ACED_HEADER.len() + ACED_LEN && data.starts_with(ACED_HEADER) =>
+            {
+                opt_aced = Some(&data[ACED_HEADER.len()..]);
+            }
+            _ => (),
+        }
+    }
+
+    if voices.is_empty() {
+        Err(anyhow::anyhow!("no TX81Z / DX21 / DX11 voice data found"))
+    } else {
+        Ok(voices)
+    }
+}
+
+/// Parse and convert all TX81Z family voices in SysEx data
+pub fn convert_voices(bytes: &[u8]) -> anyhow::Result<Vec<ConvertedVoice>> {
+    Ok(parse_voices(bytes)?
+        .iter()
+        .map(Tx81zVoice::convert)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::wave::SineVariant;
+
+    use super::*;
+
+    fn vmem_voice_bytes() -> [u8; VMEM_VOICE_LEN] {
+        let mut data = [0u8; VMEM_VOICE_LEN];
+
+        for i in 0..4 {
+            let op = &mut data[i * 10..];
+
+            op[0] = 31; // AR
+            op[1] = 10; // D1R
+            op[3] = 7; // RR
+            op[4] = 15; // D1L
+            op[7] = 90; // OUT
+            op[8] = 4; // CRS (ratio 1.0)
+            op[9] = 3; // DET
+        }
+
+        // Operator 1 (stored last): waveform W2, fine 8
+        data[80] = (1 << 4) | 8;
+
+        data[40] = (3 << 3) | 4; // FBL 3, ALG 5
+        data[46] = 24;
+        data[57..67].copy_from_slice(b"TEST VOICE");
+
+        data
+    }
+
+    fn vmem_bank_bytes() -> Vec<u8> {
+        let mut bytes = vec![0xF0, YAMAHA_ID, 0x00, FORMAT_VMEM, 0x20, 0x00];
+
+        for _ in 0..VMEM_NUM_VOICES {
+            bytes.extend_from_slice(&vmem_voice_bytes());
+        }
+
+        let checksum = bytes[6..]
+            .iter()
+            .fold(0u8, |acc, b| acc.wrapping_add(*b))
+            .wrapping_neg()
+            & 0x7F;
+
+        bytes.push(checksum);
+        bytes.push(0xF7);
+
+        bytes
+    }
+
+    fn get_value(voice: &ConvertedVoice, parameter: Parameter) -> Option<f32> {
+        voice
+            .parameter_values
+            .iter()
+            .rev()
+            .find(|(p, _)| *p == parameter)
+            .map(|(_, v)| *v)
+    }
+
+    #[test]
+    fn test_parse_vmem() {
+        let voices = parse_voices(&vmem_bank_bytes()).unwrap();
+
+        assert_eq!(voices.len(), VMEM_NUM_VOICES);
+
+        let voice = &voices[0];
+
+        assert_eq!(voice.name, "TEST VOICE");
+        assert_eq!(voice.algorithm, 4);
+        assert_eq!(voice.feedback, 3);
+        assert_eq!(voice.operators[0].wave, 1);
+        assert_eq!(voice.operators[0].fine, 8);
+        assert_eq!(voice.operators[3].wave, 0);
+
+        let converted = voice.convert();
+
+        // Algorithm 5: 2 -> 1 and 4 -> 3, with 1 and 3 as carriers
+        let op_2_targets = Operator2ModulationTargetValue::new_from_patch(
+            get_value(
+                &converted,
+                Parameter::Operator(1, OperatorParameter::ModTargets),
+            )
+            .unwrap(),
+        )
+        .get();
+        let op_4_targets = Operator4ModulationTargetValue::new_from_patch(
+            get_value(
+                &converted,
+                Parameter::Operator(3, OperatorParameter::ModTargets),
+            )
+            .unwrap(),
+        )
+        .get();
+
+        assert_eq!(op_2_targets, ModTargetStorage::new(&[true]));
+        assert_eq!(op_4_targets, ModTargetStorage::new(&[false, false, true]));
+
+        for (index, carrier) in [(0, true), (1, false), (2, true), (3, false)] {
+            let mix_out = get_value(
+                &converted,
+                Parameter::Operator(index, OperatorParameter::MixOut),
+            )
+            .unwrap();
+
+            assert_eq!(mix_out > 0.0, carrier);
+        }
+
+        // Operator 1 frequency is 1.0 * (1 + 8 / 16)
+        let free = OperatorFrequencyFreeValue::new_from_patch(
+            get_value(
+                &converted,
+                Parameter::Operator(0, OperatorParameter::FrequencyFree),
+            )
+            .unwrap(),
+        )
+        .get();
+
+        assert!((free - 1.5).abs() < 0.001);
+
+        let op_1_wave = OperatorWaveTypeValue::new_from_patch(
+            get_value(
+                &converted,
+                Parameter::Operator(0, OperatorParameter::WaveType),
+            )
+            .unwrap(),
+        )
+        .get();
+
+        assert_eq!(op_1_wave, WaveType::SineVariant(SineVariant::Squared));
+
+        assert!(converted.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse_voices(&[]).is_err());
+        assert!(parse_voices(&[0xF0, YAMAHA_ID, 0x00, FORMAT_VMEM, 0x20, 0x00, 0xF7]).is_err());
+    }
+}
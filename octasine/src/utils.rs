@@ -1,6 +1,12 @@
+#[cfg(not(feature = "wasm"))]
+use std::io::Write;
 use std::path::PathBuf;
 
-use crate::{audio::AudioState, parameters::Parameter, sync::SyncState};
+use crate::{
+    audio::{gen::SimdBackend, AudioState},
+    parameters::Parameter,
+    sync::{InstanceInfo, SyncState},
+};
 
 #[macro_export]
 macro_rules! crate_version {
@@ -10,24 +16,60 @@ macro_rules! crate_version {
 }
 
 pub fn update_audio_parameters<T>(audio: &mut AudioState, sync: &SyncState<T>) {
-    if let Some(indeces) = sync.patches.get_changed_parameters_from_audio() {
-        for (index, opt_new_value) in indeces.iter().enumerate() {
-            if let Some(new_value) = opt_new_value {
-                if let Some(parameter) = Parameter::from_index(index) {
-                    audio.set_parameter_from_patch(parameter, *new_value);
-                }
+    let patch_switched = sync.patches.take_patch_switched_from_audio();
+
+    if patch_switched {
+        audio.set_patch_crossfade_active(true);
+    }
+
+    sync.patches
+        .drain_changed_parameters_from_audio(|index, new_value| {
+            if let Some(parameter) = Parameter::from_index(index) {
+                audio.set_parameter_from_patch(parameter, new_value);
             }
-        }
+        });
+
+    sync.patches
+        .drain_changed_key_tuning_from_audio(|key, cents| audio.set_key_detune(key, cents));
+
+    if patch_switched {
+        audio.set_patch_crossfade_active(false);
+    }
+}
+
+/// Update parameters of multitimbral mode timbres from changes to the
+/// patches following the current one. Call once per processed buffer.
+pub fn update_timbre_parameters<T>(audio: &mut AudioState, sync: &SyncState<T>) {
+    for (timbre_index, timbre) in audio.timbres_mut().iter_mut().enumerate() {
+        sync.patches
+            .drain_changed_timbre_parameters_from_audio(timbre_index, |index, value| {
+                timbre.set_parameter_from_patch(index, value);
+            });
+
+        sync.patches
+            .drain_changed_timbre_key_tuning_from_audio(timbre_index, |key, cents| {
+                timbre.set_key_detune(key, cents);
+            });
     }
 }
 
+#[cfg(feature = "wasm")]
+pub fn init_logging(_plugin_type: &str) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(feature = "wasm"))]
 pub fn init_logging(plugin_type: &str) -> anyhow::Result<()> {
     let log_folder: PathBuf = get_file_storage_dir()?;
 
     // Ignore any creation error
     let _ = ::std::fs::create_dir(log_folder.clone());
 
-    let log_file = ::std::fs::File::create(log_folder.join("OctaSine.log"))?;
+    let log_file = RotatingLogFile::open(
+        log_folder.join("OctaSine.log"),
+        log_folder.join("OctaSine.1.log"),
+        LOG_FILE_MAX_BYTES,
+    )?;
 
     let log_config = match simplelog::ConfigBuilder::new().set_time_offset_to_local() {
         Ok(builder) => builder.build(),
@@ -48,6 +90,88 @@ pub fn init_logging(plugin_type: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Log instance id, RNG seed and SIMD backend, so that log lines can be
+/// matched with the instance info shown in the GUI
+pub fn log_instance_info(instance: &InstanceInfo, simd_backend: SimdBackend) {
+    // Log level is lowered to errors only after initialization
+    let max_level = ::log::max_level();
+
+    ::log::set_max_level(::log::LevelFilter::Info);
+    ::log::info!("new {}, SIMD backend {}", instance, simd_backend);
+    ::log::set_max_level(max_level);
+}
+
+/// Size above which log file is rotated
+#[cfg(not(feature = "wasm"))]
+const LOG_FILE_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Log file that is moved to a backup path (replacing any previous backup)
+/// once it exceeds a maximum size, so at most two files are kept
+#[cfg(not(feature = "wasm"))]
+struct RotatingLogFile {
+    path: PathBuf,
+    backup_path: PathBuf,
+    max_bytes: u64,
+    file: ::std::fs::File,
+    len: u64,
+    /// Only rotate between lines
+    at_line_start: bool,
+}
+
+#[cfg(not(feature = "wasm"))]
+impl RotatingLogFile {
+    fn open(path: PathBuf, backup_path: PathBuf, max_bytes: u64) -> ::std::io::Result<Self> {
+        let file = ::std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let len = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            backup_path,
+            max_bytes,
+            file,
+            len,
+            at_line_start: true,
+        })
+    }
+
+    fn rotate(&mut self) -> ::std::io::Result<()> {
+        self.file.flush()?;
+
+        ::std::fs::rename(&self.path, &self.backup_path)?;
+
+        self.file = ::std::fs::File::create(&self.path)?;
+        self.len = 0;
+
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+impl Write for RotatingLogFile {
+    fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+        if self.at_line_start && self.len >= self.max_bytes {
+            self.rotate()?;
+        }
+
+        let num_bytes = self.file.write(buf)?;
+
+        self.len += num_bytes as u64;
+
+        if num_bytes > 0 {
+            self.at_line_start = buf[num_bytes - 1] == b'\n';
+        }
+
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
 pub fn get_version_info() -> String {
     use git_testament::{git_testament, CommitKind};
 
@@ -78,7 +202,11 @@ pub fn get_version_info() -> String {
 }
 
 cfg_if::cfg_if! {
-    if #[cfg(target_os = "windows")] {
+    if #[cfg(feature = "wasm")] {
+        pub fn get_file_storage_dir() -> anyhow::Result<PathBuf> {
+            Err(anyhow::anyhow!("File storage not available in WebAssembly build"))
+        }
+    } else if #[cfg(target_os = "windows")] {
         pub fn get_file_storage_dir() -> anyhow::Result<PathBuf> {
             ::directories::UserDirs::new()
                 .and_then(|d| d.document_dir().map(|d| d.join("OctaSine")))
@@ -92,3 +220,39 @@ cfg_if::cfg_if! {
         }
     }
 }
+
+#[cfg(all(test, not(feature = "wasm")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_log_file() {
+        let dir = ::std::env::temp_dir().join(format!("octasine-log-test-{}", fastrand::u64(..)));
+
+        ::std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("test.log");
+        let backup_path = dir.join("test.1.log");
+
+        let mut file = RotatingLogFile::open(path.clone(), backup_path.clone(), 100).unwrap();
+
+        for i in 0..30 {
+            write!(file, "line {:02}", i).unwrap();
+            writeln!(file, " of log").unwrap();
+        }
+
+        file.flush().unwrap();
+
+        let current = ::std::fs::read_to_string(&path).unwrap();
+        let backup = ::std::fs::read_to_string(&backup_path).unwrap();
+
+        ::std::fs::remove_dir_all(&dir).unwrap();
+
+        // Each line is 15 bytes, so rotation happens after every seventh
+        // line, never in the middle of one
+        assert_eq!(current, "line 28 of log\nline 29 of log\n");
+        assert!(backup.starts_with("line 21 of log\n"));
+        assert!(backup.ends_with("line 27 of log\n"));
+        assert_eq!(backup.lines().count(), 7);
+    }
+}